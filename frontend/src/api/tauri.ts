@@ -463,6 +463,39 @@ export async function getGlobalShortcut(): Promise<string | null> {
   return safeInvoke<string | null>('get_global_shortcut');
 }
 
+/**
+ * 快捷键动作
+ */
+export type ShortcutAction = 'toggle_main_window' | 'quick_add' | 'capture_clipboard';
+
+/**
+ * 设置某个动作的全局快捷键（传 null 解除绑定）
+ */
+export async function setShortcut(action: ShortcutAction, shortcut: string | null): Promise<void> {
+  return safeInvoke<void>('set_shortcut', { action, shortcut });
+}
+
+/**
+ * 获取所有动作的全局快捷键
+ */
+export async function getShortcuts(): Promise<Partial<Record<ShortcutAction, string>>> {
+  return safeInvoke<Partial<Record<ShortcutAction, string>>>('get_shortcuts');
+}
+
+/**
+ * 打开/隐藏快速添加窗口
+ */
+export async function toggleQuickAddWindow(): Promise<void> {
+  return safeInvoke<void>('toggle_quick_add_window');
+}
+
+/**
+ * 将剪贴板文本创建为任务
+ */
+export async function captureClipboard(): Promise<void> {
+  return safeInvoke<void>('capture_clipboard');
+}
+
 /**
  * 切换窗口显示/隐藏
  */
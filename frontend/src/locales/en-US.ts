@@ -494,6 +494,18 @@ export default {
     zipArchive: 'ZIP Archive',
   },
 
+  // Workspaces
+  workspace: {
+    defaultName: 'Default',
+  },
+
+  // Quick add
+  quickAdd: {
+    title: 'Quick Add',
+    placeholder: 'Type a task title, Enter to save, Esc to close',
+    added: 'Task added',
+  },
+
   // Tech stack
   techStack: {
     tauri: 'Tauri 2.0',
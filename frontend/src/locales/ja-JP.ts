@@ -406,6 +406,18 @@ export default {
     zipArchive: 'ZIP アーカイブ',
   },
 
+  // ワークスペース
+  workspace: {
+    defaultName: 'デフォルト',
+  },
+
+  // クイック追加
+  quickAdd: {
+    title: 'クイック追加',
+    placeholder: 'タスク名を入力し、Enter で保存、Esc で閉じる',
+    added: 'タスクを追加しました',
+  },
+
   // 技術スタック
   techStack: {
     tauri: 'Tauri 2.0',
@@ -510,6 +510,19 @@ export default {
     zipArchive: 'ZIP Archive',
   },
 
+  // 工作区
+  workspace: {
+    // 默认工作区的名称为空，显示时使用这里的名称
+    defaultName: '默认',
+  },
+
+  // 快速添加
+  quickAdd: {
+    title: '快速添加',
+    placeholder: '输入任务标题，回车保存，Esc 关闭',
+    added: '任务已添加',
+  },
+
   // 技术栈
   techStack: {
     tauri: 'Tauri 2.0',
@@ -417,6 +417,18 @@ export default {
     zipArchive: 'ZIP Archive',
   },
 
+  // 工作區
+  workspace: {
+    defaultName: '預設',
+  },
+
+  // 快速新增
+  quickAdd: {
+    title: '快速新增',
+    placeholder: '輸入任務標題，Enter 儲存，Esc 關閉',
+    added: '任務已新增',
+  },
+
   // 技術棧
   techStack: {
     tauri: 'Tauri 2.0',
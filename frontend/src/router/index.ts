@@ -22,6 +22,13 @@ const routes: RouteRecordRaw[] = [
     name: 'about',
     component: () => import('@/views/AboutView.vue'),
   },
+  {
+    // 快速添加窗口，不使用主布局
+    path: '/quick-add',
+    name: 'quick-add',
+    component: () => import('@/views/QuickAddView.vue'),
+    meta: { bare: true },
+  },
 ];
 
 const router = createRouter({
@@ -0,0 +1,200 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 命令行参数定义
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::dates::Period;
+use rtodo_core::models::constants::{priority, status};
+
+/// Manage RTodo tasks from the terminal, sharing the desktop app's data
+#[derive(Debug, Parser)]
+#[command(name = "rtodo", version)]
+pub struct Cli {
+    /// Data directory [default: the desktop app's data directory]
+    #[arg(long, global = true, env = "RTODO_DATA_DIR", value_name = "DIR")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Workspace name or ID [default: the desktop app's current workspace]
+    #[arg(long, global = true, env = "RTODO_WORKSPACE", value_name = "NAME", conflicts_with = "data_dir")]
+    pub workspace: Option<String>,
+
+    /// Output format
+    #[arg(short = 'o', long, global = true, value_enum, default_value_t = Format::Table)]
+    pub format: Format,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create a task
+    Add(AddArgs),
+    /// List tasks
+    #[command(visible_alias = "list")]
+    Ls(ListArgs),
+    /// Show a task with its steps
+    Show {
+        id: i64,
+    },
+    /// Mark tasks as done
+    Done {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Mark tasks as in progress
+    Start {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Move tasks back to todo
+    Reopen {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Delete tasks
+    Rm {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// List tags
+    Tags,
+    /// List task groups
+    Groups,
+    /// Show task statistics
+    Stats(StatsArgs),
+    /// Run a Model Context Protocol server on stdin/stdout for AI assistants
+    Mcp(McpArgs),
+    /// Print a shell completion script
+    Completions {
+        shell: Shell,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct AddArgs {
+    pub title: String,
+
+    /// Due date: today, tomorrow, fri, +3d, 2025-01-31 or "2025-01-31 18:00"
+    #[arg(long)]
+    pub due: Option<String>,
+
+    /// Start date, same formats as --due
+    #[arg(long)]
+    pub start: Option<String>,
+
+    /// Tag name, created when missing (repeatable)
+    #[arg(short, long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Task group name or id
+    #[arg(short, long)]
+    pub group: Option<String>,
+
+    #[arg(short, long, value_enum, default_value_t = PriorityArg::Normal)]
+    pub priority: PriorityArg,
+
+    #[arg(short, long)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    #[arg(short, long, value_enum)]
+    pub status: Option<StatusArg>,
+
+    /// Tag name, includes child tags (repeatable, matches any)
+    #[arg(short, long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Task group name or id (repeatable, matches any)
+    #[arg(short, long = "group", value_name = "GROUP")]
+    pub groups: Vec<String>,
+
+    #[arg(short, long, value_enum)]
+    pub priority: Option<PriorityArg>,
+
+    /// Search title and description
+    #[arg(short = 'q', long)]
+    pub search: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct StatsArgs {
+    #[arg(long, group = "period")]
+    pub today: bool,
+    #[arg(long, group = "period")]
+    pub week: bool,
+    #[arg(long, group = "period")]
+    pub month: bool,
+}
+
+impl StatsArgs {
+    pub fn period(&self) -> Period {
+        if self.today {
+            Period::Today
+        } else if self.week {
+            Period::Week
+        } else if self.month {
+            Period::Month
+        } else {
+            Period::All
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct McpArgs {
+    /// Only expose tools that read data
+    #[arg(long)]
+    pub read_only: bool,
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatusArg {
+    Todo,
+    InProgress,
+    Done,
+}
+
+impl StatusArg {
+    pub fn value(self) -> i32 {
+        match self {
+            StatusArg::Todo => status::TODO,
+            StatusArg::InProgress => status::IN_PROGRESS,
+            StatusArg::Done => status::DONE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityArg {
+    Normal,
+    Important,
+    Urgent,
+}
+
+impl PriorityArg {
+    pub fn value(self) -> i32 {
+        match self {
+            PriorityArg::Normal => priority::NORMAL,
+            PriorityArg::Important => priority::IMPORTANT,
+            PriorityArg::Urgent => priority::URGENT,
+        }
+    }
+}
@@ -0,0 +1,330 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 子命令实现
+//!
+//! 通过 rtodo-core 直接读写桌面应用的数据库。数据库使用 WAL 模式并设置了忙等待超时，
+//! 桌面应用运行时也可以安全地并发读写；每个修改在一个事务中完成。
+//! 修改提交后与桌面应用一样执行自动化规则，并把事件加入 Webhook 投递队列，由桌面应用的后台任务发送。
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::CommandFactory;
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use std::io;
+use std::path::PathBuf;
+
+use crate::cli::{AddArgs, Cli, Command, Format, ListArgs, StatsArgs};
+use crate::dates::{self, TimeOfDay};
+use crate::mcp;
+use crate::output;
+use rtodo_core::database::paths;
+use rtodo_core::database::workspaces::{self, WorkspacesConfig};
+use rtodo_core::database::repositories::{GroupRepository, StatsRepository, TagRepository, TodoRepository};
+use rtodo_core::models::constants::status;
+use rtodo_core::models::{webhook_event, RuleEvent, Todo};
+use rtodo_core::rules::RuleEngine;
+use rtodo_core::{encryption, webhooks, Database, Error};
+
+/// 加密数据库的口令从这个环境变量读取
+const PASSPHRASE_ENV: &str = "RTODO_PASSPHRASE";
+
+/// 自动创建标签时使用的颜色（与桌面应用新建标签的默认颜色相同）
+const DEFAULT_TAG_COLOR: &str = "#409EFF";
+
+/// 执行命令
+pub async fn run(cli: Cli) -> Result<()> {
+    let format = cli.format;
+
+    if let Command::Completions { shell } = cli.command {
+        clap_complete::generate(shell, &mut Cli::command(), "rtodo", &mut io::stdout());
+        return Ok(());
+    }
+
+    let db = open_database(cli.data_dir, cli.workspace.as_deref()).await?;
+    match cli.command {
+        Command::Add(args) => add(&db, args, format).await,
+        Command::Ls(args) => list(&db, args, format).await,
+        Command::Show { id } => {
+            let todo = db.read(move |conn| find_todo(conn, id)).await?;
+            output::todo_detail(format, &todo)
+        }
+        Command::Done { ids } => set_status(&db, ids, status::DONE, format).await,
+        Command::Start { ids } => set_status(&db, ids, status::IN_PROGRESS, format).await,
+        Command::Reopen { ids } => set_status(&db, ids, status::TODO, format).await,
+        Command::Rm { ids } => remove(&db, ids, format).await,
+        Command::Tags => {
+            let tags = db.read(TagRepository::list).await?;
+            output::tags(format, &tags)
+        }
+        Command::Groups => {
+            let groups = db.read(GroupRepository::list).await?;
+            output::groups(format, &groups)
+        }
+        Command::Stats(args) => stats(&db, args, format).await,
+        Command::Mcp(args) => mcp::serve(db, args.read_only).await,
+        Command::Completions { .. } => unreachable!(),
+    }
+}
+
+/// 打开数据目录中的数据库；加密的数据库用环境变量中的口令解锁
+async fn open_database(data_dir: Option<PathBuf>, workspace: Option<&str>) -> Result<Database> {
+    let data_dir = match data_dir {
+        Some(dir) => std::path::absolute(&dir)
+            .with_context(|| format!("Invalid data directory: {}", dir.display()))?,
+        None => resolve_data_dir(workspace)?,
+    };
+    paths::set_data_dir(Some(data_dir.clone()));
+    let backup_dir = data_dir.join("backups");
+
+    if !encryption::is_enabled(&data_dir) {
+        return Database::new(Some(&backup_dir)).context("Failed to open database");
+    }
+
+    let passphrase = std::env::var(PASSPHRASE_ENV).map_err(|_| {
+        anyhow!("The database in {} is encrypted, set {} to unlock it", data_dir.display(), PASSPHRASE_ENV)
+    })?;
+    let db = Database::locked();
+    encryption::unlock(&db, &data_dir, &passphrase, Some(backup_dir)).await
+        .context("Failed to unlock database")?;
+    Ok(db)
+}
+
+/// 按桌面应用的配置确定工作区的数据目录，与桌面应用启动时的规则相同
+fn resolve_data_dir(workspace: Option<&str>) -> Result<PathBuf> {
+    let config_path = workspaces::config_path()?;
+    let config = WorkspacesConfig::load(&config_path)?;
+    let resolved = workspaces::resolve(&config, workspace).context("Failed to get data directory")?;
+    if let Some(reason) = &resolved.fallback_reason {
+        eprintln!("Warning: the configured data directory is unavailable, using {}: {}", resolved.data_dir.display(), reason);
+    }
+    Ok(resolved.data_dir)
+}
+
+async fn add(db: &Database, args: AddArgs, format: Format) -> Result<()> {
+    let todo = create(db, args).await?;
+    output::todos(format, &[todo])
+}
+
+/// 创建任务，不存在的标签自动创建
+pub async fn create(db: &Database, args: AddArgs) -> Result<Todo> {
+    let title = args.title.trim().to_string();
+    if title.is_empty() {
+        bail!("Title must not be empty");
+    }
+    let start = args.start.as_deref()
+        .map(|s| dates::parse_timestamp(s, TimeOfDay::StartOfDay))
+        .transpose()?;
+    let due = args.due.as_deref()
+        .map(|s| dates::parse_timestamp(s, TimeOfDay::EndOfDay))
+        .transpose()?;
+    dates::ensure_order(start, due)?;
+
+    db.write(move |conn| {
+        let transaction = begin_write(conn)?;
+        let group_id = args.group.as_deref().map(|g| resolve_group(conn, g)).transpose()?;
+        let mut tag_ids = Vec::new();
+        for name in &args.tags {
+            let id = ensure_tag(conn, name)?;
+            if !tag_ids.contains(&id) {
+                tag_ids.push(id);
+            }
+        }
+
+        let todo = TodoRepository::create(
+            conn,
+            &title,
+            args.description.as_deref(),
+            group_id,
+            start,
+            due,
+            args.priority.value(),
+            Some(tag_ids),
+        )?;
+        transaction.commit()?;
+
+        // 规则的动作在各自的事务中执行，需要在提交之后
+        let mut events = vec![RuleEvent::Created];
+        events.extend(todo.tags.iter().flatten().map(|t| RuleEvent::TagAdded { tag_id: t.id }));
+        let todo = apply_rules(conn, todo, &events)?;
+        queue_webhooks(webhooks::enqueue(conn, webhook_event::TODO_CREATED, &todo));
+        Ok::<_, Error>(todo)
+    }).await.context("Failed to create task")
+}
+
+async fn list(db: &Database, args: ListArgs, format: Format) -> Result<()> {
+    let todos = search(db, args).await?;
+    output::todos(format, &todos)
+}
+
+/// 按条件查询任务
+pub async fn search(db: &Database, args: ListArgs) -> Result<Vec<Todo>> {
+    db.read(move |conn| {
+        let group_ids = args.groups.iter()
+            .map(|g| resolve_group(conn, g))
+            .collect::<rtodo_core::Result<Vec<_>>>()?;
+        let tag_ids = args.tags.iter()
+            .map(|t| resolve_tag(conn, t))
+            .collect::<rtodo_core::Result<Vec<_>>>()?;
+
+        TodoRepository::list_with_filters(
+            conn,
+            Some(group_ids),
+            Some(tag_ids),
+            args.status.map(|s| s.value()),
+            args.search.as_deref(),
+            args.priority.map(|p| p.value()),
+            None,
+            None,
+        )
+    }).await.context("Failed to list tasks")
+}
+
+async fn set_status(db: &Database, ids: Vec<i64>, status: i32, format: Format) -> Result<()> {
+    let todos = change_status(db, ids, status).await?;
+    output::todos(format, &todos)
+}
+
+/// 修改任务状态，全部成功或全部不修改
+pub async fn change_status(db: &Database, ids: Vec<i64>, status: i32) -> Result<Vec<Todo>> {
+    db.write(move |conn| {
+        let transaction = begin_write(conn)?;
+        let mut changes = Vec::with_capacity(ids.len());
+        for id in ids {
+            let before = find_todo(conn, id)?;
+            // 已经是目标状态的任务保持原完成时间
+            let after = if before.status.as_i32() == status {
+                None
+            } else {
+                Some(TodoRepository::update_status(conn, id, status)?)
+            };
+            changes.push((before, after));
+        }
+        transaction.commit()?;
+
+        let mut todos = Vec::with_capacity(changes.len());
+        for (before, after) in changes {
+            let Some(after) = after else {
+                todos.push(before);
+                continue;
+            };
+            let events = RuleEngine::diff_events(&before, &after);
+            let after = apply_rules(conn, after, &events)?;
+            queue_webhooks(webhooks::enqueue_todo_updated(conn, &before, &after));
+            todos.push(after);
+        }
+        Ok::<_, Error>(todos)
+    }).await.context("Failed to update tasks")
+}
+
+async fn remove(db: &Database, ids: Vec<i64>, format: Format) -> Result<()> {
+    let deleted = ids.clone();
+    db.write(move |conn| {
+        let transaction = begin_write(conn)?;
+        let mut todos = Vec::with_capacity(ids.len());
+        for id in ids {
+            todos.push(find_todo(conn, id)?);
+            TodoRepository::delete(conn, id)?;
+        }
+        transaction.commit()?;
+
+        for todo in &todos {
+            queue_webhooks(webhooks::enqueue(conn, webhook_event::TODO_DELETED, todo));
+        }
+        Ok::<_, Error>(())
+    }).await.context("Failed to delete tasks")?;
+
+    output::deleted(format, &deleted)
+}
+
+async fn stats(db: &Database, args: StatsArgs, format: Format) -> Result<()> {
+    let (start, end) = dates::stats_bounds(args.period())?;
+    let stats = db.read(move |conn| StatsRepository::get_stats(conn, start, end)).await
+        .context("Failed to get statistics")?;
+    output::stats(format, &stats)
+}
+
+/// 执行事件触发的自动化规则，返回规则执行后的任务
+///
+/// 与桌面应用一样，规则失败不影响命令本身，只在 stderr 输出警告；命令行没有通知中心，规则的通知也输出到 stderr
+fn apply_rules(conn: &Connection, todo: Todo, events: &[RuleEvent]) -> rtodo_core::Result<Todo> {
+    let outcomes = match RuleEngine::run_all(conn, todo.id, events) {
+        Ok(outcomes) => outcomes,
+        Err(e) => {
+            eprintln!("Warning: failed to run rules for task {}: {}", todo.id, e);
+            return Ok(todo);
+        }
+    };
+    for notification in outcomes.iter().flat_map(|o| &o.notifications) {
+        match &notification.body {
+            Some(body) => eprintln!("{}: {}", notification.title, body),
+            None => eprintln!("{}", notification.title),
+        }
+    }
+    if outcomes.iter().any(|o| o.error.is_none() && !o.applied_actions.is_empty()) {
+        find_todo(conn, todo.id)
+    } else {
+        Ok(todo)
+    }
+}
+
+/// Webhook 入队失败只输出警告
+fn queue_webhooks(queued: rtodo_core::Result<usize>) {
+    if let Err(e) = queued {
+        eprintln!("Warning: failed to queue webhook deliveries: {}", e);
+    }
+}
+
+/// 开始写事务
+///
+/// 立即获取写锁：桌面应用同时在写时在这里按忙等待超时排队，
+/// 而不是在事务中途升级为写锁时直接失败
+pub fn begin_write(conn: &Connection) -> rusqlite::Result<Transaction<'_>> {
+    Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+}
+
+pub fn find_todo(conn: &Connection, id: i64) -> rtodo_core::Result<Todo> {
+    TodoRepository::get(conn, id)?
+        .ok_or_else(|| Error::NotFound(format!("Todo not found: {}", id)))
+}
+
+/// 按 ID 或名称（不区分大小写）查找任务组
+pub fn resolve_group(conn: &Connection, text: &str) -> rtodo_core::Result<i64> {
+    if let Ok(id) = text.parse::<i64>() {
+        if GroupRepository::get(conn, id)?.is_some() {
+            return Ok(id);
+        }
+    }
+
+    let matches: Vec<_> = GroupRepository::list(conn)?
+        .into_iter()
+        .filter(|g| g.name.eq_ignore_ascii_case(text.trim()))
+        .collect();
+    match matches.as_slice() {
+        [group] => Ok(group.id),
+        [] => Err(Error::NotFound(format!("Task group not found: {}", text))),
+        _ => Err(Error::InvalidInput(format!(
+            "Several task groups are named {}, use the id instead ({})",
+            text,
+            matches.iter().map(|g| g.id.to_string()).collect::<Vec<_>>().join(", "),
+        ))),
+    }
+}
+
+/// 按名称查找标签
+pub fn resolve_tag(conn: &Connection, name: &str) -> rtodo_core::Result<i64> {
+    TagRepository::find_by_name(conn, name.trim())?
+        .map(|tag| tag.id)
+        .ok_or_else(|| Error::NotFound(format!("Tag not found: {}", name)))
+}
+
+/// 按名称查找标签，不存在时创建
+pub fn ensure_tag(conn: &Connection, name: &str) -> rtodo_core::Result<i64> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidInput("Tag name must not be empty".to_string()));
+    }
+    match TagRepository::find_by_name(conn, name)? {
+        Some(tag) => Ok(tag.id),
+        None => Ok(TagRepository::create(conn, name, DEFAULT_TAG_COLOR, None)?.id),
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 日期解析
+//! 命令行中的日期写法：`today`、`tomorrow`、星期名、`+3d`/`+2w`/`+4h`、`2025-01-31`、`2025-01-31 18:00`。
+//! 只有日期时，截止时间取当天 23:59，开始时间取当天 00:00。
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use serde::Deserialize;
+
+/// 只给出日期时使用的时刻
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeOfDay {
+    /// 当天 00:00（开始时间）
+    StartOfDay,
+    /// 当天 23:59（截止时间）
+    EndOfDay,
+}
+
+impl TimeOfDay {
+    fn on(self, date: NaiveDate) -> NaiveDateTime {
+        let time = match self {
+            TimeOfDay::StartOfDay => NaiveTime::MIN,
+            TimeOfDay::EndOfDay => NaiveTime::from_hms_opt(23, 59, 0).unwrap(),
+        };
+        date.and_time(time)
+    }
+}
+
+/// 统计的时间范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    Today,
+    /// 本周（周一开始）
+    Week,
+    /// 本月
+    Month,
+    All,
+}
+
+/// 解析日期写法，`now` 为当前本地时间
+pub fn parse_when(input: &str, now: NaiveDateTime, default_time: TimeOfDay) -> Result<NaiveDateTime> {
+    let raw = input.trim();
+    let text = raw.to_lowercase();
+    let today = now.date();
+
+    let date = match text.as_str() {
+        "now" => return Ok(now),
+        "today" | "tod" => today,
+        "tomorrow" | "tom" => today + Duration::days(1),
+        "yesterday" => today - Duration::days(1),
+        _ => {
+            if let Some(offset) = text.strip_prefix('+') {
+                return parse_offset(offset, now, default_time)
+                    .ok_or_else(|| anyhow!("Invalid relative date: {} (expected e.g. +3d, +2w, +4h)", input));
+            }
+            if let Some(weekday) = parse_weekday(&text) {
+                next_weekday(today, weekday)
+            } else {
+                for format in ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"] {
+                    if let Ok(datetime) = NaiveDateTime::parse_from_str(raw, format) {
+                        return Ok(datetime);
+                    }
+                }
+                NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                    .map_err(|_| anyhow!("Invalid date: {} (expected today, tomorrow, a weekday, +3d or YYYY-MM-DD [HH:MM])", input))?
+            }
+        }
+    };
+    Ok(default_time.on(date))
+}
+
+/// 解析日期写法并转换为毫秒时间戳
+pub fn parse_timestamp(input: &str, default_time: TimeOfDay) -> Result<i64> {
+    to_millis(parse_when(input, Local::now().naive_local(), default_time)?)
+}
+
+/// 本地时间转换为毫秒时间戳
+pub fn to_millis(datetime: NaiveDateTime) -> Result<i64> {
+    Local.from_local_datetime(&datetime)
+        .earliest()
+        .map(|dt| dt.timestamp_millis())
+        .ok_or_else(|| anyhow!("Invalid local time: {}", datetime))
+}
+
+/// 毫秒时间戳格式化为本地时间 `YYYY-MM-DD HH:MM`
+pub fn format_millis(millis: i64) -> String {
+    Local.timestamp_millis_opt(millis)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 时间范围的本地起止时间（左闭右开），`Period::All` 返回 None
+pub fn period_range(period: Period, today: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    let (start, end) = match period {
+        Period::Today => (today, today + Duration::days(1)),
+        Period::Week => {
+            let start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (start, start + Duration::weeks(1))
+        }
+        Period::Month => {
+            let start = today.with_day(1)?;
+            let end = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)?
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)?
+            };
+            (start, end)
+        }
+        Period::All => return None,
+    };
+    Some((start.and_time(NaiveTime::MIN), end.and_time(NaiveTime::MIN)))
+}
+
+/// 统计用的毫秒时间范围（`get_stats` 的结束时间包含在范围内）
+pub fn stats_bounds(period: Period) -> Result<(Option<i64>, Option<i64>)> {
+    match period_range(period, Local::now().date_naive()) {
+        Some((start, end)) => Ok((Some(to_millis(start)?), Some(to_millis(end)? - 1))),
+        None => Ok((None, None)),
+    }
+}
+
+fn parse_offset(offset: &str, now: NaiveDateTime, default_time: TimeOfDay) -> Option<NaiveDateTime> {
+    let unit = offset.chars().last()?;
+    let amount: i64 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+    match unit {
+        'h' => Some(now + Duration::try_hours(amount)?),
+        'd' => Some(default_time.on(now.date() + Duration::try_days(amount)?)),
+        'w' => Some(default_time.on(now.date() + Duration::try_weeks(amount)?)),
+        _ => None,
+    }
+}
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    let weekday = match text {
+        "mon" | "monday" => Weekday::Mon,
+        "tue" | "tues" | "tuesday" => Weekday::Tue,
+        "wed" | "wednesday" => Weekday::Wed,
+        "thu" | "thur" | "thurs" | "thursday" => Weekday::Thu,
+        "fri" | "friday" => Weekday::Fri,
+        "sat" | "saturday" => Weekday::Sat,
+        "sun" | "sunday" => Weekday::Sun,
+        _ => return None,
+    };
+    Some(weekday)
+}
+
+/// 下一个指定星期几（不含今天）
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days = (weekday.num_days_from_monday() as i64 - today.weekday().num_days_from_monday() as i64 + 6) % 7 + 1;
+    today + Duration::days(days)
+}
+
+/// 校验时间先后
+pub fn ensure_order(start: Option<i64>, due: Option<i64>) -> Result<()> {
+    if let (Some(start), Some(due)) = (start, due) {
+        if start > due {
+            bail!("Start date must not be later than the due date");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+
+/// 2025-01-15 是星期三
+fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 1, 15).unwrap().and_hms_opt(10, 30, 0).unwrap()
+}
+
+fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, 0).unwrap()
+}
+
+#[test]
+fn keywords_and_weekdays() {
+    let due = |s| parse_when(s, now(), TimeOfDay::EndOfDay).unwrap();
+
+    assert_eq!(due("today"), at(2025, 1, 15, 23, 59));
+    assert_eq!(due("Tomorrow"), at(2025, 1, 16, 23, 59));
+    assert_eq!(due("now"), now());
+    assert_eq!(due("fri"), at(2025, 1, 17, 23, 59));
+    // 与今天同一个星期几时取下周
+    assert_eq!(due("wednesday"), at(2025, 1, 22, 23, 59));
+    assert_eq!(parse_when("mon", now(), TimeOfDay::StartOfDay).unwrap(), at(2025, 1, 20, 0, 0));
+}
+
+#[test]
+fn relative_and_absolute_dates() {
+    let due = |s| parse_when(s, now(), TimeOfDay::EndOfDay);
+
+    assert_eq!(due("+3d").unwrap(), at(2025, 1, 18, 23, 59));
+    assert_eq!(due("+2w").unwrap(), at(2025, 1, 29, 23, 59));
+    assert_eq!(due("+4h").unwrap(), at(2025, 1, 15, 14, 30));
+    assert_eq!(due("2025-02-01").unwrap(), at(2025, 2, 1, 23, 59));
+    assert_eq!(due("2025-02-01 18:00").unwrap(), at(2025, 2, 1, 18, 0));
+    assert_eq!(due("2025-02-01T08:15").unwrap(), at(2025, 2, 1, 8, 15));
+
+    assert!(due("+3x").is_err());
+    assert!(due("+d").is_err());
+    assert!(due("next week").is_err());
+    assert!(due("2025-02-30").is_err());
+}
+
+#[test]
+fn period_ranges() {
+    let today = now().date();
+
+    assert_eq!(period_range(Period::Today, today), Some((at(2025, 1, 15, 0, 0), at(2025, 1, 16, 0, 0))));
+    assert_eq!(period_range(Period::Week, today), Some((at(2025, 1, 13, 0, 0), at(2025, 1, 20, 0, 0))));
+    assert_eq!(period_range(Period::Month, today), Some((at(2025, 1, 1, 0, 0), at(2025, 2, 1, 0, 0))));
+    let december = NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+    assert_eq!(period_range(Period::Month, december), Some((at(2025, 12, 1, 0, 0), at(2026, 1, 1, 0, 0))));
+    assert_eq!(period_range(Period::All, today), None);
+}
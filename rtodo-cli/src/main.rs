@@ -0,0 +1,53 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! RTodo 命令行
+//! 与桌面应用共用数据目录和数据库，支持表格、JSON、CSV 输出和 shell 补全
+
+mod cli;
+mod commands;
+mod dates;
+mod mcp;
+mod output;
+
+use clap::Parser;
+use std::process::ExitCode;
+
+use cli::Cli;
+
+/// 设置这个环境变量（例如 `RTODO_LOG=debug`）后把日志输出到 stderr
+const LOG_ENV: &str = "RTODO_LOG";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging();
+
+    match commands::run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        // 输出被管道另一端提前关闭（例如 `| head`）
+        Err(e) if is_broken_pipe(&e) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn is_broken_pipe(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// 默认不输出日志，避免干扰 JSON/CSV 输出
+fn init_logging() {
+    if std::env::var_os(LOG_ENV).is_none() {
+        return;
+    }
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_env(LOG_ENV))
+        .with_writer(std::io::stderr)
+        .init();
+}
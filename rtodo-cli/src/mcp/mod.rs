@@ -0,0 +1,157 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! MCP 服务
+//! 在 stdin/stdout 上以换行分隔的 JSON-RPC 2.0 消息提供 Model Context Protocol 服务，
+//! 供本地 AI 助手读取和管理任务。`--read-only` 时只提供读取数据的工具。
+
+mod resources;
+mod tools;
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use rtodo_core::Database;
+
+/// 支持的协议版本（第一个为最新）
+pub const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const RESOURCE_NOT_FOUND: i64 = -32002;
+
+/// 在 stdin/stdout 上运行服务，直到客户端关闭输入
+pub async fn serve(db: Database, read_only: bool) -> Result<()> {
+    let server = Server::new(db, read_only);
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line).await {
+            let mut text = serde_json::to_string(&response)?;
+            text.push('\n');
+            stdout.write_all(text.as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// JSON-RPC 错误
+#[derive(Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+pub struct Server {
+    db: Database,
+    read_only: bool,
+}
+
+impl Server {
+    pub fn new(db: Database, read_only: bool) -> Self {
+        Self { db, read_only }
+    }
+
+    /// 处理一行输入；通知和客户端的响应没有返回值
+    pub async fn handle_line(&self, line: &str) -> Option<Value> {
+        match serde_json::from_str(line) {
+            Ok(message) => self.handle(message).await,
+            Err(e) => Some(error_response(Value::Null, RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)))),
+        }
+    }
+
+    pub async fn handle(&self, message: Value) -> Option<Value> {
+        let Some(object) = message.as_object() else {
+            return Some(error_response(Value::Null, RpcError::new(INVALID_REQUEST, "Expected a JSON-RPC object")));
+        };
+        let id = object.get("id").cloned();
+        let Some(method) = object.get("method").and_then(Value::as_str) else {
+            // 客户端对服务器请求的响应；本服务不向客户端发送请求
+            if object.contains_key("result") || object.contains_key("error") {
+                return None;
+            }
+            return Some(error_response(id.unwrap_or(Value::Null), RpcError::new(INVALID_REQUEST, "Missing method")));
+        };
+        let params = object.get("params").cloned().unwrap_or(Value::Null);
+
+        let result = self.dispatch(method, params).await;
+        // 通知（没有 id）不回复，包括 notifications/initialized 等未处理的通知
+        let id = id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(initialize(&params, self.read_only)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools::list(self.read_only) })),
+            "tools/call" => tools::call(&self.db, self.read_only, parse_params(params)?).await,
+            "resources/list" => Ok(json!({ "resources": resources::list() })),
+            "resources/templates/list" => Ok(json!({ "resourceTemplates": [] })),
+            "resources/read" => resources::read(&self.db, parse_params(params)?).await,
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+        }
+    }
+}
+
+/// 协商协议版本：支持客户端请求的版本时使用该版本，否则返回最新版本由客户端决定
+fn initialize(params: &Value, read_only: bool) -> Value {
+    let requested = params.get("protocolVersion").and_then(Value::as_str);
+    let version = requested
+        .filter(|v| PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSIONS[0]);
+
+    let mut instructions = String::from(
+        "Tools and resources for the user's RTodo task lists. Dates accept today, tomorrow, \
+         weekday names, +3d, YYYY-MM-DD or \"YYYY-MM-DD HH:MM\" in local time; \
+         timestamps in results are Unix milliseconds.",
+    );
+    if read_only {
+        instructions.push_str(" This server is read-only: tasks cannot be created or changed.");
+    }
+
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": {}, "resources": {} },
+        "serverInfo": { "name": "rtodo", "title": "RTodo", "version": env!("CARGO_PKG_VERSION") },
+        "instructions": instructions,
+    })
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError::invalid_params(format!("Invalid params: {}", e)))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
@@ -0,0 +1,82 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! MCP 资源：今天的任务和逾期任务
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::{RpcError, INTERNAL_ERROR, RESOURCE_NOT_FOUND};
+use crate::dates::{self, Period};
+use rtodo_core::database::repositories::TodoRepository;
+use rtodo_core::models::{Todo, TodoStatus};
+use rtodo_core::Database;
+
+pub const TODAY_URI: &str = "rtodo://todos/today";
+pub const OVERDUE_URI: &str = "rtodo://todos/overdue";
+
+/// resources/list
+pub fn list() -> Vec<Value> {
+    vec![
+        json!({
+            "uri": TODAY_URI,
+            "name": "today",
+            "title": "Today",
+            "description": "Tasks starting or due today, like the Today view in the app",
+            "mimeType": "application/json",
+        }),
+        json!({
+            "uri": OVERDUE_URI,
+            "name": "overdue",
+            "title": "Overdue",
+            "description": "Unfinished tasks whose due date has passed, oldest first",
+            "mimeType": "application/json",
+        }),
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadParams {
+    uri: String,
+}
+
+/// resources/read
+pub async fn read(db: &Database, params: ReadParams) -> Result<Value, RpcError> {
+    let todos = match params.uri.as_str() {
+        TODAY_URI => today(db).await,
+        OVERDUE_URI => overdue(db, Local::now().timestamp_millis()).await,
+        _ => return Err(RpcError::new(RESOURCE_NOT_FOUND, format!("Resource not found: {}", params.uri))),
+    };
+    // 数据库错误（例如未解锁）作为请求错误返回
+    let todos = todos.map_err(|e| RpcError::new(INTERNAL_ERROR, format!("{:#}", e)))?;
+    let text = serde_json::to_string_pretty(&todos)
+        .map_err(|e| RpcError::new(INTERNAL_ERROR, e.to_string()))?;
+
+    Ok(json!({
+        "contents": [{ "uri": params.uri, "mimeType": "application/json", "text": text }],
+    }))
+}
+
+/// 开始或截止时间在今天的任务
+async fn today(db: &Database) -> Result<Vec<Todo>> {
+    let (start, end) = dates::period_range(Period::Today, Local::now().date_naive())
+        .context("Invalid date")?;
+    let (start, end) = (dates::to_millis(start)?, dates::to_millis(end)?);
+
+    db.read(move |conn| {
+        TodoRepository::list_with_filters(conn, None, None, None, None, None, Some(start), Some(end))
+    }).await.context("Failed to list today's tasks")
+}
+
+/// 截止时间早于 `now` 的未完成任务
+pub async fn overdue(db: &Database, now: i64) -> Result<Vec<Todo>> {
+    let mut todos = db.read(move |conn| {
+        TodoRepository::list_with_filters(conn, None, None, None, None, None, None, None)
+    }).await.context("Failed to list overdue tasks")?;
+
+    todos.retain(|todo| todo.status != TodoStatus::Done && todo.due_date.is_some_and(|due| due < now));
+    todos.sort_by_key(|todo| todo.due_date);
+    Ok(todos)
+}
@@ -0,0 +1,171 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn temp_db() -> (Database, PathBuf) {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "rtodo-mcp-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let db = Database::open(&dir.join("rtodo.db"), None).unwrap();
+    (db, dir)
+}
+
+async fn request(server: &Server, method: &str, params: Value) -> Value {
+    server.handle(json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .await
+        .expect("requests get a response")
+}
+
+/// 调用工具，返回解析后的结果和 isError
+async fn call_tool(server: &Server, name: &str, arguments: Value) -> (Value, bool) {
+    let response = request(server, "tools/call", json!({ "name": name, "arguments": arguments })).await;
+    let result = &response["result"];
+    let text = result["content"][0]["text"].as_str().unwrap();
+    let is_error = result["isError"].as_bool().unwrap();
+    let value = if is_error { Value::String(text.to_string()) } else { serde_json::from_str(text).unwrap() };
+    (value, is_error)
+}
+
+#[tokio::test]
+async fn handshake_and_protocol_errors() {
+    let (db, dir) = temp_db();
+    let server = Server::new(db, false);
+
+    let response = request(&server, "initialize", json!({ "protocolVersion": "2024-11-05" })).await;
+    assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+    assert!(response["result"]["capabilities"]["tools"].is_object());
+    let response = request(&server, "initialize", json!({ "protocolVersion": "1999-01-01" })).await;
+    assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSIONS[0]);
+
+    // 通知和客户端的响应不回复
+    assert!(server.handle(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await.is_none());
+    assert!(server.handle(json!({ "jsonrpc": "2.0", "id": 5, "result": {} })).await.is_none());
+
+    let response = request(&server, "prompts/list", json!({})).await;
+    assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    let response = server.handle_line("{not json").await.unwrap();
+    assert_eq!(response["error"]["code"], PARSE_ERROR);
+    let response = request(&server, "tools/call", json!({ "name": "drop_database" })).await;
+    assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    let response = request(&server, "resources/read", json!({ "uri": "rtodo://nothing" })).await;
+    assert_eq!(response["error"]["code"], RESOURCE_NOT_FOUND);
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn read_only_mode_hides_and_refuses_writes() {
+    let (db, dir) = temp_db();
+    let server = Server::new(db, true);
+
+    let response = request(&server, "tools/list", json!({})).await;
+    let names: Vec<_> = response["result"]["tools"].as_array().unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap().to_string())
+        .collect();
+    assert!(names.contains(&"search_todos".to_string()));
+    assert!(!names.contains(&"create_todo".to_string()));
+
+    let (message, is_error) = call_tool(&server, "create_todo", json!({ "title": "Nope" })).await;
+    assert!(is_error);
+    assert!(message.as_str().unwrap().contains("read-only"));
+    let (result, _) = call_tool(&server, "search_todos", json!({})).await;
+    assert_eq!(result["total"], 0);
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn create_update_complete_and_search() {
+    let (db, dir) = temp_db();
+    let server = Server::new(db, false);
+
+    let (todo, is_error) = call_tool(&server, "create_todo", json!({
+        "title": "Write report",
+        "due": "2030-01-31",
+        "tags": ["work", "work"],
+        "priority": "important",
+    })).await;
+    assert!(!is_error, "{}", todo);
+    let id = todo["id"].as_i64().unwrap();
+    assert_eq!(todo["priority"], 1);
+    assert!(todo["due_date"].is_i64());
+
+    // null 清空截止时间，缺失的字段保持不变
+    let (todo, is_error) = call_tool(&server, "update_todo", json!({ "id": id, "due": null, "title": "Write final report" })).await;
+    assert!(!is_error, "{}", todo);
+    assert_eq!(todo["title"], "Write final report");
+    assert!(todo["due_date"].is_null());
+    assert_eq!(todo["priority"], 1);
+
+    let (message, is_error) = call_tool(&server, "update_todo", json!({ "id": id, "start": "2030-02-02", "due": "2030-02-01" })).await;
+    assert!(is_error);
+    assert!(message.as_str().unwrap().contains("Start date"));
+
+    let (todo, _) = call_tool(&server, "complete_todo", json!({ "id": id })).await;
+    assert_eq!(todo["status"], 2);
+
+    let (result, _) = call_tool(&server, "search_todos", json!({ "query": "final", "status": "done", "tags": ["work"] })).await;
+    assert_eq!(result["total"], 1);
+    assert_eq!(result["todos"][0]["id"], id);
+    let (message, is_error) = call_tool(&server, "get_todo", json!({ "id": id + 100 })).await;
+    assert!(is_error);
+    assert!(message.as_str().unwrap().contains("not found"));
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn overdue_resource_lists_unfinished_past_due() {
+    let (db, dir) = temp_db();
+    let server = Server::new(db, false);
+
+    for (title, due) in [("Late", "2020-01-01"), ("Done late", "2020-01-02"), ("Future", "2099-01-01")] {
+        call_tool(&server, "create_todo", json!({ "title": title, "due": due })).await;
+    }
+    let (result, _) = call_tool(&server, "search_todos", json!({ "query": "Done late" })).await;
+    call_tool(&server, "complete_todo", json!({ "id": result["todos"][0]["id"] })).await;
+
+    let response = request(&server, "resources/read", json!({ "uri": resources::OVERDUE_URI })).await;
+    let contents = &response["result"]["contents"][0];
+    assert_eq!(contents["uri"], resources::OVERDUE_URI);
+    let todos: Vec<Value> = serde_json::from_str(contents["text"].as_str().unwrap()).unwrap();
+    let titles: Vec<_> = todos.iter().map(|t| t["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, ["Late"]);
+
+    std::fs::remove_dir_all(dir).ok();
+}
+
+#[tokio::test]
+async fn writes_run_rules_and_queue_webhooks() {
+    use rtodo_core::database::repositories::{RuleRepository, WebhookRepository};
+    use rtodo_core::models::{RuleAction, RuleField, RuleTrigger};
+
+    let (db, dir) = temp_db();
+    db.write(|conn| {
+        let actions = [RuleAction::SetField { field: RuleField::Priority, value: json!(3) }];
+        RuleRepository::create(conn, "Urgent by default", true, &RuleTrigger::Created, &[], &actions)?;
+        WebhookRepository::create(conn, "Everything", "https://example.com/hook", None, &[], true)
+    }).await.unwrap();
+    let server = Server::new(db.clone(), false);
+
+    let (todo, _) = call_tool(&server, "create_todo", json!({ "title": "Ship it" })).await;
+    assert_eq!(todo["priority"], 3);
+    call_tool(&server, "complete_todo", json!({ "id": todo["id"] })).await;
+
+    let deliveries = db.read(|conn| WebhookRepository::list_deliveries(conn, None, 10)).await.unwrap();
+    let mut events: Vec<_> = deliveries.iter().map(|d| d.event.as_str()).collect();
+    events.sort_unstable();
+    assert_eq!(events, ["todo.created", "todo.status_changed", "todo.updated"]);
+    let executions = db.read(|conn| RuleRepository::list_executions(conn, None, 10)).await.unwrap();
+    assert_eq!(executions.len(), 1);
+
+    std::fs::remove_dir_all(dir).ok();
+}
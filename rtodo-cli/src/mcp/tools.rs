@@ -0,0 +1,342 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! MCP 工具
+//!
+//! 工具执行失败（参数不合法、任务不存在等）以 `isError` 结果返回，让助手能看到原因并修正
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use super::RpcError;
+use crate::cli::{AddArgs, ListArgs, PriorityArg, StatusArg};
+use crate::commands::{self, begin_write, ensure_tag, find_todo, resolve_group};
+use crate::dates::{self, Period, TimeOfDay};
+use rtodo_core::database::repositories::{GroupRepository, StatsRepository, TagRepository, TodoRepository};
+use rtodo_core::models::constants::status;
+use rtodo_core::{Database, Error};
+
+/// 搜索结果的默认和最大条数
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 200;
+
+const STATUS_SCHEMA: &str = r#"{ "type": "string", "enum": ["todo", "in_progress", "done"] }"#;
+const PRIORITY_SCHEMA: &str = r#"{ "type": "string", "enum": ["normal", "important", "urgent"] }"#;
+const DATE_DESCRIPTION: &str = "today, tomorrow, a weekday, +3d, +2w, YYYY-MM-DD or \"YYYY-MM-DD HH:MM\"";
+
+/// 所有工具的定义；只读模式下只列出 `readOnlyHint` 为 true 的工具
+fn definitions() -> Vec<Value> {
+    let status: Value = serde_json::from_str(STATUS_SCHEMA).unwrap();
+    let priority: Value = serde_json::from_str(PRIORITY_SCHEMA).unwrap();
+    let id = json!({ "type": "integer", "description": "Task id" });
+    let names = json!({ "type": "array", "items": { "type": "string" } });
+
+    vec![
+        tool(
+            "search_todos",
+            "Search tasks by text, status, group, tag and priority. Unfinished tasks come first, by due date.",
+            true,
+            json!({
+                "query": { "type": "string", "description": "Text in the title or description" },
+                "status": status,
+                "groups": { "type": "array", "items": { "type": "string" }, "description": "Task group names or ids (matches any)" },
+                "tags": { "type": "array", "items": { "type": "string" }, "description": "Tag names, includes child tags (matches any)" },
+                "priority": priority,
+                "limit": { "type": "integer", "minimum": 1, "maximum": MAX_LIMIT, "description": format!("Maximum number of tasks, default {}", DEFAULT_LIMIT) },
+            }),
+            &[],
+        ),
+        tool("get_todo", "Get a task with its tags, steps and attachments.", true, json!({ "id": id }), &["id"]),
+        tool("list_groups", "List task groups.", true, json!({}), &[]),
+        tool("list_tags", "List tags.", true, json!({}), &[]),
+        tool(
+            "get_stats",
+            "Count tasks by status, including overdue and important ones.",
+            true,
+            json!({
+                "period": { "type": "string", "enum": ["today", "week", "month", "all"], "description": "Only tasks created in this period, default all" },
+            }),
+            &[],
+        ),
+        tool(
+            "create_todo",
+            "Create a task. Missing tags are created.",
+            false,
+            json!({
+                "title": { "type": "string" },
+                "description": { "type": "string" },
+                "due": { "type": "string", "description": format!("Due date: {}", DATE_DESCRIPTION) },
+                "start": { "type": "string", "description": format!("Start date: {}", DATE_DESCRIPTION) },
+                "group": { "type": "string", "description": "Task group name or id" },
+                "tags": names,
+                "priority": priority,
+            }),
+            &["title"],
+        ),
+        tool(
+            "update_todo",
+            "Change fields of a task. Omitted fields are kept; null clears description, due, start and group. tags replaces all tags.",
+            false,
+            json!({
+                "id": id,
+                "title": { "type": "string" },
+                "description": { "type": ["string", "null"] },
+                "due": { "type": ["string", "null"], "description": format!("Due date: {}", DATE_DESCRIPTION) },
+                "start": { "type": ["string", "null"], "description": format!("Start date: {}", DATE_DESCRIPTION) },
+                "group": { "type": ["string", "null"], "description": "Task group name or id" },
+                "tags": names,
+                "priority": priority,
+                "status": status,
+            }),
+            &["id"],
+        ),
+        tool("complete_todo", "Mark a task as done.", false, json!({ "id": id }), &["id"]),
+    ]
+}
+
+fn tool(name: &str, description: &str, read_only: bool, properties: Value, required: &[&str]) -> Value {
+    json!({
+        "name": name,
+        "description": description,
+        "inputSchema": { "type": "object", "properties": properties, "required": required },
+        "annotations": { "readOnlyHint": read_only, "destructiveHint": false },
+    })
+}
+
+fn is_read_only(definition: &Value) -> bool {
+    definition["annotations"]["readOnlyHint"].as_bool().unwrap_or(false)
+}
+
+/// tools/list
+pub fn list(read_only: bool) -> Vec<Value> {
+    definitions().into_iter()
+        .filter(|definition| !read_only || is_read_only(definition))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallParams {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// tools/call
+pub async fn call(db: &Database, read_only: bool, params: CallParams) -> Result<Value, RpcError> {
+    let Some(definition) = definitions().into_iter().find(|d| d["name"] == params.name.as_str()) else {
+        return Err(RpcError::invalid_params(format!("Unknown tool: {}", params.name)));
+    };
+    if read_only && !is_read_only(&definition) {
+        return Ok(tool_result(Err(anyhow::anyhow!("The RTodo MCP server is running in read-only mode"))));
+    }
+    Ok(tool_result(run(db, &params.name, params.arguments).await))
+}
+
+fn tool_result(result: Result<Value>) -> Value {
+    match result {
+        Ok(value) => json!({
+            "content": [{ "type": "text", "text": serde_json::to_string_pretty(&value).unwrap_or_default() }],
+            "isError": false,
+        }),
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": format!("{:#}", e) }],
+            "isError": true,
+        }),
+    }
+}
+
+async fn run(db: &Database, name: &str, arguments: Value) -> Result<Value> {
+    match name {
+        "search_todos" => search_todos(db, parse(arguments)?).await,
+        "get_todo" => {
+            let IdArgs { id } = parse(arguments)?;
+            to_value(db.read(move |conn| find_todo(conn, id)).await?)
+        }
+        "list_groups" => to_value(db.read(GroupRepository::list).await?),
+        "list_tags" => to_value(db.read(TagRepository::list).await?),
+        "get_stats" => {
+            let StatsArgs { period } = parse(arguments)?;
+            let (start, end) = dates::stats_bounds(period.unwrap_or(Period::All))?;
+            to_value(db.read(move |conn| StatsRepository::get_stats(conn, start, end)).await?)
+        }
+        "create_todo" => {
+            let args: CreateArgs = parse(arguments)?;
+            to_value(commands::create(db, args.into()).await?)
+        }
+        "update_todo" => update_todo(db, parse(arguments)?).await,
+        "complete_todo" => {
+            let IdArgs { id } = parse(arguments)?;
+            let mut todos = commands::change_status(db, vec![id], status::DONE).await?;
+            to_value(todos.pop())
+        }
+        _ => unreachable!("tool {} has a definition but no implementation", name),
+    }
+}
+
+fn parse<T: DeserializeOwned>(arguments: Value) -> Result<T> {
+    let arguments = if arguments.is_null() { json!({}) } else { arguments };
+    serde_json::from_value(arguments).context("Invalid arguments")
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value> {
+    Ok(serde_json::to_value(value)?)
+}
+
+#[derive(Deserialize)]
+struct IdArgs {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct StatsArgs {
+    #[serde(default)]
+    period: Option<Period>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct SearchArgs {
+    query: Option<String>,
+    status: Option<StatusArg>,
+    groups: Vec<String>,
+    tags: Vec<String>,
+    priority: Option<PriorityArg>,
+    limit: Option<usize>,
+}
+
+async fn search_todos(db: &Database, args: SearchArgs) -> Result<Value> {
+    let limit = args.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let mut todos = commands::search(db, ListArgs {
+        status: args.status,
+        tags: args.tags,
+        groups: args.groups,
+        priority: args.priority,
+        search: args.query,
+    }).await?;
+
+    let total = todos.len();
+    todos.truncate(limit);
+    Ok(json!({ "total": total, "todos": todos }))
+}
+
+#[derive(Deserialize)]
+struct CreateArgs {
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    start: Option<String>,
+    #[serde(default)]
+    group: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    priority: Option<PriorityArg>,
+}
+
+impl From<CreateArgs> for AddArgs {
+    fn from(args: CreateArgs) -> Self {
+        AddArgs {
+            title: args.title,
+            due: args.due,
+            start: args.start,
+            tags: args.tags,
+            group: args.group,
+            priority: args.priority.unwrap_or(PriorityArg::Normal),
+            description: args.description,
+        }
+    }
+}
+
+/// 区分字段缺失（None）和显式的 null（Some(None)）
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+pub(super) struct UpdateArgs {
+    id: i64,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    due: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    start: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    group: Option<Option<String>>,
+    #[serde(default)]
+    tags: Option<Vec<String>>,
+    #[serde(default)]
+    priority: Option<PriorityArg>,
+    #[serde(default)]
+    status: Option<StatusArg>,
+}
+
+fn parse_date(value: Option<Option<String>>, default_time: TimeOfDay) -> Result<Option<Option<i64>>> {
+    value.map(|date| date.map(|d| dates::parse_timestamp(&d, default_time)).transpose()).transpose()
+}
+
+async fn update_todo(db: &Database, args: UpdateArgs) -> Result<Value> {
+    let title = match args.title {
+        Some(title) if title.trim().is_empty() => anyhow::bail!("Title must not be empty"),
+        title => title.map(|t| t.trim().to_string()),
+    };
+    let start = parse_date(args.start, TimeOfDay::StartOfDay)?;
+    let due = parse_date(args.due, TimeOfDay::EndOfDay)?;
+    let id = args.id;
+
+    let todo = db.write(move |conn| {
+        let transaction = begin_write(conn)?;
+        let current = find_todo(conn, id)?;
+        dates::ensure_order(start.unwrap_or(current.start_date), due.unwrap_or(current.due_date))
+            .map_err(|e| Error::InvalidInput(e.to_string()))?;
+
+        let group_id = args.group
+            .map(|group| group.map(|g| resolve_group(conn, &g)).transpose())
+            .transpose()?;
+        let tag_ids = args.tags
+            .map(|names| {
+                let mut ids = Vec::new();
+                for name in &names {
+                    let tag_id = ensure_tag(conn, name)?;
+                    if !ids.contains(&tag_id) {
+                        ids.push(tag_id);
+                    }
+                }
+                Ok::<_, Error>(ids)
+            })
+            .transpose()?;
+        // 已经是目标状态时不传状态，保持原完成时间
+        let status = args.status
+            .map(|s| s.value())
+            .filter(|s| *s != current.status.as_i32());
+
+        let todo = TodoRepository::update(
+            conn,
+            id,
+            title.as_deref(),
+            args.description,
+            status,
+            args.priority.map(|p| p.value()),
+            group_id,
+            None,
+            start,
+            due,
+            tag_ids,
+        )?;
+        transaction.commit()?;
+        Ok::<_, Error>(todo)
+    }).await.context("Failed to update task")?;
+
+    to_value(todo)
+}
@@ -0,0 +1,189 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 输出格式化
+//! 表格供人阅读，JSON 与桌面应用命令返回的结构相同，CSV 的时间使用 RFC 3339 便于其他工具处理。
+
+use anyhow::Result;
+use chrono::{Local, TimeZone};
+use comfy_table::{presets, ContentArrangement, Table};
+use serde::Serialize;
+use std::io::{self, Write};
+
+use crate::cli::Format;
+use crate::dates::format_millis;
+use rtodo_core::models::constants::priority;
+use rtodo_core::models::{Tag, TaskGroup, Todo, TodoStats, TodoStatus};
+
+const TODO_HEADERS: [&str; 8] = ["id", "title", "status", "priority", "group", "tags", "start", "due"];
+
+/// 输出任务列表
+pub fn todos(format: Format, todos: &[Todo]) -> Result<()> {
+    let rows = todos.iter().map(|todo| todo_row(todo, format)).collect();
+    emit(format, todos, &TODO_HEADERS, rows)
+}
+
+/// 输出单个任务的详细信息
+pub fn todo_detail(format: Format, todo: &Todo) -> Result<()> {
+    if format != Format::Table {
+        return emit(format, todo, &TODO_HEADERS, vec![todo_row(todo, format)]);
+    }
+
+    let time = |millis: Option<i64>| millis.map(format_millis).unwrap_or_default();
+    let mut rows = vec![
+        vec!["id".to_string(), todo.id.to_string()],
+        vec!["title".to_string(), todo.title.clone()],
+        vec!["status".to_string(), status_label(todo.status).to_string()],
+        vec!["priority".to_string(), priority_label(todo.priority).to_string()],
+        vec!["group".to_string(), group_name(todo)],
+        vec!["tags".to_string(), tag_names(todo)],
+        vec!["start".to_string(), time(todo.start_date)],
+        vec!["due".to_string(), time(todo.due_date)],
+        vec!["completed".to_string(), time(todo.completed_at)],
+        vec!["created".to_string(), format_millis(todo.created_at)],
+    ];
+    if let Some(description) = todo.description.as_deref().filter(|d| !d.is_empty()) {
+        rows.push(vec!["description".to_string(), description.to_string()]);
+    }
+    for step in todo.steps.iter().flatten() {
+        let mark = if step.is_completed { "[x]" } else { "[ ]" };
+        rows.push(vec!["step".to_string(), format!("{} {}", mark, step.title)]);
+    }
+    print_table(&["field", "value"], rows)
+}
+
+/// 输出删除的任务 ID
+pub fn deleted(format: Format, ids: &[i64]) -> Result<()> {
+    match format {
+        Format::Table => {
+            println!("Deleted {} task(s): {}", ids.len(), ids.iter().map(i64::to_string).collect::<Vec<_>>().join(", "));
+            Ok(())
+        }
+        _ => emit(format, ids, &["id"], ids.iter().map(|id| vec![id.to_string()]).collect()),
+    }
+}
+
+/// 输出标签列表
+pub fn tags(format: Format, tags: &[Tag]) -> Result<()> {
+    let rows = tags.iter()
+        .map(|tag| vec![
+            tag.id.to_string(),
+            tag.name.clone(),
+            tag.color.clone(),
+            tag.parent_id.map(|id| id.to_string()).unwrap_or_default(),
+        ])
+        .collect();
+    emit(format, tags, &["id", "name", "color", "parent_id"], rows)
+}
+
+/// 输出任务组列表
+pub fn groups(format: Format, groups: &[TaskGroup]) -> Result<()> {
+    let rows = groups.iter()
+        .map(|group| vec![
+            group.id.to_string(),
+            group.name.clone(),
+            group.parent_id.map(|id| id.to_string()).unwrap_or_default(),
+        ])
+        .collect();
+    emit(format, groups, &["id", "name", "parent_id"], rows)
+}
+
+/// 输出统计；表格纵向排列，CSV 为一行
+pub fn stats(format: Format, stats: &TodoStats) -> Result<()> {
+    let fields = [
+        ("total", stats.total),
+        ("todo", stats.todo),
+        ("in_progress", stats.in_progress),
+        ("done", stats.done),
+        ("overdue", stats.overdue),
+        ("marked", stats.marked),
+    ];
+    match format {
+        Format::Table => print_table(
+            &["metric", "count"],
+            fields.iter().map(|(name, count)| vec![name.to_string(), count.to_string()]).collect(),
+        ),
+        _ => {
+            let headers: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+            emit(format, stats, &headers, vec![fields.iter().map(|(_, count)| count.to_string()).collect()])
+        }
+    }
+}
+
+fn emit<T: Serialize + ?Sized>(format: Format, value: &T, headers: &[&str], rows: Vec<Vec<String>>) -> Result<()> {
+    match format {
+        Format::Table => print_table(headers, rows),
+        Format::Json => {
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, value)?;
+            writeln!(stdout)?;
+            Ok(())
+        }
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(io::stdout().lock());
+            writer.write_record(headers)?;
+            for row in rows {
+                writer.write_record(row)?;
+            }
+            writer.flush()?;
+            Ok(())
+        }
+    }
+}
+
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) -> Result<()> {
+    let mut table = Table::new();
+    table.load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(headers.to_vec());
+    for row in rows {
+        table.add_row(row);
+    }
+    writeln!(io::stdout().lock(), "{}", table)?;
+    Ok(())
+}
+
+fn todo_row(todo: &Todo, format: Format) -> Vec<String> {
+    let time = |millis: Option<i64>| match (millis, format) {
+        (Some(millis), Format::Csv) => Local.timestamp_millis_opt(millis)
+            .single()
+            .map(|dt| dt.to_rfc3339())
+            .unwrap_or_default(),
+        (Some(millis), _) => format_millis(millis),
+        (None, _) => String::new(),
+    };
+    vec![
+        todo.id.to_string(),
+        todo.title.clone(),
+        status_label(todo.status).to_string(),
+        priority_label(todo.priority).to_string(),
+        group_name(todo),
+        tag_names(todo),
+        time(todo.start_date),
+        time(todo.due_date),
+    ]
+}
+
+fn status_label(status: TodoStatus) -> &'static str {
+    match status {
+        TodoStatus::Todo => "todo",
+        TodoStatus::InProgress => "in-progress",
+        TodoStatus::Done => "done",
+    }
+}
+
+fn priority_label(value: i32) -> &'static str {
+    match value {
+        priority::URGENT => "urgent",
+        priority::IMPORTANT => "important",
+        _ => "normal",
+    }
+}
+
+fn group_name(todo: &Todo) -> String {
+    todo.group_info.as_ref().map(|g| g.name.clone()).unwrap_or_default()
+}
+
+fn tag_names(todo: &Todo) -> String {
+    todo.tags.iter().flatten().map(|t| t.name.as_str()).collect::<Vec<_>>().join(", ")
+}
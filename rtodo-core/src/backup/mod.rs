@@ -0,0 +1,12 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 备份快照和轮换策略
+//! 定时备份的调度由应用负责，这里只负责创建、列出、校验快照，决定哪些旧备份可以删除，以及把快照上传到远程存储。
+
+pub mod remote;
+pub mod retention;
+pub mod snapshot;
+
+#[cfg(test)]
+mod tests;
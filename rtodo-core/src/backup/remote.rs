@@ -0,0 +1,276 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 远程备份
+//!
+//! 本地快照上传到远程存储（WebDAV 等）的 `backups/<名称>/` 下，附件按 SHA-256 存放在 `attachments/<哈希>`，
+//! 多份备份共用。清单最后上传，没有清单的远程备份视为未完成。中断的上传在下次上传时按文件继续：
+//! 远程已有且大小一致的数据库文件、按哈希已存在的附件都会跳过；上传到一半的大文件在支持分块上传的服务器
+//! （Nextcloud/ownCloud）上只补传缺少的分块（见 [`crate::storage::WebDavStore`]），其他服务器上重新完整上传。
+//! 远程备份按与本地相同的轮换策略清理，不再被引用的附件随之删除。
+//! 任意一份远程备份都可以下载回本地备份目录，再按本地备份恢复。
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::retention::{self, RetentionPolicy};
+use super::snapshot::{self, MANIFEST_FILE, NAME_PREFIX};
+use crate::error::{Context, Error, Result};
+use crate::models::{BackupInfo, BackupManifest, RemoteBackupReport};
+use crate::storage::{split_path, Store};
+
+/// 远程存储中存放备份的目录
+pub const BACKUPS_DIR: &str = "backups";
+/// 远程存储中存放附件的目录
+pub const ATTACHMENTS_DIR: &str = "attachments";
+
+/// 超过这个时间仍未完成的远程备份视为已放弃
+const STALE_UPLOAD_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// 远程存储中的备份
+struct RemoteBackups {
+    /// 完整的备份（按时间倒序）
+    complete: Vec<(BackupInfo, BackupManifest)>,
+    /// 没有清单的备份（上传中或上传中断）
+    incomplete: Vec<String>,
+}
+
+/// 上传本地备份目录中轮换策略保留的、远程存储还没有的备份，再清理远程的旧备份
+///
+/// 附件从数据目录 `data_dir` 读取；备份后被删除或修改的附件无法上传，恢复时报告为缺失
+pub fn push(store: &dyn Store, backup_dir: &Path, data_dir: &Path, policy: &RetentionPolicy) -> Result<RemoteBackupReport> {
+    let now = Utc::now().timestamp_millis();
+    let mut report = RemoteBackupReport::default();
+
+    let mut local = snapshot::list_backups(backup_dir)?;
+    let pruned_locally: HashSet<String> = retention::backups_to_prune(&local, policy, now).into_iter().collect();
+    local.retain(|backup| !pruned_locally.contains(&backup.name));
+    local.reverse();
+
+    let remote = scan(store)?;
+    let uploaded: HashSet<&str> = remote.complete.iter().map(|(info, _)| info.name.as_str()).collect();
+    let mut blobs: HashMap<String, u64> = store.entries(ATTACHMENTS_DIR)?
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| (entry.name, entry.size))
+        .collect();
+
+    for backup in local.iter().filter(|backup| !uploaded.contains(backup.name.as_str())) {
+        report.attachments_uploaded += upload(store, backup_dir, data_dir, &backup.name, &mut blobs)
+            .with_context(|| format!("Failed to upload backup {}", backup.name))?;
+        report.uploaded.push(backup.name.clone());
+    }
+
+    let local_names: HashSet<&str> = local.iter().map(|backup| backup.name.as_str()).collect();
+    report.pruned = prune(store, policy, now, &local_names)?;
+    report.finished_at = Utc::now().timestamp_millis();
+    Ok(report)
+}
+
+/// 列出远程存储中的完整备份（按时间倒序）
+pub fn list(store: &dyn Store) -> Result<Vec<BackupInfo>> {
+    Ok(scan(store)?.complete.into_iter().map(|(info, _)| info).collect())
+}
+
+/// 把远程备份下载到本地备份目录，并从远程存储取回数据目录 `data_dir` 中缺失的附件
+///
+/// 数据库按清单校验后才移入备份目录，之后即可按本地备份校验和恢复；本地已有同名备份时只取回附件。
+/// 远程也没有的附件保留为缺失，由校验报告
+pub fn download(store: &dyn Store, name: &str, backup_dir: &Path, data_dir: &Path) -> Result<BackupInfo> {
+    snapshot::check_name(name)?;
+    let prefix = format!("{}/{}", BACKUPS_DIR, name);
+    let content = store.read(&format!("{}/{}", prefix, MANIFEST_FILE))?
+        .ok_or_else(|| Error::NotFound(format!("Remote backup not found: {}", name)))?;
+    let manifest: BackupManifest = serde_json::from_slice(&content)
+        .context("Failed to parse remote backup manifest")?;
+    check_manifest(&manifest)?;
+
+    let dir = backup_dir.join(name);
+    if !dir.join(MANIFEST_FILE).exists() {
+        let partial = backup_dir.join(format!(".{}.download", name));
+        if partial.exists() {
+            fs::remove_dir_all(&partial).context("Failed to remove incomplete download")?;
+        }
+        fs::create_dir_all(&partial).context("Failed to create backup directory")?;
+
+        let database = partial.join(&manifest.database.path);
+        if !store.download(&format!("{}/{}", prefix, manifest.database.path), &database)? {
+            return Err(Error::NotFound(format!("Remote backup {} has no database snapshot", name)));
+        }
+        if snapshot::file_entry(&database, manifest.database.path.clone())? != manifest.database {
+            return Err(Error::Corrupted(format!("Remote backup {} does not match its manifest checksum", name)));
+        }
+        fs::write(partial.join(MANIFEST_FILE), &content).context("Failed to write backup manifest")?;
+        fs::rename(&partial, &dir).context("Failed to move downloaded backup into place")?;
+    }
+
+    let mut restored = 0;
+    for attachment in &manifest.attachments {
+        let path = data_dir.join(&attachment.path);
+        if path.exists() {
+            continue;
+        }
+        let mut partial = path.clone().into_os_string();
+        partial.push(".download");
+        let partial = PathBuf::from(partial);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create attachments directory")?;
+        }
+        if !store.download(&format!("{}/{}", ATTACHMENTS_DIR, attachment.sha256), &partial)? {
+            tracing::warn!("Attachment {} of backup {} is missing from remote storage", attachment.path, name);
+            continue;
+        }
+        if snapshot::file_entry(&partial, attachment.path.clone()).ok().as_ref() != Some(attachment) {
+            tracing::warn!("Attachment {} of backup {} does not match its checksum, skipping it", attachment.path, name);
+            fs::remove_file(&partial).ok();
+            continue;
+        }
+        fs::rename(&partial, &path).with_context(|| format!("Failed to restore attachment {}", attachment.path))?;
+        restored += 1;
+    }
+
+    tracing::info!("Downloaded backup {} from {} ({} attachments restored)", name, store.location(), restored);
+    Ok(snapshot::to_info(name.to_string(), &dir, &manifest))
+}
+
+/// 清单中的路径来自远程存储，只允许备份目录中的单个文件和数据目录的 attachments 下的文件
+fn check_manifest(manifest: &BackupManifest) -> Result<()> {
+    let database_valid = split_path(&manifest.database.path).is_ok_and(|segments| segments.len() == 1)
+        && manifest.database.path != MANIFEST_FILE;
+    if !database_valid {
+        return Err(Error::InvalidInput(format!("Invalid database path in backup manifest: {}", manifest.database.path)));
+    }
+    for attachment in &manifest.attachments {
+        let valid = split_path(&attachment.path).is_ok_and(|segments| segments.len() > 1 && segments[0] == ATTACHMENTS_DIR)
+            && attachment.sha256.len() == 64
+            && attachment.sha256.bytes().all(|b| b.is_ascii_hexdigit());
+        if !valid {
+            return Err(Error::InvalidInput(format!("Invalid attachment in backup manifest: {}", attachment.path)));
+        }
+    }
+    Ok(())
+}
+
+fn scan(store: &dyn Store) -> Result<RemoteBackups> {
+    let mut remote = RemoteBackups { complete: Vec::new(), incomplete: Vec::new() };
+    for entry in store.entries(BACKUPS_DIR)? {
+        if !entry.is_dir || !entry.name.starts_with(NAME_PREFIX) {
+            continue;
+        }
+        let prefix = format!("{}/{}", BACKUPS_DIR, entry.name);
+        let manifest = store.read(&format!("{}/{}", prefix, MANIFEST_FILE))?
+            .map(|data| serde_json::from_slice::<BackupManifest>(&data));
+        match manifest {
+            Some(Ok(manifest)) => {
+                let info = BackupInfo {
+                    name: entry.name,
+                    path: format!("{}/{}", store.location().trim_end_matches('/'), prefix),
+                    created_at: manifest.created_at,
+                    reason: manifest.reason,
+                    schema_version: manifest.schema_version,
+                    database_size: manifest.database.size,
+                    attachment_count: manifest.attachments.len(),
+                };
+                remote.complete.push((info, manifest));
+            }
+            Some(Err(e)) => {
+                tracing::warn!("Ignoring remote backup {} with an invalid manifest: {}", entry.name, e);
+                remote.incomplete.push(entry.name);
+            }
+            None => remote.incomplete.push(entry.name),
+        }
+    }
+    remote.complete.sort_by_key(|(info, _)| std::cmp::Reverse(info.created_at));
+    Ok(remote)
+}
+
+/// 上传一份备份，返回上传的附件数
+fn upload(store: &dyn Store, backup_dir: &Path, data_dir: &Path, name: &str, blobs: &mut HashMap<String, u64>) -> Result<usize> {
+    let dir = snapshot::backup_path(backup_dir, name)?;
+    let manifest = snapshot::read_manifest(&dir)?;
+    let prefix = format!("{}/{}", BACKUPS_DIR, name);
+
+    // 上次中断前已经上传完的数据库不再上传（写入是原子的，大小一致即为完整）
+    let database = format!("{}/{}", prefix, manifest.database.path);
+    if store.stat(&database)?.map(|entry| entry.size) != Some(manifest.database.size) {
+        store.upload(&database, &dir.join(&manifest.database.path))?;
+    }
+
+    let mut uploaded = 0;
+    for attachment in &manifest.attachments {
+        if blobs.get(&attachment.sha256) == Some(&attachment.size) {
+            continue;
+        }
+        let path = data_dir.join(&attachment.path);
+        if snapshot::file_entry(&path, attachment.path.clone()).ok().as_ref() != Some(attachment) {
+            tracing::warn!("Attachment {} changed since backup {}, not uploading it", attachment.path, name);
+            continue;
+        }
+        store.upload(&format!("{}/{}", ATTACHMENTS_DIR, attachment.sha256), &path)?;
+        blobs.insert(attachment.sha256.clone(), attachment.size);
+        uploaded += 1;
+    }
+
+    let content = serde_json::to_vec_pretty(&manifest)?;
+    store.write(&format!("{}/{}", prefix, MANIFEST_FILE), &content)?;
+    tracing::info!("Uploaded backup {} to {}", name, store.location());
+    Ok(uploaded)
+}
+
+/// 按轮换策略删除远程的旧备份和不再被引用的附件，返回删除的备份
+fn prune(store: &dyn Store, policy: &RetentionPolicy, now: i64, local: &HashSet<&str>) -> Result<Vec<String>> {
+    let remote = scan(store)?;
+    let infos: Vec<BackupInfo> = remote.complete.iter().map(|(info, _)| info.clone()).collect();
+    let pruned = retention::backups_to_prune(&infos, policy, now);
+    for name in &pruned {
+        delete(store, name, true)?;
+    }
+
+    // 其他设备可能正在上传，附件只在没有进行中的上传时清理
+    let mut uploading = false;
+    for name in &remote.incomplete {
+        let stale = started_at(name).is_some_and(|t| now - t > STALE_UPLOAD_MS);
+        if stale && !local.contains(name.as_str()) {
+            delete(store, name, false)?;
+        } else {
+            uploading = true;
+        }
+    }
+    if uploading {
+        return Ok(pruned);
+    }
+
+    let referenced: HashSet<&str> = remote.complete.iter()
+        .filter(|(info, _)| !pruned.contains(&info.name))
+        .flat_map(|(_, manifest)| manifest.attachments.iter().map(|a| a.sha256.as_str()))
+        .collect();
+    for entry in store.entries(ATTACHMENTS_DIR)? {
+        if !entry.is_dir && !referenced.contains(entry.name.as_str()) {
+            store.delete(&format!("{}/{}", ATTACHMENTS_DIR, entry.name))?;
+        }
+    }
+    Ok(pruned)
+}
+
+/// 删除远程备份；先删除清单，删除中断时备份只会变为未完成
+fn delete(store: &dyn Store, name: &str, complete: bool) -> Result<()> {
+    let prefix = format!("{}/{}", BACKUPS_DIR, name);
+    if complete {
+        store.delete(&format!("{}/{}", prefix, MANIFEST_FILE))?;
+    }
+    for entry in store.entries(&prefix)? {
+        store.delete(&format!("{}/{}", prefix, entry.name))?;
+    }
+    store.delete(&prefix)?;
+    tracing::info!("Deleted remote backup {}", name);
+    Ok(())
+}
+
+/// 备份名称中的创建时间（本地时间，例如 `rtodo-20250101-093000-scheduled`）
+fn started_at(name: &str) -> Option<i64> {
+    let stamp = name.strip_prefix(NAME_PREFIX)?.get(..15)?;
+    let time = NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S").ok()?;
+    Local.from_local_datetime(&time).earliest().map(|t| t.timestamp_millis())
+}
@@ -0,0 +1,78 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 备份轮换（祖父-父-子策略）
+//! - 定时备份：保留最近 N 天每天、最近 N 周每周、最近 N 个月每月的最新一份
+//! - 安全备份（导入、清空、迁移、恢复前）：保留 `keep_daily` 天
+//! - 手动备份：不自动清理
+
+use chrono::{DateTime, Datelike, Local, TimeZone};
+use std::collections::HashSet;
+
+use crate::models::{BackupInfo, BackupReason};
+
+/// 轮换策略
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub keep_daily: u32,
+    pub keep_weekly: u32,
+    pub keep_monthly: u32,
+}
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// 返回需要删除的备份名称
+pub fn backups_to_prune(backups: &[BackupInfo], policy: &RetentionPolicy, now: i64) -> Vec<String> {
+    let mut scheduled: Vec<&BackupInfo> = backups.iter()
+        .filter(|b| b.reason == BackupReason::Scheduled)
+        .collect();
+    scheduled.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+
+    let mut keep: HashSet<&str> = HashSet::new();
+    // 始终保留最新的一份定时备份
+    if let Some(latest) = scheduled.first() {
+        keep.insert(&latest.name);
+    }
+    keep_newest_per_period(&scheduled, policy.keep_daily, &mut keep, |t| {
+        (t.year(), t.ordinal())
+    });
+    keep_newest_per_period(&scheduled, policy.keep_weekly, &mut keep, |t| {
+        let week = t.iso_week();
+        (week.year(), week.week())
+    });
+    keep_newest_per_period(&scheduled, policy.keep_monthly, &mut keep, |t| {
+        (t.year(), t.month())
+    });
+
+    let safety_cutoff = now - i64::from(policy.keep_daily.max(1)) * DAY_MS;
+
+    backups.iter()
+        .filter(|b| match b.reason {
+            BackupReason::Scheduled => !keep.contains(b.name.as_str()),
+            BackupReason::Manual => false,
+            _ => b.created_at < safety_cutoff,
+        })
+        .map(|b| b.name.clone())
+        .collect()
+}
+
+/// 按周期分组（`backups` 需按时间倒序），保留最近 `count` 个周期中各自最新的一份
+fn keep_newest_per_period<'a>(
+    backups: &[&'a BackupInfo],
+    count: u32,
+    keep: &mut HashSet<&'a str>,
+    period: impl Fn(DateTime<Local>) -> (i32, u32),
+) {
+    let mut seen = HashSet::new();
+    for backup in backups {
+        if seen.len() >= count as usize {
+            break;
+        }
+        let Some(time) = Local.timestamp_millis_opt(backup.created_at).single() else {
+            continue;
+        };
+        if seen.insert(period(time)) {
+            keep.insert(&backup.name);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 备份快照
+//! 每个备份是备份目录下的一个子目录：`rtodo.db`（SQLite 在线备份）和 `manifest.json`（附件清单）。
+//! 清单最后写入，没有清单的目录视为未完成的备份。
+
+use crate::error::{Context, Error, Result};
+use chrono::{Local, Utc};
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::database::migrations::{self, SCHEMA_VERSION};
+use crate::database::DbConnection;
+use crate::encryption::EncryptionKey;
+use crate::models::{BackupFileEntry, BackupInfo, BackupManifest, BackupReason, BackupVerifyResult};
+
+/// 备份目录中的数据库文件名
+pub const DATABASE_FILE: &str = "rtodo.db";
+pub const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_FORMAT: u32 = 1;
+pub(crate) const NAME_PREFIX: &str = "rtodo-";
+
+/// 为 `conn` 对应的数据库创建备份
+///
+/// 附件目录取数据库文件所在目录下的 `attachments`
+pub fn create_backup(conn: &Connection, backup_dir: &Path, reason: BackupReason) -> Result<BackupInfo> {
+    fs::create_dir_all(backup_dir)
+        .context("Failed to create backup directory")?;
+    let (name, dir) = new_backup_dir(backup_dir, reason)?;
+
+    let result = write_backup(conn, &dir, reason);
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&dir);
+    }
+    let manifest = result?;
+
+    tracing::info!("Created {} backup {}", reason.as_str(), name);
+    Ok(to_info(name, &dir, &manifest))
+}
+
+fn write_backup(conn: &Connection, dir: &Path, reason: BackupReason) -> Result<BackupManifest> {
+    let db_file = dir.join(DATABASE_FILE);
+    // 加密数据库的快照使用相同的密钥
+    DbConnection::copy_database(conn, &db_file)
+        .context("Failed to back up database")?;
+
+    let attachments = match data_dir_of(conn) {
+        Some(data_dir) => scan_attachments(&data_dir)?,
+        None => Vec::new(),
+    };
+
+    let manifest = BackupManifest {
+        format: MANIFEST_FORMAT,
+        created_at: Utc::now().timestamp_millis(),
+        reason,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: migrations::schema_version(conn)?,
+        database: file_entry(&db_file, DATABASE_FILE.to_string())?,
+        attachments,
+    };
+    let content = serde_json::to_string_pretty(&manifest)?;
+    fs::write(dir.join(MANIFEST_FILE), content)
+        .context("Failed to write backup manifest")?;
+
+    Ok(manifest)
+}
+
+/// 生成不重复的备份目录，例如 `rtodo-20250101-093000-scheduled`
+fn new_backup_dir(backup_dir: &Path, reason: BackupReason) -> Result<(String, PathBuf)> {
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    for attempt in 0..100 {
+        let name = match attempt {
+            0 => format!("{}{}-{}", NAME_PREFIX, stamp, reason.as_str()),
+            n => format!("{}{}-{}-{}", NAME_PREFIX, stamp, reason.as_str(), n),
+        };
+        let dir = backup_dir.join(&name);
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok((name, dir)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e).context("Failed to create backup directory"),
+        }
+    }
+    Err(Error::Other("Failed to choose a backup name".to_string()))
+}
+
+/// 列出备份目录中的所有备份（按时间倒序）
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>> {
+    if !backup_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups = Vec::new();
+    for entry in fs::read_dir(backup_dir).context("Failed to read backup directory")? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(NAME_PREFIX) || !entry.file_type()?.is_dir() {
+            continue;
+        }
+        match read_manifest(&entry.path()) {
+            Ok(manifest) => backups.push(to_info(name, &entry.path(), &manifest)),
+            Err(e) => tracing::debug!("Skipping incomplete backup {}: {}", name, e),
+        }
+    }
+
+    backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+    Ok(backups)
+}
+
+/// 获取备份目录，名称不合法或备份不存在时返回错误
+pub fn backup_path(backup_dir: &Path, name: &str) -> Result<PathBuf> {
+    check_name(name)?;
+    let path = backup_dir.join(name);
+    if !path.join(MANIFEST_FILE).exists() {
+        return Err(Error::NotFound(format!("Backup not found: {}", name)));
+    }
+    Ok(path)
+}
+
+/// 检查备份名称，防止访问备份目录以外的路径
+pub(crate) fn check_name(name: &str) -> Result<()> {
+    if !name.starts_with(NAME_PREFIX) || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(Error::InvalidInput(format!("Invalid backup name: {}", name)));
+    }
+    Ok(())
+}
+
+pub fn read_manifest(dir: &Path) -> Result<BackupManifest> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .context("Failed to read backup manifest")?;
+    serde_json::from_str(&content).context("Failed to parse backup manifest")
+}
+
+/// 校验备份：数据库文件的哈希和完整性、schema 版本，以及附件是否仍与清单一致
+///
+/// 加密数据库的备份需要用当前密钥打开
+pub fn verify_backup(backup_dir: &Path, name: &str, data_dir: &Path, key: Option<&EncryptionKey>) -> Result<BackupVerifyResult> {
+    let dir = backup_path(backup_dir, name)?;
+    let mut result = BackupVerifyResult {
+        name: name.to_string(),
+        ..Default::default()
+    };
+
+    let manifest = match read_manifest(&dir) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            result.errors.push(format!("{:#}", e));
+            return Ok(result);
+        }
+    };
+
+    let db_file = dir.join(&manifest.database.path);
+    match file_entry(&db_file, manifest.database.path.clone()) {
+        Ok(entry) if entry == manifest.database => {}
+        Ok(_) => result.errors.push("Database snapshot does not match the manifest checksum".to_string()),
+        Err(e) => result.errors.push(format!("{:#}", e)),
+    }
+    if result.errors.is_empty() {
+        if let Err(e) = check_integrity(&db_file, key) {
+            result.errors.push(format!("{:#}", e));
+        }
+    }
+    if manifest.schema_version > SCHEMA_VERSION {
+        result.errors.push(format!(
+            "Backup schema version {} is newer than this version of RTodo supports ({})",
+            manifest.schema_version, SCHEMA_VERSION
+        ));
+    }
+
+    // 附件不在备份中，只报告与当前文件的差异
+    for attachment in &manifest.attachments {
+        let path = data_dir.join(&attachment.path);
+        if !path.exists() {
+            result.missing_attachments.push(attachment.path.clone());
+        } else if file_entry(&path, attachment.path.clone()).ok().as_ref() != Some(attachment) {
+            result.changed_attachments.push(attachment.path.clone());
+        }
+    }
+
+    result.valid = result.errors.is_empty();
+    Ok(result)
+}
+
+/// 删除备份
+pub fn delete_backup(backup_dir: &Path, name: &str) -> Result<()> {
+    let dir = backup_path(backup_dir, name)?;
+    fs::remove_dir_all(&dir).context("Failed to delete backup")?;
+    tracing::info!("Deleted backup {}", name);
+    Ok(())
+}
+
+fn check_integrity(db_file: &Path, key: Option<&EncryptionKey>) -> Result<()> {
+    let conn = DbConnection::open_reader(db_file, key)
+        .context("Failed to open database snapshot")?;
+    let status: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))
+        .context("Failed to check database snapshot (it may be encrypted with a different key)")?;
+    if status != "ok" {
+        return Err(Error::Corrupted(format!("Database snapshot is corrupted: {}", status)));
+    }
+    Ok(())
+}
+
+pub(crate) fn to_info(name: String, dir: &Path, manifest: &BackupManifest) -> BackupInfo {
+    BackupInfo {
+        name,
+        path: dir.to_string_lossy().to_string(),
+        created_at: manifest.created_at,
+        reason: manifest.reason,
+        schema_version: manifest.schema_version,
+        database_size: manifest.database.size,
+        attachment_count: manifest.attachments.len(),
+    }
+}
+
+/// 数据库文件所在的数据目录（内存数据库返回 None）
+fn data_dir_of(conn: &Connection) -> Option<PathBuf> {
+    conn.path()
+        .filter(|p| !p.is_empty())
+        .and_then(|p| Path::new(p).parent().map(Path::to_path_buf))
+}
+
+/// 记录附件目录中的所有文件，路径相对于数据目录
+fn scan_attachments(data_dir: &Path) -> Result<Vec<BackupFileEntry>> {
+    let mut entries = Vec::new();
+    let attachments_dir = data_dir.join("attachments");
+    if attachments_dir.exists() {
+        scan_dir(data_dir, &attachments_dir, &mut entries)?;
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+fn scan_dir(data_dir: &Path, dir: &Path, entries: &mut Vec<BackupFileEntry>) -> Result<()> {
+    for entry in fs::read_dir(dir).context("Failed to read attachments directory")? {
+        let path = entry?.path();
+        if path.is_dir() {
+            scan_dir(data_dir, &path, entries)?;
+        } else {
+            let relative = path.strip_prefix(data_dir).unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            entries.push(file_entry(&path, relative)?);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn file_entry(path: &Path, relative: String) -> Result<BackupFileEntry> {
+    let mut file = fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", relative))?;
+    let mut hasher = Sha256::new();
+    let size = std::io::copy(&mut file, &mut hasher)
+        .with_context(|| format!("Failed to read {}", relative))?;
+
+    Ok(BackupFileEntry {
+        path: relative,
+        size,
+        sha256: hex::encode(hasher.finalize()),
+    })
+}
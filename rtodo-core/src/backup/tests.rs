@@ -0,0 +1,304 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::retention::{backups_to_prune, RetentionPolicy};
+use super::snapshot;
+use crate::database::Database;
+use crate::models::{BackupFileEntry, BackupInfo, BackupReason};
+use crate::test_support::temp_dir;
+use chrono::{Duration, Local, TimeZone};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const POLICY: RetentionPolicy = RetentionPolicy { keep_daily: 7, keep_weekly: 4, keep_monthly: 6 };
+
+fn info(name: &str, reason: BackupReason, created_at: i64) -> BackupInfo {
+    BackupInfo {
+        name: name.to_string(),
+        path: String::new(),
+        created_at,
+        reason,
+        schema_version: 0,
+        database_size: 0,
+        attachment_count: 0,
+    }
+}
+
+/// 从 `now` 往前每天 02:00 一份定时备份，共 `days` 份
+fn daily_backups(now: chrono::DateTime<Local>, days: i64) -> Vec<BackupInfo> {
+    (0..days)
+        .map(|d| {
+            let day = (now - Duration::days(d)).date_naive().and_hms_opt(2, 0, 0).unwrap();
+            let time = Local.from_local_datetime(&day).earliest().unwrap();
+            info(&format!("day-{}", d), BackupReason::Scheduled, time.timestamp_millis())
+        })
+        .collect()
+}
+
+#[test]
+fn grandfather_father_son_keeps_daily_weekly_and_monthly() {
+    let now = Local.with_ymd_and_hms(2025, 6, 30, 12, 0, 0).unwrap();
+    let backups = daily_backups(now, 400);
+
+    let pruned: HashSet<String> = backups_to_prune(&backups, &POLICY, now.timestamp_millis())
+        .into_iter()
+        .collect();
+    let kept: Vec<&BackupInfo> = backups.iter().filter(|b| !pruned.contains(&b.name)).collect();
+
+    // 最近 7 天全部保留
+    for d in 0..7 {
+        assert!(!pruned.contains(&format!("day-{}", d)), "day-{} pruned", d);
+    }
+    // 7 天 + 另外 3 周 + 另外 5 个月（本月和本周已计入）以内
+    assert!(kept.len() <= 7 + 4 + 6, "kept {}", kept.len());
+    assert!(kept.len() >= 6 + 3, "kept {}", kept.len());
+
+    // 每月保留一份，覆盖最近 6 个月
+    let months: HashSet<_> = kept.iter()
+        .map(|b| Local.timestamp_millis_opt(b.created_at).unwrap().format("%Y-%m").to_string())
+        .collect();
+    for month in ["2025-01", "2025-02", "2025-03", "2025-04", "2025-05", "2025-06"] {
+        assert!(months.contains(month), "missing {}", month);
+    }
+    assert!(!months.contains("2024-12"));
+}
+
+#[test]
+fn manual_backups_are_never_pruned() {
+    let now = Local.with_ymd_and_hms(2025, 6, 30, 12, 0, 0).unwrap();
+    let old = (now - Duration::days(1000)).timestamp_millis();
+    let backups = vec![info("manual", BackupReason::Manual, old)];
+
+    assert!(backups_to_prune(&backups, &POLICY, now.timestamp_millis()).is_empty());
+}
+
+#[test]
+fn safety_backups_expire_after_the_daily_window() {
+    let now = Local.with_ymd_and_hms(2025, 6, 30, 12, 0, 0).unwrap();
+    let backups = vec![
+        info("recent", BackupReason::PreImport, (now - Duration::days(2)).timestamp_millis()),
+        info("old", BackupReason::PreClear, (now - Duration::days(8)).timestamp_millis()),
+    ];
+
+    assert_eq!(backups_to_prune(&backups, &POLICY, now.timestamp_millis()), vec!["old".to_string()]);
+}
+
+#[test]
+fn latest_scheduled_backup_is_always_kept() {
+    let now = Local.with_ymd_and_hms(2025, 6, 30, 12, 0, 0).unwrap();
+    let policy = RetentionPolicy { keep_daily: 0, keep_weekly: 0, keep_monthly: 0 };
+    let backups = daily_backups(now, 3);
+
+    let pruned = backups_to_prune(&backups, &policy, now.timestamp_millis());
+    assert_eq!(pruned, vec!["day-1".to_string(), "day-2".to_string()]);
+}
+
+#[test]
+fn snapshot_records_attachments_and_verifies() {
+    let data_dir = temp_dir("backup");
+    let backup_dir = data_dir.join("backups");
+    fs::create_dir_all(data_dir.join("attachments")).unwrap();
+    fs::write(data_dir.join("attachments").join("a.txt"), "hello").unwrap();
+    fs::write(data_dir.join("attachments").join("b.txt"), "world").unwrap();
+
+    let conn = Connection::open(data_dir.join("rtodo.db")).unwrap();
+    crate::database::migrations::run_migrations(&conn, None).unwrap();
+    let backup = snapshot::create_backup(&conn, &backup_dir, BackupReason::Manual).unwrap();
+    assert_eq!(backup.attachment_count, 2);
+
+    let listed = snapshot::list_backups(&backup_dir).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].name, backup.name);
+
+    let result = snapshot::verify_backup(&backup_dir, &backup.name, &data_dir, None).unwrap();
+    assert!(result.valid, "{:?}", result.errors);
+    assert!(result.missing_attachments.is_empty() && result.changed_attachments.is_empty());
+
+    // 附件变化只作为提示，不影响恢复
+    fs::remove_file(data_dir.join("attachments").join("a.txt")).unwrap();
+    fs::write(data_dir.join("attachments").join("b.txt"), "changed").unwrap();
+    let result = snapshot::verify_backup(&backup_dir, &backup.name, &data_dir, None).unwrap();
+    assert!(result.valid);
+    assert_eq!(result.missing_attachments, vec!["attachments/a.txt".to_string()]);
+    assert_eq!(result.changed_attachments, vec!["attachments/b.txt".to_string()]);
+
+    // 数据库快照被修改后校验失败
+    let db_file = PathBuf::from(&backup.path).join(snapshot::DATABASE_FILE);
+    let mut bytes = fs::read(&db_file).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    fs::write(&db_file, bytes).unwrap();
+    let result = snapshot::verify_backup(&backup_dir, &backup.name, &data_dir, None).unwrap();
+    assert!(!result.valid);
+
+    snapshot::delete_backup(&backup_dir, &backup.name).unwrap();
+    assert!(snapshot::list_backups(&backup_dir).unwrap().is_empty());
+
+    drop(conn);
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn backup_names_are_validated() {
+    let dir = temp_dir("backup");
+    assert!(snapshot::backup_path(&dir, "../etc").is_err());
+    assert!(snapshot::backup_path(&dir, "rtodo-../../x").is_err());
+    assert!(snapshot::backup_path(&dir, "rtodo-missing").is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn restore_replaces_database_contents() {
+    let data_dir = temp_dir("backup");
+    let backup_dir = data_dir.join("backups");
+    let db = Database::open(&data_dir.join("rtodo.db"), None).unwrap();
+
+    let insert = |name: &'static str| {
+        let db = db.clone();
+        async move {
+            db.write(move |conn| {
+                conn.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES (?1, 0, 0)", [name])?;
+                Ok::<_, crate::Error>(())
+            }).await.unwrap();
+        }
+    };
+    let names = || {
+        let db = db.clone();
+        async move {
+            db.read(|conn| {
+                let mut stmt = conn.prepare("SELECT name FROM task_groups ORDER BY id")?;
+                let names = stmt.query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok::<_, crate::Error>(names)
+            }).await.unwrap()
+        }
+    };
+
+    insert("before").await;
+    let dir = backup_dir.clone();
+    let backup = db.write(move |conn| snapshot::create_backup(conn, &dir, BackupReason::Manual)).await.unwrap();
+    insert("after").await;
+    assert_eq!(names().await, vec!["before", "after"]);
+
+    db.restore_from(PathBuf::from(&backup.path).join(snapshot::DATABASE_FILE)).await.unwrap();
+    assert_eq!(names().await, vec!["before"]);
+
+    // 恢复后仍可正常写入
+    insert("again").await;
+    assert_eq!(names().await, vec!["before", "again"]);
+
+    drop(db);
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn remote_backups_upload_incrementally_and_resume() {
+    use super::remote;
+    use crate::storage::tests::{stand_in, webdav};
+    use crate::storage::Store;
+
+    let server = stand_in::Server::start();
+    let store = webdav(&server);
+    let data_dir = temp_dir("backup");
+    let backup_dir = data_dir.join("backups");
+    fs::create_dir_all(data_dir.join("attachments")).unwrap();
+    fs::write(data_dir.join("attachments").join("a.txt"), "hello").unwrap();
+    fs::write(data_dir.join("attachments").join("b.txt"), "world").unwrap();
+    let conn = Connection::open(data_dir.join("rtodo.db")).unwrap();
+    crate::database::migrations::run_migrations(&conn, None).unwrap();
+
+    // 两份备份共用附件，每个附件只上传一次
+    snapshot::create_backup(&conn, &backup_dir, BackupReason::Manual).unwrap();
+    snapshot::create_backup(&conn, &backup_dir, BackupReason::Manual).unwrap();
+    let report = remote::push(&store, &backup_dir, &data_dir, &POLICY).unwrap();
+    assert_eq!(report.uploaded.len(), 2);
+    assert_eq!(report.attachments_uploaded, 2);
+    assert_eq!(remote::list(&store).unwrap().len(), 2);
+
+    // 上传在数据库之后中断：继续时只上传剩下的附件和清单
+    fs::write(data_dir.join("attachments").join("c.txt"), "again").unwrap();
+    let third = snapshot::create_backup(&conn, &backup_dir, BackupReason::Manual).unwrap();
+    let prefix = format!("{}/{}", remote::BACKUPS_DIR, third.name);
+    let database = format!("{}/{}", prefix, snapshot::DATABASE_FILE);
+    store.upload(&database, &PathBuf::from(&third.path).join(snapshot::DATABASE_FILE)).unwrap();
+    assert_eq!(remote::list(&store).unwrap().len(), 2);
+
+    let before = server.puts().len();
+    let report = remote::push(&store, &backup_dir, &data_dir, &POLICY).unwrap();
+    assert_eq!(report.uploaded, vec![third.name.clone()]);
+    assert_eq!(report.attachments_uploaded, 1);
+    let puts = server.puts().split_off(before);
+    assert_eq!(puts.len(), 2, "{:?}", puts);
+    assert!(puts.iter().all(|path| !path.ends_with(snapshot::DATABASE_FILE)));
+    assert_eq!(remote::list(&store).unwrap().len(), 3);
+
+    // 过期的远程安全备份和只被它引用的附件被清理
+    let mut manifest = snapshot::read_manifest(Path::new(&third.path)).unwrap();
+    manifest.reason = BackupReason::PreImport;
+    manifest.created_at = 0;
+    manifest.attachments = vec![BackupFileEntry { path: "attachments/old.txt".into(), size: 3, sha256: "old".into() }];
+    store.write("attachments/old", b"old").unwrap();
+    store.write("backups/rtodo-20000101-000000-pre_import/rtodo.db", b"db").unwrap();
+    store.write("backups/rtodo-20000101-000000-pre_import/manifest.json", &serde_json::to_vec(&manifest).unwrap()).unwrap();
+    let report = remote::push(&store, &backup_dir, &data_dir, &POLICY).unwrap();
+    assert!(report.uploaded.is_empty());
+    assert_eq!(report.pruned, vec!["rtodo-20000101-000000-pre_import".to_string()]);
+    assert!(store.read("attachments/old").unwrap().is_none());
+    assert_eq!(store.list(remote::ATTACHMENTS_DIR).unwrap().len(), 3);
+    assert_eq!(store.list(remote::BACKUPS_DIR).unwrap().len(), 3);
+
+    drop(conn);
+    fs::remove_dir_all(&data_dir).unwrap();
+}
+
+#[test]
+fn remote_backups_can_be_restored_from_s3() {
+    use super::remote;
+    use crate::storage::tests::{s3, s3_stand_in};
+    use crate::storage::Store;
+
+    let server = s3_stand_in::Server::start();
+    let store = s3(&server, "team/rtodo");
+    let data_dir = temp_dir("backup");
+    let backup_dir = data_dir.join("backups");
+    fs::create_dir_all(data_dir.join("attachments").join("todo-1")).unwrap();
+    fs::write(data_dir.join("attachments").join("todo-1").join("a.txt"), "hello").unwrap();
+    let conn = Connection::open(data_dir.join("rtodo.db")).unwrap();
+    crate::database::migrations::run_migrations(&conn, None).unwrap();
+    let backup = snapshot::create_backup(&conn, &backup_dir, BackupReason::Manual).unwrap();
+    remote::push(&store, &backup_dir, &data_dir, &POLICY).unwrap();
+    assert_eq!(remote::list(&store).unwrap().len(), 1);
+
+    // 在另一台设备上：下载备份并取回附件，之后可以按本地备份校验
+    let other = temp_dir("backup");
+    let other_backups = other.join("backups");
+    let info = remote::download(&store, &backup.name, &other_backups, &other).unwrap();
+    assert_eq!(info.name, backup.name);
+    assert_eq!(snapshot::list_backups(&other_backups).unwrap().len(), 1);
+    assert_eq!(fs::read_to_string(other.join("attachments").join("todo-1").join("a.txt")).unwrap(), "hello");
+    let result = snapshot::verify_backup(&other_backups, &backup.name, &other, None).unwrap();
+    assert!(result.valid, "{:?}", result.errors);
+    assert!(result.missing_attachments.is_empty());
+
+    // 再次下载只补回缺失的附件
+    fs::remove_dir_all(other.join("attachments")).unwrap();
+    remote::download(&store, &backup.name, &other_backups, &other).unwrap();
+    assert!(other.join("attachments").join("todo-1").join("a.txt").exists());
+
+    assert!(matches!(remote::download(&store, "rtodo-20000101-000000-manual", &other_backups, &other), Err(crate::Error::NotFound(_))));
+    assert!(remote::download(&store, "../escape", &other_backups, &other).is_err());
+
+    // 清单中指向数据目录以外的路径被拒绝
+    let mut manifest = snapshot::read_manifest(Path::new(&backup.path)).unwrap();
+    manifest.attachments[0].path = "attachments/../../escape.txt".to_string();
+    let evil = "rtodo-20000101-000000-manual";
+    store.write(&format!("backups/{}/manifest.json", evil), &serde_json::to_vec(&manifest).unwrap()).unwrap();
+    assert!(matches!(remote::download(&store, evil, &other_backups, &other), Err(crate::Error::InvalidInput(_))));
+    assert!(!other.join("escape.txt").exists());
+
+    drop(conn);
+    fs::remove_dir_all(&data_dir).unwrap();
+    fs::remove_dir_all(&other).unwrap();
+}
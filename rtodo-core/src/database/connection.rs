@@ -0,0 +1,101 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use rusqlite::{Connection, OpenFlags};
+use crate::error::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::encryption::EncryptionKey;
+
+/// 等待其他连接释放锁的最长时间
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 数据库连接管理
+pub struct DbConnection;
+
+impl DbConnection {
+    /// 打开写连接（唯一的写连接，负责迁移和所有修改操作）
+    pub fn open_writer(db_path: &Path, key: Option<&EncryptionKey>) -> Result<Connection> {
+        if let Some(parent) = db_path.parent() {
+            // 确保目录存在
+            std::fs::create_dir_all(parent)
+                .context("Failed to create data directory")?;
+        }
+
+        tracing::info!("Database path: {:?}", db_path);
+
+        // 打开数据库连接
+        let conn = Connection::open(db_path)
+            .context("Failed to open database")?;
+        if let Some(key) = key {
+            key.apply_to(&conn)?;
+        }
+
+        // 设置性能优化参数
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.pragma_update(None, "cache_size", -64_000)?; // 64MB cache
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.pragma_update(None, "temp_store", "MEMORY")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        Ok(conn)
+    }
+
+    /// 打开只读连接（WAL 模式下可与写连接并发读取）
+    pub fn open_reader(db_path: &Path, key: Option<&EncryptionKey>) -> Result<Connection> {
+        let conn = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+        ).context("Failed to open read connection")?;
+        if let Some(key) = key {
+            key.apply_to(&conn)?;
+        }
+
+        conn.pragma_update(None, "cache_size", -16_000)?; // 16MB cache
+        conn.pragma_update(None, "temp_store", "MEMORY")?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        Ok(conn)
+    }
+
+    /// 把数据库的一致性快照复制到新文件（普通日志模式，加密数据库使用相同的密钥）
+    pub fn copy_database(conn: &Connection, target: &Path) -> Result<()> {
+        #[cfg(feature = "encryption")]
+        {
+            // SQLCipher 的在线备份不能跨密钥，附加的数据库不指定 KEY 时沿用主库的密钥
+            let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+            conn.execute("ATTACH DATABASE ?1 AS snapshot", [target.to_string_lossy()])?;
+            let exported = conn.execute_batch(&format!(
+                "SELECT sqlcipher_export('snapshot'); PRAGMA snapshot.user_version = {};",
+                version
+            ));
+            conn.execute_batch("DETACH DATABASE snapshot")?;
+            exported.context("Failed to copy database")?;
+        }
+        #[cfg(not(feature = "encryption"))]
+        {
+            conn.backup(rusqlite::DatabaseName::Main, target, None)
+                .context("Failed to copy database")?;
+            // 快照使用普通日志模式，成为不依赖 -wal/-shm 文件的单个文件
+            Connection::open(target)?.pragma_update(None, "journal_mode", "DELETE")?;
+        }
+        Ok(())
+    }
+
+    /// 获取数据库文件路径
+    pub fn get_db_path() -> Result<PathBuf> {
+        Ok(Self::get_data_dir()?.join("rtodo.db"))
+    }
+
+    /// 获取应用数据目录（优先使用配置的数据路径）
+    pub fn get_data_dir() -> Result<PathBuf> {
+        super::paths::data_dir()
+    }
+
+    /// 获取附件存储目录
+    pub fn get_attachments_dir() -> Result<PathBuf> {
+        Ok(Self::get_data_dir()?.join("attachments"))
+    }
+}
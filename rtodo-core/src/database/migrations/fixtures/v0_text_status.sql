@@ -0,0 +1,80 @@
+-- Integer primary keys, but `todos.status` still stored as text ('todo', 'in_progress', 'done').
+-- No user_version (0).
+
+CREATE TABLE task_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    parent_id INTEGER,
+    icon TEXT,
+    color TEXT,
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX idx_task_groups_parent ON task_groups(parent_id);
+
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT NOT NULL DEFAULT '#409EFF',
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE todos (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    description TEXT,
+    status TEXT NOT NULL DEFAULT 'todo',
+    priority INTEGER DEFAULT 0,
+    group_id INTEGER,
+    assignee TEXT,
+    start_date INTEGER,
+    due_date INTEGER,
+    completed_at INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX idx_todos_group ON todos(group_id);
+CREATE INDEX idx_todos_status ON todos(status);
+CREATE INDEX idx_todos_due_date ON todos(due_date);
+
+CREATE TABLE todo_tags (
+    todo_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (todo_id, tag_id)
+);
+CREATE INDEX idx_todo_tags_todo ON todo_tags(todo_id);
+CREATE INDEX idx_todo_tags_tag ON todo_tags(tag_id);
+
+CREATE TABLE todo_steps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    todo_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    is_completed INTEGER DEFAULT 0,
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX idx_todo_steps_todo ON todo_steps(todo_id);
+
+CREATE TABLE attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    todo_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    file_size INTEGER,
+    mime_type TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX idx_attachments_todo ON attachments(todo_id);
+
+INSERT INTO task_groups VALUES (1, 'Work', NULL, NULL, '#409EFF', 10, 1700000000000, 1700000000000);
+
+INSERT INTO tags VALUES (1, 'home', '#67C23A', 1700000000000);
+
+INSERT INTO todos VALUES (1, 'Plan sprint', NULL, 'todo', 1, 1, NULL, NULL, NULL, NULL, 1700000000000, 1700000000000);
+INSERT INTO todos VALUES (2, 'Fix login bug', 'Crash on empty password', 'in_progress', 3, 1, NULL, NULL, NULL, NULL, 1700000001000, 1700000001000);
+INSERT INTO todos VALUES (3, 'Clean kitchen', NULL, 'DONE', 0, NULL, NULL, NULL, NULL, 1700000009000, 1700000002000, 1700000009000);
+
+INSERT INTO todo_tags VALUES (3, 1);
+
+INSERT INTO todo_steps VALUES (1, 2, 'Reproduce', 1, 10, 1700000003000);
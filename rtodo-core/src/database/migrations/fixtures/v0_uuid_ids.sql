@@ -0,0 +1,75 @@
+-- Earliest releases: UUID (TEXT) primary keys and the `is_marked` flag on todos.
+-- No user_version (0).
+
+CREATE TABLE task_groups (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    parent_id TEXT,
+    icon TEXT,
+    color TEXT,
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE tags (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT NOT NULL DEFAULT '#409EFF',
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE todos (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    description TEXT,
+    status INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER NOT NULL DEFAULT 0,
+    is_marked INTEGER NOT NULL DEFAULT 0,
+    group_id TEXT,
+    assignee TEXT,
+    start_date INTEGER,
+    due_date INTEGER,
+    completed_at INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE todo_tags (
+    todo_id TEXT NOT NULL,
+    tag_id TEXT NOT NULL,
+    PRIMARY KEY (todo_id, tag_id)
+);
+
+CREATE TABLE todo_steps (
+    id TEXT PRIMARY KEY,
+    todo_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    is_completed INTEGER DEFAULT 0,
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE attachments (
+    id TEXT PRIMARY KEY,
+    todo_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    file_size INTEGER NOT NULL,
+    mime_type TEXT,
+    created_at INTEGER NOT NULL
+);
+
+INSERT INTO task_groups VALUES ('0b6f7c1e-0000-4000-8000-000000000001', 'Inbox', NULL, 'folder', '#409EFF', 10, 1700000000000, 1700000000000);
+
+INSERT INTO tags VALUES ('5d1c2a3b-0000-4000-8000-000000000001', 'urgent', '#F56C6C', 1700000000000);
+
+INSERT INTO todos VALUES ('9a8b7c6d-0000-4000-8000-000000000001', 'Write report', 'Quarterly numbers', 0, 2, 1, NULL, NULL, NULL, 1700500000000, NULL, 1700000000000, 1700000000000);
+INSERT INTO todos VALUES ('9a8b7c6d-0000-4000-8000-000000000002', 'Book flights', NULL, 2, 0, 0, NULL, 'me', NULL, NULL, 1700100000000, 1700000001000, 1700100000000);
+
+INSERT INTO todo_tags VALUES ('9a8b7c6d-0000-4000-8000-000000000001', '5d1c2a3b-0000-4000-8000-000000000001');
+
+INSERT INTO todo_steps VALUES ('1e2d3c4b-0000-4000-8000-000000000001', '9a8b7c6d-0000-4000-8000-000000000001', 'Collect data', 1, 10, 1700000002000);
+INSERT INTO todo_steps VALUES ('1e2d3c4b-0000-4000-8000-000000000002', '9a8b7c6d-0000-4000-8000-000000000001', 'Draft', 0, 20, 1700000003000);
+
+INSERT INTO attachments VALUES ('7f6e5d4c-0000-4000-8000-000000000001', '9a8b7c6d-0000-4000-8000-000000000002', 'ticket.pdf', 'attachments/ticket.pdf', 1024, 'application/pdf', 1700000004000);
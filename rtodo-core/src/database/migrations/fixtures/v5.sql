@@ -0,0 +1,216 @@
+-- Schema version 5: folder sync bookkeeping; some entities have already been synced.
+
+PRAGMA user_version = 5;
+
+CREATE TABLE task_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    parent_id INTEGER,
+    icon TEXT,
+    color TEXT,
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (parent_id) REFERENCES task_groups(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_task_groups_parent ON task_groups(parent_id);
+
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT NOT NULL DEFAULT '#409EFF',
+    created_at INTEGER NOT NULL,
+    parent_id INTEGER
+);
+CREATE UNIQUE INDEX idx_tags_name_nocase ON tags(name COLLATE NOCASE);
+CREATE INDEX idx_tags_parent ON tags(parent_id);
+
+CREATE TABLE todos (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    description TEXT,
+    status INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER DEFAULT 0,
+    group_id INTEGER,
+    assignee TEXT,
+    start_date INTEGER,
+    due_date INTEGER,
+    completed_at INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES task_groups(id) ON DELETE SET NULL
+);
+CREATE INDEX idx_todos_group ON todos(group_id);
+CREATE INDEX idx_todos_status ON todos(status);
+CREATE INDEX idx_todos_due_date ON todos(due_date);
+
+CREATE TABLE todo_tags (
+    todo_id INTEGER NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (todo_id, tag_id),
+    FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_todo_tags_todo ON todo_tags(todo_id);
+CREATE INDEX idx_todo_tags_tag ON todo_tags(tag_id);
+
+CREATE TABLE todo_steps (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    todo_id INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    is_completed INTEGER DEFAULT 0,
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_todo_steps_todo ON todo_steps(todo_id);
+
+CREATE TABLE attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    todo_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    file_size INTEGER,
+    mime_type TEXT,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
+);
+CREATE INDEX idx_attachments_todo ON attachments(todo_id);
+
+CREATE TABLE export_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    version TEXT NOT NULL,
+    exported_at INTEGER NOT NULL,
+    file_path TEXT NOT NULL
+);
+CREATE TABLE rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    trigger_config TEXT NOT NULL,
+    conditions TEXT NOT NULL DEFAULT '[]',
+    actions TEXT NOT NULL DEFAULT '[]',
+    sort_order INTEGER DEFAULT 0,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE rule_executions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id INTEGER NOT NULL,
+    todo_id INTEGER,
+    trigger_type TEXT NOT NULL,
+    event_key TEXT,
+    success INTEGER NOT NULL,
+    message TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX idx_rule_executions_rule_todo ON rule_executions(rule_id, todo_id);
+
+CREATE TABLE webhooks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT,
+    events TEXT NOT NULL DEFAULT '[]',
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE webhook_deliveries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    webhook_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    response_status INTEGER,
+    created_at INTEGER NOT NULL,
+    delivered_at INTEGER
+);
+CREATE INDEX idx_webhook_deliveries_pending ON webhook_deliveries(status, next_attempt_at);
+
+CREATE TABLE sync_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+CREATE TABLE sync_entities (
+    entity TEXT NOT NULL,
+    uid TEXT NOT NULL,
+    local_id INTEGER,
+    fields TEXT NOT NULL DEFAULT '{}',
+    versions TEXT NOT NULL DEFAULT '{}',
+    deleted_at INTEGER,
+    alias_of TEXT,
+    PRIMARY KEY (entity, uid)
+);
+CREATE UNIQUE INDEX idx_sync_entities_local ON sync_entities(entity, local_id) WHERE local_id IS NOT NULL;
+
+CREATE TABLE sync_cursors (
+    device_id TEXT PRIMARY KEY,
+    segment INTEGER NOT NULL
+);
+
+CREATE TABLE sync_pending (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    record TEXT NOT NULL
+);
+
+CREATE TABLE sync_outbox (
+    segment INTEGER PRIMARY KEY,
+    body TEXT NOT NULL
+);
+
+CREATE TABLE sync_conflicts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity TEXT NOT NULL,
+    uid TEXT NOT NULL,
+    local_id INTEGER,
+    field TEXT NOT NULL,
+    local_value TEXT,
+    remote_value TEXT,
+    resolution TEXT NOT NULL,
+    remote_device TEXT NOT NULL,
+    detected_at INTEGER NOT NULL
+);
+
+INSERT INTO task_groups VALUES (1, 'Work', NULL, 'briefcase', '#409EFF', 10, 1700000000000, 1700000000000);
+INSERT INTO task_groups VALUES (2, 'Clients', 1, NULL, NULL, 10, 1700000000000, 1700000000000);
+
+INSERT INTO tags VALUES (1, 'Urgent', '#F56C6C', 1700000000000, NULL);
+INSERT INTO tags VALUES (3, 'home', '#67C23A', 1700000002000, NULL);
+INSERT INTO tags VALUES (4, 'garden', '#67C23A', 1700000003000, 3);
+
+INSERT INTO todos VALUES (1, 'Send invoice', NULL, 0, 2, 2, NULL, NULL, 1700500000000, NULL, 1700000000000, 1700000000000);
+INSERT INTO todos VALUES (2, 'Review contract', 'Section 4', 1, 1, 2, 'alice', 1700000000000, NULL, NULL, 1700000001000, 1700000001000);
+INSERT INTO todos VALUES (3, 'Water plants', NULL, 2, 0, NULL, NULL, NULL, NULL, 1700000009000, 1700000002000, 1700000009000);
+
+INSERT INTO todo_tags VALUES (1, 1);
+INSERT INTO todo_tags VALUES (2, 1);
+INSERT INTO todo_tags VALUES (3, 4);
+
+INSERT INTO todo_steps VALUES (1, 2, 'Read draft', 1, 10, 1700000003000);
+INSERT INTO todo_steps VALUES (2, 2, 'Send comments', 0, 20, 1700000004000);
+
+INSERT INTO attachments VALUES (1, 2, 'contract.pdf', 'attachments/3f2a.pdf', 2048, 'application/pdf', 1700000005000);
+
+INSERT INTO rules VALUES (1, 'Urgent is high priority', 1, '{"type":"tag_added","tag_id":1}', '[]', '[{"type":"set_field","field":"priority","value":3}]', 10, 1700000007000, 1700000007000);
+
+INSERT INTO rule_executions VALUES (1, 1, 1, 'tag_added', NULL, 1, 'set priority = 3', 1700000008000);
+
+INSERT INTO webhooks VALUES (1, 'Chat', 'https://chat.example.com/hook', NULL, '["todo.completed"]', 1, 1700000010000, 1700000010000);
+
+INSERT INTO webhook_deliveries VALUES (1, 1, 'todo.completed', '{"event":"todo.completed"}', 'delivered', 1, 1700000011000, NULL, 200, 1700000011000, 1700000011000);
+
+INSERT INTO sync_state VALUES ('device_id', '0b6f3c1e-5d0a-4c61-9a57-2f1f0f6f7d10');
+
+INSERT INTO sync_entities VALUES ('group', '7c9e6679-7425-40de-944b-e07fc1f90ae7', 1, '{}', '{}', NULL, NULL);
+INSERT INTO sync_entities VALUES ('tag', 'a3bb189e-8bf9-3888-9912-ace4e6543002', 1, '{}', '{}', NULL, NULL);
+INSERT INTO sync_entities VALUES ('tag', 'b8f2d0a4-1c3e-4d5f-8a7b-9c0d1e2f3a4b', NULL, '{}', '{}', NULL, 'a3bb189e-8bf9-3888-9912-ace4e6543002');
+INSERT INTO sync_entities VALUES ('todo', 'e4d909c2-90d0-4fb1-8f3a-0b4bcf9d5c5e', 2, '{}', '{}', NULL, NULL);
+INSERT INTO sync_entities VALUES ('todo', '16fd2706-8baf-433b-82eb-8c7fada847da', NULL, '{}', '{}', 1700000012000, NULL);
+INSERT INTO sync_entities VALUES ('step', '886313e1-3b8a-5372-9b90-0c9aee199e5d', 1, '{}', '{}', NULL, NULL);
@@ -0,0 +1,139 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Versioned schema migrations
+//!
+//! The schema version is stored in `PRAGMA user_version`. Each migration runs in its own
+//! transaction together with the version bump, so a failed step leaves the database at the
+//! previous version. Databases created before versioning report version 0 and are brought up
+//! to date by the baseline migration.
+//!
+//! To change the schema, append a new step to [`MIGRATIONS`] (never edit a released one) and
+//! add a fixture for the previous version under `fixtures/`.
+
+mod legacy;
+mod v001_baseline;
+mod v002_tag_hierarchy;
+mod v003_rules;
+mod v004_webhooks;
+mod v005_sync;
+mod v006_uids;
+
+#[cfg(test)]
+mod tests;
+
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+use crate::error::{Context, Error, Result};
+use std::path::Path;
+
+use crate::backup::snapshot;
+use crate::models::BackupReason;
+
+/// A single schema migration
+pub struct Migration {
+    /// Version the database is at after this migration
+    pub version: u32,
+    pub name: &'static str,
+    up: fn(&Connection) -> Result<()>,
+}
+
+/// All migrations, in order; `version` must match the position (starting at 1)
+pub const MIGRATIONS: &[Migration] = &[
+    Migration { version: 1, name: "baseline", up: v001_baseline::up },
+    Migration { version: 2, name: "tag_hierarchy", up: v002_tag_hierarchy::up },
+    Migration { version: 3, name: "rules", up: v003_rules::up },
+    Migration { version: 4, name: "webhooks", up: v004_webhooks::up },
+    Migration { version: 5, name: "sync", up: v005_sync::up },
+    Migration { version: 6, name: "uids", up: v006_uids::up },
+];
+
+/// Schema version this build of the app expects
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// Read the schema version from `PRAGMA user_version`
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("Failed to read schema version")?;
+    Ok(version as u32)
+}
+
+/// Run all pending database migrations
+///
+/// When `backup_dir` is given and the database already contains data, a pre-migration backup is
+/// created there before the first migration is applied. Databases written by a newer app are refused.
+pub fn run_migrations(conn: &Connection, backup_dir: Option<&Path>) -> Result<()> {
+    run(conn, MIGRATIONS, backup_dir)
+}
+
+fn run(conn: &Connection, migrations: &[Migration], backup_dir: Option<&Path>) -> Result<()> {
+    let latest = migrations.last().map_or(0, |m| m.version);
+    let current = schema_version(conn)?;
+
+    if current > latest {
+        return Err(Error::SchemaTooNew { found: current, supported: latest });
+    }
+    if current == latest {
+        return Ok(());
+    }
+
+    if let Some(dir) = backup_dir {
+        if has_tables(conn)? {
+            let backup = snapshot::create_backup(conn, dir, BackupReason::PreMigration)
+                .context("Failed to back up database before migration")?;
+            tracing::info!("Backed up database at schema version {} to {}", current, backup.path);
+        }
+    }
+
+    // Table rebuilds in the migrations must not trigger cascades; foreign_keys can only be
+    // changed outside a transaction
+    let foreign_keys: bool = conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+    conn.pragma_update(None, "foreign_keys", false)?;
+    let result = apply_pending(conn, migrations, current);
+    conn.pragma_update(None, "foreign_keys", foreign_keys)?;
+    result
+}
+
+fn apply_pending(conn: &Connection, migrations: &[Migration], current: u32) -> Result<()> {
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        tracing::info!("Applying migration {} ({})...", migration.version, migration.name);
+
+        // Take the write lock up front and re-check the version: another process sharing the
+        // database (e.g. the CLI next to the app) may have applied this step meanwhile
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        if schema_version(&tx)? >= migration.version {
+            continue;
+        }
+        (migration.up)(&tx)
+            .with_context(|| format!("Migration {} ({}) failed", migration.version, migration.name))?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+    }
+
+    tracing::info!("Database schema is at version {}", schema_version(conn)?);
+    Ok(())
+}
+
+/// Whether the database contains any tables (a brand-new database needs no backup)
+fn has_tables(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Helper function to check if a column exists in a table
+fn column_exists(conn: &Connection, table_name: &str, column_name: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table_name))?;
+    let columns = stmt.query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(columns.iter().any(|c| c == column_name))
+}
+
+/// Helper function to check if a table exists
+fn table_exists(conn: &Connection, table_name: &str) -> Result<bool> {
+    let sql = "SELECT name FROM sqlite_master WHERE type='table' AND name = ?";
+    let exists: Option<String> = conn.query_row(sql, [table_name], |row| row.get(0)).ok();
+    Ok(exists.is_some())
+}
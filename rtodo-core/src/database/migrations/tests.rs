@@ -0,0 +1,274 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::test_support::temp_dir;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Fixtures for every historical schema, oldest first
+const FIXTURES: &[(&str, &str)] = &[
+    ("v0_uuid_ids", include_str!("fixtures/v0_uuid_ids.sql")),
+    ("v0_text_status", include_str!("fixtures/v0_text_status.sql")),
+    ("v0_1_4", include_str!("fixtures/v0_1_4.sql")),
+    ("v1", include_str!("fixtures/v1.sql")),
+    ("v2", include_str!("fixtures/v2.sql")),
+    ("v3", include_str!("fixtures/v3.sql")),
+    ("v4", include_str!("fixtures/v4.sql")),
+    ("v5", include_str!("fixtures/v5.sql")),
+];
+
+fn fixture(name: &str) -> Connection {
+    let (_, sql) = FIXTURES.iter()
+        .find(|(n, _)| *n == name)
+        .unwrap_or_else(|| panic!("Unknown fixture {}", name));
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(sql).unwrap();
+    conn
+}
+
+fn migrated_fresh() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    run_migrations(&conn, None).unwrap();
+    conn
+}
+
+/// Table name -> sorted column names, plus the names of all explicit indexes
+fn schema_shape(conn: &Connection) -> (BTreeMap<String, Vec<String>>, Vec<String>) {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name"
+    ).unwrap();
+    let tables: Vec<String> = stmt.query_map([], |row| row.get(0)).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+
+    let mut shape = BTreeMap::new();
+    for table in tables {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).unwrap();
+        let mut columns: Vec<String> = stmt.query_map([], |row| row.get(1)).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        columns.sort();
+        shape.insert(table, columns);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'index' AND sql IS NOT NULL ORDER BY name"
+    ).unwrap();
+    let indexes = stmt.query_map([], |row| row.get(0)).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+
+    (shape, indexes)
+}
+
+fn count(conn: &Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn migration_versions_are_sequential() {
+    for (idx, migration) in MIGRATIONS.iter().enumerate() {
+        assert_eq!(migration.version as usize, idx + 1, "migration {}", migration.name);
+    }
+}
+
+#[test]
+fn fresh_database_reaches_latest_version() {
+    let conn = migrated_fresh();
+    assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+    let (shape, _) = schema_shape(&conn);
+    for table in ["task_groups", "tags", "todos", "todo_tags", "todo_steps", "attachments", "rules", "webhooks", "sync_entities"] {
+        assert!(shape.contains_key(table), "missing table {}", table);
+    }
+    assert!(shape["tags"].contains(&"parent_id".to_string()));
+}
+
+#[test]
+fn every_fixture_upgrades_to_the_fresh_schema() {
+    let expected = schema_shape(&migrated_fresh());
+
+    for (name, _) in FIXTURES {
+        let conn = fixture(name);
+        run_migrations(&conn, None).unwrap_or_else(|e| panic!("{}: {:#}", name, e));
+
+        assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION, "{}", name);
+        assert_eq!(schema_shape(&conn), expected, "{}", name);
+    }
+}
+
+#[test]
+fn running_twice_is_a_no_op() {
+    let conn = fixture("v0_1_4");
+    run_migrations(&conn, None).unwrap();
+    let before = schema_shape(&conn);
+    let todos = count(&conn, "SELECT COUNT(*) FROM todos");
+
+    run_migrations(&conn, None).unwrap();
+    assert_eq!(schema_shape(&conn), before);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM todos"), todos);
+}
+
+#[test]
+fn uuid_ids_are_converted_to_integers() {
+    let conn = fixture("v0_uuid_ids");
+    run_migrations(&conn, None).unwrap();
+
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM todos"), 2);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM todos WHERE typeof(id) != 'integer'"), 0);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM task_groups WHERE typeof(id) != 'integer'"), 0);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM tags"), 1);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM todo_tags"), 1);
+
+    // Steps and attachments follow their todo to its new id
+    let steps_todo: String = conn.query_row(
+        "SELECT t.title FROM todo_steps s JOIN todos t ON t.id = s.todo_id GROUP BY t.id",
+        [],
+        |row| row.get(0),
+    ).unwrap();
+    assert_eq!(steps_todo, "Write report");
+    let attachment_todo: String = conn.query_row(
+        "SELECT t.title FROM attachments a JOIN todos t ON t.id = a.todo_id",
+        [],
+        |row| row.get(0),
+    ).unwrap();
+    assert_eq!(attachment_todo, "Book flights");
+}
+
+#[test]
+fn text_statuses_are_converted_to_integers() {
+    let conn = fixture("v0_text_status");
+    run_migrations(&conn, None).unwrap();
+
+    let mut stmt = conn.prepare("SELECT id, status FROM todos ORDER BY id").unwrap();
+    let statuses: Vec<(i64, i64)> = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap()
+        .collect::<Result<_, _>>().unwrap();
+    assert_eq!(statuses, vec![(1, 0), (2, 1), (3, 2)]);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM todo_steps WHERE todo_id = 2"), 1);
+}
+
+#[test]
+fn case_duplicate_tags_are_merged() {
+    for name in ["v0_1_4", "v1"] {
+        let conn = fixture(name);
+        run_migrations(&conn, None).unwrap();
+
+        let names: Vec<String> = conn.prepare("SELECT name FROM tags ORDER BY id").unwrap()
+            .query_map([], |row| row.get(0)).unwrap()
+            .collect::<Result<_, _>>().unwrap();
+        assert_eq!(names, vec!["Urgent", "home"], "{}", name);
+
+        // Todo 1 had both spellings, todo 2 only the lowercase one
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM todo_tags WHERE tag_id = 1"), 2, "{}", name);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM todo_tags WHERE tag_id = 2"), 0, "{}", name);
+    }
+}
+
+#[test]
+fn data_survives_later_migrations() {
+    let conn = fixture("v4");
+    run_migrations(&conn, None).unwrap();
+
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM todos"), 3);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM rules"), 1);
+    assert_eq!(count(&conn, "SELECT parent_id FROM tags WHERE name = 'garden'"), 3);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM webhooks"), 1);
+    assert_eq!(count(&conn, "SELECT COUNT(*) FROM sync_entities"), 0);
+}
+
+#[test]
+fn every_entity_gets_a_uid() {
+    for (name, _) in FIXTURES {
+        let conn = fixture(name);
+        run_migrations(&conn, None).unwrap();
+        for table in ["task_groups", "tags", "todos", "todo_steps", "attachments"] {
+            let missing = count(&conn, &format!("SELECT COUNT(*) FROM {} WHERE uid IS NULL", table));
+            assert_eq!(missing, 0, "{}: {}", name, table);
+            let distinct = count(&conn, &format!("SELECT COUNT(DISTINCT uid) FROM {}", table));
+            assert_eq!(distinct, count(&conn, &format!("SELECT COUNT(*) FROM {}", table)), "{}: {}", name, table);
+        }
+    }
+}
+
+#[test]
+fn synced_entities_keep_their_sync_uid() {
+    let conn = fixture("v5");
+    run_migrations(&conn, None).unwrap();
+
+    let uid = |sql: &str| -> String { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+    assert_eq!(uid("SELECT uid FROM task_groups WHERE id = 1"), "7c9e6679-7425-40de-944b-e07fc1f90ae7");
+    assert_eq!(uid("SELECT uid FROM tags WHERE id = 1"), "a3bb189e-8bf9-3888-9912-ace4e6543002");
+    assert_eq!(uid("SELECT uid FROM todos WHERE id = 2"), "e4d909c2-90d0-4fb1-8f3a-0b4bcf9d5c5e");
+    assert_eq!(uid("SELECT uid FROM todo_steps WHERE id = 1"), "886313e1-3b8a-5372-9b90-0c9aee199e5d");
+    // Not yet synced: a fresh uid, never one of a deleted entity or an alias
+    let todo = uid("SELECT uid FROM todos WHERE id = 1");
+    assert_eq!(count(&conn, &format!("SELECT COUNT(*) FROM sync_entities WHERE uid = '{}'", todo)), 0);
+}
+
+#[test]
+fn newer_database_is_refused() {
+    let conn = fixture("v3");
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION + 1).unwrap();
+
+    let err = run_migrations(&conn, None).unwrap_err();
+    assert!(err.to_string().contains("newer"), "{}", err);
+    assert_eq!(schema_version(&conn).unwrap(), SCHEMA_VERSION + 1);
+}
+
+#[test]
+fn failed_migration_is_rolled_back() {
+    fn create_marker(conn: &Connection) -> Result<()> {
+        conn.execute("CREATE TABLE marker (id INTEGER)", [])?;
+        Ok(())
+    }
+    fn fail_after_change(conn: &Connection) -> Result<()> {
+        conn.execute("CREATE TABLE half_done (id INTEGER)", [])?;
+        Err(crate::Error::Other("boom".to_string()))
+    }
+    let migrations = [
+        Migration { version: 1, name: "marker", up: create_marker },
+        Migration { version: 2, name: "broken", up: fail_after_change },
+    ];
+
+    let conn = Connection::open_in_memory().unwrap();
+    assert!(run(&conn, &migrations, None).is_err());
+
+    assert_eq!(schema_version(&conn).unwrap(), 1);
+    assert!(table_exists(&conn, "marker").unwrap());
+    assert!(!table_exists(&conn, "half_done").unwrap());
+}
+
+#[test]
+fn database_is_backed_up_before_upgrading() {
+    let dir = temp_dir("migrations");
+    let db_path = dir.join("rtodo.db");
+    let backup_dir = dir.join("backups");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(FIXTURES.iter().find(|(n, _)| *n == "v2").unwrap().1).unwrap();
+    run_migrations(&conn, Some(&backup_dir)).unwrap();
+
+    let backups = snapshot::list_backups(&backup_dir).unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].reason, BackupReason::PreMigration);
+    assert_eq!(backups[0].schema_version, 2);
+
+    let backup = Connection::open(PathBuf::from(&backups[0].path).join(snapshot::DATABASE_FILE)).unwrap();
+    assert_eq!(schema_version(&backup).unwrap(), 2);
+    assert_eq!(count(&backup, "SELECT COUNT(*) FROM todos"), 3);
+
+    drop(conn);
+    drop(backup);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn new_database_is_not_backed_up() {
+    let dir = temp_dir("migrations");
+    let backup_dir = dir.join("backups");
+
+    let conn = Connection::open(dir.join("rtodo.db")).unwrap();
+    run_migrations(&conn, Some(&backup_dir)).unwrap();
+    assert!(!backup_dir.exists());
+
+    drop(conn);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,11 +1,23 @@
 // Copyright 2025 RTodo Team. All rights reserved.
 // SPDX-License-Identifier: MIT
 
+//! Version 1: the schema as of RTodo 0.1.4
+//!
+//! Databases created before schema versioning are converted from their legacy layout first;
+//! the `IF NOT EXISTS` statements then only create what is missing.
+
 use rusqlite::Connection;
-use anyhow::Result;
+use crate::error::Result;
+
+use super::legacy;
+
+pub(super) fn up(conn: &Connection) -> Result<()> {
+    // Check if we need to migrate from UUID (TEXT) to auto-increment IDs (INTEGER)
+    legacy::migrate_uuid_to_autoincrement(conn)?;
+
+    // Check if we need to migrate status from TEXT to INTEGER
+    legacy::migrate_status_to_int(conn)?;
 
-/// 初始化数据库表结构
-pub fn init_database(conn: &Connection) -> Result<()> {
     // 任务组表
     conn.execute(
         "CREATE TABLE IF NOT EXISTS task_groups (
@@ -147,6 +159,5 @@ pub fn init_database(conn: &Connection) -> Result<()> {
         [],
     )?;
 
-    tracing::info!("Database initialized successfully");
     Ok(())
 }
@@ -0,0 +1,57 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Version 2: add `tags.parent_id` and enforce case-insensitive unique tag names
+//!
+//! Existing tags that differ only by case are merged into the oldest one first.
+
+use rusqlite::Connection;
+use crate::error::Result;
+
+use super::column_exists;
+
+pub(super) fn up(conn: &Connection) -> Result<()> {
+    // Development builds before schema versioning may already have added the column
+    if !column_exists(conn, "tags", "parent_id")? {
+        conn.execute("ALTER TABLE tags ADD COLUMN parent_id INTEGER", [])?;
+    }
+
+    // Merge tags whose names only differ by case
+    let duplicates: Vec<(i64, i64)> = {
+        let mut stmt = conn.prepare(
+            "SELECT d.id, k.keep_id
+             FROM tags d
+             JOIN (SELECT MIN(id) AS keep_id, name AS keep_name FROM tags
+                   GROUP BY name COLLATE NOCASE HAVING COUNT(*) > 1) k
+               ON d.name = k.keep_name COLLATE NOCASE AND d.id != k.keep_id"
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows
+    };
+
+    if !duplicates.is_empty() {
+        tracing::info!("Merging {} case-duplicate tags...", duplicates.len());
+    }
+    for (duplicate_id, keep_id) in duplicates {
+        conn.execute(
+            "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id)
+             SELECT todo_id, ?1 FROM todo_tags WHERE tag_id = ?2",
+            [keep_id, duplicate_id],
+        )?;
+        conn.execute("DELETE FROM todo_tags WHERE tag_id = ?", [duplicate_id])?;
+        conn.execute("UPDATE tags SET parent_id = ?1 WHERE parent_id = ?2", [keep_id, duplicate_id])?;
+        conn.execute("DELETE FROM tags WHERE id = ?", [duplicate_id])?;
+    }
+
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_tags_name_nocase ON tags(name COLLATE NOCASE)",
+        [],
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_tags_parent ON tags(parent_id)",
+        [],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Version 3: automation rules and their execution log
+
+use rusqlite::Connection;
+use crate::error::Result;
+
+pub(super) fn up(conn: &Connection) -> Result<()> {
+    // 自动化规则表（触发器、条件和动作以 JSON 存储）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            trigger_config TEXT NOT NULL,
+            conditions TEXT NOT NULL DEFAULT '[]',
+            actions TEXT NOT NULL DEFAULT '[]',
+            sort_order INTEGER DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // 规则执行日志表
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rule_executions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule_id INTEGER NOT NULL,
+            todo_id INTEGER,
+            trigger_type TEXT NOT NULL,
+            event_key TEXT,
+            success INTEGER NOT NULL,
+            message TEXT,
+            created_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // 创建索引
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_rule_executions_rule_todo ON rule_executions(rule_id, todo_id)",
+        [],
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Version 5: bookkeeping for folder sync between devices
+
+use rusqlite::Connection;
+use crate::error::Result;
+
+pub(super) fn up(conn: &Connection) -> Result<()> {
+    // 同步状态（设备 id、逻辑时钟、同步目标等键值）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 已同步实体：全局 uid 与本地 id 的映射，以及上次同步时的字段值和字段版本（JSON）
+    // local_id 为空表示已删除（deleted_at）或是另一实体的别名（alias_of）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_entities (
+            entity TEXT NOT NULL,
+            uid TEXT NOT NULL,
+            local_id INTEGER,
+            fields TEXT NOT NULL DEFAULT '{}',
+            versions TEXT NOT NULL DEFAULT '{}',
+            deleted_at INTEGER,
+            alias_of TEXT,
+            PRIMARY KEY (entity, uid)
+        )",
+        [],
+    )?;
+
+    // 每个远程设备已读取到的日志段
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_cursors (
+            device_id TEXT PRIMARY KEY,
+            segment INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // 引用的实体尚未到达、暂缓应用的远程记录
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_pending (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            record TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 已生成但尚未写入同步目录的日志段
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_outbox (
+            segment INTEGER PRIMARY KEY,
+            body TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 冲突记录（同一字段在两台设备上被并发修改）
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            entity TEXT NOT NULL,
+            uid TEXT NOT NULL,
+            local_id INTEGER,
+            field TEXT NOT NULL,
+            local_value TEXT,
+            remote_value TEXT,
+            resolution TEXT NOT NULL,
+            remote_device TEXT NOT NULL,
+            detected_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    // 创建索引
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_sync_entities_local
+         ON sync_entities(entity, local_id) WHERE local_id IS NOT NULL",
+        [],
+    )?;
+
+    Ok(())
+}
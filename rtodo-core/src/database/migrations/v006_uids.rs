@@ -0,0 +1,68 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Version 6: stable global identifiers (`uid`) for groups, tags, todos, steps and attachments
+//!
+//! Integer ids stay the local keys; the uid identifies the same entity across devices, exports and
+//! imports. Entities that have already been synced keep the uid sync assigned to them. The
+//! repositories generate the uid on insert; a trigger fills it in for inserts that leave it out.
+
+use rusqlite::{params, Connection};
+use crate::error::Result;
+
+use super::column_exists;
+
+/// A random (version 4) UUID in its usual text form, evaluated per row
+const NEW_UUID_SQL: &str = "lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' \
+    || substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + abs(random() % 4), 1) \
+    || substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)))";
+
+/// Tables that get a uid, with the entity name sync uses for them
+const TABLES: &[(&str, Option<&str>)] = &[
+    ("task_groups", Some("group")),
+    ("tags", Some("tag")),
+    ("todos", Some("todo")),
+    ("todo_steps", Some("step")),
+    ("attachments", None),
+];
+
+pub(super) fn up(conn: &Connection) -> Result<()> {
+    for (table, entity) in TABLES {
+        if !column_exists(conn, table, "uid")? {
+            conn.execute(&format!("ALTER TABLE {} ADD COLUMN uid TEXT", table), [])?;
+        }
+
+        if let Some(entity) = entity {
+            conn.execute(
+                &format!(
+                    "UPDATE {table} SET uid = (
+                        SELECT uid FROM sync_entities WHERE entity = ?1 AND local_id = {table}.id
+                     )
+                     WHERE uid IS NULL",
+                    table = table,
+                ),
+                params![entity],
+            )?;
+        }
+
+        conn.execute(&format!("UPDATE {} SET uid = {} WHERE uid IS NULL", table, NEW_UUID_SQL), [])?;
+
+        conn.execute(
+            &format!("CREATE UNIQUE INDEX IF NOT EXISTS idx_{table}_uid ON {table}(uid)", table = table),
+            [],
+        )?;
+        conn.execute(
+            &format!(
+                "CREATE TRIGGER IF NOT EXISTS {table}_default_uid AFTER INSERT ON {table}
+                 WHEN NEW.uid IS NULL
+                 BEGIN
+                    UPDATE {table} SET uid = {uuid} WHERE id = NEW.id;
+                 END",
+                table = table,
+                uuid = NEW_UUID_SQL,
+            ),
+            [],
+        )?;
+    }
+    Ok(())
+}
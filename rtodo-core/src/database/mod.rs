@@ -0,0 +1,195 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+pub mod connection;
+pub mod repositories;
+pub mod migrations;
+pub mod paths;
+pub mod workspaces;
+mod pool;
+
+#[cfg(test)]
+mod tests;
+
+pub use connection::DbConnection;
+use pool::ReadPool;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::encryption::EncryptionKey;
+use crate::error::{Context, Error, Result};
+
+/// 只读连接池大小
+const READ_POOL_SIZE: usize = 4;
+
+/// 数据库管理器
+///
+/// 一个写连接加一组 WAL 只读连接；查询在阻塞线程池中执行，不占用异步运行时。
+/// 连接可通过 [`Database::reopen`] 整体切换到另一个数据库文件，所有克隆共享切换结果。
+/// 加密数据库在解锁前处于锁定状态，此时所有查询返回 [`Error::Locked`]。
+#[derive(Clone)]
+pub struct Database {
+    connections: Arc<RwLock<Option<Connections>>>,
+}
+
+#[derive(Clone)]
+struct Connections {
+    writer: Arc<Mutex<Connection>>,
+    readers: ReadPool,
+    key: Option<Arc<EncryptionKey>>,
+}
+
+impl Database {
+    /// 打开默认位置的数据库
+    pub fn new(backup_dir: Option<&Path>) -> Result<Self> {
+        Self::open(&DbConnection::get_db_path()?, backup_dir)
+    }
+
+    /// 打开指定路径的数据库并执行迁移
+    ///
+    /// 给出 `backup_dir` 时，升级 schema 前会在其中创建迁移前备份
+    pub fn open(db_path: &Path, backup_dir: Option<&Path>) -> Result<Self> {
+        Self::open_with_key(db_path, backup_dir, None)
+    }
+
+    /// 用密钥打开加密数据库并执行迁移
+    pub fn open_with_key(db_path: &Path, backup_dir: Option<&Path>, key: Option<Arc<EncryptionKey>>) -> Result<Self> {
+        let writer = DbConnection::open_writer(db_path, key.as_deref())?;
+
+        // 按版本号执行数据库迁移
+        migrations::run_migrations(&writer, backup_dir)?;
+
+        let readers = ReadPool::open(db_path, READ_POOL_SIZE, key.as_deref())?;
+
+        Ok(Self {
+            connections: Arc::new(RwLock::new(Some(Connections {
+                writer: Arc::new(Mutex::new(writer)),
+                readers,
+                key,
+            }))),
+        })
+    }
+
+    /// 创建处于锁定状态的数据库，解锁后通过 [`Database::reopen`] 打开
+    pub fn locked() -> Self {
+        Self {
+            connections: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// 是否处于锁定状态
+    pub fn is_locked(&self) -> bool {
+        self.connections().is_err()
+    }
+
+    /// 当前数据库的加密密钥（未加密或锁定时为 None）
+    pub fn key(&self) -> Option<Arc<EncryptionKey>> {
+        self.connections().ok().and_then(|c| c.key)
+    }
+
+    fn connections(&self) -> Result<Connections> {
+        self.connections.read().unwrap_or_else(|e| e.into_inner()).clone()
+            .ok_or(Error::Locked)
+    }
+
+    fn set_connections(&self, connections: Option<Connections>) {
+        *self.connections.write().unwrap_or_else(|e| e.into_inner()) = connections;
+    }
+
+    /// 获取当前写连接的锁；等待期间数据库被切换时改为锁新的写连接
+    async fn lock_writer(&self) -> Result<OwnedMutexGuard<Connection>> {
+        loop {
+            let writer = self.connections()?.writer;
+            let guard = writer.clone().lock_owned().await;
+            if self.connections().is_ok_and(|c| Arc::ptr_eq(&writer, &c.writer)) {
+                return Ok(guard);
+            }
+        }
+    }
+
+    /// 关闭当前连接并打开另一个数据库文件（执行迁移），用于切换数据目录或解锁
+    ///
+    /// 新数据库打开失败时保持原连接不变；切换前等待进行中的写操作完成
+    pub async fn reopen(&self, db_path: PathBuf, backup_dir: Option<PathBuf>, key: Option<Arc<EncryptionKey>>) -> Result<()> {
+        let opened = tokio::task::spawn_blocking(move || Self::open_with_key(&db_path, backup_dir.as_deref(), key))
+            .await??;
+
+        // 锁定状态下没有写连接需要等待
+        let _write_guard = self.lock_writer().await.ok();
+        self.set_connections(opened.connections().ok());
+        Ok(())
+    }
+
+    /// 在写连接上执行最后一个操作，成功后关闭全部连接进入锁定状态
+    ///
+    /// 用于需要替换数据库文件的操作（例如加密转换）；返回时数据库文件已不再被占用。
+    /// `f` 失败时数据库保持打开
+    pub async fn close_with<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let conn = self.lock_writer().await?;
+        let (mut conn, result) = tokio::task::spawn_blocking(move || {
+            let result = f(&conn);
+            (conn, result)
+        })
+        .await?;
+
+        if result.is_ok() {
+            let closed = self.connections.write().unwrap_or_else(|e| e.into_inner()).take();
+            if let Some(closed) = closed {
+                closed.readers.close().await;
+            }
+            // 等待写锁的任务仍持有写连接的引用，换成内存连接以立即关闭数据库文件
+            let placeholder = Connection::open_in_memory()?;
+            drop(std::mem::replace(&mut *conn, placeholder));
+        }
+        result
+    }
+
+    /// 在只读连接上执行查询
+    pub async fn read<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Connection) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<Error> + Send + 'static,
+    {
+        let conn = self.connections()?.readers.get().await?;
+        tokio::task::spawn_blocking(move || f(&conn))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// 在写连接上执行修改（写操作串行执行）
+    pub async fn write<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        F: FnOnce(&Connection) -> Result<T, E> + Send + 'static,
+        T: Send + 'static,
+        E: From<Error> + Send + 'static,
+    {
+        let conn = self.lock_writer().await?;
+        tokio::task::spawn_blocking(move || f(&conn))
+            .await
+            .map_err(Error::from)?
+    }
+
+    /// 用快照文件替换当前数据库的全部内容，并将其迁移到当前 schema 版本
+    ///
+    /// 快照须与当前数据库使用相同的密钥
+    pub async fn restore_from(&self, snapshot: PathBuf) -> Result<()> {
+        let key = self.key();
+        let mut conn = self.lock_writer().await?;
+        tokio::task::spawn_blocking(move || {
+            let source = DbConnection::open_reader(&snapshot, key.as_deref())?;
+            rusqlite::backup::Backup::new(&source, &mut conn)?
+                .run_to_completion(256, std::time::Duration::ZERO, None)
+                .context("Failed to restore database")?;
+            conn.pragma_update(None, "journal_mode", "WAL")?;
+            migrations::run_migrations(&conn, None)
+        })
+        .await?
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 数据目录解析
+//! 数据库、附件和默认备份目录都从这里取数据目录：配置了 `data_path` 时使用该目录，否则使用平台默认目录。
+
+use crate::error::{Context, Error, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// 当前生效的自定义数据目录
+static CUSTOM_DATA_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// 获取当前生效的数据目录
+pub fn data_dir() -> Result<PathBuf> {
+    let custom = CUSTOM_DATA_DIR.read().unwrap_or_else(|e| e.into_inner()).clone();
+    match custom {
+        Some(dir) => Ok(dir),
+        None => default_data_dir(),
+    }
+}
+
+/// 切换当前生效的数据目录（`None` 表示使用默认目录）
+pub fn set_data_dir(dir: Option<PathBuf>) {
+    *CUSTOM_DATA_DIR.write().unwrap_or_else(|e| e.into_inner()) = dir;
+}
+
+/// 获取平台默认数据目录
+pub fn default_data_dir() -> Result<PathBuf> {
+    let data_dir = if cfg!(target_os = "windows") {
+        // Windows: %APPDATA%\rtodo
+        dirs::config_dir()
+            .map(|p| p.join("rtodo"))
+            .ok_or_else(|| Error::NotFound("Failed to get config directory".to_string()))?
+    } else if cfg!(target_os = "macos") {
+        // macOS: ~/Library/Application Support/rtodo
+        dirs::config_dir()
+            .map(|p| p.join("rtodo"))
+            .ok_or_else(|| Error::NotFound("Failed to get config directory".to_string()))?
+    } else {
+        // Linux: ~/.local/share/rtodo
+        dirs::data_local_dir()
+            .map(|p| p.join("rtodo"))
+            .ok_or_else(|| Error::NotFound("Failed to get data directory".to_string()))?
+    };
+
+    Ok(data_dir)
+}
+
+/// 检查目录可用：不存在时创建，并确认可写
+pub fn validate_data_dir(dir: &Path) -> Result<()> {
+    if !dir.is_absolute() {
+        return Err(Error::InvalidInput(format!("Data path must be absolute: {}", dir.display())));
+    }
+    fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+
+    let test_file = dir.join(".write_test");
+    fs::write(&test_file, b"test")
+        .with_context(|| format!("Path not writable: {}", dir.display()))?;
+    fs::remove_file(&test_file)
+        .context("Failed to cleanup test file")?;
+    Ok(())
+}
+
+/// 检查已配置的目录可用：必须已经存在且可写
+///
+/// 不创建目录，避免在未挂载的磁盘路径下建出空目录
+pub fn ensure_available(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        return Err(Error::NotFound(format!("Directory does not exist: {}", dir.display())));
+    }
+    validate_data_dir(dir)
+}
+
+/// 启动时根据配置确定数据目录
+///
+/// 配置的目录不可用（例如外部磁盘未挂载）时回退到默认目录，并返回回退原因；配置本身保持不变
+pub fn init_from_config(configured: Option<&str>) -> Option<String> {
+    let Some(configured) = configured.filter(|p| !p.is_empty()) else {
+        set_data_dir(None);
+        return None;
+    };
+
+    let dir = PathBuf::from(configured);
+    match ensure_available(&dir) {
+        Ok(()) => {
+            tracing::info!("Using custom data path: {}", dir.display());
+            set_data_dir(Some(dir));
+            None
+        }
+        Err(e) => {
+            tracing::warn!("Configured data path is unavailable, falling back to default: {:#}", e);
+            set_data_dir(None);
+            Some(format!("{:#}", e))
+        }
+    }
+}
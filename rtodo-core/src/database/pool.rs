@@ -0,0 +1,86 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 只读连接池
+//! 固定数量的只读连接，借出时占用一个信号量许可，归还时放回空闲列表
+
+use rusqlite::Connection;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::DbConnection;
+use crate::encryption::EncryptionKey;
+use crate::error::{Error, Result};
+
+#[derive(Clone)]
+pub(super) struct ReadPool {
+    idle: Arc<Mutex<Vec<Connection>>>,
+    permits: Arc<Semaphore>,
+    size: usize,
+}
+
+impl ReadPool {
+    /// 打开 `size` 个只读连接
+    pub(super) fn open(db_path: &Path, size: usize, key: Option<&EncryptionKey>) -> Result<Self> {
+        let connections = (0..size)
+            .map(|_| DbConnection::open_reader(db_path, key))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            idle: Arc::new(Mutex::new(connections)),
+            permits: Arc::new(Semaphore::new(size)),
+            size,
+        })
+    }
+
+    /// 借出一个连接，池中没有空闲连接时等待
+    pub(super) async fn get(&self) -> Result<PooledConnection> {
+        let permit = self.permits.clone().acquire_owned().await
+            .map_err(|_| Error::PoolUnavailable)?;
+        let conn = self.idle.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .pop()
+            .ok_or(Error::PoolUnavailable)?;
+
+        Ok(PooledConnection {
+            conn: Some(conn),
+            idle: self.idle.clone(),
+            _permit: permit,
+        })
+    }
+
+    /// 等待借出的连接全部归还后关闭所有连接，之后借用返回 [`Error::PoolUnavailable`]
+    pub(super) async fn close(&self) {
+        if let Ok(permits) = self.permits.acquire_many(self.size as u32).await {
+            permits.forget();
+        }
+        self.permits.close();
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// 借出的连接，drop 时自动归还
+pub(super) struct PooledConnection {
+    conn: Option<Connection>,
+    idle: Arc<Mutex<Vec<Connection>>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("connection already returned")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            // 许可在此之后才释放，下一个借用者一定能取到连接
+            self.idle.lock().unwrap_or_else(|e| e.into_inner()).push(conn);
+        }
+    }
+}
@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: MIT
 
 use rusqlite::{Connection, params, OptionalExtension};
-use anyhow::{Result, Context};
+use crate::error::{Context, Error, Result};
 use chrono::Utc;
 
 use crate::models::Attachment;
+use crate::utils::new_uuid;
 
 /// Attachment 仓库
 pub struct AttachmentRepository;
@@ -14,7 +15,7 @@ impl AttachmentRepository {
     /// 获取所有附件
     pub fn list_all(conn: &Connection) -> Result<Vec<Attachment>> {
         let mut stmt = conn.prepare(
-            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at
+            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at, uid
              FROM attachments
              ORDER BY todo_id ASC, created_at DESC"
         )
@@ -23,6 +24,7 @@ impl AttachmentRepository {
         let attachments = stmt.query_map([], |row| {
             Ok(Attachment {
                 id: row.get(0)?,
+                uid: row.get(7)?,
                 todo_id: row.get(1)?,
                 name: row.get(2)?,
                 file_path: row.get(3)?,
@@ -41,7 +43,7 @@ impl AttachmentRepository {
     /// 获取任务的所有附件
     pub fn list_by_todo(conn: &Connection, todo_id: i64) -> Result<Vec<Attachment>> {
         let mut stmt = conn.prepare(
-            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at
+            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at, uid
              FROM attachments
              WHERE todo_id = ?
              ORDER BY created_at DESC"
@@ -51,6 +53,7 @@ impl AttachmentRepository {
         let attachments = stmt.query_map(params![todo_id], |row| {
             Ok(Attachment {
                 id: row.get(0)?,
+                uid: row.get(7)?,
                 todo_id: row.get(1)?,
                 name: row.get(2)?,
                 file_path: row.get(3)?,
@@ -69,12 +72,13 @@ impl AttachmentRepository {
     /// 根据 ID 获取单个附件
     pub fn get(conn: &Connection, id: i64) -> Result<Option<Attachment>> {
         let attachment = conn.query_row(
-            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at
+            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at, uid
              FROM attachments WHERE id = ?",
             params![id],
             |row| {
                 Ok(Attachment {
                     id: row.get(0)?,
+                    uid: row.get(7)?,
                     todo_id: row.get(1)?,
                     name: row.get(2)?,
                     file_path: row.get(3)?,
@@ -100,11 +104,12 @@ impl AttachmentRepository {
         mime_type: Option<&str>,
     ) -> Result<Attachment> {
         let now = Utc::now().timestamp_millis();
+        let uid = new_uuid();
 
         conn.execute(
-            "INSERT INTO attachments (todo_id, name, file_path, file_size, mime_type, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![todo_id, name, file_path, file_size, mime_type, now],
+            "INSERT INTO attachments (uid, todo_id, name, file_path, file_size, mime_type, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![uid, todo_id, name, file_path, file_size, mime_type, now],
         )
         .context("Failed to insert attachment")?;
 
@@ -113,6 +118,7 @@ impl AttachmentRepository {
 
         Ok(Attachment {
             id,
+            uid,
             todo_id,
             name: name.to_string(),
             file_path: file_path.to_string(),
@@ -131,9 +137,21 @@ impl AttachmentRepository {
         .context("Failed to delete attachment")?;
 
         if rows_affected == 0 {
-            anyhow::bail!("Attachment not found");
+            return Err(Error::NotFound("Attachment not found".to_string()));
         }
 
         Ok(())
     }
+
+    /// 统计引用指定文件的附件记录数（复制任务时附件文件可能被共享）
+    pub fn count_by_file_path(conn: &Connection, file_path: &str) -> Result<i64> {
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM attachments WHERE file_path = ?",
+            params![file_path],
+            |row| row.get(0),
+        )
+        .context("Failed to count attachments by file path")?;
+
+        Ok(count)
+    }
 }
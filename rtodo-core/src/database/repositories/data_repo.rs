@@ -0,0 +1,170 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 数据管理仓库
+//! 处理数据的导入、导出和清理操作
+
+use rusqlite::Connection;
+use std::path::Path;
+use crate::encryption::EncryptionKey;
+use crate::error::{Context, Result};
+use crate::import;
+use crate::models::{ExportData, ImportOptions, ImportReport, ImportSource, Todo};
+use crate::database::repositories::{AttachmentRepository, GroupRepository, StepRepository, TagRepository, TodoRepository};
+use crate::utils::data_export::{
+    export_groups_to_csv,
+    export_tags_to_csv,
+    export_todos_to_csv,
+    export_todo_tags_to_csv,
+    export_steps_to_csv,
+    export_attachments_to_csv,
+    create_zip_archive_with_attachments,
+    csv_to_export_data,
+    ZipCsvData,
+};
+use crate::utils::ical;
+
+/// 数据管理仓库
+pub struct DataRepository;
+
+impl DataRepository {
+    /// 导出所有数据
+    pub fn export_all(conn: &Connection) -> Result<ExportData> {
+        // 导出任务组
+        let task_groups = GroupRepository::list(conn)?;
+
+        // 导出标签
+        let tags = TagRepository::list(conn)?;
+
+        // 导出所有任务（不过滤）
+        let todos = TodoRepository::list(
+            conn,
+            None,  // group_id
+            None,  // tag_id
+            None,  // status
+            None,  // search
+            None,  // priority
+            None,  // start_date
+            None,  // end_date
+        )?;
+
+        let exported_at = chrono::Utc::now().timestamp_millis();
+
+        Ok(ExportData {
+            version: "1.0".to_string(),
+            exported_at,
+            task_groups,
+            tags,
+            todos,
+        })
+    }
+
+    /// 导出所有数据为 CSV 压缩包（包含步骤、附件表和附件文件）
+    ///
+    /// `attachments_dir` 不存在时只导出表格；加密的附件用 `key` 解密后写入压缩包
+    pub fn export_archive(conn: &Connection, attachments_dir: &Path, key: Option<&EncryptionKey>) -> Result<Vec<u8>> {
+        let export_data = Self::export_all(conn)?;
+        let steps = StepRepository::list_all(conn)?;
+        let attachments = AttachmentRepository::list_all(conn)?;
+        tracing::info!("Exported {} steps and {} attachments", steps.len(), attachments.len());
+
+        let attachments_path = if attachments_dir.exists() {
+            Some(attachments_dir)
+        } else {
+            tracing::info!("Attachments directory does not exist: {}", attachments_dir.display());
+            None
+        };
+
+        create_zip_archive_with_attachments(
+            export_groups_to_csv(&export_data.task_groups)?,
+            export_tags_to_csv(&export_data.tags)?,
+            export_todos_to_csv(&export_data.todos)?,
+            export_todo_tags_to_csv(&export_data.todos)?,
+            export_steps_to_csv(&steps)?,
+            export_attachments_to_csv(&attachments)?,
+            attachments_path,
+            key,
+        )
+        .context("Failed to create ZIP")
+    }
+
+    /// 预览导入结果，不写入任何数据
+    pub fn preview_import(conn: &Connection, data: &ExportData, options: ImportOptions) -> Result<ImportReport> {
+        import::preview(conn, data, options)
+    }
+
+    /// 导入数据（在事务中执行）
+    ///
+    /// JSON 导出文件不包含附件文件，忽略其中的附件记录
+    pub fn import_data(conn: &Connection, data: &ExportData, options: ImportOptions) -> Result<ImportReport> {
+        import::import(conn, data, options, false)
+    }
+
+    /// 预览从 CSV 压缩包导入的结果，不写入任何数据
+    pub fn preview_csv_import(conn: &Connection, csv_data: &ZipCsvData, options: ImportOptions) -> Result<ImportReport> {
+        import::preview(conn, &csv_to_export_data(csv_data)?, options)
+    }
+
+    /// 从 CSV 数据导入所有数据（在事务中执行），附件文件由调用方从压缩包中解压
+    pub fn import_from_csv(conn: &Connection, csv_data: &ZipCsvData, options: ImportOptions) -> Result<ImportReport> {
+        import::import(conn, &csv_to_export_data(csv_data)?, options, true)
+    }
+
+    /// 预览从其他应用的导出文件导入的结果，不写入任何数据
+    pub fn preview_external_import(
+        conn: &Connection,
+        source: ImportSource,
+        name: &str,
+        content: &str,
+        options: ImportOptions,
+    ) -> Result<ImportReport> {
+        import::preview_external(conn, source, name, content, options)
+    }
+
+    /// 从其他应用的导出文件导入（在事务中执行），`name` 为文件名（不含扩展名）
+    pub fn import_external(
+        conn: &Connection,
+        source: ImportSource,
+        name: &str,
+        content: &str,
+        options: ImportOptions,
+    ) -> Result<ImportReport> {
+        import::import_external(conn, source, name, content, options)
+    }
+
+    /// 把任务导出为 iCalendar 文本（VTODO）
+    pub fn export_ical(conn: &Connection, todos: &[Todo]) -> Result<String> {
+        let groups = GroupRepository::list(conn)?;
+        Ok(ical::write_todos(todos, &groups))
+    }
+
+    /// 预览从 iCalendar 文件导入的结果，不写入任何数据
+    pub fn preview_ical_import(conn: &Connection, content: &str, with_events: bool, options: ImportOptions) -> Result<ImportReport> {
+        import::preview_ical(conn, content, with_events, options)
+    }
+
+    /// 从 iCalendar 文件导入任务（在事务中执行），`with_events` 为 true 时事件也作为任务导入
+    pub fn import_ical(conn: &Connection, content: &str, with_events: bool, options: ImportOptions) -> Result<ImportReport> {
+        import::import_ical(conn, content, with_events, options)
+    }
+
+    /// 清空所有数据（在事务中执行）
+    pub fn clear_all(conn: &Connection) -> Result<()> {
+        // 开始事务
+        let transaction = conn.unchecked_transaction()?;
+
+        // 删除所有数据（按依赖关系倒序）
+        transaction.execute("DELETE FROM todo_tags", [])?;
+        transaction.execute("DELETE FROM todo_steps", [])?;
+        transaction.execute("DELETE FROM attachments", [])?;
+        transaction.execute("DELETE FROM todos", [])?;
+        transaction.execute("DELETE FROM tags", [])?;
+        transaction.execute("DELETE FROM task_groups", [])?;
+
+        // 提交事务
+        transaction.commit()?;
+
+        Ok(())
+    }
+}
+
@@ -0,0 +1,583 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use rusqlite::{Connection, params, OptionalExtension};
+use crate::error::{Context, Error, Result};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+
+use crate::models::{TaskGroup, GroupChildPolicy, GroupTodoPolicy, GroupDeleteSummary, GroupTreeNode};
+use crate::database::repositories::TodoRepository;
+use crate::utils::new_uuid;
+
+/// TaskGroup 仓库
+pub struct GroupRepository;
+
+impl GroupRepository {
+    /// 获取所有任务组
+    pub fn list(conn: &Connection) -> Result<Vec<TaskGroup>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, parent_id, icon, color, sort_order, created_at, updated_at, uid
+             FROM task_groups
+             ORDER BY sort_order ASC, name ASC"
+        )
+        .context("Failed to prepare list groups query")?;
+
+        let groups = stmt.query_map([], |row| {
+            Ok(TaskGroup {
+                id: row.get(0)?,
+                uid: row.get(8)?,
+                name: row.get(1)?,
+                parent_id: row.get(2)?,
+                icon: row.get(3)?,
+                color: row.get(4)?,
+                sort_order: row.get(5)?,
+                created_at: row.get(6)?,
+                updated_at: row.get(7)?,
+            })
+        })
+        .context("Failed to execute list groups query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse groups")?;
+
+        Ok(groups)
+    }
+
+    /// 根据 ID 获取单个任务组
+    pub fn get(conn: &Connection, id: i64) -> Result<Option<TaskGroup>> {
+        let group = conn.query_row(
+            "SELECT id, name, parent_id, icon, color, sort_order, created_at, updated_at, uid
+             FROM task_groups WHERE id = ?",
+            params![id],
+            |row| {
+                Ok(TaskGroup {
+                    id: row.get(0)?,
+                    uid: row.get(8)?,
+                    name: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    icon: row.get(3)?,
+                    color: row.get(4)?,
+                    sort_order: row.get(5)?,
+                    created_at: row.get(6)?,
+                    updated_at: row.get(7)?,
+                })
+            },
+        )
+        .optional()
+        .context("Failed to execute get group query")?;
+
+        Ok(group)
+    }
+
+    /// 创建任务组
+    pub fn create(
+        conn: &Connection,
+        name: &str,
+        parent_id: Option<i64>,
+        icon: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<TaskGroup> {
+        // 生成新的 sort_order (当前最大值 + 10)
+        let sort_order: i32 = conn
+            .query_row("SELECT COALESCE(MAX(sort_order), 0) + 10 FROM task_groups", [], |row| {
+                row.get(0)
+            })
+            .unwrap_or(10);
+
+        let now = Utc::now().timestamp_millis();
+        let uid = new_uuid();
+        let icon_value = icon.unwrap_or("📁");
+        let color_value = color.unwrap_or("#409EFF");
+
+        conn.execute(
+            "INSERT INTO task_groups (uid, name, parent_id, icon, color, sort_order, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                uid,
+                name,
+                parent_id,
+                icon_value,
+                color_value,
+                sort_order,
+                now,
+                now,
+            ],
+        )
+        .context("Failed to insert group")?;
+
+        // 获取新插入的ID
+        let id: i64 = conn.last_insert_rowid();
+
+        Ok(TaskGroup {
+            id,
+            uid,
+            name: name.to_string(),
+            parent_id,
+            icon: Some(icon_value.to_string()),
+            color: Some(color_value.to_string()),
+            sort_order,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// 更新任务组
+    pub fn update(
+        conn: &Connection,
+        id: i64,
+        name: Option<&str>,
+        parent_id: Option<i64>,
+        icon: Option<&str>,
+        color: Option<&str>,
+    ) -> Result<TaskGroup> {
+        // 首先获取现有任务组
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Group not found: {}", id)))?;
+
+        let new_name = name.unwrap_or(existing.name.as_str());
+        let new_icon = icon.or(existing.icon.as_deref());
+        let new_color = color.or(existing.color.as_deref());
+        let new_parent_id = parent_id.or(existing.parent_id);
+
+        if parent_id.is_some() && parent_id != existing.parent_id {
+            Self::ensure_valid_parent(conn, id, new_parent_id)?;
+        }
+
+        let now = Utc::now().timestamp_millis();
+
+        conn.execute(
+            "UPDATE task_groups
+             SET name = ?1, parent_id = ?2, icon = ?3, color = ?4, updated_at = ?5
+             WHERE id = ?6",
+            params![new_name, new_parent_id, new_icon, new_color, now, id],
+        )
+        .context("Failed to update group")?;
+
+        Ok(TaskGroup {
+            id,
+            uid: existing.uid.clone(),
+            name: new_name.to_string(),
+            parent_id: new_parent_id,
+            icon: new_icon.map(|s| s.to_string()),
+            color: new_color.map(|s| s.to_string()),
+            sort_order: existing.sort_order,
+            created_at: existing.created_at,
+            updated_at: now,
+        })
+    }
+
+    /// 删除任务组
+    /// 子任务组随之删除，组内（含子孙任务组）的任务 group_id 置为 NULL
+    pub fn delete(conn: &Connection, id: i64) -> Result<GroupDeleteSummary> {
+        Self::delete_with_options(conn, id, GroupChildPolicy::Cascade, GroupTodoPolicy::Unassign)
+    }
+
+    /// 按指定策略删除任务组
+    ///
+    /// 不依赖外键的 ON DELETE 行为（旧版本迁移后的表没有外键约束），所有关联数据均显式处理
+    pub fn delete_with_options(
+        conn: &Connection,
+        id: i64,
+        child_policy: GroupChildPolicy,
+        todo_policy: GroupTodoPolicy,
+    ) -> Result<GroupDeleteSummary> {
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Group not found: {}", id)))?;
+
+        let transaction = conn.unchecked_transaction()?;
+        let mut summary = GroupDeleteSummary::default();
+
+        // 确定要删除的任务组
+        let deleted_ids: Vec<i64> = match child_policy {
+            GroupChildPolicy::Cascade => {
+                let mut ids = vec![id];
+                ids.extend(Self::descendant_ids(&transaction, id)?);
+                ids
+            }
+            GroupChildPolicy::Reparent => {
+                let now = Utc::now().timestamp_millis();
+                let mut stmt = transaction.prepare("SELECT id FROM task_groups WHERE parent_id = ? ORDER BY sort_order ASC")
+                    .context("Failed to prepare child groups query")?;
+                let children = stmt.query_map(params![id], |row| row.get::<_, i64>(0))
+                    .context("Failed to execute child groups query")?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Failed to parse child group ids")?;
+                transaction.execute(
+                    "UPDATE task_groups SET parent_id = ?1, updated_at = ?2 WHERE parent_id = ?3",
+                    params![existing.parent_id, now, id],
+                )
+                .context("Failed to reparent child groups")?;
+                summary.reparented_group_ids = children;
+                vec![id]
+            }
+        };
+
+        let placeholders = vec!["?"; deleted_ids.len()].join(", ");
+        let id_params: Vec<&dyn rusqlite::ToSql> = deleted_ids.iter().map(|i| i as &dyn rusqlite::ToSql).collect();
+
+        let mut stmt = transaction.prepare(&format!("SELECT id FROM todos WHERE group_id IN ({})", placeholders))
+            .context("Failed to prepare group todos query")?;
+        summary.affected_todo_ids = stmt.query_map(id_params.as_slice(), |row| row.get::<_, i64>(0))
+            .context("Failed to execute group todos query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse group todo ids")?;
+        drop(stmt);
+
+        // 处理组内任务
+        match todo_policy {
+            GroupTodoPolicy::Unassign => {
+                summary.unassigned_todos = transaction.execute(
+                    &format!("UPDATE todos SET group_id = NULL WHERE group_id IN ({})", placeholders),
+                    id_params.as_slice(),
+                )
+                .context("Failed to update todos group_id")? as i64;
+            }
+            GroupTodoPolicy::MoveTo { group_id } => {
+                if deleted_ids.contains(&group_id) {
+                    return Err(Error::InvalidInput(format!("Cannot move todos into a group that is being deleted: {}", group_id)));
+                }
+                if Self::get(&transaction, group_id)?.is_none() {
+                    return Err(Error::NotFound(format!("Target group not found: {}", group_id)));
+                }
+                let mut move_params: Vec<&dyn rusqlite::ToSql> = vec![&group_id];
+                move_params.extend(id_params.iter().copied());
+                summary.moved_todos = transaction.execute(
+                    &format!("UPDATE todos SET group_id = ? WHERE group_id IN ({})", placeholders),
+                    move_params.as_slice(),
+                )
+                .context("Failed to move todos to target group")? as i64;
+            }
+            GroupTodoPolicy::Delete => {
+                let todo_filter = format!("SELECT id FROM todos WHERE group_id IN ({})", placeholders);
+
+                // 记录将被删除的附件文件
+                let mut stmt = transaction.prepare(&format!(
+                    "SELECT DISTINCT file_path FROM attachments WHERE todo_id IN ({})", todo_filter
+                ))
+                .context("Failed to prepare attachment files query")?;
+                let files = stmt.query_map(id_params.as_slice(), |row| row.get::<_, String>(0))
+                    .context("Failed to execute attachment files query")?
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("Failed to parse attachment files")?;
+
+                for table in ["todo_tags", "todo_steps", "attachments"] {
+                    transaction.execute(
+                        &format!("DELETE FROM {} WHERE todo_id IN ({})", table, todo_filter),
+                        id_params.as_slice(),
+                    )
+                    .context(format!("Failed to delete {} of group todos", table))?;
+                }
+                summary.deleted_todos = transaction.execute(
+                    &format!("DELETE FROM todos WHERE group_id IN ({})", placeholders),
+                    id_params.as_slice(),
+                )
+                .context("Failed to delete group todos")? as i64;
+
+                // 仅保留不再被其他附件记录引用的文件
+                for file_path in files {
+                    let remaining: i64 = transaction.query_row(
+                        "SELECT COUNT(*) FROM attachments WHERE file_path = ?",
+                        params![file_path],
+                        |row| row.get(0),
+                    )
+                    .context("Failed to count attachment references")?;
+                    if remaining == 0 {
+                        summary.orphaned_files.push(file_path);
+                    }
+                }
+            }
+        }
+
+        // 删除任务组（子孙节点在前）
+        for group_id in deleted_ids.iter().rev() {
+            transaction.execute("DELETE FROM task_groups WHERE id = ?", params![group_id])
+                .context("Failed to delete group")?;
+        }
+
+        transaction.commit()?;
+
+        summary.deleted_group_ids = deleted_ids;
+        tracing::info!("Deleted group {} with {:?}/{:?}: {:?}", id, child_policy, todo_policy, summary);
+        Ok(summary)
+    }
+
+    /// 移动任务组到新的父级（None 表示顶层），并放到同级的指定位置
+    pub fn move_group(
+        conn: &Connection,
+        id: i64,
+        new_parent_id: Option<i64>,
+        position: Option<usize>,
+    ) -> Result<TaskGroup> {
+        Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Group not found: {}", id)))?;
+        Self::ensure_valid_parent(conn, id, new_parent_id)?;
+
+        let transaction = conn.unchecked_transaction()?;
+        let now = Utc::now().timestamp_millis();
+
+        transaction.execute(
+            "UPDATE task_groups SET parent_id = ?1, updated_at = ?2 WHERE id = ?3",
+            params![new_parent_id, now, id],
+        )
+        .context("Failed to move group")?;
+
+        // 重新计算新父级下的顺序
+        let mut siblings: Vec<i64> = Self::sibling_ids(&transaction, new_parent_id)?
+            .into_iter()
+            .filter(|sid| *sid != id)
+            .collect();
+        let index = position.unwrap_or(siblings.len()).min(siblings.len());
+        siblings.insert(index, id);
+        Self::apply_sort_order(&transaction, &siblings)?;
+
+        transaction.commit()?;
+
+        Self::get(conn, id)?.context("Moved group not found")
+    }
+
+    /// 调整同级任务组的顺序
+    ///
+    /// `ordered_ids` 中未列出的同级任务组保持原有相对顺序并排在最后
+    pub fn reorder(conn: &Connection, parent_id: Option<i64>, ordered_ids: &[i64]) -> Result<Vec<TaskGroup>> {
+        let transaction = conn.unchecked_transaction()?;
+
+        let siblings = Self::sibling_ids(&transaction, parent_id)?;
+        let sibling_set: HashSet<i64> = siblings.iter().copied().collect();
+
+        let mut seen = HashSet::new();
+        for gid in ordered_ids {
+            if !sibling_set.contains(gid) {
+                return Err(Error::InvalidInput(format!("Group {} is not a child of {:?}", gid, parent_id)));
+            }
+            if !seen.insert(*gid) {
+                return Err(Error::InvalidInput(format!("Duplicate group id in order: {}", gid)));
+            }
+        }
+
+        let mut order: Vec<i64> = ordered_ids.to_vec();
+        order.extend(siblings.into_iter().filter(|sid| !seen.contains(sid)));
+        Self::apply_sort_order(&transaction, &order)?;
+
+        transaction.commit()?;
+
+        let mut groups = Vec::with_capacity(order.len());
+        for gid in order {
+            if let Some(group) = Self::get(conn, gid)? {
+                groups.push(group);
+            }
+        }
+        Ok(groups)
+    }
+
+    /// 获取任务组树（含直接和聚合的任务数量）
+    pub fn tree(conn: &Connection) -> Result<Vec<GroupTreeNode>> {
+        let groups = Self::list(conn)?;
+
+        // 每个任务组直接包含的任务数 (total, done)
+        let mut counts: HashMap<i64, (i64, i64)> = HashMap::new();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT group_id, COUNT(*), SUM(CASE WHEN status = {} THEN 1 ELSE 0 END)
+             FROM todos WHERE group_id IS NOT NULL GROUP BY group_id",
+            crate::models::status::DONE
+        ))
+        .context("Failed to prepare group counts query")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+        })
+        .context("Failed to execute group counts query")?;
+        for row in rows {
+            let (gid, total, done) = row.context("Failed to parse group counts")?;
+            counts.insert(gid, (total, done));
+        }
+
+        let ids: HashSet<i64> = groups.iter().map(|g| g.id).collect();
+        let mut children_of: HashMap<Option<i64>, Vec<TaskGroup>> = HashMap::new();
+        for group in groups {
+            // 父任务组不存在时视为顶层
+            let parent = group.parent_id.filter(|pid| ids.contains(pid));
+            children_of.entry(parent).or_default().push(group);
+        }
+
+        let mut visited = HashSet::new();
+        let mut roots = Self::build_tree_nodes(None, &mut children_of, &counts, &mut visited);
+
+        // 处于环中的任务组无法从顶层到达，作为顶层节点返回以免丢失
+        let remaining: Vec<i64> = children_of.values().flatten().map(|g| g.id).collect();
+        for gid in remaining {
+            if visited.contains(&gid) {
+                continue;
+            }
+            tracing::warn!("Group {} is part of a parent cycle, returning it as a root", gid);
+            let group = children_of.values_mut()
+                .find_map(|list| list.iter().position(|g| g.id == gid).map(|i| list.remove(i)));
+            if let Some(group) = group {
+                visited.insert(group.id);
+                roots.push(Self::build_tree_node(group, &mut children_of, &counts, &mut visited));
+            }
+        }
+
+        Ok(roots)
+    }
+
+    /// 复制任务组及组内所有任务到一个新的任务组
+    ///
+    /// 新任务组与原任务组同级；`attachment_paths` 的含义同 `TodoRepository::duplicate`
+    pub fn duplicate(
+        conn: &Connection,
+        id: i64,
+        name: Option<&str>,
+        reset_status: bool,
+        reset_dates: bool,
+        attachment_paths: &HashMap<i64, String>,
+    ) -> Result<TaskGroup> {
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Group not found: {}", id)))?;
+
+        let new_name = match name {
+            Some(n) if !n.trim().is_empty() => n.to_string(),
+            _ => format!("{} (copy)", existing.name),
+        };
+
+        let transaction = conn.unchecked_transaction()?;
+
+        let group = Self::create(
+            &transaction,
+            &new_name,
+            existing.parent_id,
+            existing.icon.as_deref(),
+            existing.color.as_deref(),
+        )?;
+
+        let todo_ids: Vec<i64> = {
+            let mut stmt = transaction.prepare("SELECT id FROM todos WHERE group_id = ? ORDER BY id ASC")
+                .context("Failed to prepare group todos query")?;
+            let ids = stmt.query_map(params![id], |row| row.get(0))
+                .context("Failed to execute group todos query")?
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to parse group todo ids")?;
+            ids
+        };
+
+        for todo_id in &todo_ids {
+            TodoRepository::copy_todo(
+                &transaction,
+                *todo_id,
+                Some(group.id),
+                reset_status,
+                reset_dates,
+                attachment_paths,
+            )?;
+        }
+
+        transaction.commit()?;
+
+        tracing::info!("Duplicated group {} as {} with {} todos", id, group.id, todo_ids.len());
+        Ok(group)
+    }
+
+    // ========== 辅助方法 ==========
+
+    fn build_tree_nodes(
+        parent: Option<i64>,
+        children_of: &mut HashMap<Option<i64>, Vec<TaskGroup>>,
+        counts: &HashMap<i64, (i64, i64)>,
+        visited: &mut HashSet<i64>,
+    ) -> Vec<GroupTreeNode> {
+        let children = children_of.remove(&parent).unwrap_or_default();
+        let mut nodes = Vec::with_capacity(children.len());
+        for group in children {
+            if visited.insert(group.id) {
+                nodes.push(Self::build_tree_node(group, children_of, counts, visited));
+            }
+        }
+        nodes
+    }
+
+    fn build_tree_node(
+        group: TaskGroup,
+        children_of: &mut HashMap<Option<i64>, Vec<TaskGroup>>,
+        counts: &HashMap<i64, (i64, i64)>,
+        visited: &mut HashSet<i64>,
+    ) -> GroupTreeNode {
+        let children = Self::build_tree_nodes(Some(group.id), children_of, counts, visited);
+        let (todo_count, done_count) = counts.get(&group.id).copied().unwrap_or((0, 0));
+        let total_todo_count = todo_count + children.iter().map(|c| c.total_todo_count).sum::<i64>();
+        let total_done_count = done_count + children.iter().map(|c| c.total_done_count).sum::<i64>();
+
+        GroupTreeNode {
+            group,
+            todo_count,
+            done_count,
+            total_todo_count,
+            total_done_count,
+            children,
+        }
+    }
+
+    /// 获取任务组的所有子孙任务组 ID
+    fn descendant_ids(conn: &Connection, id: i64) -> Result<Vec<i64>> {
+        // 使用 UNION 去重，即使数据中已存在环也不会无限递归
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM task_groups WHERE parent_id = ?1
+                UNION
+                SELECT g.id FROM task_groups g JOIN descendants d ON g.parent_id = d.id
+             )
+             SELECT id FROM descendants WHERE id != ?1"
+        )
+        .context("Failed to prepare descendants query")?;
+
+        let ids = stmt.query_map(params![id], |row| row.get(0))
+            .context("Failed to execute descendants query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse descendant ids")?;
+
+        Ok(ids)
+    }
+
+    /// 校验 `parent_id` 可以作为 `id` 的父级（存在且不会形成环）
+    fn ensure_valid_parent(conn: &Connection, id: i64, parent_id: Option<i64>) -> Result<()> {
+        let Some(pid) = parent_id else {
+            return Ok(());
+        };
+
+        if pid == id {
+            return Err(Error::InvalidInput("A group cannot be its own parent".to_string()));
+        }
+        if Self::get(conn, pid)?.is_none() {
+            return Err(Error::NotFound(format!("Parent group not found: {}", pid)));
+        }
+        if Self::descendant_ids(conn, id)?.contains(&pid) {
+            return Err(Error::InvalidInput(format!("Cannot move group {} under its descendant {}", id, pid)));
+        }
+
+        Ok(())
+    }
+
+    /// 获取指定父级下的任务组 ID（按当前顺序）
+    fn sibling_ids(conn: &Connection, parent_id: Option<i64>) -> Result<Vec<i64>> {
+        let mut stmt = conn.prepare(
+            "SELECT id FROM task_groups WHERE parent_id IS ? ORDER BY sort_order ASC, name ASC"
+        )
+        .context("Failed to prepare siblings query")?;
+
+        let ids = stmt.query_map(params![parent_id], |row| row.get(0))
+            .context("Failed to execute siblings query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse sibling ids")?;
+
+        Ok(ids)
+    }
+
+    /// 按给定顺序重写 sort_order（间隔 10，便于插入）
+    fn apply_sort_order(conn: &Connection, ordered_ids: &[i64]) -> Result<()> {
+        for (index, gid) in ordered_ids.iter().enumerate() {
+            conn.execute(
+                "UPDATE task_groups SET sort_order = ?1 WHERE id = ?2",
+                params![((index + 1) * 10) as i32, gid],
+            )
+            .context("Failed to update group sort_order")?;
+        }
+        Ok(())
+    }
+}
@@ -8,6 +8,11 @@ pub mod step_repo;
 pub mod attachment_repo;
 pub mod stats_repo;
 pub mod data_repo;
+pub mod rule_repo;
+pub mod webhook_repo;
+
+#[cfg(test)]
+mod tests;
 
 // Re-export repositories
 pub use todo_repo::TodoRepository;
@@ -17,3 +22,5 @@ pub use step_repo::StepRepository;
 pub use attachment_repo::AttachmentRepository;
 pub use stats_repo::StatsRepository;
 pub use data_repo::DataRepository;
+pub use rule_repo::RuleRepository;
+pub use webhook_repo::WebhookRepository;
@@ -0,0 +1,253 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use rusqlite::{Connection, params, OptionalExtension, Row};
+use crate::error::{Context, Error, Result};
+use chrono::Utc;
+
+use crate::models::{Rule, RuleTrigger, RuleCondition, RuleAction, RuleExecution};
+
+/// 执行日志最多保留的条数
+const MAX_EXECUTION_LOGS: i64 = 5000;
+
+/// Rule 仓库
+pub struct RuleRepository;
+
+impl RuleRepository {
+    /// 获取所有规则
+    pub fn list(conn: &Connection) -> Result<Vec<Rule>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, enabled, trigger_config, conditions, actions, sort_order, created_at, updated_at
+             FROM rules
+             ORDER BY sort_order ASC, id ASC"
+        )
+        .context("Failed to prepare list rules query")?;
+
+        let rows = stmt.query_map([], Self::read_row)
+            .context("Failed to execute list rules query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read rules")?;
+
+        rows.into_iter().map(Self::parse_row).collect()
+    }
+
+    /// 获取启用的、指定触发类型的规则
+    pub fn list_enabled_by_kind(conn: &Connection, kind: &str) -> Result<Vec<Rule>> {
+        Ok(Self::list(conn)?
+            .into_iter()
+            .filter(|r| r.enabled && r.trigger.kind() == kind)
+            .collect())
+    }
+
+    /// 根据 ID 获取单条规则
+    pub fn get(conn: &Connection, id: i64) -> Result<Option<Rule>> {
+        let row = conn.query_row(
+            "SELECT id, name, enabled, trigger_config, conditions, actions, sort_order, created_at, updated_at
+             FROM rules WHERE id = ?",
+            params![id],
+            Self::read_row,
+        )
+        .optional()
+        .context("Failed to execute get rule query")?;
+
+        row.map(Self::parse_row).transpose()
+    }
+
+    /// 创建规则
+    pub fn create(
+        conn: &Connection,
+        name: &str,
+        enabled: bool,
+        trigger: &RuleTrigger,
+        conditions: &[RuleCondition],
+        actions: &[RuleAction],
+    ) -> Result<Rule> {
+        let now = Utc::now().timestamp_millis();
+
+        let sort_order: i32 = conn.query_row(
+            "SELECT COALESCE(MAX(sort_order), 0) + 10 FROM rules",
+            [],
+            |row| row.get(0),
+        ).unwrap_or(10);
+
+        conn.execute(
+            "INSERT INTO rules (name, enabled, trigger_config, conditions, actions, sort_order, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                name,
+                enabled as i32,
+                serde_json::to_string(trigger)?,
+                serde_json::to_string(conditions)?,
+                serde_json::to_string(actions)?,
+                sort_order,
+                now,
+                now,
+            ],
+        )
+        .context("Failed to insert rule")?;
+
+        let id = conn.last_insert_rowid();
+        Self::get(conn, id)?.context("Created rule not found")
+    }
+
+    /// 更新规则
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        conn: &Connection,
+        id: i64,
+        name: Option<&str>,
+        enabled: Option<bool>,
+        trigger: Option<&RuleTrigger>,
+        conditions: Option<&[RuleCondition]>,
+        actions: Option<&[RuleAction]>,
+        sort_order: Option<i32>,
+    ) -> Result<Rule> {
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Rule not found: {}", id)))?;
+
+        let now = Utc::now().timestamp_millis();
+
+        conn.execute(
+            "UPDATE rules SET name = ?1, enabled = ?2, trigger_config = ?3, conditions = ?4, actions = ?5,
+             sort_order = ?6, updated_at = ?7 WHERE id = ?8",
+            params![
+                name.unwrap_or(existing.name.as_str()),
+                enabled.unwrap_or(existing.enabled) as i32,
+                serde_json::to_string(trigger.unwrap_or(&existing.trigger))?,
+                serde_json::to_string(conditions.unwrap_or(&existing.conditions))?,
+                serde_json::to_string(actions.unwrap_or(&existing.actions))?,
+                sort_order.unwrap_or(existing.sort_order),
+                now,
+                id,
+            ],
+        )
+        .context("Failed to update rule")?;
+
+        Self::get(conn, id)?.context("Updated rule not found")
+    }
+
+    /// 删除规则（连同执行日志）
+    pub fn delete(conn: &Connection, id: i64) -> Result<()> {
+        conn.execute("DELETE FROM rule_executions WHERE rule_id = ?", params![id])
+            .context("Failed to delete rule executions")?;
+
+        let rows_affected = conn.execute("DELETE FROM rules WHERE id = ?", params![id])
+            .context("Failed to delete rule")?;
+
+        if rows_affected == 0 {
+            return Err(Error::NotFound("Rule not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // ========== 执行日志 ==========
+
+    /// 记录一次规则执行
+    pub fn log_execution(
+        conn: &Connection,
+        rule_id: i64,
+        todo_id: Option<i64>,
+        trigger_type: &str,
+        event_key: Option<&str>,
+        success: bool,
+        message: Option<&str>,
+    ) -> Result<()> {
+        let now = Utc::now().timestamp_millis();
+
+        conn.execute(
+            "INSERT INTO rule_executions (rule_id, todo_id, trigger_type, event_key, success, message, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![rule_id, todo_id, trigger_type, event_key, success as i32, message, now],
+        )
+        .context("Failed to insert rule execution")?;
+
+        // 只保留最近的日志
+        conn.execute(
+            "DELETE FROM rule_executions WHERE id <= (SELECT MAX(id) FROM rule_executions) - ?",
+            params![MAX_EXECUTION_LOGS],
+        )
+        .context("Failed to prune rule executions")?;
+
+        Ok(())
+    }
+
+    /// 判断规则是否已对任务的某个事件执行过
+    pub fn has_execution(conn: &Connection, rule_id: i64, todo_id: i64, event_key: &str) -> Result<bool> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM rule_executions WHERE rule_id = ?1 AND todo_id = ?2 AND event_key = ?3",
+            params![rule_id, todo_id, event_key],
+            |row| row.get(0),
+        )
+        .context("Failed to count rule executions")?;
+
+        Ok(count > 0)
+    }
+
+    /// 获取执行日志（按时间倒序）
+    pub fn list_executions(conn: &Connection, rule_id: Option<i64>, limit: i64) -> Result<Vec<RuleExecution>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, rule_id, todo_id, trigger_type, event_key, success, message, created_at
+             FROM rule_executions
+             WHERE ?1 IS NULL OR rule_id = ?1
+             ORDER BY id DESC
+             LIMIT ?2"
+        )
+        .context("Failed to prepare list rule executions query")?;
+
+        let executions = stmt.query_map(params![rule_id, limit], |row| {
+            Ok(RuleExecution {
+                id: row.get(0)?,
+                rule_id: row.get(1)?,
+                todo_id: row.get(2)?,
+                trigger_type: row.get(3)?,
+                event_key: row.get(4)?,
+                success: row.get::<_, i32>(5)? != 0,
+                message: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })
+        .context("Failed to execute list rule executions query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse rule executions")?;
+
+        Ok(executions)
+    }
+
+    // ========== 辅助方法 ==========
+
+    #[allow(clippy::type_complexity)]
+    fn read_row(row: &Row<'_>) -> rusqlite::Result<(i64, String, i32, String, String, String, i32, i64, i64)> {
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+            row.get(5)?,
+            row.get::<_, Option<i32>>(6)?.unwrap_or(0),
+            row.get(7)?,
+            row.get(8)?,
+        ))
+    }
+
+    fn parse_row(
+        (id, name, enabled, trigger, conditions, actions, sort_order, created_at, updated_at):
+            (i64, String, i32, String, String, String, i32, i64, i64),
+    ) -> Result<Rule> {
+        Ok(Rule {
+            id,
+            trigger: serde_json::from_str(&trigger)
+                .context(format!("Failed to parse trigger of rule {}", id))?,
+            conditions: serde_json::from_str(&conditions)
+                .context(format!("Failed to parse conditions of rule {}", id))?,
+            actions: serde_json::from_str(&actions)
+                .context(format!("Failed to parse actions of rule {}", id))?,
+            name,
+            enabled: enabled != 0,
+            sort_order,
+            created_at,
+            updated_at,
+        })
+    }
+}
@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: MIT
 
 use rusqlite::{Connection, params};
-use anyhow::{Result, Context};
+use crate::error::{Context, Result};
 use chrono::Utc;
-use crate::models::{TodoStats, StatsByDate, TodoStatsWithDetails, Todo, TodoStatus};
+use crate::models::{TodoStats, StatsByDate, TodoStatsWithDetails, Todo, TodoStatus, TagStats};
 use crate::models::constants::{priority, status};
+use crate::database::repositories::tag_repo::TODO_HAS_TAG_TREE_SQL;
 
 /// 统计仓库
 pub struct StatsRepository;
@@ -271,6 +272,77 @@ impl StatsRepository {
         })
     }
 
+    /// 标签使用情况报告
+    ///
+    /// 按直接关联的任务数降序排列，未使用的标签也会返回（count 为 0），便于清理；
+    /// 时间范围按任务 created_at 过滤。`tag_tree` 把每个标签和它的所有子孙标签配对，
+    /// 一次查询同时得到直接关联和包含子标签的任务数
+    pub fn get_tag_usage(conn: &Connection, start_date: Option<i64>, end_date: Option<i64>) -> Result<Vec<TagStats>> {
+        let mut time_filter = String::new();
+        let mut time_params: Vec<i64> = Vec::new();
+        if let Some(start) = start_date {
+            time_filter.push_str(" AND t.created_at >= ?");
+            time_params.push(start);
+        }
+        if let Some(end) = end_date {
+            time_filter.push_str(" AND t.created_at <= ?");
+            time_params.push(end);
+        }
+
+        // UNION 去重，即使标签的父子关系中存在环也不会无限递归
+        let query = format!(
+            "WITH RECURSIVE tag_tree(root_id, id) AS (
+                SELECT id, id FROM tags
+                UNION
+                SELECT p.root_id, c.id FROM tags c JOIN tag_tree p ON c.parent_id = p.id
+             ),
+             tagged(tag_id, todo_id, status, updated_at) AS (
+                SELECT tt.tag_id, t.id, t.status, t.updated_at
+                FROM todo_tags tt JOIN todos t ON t.id = tt.todo_id
+                WHERE 1 = 1{}
+             ),
+             direct AS (
+                SELECT tag_id, COUNT(*) AS count,
+                       SUM(CASE WHEN status = {} THEN 1 ELSE 0 END) AS done,
+                       MAX(updated_at) AS last_used_at
+                FROM tagged GROUP BY tag_id
+             ),
+             tree AS (
+                SELECT r.root_id AS tag_id, COUNT(DISTINCT d.todo_id) AS total
+                FROM tag_tree r JOIN tagged d ON d.tag_id = r.id
+                GROUP BY r.root_id
+             )
+             SELECT g.id, g.name, g.color, g.parent_id,
+                    COALESCE(direct.count, 0), COALESCE(direct.done, 0), direct.last_used_at,
+                    COALESCE(tree.total, 0)
+             FROM tags g
+             LEFT JOIN direct ON direct.tag_id = g.id
+             LEFT JOIN tree ON tree.tag_id = g.id
+             ORDER BY COALESCE(direct.count, 0) DESC, g.name ASC",
+            time_filter, status::DONE
+        );
+
+        let mut stmt = conn.prepare(&query)
+            .context("Failed to prepare tag usage query")?;
+        let report = stmt.query_map(rusqlite::params_from_iter(time_params.iter()), |row| {
+            Ok(TagStats {
+                id: row.get::<_, i64>(0)?.to_string(),
+                name: row.get(1)?,
+                color: row.get(2)?,
+                parent_id: row.get::<_, Option<i64>>(3)?.map(|v| v.to_string()),
+                count: row.get(4)?,
+                done: row.get(5)?,
+                total_with_children: row.get(7)?,
+                last_used_at: row.get(6)?,
+            })
+        })
+        .context("Failed to execute tag usage query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse tag usage")?;
+
+        Ok(report)
+    }
+
     /// 构建时间过滤条件
     fn build_time_filters(start_date: Option<i64>, end_date: Option<i64>) -> (String, String, String) {
         let created_filter: String = match (start_date, end_date) {
@@ -329,12 +401,12 @@ impl StatsRepository {
         }
     }
 
-    /// 构建标签过滤条件
+    /// 构建标签过滤条件（包含子标签）
     fn build_tag_filter(tag_ids: &Option<Vec<i64>>) -> (String, Vec<i64>) {
         if let Some(tids) = tag_ids {
             if !tids.is_empty() {
                 let tag_conditions: Vec<String> = tids.iter().map(|_| {
-                    "EXISTS (SELECT 1 FROM todo_tags tt WHERE tt.todo_id = t.id AND tt.tag_id = ?)".to_string()
+                    TODO_HAS_TAG_TREE_SQL.to_string()
                 }).collect();
                 (format!(" AND ({})", tag_conditions.join(" OR ")), tids.clone())
             } else {
@@ -360,6 +432,7 @@ impl StatsRepository {
     }
 
     /// 按状态和时间字段查询任务
+    #[allow(clippy::too_many_arguments)]
     fn query_tasks_by_status(
         conn: &Connection,
         status: i32,
@@ -410,6 +483,7 @@ impl StatsRepository {
             let status = TodoStatus::from_i32(status_int);
             Ok(Todo {
                 id: row.get("id")?,
+                uid: row.get("uid")?,
                 title: row.get("title")?,
                 description: row.get("description")?,
                 status,
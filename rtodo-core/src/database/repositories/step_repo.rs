@@ -2,10 +2,11 @@
 // SPDX-License-Identifier: MIT
 
 use rusqlite::{Connection, params, OptionalExtension};
-use anyhow::{Result, Context};
+use crate::error::{Context, Error, Result};
 use chrono::Utc;
 
 use crate::models::TodoStep;
+use crate::utils::new_uuid;
 
 /// TodoStep 仓库
 pub struct StepRepository;
@@ -14,7 +15,7 @@ impl StepRepository {
     /// 获取所有步骤
     pub fn list_all(conn: &Connection) -> Result<Vec<TodoStep>> {
         let mut stmt = conn.prepare(
-            "SELECT id, todo_id, title, is_completed, sort_order, created_at
+            "SELECT id, todo_id, title, is_completed, sort_order, created_at, uid
              FROM todo_steps
              ORDER BY todo_id ASC, sort_order ASC"
         )
@@ -23,6 +24,7 @@ impl StepRepository {
         let steps = stmt.query_map([], |row| {
             Ok(TodoStep {
                 id: row.get(0)?,
+                uid: row.get(6)?,
                 todo_id: row.get(1)?,
                 title: row.get(2)?,
                 is_completed: row.get::<_, i32>(3)? == 1,
@@ -40,7 +42,7 @@ impl StepRepository {
     /// 获取任务的所有步骤
     pub fn list_by_todo(conn: &Connection, todo_id: i64) -> Result<Vec<TodoStep>> {
         let mut stmt = conn.prepare(
-            "SELECT id, todo_id, title, is_completed, sort_order, created_at
+            "SELECT id, todo_id, title, is_completed, sort_order, created_at, uid
              FROM todo_steps
              WHERE todo_id = ?
              ORDER BY sort_order ASC"
@@ -50,6 +52,7 @@ impl StepRepository {
         let steps = stmt.query_map(params![todo_id], |row| {
             Ok(TodoStep {
                 id: row.get(0)?,
+                uid: row.get(6)?,
                 todo_id: row.get(1)?,
                 title: row.get(2)?,
                 is_completed: row.get::<_, i32>(3)? == 1,
@@ -67,12 +70,13 @@ impl StepRepository {
     /// 根据 ID 获取单个步骤
     pub fn get(conn: &Connection, id: i64) -> Result<Option<TodoStep>> {
         let step = conn.query_row(
-            "SELECT id, todo_id, title, is_completed, sort_order, created_at
+            "SELECT id, todo_id, title, is_completed, sort_order, created_at, uid
              FROM todo_steps WHERE id = ?",
             params![id],
             |row| {
                 Ok(TodoStep {
                     id: row.get(0)?,
+                    uid: row.get(6)?,
                     todo_id: row.get(1)?,
                     title: row.get(2)?,
                     is_completed: row.get::<_, i32>(3)? == 1,
@@ -88,6 +92,8 @@ impl StepRepository {
     }
 
     /// 创建步骤
+    ///
+    /// 不触发自动化规则，原因见 [`crate::rules`]
     pub fn create(
         conn: &Connection,
         todo_id: i64,
@@ -101,11 +107,12 @@ impl StepRepository {
         ).unwrap_or(10);
 
         let now = Utc::now().timestamp_millis();
+        let uid = new_uuid();
 
         conn.execute(
-            "INSERT INTO todo_steps (todo_id, title, is_completed, sort_order, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![todo_id, title, 0, sort_order, now],
+            "INSERT INTO todo_steps (uid, todo_id, title, is_completed, sort_order, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![uid, todo_id, title, 0, sort_order, now],
         )
         .context("Failed to insert step")?;
 
@@ -114,6 +121,7 @@ impl StepRepository {
 
         Ok(TodoStep {
             id,
+            uid,
             todo_id,
             title: title.to_string(),
             is_completed: false,
@@ -163,7 +171,7 @@ impl StepRepository {
         .context("Failed to delete step")?;
 
         if rows_affected == 0 {
-            anyhow::bail!("Step not found");
+            return Err(Error::NotFound("Step not found".to_string()));
         }
 
         Ok(())
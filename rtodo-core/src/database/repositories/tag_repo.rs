@@ -0,0 +1,335 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use rusqlite::{Connection, params, OptionalExtension};
+use crate::error::{Context, Error, Result};
+use chrono::Utc;
+use std::collections::HashSet;
+
+use crate::models::Tag;
+use crate::utils::new_uuid;
+
+/// 任务（别名 `t`）关联了指定标签或其任一子孙标签的条件，需绑定一个标签 ID 参数
+pub(crate) const TODO_HAS_TAG_TREE_SQL: &str =
+    "EXISTS (SELECT 1 FROM todo_tags tt WHERE tt.todo_id = t.id AND tt.tag_id IN (
+        WITH RECURSIVE tag_tree(id) AS (
+            SELECT ?
+            UNION
+            SELECT c.id FROM tags c JOIN tag_tree p ON c.parent_id = p.id
+        )
+        SELECT id FROM tag_tree
+    ))";
+
+/// Tag 仓库
+pub struct TagRepository;
+
+impl TagRepository {
+    /// 获取所有标签
+    pub fn list(conn: &Connection) -> Result<Vec<Tag>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, color, parent_id, created_at, uid
+             FROM tags
+             ORDER BY name ASC"
+        )
+        .context("Failed to prepare list tags query")?;
+
+        let tags = stmt.query_map([], |row| {
+            Ok(Tag {
+                id: row.get(0)?,
+                uid: row.get(5)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                parent_id: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .context("Failed to execute list tags query")?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to parse tags")?;
+
+        Ok(tags)
+    }
+
+    /// 根据 ID 获取单个标签
+    pub fn get(conn: &Connection, id: i64) -> Result<Option<Tag>> {
+        let tag = conn.query_row(
+            "SELECT id, name, color, parent_id, created_at, uid FROM tags WHERE id = ?",
+            params![id],
+            |row| {
+                Ok(Tag {
+                    id: row.get(0)?,
+                    uid: row.get(5)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    parent_id: row.get(3)?,
+                    created_at: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+        .context("Failed to execute get tag query")?;
+
+        Ok(tag)
+    }
+
+    /// 根据名称获取标签（不区分大小写）
+    pub fn find_by_name(conn: &Connection, name: &str) -> Result<Option<Tag>> {
+        let id: Option<i64> = conn.query_row(
+            "SELECT id FROM tags WHERE name = ? COLLATE NOCASE",
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()
+        .context("Failed to execute find tag by name query")?;
+
+        match id {
+            Some(id) => Self::get(conn, id),
+            None => Ok(None),
+        }
+    }
+
+    /// 创建标签
+    pub fn create(
+        conn: &Connection,
+        name: &str,
+        color: &str,
+        parent_id: Option<i64>,
+    ) -> Result<Tag> {
+        Self::ensure_unique_name(conn, name, None)?;
+        if let Some(pid) = parent_id {
+            if Self::get(conn, pid)?.is_none() {
+                return Err(Error::NotFound(format!("Parent tag not found: {}", pid)));
+            }
+        }
+
+        let now = Utc::now().timestamp_millis();
+        let uid = new_uuid();
+
+        conn.execute(
+            "INSERT INTO tags (uid, name, color, parent_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![uid, name, color, parent_id, now],
+        )
+        .context("Failed to insert tag")?;
+
+        // 获取新插入的ID
+        let id: i64 = conn.last_insert_rowid();
+
+        Ok(Tag {
+            id,
+            uid,
+            name: name.to_string(),
+            color: color.to_string(),
+            parent_id,
+            created_at: now,
+        })
+    }
+
+    /// 更新标签
+    ///
+    /// 任务通过 ID 关联标签，重命名后所有任务自动显示新名称
+    pub fn update(
+        conn: &Connection,
+        id: i64,
+        name: Option<&str>,
+        color: Option<&str>,
+        parent_id: Option<i64>,
+    ) -> Result<Tag> {
+        // 首先获取现有标签
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Tag not found: {}", id)))?;
+
+        let new_name = name.unwrap_or(existing.name.as_str());
+        let new_color = color.unwrap_or(existing.color.as_str());
+        let new_parent_id = parent_id.or(existing.parent_id);
+
+        if new_name != existing.name {
+            Self::ensure_unique_name(conn, new_name, Some(id))?;
+        }
+        if parent_id.is_some() && parent_id != existing.parent_id {
+            Self::ensure_valid_parent(conn, id, new_parent_id)?;
+        }
+
+        conn.execute(
+            "UPDATE tags SET name = ?1, color = ?2, parent_id = ?3 WHERE id = ?4",
+            params![new_name, new_color, new_parent_id, id],
+        )
+        .context("Failed to update tag")?;
+
+        Ok(Tag {
+            id,
+            uid: existing.uid.clone(),
+            name: new_name.to_string(),
+            color: new_color.to_string(),
+            parent_id: new_parent_id,
+            created_at: existing.created_at,
+        })
+    }
+
+    /// 删除标签
+    /// 注意：由于外键约束 ON DELETE CASCADE，删除标签后，todo_tags 关联会自动删除
+    /// 这里我们显式执行以确保数据一致性；子标签移动到被删除标签的父级
+    pub fn delete(conn: &Connection, id: i64) -> Result<()> {
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound("Tag not found".to_string()))?;
+
+        let transaction = conn.unchecked_transaction()?;
+
+        // 显式删除标签与任务的关联
+        transaction.execute(
+            "DELETE FROM todo_tags WHERE tag_id = ?",
+            params![id],
+        )
+        .context("Failed to delete todo_tags associations")?;
+
+        transaction.execute(
+            "UPDATE tags SET parent_id = ?1 WHERE parent_id = ?2",
+            params![existing.parent_id, id],
+        )
+        .context("Failed to reparent child tags")?;
+
+        // 删除标签
+        transaction.execute(
+            "DELETE FROM tags WHERE id = ?",
+            params![id],
+        )
+        .context("Failed to delete tag")?;
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    /// 合并标签：将 `source_ids` 的任务关联全部指向 `target_id`，然后删除这些标签
+    ///
+    /// 被合并标签的子标签挂到目标标签下；返回合并后的标签和新关联到该标签的任务 ID
+    pub fn merge(conn: &Connection, target_id: i64, source_ids: &[i64]) -> Result<(Tag, Vec<i64>)> {
+        let target = Self::get(conn, target_id)?
+            .ok_or_else(|| Error::NotFound(format!("Tag not found: {}", target_id)))?;
+
+        let sources: Vec<i64> = source_ids.iter()
+            .copied()
+            .filter(|sid| *sid != target_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        if sources.is_empty() {
+            return Ok((target, Vec::new()));
+        }
+        for sid in &sources {
+            if Self::get(conn, *sid)?.is_none() {
+                return Err(Error::NotFound(format!("Tag not found: {}", sid)));
+            }
+        }
+
+        let transaction = conn.unchecked_transaction()?;
+
+        // 目标标签原本挂在某个被合并标签之下时，向上找到第一个保留的祖先
+        let mut target_parent = target.parent_id;
+        let mut visited = HashSet::new();
+        while let Some(pid) = target_parent.filter(|pid| sources.contains(pid)) {
+            if !visited.insert(pid) {
+                target_parent = None;
+                break;
+            }
+            target_parent = Self::get(&transaction, pid)?.and_then(|t| t.parent_id);
+        }
+        if target_parent != target.parent_id {
+            transaction.execute(
+                "UPDATE tags SET parent_id = ?1 WHERE id = ?2",
+                params![target_parent, target_id],
+            )
+            .context("Failed to update target tag parent")?;
+        }
+
+        let mut newly_tagged: Vec<i64> = Vec::new();
+        for sid in &sources {
+            let mut stmt = transaction.prepare(
+                "SELECT todo_id FROM todo_tags WHERE tag_id = ?1
+                 AND todo_id NOT IN (SELECT todo_id FROM todo_tags WHERE tag_id = ?2)"
+            )
+            .context("Failed to prepare merged todos query")?;
+            let todo_ids = stmt.query_map(params![sid, target_id], |row| row.get::<_, i64>(0))
+                .context("Failed to execute merged todos query")?
+                .collect::<Result<Vec<_>, _>>()
+                .context("Failed to parse merged todo ids")?;
+            newly_tagged.extend(todo_ids);
+
+            transaction.execute(
+                "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id)
+                 SELECT todo_id, ?1 FROM todo_tags WHERE tag_id = ?2",
+                params![target_id, sid],
+            )
+            .context("Failed to re-point todo_tags")?;
+            transaction.execute("DELETE FROM todo_tags WHERE tag_id = ?", params![sid])
+                .context("Failed to delete merged todo_tags")?;
+            transaction.execute(
+                "UPDATE tags SET parent_id = ?1 WHERE parent_id = ?2 AND id != ?1",
+                params![target_id, sid],
+            )
+            .context("Failed to move child tags")?;
+        }
+
+        for sid in &sources {
+            transaction.execute("DELETE FROM tags WHERE id = ?", params![sid])
+                .context("Failed to delete merged tag")?;
+        }
+
+        transaction.commit()?;
+
+        tracing::info!("Merged tags {:?} into {}", sources, target_id);
+        let tag = Self::get(conn, target_id)?.context("Merged tag not found")?;
+        Ok((tag, newly_tagged))
+    }
+
+    /// 获取标签的所有子孙标签 ID
+    pub fn descendant_ids(conn: &Connection, id: i64) -> Result<Vec<i64>> {
+        // 使用 UNION 去重，即使数据中已存在环也不会无限递归
+        let mut stmt = conn.prepare(
+            "WITH RECURSIVE descendants(id) AS (
+                SELECT id FROM tags WHERE parent_id = ?1
+                UNION
+                SELECT c.id FROM tags c JOIN descendants d ON c.parent_id = d.id
+             )
+             SELECT id FROM descendants WHERE id != ?1"
+        )
+        .context("Failed to prepare tag descendants query")?;
+
+        let ids = stmt.query_map(params![id], |row| row.get(0))
+            .context("Failed to execute tag descendants query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse tag descendant ids")?;
+
+        Ok(ids)
+    }
+
+    // ========== 辅助方法 ==========
+
+    /// 标签名不区分大小写唯一
+    fn ensure_unique_name(conn: &Connection, name: &str, exclude_id: Option<i64>) -> Result<()> {
+        if let Some(existing) = Self::find_by_name(conn, name)? {
+            if Some(existing.id) != exclude_id {
+                return Err(Error::Conflict(format!("Tag already exists: {}", existing.name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// 校验 `parent_id` 可以作为 `id` 的父标签（存在且不会形成环）
+    fn ensure_valid_parent(conn: &Connection, id: i64, parent_id: Option<i64>) -> Result<()> {
+        let Some(pid) = parent_id else {
+            return Ok(());
+        };
+
+        if pid == id {
+            return Err(Error::InvalidInput("A tag cannot be its own parent".to_string()));
+        }
+        if Self::get(conn, pid)?.is_none() {
+            return Err(Error::NotFound(format!("Parent tag not found: {}", pid)));
+        }
+        if Self::descendant_ids(conn, id)?.contains(&pid) {
+            return Err(Error::InvalidInput(format!("Cannot move tag {} under its descendant {}", id, pid)));
+        }
+
+        Ok(())
+    }
+}
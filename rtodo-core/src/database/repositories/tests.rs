@@ -0,0 +1,464 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::models::{status, GroupChildPolicy, GroupTodoPolicy, ImportOptions};
+use crate::Error;
+use crate::test_support::memory_db;
+use std::collections::HashMap;
+
+#[test]
+fn todos_round_trip_with_tags_and_group() {
+    let conn = memory_db();
+    let group = GroupRepository::create(&conn, "Work", None, None, None).unwrap();
+    let tag = TagRepository::create(&conn, "urgent", "#ff0000", None).unwrap();
+
+    let todo = TodoRepository::create(&conn, "Write report", Some("Q3"), Some(group.id), None, None, 1, Some(vec![tag.id]))
+        .unwrap();
+    let loaded = TodoRepository::get(&conn, todo.id).unwrap().unwrap();
+    assert_eq!(loaded.title, "Write report");
+    assert_eq!(loaded.group_id, Some(group.id));
+    assert_eq!(loaded.tags.unwrap().iter().map(|t| t.id).collect::<Vec<_>>(), vec![tag.id]);
+
+    TodoRepository::update_status(&conn, todo.id, status::DONE).unwrap();
+    let stats = StatsRepository::get_stats(&conn, None, None).unwrap();
+    assert_eq!((stats.total, stats.done), (1, 1));
+}
+
+#[test]
+fn todo_update_filter_and_delete() {
+    let conn = memory_db();
+    let group = GroupRepository::create(&conn, "Work", None, None, None).unwrap();
+    let work = TagRepository::create(&conn, "work", "#0000ff", None).unwrap();
+    let home = TagRepository::create(&conn, "home", "#00ff00", None).unwrap();
+    let report = TodoRepository::create(&conn, "Write report", Some("Q3"), None, None, None, 0, Some(vec![home.id]))
+        .unwrap();
+    let chores = TodoRepository::create(&conn, "Do chores", None, None, None, None, 0, Some(vec![home.id])).unwrap();
+
+    // None 保持不变，Some(None) 清空
+    let updated = TodoRepository::update(
+        &conn, report.id, Some("Write final report"), Some(None), None, Some(1), Some(Some(group.id)),
+        Some(Some("alice".to_string())), None, Some(Some(2_000)), Some(vec![work.id]),
+    ).unwrap();
+    assert_eq!(updated.title, "Write final report");
+    assert_eq!((updated.description, updated.priority, updated.group_id), (None, 1, Some(group.id)));
+    assert_eq!((updated.assignee.as_deref(), updated.due_date), (Some("alice"), Some(2_000)));
+    assert_eq!(updated.tags.unwrap().iter().map(|t| t.id).collect::<Vec<_>>(), vec![work.id]);
+
+    let filter = |groups: Option<Vec<i64>>, tags: Option<Vec<i64>>, status: Option<i32>, search: Option<&str>| {
+        let todos = TodoRepository::list_with_filters(&conn, groups, tags, status, search, None, None, None).unwrap();
+        todos.iter().map(|t| t.id).collect::<Vec<_>>()
+    };
+    assert_eq!(filter(Some(vec![group.id]), None, None, None), vec![report.id]);
+    assert_eq!(filter(None, Some(vec![home.id]), None, None), vec![chores.id]);
+    assert_eq!(filter(None, None, None, Some("final")), vec![report.id]);
+
+    let done = TodoRepository::update_status(&conn, chores.id, status::DONE).unwrap();
+    assert!(done.completed_at.is_some());
+    assert_eq!(filter(None, None, Some(status::DONE), None), vec![chores.id]);
+    let reopened = TodoRepository::update_status(&conn, chores.id, status::TODO).unwrap();
+    assert!(reopened.completed_at.is_none());
+
+    // 删除任务时步骤和标签关联一并删除
+    StepRepository::create(&conn, report.id, "Outline").unwrap();
+    TodoRepository::delete(&conn, report.id).unwrap();
+    assert!(TodoRepository::get(&conn, report.id).unwrap().is_none());
+    assert!(StepRepository::list_all(&conn).unwrap().is_empty());
+    assert!(TodoRepository::delete(&conn, report.id).unwrap_err().is_not_found());
+    assert_eq!(filter(None, Some(vec![work.id]), None, None), Vec::<i64>::new());
+}
+
+#[test]
+fn group_create_update_and_delete() {
+    let conn = memory_db();
+    let work = GroupRepository::create(&conn, "Work", None, Some("briefcase"), None).unwrap();
+    let home = GroupRepository::create(&conn, "Home", None, None, None).unwrap();
+    let clients = GroupRepository::create(&conn, "Clients", Some(work.id), None, None).unwrap();
+    assert!(home.sort_order > work.sort_order);
+    let names: Vec<_> = GroupRepository::list(&conn).unwrap().into_iter().map(|g| g.name).collect();
+    assert_eq!(names.len(), 3);
+
+    // 未指定的字段保持不变
+    let renamed = GroupRepository::update(&conn, work.id, Some("Office"), None, None, Some("#123456")).unwrap();
+    assert_eq!(renamed.icon.as_deref(), Some("briefcase"));
+    let loaded = GroupRepository::get(&conn, work.id).unwrap().unwrap();
+    assert_eq!((loaded.name.as_str(), loaded.color.as_deref()), ("Office", Some("#123456")));
+    assert!(GroupRepository::update(&conn, 99, Some("Nope"), None, None, None).unwrap_err().is_not_found());
+
+    // 默认删除子任务组，组内任务移出任务组
+    let todo = TodoRepository::create(&conn, "Invoice", None, Some(clients.id), None, None, 0, None).unwrap();
+    GroupRepository::delete(&conn, work.id).unwrap();
+    assert!(GroupRepository::get(&conn, clients.id).unwrap().is_none());
+    assert_eq!(TodoRepository::get(&conn, todo.id).unwrap().unwrap().group_id, None);
+    let remaining: Vec<_> = GroupRepository::list(&conn).unwrap().into_iter().map(|g| g.id).collect();
+    assert_eq!(remaining, vec![home.id]);
+}
+
+#[test]
+fn group_cannot_move_under_itself_or_a_descendant() {
+    let conn = memory_db();
+    let root = GroupRepository::create(&conn, "Root", None, None, None).unwrap();
+    let child = GroupRepository::create(&conn, "Child", Some(root.id), None, None).unwrap();
+    let grandchild = GroupRepository::create(&conn, "Grandchild", Some(child.id), None, None).unwrap();
+    let other = GroupRepository::create(&conn, "Other", None, None, None).unwrap();
+
+    let invalid = |result: crate::Result<_>| matches!(result, Err(Error::InvalidInput(_)));
+    assert!(invalid(GroupRepository::move_group(&conn, root.id, Some(grandchild.id), None)));
+    assert!(invalid(GroupRepository::move_group(&conn, root.id, Some(root.id), None)));
+    assert!(invalid(GroupRepository::update(&conn, root.id, None, Some(child.id), None, None)));
+    assert!(GroupRepository::move_group(&conn, root.id, Some(99), None).unwrap_err().is_not_found());
+    assert_eq!(GroupRepository::get(&conn, root.id).unwrap().unwrap().parent_id, None);
+
+    // 移到其他分支和顶层都可以，位置决定同级顺序
+    let moved = GroupRepository::move_group(&conn, grandchild.id, Some(other.id), None).unwrap();
+    assert_eq!(moved.parent_id, Some(other.id));
+    let moved = GroupRepository::move_group(&conn, child.id, None, Some(0)).unwrap();
+    assert_eq!(moved.parent_id, None);
+    let names: Vec<_> = GroupRepository::list(&conn).unwrap().into_iter()
+        .filter(|g| g.parent_id.is_none())
+        .map(|g| g.name)
+        .collect();
+    assert_eq!(names, vec!["Child", "Root", "Other"]);
+}
+
+#[test]
+fn group_delete_policies_handle_children_and_todos() {
+    let conn = memory_db();
+    let root = GroupRepository::create(&conn, "Root", None, None, None).unwrap();
+    let target = GroupRepository::create(&conn, "Target", None, None, None).unwrap();
+    let group = GroupRepository::create(&conn, "Group", Some(root.id), None, None).unwrap();
+    let first = GroupRepository::create(&conn, "First", Some(group.id), None, None).unwrap();
+    let second = GroupRepository::create(&conn, "Second", Some(group.id), None, None).unwrap();
+    let direct = TodoRepository::create(&conn, "Direct", None, Some(group.id), None, None, 0, None).unwrap();
+    let nested = TodoRepository::create(&conn, "Nested", None, Some(first.id), None, None, 0, None).unwrap();
+
+    // 移动到正在删除的任务组或不存在的任务组时不做任何修改
+    let result = GroupRepository::delete_with_options(
+        &conn, group.id, GroupChildPolicy::Cascade, GroupTodoPolicy::MoveTo { group_id: first.id },
+    );
+    assert!(matches!(result, Err(Error::InvalidInput(_))));
+    let result = GroupRepository::delete_with_options(
+        &conn, group.id, GroupChildPolicy::Reparent, GroupTodoPolicy::MoveTo { group_id: 99 },
+    );
+    assert!(result.unwrap_err().is_not_found());
+    assert_eq!(GroupRepository::get(&conn, first.id).unwrap().unwrap().parent_id, Some(group.id));
+
+    // 子任务组上移一级，只有直接属于该组的任务被移走
+    let summary = GroupRepository::delete_with_options(
+        &conn, group.id, GroupChildPolicy::Reparent, GroupTodoPolicy::MoveTo { group_id: target.id },
+    ).unwrap();
+    assert_eq!(summary.deleted_group_ids, vec![group.id]);
+    assert_eq!(summary.reparented_group_ids, vec![first.id, second.id]);
+    assert_eq!((summary.moved_todos, summary.affected_todo_ids), (1, vec![direct.id]));
+    assert!(GroupRepository::get(&conn, group.id).unwrap().is_none());
+    assert_eq!(GroupRepository::get(&conn, second.id).unwrap().unwrap().parent_id, Some(root.id));
+    assert_eq!(TodoRepository::get(&conn, direct.id).unwrap().unwrap().group_id, Some(target.id));
+    assert_eq!(TodoRepository::get(&conn, nested.id).unwrap().unwrap().group_id, Some(first.id));
+
+    // 删除整个子树和其中的任务，仍被其他任务引用的附件文件不算孤立
+    let tag = TagRepository::create(&conn, "work", "#0000ff", None).unwrap();
+    let nested_child = GroupRepository::create(&conn, "Nested child", Some(first.id), None, None).unwrap();
+    let deep = TodoRepository::create(&conn, "Deep", None, Some(nested_child.id), None, None, 0, Some(vec![tag.id])).unwrap();
+    StepRepository::create(&conn, deep.id, "Step").unwrap();
+    AttachmentRepository::create(&conn, deep.id, "own.pdf", "attachments/own.pdf", 1, None).unwrap();
+    AttachmentRepository::create(&conn, nested.id, "shared.pdf", "attachments/shared.pdf", 1, None).unwrap();
+    AttachmentRepository::create(&conn, direct.id, "shared.pdf", "attachments/shared.pdf", 1, None).unwrap();
+
+    let summary = GroupRepository::delete_with_options(&conn, first.id, GroupChildPolicy::Cascade, GroupTodoPolicy::Delete)
+        .unwrap();
+    assert_eq!(summary.deleted_group_ids, vec![first.id, nested_child.id]);
+    assert_eq!(summary.deleted_todos, 2);
+    assert_eq!(summary.orphaned_files, vec!["attachments/own.pdf".to_string()]);
+    assert!(GroupRepository::get(&conn, nested_child.id).unwrap().is_none());
+    assert!(TodoRepository::get(&conn, deep.id).unwrap().is_none());
+    assert!(StepRepository::list_all(&conn).unwrap().is_empty());
+    assert_eq!(AttachmentRepository::list_all(&conn).unwrap().len(), 1);
+    assert!(GroupRepository::delete(&conn, first.id).unwrap_err().is_not_found());
+}
+
+#[test]
+fn group_reorder_persists_sort_order() {
+    let conn = memory_db();
+    let a = GroupRepository::create(&conn, "A", None, None, None).unwrap();
+    let b = GroupRepository::create(&conn, "B", None, None, None).unwrap();
+    let c = GroupRepository::create(&conn, "C", None, None, None).unwrap();
+    let child = GroupRepository::create(&conn, "Child", Some(a.id), None, None).unwrap();
+
+    // 未列出的任务组排在最后
+    let groups = GroupRepository::reorder(&conn, None, &[c.id, a.id]).unwrap();
+    assert_eq!(groups.iter().map(|g| g.id).collect::<Vec<_>>(), vec![c.id, a.id, b.id]);
+    let stored: Vec<_> = [c.id, a.id, b.id].iter()
+        .map(|id| GroupRepository::get(&conn, *id).unwrap().unwrap().sort_order)
+        .collect();
+    assert!(stored.windows(2).all(|w| w[0] < w[1]));
+    let top: Vec<_> = GroupRepository::list(&conn).unwrap().into_iter()
+        .filter(|g| g.parent_id.is_none())
+        .map(|g| g.id)
+        .collect();
+    assert_eq!(top, vec![c.id, a.id, b.id]);
+
+    let invalid = |result: crate::Result<_>| matches!(result, Err(Error::InvalidInput(_)));
+    assert!(invalid(GroupRepository::reorder(&conn, None, &[child.id])));
+    assert!(invalid(GroupRepository::reorder(&conn, None, &[b.id, b.id])));
+}
+
+#[test]
+fn tag_update_find_and_delete() {
+    let conn = memory_db();
+    let parent = TagRepository::create(&conn, "home", "#00ff00", None).unwrap();
+    let child = TagRepository::create(&conn, "garden", "#00ff00", Some(parent.id)).unwrap();
+    let other = TagRepository::create(&conn, "work", "#0000ff", None).unwrap();
+    let todo = TodoRepository::create(&conn, "Mow lawn", None, None, None, None, 0, Some(vec![parent.id])).unwrap();
+
+    let renamed = TagRepository::update(&conn, parent.id, Some("house"), Some("#ff00ff"), None).unwrap();
+    assert_eq!((renamed.name.as_str(), renamed.color.as_str()), ("house", "#ff00ff"));
+    assert_eq!(TagRepository::find_by_name(&conn, "HOUSE").unwrap().unwrap().id, parent.id);
+    assert!(TagRepository::find_by_name(&conn, "home").unwrap().is_none());
+    assert!(matches!(TagRepository::update(&conn, child.id, Some("Work"), None, None), Err(Error::Conflict(_))));
+    let tags = TodoRepository::get(&conn, todo.id).unwrap().unwrap().tags.unwrap();
+    assert_eq!(tags[0].name, "house");
+
+    // 删除后子标签挂到上一级，任务不再关联
+    TagRepository::delete(&conn, parent.id).unwrap();
+    assert_eq!(TagRepository::get(&conn, child.id).unwrap().unwrap().parent_id, None);
+    assert!(TodoRepository::get(&conn, todo.id).unwrap().unwrap().tags.unwrap_or_default().is_empty());
+    let ids: Vec<_> = TagRepository::list(&conn).unwrap().into_iter().map(|t| t.id).collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&child.id) && ids.contains(&other.id));
+    assert!(TagRepository::delete(&conn, parent.id).unwrap_err().is_not_found());
+}
+
+#[test]
+fn tag_merge_repoints_links_and_children() {
+    let conn = memory_db();
+    let office = TagRepository::create(&conn, "office", "#000000", None).unwrap();
+    let work = TagRepository::create(&conn, "work", "#0000ff", Some(office.id)).unwrap();
+    let job = TagRepository::create(&conn, "job", "#ff0000", None).unwrap();
+    let meetings = TagRepository::create(&conn, "meetings", "#ff0000", Some(job.id)).unwrap();
+    let only_job = TodoRepository::create(&conn, "Only job", None, None, None, None, 0, Some(vec![job.id])).unwrap();
+    let shared = TodoRepository::create(&conn, "Shared", None, None, None, None, 0, Some(vec![job.id, work.id])).unwrap();
+    let both = TodoRepository::create(&conn, "Both", None, None, None, None, 0, Some(vec![job.id, office.id])).unwrap();
+
+    assert!(TagRepository::merge(&conn, work.id, &[job.id, 99]).unwrap_err().is_not_found());
+    assert!(TagRepository::get(&conn, job.id).unwrap().is_some());
+
+    let (tag, mut newly_tagged) = TagRepository::merge(&conn, work.id, &[job.id, office.id, work.id, job.id]).unwrap();
+    newly_tagged.sort();
+    assert_eq!(newly_tagged, vec![only_job.id, both.id]);
+    // 目标原本挂在被合并的标签下，上移到保留的祖先（顶层）
+    assert_eq!((tag.id, tag.parent_id), (work.id, None));
+    assert!(TagRepository::get(&conn, job.id).unwrap().is_none());
+    assert!(TagRepository::get(&conn, office.id).unwrap().is_none());
+    assert_eq!(TagRepository::get(&conn, meetings.id).unwrap().unwrap().parent_id, Some(work.id));
+
+    // 已经关联目标标签的任务不会出现重复关联
+    for todo in [&only_job, &shared, &both] {
+        let tags = TodoRepository::get(&conn, todo.id).unwrap().unwrap().tags.unwrap();
+        assert_eq!(tags.iter().map(|t| t.id).collect::<Vec<_>>(), vec![work.id]);
+    }
+    let links: i64 = conn.query_row("SELECT COUNT(*) FROM todo_tags", [], |row| row.get(0)).unwrap();
+    assert_eq!(links, 3);
+
+    let (_, newly_tagged) = TagRepository::merge(&conn, work.id, &[]).unwrap();
+    assert!(newly_tagged.is_empty());
+}
+
+#[test]
+fn tag_usage_counts_direct_and_nested_todos() {
+    let conn = memory_db();
+    let home = TagRepository::create(&conn, "home", "#00ff00", None).unwrap();
+    let garden = TagRepository::create(&conn, "garden", "#00ff00", Some(home.id)).unwrap();
+    let lawn = TagRepository::create(&conn, "lawn", "#00ff00", Some(garden.id)).unwrap();
+    TagRepository::create(&conn, "idle", "#cccccc", None).unwrap();
+    let cleaning = TodoRepository::create(&conn, "Clean", None, None, None, None, 0, Some(vec![home.id])).unwrap();
+    TodoRepository::create(&conn, "Weed", None, None, None, None, 0, Some(vec![garden.id])).unwrap();
+    let mowing = TodoRepository::create(&conn, "Mow", None, None, None, None, 0, Some(vec![lawn.id, home.id])).unwrap();
+    TodoRepository::update_status(&conn, cleaning.id, status::DONE).unwrap();
+    conn.execute("UPDATE todos SET created_at = 1000, updated_at = 5000 WHERE id = ?", [cleaning.id]).unwrap();
+    conn.execute("UPDATE todos SET updated_at = 9000 WHERE id = ?", [mowing.id]).unwrap();
+
+    let usage = |start: Option<i64>| {
+        StatsRepository::get_tag_usage(&conn, start, None).unwrap().into_iter()
+            .map(|s| (s.name, s.count, s.done, s.total_with_children))
+            .collect::<Vec<_>>()
+    };
+    // 同一任务在子标签和父标签上都有关联时只计一次
+    assert_eq!(usage(None), vec![
+        ("home".to_string(), 2, 1, 3),
+        ("garden".to_string(), 1, 0, 2),
+        ("lawn".to_string(), 1, 0, 1),
+        ("idle".to_string(), 0, 0, 0),
+    ]);
+    assert_eq!(usage(Some(2000))[0], ("garden".to_string(), 1, 0, 2));
+    assert_eq!(usage(Some(2000))[1], ("home".to_string(), 1, 0, 2));
+
+    let report = StatsRepository::get_tag_usage(&conn, None, None).unwrap();
+    assert_eq!(report[0].last_used_at, Some(9000));
+    assert_eq!(report[2].parent_id, Some(garden.id.to_string()));
+    assert_eq!(report[3].last_used_at, None);
+}
+
+#[test]
+fn duplicate_todo_copies_tags_steps_and_attachments() {
+    let conn = memory_db();
+    let work = GroupRepository::create(&conn, "Work", None, None, None).unwrap();
+    let archive = GroupRepository::create(&conn, "Archive", None, None, None).unwrap();
+    let tag = TagRepository::create(&conn, "urgent", "#ff0000", None).unwrap();
+    let todo = TodoRepository::create(&conn, "Write report", Some("Q3"), Some(work.id), Some(1_000), Some(2_000), 2, Some(vec![tag.id]))
+        .unwrap();
+    let outline = StepRepository::create(&conn, todo.id, "Outline").unwrap();
+    StepRepository::create(&conn, todo.id, "Draft").unwrap();
+    StepRepository::toggle(&conn, outline.id).unwrap();
+    let attachment = AttachmentRepository::create(&conn, todo.id, "a.pdf", "attachments/a.pdf", 10, Some("application/pdf"))
+        .unwrap();
+    let source = TodoRepository::update_status(&conn, todo.id, status::DONE).unwrap();
+    let source = TodoRepository::get(&conn, source.id).unwrap().unwrap();
+
+    // 保留状态和日期，复制出的附件使用新路径
+    let paths = HashMap::from([(attachment.id, "attachments/a-copy.pdf".to_string())]);
+    let copy = TodoRepository::duplicate(&conn, todo.id, Some(archive.id), false, false, &paths).unwrap();
+    assert_ne!((copy.id, copy.uid.as_str()), (source.id, source.uid.as_str()));
+    assert_eq!((copy.title.as_str(), copy.description.as_deref(), copy.priority), ("Write report", Some("Q3"), 2));
+    assert_eq!(copy.group_id, Some(archive.id));
+    assert_eq!((copy.status, copy.completed_at), (source.status, source.completed_at));
+    assert_eq!((copy.start_date, copy.due_date), (Some(1_000), Some(2_000)));
+    assert_eq!(copy.tags.unwrap().iter().map(|t| t.id).collect::<Vec<_>>(), vec![tag.id]);
+    let steps = copy.steps.unwrap();
+    let source_steps = source.steps.unwrap();
+    assert_eq!(
+        steps.iter().map(|s| (s.title.as_str(), s.is_completed)).collect::<Vec<_>>(),
+        vec![("Outline", true), ("Draft", false)],
+    );
+    assert!(steps.iter().all(|s| source_steps.iter().all(|o| o.uid != s.uid)));
+    let attachments = copy.attachments.unwrap();
+    assert_eq!((attachments[0].name.as_str(), attachments[0].file_path.as_str()), ("a.pdf", "attachments/a-copy.pdf"));
+    assert_ne!(attachments[0].uid, attachment.uid);
+    let original = TodoRepository::get(&conn, todo.id).unwrap().unwrap();
+    assert_eq!(original.attachments.unwrap()[0].file_path, "attachments/a.pdf");
+
+    // 重置状态和日期，未指定任务组时留在原任务组，附件共享原文件
+    let reset = TodoRepository::duplicate(&conn, todo.id, None, true, true, &HashMap::new()).unwrap();
+    assert_eq!(reset.group_id, Some(work.id));
+    assert_eq!((reset.status as i32, reset.completed_at), (status::TODO, None));
+    assert_eq!((reset.start_date, reset.due_date), (None, None));
+    assert!(reset.steps.unwrap().iter().all(|s| !s.is_completed));
+    assert_eq!(reset.attachments.unwrap()[0].file_path, "attachments/a.pdf");
+
+    assert!(TodoRepository::duplicate(&conn, 99, None, false, false, &HashMap::new()).unwrap_err().is_not_found());
+}
+
+#[test]
+fn duplicate_group_copies_its_todos() {
+    let conn = memory_db();
+    let parent = GroupRepository::create(&conn, "Org", None, None, None).unwrap();
+    let work = GroupRepository::create(&conn, "Work", Some(parent.id), Some("briefcase"), None).unwrap();
+    let report = TodoRepository::create(&conn, "Write report", None, Some(work.id), None, Some(2_000), 0, None).unwrap();
+    let invoice = TodoRepository::create(&conn, "Send invoice", None, Some(work.id), None, None, 0, None).unwrap();
+    TodoRepository::create(&conn, "Elsewhere", None, None, None, None, 0, None).unwrap();
+    TodoRepository::update_status(&conn, report.id, status::DONE).unwrap();
+    let attachment = AttachmentRepository::create(&conn, invoice.id, "invoice.pdf", "attachments/invoice.pdf", 10, None)
+        .unwrap();
+
+    let paths = HashMap::from([(attachment.id, "attachments/invoice-copy.pdf".to_string())]);
+    let copy = GroupRepository::duplicate(&conn, work.id, None, true, false, &paths).unwrap();
+    assert_ne!(copy.uid, work.uid);
+    assert_eq!((copy.name.as_str(), copy.parent_id, copy.icon.as_deref()), ("Work (copy)", Some(parent.id), Some("briefcase")));
+
+    let copied = TodoRepository::list_with_filters(&conn, Some(vec![copy.id]), None, None, None, None, None, None).unwrap();
+    let mut todos: Vec<_> = copied.iter().map(|t| TodoRepository::get(&conn, t.id).unwrap().unwrap()).collect();
+    todos.sort_by_key(|t| t.title.clone());
+    assert_eq!(todos.iter().map(|t| t.title.as_str()).collect::<Vec<_>>(), vec!["Send invoice", "Write report"]);
+    assert!(todos.iter().all(|t| t.status as i32 == status::TODO && t.uid != report.uid && t.uid != invoice.uid));
+    assert_eq!(todos[1].due_date, Some(2_000));
+    assert_eq!(todos[0].attachments.as_ref().unwrap()[0].file_path, "attachments/invoice-copy.pdf");
+    let originals = TodoRepository::list_with_filters(&conn, Some(vec![work.id]), None, None, None, None, None, None).unwrap();
+    assert_eq!(originals.len(), 2);
+
+    let named = GroupRepository::duplicate(&conn, work.id, Some("Archive"), false, false, &HashMap::new()).unwrap();
+    assert_eq!(named.name, "Archive");
+    let blank = GroupRepository::duplicate(&conn, work.id, Some("  "), false, false, &HashMap::new()).unwrap();
+    assert_eq!(blank.name, "Work (copy)");
+    assert!(GroupRepository::duplicate(&conn, 99, None, false, false, &HashMap::new()).unwrap_err().is_not_found());
+}
+
+#[test]
+fn errors_are_typed() {
+    let conn = memory_db();
+    assert!(TodoRepository::delete(&conn, 42).unwrap_err().is_not_found());
+    assert!(TodoRepository::get(&conn, 42).unwrap().is_none());
+
+    let parent = TagRepository::create(&conn, "home", "#00ff00", None).unwrap();
+    let child = TagRepository::create(&conn, "garden", "#00ff00", Some(parent.id)).unwrap();
+    assert!(matches!(TagRepository::create(&conn, "HOME", "#000000", None), Err(Error::Conflict(_))));
+    assert!(matches!(
+        TagRepository::update(&conn, parent.id, None, None, Some(child.id)),
+        Err(Error::InvalidInput(_))
+    ));
+}
+
+#[test]
+fn export_and_clear_all() {
+    let conn = memory_db();
+    let todo = TodoRepository::create(&conn, "Buy milk", None, None, None, None, 0, None).unwrap();
+    StepRepository::create(&conn, todo.id, "Go to the shop").unwrap();
+
+    let exported = DataRepository::export_all(&conn).unwrap();
+    assert_eq!(exported.todos.len(), 1);
+
+    DataRepository::clear_all(&conn).unwrap();
+    assert!(TodoRepository::get(&conn, todo.id).unwrap().is_none());
+    assert!(StepRepository::list_all(&conn).unwrap().is_empty());
+}
+
+#[test]
+fn imports_match_rows_by_uid() {
+    let source = memory_db();
+    let group = GroupRepository::create(&source, "Work", None, None, None).unwrap();
+    let tag = TagRepository::create(&source, "urgent", "#ff0000", None).unwrap();
+    let todo = TodoRepository::create(&source, "Write report", None, Some(group.id), None, None, 1, Some(vec![tag.id]))
+        .unwrap();
+    let exported = DataRepository::export_all(&source).unwrap();
+
+    // 目标库中已有占用相同 ID 的其他记录
+    let target = memory_db();
+    let local_group = GroupRepository::create(&target, "Home", None, None, None).unwrap();
+    let local_todo = TodoRepository::create(&target, "Water plants", None, Some(local_group.id), None, None, 0, None).unwrap();
+    assert_eq!((local_group.id, local_todo.id), (group.id, todo.id));
+
+    DataRepository::import_data(&target, &exported, ImportOptions::default()).unwrap();
+    DataRepository::import_data(&target, &exported, ImportOptions::default()).unwrap();
+
+    let todos = TodoRepository::list(&target, None, None, None, None, None, None, None).unwrap();
+    assert_eq!(todos.len(), 2);
+    let local = todos.iter().find(|t| t.id == local_todo.id).unwrap();
+    assert_eq!((local.title.as_str(), local.group_id), ("Water plants", Some(local_group.id)));
+
+    let imported = todos.iter().find(|t| t.uid == todo.uid).unwrap();
+    assert_ne!(imported.id, todo.id);
+    let imported_group = GroupRepository::get(&target, imported.group_id.unwrap()).unwrap().unwrap();
+    assert_eq!((imported_group.name.as_str(), imported_group.uid.as_str()), ("Work", group.uid.as_str()));
+    assert_eq!(imported.tags.as_ref().unwrap()[0].uid, tag.uid);
+}
+
+#[test]
+fn csv_archives_keep_uids() {
+    let source = memory_db();
+    let parent = TagRepository::create(&source, "home", "#00ff00", None).unwrap();
+    let child = TagRepository::create(&source, "garden", "#00ff00", Some(parent.id)).unwrap();
+    let todo = TodoRepository::create(&source, "Mow lawn", None, None, None, None, 0, Some(vec![child.id])).unwrap();
+    let step = StepRepository::create(&source, todo.id, "Sharpen blades").unwrap();
+    let archive = DataRepository::export_archive(&source, std::path::Path::new("missing"), None).unwrap();
+    let csv_data = crate::utils::data_export::extract_csv_from_zip(archive).unwrap();
+
+    let target = memory_db();
+    TagRepository::create(&target, "work", "#0000ff", None).unwrap();
+    DataRepository::import_from_csv(&target, &csv_data, ImportOptions::default()).unwrap();
+
+    let imported = TodoRepository::list(&target, None, None, None, None, None, None, None).unwrap().remove(0);
+    assert_eq!(imported.uid, todo.uid);
+    let tag = &imported.tags.unwrap()[0];
+    assert_eq!(tag.uid, child.uid);
+    let tag_parent = TagRepository::get(&target, tag.parent_id.unwrap()).unwrap().unwrap();
+    assert_eq!(tag_parent.uid, parent.uid);
+    assert_eq!(StepRepository::list_by_todo(&target, imported.id).unwrap()[0].uid, step.uid);
+}
@@ -2,17 +2,21 @@
 // SPDX-License-Identifier: MIT
 
 use rusqlite::{Connection, params, OptionalExtension};
-use anyhow::{Result, Context};
+use crate::error::{Context, Error, Result};
 use chrono::Utc;
+use std::collections::HashMap;
 
 use crate::models::{Todo, TodoStatus, Tag, TodoStep, Attachment, TaskGroup};
 use crate::models::constants::status;
+use crate::database::repositories::tag_repo::TODO_HAS_TAG_TREE_SQL;
+use crate::utils::new_uuid;
 
 /// Todo 仓库
 pub struct TodoRepository;
 
 impl TodoRepository {
     /// 获取任务列表（支持筛选）
+    #[allow(clippy::too_many_arguments)]
     pub fn list(
         conn: &Connection,
         group_id: Option<i64>,
@@ -42,14 +46,9 @@ impl TodoRepository {
             params.push(Box::new(s));
         }
 
-        // 按标签筛选（需要 JOIN）
+        // 按标签筛选（包含子标签）
         if let Some(tid) = tag_id {
-            where_clauses.push(
-                "EXISTS (
-                    SELECT 1 FROM todo_tags tt
-                    WHERE tt.todo_id = t.id AND tt.tag_id = ?
-                )"
-            );
+            where_clauses.push(TODO_HAS_TAG_TREE_SQL);
             params.push(Box::new(tid));
         }
 
@@ -132,6 +131,7 @@ impl TodoRepository {
             let status = TodoStatus::from_i32(status_int);
             Ok(Todo {
                 id: row.get("id")?,
+                uid: row.get("uid")?,
                 title: row.get("title")?,
                 description: row.get("description")?,
                 status,
@@ -188,6 +188,7 @@ impl TodoRepository {
 
             Ok(Todo {
                 id: row.get("id")?,
+                uid: row.get("uid")?,
                 title: row.get("title")?,
                 description: row.get("description")?,
                 status,
@@ -222,6 +223,9 @@ impl TodoRepository {
     }
 
     /// 创建新任务
+    ///
+    /// 不执行自动化规则，由调用方在创建后用 `RuleEngine::run_all` 执行 `Created` 事件
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         conn: &Connection,
         title: &str,
@@ -242,11 +246,12 @@ impl TodoRepository {
 
         conn.execute(
             "INSERT INTO todos (
-                title, description, status, priority,
+                uid, title, description, status, priority,
                 group_id, start_date, due_date,
                 created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
+                new_uuid(),
                 title,
                 description,
                 status as i32,
@@ -284,6 +289,7 @@ impl TodoRepository {
     }
 
     /// 更新任务
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         conn: &Connection,
         id: i64,
@@ -450,13 +456,14 @@ impl TodoRepository {
         .context("Failed to delete todo")?;
 
         if rows_affected == 0 {
-            anyhow::bail!("Todo not found");
+            return Err(Error::NotFound("Todo not found".to_string()));
         }
 
         Ok(())
     }
 
     /// 支持多任务组和多标签筛选的任务列表
+    #[allow(clippy::too_many_arguments)]
     pub fn list_with_filters(
         conn: &Connection,
         group_ids: Option<Vec<i64>>,
@@ -491,11 +498,11 @@ impl TodoRepository {
             params.push(Box::new(s));
         }
 
-        // 多标签筛选（任务满足任一标签即可）
+        // 多标签筛选（任务满足任一标签或其子标签即可）
         if let Some(tids) = &tag_ids {
             if !tids.is_empty() {
                 let tag_conditions: Vec<String> = tids.iter().map(|_| {
-                    "EXISTS (SELECT 1 FROM todo_tags tt WHERE tt.todo_id = t.id AND tt.tag_id = ?)".to_string()
+                    TODO_HAS_TAG_TREE_SQL.to_string()
                 }).collect();
                 where_clauses.push(format!("({})", tag_conditions.join(" OR ")));
                 for tid in tids {
@@ -568,6 +575,7 @@ impl TodoRepository {
             let status = TodoStatus::from_i32(status_int);
             Ok(Todo {
                 id: row.get("id")?,
+                uid: row.get("uid")?,
                 title: row.get("title")?,
                 description: row.get("description")?,
                 status,
@@ -613,13 +621,157 @@ impl TodoRepository {
         Self::get(conn, id)?.context("Updated todo not found")
     }
 
+    /// 复制任务（连同步骤、标签关联和附件记录）
+    ///
+    /// `group_id` 为 None 时保留原任务组；`attachment_paths` 为原附件 ID 到新文件相对路径的映射，
+    /// 未出现在映射中的附件与原任务共享同一个文件
+    pub fn duplicate(
+        conn: &Connection,
+        id: i64,
+        group_id: Option<i64>,
+        reset_status: bool,
+        reset_dates: bool,
+        attachment_paths: &HashMap<i64, String>,
+    ) -> Result<Todo> {
+        let transaction = conn.unchecked_transaction()?;
+
+        let new_id = Self::copy_todo(&transaction, id, group_id, reset_status, reset_dates, attachment_paths)?;
+
+        transaction.commit()?;
+
+        Self::get(conn, new_id)?.context("Duplicated todo not found")
+    }
+
+    /// 在当前连接（或事务）中复制单个任务，返回新任务 ID
+    pub(crate) fn copy_todo(
+        conn: &Connection,
+        id: i64,
+        group_id: Option<i64>,
+        reset_status: bool,
+        reset_dates: bool,
+        attachment_paths: &HashMap<i64, String>,
+    ) -> Result<i64> {
+        let source = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Todo not found: {}", id)))?;
+
+        let now = Utc::now().timestamp_millis();
+        let (status, completed_at) = if reset_status {
+            (TodoStatus::Todo, None)
+        } else {
+            (source.status, source.completed_at)
+        };
+        let (start_date, due_date) = if reset_dates {
+            (None, None)
+        } else {
+            (source.start_date, source.due_date)
+        };
+        let group_id = group_id.or(source.group_id);
+
+        conn.execute(
+            "INSERT INTO todos (
+                uid, title, description, status, priority,
+                group_id, assignee, start_date, due_date, completed_at,
+                created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                new_uuid(),
+                source.title,
+                source.description,
+                status as i32,
+                source.priority,
+                group_id,
+                source.assignee,
+                start_date,
+                due_date,
+                completed_at,
+                now,
+                now,
+            ],
+        )
+        .context("Failed to insert duplicated todo")?;
+
+        let new_id: i64 = conn.last_insert_rowid();
+
+        // 复制标签关联
+        for tag in source.tags.iter().flatten() {
+            conn.execute(
+                "INSERT INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)",
+                params![new_id, tag.id],
+            )
+            .context("Failed to duplicate todo_tags")?;
+        }
+
+        // 复制步骤
+        for step in source.steps.iter().flatten() {
+            let is_completed = !reset_status && step.is_completed;
+            conn.execute(
+                "INSERT INTO todo_steps (uid, todo_id, title, is_completed, sort_order, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![new_uuid(), new_id, step.title, is_completed as i32, step.sort_order, now],
+            )
+            .context("Failed to duplicate step")?;
+        }
+
+        // 复制附件记录
+        for attachment in source.attachments.iter().flatten() {
+            let file_path = attachment_paths
+                .get(&attachment.id)
+                .unwrap_or(&attachment.file_path);
+            conn.execute(
+                "INSERT INTO attachments (uid, todo_id, name, file_path, file_size, mime_type, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![new_uuid(), new_id, attachment.name, file_path, attachment.file_size, attachment.mime_type, now],
+            )
+            .context("Failed to duplicate attachment")?;
+        }
+
+        tracing::info!("[TodoRepository::copy_todo] Duplicated todo {} as {}", id, new_id);
+        Ok(new_id)
+    }
+
+    /// 为任务添加单个标签，返回是否为新增的关联
+    pub fn add_tag(conn: &Connection, todo_id: i64, tag_id: i64) -> Result<bool> {
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)",
+            params![todo_id, tag_id],
+        )
+        .context("Failed to insert todo_tags")?;
+
+        if inserted > 0 {
+            conn.execute(
+                "UPDATE todos SET updated_at = ?1 WHERE id = ?2",
+                params![Utc::now().timestamp_millis(), todo_id],
+            )
+            .context("Failed to update todo updated_at")?;
+        }
+
+        Ok(inserted > 0)
+    }
+
+    /// 获取截止时间在 `[from, to]` 内的未完成任务，返回 (任务 ID, 截止时间)
+    pub fn list_due_between(conn: &Connection, from: i64, to: i64) -> Result<Vec<(i64, i64)>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, due_date FROM todos
+             WHERE due_date IS NOT NULL AND due_date >= ?1 AND due_date <= ?2 AND status != ?3
+             ORDER BY due_date ASC"
+        )
+        .context("Failed to prepare due todos query")?;
+
+        let todos = stmt.query_map(params![from, to, status::DONE], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context("Failed to execute due todos query")?
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("Failed to parse due todos")?;
+
+        Ok(todos)
+    }
+
     // ========== 辅助方法 ==========
 
     /// 加载关联数据（标签、步骤、附件、任务组）
     fn load_relations(conn: &Connection, todo: &mut Todo) -> Result<()> {
         // 加载标签
         let mut tag_stmt = conn.prepare(
-            "SELECT t.id, t.name, t.color, t.created_at
+            "SELECT t.id, t.name, t.color, t.parent_id, t.created_at, t.uid
              FROM tags t
              JOIN todo_tags tt ON t.id = tt.tag_id
              WHERE tt.todo_id = ?"
@@ -629,9 +781,11 @@ impl TodoRepository {
         let tags = tag_stmt.query_map(params![todo.id], |row| {
             Ok(Tag {
                 id: row.get(0)?,
+                uid: row.get(5)?,
                 name: row.get(1)?,
                 color: row.get(2)?,
-                created_at: row.get(3)?,
+                parent_id: row.get(3)?,
+                created_at: row.get(4)?,
             })
         })
         .context("Failed to execute tags query")?
@@ -644,7 +798,7 @@ impl TodoRepository {
 
         // 加载步骤
         let mut step_stmt = conn.prepare(
-            "SELECT id, todo_id, title, is_completed, sort_order, created_at
+            "SELECT id, todo_id, title, is_completed, sort_order, created_at, uid
              FROM todo_steps
              WHERE todo_id = ?
              ORDER BY sort_order ASC"
@@ -654,6 +808,7 @@ impl TodoRepository {
         let steps = step_stmt.query_map(params![todo.id], |row| {
             Ok(TodoStep {
                 id: row.get(0)?,
+                uid: row.get(6)?,
                 todo_id: row.get(1)?,
                 title: row.get(2)?,
                 is_completed: row.get::<_, i32>(3)? == 1,
@@ -671,7 +826,7 @@ impl TodoRepository {
 
         // 加载附件
         let mut attachment_stmt = conn.prepare(
-            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at
+            "SELECT id, todo_id, name, file_path, file_size, mime_type, created_at, uid
              FROM attachments
              WHERE todo_id = ?"
         )
@@ -680,6 +835,7 @@ impl TodoRepository {
         let attachments = attachment_stmt.query_map(params![todo.id], |row| {
             Ok(Attachment {
                 id: row.get(0)?,
+                uid: row.get(7)?,
                 todo_id: row.get(1)?,
                 name: row.get(2)?,
                 file_path: row.get(3)?,
@@ -699,13 +855,14 @@ impl TodoRepository {
         // 加载任务组
         if let Some(gid) = &todo.group_id {
             let group_opt = conn.query_row(
-                "SELECT id, name, parent_id, icon, color, sort_order, created_at, updated_at
+                "SELECT id, name, parent_id, icon, color, sort_order, created_at, updated_at, uid
                  FROM task_groups
                  WHERE id = ?",
                 params![gid],
                 |row| {
                     Ok(TaskGroup {
                         id: row.get(0)?,
+                        uid: row.get(8)?,
                         name: row.get(1)?,
                         parent_id: row.get(2)?,
                         icon: row.get(3)?,
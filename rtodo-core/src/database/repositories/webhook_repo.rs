@@ -0,0 +1,317 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use rusqlite::{Connection, params, OptionalExtension, Row};
+use crate::error::{Context, Error, Result};
+use chrono::Utc;
+
+use crate::models::{Webhook, WebhookDelivery, DeliveryStatus};
+
+/// 已完成（成功或最终失败）的投递记录最多保留的条数
+const MAX_FINISHED_DELIVERIES: i64 = 2000;
+
+/// Webhook 仓库
+pub struct WebhookRepository;
+
+impl WebhookRepository {
+    /// 获取所有 Webhook
+    pub fn list(conn: &Connection) -> Result<Vec<Webhook>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, url, secret, events, enabled, created_at, updated_at
+             FROM webhooks
+             ORDER BY id ASC"
+        )
+        .context("Failed to prepare list webhooks query")?;
+
+        let rows = stmt.query_map([], Self::read_webhook)
+            .context("Failed to execute list webhooks query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to read webhooks")?;
+
+        rows.into_iter().map(Self::parse_webhook).collect()
+    }
+
+    /// 根据 ID 获取 Webhook
+    pub fn get(conn: &Connection, id: i64) -> Result<Option<Webhook>> {
+        let row = conn.query_row(
+            "SELECT id, name, url, secret, events, enabled, created_at, updated_at
+             FROM webhooks WHERE id = ?",
+            params![id],
+            Self::read_webhook,
+        )
+        .optional()
+        .context("Failed to execute get webhook query")?;
+
+        row.map(Self::parse_webhook).transpose()
+    }
+
+    /// 创建 Webhook
+    pub fn create(
+        conn: &Connection,
+        name: &str,
+        url: &str,
+        secret: Option<&str>,
+        events: &[String],
+        enabled: bool,
+    ) -> Result<Webhook> {
+        Self::ensure_valid_url(url)?;
+        let now = Utc::now().timestamp_millis();
+
+        conn.execute(
+            "INSERT INTO webhooks (name, url, secret, events, enabled, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![name, url, secret, serde_json::to_string(events)?, enabled as i32, now, now],
+        )
+        .context("Failed to insert webhook")?;
+
+        let id = conn.last_insert_rowid();
+        Self::get(conn, id)?.context("Created webhook not found")
+    }
+
+    /// 更新 Webhook；`secret` 为 Some(None) 时清除密钥
+    pub fn update(
+        conn: &Connection,
+        id: i64,
+        name: Option<&str>,
+        url: Option<&str>,
+        secret: Option<Option<&str>>,
+        events: Option<&[String]>,
+        enabled: Option<bool>,
+    ) -> Result<Webhook> {
+        let existing = Self::get(conn, id)?
+            .ok_or_else(|| Error::NotFound(format!("Webhook not found: {}", id)))?;
+
+        if let Some(u) = url {
+            Self::ensure_valid_url(u)?;
+        }
+
+        let now = Utc::now().timestamp_millis();
+
+        conn.execute(
+            "UPDATE webhooks SET name = ?1, url = ?2, secret = ?3, events = ?4, enabled = ?5, updated_at = ?6
+             WHERE id = ?7",
+            params![
+                name.unwrap_or(existing.name.as_str()),
+                url.unwrap_or(existing.url.as_str()),
+                secret.unwrap_or(existing.secret.as_deref()),
+                serde_json::to_string(events.unwrap_or(&existing.events))?,
+                enabled.unwrap_or(existing.enabled) as i32,
+                now,
+                id,
+            ],
+        )
+        .context("Failed to update webhook")?;
+
+        Self::get(conn, id)?.context("Updated webhook not found")
+    }
+
+    /// 删除 Webhook（连同投递记录）
+    pub fn delete(conn: &Connection, id: i64) -> Result<()> {
+        conn.execute("DELETE FROM webhook_deliveries WHERE webhook_id = ?", params![id])
+            .context("Failed to delete webhook deliveries")?;
+
+        let rows_affected = conn.execute("DELETE FROM webhooks WHERE id = ?", params![id])
+            .context("Failed to delete webhook")?;
+
+        if rows_affected == 0 {
+            return Err(Error::NotFound("Webhook not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // ========== 投递队列 ==========
+
+    /// 为订阅了该事件的所有已启用 Webhook 创建投递记录，返回创建的数量
+    pub fn enqueue(conn: &Connection, event: &str, payload: &str) -> Result<usize> {
+        let now = Utc::now().timestamp_millis();
+        let mut count = 0;
+
+        for webhook in Self::list(conn)? {
+            if !webhook.enabled || !webhook.subscribes(event) {
+                continue;
+            }
+            conn.execute(
+                "INSERT INTO webhook_deliveries (webhook_id, event, payload, status, attempts, next_attempt_at, created_at)
+                 VALUES (?1, ?2, ?3, ?4, 0, ?5, ?6)",
+                params![webhook.id, event, payload, DeliveryStatus::Pending.as_str(), now, now],
+            )
+            .context("Failed to insert webhook delivery")?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// 获取已到重试时间的待投递记录
+    pub fn list_due(conn: &Connection, now: i64, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, webhook_id, event, payload, status, attempts, next_attempt_at, last_error,
+                    response_status, created_at, delivered_at
+             FROM webhook_deliveries
+             WHERE status = ?1 AND next_attempt_at <= ?2
+             ORDER BY next_attempt_at ASC, id ASC
+             LIMIT ?3"
+        )
+        .context("Failed to prepare due deliveries query")?;
+
+        let deliveries = stmt.query_map(params![DeliveryStatus::Pending.as_str(), now, limit], Self::read_delivery)
+            .context("Failed to execute due deliveries query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse deliveries")?;
+
+        Ok(deliveries)
+    }
+
+    /// 最近的待投递时间（用于决定工作线程的休眠时长）
+    pub fn next_attempt_at(conn: &Connection) -> Result<Option<i64>> {
+        let next: Option<i64> = conn.query_row(
+            "SELECT MIN(next_attempt_at) FROM webhook_deliveries WHERE status = ?",
+            params![DeliveryStatus::Pending.as_str()],
+            |row| row.get(0),
+        )
+        .context("Failed to query next delivery time")?;
+
+        Ok(next)
+    }
+
+    /// 获取投递记录（按时间倒序）
+    pub fn list_deliveries(conn: &Connection, webhook_id: Option<i64>, limit: i64) -> Result<Vec<WebhookDelivery>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, webhook_id, event, payload, status, attempts, next_attempt_at, last_error,
+                    response_status, created_at, delivered_at
+             FROM webhook_deliveries
+             WHERE ?1 IS NULL OR webhook_id = ?1
+             ORDER BY id DESC
+             LIMIT ?2"
+        )
+        .context("Failed to prepare list deliveries query")?;
+
+        let deliveries = stmt.query_map(params![webhook_id, limit], Self::read_delivery)
+            .context("Failed to execute list deliveries query")?
+            .collect::<Result<Vec<_>, _>>()
+            .context("Failed to parse deliveries")?;
+
+        Ok(deliveries)
+    }
+
+    /// 标记投递成功
+    pub fn mark_delivered(conn: &Connection, id: i64, response_status: u16) -> Result<()> {
+        let now = Utc::now().timestamp_millis();
+
+        conn.execute(
+            "UPDATE webhook_deliveries SET status = ?1, attempts = attempts + 1, response_status = ?2,
+             last_error = NULL, delivered_at = ?3 WHERE id = ?4",
+            params![DeliveryStatus::Delivered.as_str(), response_status, now, id],
+        )
+        .context("Failed to mark delivery delivered")?;
+
+        Self::prune(conn)
+    }
+
+    /// 记录一次失败的投递；`next_attempt_at` 为 None 表示不再重试
+    pub fn mark_attempt_failed(
+        conn: &Connection,
+        id: i64,
+        error: &str,
+        response_status: Option<u16>,
+        next_attempt_at: Option<i64>,
+    ) -> Result<()> {
+        let status = if next_attempt_at.is_some() { DeliveryStatus::Pending } else { DeliveryStatus::Failed };
+
+        conn.execute(
+            "UPDATE webhook_deliveries SET status = ?1, attempts = attempts + 1, last_error = ?2,
+             response_status = ?3, next_attempt_at = COALESCE(?4, next_attempt_at) WHERE id = ?5",
+            params![status.as_str(), error, response_status, next_attempt_at, id],
+        )
+        .context("Failed to update delivery attempt")?;
+
+        Self::prune(conn)
+    }
+
+    /// 将失败的投递重新放回队列
+    pub fn retry(conn: &Connection, id: i64) -> Result<()> {
+        let now = Utc::now().timestamp_millis();
+
+        let rows_affected = conn.execute(
+            "UPDATE webhook_deliveries SET status = ?1, attempts = 0, next_attempt_at = ?2 WHERE id = ?3",
+            params![DeliveryStatus::Pending.as_str(), now, id],
+        )
+        .context("Failed to retry delivery")?;
+
+        if rows_affected == 0 {
+            return Err(Error::NotFound(format!("Delivery not found: {}", id)));
+        }
+
+        Ok(())
+    }
+
+    // ========== 辅助方法 ==========
+
+    /// 只保留最近的已完成投递记录，待投递的记录不会被删除
+    fn prune(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "DELETE FROM webhook_deliveries WHERE status != ?1 AND id NOT IN (
+                SELECT id FROM webhook_deliveries WHERE status != ?1 ORDER BY id DESC LIMIT ?2
+             )",
+            params![DeliveryStatus::Pending.as_str(), MAX_FINISHED_DELIVERIES],
+        )
+        .context("Failed to prune webhook deliveries")?;
+
+        Ok(())
+    }
+
+    fn ensure_valid_url(url: &str) -> Result<()> {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(Error::InvalidInput("Webhook URL must start with http:// or https://".to_string()));
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn read_webhook(row: &Row<'_>) -> rusqlite::Result<(i64, String, String, Option<String>, String, i32, i64, i64)> {
+        Ok((
+            row.get(0)?,
+            row.get(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            row.get(4)?,
+            row.get(5)?,
+            row.get(6)?,
+            row.get(7)?,
+        ))
+    }
+
+    fn parse_webhook(
+        (id, name, url, secret, events, enabled, created_at, updated_at):
+            (i64, String, String, Option<String>, String, i32, i64, i64),
+    ) -> Result<Webhook> {
+        Ok(Webhook {
+            id,
+            name,
+            url,
+            secret: secret.filter(|s| !s.is_empty()),
+            events: serde_json::from_str(&events)
+                .context(format!("Failed to parse events of webhook {}", id))?,
+            enabled: enabled != 0,
+            created_at,
+            updated_at,
+        })
+    }
+
+    fn read_delivery(row: &Row<'_>) -> rusqlite::Result<WebhookDelivery> {
+        Ok(WebhookDelivery {
+            id: row.get(0)?,
+            webhook_id: row.get(1)?,
+            event: row.get(2)?,
+            payload: row.get(3)?,
+            status: DeliveryStatus::from_str_lossy(&row.get::<_, String>(4)?),
+            attempts: row.get(5)?,
+            next_attempt_at: row.get(6)?,
+            last_error: row.get(7)?,
+            response_status: row.get(8)?,
+            created_at: row.get(9)?,
+            delivered_at: row.get(10)?,
+        })
+    }
+}
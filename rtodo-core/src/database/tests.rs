@@ -0,0 +1,177 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn temp_db() -> (PathBuf, Database) {
+    let dir = std::env::temp_dir().join(format!("rtodo-db-{}", uuid::Uuid::new_v4()));
+    let db = Database::open(&dir.join("rtodo.db"), None).unwrap();
+    (dir, db)
+}
+
+fn count_groups(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM task_groups", [], |row| row.get(0))?)
+}
+
+#[tokio::test]
+async fn reads_see_committed_writes() {
+    let (dir, db) = temp_db();
+
+    db.write(|conn| {
+        conn.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES ('Inbox', 0, 0)", [])?;
+        Ok::<_, crate::Error>(())
+    }).await.unwrap();
+    assert_eq!(db.read(count_groups).await.unwrap(), 1);
+
+    drop(db);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn reads_are_not_blocked_by_a_running_write() {
+    let (dir, db) = temp_db();
+
+    // 写连接被长时间占用时，读操作仍能完成
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let writer = db.clone();
+    let write = tokio::spawn(async move {
+        writer.write(move |conn| {
+            let tx = conn.unchecked_transaction()?;
+            tx.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES ('Slow', 0, 0)", [])?;
+            let _ = started_tx.send(());
+            let _ = release_rx.recv();
+            tx.commit()?;
+            Ok::<_, crate::Error>(())
+        }).await
+    });
+    started_rx.await.unwrap();
+
+    let mut reads = tokio::task::JoinSet::new();
+    for _ in 0..READ_POOL_SIZE * 2 {
+        let db = db.clone();
+        reads.spawn(async move { db.read(count_groups).await });
+    }
+    let counts = tokio::time::timeout(Duration::from_secs(5), async {
+        let mut counts = Vec::new();
+        while let Some(count) = reads.join_next().await {
+            counts.push(count.unwrap().unwrap());
+        }
+        counts
+    }).await.expect("reads waited for the writer");
+
+    // 未提交的写入对读连接不可见
+    assert_eq!(counts, vec![0; READ_POOL_SIZE * 2]);
+
+    release_tx.send(()).unwrap();
+    write.await.unwrap().unwrap();
+    assert_eq!(db.read(count_groups).await.unwrap(), 1);
+
+    drop(db);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn read_connections_are_read_only() {
+    let (dir, db) = temp_db();
+
+    let result = db.read(|conn| {
+        conn.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES ('Nope', 0, 0)", [])?;
+        Ok::<_, crate::Error>(())
+    }).await;
+    assert!(result.is_err());
+
+    drop(db);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn reopen_switches_every_clone_to_the_new_file() {
+    let (dir, db) = temp_db();
+    let other_dir = std::env::temp_dir().join(format!("rtodo-db-{}", uuid::Uuid::new_v4()));
+    let clone = db.clone();
+
+    db.write(|conn| {
+        conn.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES ('Inbox', 0, 0)", [])?;
+        Ok::<_, crate::Error>(())
+    }).await.unwrap();
+
+    // 新位置没有数据库时创建并迁移
+    db.reopen(other_dir.join("rtodo.db"), None, None).await.unwrap();
+    assert_eq!(clone.read(count_groups).await.unwrap(), 0);
+    clone.write(|conn| {
+        conn.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES ('Work', 0, 0)", [])?;
+        conn.execute("INSERT INTO task_groups (name, created_at, updated_at) VALUES ('Home', 0, 0)", [])?;
+        Ok::<_, crate::Error>(())
+    }).await.unwrap();
+
+    db.reopen(dir.join("rtodo.db"), None, None).await.unwrap();
+    assert_eq!(clone.read(count_groups).await.unwrap(), 1);
+
+    drop(db);
+    drop(clone);
+    std::fs::remove_dir_all(&dir).unwrap();
+    std::fs::remove_dir_all(&other_dir).unwrap();
+}
+
+#[tokio::test]
+async fn failed_reopen_keeps_the_current_database() {
+    let (dir, db) = temp_db();
+
+    // 目标路径是一个目录，无法作为数据库打开
+    assert!(db.reopen(dir.clone(), None, None).await.is_err());
+    assert_eq!(db.read(count_groups).await.unwrap(), 0);
+
+    drop(db);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unavailable_data_path_falls_back_to_default() {
+    let missing = std::env::temp_dir().join(format!("rtodo-missing-{}", uuid::Uuid::new_v4()));
+
+    let reason = paths::init_from_config(Some(&missing.to_string_lossy()));
+    assert!(reason.is_some());
+    assert!(!missing.exists());
+    assert_eq!(paths::data_dir().unwrap(), paths::default_data_dir().unwrap());
+}
+
+#[test]
+fn workspaces_resolve_like_the_desktop_app() {
+    let base = std::env::temp_dir().join(format!("rtodo-workspaces-{}", uuid::Uuid::new_v4()));
+    let (home, work) = (base.join("home"), base.join("work"));
+    std::fs::create_dir_all(&home).unwrap();
+    std::fs::create_dir_all(&work).unwrap();
+    let missing = base.join("missing");
+
+    // 旧版本的配置：只有默认工作区的数据路径
+    let legacy = workspaces::WorkspacesConfig::parse(&format!(r#"{{ "data_path": {:?} }}"#, home)).unwrap();
+    let resolved = workspaces::resolve(&legacy, None).unwrap();
+    assert_eq!((resolved.workspace_id.as_str(), &resolved.data_dir), (workspaces::DEFAULT_WORKSPACE_ID, &home));
+
+    let config = |work_path: &PathBuf| workspaces::WorkspacesConfig::parse(&serde_json::json!({
+        "active_workspace": "w1",
+        "workspaces": [
+            { "id": "default", "name": "", "data_path": home },
+            { "id": "w1", "name": "Work", "data_path": work_path },
+        ],
+    }).to_string()).unwrap();
+    let resolved = workspaces::resolve(&config(&work), None).unwrap();
+    assert_eq!((resolved.workspace_id.as_str(), &resolved.data_dir), ("w1", &work));
+    assert!(resolved.fallback_reason.is_none());
+    // 按名称选择工作区，不区分大小写
+    assert_eq!(workspaces::resolve(&config(&work), Some("default")).unwrap().data_dir, home);
+    assert_eq!(workspaces::resolve(&config(&work), Some("WORK")).unwrap().workspace_id, "w1");
+
+    // 当前工作区不可用时回退到默认工作区，明确指定时返回错误
+    let resolved = workspaces::resolve(&config(&missing), None).unwrap();
+    assert_eq!((resolved.workspace_id.as_str(), &resolved.data_dir), ("default", &home));
+    assert!(resolved.fallback_reason.unwrap().contains("does not exist"));
+    assert!(!missing.exists());
+    assert!(workspaces::resolve(&config(&missing), Some("Work")).is_err());
+    assert!(workspaces::resolve(&config(&work), Some("Play")).unwrap_err().is_not_found());
+
+    std::fs::remove_dir_all(&base).unwrap();
+}
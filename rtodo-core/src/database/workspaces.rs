@@ -0,0 +1,182 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 工作区的数据目录
+//!
+//! 桌面应用的 `config.json` 记录各工作区的数据路径和当前工作区。默认工作区未设置路径时使用平台默认目录，
+//! 其他工作区默认放在其下的 `workspaces/<id>`。桌面应用启动时和命令行都用 [`resolve`] 确定要打开的目录。
+
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::paths;
+use crate::error::{Context, Error, Result};
+
+/// 默认工作区的 ID，不能删除
+pub const DEFAULT_WORKSPACE_ID: &str = "default";
+
+/// 桌面应用的标识符，配置目录以它命名
+const APP_IDENTIFIER: &str = "com.rtodo.app";
+
+/// 桌面应用配置文件的路径
+pub fn config_path() -> Result<PathBuf> {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER).join("config.json"))
+        .ok_or_else(|| Error::NotFound("Failed to get config directory".to_string()))
+}
+
+/// 工作区未设置数据路径时使用的目录
+pub fn default_data_dir(id: &str) -> Result<PathBuf> {
+    let base = paths::default_data_dir()?;
+    Ok(if id == DEFAULT_WORKSPACE_ID { base } else { base.join("workspaces").join(id) })
+}
+
+/// 工作区的数据目录（忽略空的自定义路径）
+pub fn data_dir(id: &str, data_path: Option<&str>) -> Result<PathBuf> {
+    match data_path.filter(|p| !p.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => default_data_dir(id),
+    }
+}
+
+/// 配置文件中的一个工作区（只包含确定数据目录需要的字段）
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkspaceEntry {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub data_path: Option<String>,
+}
+
+impl WorkspaceEntry {
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_WORKSPACE_ID
+    }
+
+    /// 可以打开的数据目录；默认工作区未设置路径时总是使用平台默认目录，其他目录必须已经存在且可写
+    fn available_dir(&self) -> Result<PathBuf> {
+        if self.is_default() && self.data_path.as_deref().filter(|p| !p.is_empty()).is_none() {
+            return paths::default_data_dir();
+        }
+        let dir = data_dir(&self.id, self.data_path.as_deref())?;
+        paths::ensure_available(&dir)?;
+        Ok(dir)
+    }
+}
+
+/// 配置文件中与数据目录有关的部分
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct WorkspacesConfig {
+    #[serde(default)]
+    workspaces: Vec<WorkspaceEntry>,
+    #[serde(default)]
+    active_workspace: Option<String>,
+    /// 旧版本的自定义数据路径，属于默认工作区
+    #[serde(default)]
+    data_path: Option<String>,
+}
+
+impl WorkspacesConfig {
+    pub fn new(workspaces: Vec<WorkspaceEntry>, active_workspace: &str) -> Self {
+        Self { workspaces, active_workspace: Some(active_workspace.to_string()), data_path: None }
+    }
+
+    /// 读取配置文件；文件不存在时只有默认工作区
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Failed to parse config file {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// 按 ID 或名称（不区分大小写）查找工作区
+    pub fn find(&self, id_or_name: &str) -> Option<WorkspaceEntry> {
+        let entries = self.entries();
+        let position = entries.iter().position(|w| w.id == id_or_name)
+            .or_else(|| entries.iter().position(|w| w.name.to_lowercase() == id_or_name.to_lowercase()));
+        position.map(|index| entries[index].clone())
+    }
+
+    /// 当前工作区，配置中的当前工作区不存在时为默认工作区
+    pub fn active(&self) -> WorkspaceEntry {
+        let active = self.active_workspace.as_deref().unwrap_or(DEFAULT_WORKSPACE_ID);
+        self.entries().into_iter()
+            .find(|w| w.id == active)
+            .unwrap_or_else(|| self.default_entry())
+    }
+
+    /// 所有工作区；旧版本的配置没有工作区列表，只有默认工作区
+    fn entries(&self) -> Vec<WorkspaceEntry> {
+        let mut entries = self.workspaces.clone();
+        if !entries.iter().any(WorkspaceEntry::is_default) {
+            entries.insert(0, self.default_entry());
+        }
+        entries
+    }
+
+    fn default_entry(&self) -> WorkspaceEntry {
+        self.workspaces.iter().find(|w| w.is_default()).cloned().unwrap_or_else(|| WorkspaceEntry {
+            id: DEFAULT_WORKSPACE_ID.to_string(),
+            name: String::new(),
+            data_path: self.data_path.clone(),
+        })
+    }
+}
+
+/// [`resolve`] 的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedDataDir {
+    /// 实际使用的工作区（回退时为默认工作区）
+    pub workspace_id: String,
+    pub data_dir: PathBuf,
+    /// 配置的目录不可用时的原因
+    pub fallback_reason: Option<String>,
+}
+
+/// 确定要打开的工作区和数据目录，不修改当前生效的数据目录
+///
+/// `workspace` 为工作区 ID 或名称，为空时使用当前工作区。当前工作区不可用时回退到默认工作区，
+/// 避免在该工作区名下显示其他工作区的数据；默认工作区的自定义目录不可用时回退到平台默认目录。
+/// 明确指定的工作区不存在或不可用时返回错误。
+pub fn resolve(config: &WorkspacesConfig, workspace: Option<&str>) -> Result<ResolvedDataDir> {
+    let entry = match workspace {
+        Some(name) => config.find(name)
+            .ok_or_else(|| Error::NotFound(format!("Workspace not found: {}", name)))?,
+        None => config.active(),
+    };
+
+    let reason = match entry.available_dir() {
+        Ok(data_dir) => return Ok(ResolvedDataDir { workspace_id: entry.id, data_dir, fallback_reason: None }),
+        Err(e) if workspace.is_some() => {
+            return Err(e).with_context(|| format!("Workspace {} is not usable", entry.id));
+        }
+        Err(e) => format!("{:#}", e),
+    };
+
+    let default = config.default_entry();
+    let reason = if entry.is_default() {
+        reason
+    } else {
+        match default.available_dir() {
+            Ok(data_dir) => {
+                tracing::warn!("Workspace {} is unavailable, using the default workspace: {}", entry.id, reason);
+                return Ok(ResolvedDataDir { workspace_id: default.id, data_dir, fallback_reason: Some(reason) });
+            }
+            Err(e) => format!("{}; {:#}", reason, e),
+        }
+    };
+    tracing::warn!("Configured data path is unavailable, falling back to default: {}", reason);
+    Ok(ResolvedDataDir {
+        workspace_id: default.id,
+        data_dir: paths::default_data_dir()?,
+        fallback_reason: Some(reason),
+    })
+}
@@ -0,0 +1,422 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 静态数据加密
+//!
+//! 数据库由 SQLCipher 整库加密，附件文件用 XChaCha20-Poly1305 逐个加密。两把密钥都由口令经 Argon2id 派生。
+//! 数据目录下的 `encryption.json` 只记录派生参数和用于校验口令的摘要；该文件存在即表示数据目录已加密，
+//! 启动时数据库处于锁定状态，直到用口令解锁。
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{Context, Error, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::database::Database;
+
+/// 当前构建是否支持加密（需要启用 `encryption` 特性，使用 SQLCipher）
+pub const SUPPORTED: bool = cfg!(feature = "encryption");
+
+/// 数据目录中的加密配置文件
+pub const CONFIG_FILE: &str = "encryption.json";
+const DATABASE_FILE: &str = "rtodo.db";
+const CONFIG_VERSION: u32 = 1;
+/// 口令最短长度（字符数）
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// 加密文件格式：魔数 + 24 字节随机 nonce + 密文（含认证标签）
+const FILE_MAGIC: &[u8] = b"RTODOEC1";
+const NONCE_LEN: usize = 24;
+/// 转换文件时先写入带此后缀的临时文件，再原子替换原文件
+const CONVERTING_SUFFIX: &str = ".converting";
+
+/// Argon2id 默认参数（OWASP 推荐的 19 MiB、2 次迭代）
+const DEFAULT_MEMORY_KIB: u32 = 19 * 1024;
+const DEFAULT_ITERATIONS: u32 = 2;
+const DEFAULT_PARALLELISM: u32 = 1;
+
+/// 加密配置（不含密钥）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    pub version: u32,
+    /// Argon2id 盐（十六进制）
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    /// 密钥摘要，用于判断口令是否正确（十六进制）
+    verifier: String,
+}
+
+impl EncryptionConfig {
+    /// 为新口令生成配置（随机盐）并派生密钥
+    pub fn create(passphrase: &str) -> Result<(Self, EncryptionKey)> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(Error::InvalidInput(format!("Passphrase must be at least {} characters", MIN_PASSPHRASE_LEN)));
+        }
+
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let mut config = Self {
+            version: CONFIG_VERSION,
+            salt: hex::encode(salt),
+            memory_kib: DEFAULT_MEMORY_KIB,
+            iterations: DEFAULT_ITERATIONS,
+            parallelism: DEFAULT_PARALLELISM,
+            verifier: String::new(),
+        };
+        let key = config.derive(passphrase)?;
+        config.verifier = key.verifier();
+        Ok((config, key))
+    }
+
+    /// 读取数据目录的加密配置，未启用加密时返回 None
+    pub fn load(data_dir: &Path) -> Result<Option<Self>> {
+        let path = data_dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)
+            .context("Failed to read encryption config")?;
+        let config: Self = serde_json::from_str(&content)
+            .context("Failed to parse encryption config")?;
+        if config.version > CONFIG_VERSION {
+            return Err(Error::Other(format!("Encryption config version {} is not supported", config.version)));
+        }
+        Ok(Some(config))
+    }
+
+    /// 写入数据目录（先写临时文件再替换，避免留下不完整的配置）
+    pub fn save(&self, data_dir: &Path) -> Result<()> {
+        let path = data_dir.join(CONFIG_FILE);
+        let tmp = converting_path(&path);
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .context("Failed to write encryption config")?;
+        fs::rename(&tmp, &path).context("Failed to write encryption config")?;
+        Ok(())
+    }
+
+    /// 用口令派生密钥，口令错误时返回错误
+    pub fn unlock(&self, passphrase: &str) -> Result<EncryptionKey> {
+        let key = self.derive(passphrase)?;
+        if !self.matches(&key) {
+            return Err(Error::IncorrectPassphrase);
+        }
+        Ok(key)
+    }
+
+    /// 密钥是否由此配置对应的口令派生
+    pub fn matches(&self, key: &EncryptionKey) -> bool {
+        key.verifier() == self.verifier
+    }
+
+    fn derive(&self, passphrase: &str) -> Result<EncryptionKey> {
+        let salt = hex::decode(&self.salt).context("Invalid encryption salt")?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(64))
+            .map_err(|e| Error::Crypto(format!("Invalid key derivation parameters: {}", e)))?;
+        let mut output = Zeroizing::new([0u8; 64]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, output.as_mut())
+            .map_err(|e| Error::Crypto(format!("Failed to derive encryption key: {}", e)))?;
+
+        let mut key = EncryptionKey { database: [0; 32], files: [0; 32] };
+        key.database.copy_from_slice(&output[..32]);
+        key.files.copy_from_slice(&output[32..]);
+        Ok(key)
+    }
+}
+
+/// 由口令派生的密钥：数据库密钥交给 SQLCipher，文件密钥用于附件；释放时清零
+pub struct EncryptionKey {
+    database: [u8; 32],
+    files: [u8; 32],
+}
+
+impl EncryptionKey {
+    /// 在新打开的连接上设置数据库密钥，必须先于其他语句执行
+    pub fn apply_to(&self, conn: &Connection) -> Result<()> {
+        if !SUPPORTED {
+            return Err(Error::EncryptionUnsupported);
+        }
+        let pragma = Zeroizing::new(format!("PRAGMA key = \"{}\";", self.sql_key().as_str()));
+        conn.execute_batch(&pragma).context("Failed to apply database key")
+    }
+
+    /// SQLCipher 原始密钥写法 `x'...'`，跳过 SQLCipher 自身的密钥派生
+    fn sql_key(&self) -> Zeroizing<String> {
+        Zeroizing::new(format!("x'{}'", hex::encode(self.database)))
+    }
+
+    fn verifier(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(b"rtodo-encryption-verifier");
+        hasher.update(self.database);
+        hasher.update(self.files);
+        hex::encode(hasher.finalize())
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new(Key::from_slice(&self.files))
+    }
+}
+
+impl Drop for EncryptionKey {
+    fn drop(&mut self) {
+        self.database.zeroize();
+        self.files.zeroize();
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// 数据目录是否已启用加密
+pub fn is_enabled(data_dir: &Path) -> bool {
+    data_dir.join(CONFIG_FILE).exists()
+}
+
+/// 内容是否为加密文件格式
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(FILE_MAGIC)
+}
+
+/// 文件是否为加密文件格式（只读取文件头）
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut header = [0u8; FILE_MAGIC.len()];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| is_encrypted(&header))
+}
+
+/// 加密文件内容
+pub fn encrypt_bytes(plaintext: &[u8], key: &EncryptionKey) -> Result<Vec<u8>> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = key.cipher().encrypt(&nonce, plaintext)
+        .map_err(|_| Error::Crypto("Failed to encrypt file".to_string()))?;
+
+    let mut bytes = Vec::with_capacity(FILE_MAGIC.len() + NONCE_LEN + ciphertext.len());
+    bytes.extend_from_slice(FILE_MAGIC);
+    bytes.extend_from_slice(&nonce);
+    bytes.extend_from_slice(&ciphertext);
+    Ok(bytes)
+}
+
+/// 解密文件内容，密钥错误或内容被篡改时返回错误
+pub fn decrypt_bytes(bytes: &[u8], key: &EncryptionKey) -> Result<Vec<u8>> {
+    let body = bytes.strip_prefix(FILE_MAGIC).context("File is not encrypted")?;
+    if body.len() < NONCE_LEN {
+        return Err(Error::Corrupted("Encrypted file is truncated".to_string()));
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    key.cipher().decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| Error::Crypto("Failed to decrypt file: wrong key or corrupted data".to_string()))
+}
+
+/// 读取附件文件，加密的文件用 `key` 解密，未加密的文件原样返回
+pub fn read_file(path: &Path, key: Option<&EncryptionKey>) -> Result<Vec<u8>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if !is_encrypted(&bytes) {
+        return Ok(bytes);
+    }
+    let key = key.context("File is encrypted, unlock the database first")?;
+    decrypt_bytes(&bytes, key)
+}
+
+/// 写入附件文件，给出 `key` 时加密
+pub fn write_file(path: &Path, contents: &[u8], key: Option<&EncryptionKey>) -> Result<()> {
+    let result = match key {
+        Some(key) => fs::write(path, encrypt_bytes(contents, key)?),
+        None => fs::write(path, contents),
+    };
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// 加密目录下所有未加密的文件（可重复执行，已加密的文件跳过）
+pub fn encrypt_dir(dir: &Path, key: &EncryptionKey) -> Result<usize> {
+    convert_dir(dir, key, true)
+}
+
+/// 解密目录下所有加密的文件（可重复执行，未加密的文件跳过）
+pub fn decrypt_dir(dir: &Path, key: &EncryptionKey) -> Result<usize> {
+    convert_dir(dir, key, false)
+}
+
+fn convert_dir(dir: &Path, key: &EncryptionKey, encrypt: bool) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut converted = 0;
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            converted += convert_dir(&path, key, encrypt)?;
+            continue;
+        }
+        // 上次中断留下的临时文件，原文件仍然完整
+        if path.to_string_lossy().ends_with(CONVERTING_SUFFIX) {
+            fs::remove_file(&path)?;
+            continue;
+        }
+
+        let bytes = fs::read(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if is_encrypted(&bytes) == encrypt {
+            continue;
+        }
+        let output = if encrypt { encrypt_bytes(&bytes, key)? } else { decrypt_bytes(&bytes, key)? };
+
+        let tmp = converting_path(&path);
+        fs::write(&tmp, output)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        converted += 1;
+    }
+    Ok(converted)
+}
+
+fn converting_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(CONVERTING_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// 用口令解锁数据目录中的加密数据库
+///
+/// 给出 `backup_dir` 时，需要升级 schema 的数据库会先在其中创建迁移前备份
+pub async fn unlock(db: &Database, data_dir: &Path, passphrase: &str, backup_dir: Option<PathBuf>) -> Result<()> {
+    let config = EncryptionConfig::load(data_dir)?
+        .context("Encryption is not enabled")?;
+    let passphrase = Zeroizing::new(passphrase.to_string());
+    let key = tokio::task::spawn_blocking(move || config.unlock(&passphrase)).await??;
+
+    db.reopen(data_dir.join(DATABASE_FILE), backup_dir, Some(Arc::new(key))).await
+        .context("Failed to open encrypted database")
+}
+
+/// 启用加密：派生密钥后把数据库和附件就地转换为加密格式
+///
+/// 数据库转换是原子的，失败时保持未加密；附件逐个转换，中断后未转换的附件仍可正常读取
+pub async fn enable(db: &Database, data_dir: &Path, passphrase: &str) -> Result<()> {
+    if !SUPPORTED {
+        return Err(Error::EncryptionUnsupported);
+    }
+    if is_enabled(data_dir) {
+        return Err(Error::Conflict("Encryption is already enabled".to_string()));
+    }
+
+    let passphrase = Zeroizing::new(passphrase.to_string());
+    let (config, key) = tokio::task::spawn_blocking(move || EncryptionConfig::create(&passphrase)).await??;
+    let key = Arc::new(key);
+
+    convert_database(db, data_dir, &config, Some(key.clone())).await?;
+
+    let attachments = data_dir.join("attachments");
+    let count = tokio::task::spawn_blocking(move || encrypt_dir(&attachments, &key)).await??;
+    tracing::info!("Encryption enabled, {} attachments encrypted", count);
+    Ok(())
+}
+
+/// 关闭加密：校验口令后把数据库和附件就地转换回未加密格式
+pub async fn disable(db: &Database, data_dir: &Path, passphrase: &str) -> Result<()> {
+    let config = EncryptionConfig::load(data_dir)?
+        .context("Encryption is not enabled")?;
+    let check = config.clone();
+    let passphrase = Zeroizing::new(passphrase.to_string());
+    let key = Arc::new(tokio::task::spawn_blocking(move || check.unlock(&passphrase)).await??);
+
+    convert_database(db, data_dir, &config, None).await?;
+
+    let attachments = data_dir.join("attachments");
+    let count = tokio::task::spawn_blocking(move || decrypt_dir(&attachments, &key)).await??;
+    tracing::info!("Encryption disabled, {} attachments decrypted", count);
+    Ok(())
+}
+
+/// 把数据库导出为用 `key` 加密（None 为不加密）的新文件后替换原文件，并以新密钥重新打开
+///
+/// 加密配置在替换文件前写入（启用）或删除（关闭），替换失败时还原配置并重新打开原数据库
+async fn convert_database(
+    db: &Database,
+    data_dir: &Path,
+    config: &EncryptionConfig,
+    key: Option<Arc<EncryptionKey>>,
+) -> Result<()> {
+    let db_path = data_dir.join(DATABASE_FILE);
+    let converted = converting_path(&db_path);
+    let _ = fs::remove_file(&converted);
+
+    let old_key = db.key();
+    let target = converted.clone();
+    let export_key = key.clone();
+    db.close_with(move |conn| export_database(conn, &target, export_key.as_deref())).await?;
+
+    let enabling = key.is_some();
+    let update_config = |enable: bool| {
+        if enable {
+            config.save(data_dir)
+        } else {
+            fs::remove_file(data_dir.join(CONFIG_FILE)).context("Failed to remove encryption config")
+        }
+    };
+    let replaced = update_config(enabling).and_then(|_| {
+        replace_database(&converted, &db_path).inspect_err(|_| {
+            let _ = update_config(!enabling);
+        })
+    });
+    if let Err(e) = replaced {
+        let _ = fs::remove_file(&converted);
+        db.reopen(db_path, None, old_key).await
+            .context("Failed to reopen database after conversion failed")?;
+        return Err(e);
+    }
+
+    db.reopen(db_path, None, key).await
+}
+
+/// 通过 SQLCipher 导出数据库，`key` 为 None 时导出为未加密的数据库
+fn export_database(conn: &Connection, target: &Path, key: Option<&EncryptionKey>) -> Result<()> {
+    let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    let sql_key = key.map(EncryptionKey::sql_key).unwrap_or_default();
+    conn.execute(
+        "ATTACH DATABASE ?1 AS converted KEY ?2",
+        params![target.to_string_lossy(), sql_key.as_str()],
+    )?;
+    let exported = conn.execute_batch(&format!(
+        "SELECT sqlcipher_export('converted'); PRAGMA converted.user_version = {};",
+        version
+    ));
+    conn.execute_batch("DETACH DATABASE converted")?;
+    exported.context("Failed to convert database")
+}
+
+/// 用转换后的文件替换数据库（原数据库已关闭，-wal/-shm 文件不再需要）
+fn replace_database(converted: &Path, db_path: &Path) -> Result<()> {
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = db_path.as_os_str().to_os_string();
+        sidecar.push(suffix);
+        let sidecar = PathBuf::from(sidecar);
+        if sidecar.exists() {
+            fs::remove_file(&sidecar).context("Failed to remove database journal")?;
+        }
+    }
+    fs::rename(converted, db_path).context("Failed to replace database")
+}
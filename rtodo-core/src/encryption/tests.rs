@@ -0,0 +1,104 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::test_support::temp_dir;
+
+#[test]
+fn passphrase_is_verified() {
+    let dir = temp_dir("encryption");
+    let (config, key) = EncryptionConfig::create("correct horse").unwrap();
+    config.save(&dir).unwrap();
+    assert!(is_enabled(&dir));
+
+    let loaded = EncryptionConfig::load(&dir).unwrap().unwrap();
+    assert!(loaded.matches(&key));
+    assert!(loaded.unlock("correct horse").is_ok());
+    assert_eq!(loaded.unlock("wrong horse").unwrap_err().to_string(), "Incorrect passphrase");
+    assert!(EncryptionConfig::create("short").is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn files_round_trip_and_reject_wrong_key() {
+    let (_, key) = EncryptionConfig::create("correct horse").unwrap();
+    let (_, other) = EncryptionConfig::create("correct horse").unwrap();
+
+    let encrypted = encrypt_bytes(b"contract details", &key).unwrap();
+    assert!(is_encrypted(&encrypted));
+    assert_eq!(decrypt_bytes(&encrypted, &key).unwrap(), b"contract details");
+    // 相同口令、不同盐派生出不同的密钥
+    assert!(decrypt_bytes(&encrypted, &other).is_err());
+
+    let mut tampered = encrypted.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 0xff;
+    assert!(decrypt_bytes(&tampered, &key).is_err());
+}
+
+#[test]
+fn directories_convert_in_place_and_plaintext_passes_through() {
+    let dir = temp_dir("encryption");
+    let (_, key) = EncryptionConfig::create("correct horse").unwrap();
+    fs::create_dir_all(dir.join("sub")).unwrap();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+
+    assert_eq!(read_file(&dir.join("a.txt"), None).unwrap(), b"a");
+    assert_eq!(encrypt_dir(&dir, &key).unwrap(), 2);
+    assert_eq!(encrypt_dir(&dir, &key).unwrap(), 0);
+    assert!(is_encrypted(&fs::read(dir.join("sub").join("b.txt")).unwrap()));
+    assert_eq!(read_file(&dir.join("sub").join("b.txt"), Some(&key)).unwrap(), b"b");
+    assert!(read_file(&dir.join("a.txt"), None).is_err());
+
+    write_file(&dir.join("c.txt"), b"c", None).unwrap();
+    assert_eq!(decrypt_dir(&dir, &key).unwrap(), 2);
+    assert_eq!(fs::read(dir.join("a.txt")).unwrap(), b"a");
+    assert_eq!(fs::read(dir.join("c.txt")).unwrap(), b"c");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "encryption")]
+#[tokio::test]
+async fn database_is_converted_in_place() {
+    let dir = temp_dir("encryption");
+    fs::create_dir_all(dir.join("attachments")).unwrap();
+    fs::write(dir.join("attachments").join("a.txt"), "a").unwrap();
+    let db_path = dir.join(DATABASE_FILE);
+    let db = Database::open(&db_path, None).unwrap();
+    db.write(|conn| Ok::<_, crate::Error>(conn.execute("INSERT INTO tags (name, created_at) VALUES ('secret', 0)", [])?))
+        .await
+        .unwrap();
+
+    enable(&db, &dir, "correct horse").await.unwrap();
+    assert!(is_enabled(&dir));
+    assert!(db.key().is_some());
+    assert!(Connection::open(&db_path).unwrap()
+        .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get::<_, i64>(0))
+        .is_err());
+    assert!(is_encrypted(&fs::read(dir.join("attachments").join("a.txt")).unwrap()));
+
+    // 重新启动：锁定状态下查询失败，解锁后数据完整
+    let db = Database::locked();
+    assert!(db.read(|conn| conn.execute_batch("SELECT 1").map_err(crate::Error::from)).await.is_err());
+    assert!(unlock(&db, &dir, "wrong horse", None).await.is_err());
+    unlock(&db, &dir, "correct horse", None).await.unwrap();
+    let name: String = db.read(|conn| Ok::<_, crate::Error>(conn.query_row("SELECT name FROM tags", [], |row| row.get(0))?))
+        .await
+        .unwrap();
+    assert_eq!(name, "secret");
+
+    disable(&db, &dir, "correct horse").await.unwrap();
+    assert!(!is_enabled(&dir));
+    assert!(db.key().is_none());
+    let count: i64 = Connection::open(&db_path).unwrap()
+        .query_row("SELECT COUNT(*) FROM tags", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(fs::read(dir.join("attachments").join("a.txt")).unwrap(), b"a");
+
+    drop(db);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,143 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 错误类型
+//! 调用方可以按变体区分记录不存在、参数错误、数据库锁定等情况；
+//! 底层错误通过 [`Error::Context`] 附加说明，`Display` 依次显示各层说明和原始错误。
+
+use std::fmt;
+
+/// rtodo-core 的结果类型
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// rtodo-core 的错误
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// 记录或文件不存在
+    #[error("{0}")]
+    NotFound(String),
+    /// 参数或数据不合法
+    #[error("{0}")]
+    InvalidInput(String),
+    /// 与现有数据冲突（例如重名）
+    #[error("{0}")]
+    Conflict(String),
+    /// 数据损坏（校验失败、文件被截断等）
+    #[error("{0}")]
+    Corrupted(String),
+    /// 加密数据库尚未解锁
+    #[error("Database is locked, unlock it with the passphrase first")]
+    Locked,
+    /// 没有可用的数据库连接（连接池已关闭）
+    #[error("No database connection available")]
+    PoolUnavailable,
+    /// 数据库由更新版本的 RTodo 写入
+    #[error("Database schema version {found} is newer than this version of RTodo supports ({supported}). Please upgrade RTodo.")]
+    SchemaTooNew { found: u32, supported: u32 },
+    /// 口令错误
+    #[error("Incorrect passphrase")]
+    IncorrectPassphrase,
+    /// 当前构建不支持加密
+    #[error("This build of RTodo does not support encryption")]
+    EncryptionUnsupported,
+    /// 加密的数据库不能同步：变更日志以明文写入同步目标
+    #[error("Sync is not available while the database is encrypted, because change logs would be stored unencrypted")]
+    SyncEncrypted,
+    /// 加密或密钥派生失败
+    #[error("{0}")]
+    Crypto(String),
+    /// 远程存储（WebDAV 等）请求失败
+    #[error("{0}")]
+    Remote(String),
+    /// 附加了说明的错误
+    #[error("{context}: {error}")]
+    Context { context: String, error: Box<Error> },
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    /// 后台查询线程崩溃或被取消
+    #[error("Database task failed: {0}")]
+    Task(#[from] tokio::task::JoinError),
+    /// 其他错误
+    #[error("{0}")]
+    Other(String),
+}
+
+impl Error {
+    /// 去掉附加说明后的原始错误
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { error, .. } => error.root(),
+            other => other,
+        }
+    }
+
+    /// 是否为记录不存在
+    pub fn is_not_found(&self) -> bool {
+        matches!(self.root(), Error::NotFound(_))
+    }
+}
+
+impl From<std::num::ParseIntError> for Error {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Error::InvalidInput(e.to_string())
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(e: hex::FromHexError) -> Self {
+        Error::Corrupted(e.to_string())
+    }
+}
+
+impl<W> From<csv::IntoInnerError<W>> for Error {
+    fn from(e: csv::IntoInnerError<W>) -> Self {
+        Error::Io(e.into_error())
+    }
+}
+
+impl From<Error> for String {
+    fn from(e: Error) -> Self {
+        e.to_string()
+    }
+}
+
+/// 为错误附加说明，用法与 `anyhow::Context` 相同；用于 `Option` 时 `None` 成为 [`Error::NotFound`]
+pub trait Context<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T>;
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.map_err(|e| Error::Context {
+            context: context.to_string(),
+            error: Box::new(e.into()),
+        })
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.map_err(|e| Error::Context {
+            context: f().to_string(),
+            error: Box::new(e.into()),
+        })
+    }
+}
+
+impl<T> Context<T> for Option<T> {
+    fn context<C: fmt::Display>(self, context: C) -> Result<T> {
+        self.ok_or_else(|| Error::NotFound(context.to_string()))
+    }
+
+    fn with_context<C: fmt::Display, F: FnOnce() -> C>(self, f: F) -> Result<T> {
+        self.ok_or_else(|| Error::NotFound(f().to_string()))
+    }
+}
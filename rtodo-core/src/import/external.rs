@@ -0,0 +1,373 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 其他应用导出文件的公共部分：组装导出数据、记录未导入的字段、解析日期
+//!
+//! 任务组和标签不设置 uid，合并时按名称与本地已有的匹配；任务的 uid 由来源应用中的 id 生成，
+//! 再次导入同一个文件时匹配到上次导入的任务。
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use csv::StringRecord;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{Context, Error, Result};
+use crate::models::{ExportData, Tag, TaskGroup, Todo, TodoStatus, TodoStep, UnmappedField};
+use crate::utils::{name_uuid, now_timestamp};
+
+/// 新建标签的颜色
+const TAG_COLOR: &str = "#409EFF";
+
+/// 转换后的导出数据和未导入的字段
+pub struct External {
+    pub data: ExportData,
+    pub unmapped: Vec<UnmappedField>,
+}
+
+/// 逐条添加任务组、标签和任务，生成导出数据
+pub(super) struct Builder {
+    /// 来源应用，用于生成任务的 uid
+    source: &'static str,
+    now: i64,
+    groups: Vec<TaskGroup>,
+    /// 任务组路径（以 `/` 连接）到 id
+    group_ids: HashMap<String, i64>,
+    tags: Vec<Tag>,
+    /// 小写的标签名到 id
+    tag_ids: HashMap<String, i64>,
+    todos: Vec<Todo>,
+    unmapped: BTreeMap<String, UnmappedField>,
+}
+
+impl Builder {
+    pub(super) fn new(source: &'static str) -> Self {
+        Self {
+            source,
+            now: now_timestamp(),
+            groups: Vec::new(),
+            group_ids: HashMap::new(),
+            tags: Vec::new(),
+            tag_ids: HashMap::new(),
+            todos: Vec::new(),
+            unmapped: BTreeMap::new(),
+        }
+    }
+
+    /// 按路径返回任务组 id，不存在的层级依次创建；路径为空时返回 None
+    pub(super) fn group(&mut self, path: &[&str]) -> Option<i64> {
+        let mut parent_id = None;
+        for depth in 1..=path.len() {
+            let name = path[depth - 1].trim();
+            if name.is_empty() {
+                continue;
+            }
+            let key = path[..depth].join("/");
+            parent_id = Some(match self.group_ids.get(&key) {
+                Some(&id) => id,
+                None => {
+                    let id = self.groups.len() as i64 + 1;
+                    self.groups.push(TaskGroup {
+                        id,
+                        uid: String::new(),
+                        name: name.to_string(),
+                        parent_id,
+                        icon: None,
+                        color: None,
+                        sort_order: id as i32,
+                        created_at: self.now,
+                        updated_at: self.now,
+                    });
+                    self.group_ids.insert(key, id);
+                    id
+                }
+            });
+        }
+        parent_id
+    }
+
+    /// 按名称返回标签，不存在时创建
+    pub(super) fn tag(&mut self, name: &str) -> Option<Tag> {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let id = match self.tag_ids.get(&name.to_lowercase()) {
+            Some(&id) => id,
+            None => {
+                let id = self.tags.len() as i64 + 1;
+                self.tags.push(Tag {
+                    id,
+                    uid: String::new(),
+                    name: name.to_string(),
+                    color: TAG_COLOR.to_string(),
+                    parent_id: None,
+                    created_at: self.now,
+                });
+                self.tag_ids.insert(name.to_lowercase(), id);
+                id
+            }
+        };
+        self.tags.iter().find(|tag| tag.id == id).cloned()
+    }
+
+    /// 新的待办任务，`key` 为来源应用中任务的标识（没有时由调用方用标题等内容组合）
+    pub(super) fn todo(&self, key: &str, title: &str) -> Todo {
+        Todo {
+            id: self.todos.len() as i64 + 1,
+            uid: name_uuid(&format!("{}:{}", self.source, key)),
+            title: title.trim().to_string(),
+            description: None,
+            status: TodoStatus::Todo,
+            priority: 0,
+            group_id: None,
+            assignee: None,
+            start_date: None,
+            due_date: None,
+            completed_at: None,
+            created_at: self.now,
+            updated_at: self.now,
+            tags: Some(Vec::new()),
+            steps: Some(Vec::new()),
+            attachments: None,
+            group_info: None,
+        }
+    }
+
+    /// 添加任务，返回其下标
+    pub(super) fn push(&mut self, todo: Todo) -> usize {
+        self.todos.push(todo);
+        self.todos.len() - 1
+    }
+
+    /// 为已添加的任务增加一个步骤
+    pub(super) fn step(&mut self, index: usize, title: &str, is_completed: bool) {
+        let now = self.now;
+        let todo = &mut self.todos[index];
+        let steps = todo.steps.get_or_insert_with(Vec::new);
+        steps.push(TodoStep {
+            id: 0,
+            uid: String::new(),
+            todo_id: todo.id,
+            title: title.trim().to_string(),
+            is_completed,
+            sort_order: steps.len() as i32,
+            created_at: now,
+        });
+    }
+
+    /// 在已添加的任务的描述后追加一段文字
+    pub(super) fn append_description(&mut self, index: usize, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        let todo = &mut self.todos[index];
+        todo.description = Some(match todo.description.take() {
+            Some(description) => format!("{}\n\n{}", description, text),
+            None => text.to_string(),
+        });
+    }
+
+    /// 记录一个未导入的字段，值为空时忽略
+    pub(super) fn unmapped(&mut self, field: &str, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        let entry = self.unmapped.entry(field.to_string()).or_insert_with(|| UnmappedField {
+            field: field.to_string(),
+            count: 0,
+            example: value.chars().take(80).collect(),
+        });
+        entry.count += 1;
+    }
+
+    pub(super) fn finish(self) -> External {
+        // 步骤的 id 只需在文件内唯一
+        let mut todos = self.todos;
+        for (id, step) in (1..).zip(todos.iter_mut().flat_map(|todo| todo.steps.iter_mut().flatten())) {
+            step.id = id;
+        }
+
+        External {
+            data: ExportData {
+                version: "1.0".to_string(),
+                exported_at: self.now,
+                task_groups: self.groups,
+                tags: self.tags,
+                todos,
+            },
+            unmapped: self.unmapped.into_values().collect(),
+        }
+    }
+}
+
+/// 让没有 uid 的任务组和标签沿用本地同名的记录（任务组还需父级相同），
+/// 合并时视为同一条记录，不会用导入文件中的默认值覆盖本地的图标和颜色
+pub(super) fn adopt_local(conn: &Connection, data: &mut ExportData) -> Result<()> {
+    // 导入文件中的任务组 id 到本地 id；父级总是排在子级之前
+    let mut local_ids = HashMap::new();
+    for group in data.task_groups.iter_mut().filter(|group| group.uid.is_empty()) {
+        let parent_id = match group.parent_id {
+            Some(parent_id) => match local_ids.get(&parent_id) {
+                Some(&local_id) => Some(local_id),
+                None => continue,
+            },
+            None => None,
+        };
+        let local = conn
+            .query_row(
+                "SELECT id, uid, icon, color, sort_order, created_at, updated_at FROM task_groups
+                 WHERE name = ?1 AND parent_id IS ?2 ORDER BY id",
+                params![group.name, parent_id],
+                |row| {
+                    Ok(TaskGroup {
+                        id: row.get(0)?,
+                        uid: row.get(1)?,
+                        name: group.name.clone(),
+                        parent_id: group.parent_id,
+                        icon: row.get(2)?,
+                        color: row.get(3)?,
+                        sort_order: row.get(4)?,
+                        created_at: row.get(5)?,
+                        updated_at: row.get(6)?,
+                    })
+                },
+            )
+            .optional()
+            .context("Failed to look up existing group")?;
+        if let Some(local) = local {
+            local_ids.insert(group.id, local.id);
+            *group = TaskGroup { id: group.id, ..local };
+        }
+    }
+
+    for tag in data.tags.iter_mut().filter(|tag| tag.uid.is_empty()) {
+        let local = conn
+            .query_row(
+                "SELECT uid, name, color, created_at FROM tags WHERE name = ?1 COLLATE NOCASE",
+                params![tag.name],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .optional()
+            .context("Failed to look up existing tag")?;
+        if let Some((uid, name, color, created_at)) = local {
+            (tag.uid, tag.name, tag.color, tag.created_at) = (uid, name, color, created_at);
+        }
+    }
+    Ok(())
+}
+
+/// 带表头的 CSV
+pub(super) struct Table {
+    headers: Vec<String>,
+    pub(super) rows: Vec<StringRecord>,
+}
+
+impl Table {
+    /// 解析 CSV，表头为第一列是 `first_column` 的行，之前的说明文字被忽略
+    pub(super) fn parse(content: &str, first_column: &str) -> Result<Self> {
+        let content = content.trim_start_matches('\u{feff}');
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(content.as_bytes());
+        let mut records = reader.records();
+        let headers = loop {
+            let record = records
+                .next()
+                .ok_or_else(|| Error::InvalidInput(format!("CSV header \"{}\" not found", first_column)))??;
+            if record.get(0).map(str::trim) == Some(first_column) {
+                break record.iter().map(|header| header.trim().to_string()).collect();
+            }
+        };
+        let rows = records.collect::<std::result::Result<_, _>>()?;
+        Ok(Self { headers, rows })
+    }
+
+    /// 一行中某列的值，没有该列时为空
+    pub(super) fn get<'r>(&self, row: &'r StringRecord, column: &str) -> &'r str {
+        self.headers
+            .iter()
+            .position(|header| header == column)
+            .and_then(|index| row.get(index))
+            .map(str::trim)
+            .unwrap_or_default()
+    }
+
+    /// 一行中不在 `known` 里的列及其值
+    pub(super) fn others<'r>(&'r self, row: &'r StringRecord, known: &'r [&str]) -> impl Iterator<Item = (&'r str, &'r str)> {
+        self.headers
+            .iter()
+            .zip(row.iter())
+            .filter(move |(header, _)| !known.contains(&header.as_str()))
+            .map(|(header, value)| (header.as_str(), value))
+    }
+}
+
+/// 非空的文本
+pub(super) fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// JSON 对象的字符串属性，没有时为空
+pub(super) fn json_str<'v>(value: &'v Value, field: &str) -> &'v str {
+    value.get(field).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// JSON 值是否为空（null、false、空字符串、空数组或空对象）
+pub(super) fn json_is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(text) => text.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        _ => false,
+    }
+}
+
+/// 用于报告未导入字段的 JSON 值
+pub(super) fn json_summary(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 解析常见的日期时间格式，返回毫秒时间戳；没有时区的时间按本地时间处理
+///
+/// 支持 RFC 3339、`2024-03-15T09:00:00+0000`、`20240315T091500Z`（iCalendar 和 Taskwarrior）、
+/// `2024-03-15 09:00` 和 `2024-03-15`
+pub(crate) fn parse_datetime(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.timestamp_millis());
+    }
+    if let Ok(time) = DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z") {
+        return Some(time.timestamp_millis());
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Some(Utc.from_utc_datetime(&time).timestamp_millis());
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y%m%dT%H%M%S"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(value, format) {
+            return local_millis(time);
+        }
+    }
+    for format in ["%Y-%m-%d", "%Y%m%d"] {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return local_millis(date.and_hms_opt(0, 0, 0)?);
+        }
+    }
+    None
+}
+
+/// 本地时间转换为毫秒时间戳（夏令时切换时有歧义的时间取较早的一个）
+pub(crate) fn local_millis(time: NaiveDateTime) -> Option<i64> {
+    Local.from_local_datetime(&time).earliest().map(|time| time.timestamp_millis())
+}
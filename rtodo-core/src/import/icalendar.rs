@@ -0,0 +1,131 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! iCalendar（`.ics`）中的 VTODO，以及可选的 VEVENT
+//!
+//! UID 为 UUID 时直接作为任务的 uid（RTodo 导出的文件），否则由 UID 生成；类别（CATEGORIES）对应标签，
+//! `X-RTODO-GROUP` 对应任务组路径。iCalendar 中没有步骤，已有任务的步骤保持不变。
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::external::{Builder, External};
+use crate::error::{Context, Error, Result};
+use crate::models::{priority, ExportData, TodoStatus};
+use crate::utils::ical::{self, Component};
+
+/// 已导入或有意忽略的属性
+const KNOWN: &[&str] = &[
+    "UID", "DTSTAMP", "SEQUENCE", "SUMMARY", "DESCRIPTION", "STATUS", "PRIORITY", "DTSTART", "DUE", "DTEND",
+    "COMPLETED", "PERCENT-COMPLETE", "CREATED", "LAST-MODIFIED", "CATEGORIES", "CLASS", "TRANSP", "X-RTODO-GROUP",
+];
+
+pub(super) fn read(content: &str, with_events: bool) -> Result<External> {
+    let calendars = ical::parse(content)?;
+    let calendars: Vec<_> = calendars.iter().filter(|component| component.name == "VCALENDAR").collect();
+    if calendars.is_empty() {
+        return Err(Error::InvalidInput("No VCALENDAR found in the file".to_string()));
+    }
+
+    let mut builder = Builder::new("ical");
+    for component in calendars.iter().flat_map(|calendar| &calendar.components) {
+        match component.name.as_str() {
+            "VTODO" => read_todo(&mut builder, component, false),
+            "VEVENT" if with_events => read_todo(&mut builder, component, true),
+            "VTIMEZONE" => {}
+            other => {
+                let summary = component.property("SUMMARY").map(|summary| summary.text()).unwrap_or_default();
+                builder.unmapped(other, if summary.is_empty() { other } else { &summary });
+            }
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// 一个 VTODO 或 VEVENT（事件的结束时间作为截止日期）
+fn read_todo(builder: &mut Builder, component: &Component, is_event: bool) {
+    let text = |name| component.property(name).map(|property| property.text()).unwrap_or_default();
+    let time = |name| component.property(name).and_then(|property| property.timestamp());
+
+    let summary = text("SUMMARY");
+    let uid = text("UID");
+    let key = match uid.as_str() {
+        "" => format!("{}/{}", summary, text("CREATED")),
+        uid => uid.to_string(),
+    };
+    let mut todo = builder.todo(&key, &summary);
+    if let Ok(uuid) = uuid::Uuid::parse_str(&uid) {
+        todo.uid = uuid.to_string();
+    }
+    todo.description = Some(text("DESCRIPTION")).filter(|description| !description.is_empty());
+    // 只有步骤和附件保持本地的
+    todo.steps = None;
+
+    todo.status = match text("STATUS").to_uppercase().as_str() {
+        "IN-PROCESS" => TodoStatus::InProgress,
+        "COMPLETED" => TodoStatus::Done,
+        "NEEDS-ACTION" | "" | "TENTATIVE" | "CONFIRMED" => TodoStatus::Todo,
+        other => {
+            builder.unmapped("STATUS", other);
+            // 已取消的任务视为完成，已取消的事件保留为待办
+            if is_event {
+                TodoStatus::Todo
+            } else {
+                TodoStatus::Done
+            }
+        }
+    };
+    // 1–4 为高、5 为中、6–9 为低，0 为未指定
+    todo.priority = match text("PRIORITY").trim().parse::<u8>().unwrap_or(0) {
+        1..=4 => priority::URGENT,
+        5 => priority::IMPORTANT,
+        _ => priority::NORMAL,
+    };
+    todo.start_date = time("DTSTART");
+    todo.due_date = if is_event { time("DTEND") } else { time("DUE") };
+    if let Some(created_at) = time("CREATED") {
+        todo.created_at = created_at;
+        todo.updated_at = created_at;
+    }
+    if let Some(updated_at) = time("LAST-MODIFIED") {
+        todo.updated_at = updated_at;
+    }
+    todo.completed_at = time("COMPLETED");
+    if todo.status == TodoStatus::Done && todo.completed_at.is_none() {
+        todo.completed_at = Some(todo.updated_at);
+    }
+
+    // CATEGORIES 可以出现多次
+    let categories = component.properties.iter().filter(|property| property.name == "CATEGORIES");
+    let names: Vec<_> = categories.flat_map(|property| property.texts()).collect();
+    todo.tags = Some(names.iter().filter_map(|name| builder.tag(name)).collect());
+    if let Some(group) = component.property("X-RTODO-GROUP") {
+        let path = group.texts();
+        todo.group_id = builder.group(&path.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+
+    for property in &component.properties {
+        if !KNOWN.contains(&property.name.as_str()) {
+            builder.unmapped(&property.name, &property.text());
+        }
+    }
+    // 提醒（VALARM）等子组件
+    for child in &component.components {
+        let trigger = child.property("TRIGGER").map(|trigger| trigger.value.clone()).unwrap_or_default();
+        builder.unmapped(&child.name, if trigger.is_empty() { &child.name } else { &trigger });
+    }
+    builder.push(todo);
+}
+
+/// iCalendar 的时间只精确到秒：与本地同一秒修改的任务视为同一版本，再次导入导出的文件时不会被当作有修改
+pub(super) fn adopt_versions(conn: &Connection, data: &mut ExportData) -> Result<()> {
+    for todo in data.todos.iter_mut() {
+        let local: Option<i64> = conn
+            .query_row("SELECT updated_at FROM todos WHERE uid = ?1", params![todo.uid], |row| row.get(0))
+            .optional()
+            .context("Failed to look up existing todo")?;
+        if let Some(updated_at) = local.filter(|updated_at| updated_at.div_euclid(1000) == todo.updated_at.div_euclid(1000)) {
+            todo.updated_at = updated_at;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Microsoft To Do / Outlook 任务的 JSON（Microsoft Graph 的 todoTaskList 和 todoTask 格式）
+//!
+//! 文件可以是任务列表的数组，或 `{"lists": [...]}`、`{"value": [...]}`；每个列表的 `tasks` 为任务数组。
+//! 列表对应任务组，类别（categories）对应标签，清单项（checklistItems）对应步骤。
+
+use serde_json::Value;
+
+use super::external::{json_is_empty, json_str, json_summary, non_empty, parse_datetime, Builder, External};
+use crate::error::{Error, Result};
+use crate::models::{priority, TodoStatus};
+
+/// 已导入或有意忽略的属性
+const KNOWN: &[&str] = &[
+    "id", "@odata.etag", "title", "body", "importance", "status", "createdDateTime", "lastModifiedDateTime",
+    "completedDateTime", "dueDateTime", "startDateTime", "categories", "checklistItems", "bodyLastModifiedDateTime",
+    "isReminderOn", "hasAttachments",
+];
+
+pub(super) fn read(content: &str) -> Result<External> {
+    let root: Value = serde_json::from_str(content.trim_start_matches('\u{feff}'))?;
+    let lists = match &root {
+        Value::Array(lists) => lists,
+        Value::Object(object) => match object.get("lists").or_else(|| object.get("value")) {
+            Some(Value::Array(lists)) => lists,
+            _ => return Err(Error::InvalidInput("Expected a \"lists\" array of task lists".to_string())),
+        },
+        _ => return Err(Error::InvalidInput("Expected an array of task lists".to_string())),
+    };
+
+    let mut builder = Builder::new("microsoft_todo");
+    for list in lists {
+        let name = json_str(list, "displayName");
+        let group_id = builder.group(&[name]);
+        for task in list.get("tasks").and_then(Value::as_array).into_iter().flatten() {
+            let key = match task.get("id").and_then(Value::as_str) {
+                Some(id) => id.to_string(),
+                None => format!("{}/{}/{}", name, json_str(task, "title"), json_str(task, "createdDateTime")),
+            };
+            let mut todo = builder.todo(&key, json_str(task, "title"));
+            todo.group_id = group_id;
+            todo.description = task.get("body").map(|body| json_str(body, "content")).and_then(non_empty);
+            if json_str(task, "importance") == "high" {
+                todo.priority = priority::IMPORTANT;
+            }
+            todo.status = match json_str(task, "status") {
+                "completed" => TodoStatus::Done,
+                "inProgress" => TodoStatus::InProgress,
+                "notStarted" | "" => TodoStatus::Todo,
+                other => {
+                    builder.unmapped("status", other);
+                    TodoStatus::Todo
+                }
+            };
+            if let Some(created_at) = parse_datetime(json_str(task, "createdDateTime")) {
+                todo.created_at = created_at;
+                todo.updated_at = created_at;
+            }
+            if let Some(updated_at) = parse_datetime(json_str(task, "lastModifiedDateTime")) {
+                todo.updated_at = updated_at;
+            }
+            todo.completed_at = date_time(task, "completedDateTime");
+            todo.due_date = date_time(task, "dueDateTime");
+            todo.start_date = date_time(task, "startDateTime");
+            if todo.status == TodoStatus::Done && todo.completed_at.is_none() {
+                todo.completed_at = Some(todo.updated_at);
+            }
+            let categories = task.get("categories").and_then(Value::as_array).into_iter().flatten();
+            todo.tags = Some(categories.filter_map(Value::as_str).filter_map(|name| builder.tag(name)).collect());
+
+            for (field, value) in task.as_object().into_iter().flatten() {
+                if !KNOWN.contains(&field.as_str()) && !json_is_empty(value) {
+                    builder.unmapped(field, &json_summary(value));
+                }
+            }
+
+            let index = builder.push(todo);
+            for item in task.get("checklistItems").and_then(Value::as_array).into_iter().flatten() {
+                let is_checked = item.get("isChecked").and_then(Value::as_bool).unwrap_or(false);
+                builder.step(index, json_str(item, "displayName"), is_checked);
+            }
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// `{"dateTime": "...", "timeZone": "UTC"}` 形式的时间；其他时区按本地时间处理
+fn date_time(task: &Value, field: &str) -> Option<i64> {
+    let value = task.get(field)?;
+    let time = json_str(value, "dateTime");
+    match json_str(value, "timeZone") {
+        "UTC" | "Etc/UTC" if !time.ends_with('Z') => parse_datetime(&format!("{}Z", time)),
+        _ => parse_datetime(time),
+    }
+}
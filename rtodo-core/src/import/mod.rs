@@ -0,0 +1,119 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 导入导出文件
+//!
+//! 导入分两步：先对比导入文件和本地数据，为每个任务组、标签和任务生成处理方式（新建、更新或跳过），
+//! 再在一个事务中执行。预览只生成第一步的结果，不写入任何数据。
+//!
+//! 合并时按 uid 匹配本地记录；旧版本的导出文件没有 uid，任务组和标签按名称、任务按标题和创建时间匹配。
+//! 导入文件中的 ID 只用于文件内部的引用（父级、任务组、标签），写入时映射为本地 ID。
+//!
+//! 其他应用（Todoist、Microsoft To Do、TickTick、Taskwarrior）的导出文件先转换为导出数据再按同样的方式导入，
+//! 没有对应字段的内容列在报告的 `unmapped` 中。iCalendar 文件中的 VTODO（以及可选的 VEVENT）也按这种方式导入。
+
+mod external;
+mod icalendar;
+mod microsoft_todo;
+mod plan;
+mod taskwarrior;
+mod ticktick;
+mod todoist;
+
+#[cfg(test)]
+mod tests;
+
+use rusqlite::Connection;
+
+use crate::error::Result;
+use crate::models::{ExportData, ImportOptions, ImportReport, ImportSource};
+pub use external::External;
+use plan::Plan;
+
+/// 预览导入结果，不写入任何数据
+pub fn preview(conn: &Connection, data: &ExportData, options: ImportOptions) -> Result<ImportReport> {
+    Ok(Plan::build(conn, data, options)?.report(true))
+}
+
+/// 导入数据（在事务中执行）
+///
+/// `with_attachments` 为 false 时忽略附件记录，用于不包含附件文件的导入文件
+pub fn import(conn: &Connection, data: &ExportData, options: ImportOptions, with_attachments: bool) -> Result<ImportReport> {
+    let transaction = conn.unchecked_transaction()?;
+    let plan = Plan::build(&transaction, data, options)?;
+    plan.apply(&transaction, with_attachments)?;
+    transaction.commit()?;
+
+    let report = plan.report(false);
+    tracing::info!(
+        "Imported data ({:?}): {} created, {} updated, {} skipped, {} conflicts",
+        options.mode, report.created, report.updated, report.skipped, report.conflicts
+    );
+    Ok(report)
+}
+
+/// 读取其他应用的导出文件，`name` 为文件名（不含扩展名），Todoist 用作项目名称
+pub fn read_external(source: ImportSource, name: &str, content: &str) -> Result<External> {
+    match source {
+        ImportSource::Todoist => todoist::read(name, content),
+        ImportSource::MicrosoftTodo => microsoft_todo::read(content),
+        ImportSource::TickTick => ticktick::read(content),
+        ImportSource::Taskwarrior => taskwarrior::read(content),
+    }
+}
+
+/// 预览从其他应用导入的结果，不写入任何数据
+pub fn preview_external(
+    conn: &Connection,
+    source: ImportSource,
+    name: &str,
+    content: &str,
+    options: ImportOptions,
+) -> Result<ImportReport> {
+    preview_converted(conn, read_external(source, name, content)?, options)
+}
+
+/// 从其他应用的导出文件导入
+pub fn import_external(
+    conn: &Connection,
+    source: ImportSource,
+    name: &str,
+    content: &str,
+    options: ImportOptions,
+) -> Result<ImportReport> {
+    import_converted(conn, read_external(source, name, content)?, options)
+}
+
+/// 读取 iCalendar 文件中的 VTODO，`with_events` 为 true 时 VEVENT 也作为任务导入
+pub fn read_ical(content: &str, with_events: bool) -> Result<External> {
+    icalendar::read(content, with_events)
+}
+
+/// 预览从 iCalendar 文件导入的结果，不写入任何数据
+pub fn preview_ical(conn: &Connection, content: &str, with_events: bool, options: ImportOptions) -> Result<ImportReport> {
+    let mut external = read_ical(content, with_events)?;
+    icalendar::adopt_versions(conn, &mut external.data)?;
+    preview_converted(conn, external, options)
+}
+
+/// 从 iCalendar 文件导入
+pub fn import_ical(conn: &Connection, content: &str, with_events: bool, options: ImportOptions) -> Result<ImportReport> {
+    let mut external = read_ical(content, with_events)?;
+    icalendar::adopt_versions(conn, &mut external.data)?;
+    import_converted(conn, external, options)
+}
+
+fn preview_converted(conn: &Connection, mut external: External, options: ImportOptions) -> Result<ImportReport> {
+    external::adopt_local(conn, &mut external.data)?;
+    let mut report = preview(conn, &external.data, options)?;
+    report.unmapped = external.unmapped;
+    Ok(report)
+}
+
+/// 转换后的数据不包含附件
+fn import_converted(conn: &Connection, mut external: External, options: ImportOptions) -> Result<ImportReport> {
+    external::adopt_local(conn, &mut external.data)?;
+    let mut report = import(conn, &external.data, options, false)?;
+    report.unmapped = external.unmapped;
+    Ok(report)
+}
@@ -0,0 +1,461 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 导入计划的生成和执行
+
+use rusqlite::{params, Connection, OptionalExtension, Params};
+use std::collections::HashMap;
+
+use crate::error::{Context, Result};
+use crate::models::{
+    ExportData, ImportAction, ImportItem, ImportMode, ImportOptions, ImportReport, MergePolicy, Tag, TaskGroup, Todo,
+};
+use crate::utils::new_uuid;
+
+/// 导入文件中的 ID 到本地 ID 的映射
+type IdMap = HashMap<i64, i64>;
+
+/// 导入文件中的一条记录及其处理方式
+struct Entry<'a, T> {
+    record: &'a T,
+    action: ImportAction,
+    /// 匹配到的本地记录
+    local_id: Option<i64>,
+    conflict: bool,
+}
+
+impl<'a, T> Entry<'a, T> {
+    fn create(record: &'a T) -> Self {
+        Self { record, action: ImportAction::Create, local_id: None, conflict: false }
+    }
+
+    fn matched(record: &'a T, local_id: i64, action: ImportAction, conflict: bool) -> Self {
+        Self { record, action, local_id: Some(local_id), conflict }
+    }
+
+    /// 按修改时间和合并策略决定已存在的记录如何处理；修改时间相同视为内容相同
+    fn resolve(record: &'a T, local_id: i64, policy: MergePolicy, incoming: i64, local: i64) -> Self {
+        if incoming == local {
+            return Self::matched(record, local_id, ImportAction::Skip, false);
+        }
+        let action = match policy {
+            MergePolicy::Overwrite => ImportAction::Update,
+            MergePolicy::KeepNewer if incoming > local => ImportAction::Update,
+            _ => ImportAction::Skip,
+        };
+        Self::matched(record, local_id, action, true)
+    }
+
+    fn item(&self, entity: &str, uid: &str, name: &str) -> ImportItem {
+        ImportItem {
+            entity: entity.to_string(),
+            uid: uid.to_string(),
+            name: name.to_string(),
+            action: self.action,
+            local_id: self.local_id,
+            conflict: self.conflict,
+        }
+    }
+
+    /// 新建或更新后的本地记录 id
+    fn target(&self) -> Result<i64> {
+        self.local_id.context("Import entry has no local row")
+    }
+}
+
+/// 导入计划：导入文件中每个任务组、标签和任务的处理方式
+pub(super) struct Plan<'a> {
+    options: ImportOptions,
+    groups: Vec<Entry<'a, TaskGroup>>,
+    tags: Vec<Entry<'a, Tag>>,
+    todos: Vec<Entry<'a, Todo>>,
+    /// 替换时清除的本地记录数
+    removed: usize,
+}
+
+impl<'a> Plan<'a> {
+    /// 对比导入文件和本地数据生成计划，不写入数据
+    pub(super) fn build(conn: &Connection, data: &'a ExportData, options: ImportOptions) -> Result<Self> {
+        let mut plan = Plan { options, groups: Vec::new(), tags: Vec::new(), todos: Vec::new(), removed: 0 };
+        match options.mode {
+            ImportMode::Replace => {
+                plan.removed = count(conn, "task_groups")? + count(conn, "tags")? + count(conn, "todos")?;
+                plan.groups = data.task_groups.iter().map(Entry::create).collect();
+                plan.tags = data.tags.iter().map(Entry::create).collect();
+                plan.todos = data.todos.iter().map(Entry::create).collect();
+            }
+            ImportMode::Copy => {
+                plan.groups = data.task_groups.iter().map(Entry::create).collect();
+                // 标签名称不能重复：同名标签使用本地已有的
+                for tag in &data.tags {
+                    plan.tags.push(match find_tag(conn, "SELECT id, name, color FROM tags WHERE name = ?1 COLLATE NOCASE", params![tag.name])? {
+                        Some((local_id, _, _)) => Entry::matched(tag, local_id, ImportAction::Skip, false),
+                        None => Entry::create(tag),
+                    });
+                }
+                plan.todos = data.todos.iter().map(Entry::create).collect();
+            }
+            ImportMode::Merge => plan.merge(conn, data)?,
+        }
+        Ok(plan)
+    }
+
+    fn merge(&mut self, conn: &Connection, data: &'a ExportData) -> Result<()> {
+        let policy = self.options.on_conflict;
+
+        for group in &data.task_groups {
+            let local = if group.uid.is_empty() {
+                find_version(conn, "SELECT id, updated_at FROM task_groups WHERE name = ?1 ORDER BY id", params![group.name])?
+            } else {
+                find_version(conn, "SELECT id, updated_at FROM task_groups WHERE uid = ?1", params![group.uid])?
+            };
+            self.groups.push(match local {
+                Some((local_id, updated_at)) => Entry::resolve(group, local_id, policy, group.updated_at, updated_at),
+                None => Entry::create(group),
+            });
+        }
+
+        // 标签没有修改时间，内容不同时只有 Overwrite 会覆盖；名称不能重复，uid 不同的同名标签视为同一个
+        for tag in &data.tags {
+            let local = match find_tag(conn, "SELECT id, name, color FROM tags WHERE uid = ?1", params![tag.uid])? {
+                Some(local) => Some(local),
+                None => find_tag(conn, "SELECT id, name, color FROM tags WHERE name = ?1 COLLATE NOCASE", params![tag.name])?,
+            };
+            self.tags.push(match local {
+                Some((local_id, name, color)) if name == tag.name && color == tag.color => {
+                    Entry::matched(tag, local_id, ImportAction::Skip, false)
+                }
+                Some((local_id, _, _)) => {
+                    let action = if policy == MergePolicy::Overwrite { ImportAction::Update } else { ImportAction::Skip };
+                    Entry::matched(tag, local_id, action, true)
+                }
+                None => Entry::create(tag),
+            });
+        }
+
+        for todo in &data.todos {
+            let local = if todo.uid.is_empty() {
+                find_version(
+                    conn,
+                    "SELECT id, updated_at FROM todos WHERE title = ?1 AND created_at = ?2 ORDER BY id",
+                    params![todo.title, todo.created_at],
+                )?
+            } else {
+                find_version(conn, "SELECT id, updated_at FROM todos WHERE uid = ?1", params![todo.uid])?
+            };
+            self.todos.push(match local {
+                Some((local_id, updated_at)) => Entry::resolve(todo, local_id, policy, todo.updated_at, updated_at),
+                None => Entry::create(todo),
+            });
+        }
+        Ok(())
+    }
+
+    pub(super) fn report(&self, dry_run: bool) -> ImportReport {
+        let mut items = Vec::new();
+        items.extend(self.groups.iter().map(|entry| entry.item("group", &entry.record.uid, &entry.record.name)));
+        items.extend(self.tags.iter().map(|entry| entry.item("tag", &entry.record.uid, &entry.record.name)));
+        items.extend(self.todos.iter().map(|entry| entry.item("todo", &entry.record.uid, &entry.record.title)));
+
+        let count = |action| items.iter().filter(|item| item.action == action).count();
+        ImportReport {
+            options: self.options,
+            dry_run,
+            created: count(ImportAction::Create),
+            updated: count(ImportAction::Update),
+            skipped: count(ImportAction::Skip),
+            conflicts: items.iter().filter(|item| item.conflict).count(),
+            removed: self.removed,
+            items,
+            unmapped: Vec::new(),
+        }
+    }
+
+    /// 执行计划，调用方负责事务
+    pub(super) fn apply(&self, conn: &Connection, with_attachments: bool) -> Result<()> {
+        if self.options.mode == ImportMode::Replace {
+            for table in ["todo_tags", "todo_steps", "attachments", "todos", "tags", "task_groups"] {
+                conn.execute(&format!("DELETE FROM {}", table), [])
+                    .with_context(|| format!("Failed to clear {}", table))?;
+            }
+        }
+
+        // 任务组（父级在全部写入后再关联）
+        let mut groups = IdMap::new();
+        let mut parents = Vec::new();
+        for entry in &self.groups {
+            let group = entry.record;
+            let local_id = match entry.action {
+                ImportAction::Create => {
+                    conn.execute(
+                        "INSERT INTO task_groups (id, uid, name, icon, color, sort_order, created_at, updated_at)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            self.new_id(group.id),
+                            self.new_uid(conn, "task_groups", &group.uid)?,
+                            group.name,
+                            group.icon,
+                            group.color,
+                            group.sort_order,
+                            group.created_at,
+                            group.updated_at,
+                        ],
+                    )
+                    .with_context(|| format!("Failed to import task group {}", group.name))?;
+                    conn.last_insert_rowid()
+                }
+                ImportAction::Update => {
+                    let local_id = entry.target()?;
+                    conn.execute(
+                        "UPDATE task_groups SET name = ?1, icon = ?2, color = ?3, sort_order = ?4, updated_at = ?5
+                         WHERE id = ?6",
+                        params![group.name, group.icon, group.color, group.sort_order, group.updated_at, local_id],
+                    )
+                    .with_context(|| format!("Failed to update task group {}", group.name))?;
+                    local_id
+                }
+                ImportAction::Skip => {
+                    groups.insert(group.id, entry.target()?);
+                    continue;
+                }
+            };
+            groups.insert(group.id, local_id);
+            parents.push((local_id, group.parent_id));
+        }
+        set_parents(conn, "task_groups", &parents, &groups)?;
+
+        // 标签
+        let mut tags = IdMap::new();
+        let mut parents = Vec::new();
+        for entry in &self.tags {
+            let tag = entry.record;
+            let local_id = match entry.action {
+                ImportAction::Create => {
+                    conn.execute(
+                        "INSERT INTO tags (id, uid, name, color, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                        params![self.new_id(tag.id), self.new_uid(conn, "tags", &tag.uid)?, tag.name, tag.color, tag.created_at],
+                    )
+                    .with_context(|| format!("Failed to import tag {}", tag.name))?;
+                    conn.last_insert_rowid()
+                }
+                ImportAction::Update => {
+                    let local_id = entry.target()?;
+                    conn.execute(
+                        "UPDATE tags SET name = ?1, color = ?2 WHERE id = ?3",
+                        params![tag.name, tag.color, local_id],
+                    )
+                    .with_context(|| format!("Failed to update tag {}", tag.name))?;
+                    local_id
+                }
+                ImportAction::Skip => {
+                    tags.insert(tag.id, entry.target()?);
+                    continue;
+                }
+            };
+            tags.insert(tag.id, local_id);
+            parents.push((local_id, tag.parent_id));
+        }
+        set_parents(conn, "tags", &parents, &tags)?;
+
+        // 任务及其标签关联、步骤和附件
+        for entry in &self.todos {
+            let todo = entry.record;
+            let group_id = todo.group_id.and_then(|group_id| groups.get(&group_id).copied());
+            let todo_id = match entry.action {
+                ImportAction::Create => {
+                    conn.execute(
+                        "INSERT INTO todos (
+                            id, uid, title, description, status, priority,
+                            group_id, assignee, start_date, due_date, completed_at,
+                            created_at, updated_at
+                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+                        params![
+                            self.new_id(todo.id),
+                            self.new_uid(conn, "todos", &todo.uid)?,
+                            todo.title,
+                            todo.description,
+                            todo.status as i32,
+                            todo.priority,
+                            group_id,
+                            todo.assignee,
+                            todo.start_date,
+                            todo.due_date,
+                            todo.completed_at,
+                            todo.created_at,
+                            todo.updated_at,
+                        ],
+                    )
+                    .with_context(|| format!("Failed to import todo {}", todo.title))?;
+                    conn.last_insert_rowid()
+                }
+                ImportAction::Update => {
+                    let local_id = entry.target()?;
+                    conn.execute(
+                        "UPDATE todos SET
+                            title = ?1, description = ?2, status = ?3, priority = ?4,
+                            group_id = ?5, assignee = ?6,
+                            start_date = ?7, due_date = ?8, completed_at = ?9,
+                            updated_at = ?10
+                         WHERE id = ?11",
+                        params![
+                            todo.title,
+                            todo.description,
+                            todo.status as i32,
+                            todo.priority,
+                            group_id,
+                            todo.assignee,
+                            todo.start_date,
+                            todo.due_date,
+                            todo.completed_at,
+                            todo.updated_at,
+                            local_id,
+                        ],
+                    )
+                    .with_context(|| format!("Failed to update todo {}", todo.title))?;
+                    local_id
+                }
+                ImportAction::Skip => continue,
+            };
+            self.apply_children(conn, todo, todo_id, &tags, with_attachments)?;
+        }
+        Ok(())
+    }
+
+    /// 写入任务的标签关联、步骤和附件；导入文件中没有的部分（字段为 None）保持本地不变
+    fn apply_children(&self, conn: &Connection, todo: &Todo, todo_id: i64, tags: &IdMap, with_attachments: bool) -> Result<()> {
+        if let Some(todo_tags) = &todo.tags {
+            conn.execute("DELETE FROM todo_tags WHERE todo_id = ?1", params![todo_id])
+                .context("Failed to clear todo_tags")?;
+            for tag_id in todo_tags.iter().filter_map(|tag| tags.get(&tag.id)) {
+                conn.execute(
+                    "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)",
+                    params![todo_id, tag_id],
+                )
+                .context("Failed to import todo_tags")?;
+            }
+        }
+
+        if let Some(steps) = &todo.steps {
+            conn.execute("DELETE FROM todo_steps WHERE todo_id = ?1", params![todo_id])
+                .context("Failed to clear steps")?;
+            for step in steps {
+                conn.execute(
+                    "INSERT INTO todo_steps (id, uid, todo_id, title, is_completed, sort_order, created_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        self.new_id(step.id),
+                        self.new_uid(conn, "todo_steps", &step.uid)?,
+                        todo_id,
+                        step.title,
+                        step.is_completed as i32,
+                        step.sort_order,
+                        step.created_at,
+                    ],
+                )
+                .with_context(|| format!("Failed to import step {}", step.title))?;
+            }
+        }
+
+        // 附件只增加本地没有的记录，不删除本地附件
+        for attachment in todo.attachments.iter().flatten().filter(|_| with_attachments) {
+            if self.options.mode != ImportMode::Copy && exists(conn, "attachments", &attachment.uid)? {
+                continue;
+            }
+            conn.execute(
+                "INSERT INTO attachments (id, uid, todo_id, name, file_path, file_size, mime_type, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    self.new_id(attachment.id),
+                    self.new_uid(conn, "attachments", &attachment.uid)?,
+                    todo_id,
+                    attachment.name,
+                    attachment.file_path,
+                    attachment.file_size,
+                    attachment.mime_type,
+                    attachment.created_at,
+                ],
+            )
+            .with_context(|| format!("Failed to import attachment {}", attachment.name))?;
+        }
+        Ok(())
+    }
+
+    /// 替换时沿用导入文件中的 ID，否则由数据库分配
+    fn new_id(&self, id: i64) -> Option<i64> {
+        (self.options.mode == ImportMode::Replace).then_some(id)
+    }
+
+    /// 沿用导入文件中的 uid；作为副本导入、文件中没有 uid 或 uid 已被本地其他记录使用时生成新的
+    fn new_uid(&self, conn: &Connection, table: &str, uid: &str) -> Result<String> {
+        if self.options.mode == ImportMode::Copy || uid.is_empty() || exists(conn, table, uid)? {
+            Ok(new_uuid())
+        } else {
+            Ok(uid.to_string())
+        }
+    }
+}
+
+fn count(conn: &Connection, table: &str) -> Result<usize> {
+    let count: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+        .with_context(|| format!("Failed to count {}", table))?;
+    Ok(count as usize)
+}
+
+fn exists(conn: &Connection, table: &str, uid: &str) -> Result<bool> {
+    conn.query_row(&format!("SELECT EXISTS(SELECT 1 FROM {} WHERE uid = ?1)", table), params![uid], |row| row.get(0))
+        .with_context(|| format!("Failed to look up {} uid", table))
+}
+
+/// 查询本地记录的 id 和修改时间
+fn find_version(conn: &Connection, sql: &str, params: impl Params) -> Result<Option<(i64, i64)>> {
+    conn.query_row(sql, params, |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()
+        .context("Failed to look up existing row")
+}
+
+/// 查询本地标签的 id、名称和颜色
+fn find_tag(conn: &Connection, sql: &str, params: impl Params) -> Result<Option<(i64, String, String)>> {
+    conn.query_row(sql, params, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .optional()
+        .context("Failed to look up existing tag")
+}
+
+/// 关联写入的记录的父级：导入文件中为顶级的设为顶级，父级不在导入文件中时保持不变，会形成循环时忽略
+fn set_parents(conn: &Connection, table: &str, parents: &[(i64, Option<i64>)], ids: &IdMap) -> Result<()> {
+    for &(id, parent_id) in parents {
+        let parent = match parent_id {
+            Some(parent_id) => match ids.get(&parent_id) {
+                Some(&parent) => Some(parent),
+                None => continue,
+            },
+            None => None,
+        };
+        if let Some(parent) = parent {
+            if creates_cycle(conn, table, id, parent)? {
+                tracing::warn!("Ignoring imported parent {} of {} {}: it would create a cycle", parent, table, id);
+                continue;
+            }
+        }
+        conn.execute(&format!("UPDATE {} SET parent_id = ?1 WHERE id = ?2", table), params![parent, id])
+            .with_context(|| format!("Failed to set parent of {} {}", table, id))?;
+    }
+    Ok(())
+}
+
+/// `parent` 是否为 `id` 自身或其后代
+fn creates_cycle(conn: &Connection, table: &str, id: i64, parent: i64) -> Result<bool> {
+    conn.query_row(
+        &format!(
+            "WITH RECURSIVE ancestors(id) AS (
+                SELECT ?1
+                UNION
+                SELECT t.parent_id FROM {} t JOIN ancestors a ON t.id = a.id WHERE t.parent_id IS NOT NULL
+             )
+             SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?2)",
+            table,
+        ),
+        params![parent, id],
+        |row| row.get(0),
+    )
+    .with_context(|| format!("Failed to check {} hierarchy", table))
+}
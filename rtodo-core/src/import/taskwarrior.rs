@@ -0,0 +1,92 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Taskwarrior `task export` 的 JSON
+//!
+//! 文件是任务数组，或每行一个任务（旧版本的导出格式）。任务沿用 Taskwarrior 的 uuid；
+//! 项目（`work.clientA`）对应多级任务组，注释（annotations）追加到描述中。
+//! 已删除的任务和周期任务的模板不导入。
+
+use serde_json::Value;
+
+use super::external::{json_is_empty, json_str, json_summary, parse_datetime, Builder, External};
+use crate::error::Result;
+use crate::models::{priority, TodoStatus};
+
+/// 已导入或有意忽略的属性
+const KNOWN: &[&str] = &[
+    "id", "uuid", "description", "status", "project", "tags", "priority", "entry", "modified", "end", "start", "due",
+    "scheduled", "annotations", "urgency", "mask", "imask", "parent",
+];
+
+pub(super) fn read(content: &str) -> Result<External> {
+    let content = content.trim_start_matches('\u{feff}').trim();
+    let tasks: Vec<Value> = if content.starts_with('[') {
+        serde_json::from_str(content)?
+    } else {
+        content
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?
+    };
+
+    let mut builder = Builder::new("taskwarrior");
+    for task in &tasks {
+        let status = json_str(task, "status");
+        if matches!(status, "deleted" | "recurring") {
+            builder.unmapped("status", status);
+            continue;
+        }
+
+        let uuid = json_str(task, "uuid");
+        let key = match uuid {
+            "" => format!("{}/{}", json_str(task, "description"), json_str(task, "entry")),
+            uuid => uuid.to_string(),
+        };
+        let mut todo = builder.todo(&key, json_str(task, "description"));
+        if uuid::Uuid::parse_str(uuid).is_ok() {
+            todo.uid = uuid.to_lowercase();
+        }
+        let project: Vec<_> = json_str(task, "project").split('.').collect();
+        todo.group_id = builder.group(&project);
+        todo.priority = match json_str(task, "priority") {
+            "H" => priority::URGENT,
+            "M" => priority::IMPORTANT,
+            _ => priority::NORMAL,
+        };
+        todo.status = if status == "completed" {
+            TodoStatus::Done
+        } else if task.get("start").is_some() {
+            TodoStatus::InProgress
+        } else {
+            TodoStatus::Todo
+        };
+        if let Some(created_at) = parse_datetime(json_str(task, "entry")) {
+            todo.created_at = created_at;
+            todo.updated_at = created_at;
+        }
+        if let Some(updated_at) = parse_datetime(json_str(task, "modified")) {
+            todo.updated_at = updated_at;
+        }
+        todo.completed_at = parse_datetime(json_str(task, "end")).filter(|_| todo.status == TodoStatus::Done);
+        todo.due_date = parse_datetime(json_str(task, "due"));
+        todo.start_date = parse_datetime(json_str(task, "scheduled"));
+        let tags = task.get("tags").and_then(Value::as_array).into_iter().flatten();
+        todo.tags = Some(tags.filter_map(Value::as_str).filter_map(|name| builder.tag(name)).collect());
+
+        // 依赖、重复、等待等属性，以及用户自定义属性（UDA）
+        for (field, value) in task.as_object().into_iter().flatten() {
+            if !KNOWN.contains(&field.as_str()) && !json_is_empty(value) {
+                builder.unmapped(field, &json_summary(value));
+            }
+        }
+
+        let index = builder.push(todo);
+        for annotation in task.get("annotations").and_then(Value::as_array).into_iter().flatten() {
+            builder.append_description(index, json_str(annotation, "description"));
+        }
+    }
+    Ok(builder.finish())
+}
@@ -0,0 +1,334 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::database::repositories::{DataRepository, GroupRepository, TagRepository, TodoRepository};
+use crate::models::{priority, ImportAction, ImportMode, ImportSource, MergePolicy, Todo, TodoStatus};
+use crate::test_support::memory_db;
+use rusqlite::params;
+
+/// 含一个任务组、一个标签和两个任务的导出数据
+fn sample() -> ExportData {
+    let conn = memory_db();
+    let group = GroupRepository::create(&conn, "Work", None, None, None).unwrap();
+    let tag = TagRepository::create(&conn, "urgent", "#ff0000", None).unwrap();
+    TodoRepository::create(&conn, "Write report", None, Some(group.id), None, None, 1, Some(vec![tag.id])).unwrap();
+    TodoRepository::create(&conn, "Book flights", None, Some(group.id), None, None, 0, None).unwrap();
+    DataRepository::export_all(&conn).unwrap()
+}
+
+fn options(mode: ImportMode, on_conflict: MergePolicy) -> ImportOptions {
+    ImportOptions { mode, on_conflict }
+}
+
+fn todos(conn: &Connection) -> Vec<Todo> {
+    let mut todos = TodoRepository::list(conn, None, None, None, None, None, None, None).unwrap();
+    todos.sort_by(|a, b| a.title.cmp(&b.title));
+    todos
+}
+
+#[test]
+fn preview_matches_import_without_writing() {
+    let data = sample();
+    let conn = memory_db();
+    let options = ImportOptions::default();
+
+    let planned = preview(&conn, &data, options).unwrap();
+    assert!(planned.dry_run);
+    assert_eq!((planned.created, planned.updated, planned.skipped, planned.conflicts), (4, 0, 0, 0));
+    assert!(todos(&conn).is_empty());
+
+    let report = import(&conn, &data, options, false).unwrap();
+    assert!(!report.dry_run);
+    assert_eq!(report.created, planned.created);
+    assert_eq!(todos(&conn).len(), 2);
+
+    // 再次导入同一份文件时全部跳过
+    let again = preview(&conn, &data, options).unwrap();
+    assert_eq!((again.created, again.skipped, again.conflicts), (0, 4, 0));
+}
+
+#[test]
+fn merge_policies_decide_conflicting_todos() {
+    let data = sample();
+    let conn = memory_db();
+    import(&conn, &data, ImportOptions::default(), false).unwrap();
+
+    // 两个任务在导入文件中都有修改；本地的 "Write report" 修改得更晚
+    let mut incoming = data.clone();
+    for todo in incoming.todos.iter_mut() {
+        todo.title = format!("{} (remote)", todo.title);
+        todo.updated_at += 1000;
+    }
+    conn.execute(
+        "UPDATE todos SET title = 'Write report (local)', updated_at = updated_at + 5000 WHERE title = 'Write report'",
+        [],
+    )
+    .unwrap();
+
+    let planned = preview(&conn, &incoming, options(ImportMode::Merge, MergePolicy::Skip)).unwrap();
+    assert_eq!(planned.conflicts, 2);
+    assert!(planned.items.iter().filter(|item| item.entity == "todo").all(|item| item.action == ImportAction::Skip));
+
+    import(&conn, &incoming, options(ImportMode::Merge, MergePolicy::KeepNewer), false).unwrap();
+    let titles: Vec<_> = todos(&conn).into_iter().map(|t| t.title).collect();
+    assert_eq!(titles, ["Book flights (remote)", "Write report (local)"]);
+
+    import(&conn, &incoming, options(ImportMode::Merge, MergePolicy::Overwrite), false).unwrap();
+    let titles: Vec<_> = todos(&conn).into_iter().map(|t| t.title).collect();
+    assert_eq!(titles, ["Book flights (remote)", "Write report (remote)"]);
+}
+
+#[test]
+fn copies_get_new_ids_and_references() {
+    let data = sample();
+    let conn = memory_db();
+    let local = GroupRepository::create(&conn, "Home", None, None, None).unwrap();
+    TodoRepository::create(&conn, "Water plants", None, Some(local.id), None, None, 0, None).unwrap();
+
+    let copy = options(ImportMode::Copy, MergePolicy::default());
+    import(&conn, &data, copy, false).unwrap();
+    let report = import(&conn, &data, copy, false).unwrap();
+    // 同名标签使用本地已有的
+    assert_eq!((report.created, report.skipped), (3, 1));
+
+    let all = todos(&conn);
+    assert_eq!(all.len(), 5);
+    let reports: Vec<_> = all.iter().filter(|t| t.title == "Write report").collect();
+    assert_ne!(reports[0].uid, reports[1].uid);
+    assert!(reports.iter().all(|t| !data.todos.iter().any(|source| source.uid == t.uid)));
+    assert_ne!(reports[0].group_id, reports[1].group_id);
+    for todo in reports {
+        let group = GroupRepository::get(&conn, todo.group_id.unwrap()).unwrap().unwrap();
+        assert_eq!(group.name, "Work");
+        assert_eq!(todo.tags.as_ref().unwrap()[0].name, "urgent");
+    }
+    let local_todo = all.iter().find(|t| t.title == "Water plants").unwrap();
+    assert_eq!(local_todo.group_id, Some(local.id));
+}
+
+#[test]
+fn replace_restores_ids_and_uids() {
+    let data = sample();
+    let conn = memory_db();
+    for title in ["Old one", "Old two", "Old three"] {
+        TodoRepository::create(&conn, title, None, None, None, None, 0, None).unwrap();
+    }
+
+    let report = import(&conn, &data, options(ImportMode::Replace, MergePolicy::default()), false).unwrap();
+    assert_eq!(report.removed, 3);
+
+    let mut ids: Vec<_> = todos(&conn).into_iter().map(|t| (t.id, t.uid)).collect();
+    let mut expected: Vec<_> = data.todos.iter().map(|t| (t.id, t.uid.clone())).collect();
+    ids.sort();
+    expected.sort();
+    assert_eq!(ids, expected);
+    let group_id: i64 = conn.query_row("SELECT group_id FROM todos WHERE id = ?1", params![expected[0].0], |row| row.get(0))
+        .unwrap();
+    assert_eq!(group_id, data.task_groups[0].id);
+}
+
+fn unmapped(report: &ImportReport) -> Vec<&str> {
+    report.unmapped.iter().map(|field| field.field.as_str()).collect()
+}
+
+#[test]
+fn reads_todoist_projects() {
+    let csv = "\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE,DURATION,DURATION_UNIT
+meta,view_style=list,,,,,,,,,,
+task,Write report @work @writing,Quarterly numbers,1,1,Alice (111),Bob (222),2024-03-15,en,UTC,30,minute
+task,Collect data,,4,2,Alice (111),,,en,UTC,,
+note,Ask finance first,,,,Alice (111),,,,,,
+section,Later,,,,,,,,,,
+task,Water plants @home,,4,1,Alice (111),,every day,en,UTC,,
+";
+    let data = read_external(ImportSource::Todoist, "Work", csv).unwrap().data;
+    let groups: Vec<_> = data.task_groups.iter().map(|g| (g.name.as_str(), g.parent_id)).collect();
+    assert_eq!(groups, [("Work", None), ("Later", Some(data.task_groups[0].id))]);
+
+    let report = &data.todos[0];
+    assert_eq!(report.title, "Write report");
+    assert_eq!(report.description.as_deref(), Some("Quarterly numbers\n\nAsk finance first"));
+    assert_eq!(report.priority, priority::URGENT);
+    assert_eq!(report.assignee.as_deref(), Some("Bob"));
+    assert!(report.due_date.is_some());
+    let tags: Vec<_> = report.tags.as_ref().unwrap().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(tags, ["work", "writing"]);
+    assert_eq!(report.steps.as_ref().unwrap()[0].title, "Collect data");
+
+    let plants = &data.todos[1];
+    assert_eq!(plants.group_id, Some(data.task_groups[1].id));
+    assert_eq!(plants.due_date, None);
+
+    let conn = memory_db();
+    let report = preview_external(&conn, ImportSource::Todoist, "Work", csv, ImportOptions::default()).unwrap();
+    assert_eq!(unmapped(&report), ["DATE", "DURATION", "DURATION_UNIT"]);
+    assert_eq!(report.unmapped[0].example, "every day");
+}
+
+#[test]
+fn reads_microsoft_todo_lists() {
+    let json = r#"{"lists": [{"displayName": "Errands", "tasks": [
+        {"id": "AAMk1", "title": "Buy milk", "importance": "high", "status": "completed",
+         "body": {"content": "Semi-skimmed", "contentType": "text"},
+         "createdDateTime": "2024-03-10T08:00:00.1234567Z", "lastModifiedDateTime": "2024-03-11T08:00:00Z",
+         "completedDateTime": {"dateTime": "2024-03-11T00:00:00.0000000", "timeZone": "UTC"},
+         "dueDateTime": {"dateTime": "2024-03-12T00:00:00.0000000", "timeZone": "UTC"},
+         "categories": ["Shopping"], "isReminderOn": false,
+         "recurrence": {"pattern": {"type": "weekly"}},
+         "checklistItems": [{"displayName": "Check fridge", "isChecked": true}]},
+        {"id": "AAMk2", "title": "Call plumber", "status": "waitingOnOthers"}
+    ]}]}"#;
+    let data = read_external(ImportSource::MicrosoftTodo, "tasks", json).unwrap().data;
+    assert_eq!(data.task_groups[0].name, "Errands");
+
+    let milk = &data.todos[0];
+    assert_eq!(milk.status, TodoStatus::Done);
+    assert_eq!(milk.priority, priority::IMPORTANT);
+    assert_eq!(milk.description.as_deref(), Some("Semi-skimmed"));
+    assert_eq!(milk.completed_at, Some(1710115200000));
+    assert_eq!(milk.due_date, Some(1710201600000));
+    assert_eq!(milk.updated_at, 1710144000000);
+    assert_eq!(milk.tags.as_ref().unwrap()[0].name, "Shopping");
+    assert!(milk.steps.as_ref().unwrap()[0].is_completed);
+    assert_eq!(data.todos[1].status, TodoStatus::Todo);
+
+    let conn = memory_db();
+    let report = preview_external(&conn, ImportSource::MicrosoftTodo, "tasks", json, ImportOptions::default()).unwrap();
+    assert_eq!(unmapped(&report), ["recurrence", "status"]);
+}
+
+#[test]
+fn reads_ticktick_backups() {
+    let csv = "\"Date: 2024-03-15+0000\"
+\"Version: 7.1\"
+\"Status: 
+0 Normal
+1 Completed
+2 Archived\"
+\"Folder Name\",\"List Name\",\"Title\",\"Kind\",\"Tags\",\"Content\",\"Is Check list\",\"Start Date\",\"Due Date\",\"Reminder\",\"Repeat\",\"Priority\",\"Status\",\"Created Time\",\"Completed Time\",\"Order\",\"Timezone\",\"Is All Day\",\"Is Floating\",\"Column Name\",\"Column Order\",\"View Mode\",\"taskId\",\"parentId\"
+\"Home\",\"Chores\",\"Pack\",\"CHECKLIST\",\"travel, home\",\"▪Passport
+▫Charger\",\"Y\",\"\",\"2024-03-20T09:00:00+0000\",\"\",\"\",\"5\",\"0\",\"2024-03-01T10:00:00+0000\",\"\",\"1\",\"UTC\",\"false\",\"false\",\"\",\"\",\"list\",\"1\",\"\"
+\"Home\",\"Chores\",\"Book taxi\",\"TEXT\",\"\",\"\",\"N\",\"\",\"\",\"\",\"\",\"0\",\"2\",\"2024-03-01T10:00:00+0000\",\"2024-03-02T10:00:00+0000\",\"2\",\"UTC\",\"false\",\"false\",\"\",\"\",\"list\",\"2\",\"1\"
+\"\",\"Inbox\",\"Renew lease\",\"TEXT\",\"\",\"Before May\",\"N\",\"\",\"\",\"(0)PT0S\",\"RRULE:FREQ=YEARLY\",\"3\",\"0\",\"2024-03-01T10:00:00+0000\",\"\",\"3\",\"UTC\",\"false\",\"false\",\"\",\"\",\"list\",\"3\",\"\"
+";
+    let data = read_external(ImportSource::TickTick, "backup", csv).unwrap().data;
+    let groups: Vec<_> = data.task_groups.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(groups, ["Home", "Chores", "Inbox"]);
+    assert_eq!(data.todos.len(), 2);
+
+    let pack = &data.todos[0];
+    assert_eq!(pack.priority, priority::URGENT);
+    assert_eq!(pack.group_id, Some(data.task_groups[1].id));
+    assert_eq!(pack.due_date, Some(1710925200000));
+    let steps: Vec<_> = pack.steps.as_ref().unwrap().iter().map(|s| (s.title.as_str(), s.is_completed)).collect();
+    assert_eq!(steps, [("Passport", true), ("Charger", false), ("Book taxi", true)]);
+    assert_eq!(pack.tags.as_ref().unwrap().len(), 2);
+
+    let lease = &data.todos[1];
+    assert_eq!(lease.priority, priority::IMPORTANT);
+    assert_eq!(lease.description.as_deref(), Some("Before May"));
+
+    let conn = memory_db();
+    let report = preview_external(&conn, ImportSource::TickTick, "backup", csv, ImportOptions::default()).unwrap();
+    assert_eq!(unmapped(&report), ["Reminder", "Repeat"]);
+}
+
+#[test]
+fn reimporting_taskwarrior_exports_matches_previous_rows() {
+    let json = r#"[
+{"id":1,"description":"Fix bug","entry":"20240301T100000Z","modified":"20240302T100000Z","project":"work.backend","priority":"H","status":"pending","start":"20240302T090000Z","tags":["code"],"uuid":"6F1C7A8E-3E0B-4C55-9F0A-2B7C1D0E9A11","annotations":[{"entry":"20240302T100000Z","description":"See issue 42"}],"estimate":"PT2H","urgency":8.9},
+{"id":0,"description":"Old task","entry":"20240101T100000Z","modified":"20240102T100000Z","end":"20240102T100000Z","status":"completed","uuid":"0d5b6a0e-4a6f-4d1e-8f7a-5a4c3b2a1f00"},
+{"id":0,"description":"Gone","entry":"20240101T100000Z","status":"deleted","uuid":"11111111-2222-4333-8444-555555555555"}
+]"#;
+    let conn = memory_db();
+    // 本地已有同名任务组
+    let work = GroupRepository::create(&conn, "work", None, None, Some("#ff0000")).unwrap();
+
+    let report = import_external(&conn, ImportSource::Taskwarrior, "tasks", json, ImportOptions::default()).unwrap();
+    assert_eq!(unmapped(&report), ["estimate", "status"]);
+    let all = todos(&conn);
+    assert_eq!(all.len(), 2);
+    let bug = &all[0];
+    assert_eq!(bug.uid, "6f1c7a8e-3e0b-4c55-9f0a-2b7c1d0e9a11");
+    assert_eq!(bug.status, TodoStatus::InProgress);
+    assert_eq!(bug.description.as_deref(), Some("See issue 42"));
+    let backend = GroupRepository::get(&conn, bug.group_id.unwrap()).unwrap().unwrap();
+    assert_eq!((backend.name.as_str(), backend.parent_id), ("backend", Some(work.id)));
+    assert_eq!(all[1].status, TodoStatus::Done);
+
+    let again = preview_external(&conn, ImportSource::Taskwarrior, "tasks", json, ImportOptions::default()).unwrap();
+    assert_eq!((again.created, again.updated, again.conflicts), (0, 0, 0));
+    let work = GroupRepository::get(&conn, work.id).unwrap().unwrap();
+    assert_eq!(work.color.as_deref(), Some("#ff0000"));
+}
+
+#[test]
+fn ical_exports_round_trip_through_uids() {
+    let source = memory_db();
+    let work = GroupRepository::create(&source, "Work", None, None, None).unwrap();
+    let backend = GroupRepository::create(&source, "Back, end", Some(work.id), None, None).unwrap();
+    let tag = TagRepository::create(&source, "review", "#ff0000", None).unwrap();
+    let title = "Migrate the database; then update every deployment script in the 生产环境 cluster";
+    let description = Some("Line one\nLine two, with comma");
+    let (start, due) = (Some(1_710_000_000_000), Some(1_710_500_000_000));
+    let todo = TodoRepository::create(&source, title, description, Some(backend.id), start, due, priority::URGENT, Some(vec![tag.id]))
+        .unwrap();
+    TodoRepository::create(&source, "Plain", None, None, None, None, 0, None).unwrap();
+    source.execute("UPDATE todos SET updated_at = updated_at + 123 WHERE id = ?1", params![todo.id]).unwrap();
+
+    let ics = DataRepository::export_ical(&source, &todos(&source)).unwrap();
+    assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    assert!(ics.contains("PRIORITY:1\r\n"));
+    assert!(ics.contains("X-RTODO-GROUP:Work,Back\\, end\r\n"));
+
+    // 导出后再导入同一个数据库时没有变化
+    let again = preview_ical(&source, &ics, false, ImportOptions::default()).unwrap();
+    assert_eq!((again.created, again.updated, again.conflicts), (0, 0, 0));
+
+    let target = memory_db();
+    let report = import_ical(&target, &ics, false, ImportOptions::default()).unwrap();
+    assert!(report.unmapped.is_empty());
+    let imported = todos(&target);
+    let original = todos(&source);
+    assert_eq!(imported.len(), 2);
+    for (imported, original) in imported.iter().zip(&original) {
+        assert_eq!(imported.uid, original.uid);
+        assert_eq!(imported.title, original.title);
+        assert_eq!(imported.description, original.description);
+        assert_eq!(imported.priority, original.priority);
+        assert_eq!(imported.due_date.map(|ms| ms / 1000), original.due_date.map(|ms| ms / 1000));
+    }
+    let migrated = &imported[0];
+    assert_eq!(migrated.tags.as_ref().unwrap()[0].name, "review");
+    let group = GroupRepository::get(&target, migrated.group_id.unwrap()).unwrap().unwrap();
+    let parent = GroupRepository::get(&target, group.parent_id.unwrap()).unwrap().unwrap();
+    assert_eq!((group.name.as_str(), parent.name.as_str()), ("Back, end", "Work"));
+}
+
+#[test]
+fn reads_vevents_only_when_asked() {
+    let ics = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//Example//Calendar//EN\r\n\
+BEGIN:VTODO\r\nUID:task-1@example.com\r\nSUMMARY:Renew passport\r\nDUE;VALUE=DATE:20240415\r\n\
+STATUS:CANCELLED\r\nPRIORITY:5\r\nCATEGORIES:Admin,Travel\r\nRRULE:FREQ=YEARLY\r\n\
+BEGIN:VALARM\r\nACTION:DISPLAY\r\nTRIGGER:-PT15M\r\nEND:VALARM\r\nEND:VTODO\r\n\
+BEGIN:VEVENT\r\nUID:event-1@example.com\r\nSUMMARY:Team lunch\r\nDTSTART:20240410T110000Z\r\n\
+DTEND:20240410T120000Z\r\nLOCATION:Cafe\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    let todos_only = read_ical(ics, false).unwrap();
+    assert_eq!(todos_only.data.todos.len(), 1);
+    let passport = &todos_only.data.todos[0];
+    assert_eq!(passport.status, TodoStatus::Done);
+    assert_eq!(passport.priority, priority::IMPORTANT);
+    assert!(passport.due_date.is_some());
+    assert_eq!(passport.tags.as_ref().unwrap().len(), 2);
+    assert_eq!(read_ical(ics, false).unwrap().data.todos[0].uid, passport.uid);
+    let fields: Vec<_> = todos_only.unmapped.iter().map(|field| field.field.as_str()).collect();
+    assert_eq!(fields, ["RRULE", "STATUS", "VALARM", "VEVENT"]);
+
+    let with_events = read_ical(ics, true).unwrap();
+    let lunch = &with_events.data.todos[1];
+    assert_eq!(lunch.title, "Team lunch");
+    assert_eq!((lunch.start_date, lunch.due_date), (Some(1712746800000), Some(1712750400000)));
+    assert!(with_events.unmapped.iter().any(|field| field.field == "LOCATION"));
+}
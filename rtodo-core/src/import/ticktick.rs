@@ -0,0 +1,102 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! TickTick（滴答清单）备份的 CSV
+//!
+//! 文件开头的几行说明文字之后是表头。文件夹和清单对应两级任务组；检查清单类型的任务的内容
+//! 每行是一个检查项（`▫` 未完成，`▪` 已完成），对应步骤；有父任务的子任务也对应父任务的步骤。
+
+use std::collections::{HashMap, HashSet};
+
+use super::external::{non_empty, parse_datetime, Builder, External, Table};
+use crate::error::Result;
+use crate::models::{priority, TodoStatus};
+
+/// 已导入或有意忽略的列
+const KNOWN: &[&str] = &[
+    "Folder Name", "List Name", "Title", "Kind", "Tags", "Content", "Is Check list", "Start Date", "Due Date",
+    "Priority", "Status", "Created Time", "Completed Time", "Order", "Timezone", "Is All Day", "Is Floating",
+    "Column Order", "View Mode", "taskId", "parentId",
+];
+
+pub(super) fn read(content: &str) -> Result<External> {
+    let table = Table::parse(content, "Folder Name")?;
+    let mut builder = Builder::new("ticktick");
+
+    // 文件中的任务，以及被其他任务引用为父任务的任务；子任务本身还有子任务时作为单独的任务导入
+    let ids: HashSet<&str> = table.rows.iter().map(|row| table.get(row, "taskId")).collect();
+    let parents: HashSet<&str> = table.rows.iter().map(|row| table.get(row, "parentId")).collect();
+    let mut indexes = HashMap::new();
+    let mut subtasks = Vec::new();
+
+    for row in &table.rows {
+        let task_id = table.get(row, "taskId");
+        let parent_id = table.get(row, "parentId");
+        let is_completed = matches!(table.get(row, "Status"), "1" | "2");
+        if !parent_id.is_empty() && ids.contains(parent_id) && !parents.contains(task_id) {
+            subtasks.push((parent_id, table.get(row, "Title"), is_completed));
+            continue;
+        }
+
+        let title = table.get(row, "Title");
+        let key = match task_id {
+            "" => format!("{}/{}/{}", table.get(row, "List Name"), title, table.get(row, "Created Time")),
+            id => id.to_string(),
+        };
+        let mut todo = builder.todo(&key, title);
+        todo.group_id = builder.group(&[table.get(row, "Folder Name"), table.get(row, "List Name")]);
+        todo.priority = match table.get(row, "Priority") {
+            "5" => priority::URGENT,
+            "3" => priority::IMPORTANT,
+            _ => priority::NORMAL,
+        };
+        if is_completed {
+            todo.status = TodoStatus::Done;
+        }
+        if let Some(created_at) = parse_datetime(table.get(row, "Created Time")) {
+            todo.created_at = created_at;
+            todo.updated_at = created_at;
+        }
+        todo.completed_at = parse_datetime(table.get(row, "Completed Time"));
+        if let Some(completed_at) = todo.completed_at {
+            todo.updated_at = todo.updated_at.max(completed_at);
+        }
+        todo.start_date = parse_datetime(table.get(row, "Start Date"));
+        todo.due_date = parse_datetime(table.get(row, "Due Date"));
+        let tags = table.get(row, "Tags").split(',');
+        todo.tags = Some(tags.filter_map(|name| builder.tag(name)).collect());
+
+        let is_checklist = table.get(row, "Is Check list") == "Y";
+        if !is_checklist {
+            todo.description = non_empty(table.get(row, "Content"));
+        }
+        for (field, value) in table.others(row, KNOWN) {
+            builder.unmapped(field, value);
+        }
+
+        let index = builder.push(todo);
+        if is_checklist {
+            for line in table.get(row, "Content").lines() {
+                let line = line.trim();
+                if let Some(item) = line.strip_prefix('▪') {
+                    builder.step(index, item, true);
+                } else if let Some(item) = line.strip_prefix('▫') {
+                    builder.step(index, item, false);
+                } else if !line.is_empty() {
+                    builder.append_description(index, line);
+                }
+            }
+        }
+        if !task_id.is_empty() {
+            indexes.insert(task_id, index);
+        }
+    }
+
+    // 子任务可能排在父任务之前
+    for (parent_id, title, is_completed) in subtasks {
+        if let Some(&index) = indexes.get(parent_id) {
+            builder.step(index, title, is_completed);
+        }
+    }
+    Ok(builder.finish())
+}
@@ -0,0 +1,119 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Todoist 项目导出的 CSV
+//!
+//! 每个文件是一个项目，对应一个任务组（以文件名命名）；分区（section）对应子任务组，
+//! 缩进的子任务对应上一个任务的步骤，评论（note）追加到上一个任务的描述中，
+//! 标题中的 `@标签` 对应标签。CSV 中的优先级 1 最高、4 最低。
+
+use std::collections::HashMap;
+
+use super::external::{non_empty, parse_datetime, Builder, External, Table};
+use crate::error::Result;
+use crate::models::priority;
+
+/// 已导入或有意忽略的列
+const KNOWN: &[&str] = &[
+    "TYPE", "CONTENT", "DESCRIPTION", "PRIORITY", "INDENT", "AUTHOR", "RESPONSIBLE", "DATE", "DATE_LANG", "TIMEZONE",
+    "DEADLINE", "DEADLINE_LANG",
+];
+
+pub(super) fn read(project: &str, content: &str) -> Result<External> {
+    let table = Table::parse(content, "TYPE")?;
+    let mut builder = Builder::new("todoist");
+    let project = project.trim();
+    let mut group_id = builder.group(&[project]);
+    // 当前的顶级任务，缩进的子任务和评论属于它
+    let mut parent = None;
+    // 同一分区中同名任务的序号，用于生成稳定的 uid
+    let mut seen = HashMap::new();
+    let mut section = String::new();
+
+    for row in &table.rows {
+        let text = table.get(row, "CONTENT");
+        match table.get(row, "TYPE") {
+            "section" => {
+                section = text.to_string();
+                group_id = builder.group(&[project, text]);
+                parent = None;
+            }
+            "note" => match parent {
+                Some(index) => builder.append_description(index, text),
+                None => builder.unmapped("note", text),
+            },
+            "task" => {
+                let (title, labels) = split_labels(text);
+                let indent: u32 = table.get(row, "INDENT").parse().unwrap_or(1);
+                if indent > 1 {
+                    if let Some(index) = parent {
+                        builder.step(index, &title, false);
+                        // 步骤只有标题
+                        for column in ["DESCRIPTION", "DATE"] {
+                            builder.unmapped(&format!("{} (sub-task)", column), table.get(row, column));
+                        }
+                        continue;
+                    }
+                }
+
+                let occurrence = seen.entry((section.clone(), title.clone())).or_insert(0);
+                *occurrence += 1;
+                let mut todo = builder.todo(&format!("{}/{}/{}#{}", project, section, title, occurrence), &title);
+                todo.group_id = group_id;
+                todo.description = non_empty(table.get(row, "DESCRIPTION"));
+                todo.priority = match table.get(row, "PRIORITY") {
+                    "1" => priority::URGENT,
+                    "2" => priority::IMPORTANT,
+                    _ => priority::NORMAL,
+                };
+                todo.assignee = non_empty(strip_user_id(table.get(row, "RESPONSIBLE")));
+                todo.tags = Some(labels.iter().filter_map(|label| builder.tag(label)).collect());
+
+                // 重复日期（如 "every monday"）等自然语言无法解析
+                let date = table.get(row, "DATE");
+                let deadline = table.get(row, "DEADLINE");
+                todo.due_date = parse_datetime(date);
+                if todo.due_date.is_none() {
+                    builder.unmapped("DATE", date);
+                    todo.due_date = parse_datetime(deadline);
+                    if todo.due_date.is_none() {
+                        builder.unmapped("DEADLINE", deadline);
+                    }
+                } else {
+                    builder.unmapped("DEADLINE", deadline);
+                }
+                for (field, value) in table.others(row, KNOWN) {
+                    builder.unmapped(field, value);
+                }
+                parent = Some(builder.push(todo));
+            }
+            // meta 行是项目的显示设置
+            _ => {}
+        }
+    }
+    Ok(builder.finish())
+}
+
+/// 从任务标题中取出 `@标签`
+fn split_labels(content: &str) -> (String, Vec<String>) {
+    let mut labels = Vec::new();
+    let words: Vec<_> = content
+        .split_whitespace()
+        .filter(|word| match word.strip_prefix('@') {
+            Some(label) if !label.is_empty() => {
+                labels.push(label.to_string());
+                false
+            }
+            _ => true,
+        })
+        .collect();
+    (words.join(" "), labels)
+}
+
+/// 去掉 `Name (12345)` 中的用户 ID
+fn strip_user_id(user: &str) -> &str {
+    match user.rsplit_once(" (") {
+        Some((name, id)) if id.ends_with(')') => name,
+        _ => user,
+    }
+}
@@ -0,0 +1,347 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 数据完整性检查与修复
+//!
+//! 检查数据库损坏、外键、附件文件与附件记录是否对应、状态和优先级取值以及任务组循环。
+//! 修复时重新检查一遍，只处理仍然存在且可以自动修复的问题。
+
+#[cfg(test)]
+mod tests;
+
+use crate::error::{Context, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::models::{priority, status, IntegrityIssue, IntegrityIssueKind, IntegrityRepairResult, IntegrityReport};
+
+/// 最近写入的附件文件不算孤立文件（上传时先复制文件再写入记录）
+const ORPHAN_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+/// 违反的外键约束
+struct ForeignKeyViolation {
+    column: String,
+    parent: String,
+    not_null: bool,
+}
+
+/// 检查数据完整性，`data_dir` 为附件路径的基准目录
+pub fn check(conn: &Connection, data_dir: &Path) -> Result<IntegrityReport> {
+    let mut issues = Vec::new();
+    let database_ok = check_database(conn, &mut issues)?;
+    check_foreign_keys(conn, &mut issues)?;
+    check_attachments(conn, data_dir, &mut issues)?;
+    check_values(conn, &mut issues)?;
+    check_group_cycles(conn, &mut issues)?;
+
+    Ok(IntegrityReport {
+        checked_at: Utc::now().timestamp_millis(),
+        database_ok,
+        issues,
+    })
+}
+
+/// 修复选中的问题
+///
+/// 数据库修改在一个事务中完成，提交后再删除孤立文件；`dry_run` 时只返回将要修复的问题
+pub fn repair(
+    conn: &Connection,
+    data_dir: &Path,
+    selected: &[IntegrityIssue],
+    dry_run: bool,
+) -> Result<IntegrityRepairResult> {
+    let current = check(conn, data_dir)?;
+
+    let mut repaired: Vec<IntegrityIssue> = Vec::new();
+    let mut skipped = Vec::new();
+    for wanted in selected {
+        if repaired.iter().any(|i| i.same_target(wanted)) {
+            continue;
+        }
+        match current.issues.iter().find(|i| i.same_target(wanted)) {
+            Some(issue) if issue.repair.is_some() => repaired.push(issue.clone()),
+            _ => skipped.push(wanted.clone()),
+        }
+    }
+
+    if dry_run || repaired.is_empty() {
+        return Ok(IntegrityRepairResult { dry_run, repaired, skipped });
+    }
+
+    let transaction = conn.unchecked_transaction()?;
+    for issue in &repaired {
+        let table = issue.table.as_deref().unwrap_or_default();
+        let row_id = issue.row_id.unwrap_or_default();
+        match issue.kind {
+            IntegrityIssueKind::ForeignKeyViolation => repair_foreign_key(&transaction, table, row_id)?,
+            IntegrityIssueKind::MissingAttachmentFile => {
+                transaction.execute("DELETE FROM attachments WHERE id = ?1", params![row_id])?;
+            }
+            IntegrityIssueKind::InvalidStatus => {
+                transaction.execute("UPDATE todos SET status = ?1 WHERE id = ?2", params![status::TODO, row_id])?;
+            }
+            IntegrityIssueKind::InvalidPriority => {
+                transaction.execute("UPDATE todos SET priority = ?1 WHERE id = ?2", params![priority::NORMAL, row_id])?;
+            }
+            IntegrityIssueKind::GroupCycle => {
+                transaction.execute("UPDATE task_groups SET parent_id = NULL WHERE id = ?1", params![row_id])?;
+            }
+            IntegrityIssueKind::OrphanAttachmentFile | IntegrityIssueKind::Corruption => {}
+        }
+    }
+    transaction.commit()?;
+
+    // 文件删除失败的问题归入跳过
+    let (repaired, failed): (Vec<_>, Vec<_>) = repaired.into_iter().partition(|issue| {
+        if issue.kind != IntegrityIssueKind::OrphanAttachmentFile {
+            return true;
+        }
+        let path = issue.path.as_deref().unwrap_or_default();
+        match fs::remove_file(data_dir.join(path)) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Failed to remove orphan attachment file {}: {}", path, e);
+                false
+            }
+        }
+    });
+    skipped.extend(failed);
+
+    tracing::info!("Repaired {} integrity issues, skipped {}", repaired.len(), skipped.len());
+    Ok(IntegrityRepairResult { dry_run, repaired, skipped })
+}
+
+fn row_issue(kind: IntegrityIssueKind, table: &str, row_id: i64, message: String, repair: &str) -> IntegrityIssue {
+    IntegrityIssue {
+        kind,
+        table: Some(table.to_string()),
+        row_id: Some(row_id),
+        path: None,
+        message,
+        repair: Some(repair.to_string()),
+    }
+}
+
+/// `PRAGMA integrity_check`，返回数据库是否完好
+fn check_database(conn: &Connection, issues: &mut Vec<IntegrityIssue>) -> Result<bool> {
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    let messages = stmt.query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to run integrity check")?;
+
+    if messages.len() == 1 && messages[0] == "ok" {
+        return Ok(true);
+    }
+    issues.extend(messages.into_iter().map(|message| IntegrityIssue {
+        kind: IntegrityIssueKind::Corruption,
+        table: None,
+        row_id: None,
+        path: None,
+        message,
+        repair: None,
+    }));
+    Ok(false)
+}
+
+/// 按（表, rowid）汇总外键违规
+fn foreign_key_violations(conn: &Connection) -> Result<BTreeMap<(String, i64), Vec<ForeignKeyViolation>>> {
+    let mut stmt = conn.prepare("PRAGMA foreign_key_check")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+
+    let mut violations: BTreeMap<(String, i64), Vec<ForeignKeyViolation>> = BTreeMap::new();
+    for (table, row_id, parent, fk_id) in rows {
+        let Some(row_id) = row_id else { continue };
+        let column: String = conn.query_row(
+            &format!("SELECT \"from\" FROM pragma_foreign_key_list('{}') WHERE id = ?1", table),
+            params![fk_id],
+            |row| row.get(0),
+        )?;
+        let not_null: bool = conn.query_row(
+            &format!("SELECT \"notnull\" FROM pragma_table_info('{}') WHERE name = ?1", table),
+            params![column],
+            |row| row.get(0),
+        )?;
+        violations.entry((table, row_id)).or_default().push(ForeignKeyViolation { column, parent, not_null });
+    }
+    Ok(violations)
+}
+
+fn check_foreign_keys(conn: &Connection, issues: &mut Vec<IntegrityIssue>) -> Result<()> {
+    for ((table, row_id), violations) in foreign_key_violations(conn)? {
+        let message = violations.iter()
+            .map(|v| format!("{} #{}: {} 引用的 {} 记录不存在", table, row_id, v.column, v.parent))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let repair = if violations.iter().any(|v| v.not_null) {
+            "删除该记录".to_string()
+        } else {
+            let columns = violations.iter().map(|v| v.column.as_str()).collect::<Vec<_>>().join(", ");
+            format!("清空 {}", columns)
+        };
+        issues.push(row_issue(IntegrityIssueKind::ForeignKeyViolation, &table, row_id, message, &repair));
+    }
+    Ok(())
+}
+
+/// 删除违规记录，或在外键列可为空时清空这些列
+fn repair_foreign_key(conn: &Connection, table: &str, row_id: i64) -> Result<()> {
+    let violations = foreign_key_violations(conn)?;
+    let Some(violations) = violations.get(&(table.to_string(), row_id)) else {
+        return Ok(());
+    };
+
+    if violations.iter().any(|v| v.not_null) {
+        conn.execute(&format!("DELETE FROM \"{}\" WHERE rowid = ?1", table), params![row_id])?;
+    } else {
+        for v in violations {
+            conn.execute(
+                &format!("UPDATE \"{}\" SET \"{}\" = NULL WHERE rowid = ?1", table, v.column),
+                params![row_id],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// 附件记录与附件目录中的文件互相核对
+fn check_attachments(conn: &Connection, data_dir: &Path, issues: &mut Vec<IntegrityIssue>) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id, file_path FROM attachments ORDER BY id")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut referenced = HashSet::new();
+    for (id, file_path) in rows {
+        if !data_dir.join(&file_path).is_file() {
+            issues.push(IntegrityIssue {
+                path: Some(file_path.clone()),
+                ..row_issue(
+                    IntegrityIssueKind::MissingAttachmentFile,
+                    "attachments",
+                    id,
+                    format!("附件 #{} 的文件 {} 不存在", id, file_path),
+                    "删除附件记录",
+                )
+            });
+        }
+        referenced.insert(file_path.replace('\\', "/"));
+    }
+
+    let attachments_dir = data_dir.join("attachments");
+    if !attachments_dir.is_dir() {
+        return Ok(());
+    }
+    let mut files = Vec::new();
+    collect_files(data_dir, &attachments_dir, &mut files)?;
+    files.sort();
+
+    let recent = SystemTime::now() - ORPHAN_GRACE_PERIOD;
+    for path in files {
+        if referenced.contains(&path) {
+            continue;
+        }
+        let modified = fs::metadata(data_dir.join(&path)).and_then(|m| m.modified()).ok();
+        if modified.is_some_and(|m| m > recent) {
+            continue;
+        }
+        issues.push(IntegrityIssue {
+            kind: IntegrityIssueKind::OrphanAttachmentFile,
+            table: None,
+            row_id: None,
+            message: format!("文件 {} 没有被任何附件引用", path),
+            path: Some(path),
+            repair: Some("删除文件".to_string()),
+        });
+    }
+    Ok(())
+}
+
+/// 收集目录下的所有文件，路径相对于 `data_dir`，使用 `/` 分隔
+fn collect_files(data_dir: &Path, dir: &Path, files: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir).context("Failed to read attachments directory")? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(data_dir, &path, files)?;
+        } else {
+            let relative = path.strip_prefix(data_dir).unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push(relative);
+        }
+    }
+    Ok(())
+}
+
+/// 状态和优先级必须是已定义的整数值
+fn check_values(conn: &Connection, issues: &mut Vec<IntegrityIssue>) -> Result<()> {
+    let checks = [
+        (IntegrityIssueKind::InvalidStatus, "status", &status::ALL, "重置为待办"),
+        (IntegrityIssueKind::InvalidPriority, "priority", &priority::ALL, "重置为普通"),
+    ];
+
+    for (kind, column, valid, repair) in checks {
+        let valid_list = valid.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ");
+        let sql = format!(
+            "SELECT id, quote({column}) FROM todos
+             WHERE {column} IS NULL OR typeof({column}) != 'integer' OR {column} NOT IN ({valid_list})
+             ORDER BY id"
+        );
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, value) in rows {
+            let message = format!("任务 #{} 的 {} 取值无效: {}", id, column, value);
+            issues.push(row_issue(kind, "todos", id, message, repair));
+        }
+    }
+    Ok(())
+}
+
+/// 查找任务组父级链中的循环，每个循环报告一次，修复时将其中 id 最小的任务组移到顶层
+fn check_group_cycles(conn: &Connection, issues: &mut Vec<IntegrityIssue>) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id, parent_id FROM task_groups ORDER BY id")?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<i64>>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let parents: HashMap<i64, Option<i64>> = rows.iter().copied().collect();
+
+    let mut visited = HashSet::new();
+    for (start, _) in rows {
+        let mut path = Vec::new();
+        let mut current = Some(start);
+        while let Some(id) = current {
+            if visited.contains(&id) {
+                break;
+            }
+            if let Some(pos) = path.iter().position(|&p| p == id) {
+                let cycle: &[i64] = &path[pos..];
+                let break_at = *cycle.iter().min().unwrap_or(&id);
+                let chain = cycle.iter().chain(std::iter::once(&id))
+                    .map(|id| format!("#{}", id))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let repair = format!("将任务组 #{} 移到顶层", break_at);
+                issues.push(row_issue(
+                    IntegrityIssueKind::GroupCycle,
+                    "task_groups",
+                    break_at,
+                    format!("任务组形成循环: {}", chain),
+                    &repair,
+                ));
+                break;
+            }
+            path.push(id);
+            current = parents.get(&id).copied().flatten();
+        }
+        visited.extend(path);
+    }
+    Ok(())
+}
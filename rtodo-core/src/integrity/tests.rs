@@ -0,0 +1,159 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::database::migrations::run_migrations;
+use crate::test_support::temp_dir;
+use std::path::PathBuf;
+
+fn setup() -> (PathBuf, Connection) {
+    let dir = temp_dir("integrity");
+    fs::create_dir_all(dir.join("attachments")).unwrap();
+    let conn = Connection::open(dir.join("rtodo.db")).unwrap();
+    run_migrations(&conn, None).unwrap();
+    (dir, conn)
+}
+
+/// 写入一组各类问题都有的数据（关闭外键约束模拟旧版本导入）
+fn seed_problems(conn: &Connection, dir: &Path) {
+    conn.execute_batch(
+        "PRAGMA foreign_keys = OFF;
+         INSERT INTO task_groups (id, name, parent_id, created_at, updated_at) VALUES
+             (1, 'A', 2, 0, 0), (2, 'B', 1, 0, 0), (3, 'C', NULL, 0, 0), (4, 'D', 99, 0, 0);
+         INSERT INTO tags (id, name, created_at) VALUES (1, 'ok', 0);
+         INSERT INTO todos (id, title, status, priority, group_id, created_at, updated_at) VALUES
+             (1, 'fine', 0, 0, 3, 0, 0),
+             (2, 'bad status', 7, 1, NULL, 0, 0),
+             (3, 'bad priority', 2, 2, NULL, 0, 0),
+             (4, 'text status', 'done', NULL, NULL, 0, 0);
+         INSERT INTO todo_tags (todo_id, tag_id) VALUES (1, 1), (1, 42);
+         INSERT INTO attachments (id, todo_id, name, file_path, file_size, created_at) VALUES
+             (1, 1, 'present.txt', 'attachments/present.txt', 1, 0),
+             (2, 1, 'missing.txt', 'attachments/missing.txt', 1, 0);
+         PRAGMA foreign_keys = ON;",
+    ).unwrap();
+    fs::write(dir.join("attachments").join("present.txt"), "x").unwrap();
+    fs::write(dir.join("attachments").join("orphan.txt"), "x").unwrap();
+    // 超过宽限期的孤立文件
+    let old = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options().write(true).open(dir.join("attachments").join("orphan.txt")).unwrap()
+        .set_modified(old).unwrap();
+    fs::write(dir.join("attachments").join("uploading.txt"), "x").unwrap();
+}
+
+fn kinds(issues: &[IntegrityIssue]) -> Vec<(IntegrityIssueKind, Option<String>, Option<i64>)> {
+    let mut kinds: Vec<_> = issues.iter().map(|i| (i.kind, i.table.clone(), i.row_id)).collect();
+    kinds.sort_by_key(|(kind, table, row)| (format!("{:?}", kind), table.clone(), *row));
+    kinds
+}
+
+#[test]
+fn clean_database_has_no_issues() {
+    let (dir, conn) = setup();
+
+    let report = check(&conn, &dir).unwrap();
+    assert!(report.database_ok);
+    assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+    drop(conn);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn finds_every_kind_of_problem() {
+    let (dir, conn) = setup();
+    seed_problems(&conn, &dir);
+
+    let report = check(&conn, &dir).unwrap();
+    assert!(report.database_ok);
+    use IntegrityIssueKind::*;
+    let table = |t: &str| Some(t.to_string());
+    assert_eq!(kinds(&report.issues), vec![
+        (ForeignKeyViolation, table("task_groups"), Some(4)),
+        (ForeignKeyViolation, table("todo_tags"), Some(2)),
+        (GroupCycle, table("task_groups"), Some(1)),
+        (InvalidPriority, table("todos"), Some(3)),
+        (InvalidPriority, table("todos"), Some(4)),
+        (InvalidStatus, table("todos"), Some(2)),
+        (InvalidStatus, table("todos"), Some(4)),
+        (MissingAttachmentFile, table("attachments"), Some(2)),
+        (OrphanAttachmentFile, None, None),
+    ]);
+
+    let orphan = report.issues.iter().find(|i| i.kind == OrphanAttachmentFile).unwrap();
+    assert_eq!(orphan.path.as_deref(), Some("attachments/orphan.txt"));
+    let dangling_group = report.issues.iter().find(|i| i.row_id == Some(4) && i.kind == ForeignKeyViolation).unwrap();
+    assert_eq!(dangling_group.repair.as_deref(), Some("清空 parent_id"));
+    let dangling_tag = report.issues.iter().find(|i| i.table == table("todo_tags")).unwrap();
+    assert_eq!(dangling_tag.repair.as_deref(), Some("删除该记录"));
+
+    drop(conn);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dry_run_changes_nothing() {
+    let (dir, conn) = setup();
+    seed_problems(&conn, &dir);
+    let report = check(&conn, &dir).unwrap();
+
+    let result = repair(&conn, &dir, &report.issues, true).unwrap();
+    assert!(result.dry_run);
+    assert_eq!(result.repaired.len(), report.issues.len());
+    assert_eq!(kinds(&check(&conn, &dir).unwrap().issues), kinds(&report.issues));
+    assert!(dir.join("attachments").join("orphan.txt").exists());
+
+    drop(conn);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn repairing_everything_leaves_a_clean_report() {
+    let (dir, conn) = setup();
+    seed_problems(&conn, &dir);
+    let report = check(&conn, &dir).unwrap();
+
+    let result = repair(&conn, &dir, &report.issues, false).unwrap();
+    assert_eq!(result.repaired.len(), report.issues.len());
+    assert!(result.skipped.is_empty());
+    assert!(check(&conn, &dir).unwrap().issues.is_empty());
+
+    let (status, priority): (i32, i32) = conn.query_row(
+        "SELECT status, priority FROM todos WHERE id = 4", [], |row| Ok((row.get(0)?, row.get(1)?)),
+    ).unwrap();
+    assert_eq!((status, priority), (status::TODO, priority::NORMAL));
+    let parent: Option<i64> = conn.query_row("SELECT parent_id FROM task_groups WHERE id = 1", [], |row| row.get(0)).unwrap();
+    assert_eq!(parent, None);
+    assert!(!dir.join("attachments").join("orphan.txt").exists());
+    assert!(dir.join("attachments").join("present.txt").exists());
+    assert!(dir.join("attachments").join("uploading.txt").exists());
+
+    drop(conn);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn only_selected_and_still_present_issues_are_repaired() {
+    let (dir, conn) = setup();
+    seed_problems(&conn, &dir);
+    let report = check(&conn, &dir).unwrap();
+
+    let status_issue = report.issues.iter()
+        .find(|i| i.kind == IntegrityIssueKind::InvalidStatus && i.row_id == Some(2))
+        .unwrap()
+        .clone();
+    conn.execute("UPDATE todos SET status = 1 WHERE id = 2", []).unwrap();
+    let priority_issue = report.issues.iter()
+        .find(|i| i.kind == IntegrityIssueKind::InvalidPriority && i.row_id == Some(3))
+        .unwrap()
+        .clone();
+
+    let result = repair(&conn, &dir, &[status_issue, priority_issue], false).unwrap();
+    assert_eq!(kinds(&result.repaired), vec![(IntegrityIssueKind::InvalidPriority, Some("todos".to_string()), Some(3))]);
+    assert_eq!(result.skipped.len(), 1);
+    // 其他问题保持不变
+    assert_eq!(check(&conn, &dir).unwrap().issues.len(), report.issues.len() - 2);
+
+    drop(conn);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,25 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! RTodo 核心库
+//!
+//! 不依赖 Tauri 的领域逻辑：数据库连接与迁移、各实体的仓库、统计、导入导出、备份快照、
+//! 加密、数据完整性检查、设备间同步、自动化规则和 Webhook 投递队列。桌面应用和其他工具都通过这里读写数据。
+
+pub mod backup;
+pub mod database;
+pub mod encryption;
+pub mod error;
+pub mod import;
+pub mod integrity;
+pub mod models;
+pub mod rules;
+pub mod storage;
+pub mod sync;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub mod utils;
+pub mod webhooks;
+
+pub use database::{Database, DbConnection};
+pub use error::{Context, Error, Result};
@@ -0,0 +1,100 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// 备份原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    /// 定时备份，按祖父-父-子策略轮换
+    Scheduled,
+    /// 用户手动创建，不会被自动清理
+    Manual,
+    PreImport,
+    PreClear,
+    PreMigration,
+    PreRestore,
+    PreRepair,
+}
+
+impl BackupReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Manual => "manual",
+            Self::PreImport => "pre_import",
+            Self::PreClear => "pre_clear",
+            Self::PreMigration => "pre_migration",
+            Self::PreRestore => "pre_restore",
+            Self::PreRepair => "pre_repair",
+        }
+    }
+
+    /// 是否为高风险操作前自动创建的安全备份
+    pub fn is_safety(self) -> bool {
+        matches!(self, Self::PreImport | Self::PreClear | Self::PreMigration | Self::PreRestore | Self::PreRepair)
+    }
+}
+
+/// 备份中的文件记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFileEntry {
+    /// 相对于数据目录（附件）或备份目录（数据库）的路径，使用 `/` 分隔
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// 备份清单（manifest.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// 清单格式版本
+    pub format: u32,
+    pub created_at: i64,
+    pub reason: BackupReason,
+    pub app_version: String,
+    pub schema_version: u32,
+    pub database: BackupFileEntry,
+    /// 备份时附件目录中的文件（附件本身不复制）
+    pub attachments: Vec<BackupFileEntry>,
+}
+
+/// 备份列表项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    /// 备份目录名，作为备份的标识
+    pub name: String,
+    pub path: String,
+    pub created_at: i64,
+    pub reason: BackupReason,
+    pub schema_version: u32,
+    pub database_size: u64,
+    pub attachment_count: usize,
+}
+
+/// 备份校验结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupVerifyResult {
+    pub name: String,
+    /// 数据库快照完整且可以恢复
+    pub valid: bool,
+    /// 导致备份不可用的问题
+    pub errors: Vec<String>,
+    /// 清单中记录、但当前附件目录中已不存在的附件
+    pub missing_attachments: Vec<String>,
+    /// 备份后内容被修改的附件
+    pub changed_attachments: Vec<String>,
+}
+
+/// 上传到远程存储的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteBackupReport {
+    /// 本次上传的备份
+    pub uploaded: Vec<String>,
+    /// 本次上传的附件数（已在远程存储中的附件不再上传）
+    pub attachments_uploaded: usize,
+    /// 按轮换策略从远程存储删除的备份
+    pub pruned: Vec<String>,
+    pub finished_at: i64,
+}
@@ -9,6 +9,9 @@ pub mod status {
     pub const TODO: i32 = 0;
     pub const IN_PROGRESS: i32 = 1;
     pub const DONE: i32 = 2;
+
+    /// 所有有效的状态值
+    pub const ALL: [i32; 3] = [TODO, IN_PROGRESS, DONE];
 }
 
 /// 优先级常量
@@ -17,6 +20,9 @@ pub mod priority {
     pub const IMPORTANT: i32 = 1;
     pub const URGENT: i32 = 3;
 
+    /// 所有有效的优先级值
+    pub const ALL: [i32; 3] = [NORMAL, IMPORTANT, URGENT];
+
     /// 判断是否为重要任务的阈值
     pub const MARKED_THRESHOLD: i32 = IMPORTANT;
 }
@@ -0,0 +1,76 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// 任务组
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskGroup {
+    pub id: i64,
+    /// 全局唯一标识，在设备之间以及导出和导入时保持不变（旧版本的导出文件中没有）
+    #[serde(default)]
+    pub uid: String,
+    pub name: String,
+    pub parent_id: Option<i64>,
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// 删除任务组时子任务组的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupChildPolicy {
+    /// 子任务组上移到被删除任务组的父级
+    Reparent,
+    /// 连同整个子树一起删除
+    #[default]
+    Cascade,
+}
+
+/// 删除任务组时组内任务的处理方式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GroupTodoPolicy {
+    /// 任务保留，但不再属于任何任务组
+    #[default]
+    Unassign,
+    /// 任务移动到指定任务组
+    MoveTo { group_id: i64 },
+    /// 连同任务一起删除
+    Delete,
+}
+
+/// 删除任务组的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GroupDeleteSummary {
+    pub deleted_group_ids: Vec<i64>,
+    pub reparented_group_ids: Vec<i64>,
+    pub moved_todos: i64,
+    pub unassigned_todos: i64,
+    pub deleted_todos: i64,
+    /// 被移动、取消分组或删除的任务 ID
+    #[serde(default)]
+    pub affected_todo_ids: Vec<i64>,
+    /// 已删除任务留下的、不再被引用的附件文件（相对路径）
+    #[serde(default)]
+    pub orphaned_files: Vec<String>,
+}
+
+/// 任务组树节点（含任务数量统计）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupTreeNode {
+    #[serde(flatten)]
+    pub group: TaskGroup,
+    /// 直接属于该任务组的任务数
+    pub todo_count: i64,
+    /// 直接属于该任务组的已完成任务数
+    pub done_count: i64,
+    /// 包含所有子孙任务组的任务数
+    pub total_todo_count: i64,
+    /// 包含所有子孙任务组的已完成任务数
+    pub total_done_count: i64,
+    pub children: Vec<GroupTreeNode>,
+}
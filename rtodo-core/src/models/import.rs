@@ -0,0 +1,111 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// 导入方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// 清空现有数据，按导出文件恢复（沿用导出文件中的 ID 和 uid）
+    Replace,
+    /// 按 uid 与现有数据合并，已存在的记录按 `MergePolicy` 处理
+    #[default]
+    Merge,
+    /// 作为副本导入：全部新建，分配新的 ID 和 uid
+    Copy,
+}
+
+/// 合并时本地已存在的记录的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    /// 保留本地记录
+    Skip,
+    /// 用导入文件覆盖本地记录
+    Overwrite,
+    /// 按 `updated_at` 保留较新的一方；标签没有修改时间，保留本地的
+    #[default]
+    KeepNewer,
+}
+
+/// 导入选项
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub mode: ImportMode,
+    /// 仅在 `Merge` 模式下使用
+    #[serde(default)]
+    pub on_conflict: MergePolicy,
+}
+
+/// 对导入文件中一条记录的处理
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Skip,
+}
+
+/// 导入文件中的一条记录（任务组、标签或任务；步骤、附件和标签关联跟随所属任务）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportItem {
+    /// 实体类型：group、tag、todo
+    pub entity: String,
+    /// 导入文件中的 uid，旧版本的导出文件中为空
+    pub uid: String,
+    /// 名称或标题
+    pub name: String,
+    pub action: ImportAction,
+    /// 匹配到的本地记录 id
+    #[serde(default)]
+    pub local_id: Option<i64>,
+    /// 本地记录与导入文件中的内容不同
+    #[serde(default)]
+    pub conflict: bool,
+}
+
+/// 可以导入的其他应用的导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    /// Todoist 项目导出的 CSV
+    Todoist,
+    /// Microsoft To Do / Outlook 任务的 JSON（Microsoft Graph 的任务列表格式）
+    MicrosoftTodo,
+    /// TickTick（滴答清单）备份的 CSV
+    TickTick,
+    /// Taskwarrior `task export` 的 JSON
+    Taskwarrior,
+}
+
+/// 导入文件中没有对应字段、未被导入的内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnmappedField {
+    /// 导入文件中的列名或属性名
+    pub field: String,
+    /// 含有该内容的记录数
+    pub count: usize,
+    /// 其中一个值
+    pub example: String,
+}
+
+/// 导入预览或导入结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub options: ImportOptions,
+    /// 是否只是预览（没有写入任何数据）
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    /// 本地内容与导入文件不同的记录数
+    pub conflicts: usize,
+    /// `Replace` 模式下清除的本地任务组、标签和任务数
+    pub removed: usize,
+    pub items: Vec<ImportItem>,
+    /// 从其他应用导入时未导入的字段
+    #[serde(default)]
+    pub unmapped: Vec<UnmappedField>,
+}
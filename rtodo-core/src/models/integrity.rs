@@ -0,0 +1,71 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// 数据完整性问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityIssueKind {
+    /// `PRAGMA integrity_check` 报告的数据库损坏，只能从备份恢复
+    Corruption,
+    /// 外键指向不存在的记录（例如标签已删除的 todo_tags）
+    ForeignKeyViolation,
+    /// 附件记录对应的文件不存在
+    MissingAttachmentFile,
+    /// 附件目录中没有任何记录引用的文件
+    OrphanAttachmentFile,
+    InvalidStatus,
+    InvalidPriority,
+    /// 任务组的父级链形成循环
+    GroupCycle,
+}
+
+/// 完整性问题
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    /// 问题所在的表
+    #[serde(default)]
+    pub table: Option<String>,
+    /// 问题所在记录的 rowid
+    #[serde(default)]
+    pub row_id: Option<i64>,
+    /// 相对于数据目录的文件路径
+    #[serde(default)]
+    pub path: Option<String>,
+    #[serde(default)]
+    pub message: String,
+    /// 修复方式说明，为空表示无法自动修复
+    #[serde(default)]
+    pub repair: Option<String>,
+}
+
+impl IntegrityIssue {
+    /// 是否与另一个问题指向同一处（忽略描述文字）
+    pub fn same_target(&self, other: &IntegrityIssue) -> bool {
+        self.kind == other.kind
+            && self.table == other.table
+            && self.row_id == other.row_id
+            && self.path == other.path
+    }
+}
+
+/// 完整性检查报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked_at: i64,
+    /// `PRAGMA integrity_check` 是否通过
+    pub database_ok: bool,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+/// 修复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityRepairResult {
+    pub dry_run: bool,
+    /// 已修复（预演时为将要修复）的问题
+    pub repaired: Vec<IntegrityIssue>,
+    /// 已不存在或无法自动修复而跳过的问题
+    pub skipped: Vec<IntegrityIssue>,
+}
@@ -0,0 +1,37 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+pub mod todo;
+pub mod group;
+pub mod tag;
+pub mod step;
+pub mod attachment;
+pub mod stats;
+pub mod rule;
+pub mod webhook;
+pub mod backup;
+pub mod integrity;
+pub mod sync;
+pub mod import;
+pub mod constants;
+
+// 重新导出数据模型
+pub use todo::{Todo, TodoStatus};
+pub use group::{TaskGroup, GroupChildPolicy, GroupTodoPolicy, GroupDeleteSummary, GroupTreeNode};
+pub use tag::Tag;
+pub use step::TodoStep;
+pub use attachment::Attachment;
+pub use stats::{
+    TodoStats, StatsByDate, TodoStatsWithDetails, ExportData,
+    GroupStats, TagStats
+};
+pub use rule::{
+    Rule, RuleTrigger, RuleEvent, RuleField, ConditionOp, RuleCondition, RuleAction,
+    RuleNotification, RuleOutcome, RuleExecution
+};
+pub use webhook::{Webhook, WebhookDelivery, DeliveryStatus, WebhookTestResult, webhook_event};
+pub use backup::{BackupReason, BackupFileEntry, BackupManifest, BackupInfo, BackupVerifyResult, RemoteBackupReport};
+pub use integrity::{IntegrityIssueKind, IntegrityIssue, IntegrityReport, IntegrityRepairResult};
+pub use sync::{SyncReport, SyncConflict, ConflictResolution};
+pub use import::{ImportMode, MergePolicy, ImportOptions, ImportAction, ImportItem, ImportReport, ImportSource, UnmappedField};
+pub use constants::{priority, status};
@@ -0,0 +1,184 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::models::Todo;
+
+/// 自动化规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rule {
+    pub id: i64,
+    pub name: String,
+    pub enabled: bool,
+    pub trigger: RuleTrigger,
+    /// 所有条件均满足时才执行动作
+    pub conditions: Vec<RuleCondition>,
+    pub actions: Vec<RuleAction>,
+    pub sort_order: i32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// 规则触发器
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleTrigger {
+    /// 任务创建
+    Created,
+    /// 任务状态变化；`to` 为空表示任意状态
+    StatusChanged {
+        #[serde(default)]
+        to: Option<i32>,
+    },
+    /// 距离截止时间不足 `within_minutes` 分钟（未完成的任务，每个截止时间只触发一次）
+    DueSoon { within_minutes: i64 },
+    /// 任务添加了标签；`tag_id` 为空表示任意标签
+    TagAdded {
+        #[serde(default)]
+        tag_id: Option<i64>,
+    },
+    /// 任务的某个步骤被完成
+    StepCompleted,
+}
+
+impl RuleTrigger {
+    /// 触发器类型名称（用于执行日志）
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::StatusChanged { .. } => "status_changed",
+            Self::DueSoon { .. } => "due_soon",
+            Self::TagAdded { .. } => "tag_added",
+            Self::StepCompleted => "step_completed",
+        }
+    }
+}
+
+/// 触发规则的事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleEvent {
+    Created,
+    StatusChanged { from: i32, to: i32 },
+    DueSoon { due_date: i64 },
+    TagAdded { tag_id: i64 },
+    StepCompleted { step_id: i64 },
+}
+
+impl RuleEvent {
+    /// 事件类型名称，与 `RuleTrigger::kind` 对应
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::StatusChanged { .. } => "status_changed",
+            Self::DueSoon { .. } => "due_soon",
+            Self::TagAdded { .. } => "tag_added",
+            Self::StepCompleted { .. } => "step_completed",
+        }
+    }
+}
+
+/// 规则条件中可使用的任务字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleField {
+    Title,
+    Description,
+    Status,
+    Priority,
+    GroupId,
+    /// 任务的标签 ID 列表
+    TagIds,
+    Assignee,
+    StartDate,
+    DueDate,
+    /// 未完成的步骤数
+    StepsRemaining,
+}
+
+/// 条件运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    Eq,
+    Ne,
+    /// 文本包含（不区分大小写）或列表包含
+    Contains,
+    NotContains,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    IsEmpty,
+    IsNotEmpty,
+}
+
+/// 规则条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleCondition {
+    pub field: RuleField,
+    pub op: ConditionOp,
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// 规则动作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// 设置字段（支持 title、description、status、priority、assignee、start_date、due_date），`null` 表示清空
+    SetField { field: RuleField, value: Value },
+    AddTag { tag_id: i64 },
+    CreateStep { title: String },
+    /// 移动到任务组；`group_id` 为空表示移出任务组
+    MoveToGroup {
+        #[serde(default)]
+        group_id: Option<i64>,
+    },
+    /// 发送通知，`title`/`body` 中的 `{title}` 会替换为任务标题
+    Notify {
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+    },
+}
+
+/// 规则产生的通知
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleNotification {
+    pub rule_id: i64,
+    pub todo_id: i64,
+    pub title: String,
+    pub body: Option<String>,
+}
+
+/// 单条规则对单个任务的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleOutcome {
+    pub rule_id: i64,
+    pub rule_name: String,
+    pub todo_id: i64,
+    pub matched: bool,
+    /// 已执行动作的描述
+    pub applied_actions: Vec<String>,
+    pub notifications: Vec<RuleNotification>,
+    pub error: Option<String>,
+    /// 试运行时执行动作后的任务（不会写入数据库）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub todo_after: Option<Todo>,
+}
+
+/// 规则执行日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleExecution {
+    pub id: i64,
+    pub rule_id: i64,
+    pub todo_id: Option<i64>,
+    pub trigger_type: String,
+    /// 用于去重的事件标识（如截止时间提醒）
+    pub event_key: Option<String>,
+    pub success: bool,
+    pub message: Option<String>,
+    pub created_at: i64,
+}
@@ -40,6 +40,17 @@ pub struct TagStats {
     pub name: String,
     pub color: String,
     pub count: i32,
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// 已完成的任务数（直接关联）
+    #[serde(default)]
+    pub done: i32,
+    /// 包含子标签在内的任务数（同一任务只计一次）
+    #[serde(default)]
+    pub total_with_children: i32,
+    /// 关联任务的最近更新时间
+    #[serde(default)]
+    pub last_used_at: Option<i64>,
 }
 
 /// 导出数据结构
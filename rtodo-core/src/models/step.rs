@@ -7,6 +7,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoStep {
     pub id: i64,
+    /// 全局唯一标识，在设备之间以及导出和导入时保持不变（旧版本的导出文件中没有）
+    #[serde(default)]
+    pub uid: String,
     pub todo_id: i64,
     pub title: String,
     pub is_completed: bool,
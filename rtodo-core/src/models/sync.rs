@@ -0,0 +1,76 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 一次同步的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// 本设备的同步 id
+    pub device_id: String,
+    /// 写入同步目录的变更记录数
+    pub pushed: usize,
+    /// 从其他设备读取的变更记录数
+    pub pulled: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    /// 引用的数据尚未到达、留待下次同步的记录数
+    pub deferred: usize,
+    /// 本次新发现的冲突数
+    pub conflicts: usize,
+    pub finished_at: i64,
+}
+
+impl SyncReport {
+    /// 本地数据是否因其他设备的变更而改变
+    pub fn changed_local(&self) -> bool {
+        self.created + self.updated + self.deleted > 0
+    }
+}
+
+/// 冲突的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// 保留本设备的值
+    Local,
+    /// 采用其他设备的值
+    Remote,
+}
+
+impl ConflictResolution {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Local => "local",
+            Self::Remote => "remote",
+        }
+    }
+
+    /// 从字符串解析，无效值视为 Local
+    pub fn from_str_lossy(value: &str) -> Self {
+        match value {
+            "remote" => Self::Remote,
+            _ => Self::Local,
+        }
+    }
+}
+
+/// 同步冲突：同一字段在两台设备上被并发修改，按最后修改时间自动取舍
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub id: i64,
+    /// 实体类型：group、tag、todo、step
+    pub entity: String,
+    /// 实体的同步 uid
+    pub uid: String,
+    /// 本地记录 id，记录已删除时为空
+    pub local_id: Option<i64>,
+    pub field: String,
+    pub local_value: Value,
+    pub remote_value: Value,
+    pub resolution: ConflictResolution,
+    pub remote_device: String,
+    pub detected_at: i64,
+}
@@ -7,8 +7,14 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
     pub id: i64,
+    /// 全局唯一标识，在设备之间以及导出和导入时保持不变（旧版本的导出文件中没有）
+    #[serde(default)]
+    pub uid: String,
     pub name: String,
     pub color: String,
+    /// 父标签 ID（如 `work/clientA` 中的 `work`）
+    #[serde(default)]
+    pub parent_id: Option<i64>,
     pub created_at: i64,
 }
 
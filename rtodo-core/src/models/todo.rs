@@ -36,6 +36,9 @@ impl TodoStatus {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Todo {
     pub id: i64,
+    /// 全局唯一标识，在设备之间以及导出和导入时保持不变（旧版本的导出文件中没有）
+    #[serde(default)]
+    pub uid: String,
     pub title: String,
     pub description: Option<String>,
     pub status: TodoStatus,
@@ -0,0 +1,104 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Webhook 事件名称
+pub mod webhook_event {
+    pub const TODO_CREATED: &str = "todo.created";
+    pub const TODO_UPDATED: &str = "todo.updated";
+    pub const TODO_STATUS_CHANGED: &str = "todo.status_changed";
+    pub const TODO_DELETED: &str = "todo.deleted";
+    pub const GROUP_CREATED: &str = "group.created";
+    pub const GROUP_UPDATED: &str = "group.updated";
+    pub const GROUP_DELETED: &str = "group.deleted";
+    pub const TAG_CREATED: &str = "tag.created";
+    pub const TAG_UPDATED: &str = "tag.updated";
+    pub const TAG_DELETED: &str = "tag.deleted";
+    /// 测试事件，仅由 test_webhook 发送
+    pub const PING: &str = "ping";
+}
+
+/// Webhook 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub name: String,
+    pub url: String,
+    /// 签名密钥，为空时不发送签名头
+    pub secret: Option<String>,
+    /// 订阅的事件，支持 `todo.*` 通配；为空表示全部事件
+    pub events: Vec<String>,
+    pub enabled: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl Webhook {
+    /// 是否订阅了指定事件
+    pub fn subscribes(&self, event: &str) -> bool {
+        self.events.is_empty()
+            || self.events.iter().any(|filter| {
+                filter == "*"
+                    || filter == event
+                    || filter.strip_suffix(".*").is_some_and(|prefix| {
+                        event.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('.'))
+                    })
+            })
+    }
+}
+
+/// 投递状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    /// 超过最大重试次数
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Failed => "failed",
+        }
+    }
+
+    /// 从字符串解析，无效值视为 Pending
+    pub fn from_str_lossy(value: &str) -> Self {
+        match value {
+            "delivered" => Self::Delivered,
+            "failed" => Self::Failed,
+            _ => Self::Pending,
+        }
+    }
+}
+
+/// Webhook 投递记录（持久化队列）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    /// 请求体 JSON
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub response_status: Option<i32>,
+    pub created_at: i64,
+    pub delivered_at: Option<i64>,
+}
+
+/// 测试投递结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookTestResult {
+    pub success: bool,
+    pub response_status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: i64,
+}
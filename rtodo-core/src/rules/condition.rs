@@ -0,0 +1,92 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 规则条件求值
+
+use serde_json::{json, Value};
+use crate::models::{ConditionOp, RuleCondition, RuleField, Todo};
+
+/// 所有条件均满足（没有条件时视为满足）
+pub fn conditions_match(conditions: &[RuleCondition], todo: &Todo) -> bool {
+    conditions.iter().all(|c| condition_matches(c, todo))
+}
+
+/// 单个条件是否满足
+pub fn condition_matches(condition: &RuleCondition, todo: &Todo) -> bool {
+    let actual = field_value(condition.field, todo);
+    let expected = &condition.value;
+
+    match condition.op {
+        ConditionOp::Eq => values_equal(&actual, expected),
+        ConditionOp::Ne => !values_equal(&actual, expected),
+        ConditionOp::Contains => contains(&actual, expected),
+        ConditionOp::NotContains => !contains(&actual, expected),
+        ConditionOp::Gt => compare(&actual, expected).is_some_and(|o| o.is_gt()),
+        ConditionOp::Gte => compare(&actual, expected).is_some_and(|o| o.is_ge()),
+        ConditionOp::Lt => compare(&actual, expected).is_some_and(|o| o.is_lt()),
+        ConditionOp::Lte => compare(&actual, expected).is_some_and(|o| o.is_le()),
+        ConditionOp::IsEmpty => is_empty(&actual),
+        ConditionOp::IsNotEmpty => !is_empty(&actual),
+    }
+}
+
+/// 字段名称（与序列化名称一致）
+pub fn field_name(field: RuleField) -> String {
+    serde_json::to_value(field)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// 取任务的字段值
+fn field_value(field: RuleField, todo: &Todo) -> Value {
+    match field {
+        RuleField::Title => json!(todo.title),
+        RuleField::Description => json!(todo.description),
+        RuleField::Status => json!(todo.status.as_i32()),
+        RuleField::Priority => json!(todo.priority),
+        RuleField::GroupId => json!(todo.group_id),
+        RuleField::TagIds => {
+            let ids: Vec<i64> = todo.tags.iter().flatten().map(|t| t.id).collect();
+            json!(ids)
+        }
+        RuleField::Assignee => json!(todo.assignee),
+        RuleField::StartDate => json!(todo.start_date),
+        RuleField::DueDate => json!(todo.due_date),
+        RuleField::StepsRemaining => {
+            let remaining = todo.steps.iter().flatten().filter(|s| !s.is_completed).count();
+            json!(remaining)
+        }
+    }
+}
+
+/// 相等比较：文本不区分大小写，数字按数值比较，列表字段只要包含即视为相等
+fn values_equal(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::String(a), Value::String(b)) => a.to_lowercase() == b.to_lowercase(),
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Array(items), v) if !v.is_array() => items.iter().any(|i| values_equal(i, v)),
+        (a, b) => a == b,
+    }
+}
+
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::String(a), Value::String(b)) => a.to_lowercase().contains(&b.to_lowercase()),
+        (Value::Array(items), v) => items.iter().any(|i| values_equal(i, v)),
+        _ => false,
+    }
+}
+
+fn compare(actual: &Value, expected: &Value) -> Option<std::cmp::Ordering> {
+    actual.as_f64()?.partial_cmp(&expected.as_f64()?)
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 规则执行引擎
+
+use rusqlite::Connection;
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::database::repositories::{GroupRepository, RuleRepository, StepRepository, TagRepository, TodoRepository};
+use crate::models::{Rule, RuleAction, RuleEvent, RuleField, RuleNotification, RuleOutcome, RuleTrigger, Todo};
+use crate::rules::condition::{conditions_match, field_name};
+
+/// 规则引擎
+pub struct RuleEngine;
+
+impl RuleEngine {
+    /// 事件发生后，对任务执行所有匹配的已启用规则
+    ///
+    /// 只记录条件满足的执行；规则动作产生的变化不会再次触发规则，避免循环
+    pub fn run(conn: &Connection, todo_id: i64, event: &RuleEvent) -> Result<Vec<RuleOutcome>> {
+        let rules = RuleRepository::list_enabled_by_kind(conn, event.kind())?;
+        let event_key = Self::event_key(event);
+        let mut outcomes = Vec::new();
+
+        for rule in rules {
+            if !Self::trigger_matches(conn, &rule.trigger, event)? {
+                continue;
+            }
+            if let Some(key) = &event_key {
+                if RuleRepository::has_execution(conn, rule.id, todo_id, key)? {
+                    continue;
+                }
+            }
+
+            let outcome = Self::execute(conn, &rule, todo_id, false)?;
+            if !outcome.matched {
+                continue;
+            }
+
+            let message = match &outcome.error {
+                Some(e) => e.clone(),
+                None => outcome.applied_actions.join("; "),
+            };
+            RuleRepository::log_execution(
+                conn,
+                rule.id,
+                Some(todo_id),
+                event.kind(),
+                event_key.as_deref(),
+                outcome.error.is_none(),
+                Some(&message),
+            )?;
+
+            match &outcome.error {
+                Some(e) => tracing::warn!("Rule {} failed on todo {}: {}", rule.id, todo_id, e),
+                None => tracing::info!("Rule {} applied to todo {}: {}", rule.id, todo_id, message),
+            }
+            outcomes.push(outcome);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// 对一组事件依次执行规则（如任务更新时同时发生状态变化和添加标签）
+    pub fn run_all(conn: &Connection, todo_id: i64, events: &[RuleEvent]) -> Result<Vec<RuleOutcome>> {
+        let mut outcomes = Vec::new();
+        for event in events {
+            outcomes.extend(Self::run(conn, todo_id, event)?);
+        }
+        Ok(outcomes)
+    }
+
+    /// 试运行：忽略触发器和启用状态，评估条件并在事务中执行动作后回滚
+    pub fn dry_run(conn: &Connection, rule: &Rule, todo_id: i64) -> Result<RuleOutcome> {
+        Self::execute(conn, rule, todo_id, true)
+    }
+
+    /// 检查即将到期的任务，执行截止时间规则
+    pub fn check_due_soon(conn: &Connection) -> Result<Vec<RuleOutcome>> {
+        let rules = RuleRepository::list_enabled_by_kind(conn, "due_soon")?;
+        let max_window = rules.iter()
+            .filter_map(|r| match r.trigger {
+                RuleTrigger::DueSoon { within_minutes } => Some(within_minutes),
+                _ => None,
+            })
+            .max();
+        let Some(window) = max_window else {
+            return Ok(Vec::new());
+        };
+
+        let now = Utc::now().timestamp_millis();
+        let mut outcomes = Vec::new();
+        for (todo_id, due_date) in TodoRepository::list_due_between(conn, now, now + window * 60 * 1000)? {
+            outcomes.extend(Self::run(conn, todo_id, &RuleEvent::DueSoon { due_date })?);
+        }
+        Ok(outcomes)
+    }
+
+    /// 比较任务更新前后的数据，得到需要触发规则的事件
+    pub fn diff_events(before: &Todo, after: &Todo) -> Vec<RuleEvent> {
+        let mut events = Vec::new();
+
+        if before.status != after.status {
+            events.push(RuleEvent::StatusChanged {
+                from: before.status.as_i32(),
+                to: after.status.as_i32(),
+            });
+        }
+
+        let before_tags: Vec<i64> = before.tags.iter().flatten().map(|t| t.id).collect();
+        for tag in after.tags.iter().flatten() {
+            if !before_tags.contains(&tag.id) {
+                events.push(RuleEvent::TagAdded { tag_id: tag.id });
+            }
+        }
+
+        events
+    }
+
+    // ========== 辅助方法 ==========
+
+    fn execute(conn: &Connection, rule: &Rule, todo_id: i64, dry_run: bool) -> Result<RuleOutcome> {
+        let todo = TodoRepository::get(conn, todo_id)?
+            .ok_or_else(|| Error::NotFound(format!("Todo not found: {}", todo_id)))?;
+
+        let mut outcome = RuleOutcome {
+            rule_id: rule.id,
+            rule_name: rule.name.clone(),
+            todo_id,
+            matched: conditions_match(&rule.conditions, &todo),
+            applied_actions: Vec::new(),
+            notifications: Vec::new(),
+            error: None,
+            todo_after: None,
+        };
+        if !outcome.matched {
+            return Ok(outcome);
+        }
+
+        // 所有动作在同一个事务中执行，任一动作失败则整条规则回滚
+        let transaction = conn.unchecked_transaction()?;
+        let result = rule.actions.iter()
+            .try_for_each(|action| Self::apply_action(&transaction, rule, &todo, action, &mut outcome));
+
+        match result {
+            Ok(()) if dry_run => {
+                outcome.todo_after = TodoRepository::get(&transaction, todo_id)?;
+                transaction.rollback()?;
+            }
+            Ok(()) => transaction.commit()?,
+            Err(e) => {
+                transaction.rollback()?;
+                outcome.applied_actions.clear();
+                outcome.notifications.clear();
+                outcome.error = Some(e.to_string());
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    fn trigger_matches(conn: &Connection, trigger: &RuleTrigger, event: &RuleEvent) -> Result<bool> {
+        let matches = match (trigger, event) {
+            (RuleTrigger::Created, RuleEvent::Created) => true,
+            (RuleTrigger::StatusChanged { to }, RuleEvent::StatusChanged { to: actual, .. }) => {
+                to.is_none() || *to == Some(*actual)
+            }
+            (RuleTrigger::TagAdded { tag_id }, RuleEvent::TagAdded { tag_id: actual }) => match tag_id {
+                None => true,
+                // 父标签的规则同样适用于子标签
+                Some(t) => t == actual || TagRepository::descendant_ids(conn, *t)?.contains(actual),
+            },
+            (RuleTrigger::DueSoon { within_minutes }, RuleEvent::DueSoon { due_date }) => {
+                *due_date - Utc::now().timestamp_millis() <= within_minutes * 60 * 1000
+            }
+            (RuleTrigger::StepCompleted, RuleEvent::StepCompleted { .. }) => true,
+            _ => false,
+        };
+        Ok(matches)
+    }
+
+    /// 需要去重的事件标识（每个截止时间只提醒一次）
+    fn event_key(event: &RuleEvent) -> Option<String> {
+        match event {
+            RuleEvent::DueSoon { due_date } => Some(format!("due_soon:{}", due_date)),
+            _ => None,
+        }
+    }
+
+    fn apply_action(
+        conn: &Connection,
+        rule: &Rule,
+        todo: &Todo,
+        action: &RuleAction,
+        outcome: &mut RuleOutcome,
+    ) -> Result<()> {
+        match action {
+            RuleAction::SetField { field, value } => {
+                Self::set_field(conn, todo.id, *field, value)?;
+                outcome.applied_actions.push(format!("set {} = {}", field_name(*field), value));
+            }
+            RuleAction::AddTag { tag_id } => {
+                let tag = TagRepository::get(conn, *tag_id)?
+                    .ok_or_else(|| Error::NotFound(format!("Tag not found: {}", tag_id)))?;
+                TodoRepository::add_tag(conn, todo.id, *tag_id)?;
+                outcome.applied_actions.push(format!("add tag {}", tag.name));
+            }
+            RuleAction::CreateStep { title } => {
+                let title = Self::render(title, todo);
+                StepRepository::create(conn, todo.id, &title)?;
+                outcome.applied_actions.push(format!("create step {}", title));
+            }
+            RuleAction::MoveToGroup { group_id } => {
+                if let Some(gid) = group_id {
+                    GroupRepository::get(conn, *gid)?
+                        .ok_or_else(|| Error::NotFound(format!("Group not found: {}", gid)))?;
+                }
+                TodoRepository::update(conn, todo.id, None, None, None, None, Some(*group_id), None, None, None, None)?;
+                outcome.applied_actions.push(format!("move to group {:?}", group_id));
+            }
+            RuleAction::Notify { title, body } => {
+                let notification = RuleNotification {
+                    rule_id: rule.id,
+                    todo_id: todo.id,
+                    title: Self::render(title, todo),
+                    body: body.as_deref().map(|b| Self::render(b, todo)),
+                };
+                outcome.applied_actions.push(format!("notify {}", notification.title));
+                outcome.notifications.push(notification);
+            }
+        }
+        Ok(())
+    }
+
+    fn set_field(conn: &Connection, todo_id: i64, field: RuleField, value: &Value) -> Result<()> {
+        let text = || -> Result<Option<String>> {
+            match value {
+                Value::Null => Ok(None),
+                Value::String(s) => Ok(Some(s.clone())),
+                _ => Err(Error::InvalidInput(format!("Expected text value for {}", field_name(field)))),
+            }
+        };
+        let number = || -> Result<Option<i64>> {
+            match value {
+                Value::Null => Ok(None),
+                v => v.as_i64().map(Some)
+                    .ok_or_else(|| Error::InvalidInput(format!("Expected integer value for {}", field_name(field)))),
+            }
+        };
+        let required = |v: Option<i64>| v.ok_or_else(|| Error::InvalidInput(format!("{} cannot be cleared", field_name(field))));
+
+        match field {
+            RuleField::Title => {
+                let title = text()?.filter(|t| !t.trim().is_empty())
+                    .ok_or_else(|| Error::InvalidInput("title cannot be empty".to_string()))?;
+                TodoRepository::update(conn, todo_id, Some(&title), None, None, None, None, None, None, None, None)?;
+            }
+            RuleField::Description => {
+                TodoRepository::update(conn, todo_id, None, Some(text()?), None, None, None, None, None, None, None)?;
+            }
+            RuleField::Status => {
+                let status = required(number()?)? as i32;
+                if !(crate::models::status::TODO..=crate::models::status::DONE).contains(&status) {
+                    return Err(Error::InvalidInput(format!("Invalid status: {}", status)));
+                }
+                TodoRepository::update_status(conn, todo_id, status)?;
+            }
+            RuleField::Priority => {
+                let priority = required(number()?)? as i32;
+                TodoRepository::update(conn, todo_id, None, None, None, Some(priority), None, None, None, None, None)?;
+            }
+            RuleField::Assignee => {
+                TodoRepository::update(conn, todo_id, None, None, None, None, None, Some(text()?), None, None, None)?;
+            }
+            RuleField::StartDate => {
+                TodoRepository::update(conn, todo_id, None, None, None, None, None, None, Some(number()?), None, None)?;
+            }
+            RuleField::DueDate => {
+                TodoRepository::update(conn, todo_id, None, None, None, None, None, None, None, Some(number()?), None)?;
+            }
+            RuleField::GroupId | RuleField::TagIds | RuleField::StepsRemaining => {
+                return Err(Error::InvalidInput(format!("Field {} cannot be set by rules", field_name(field))));
+            }
+        }
+        Ok(())
+    }
+
+    /// 替换模板中的 `{title}`
+    fn render(template: &str, todo: &Todo) -> String {
+        template.replace("{title}", &todo.title)
+    }
+}
@@ -0,0 +1,19 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 自动化规则
+//!
+//! 规则由触发器、条件和动作组成，在任务创建、状态变化、添加标签、完成步骤及即将到期时执行。
+//! 这里只负责求值和执行，通知的发送和定时检查由调用方负责。
+//!
+//! 仓库本身不执行规则：调用方（桌面应用的命令、命令行）在修改完成后用 [`RuleEngine::run_all`] 执行，
+//! 规则动作对任务的修改因此不会再次触发规则。创建步骤不触发规则：没有对应的触发器，
+//! 而 `CreateStep` 动作本身就会创建步骤，若触发规则容易形成循环。
+
+#[cfg(test)]
+mod tests;
+
+pub mod condition;
+pub mod engine;
+
+pub use engine::RuleEngine;
@@ -0,0 +1,214 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::condition::conditions_match;
+use super::*;
+use crate::database::repositories::{GroupRepository, RuleRepository, StepRepository, TagRepository, TodoRepository};
+use crate::models::{
+    priority, status, ConditionOp, RuleAction, RuleCondition, RuleEvent, RuleField, RuleTrigger, Todo,
+};
+use crate::test_support::memory_db;
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+fn condition(field: RuleField, op: ConditionOp, value: Value) -> RuleCondition {
+    RuleCondition { field, op, value }
+}
+
+fn load(conn: &Connection, id: i64) -> Todo {
+    TodoRepository::get(conn, id).unwrap().unwrap()
+}
+
+#[test]
+fn conditions_compare_text_numbers_and_lists() {
+    let conn = memory_db();
+    let tag = TagRepository::create(&conn, "work", "#0000ff", None).unwrap();
+    let todo = TodoRepository::create(&conn, "Write Report", None, None, None, Some(5_000), priority::IMPORTANT, Some(vec![tag.id]))
+        .unwrap();
+    StepRepository::create(&conn, todo.id, "Outline").unwrap();
+    let todo = load(&conn, todo.id);
+
+    let matches = |c: RuleCondition| conditions_match(&[c], &todo);
+    assert!(matches(condition(RuleField::Title, ConditionOp::Eq, json!("write report"))));
+    assert!(matches(condition(RuleField::Title, ConditionOp::Contains, json!("REPORT"))));
+    assert!(matches(condition(RuleField::Title, ConditionOp::NotContains, json!("invoice"))));
+    assert!(matches(condition(RuleField::Priority, ConditionOp::Gte, json!(priority::IMPORTANT))));
+    assert!(!matches(condition(RuleField::Priority, ConditionOp::Gt, json!(priority::IMPORTANT))));
+    assert!(matches(condition(RuleField::DueDate, ConditionOp::Lt, json!(6_000))));
+    assert!(matches(condition(RuleField::TagIds, ConditionOp::Eq, json!(tag.id))));
+    assert!(matches(condition(RuleField::TagIds, ConditionOp::Contains, json!(tag.id))));
+    assert!(matches(condition(RuleField::StepsRemaining, ConditionOp::Eq, json!(1))));
+    assert!(matches(condition(RuleField::Description, ConditionOp::IsEmpty, Value::Null)));
+    assert!(matches(condition(RuleField::GroupId, ConditionOp::IsEmpty, Value::Null)));
+    assert!(!matches(condition(RuleField::Status, ConditionOp::Ne, json!(status::TODO))));
+    // 数值比较的另一边不是数字时不满足
+    assert!(!matches(condition(RuleField::Priority, ConditionOp::Lt, json!("high"))));
+
+    // 所有条件都满足才匹配，没有条件时视为匹配
+    assert!(conditions_match(&[], &todo));
+    assert!(!conditions_match(&[
+        condition(RuleField::Title, ConditionOp::Contains, json!("report")),
+        condition(RuleField::Assignee, ConditionOp::IsNotEmpty, Value::Null),
+    ], &todo));
+}
+
+#[test]
+fn matching_rules_apply_actions_and_log_executions() {
+    let conn = memory_db();
+    let group = GroupRepository::create(&conn, "Work", None, None, None).unwrap();
+    let tag = TagRepository::create(&conn, "triage", "#ff0000", None).unwrap();
+    let actions = [
+        RuleAction::SetField { field: RuleField::Priority, value: json!(priority::URGENT) },
+        RuleAction::AddTag { tag_id: tag.id },
+        RuleAction::CreateStep { title: "Review {title}".to_string() },
+        RuleAction::MoveToGroup { group_id: Some(group.id) },
+        RuleAction::Notify { title: "New: {title}".to_string(), body: None },
+    ];
+    let conditions = [condition(RuleField::Title, ConditionOp::Contains, json!("bug"))];
+    let rule = RuleRepository::create(&conn, "Bugs", true, &RuleTrigger::Created, &conditions, &actions).unwrap();
+    // 已停用的规则和其他触发器的规则不执行
+    let stop = [RuleAction::SetField { field: RuleField::Title, value: json!("changed") }];
+    RuleRepository::create(&conn, "Disabled", false, &RuleTrigger::Created, &[], &stop).unwrap();
+    RuleRepository::create(&conn, "On done", true, &RuleTrigger::StatusChanged { to: Some(status::DONE) }, &[], &stop)
+        .unwrap();
+
+    let bug = TodoRepository::create(&conn, "Fix bug", None, None, None, None, priority::NORMAL, None).unwrap();
+    let outcomes = RuleEngine::run(&conn, bug.id, &RuleEvent::Created).unwrap();
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].applied_actions.len(), 5);
+    assert_eq!(outcomes[0].notifications[0].title, "New: Fix bug");
+
+    let bug = load(&conn, bug.id);
+    assert_eq!((bug.title.as_str(), bug.priority, bug.group_id), ("Fix bug", priority::URGENT, Some(group.id)));
+    assert_eq!(bug.tags.unwrap()[0].id, tag.id);
+    assert_eq!(bug.steps.unwrap()[0].title, "Review Fix bug");
+
+    // 条件不满足时不执行也不记录
+    let chore = TodoRepository::create(&conn, "Water plants", None, None, None, None, priority::NORMAL, None).unwrap();
+    assert!(RuleEngine::run(&conn, chore.id, &RuleEvent::Created).unwrap().is_empty());
+    assert_eq!(load(&conn, chore.id).priority, priority::NORMAL);
+
+    let executions = RuleRepository::list_executions(&conn, None, 10).unwrap();
+    assert_eq!(executions.len(), 1);
+    let execution = &executions[0];
+    assert_eq!((execution.rule_id, execution.todo_id), (rule.id, Some(bug.id)));
+    assert_eq!(execution.trigger_type, "created");
+    assert!(execution.success);
+    assert!(execution.message.as_deref().unwrap().contains("add tag triage"));
+}
+
+#[test]
+fn failing_action_rolls_back_the_whole_rule() {
+    let conn = memory_db();
+    let actions = [
+        RuleAction::SetField { field: RuleField::Priority, value: json!(priority::URGENT) },
+        RuleAction::CreateStep { title: "Check".to_string() },
+        RuleAction::AddTag { tag_id: 404 },
+    ];
+    let rule = RuleRepository::create(&conn, "Broken", true, &RuleTrigger::Created, &[], &actions).unwrap();
+    let todo = TodoRepository::create(&conn, "Task", None, None, None, None, priority::NORMAL, None).unwrap();
+
+    let outcomes = RuleEngine::run(&conn, todo.id, &RuleEvent::Created).unwrap();
+    assert!(outcomes[0].error.as_deref().unwrap().contains("Tag not found"));
+    assert!(outcomes[0].applied_actions.is_empty());
+
+    let todo = load(&conn, todo.id);
+    assert_eq!(todo.priority, priority::NORMAL);
+    assert!(todo.steps.unwrap_or_default().is_empty());
+
+    let executions = RuleRepository::list_executions(&conn, Some(rule.id), 10).unwrap();
+    assert!(!executions[0].success);
+    assert!(executions[0].message.as_deref().unwrap().contains("Tag not found"));
+
+    // 字段类型不对同样回滚
+    let invalid = [RuleAction::SetField { field: RuleField::Status, value: json!("done") }];
+    let rule = RuleRepository::create(&conn, "Invalid", true, &RuleTrigger::Created, &[], &invalid).unwrap();
+    let outcome = RuleEngine::dry_run(&conn, &rule, todo.id).unwrap();
+    assert!(outcome.error.as_deref().unwrap().contains("Expected integer value for status"));
+}
+
+#[test]
+fn triggers_match_events() {
+    let conn = memory_db();
+    let parent = TagRepository::create(&conn, "home", "#00ff00", None).unwrap();
+    let child = TagRepository::create(&conn, "garden", "#00ff00", Some(parent.id)).unwrap();
+    let notify = [RuleAction::Notify { title: "{title}".to_string(), body: None }];
+    RuleRepository::create(&conn, "Done", true, &RuleTrigger::StatusChanged { to: Some(status::DONE) }, &[], &notify)
+        .unwrap();
+    RuleRepository::create(&conn, "Home", true, &RuleTrigger::TagAdded { tag_id: Some(parent.id) }, &[], &notify)
+        .unwrap();
+    let todo = TodoRepository::create(&conn, "Task", None, None, None, None, priority::NORMAL, None).unwrap();
+
+    let run = |event: RuleEvent| RuleEngine::run(&conn, todo.id, &event).unwrap().len();
+    assert_eq!(run(RuleEvent::StatusChanged { from: status::TODO, to: status::IN_PROGRESS }), 0);
+    assert_eq!(run(RuleEvent::StatusChanged { from: status::IN_PROGRESS, to: status::DONE }), 1);
+    // 父标签的规则同样适用于子标签
+    assert_eq!(run(RuleEvent::TagAdded { tag_id: child.id }), 1);
+    assert_eq!(run(RuleEvent::Created), 0);
+
+    // 更新前后的差异得到状态变化和新增标签的事件
+    let before = load(&conn, todo.id);
+    TodoRepository::update_status(&conn, todo.id, status::DONE).unwrap();
+    TodoRepository::add_tag(&conn, todo.id, child.id).unwrap();
+    let events = RuleEngine::diff_events(&before, &load(&conn, todo.id));
+    assert_eq!(events, [
+        RuleEvent::StatusChanged { from: status::TODO, to: status::DONE },
+        RuleEvent::TagAdded { tag_id: child.id },
+    ]);
+}
+
+#[test]
+fn due_soon_rules_fire_once_per_due_date() {
+    let conn = memory_db();
+    let notify = [RuleAction::Notify { title: "Due soon: {title}".to_string(), body: None }];
+    RuleRepository::create(&conn, "Reminder", true, &RuleTrigger::DueSoon { within_minutes: 60 }, &[], &notify)
+        .unwrap();
+    let soon = chrono::Utc::now().timestamp_millis() + 10 * 60 * 1000;
+    let todo = TodoRepository::create(&conn, "Call back", None, None, None, Some(soon), priority::NORMAL, None).unwrap();
+    TodoRepository::create(&conn, "Later", None, None, None, Some(soon + 24 * 60 * 60 * 1000), priority::NORMAL, None)
+        .unwrap();
+
+    let outcomes = RuleEngine::check_due_soon(&conn).unwrap();
+    assert_eq!(outcomes.len(), 1);
+    assert_eq!(outcomes[0].notifications[0].title, "Due soon: Call back");
+    assert!(RuleEngine::check_due_soon(&conn).unwrap().is_empty());
+
+    // 截止时间改变后再次提醒
+    let moved = soon + 5 * 60 * 1000;
+    TodoRepository::update(&conn, todo.id, None, None, None, None, None, None, None, Some(Some(moved)), None).unwrap();
+    assert_eq!(RuleEngine::check_due_soon(&conn).unwrap().len(), 1);
+    let executions = RuleRepository::list_executions(&conn, None, 10).unwrap();
+    let mut keys: Vec<_> = executions.iter().filter_map(|e| e.event_key.clone()).collect();
+    keys.sort();
+    assert_eq!(keys, [format!("due_soon:{}", soon), format!("due_soon:{}", moved)]);
+}
+
+#[test]
+fn dry_run_previews_without_writing() {
+    let conn = memory_db();
+    let actions = [
+        RuleAction::SetField { field: RuleField::Title, value: json!("Renamed") },
+        RuleAction::CreateStep { title: "First step".to_string() },
+    ];
+    let conditions = [condition(RuleField::Priority, ConditionOp::Eq, json!(priority::NORMAL))];
+    // 试运行忽略启用状态和触发器
+    let rule = RuleRepository::create(&conn, "Preview", false, &RuleTrigger::StepCompleted, &conditions, &actions)
+        .unwrap();
+    let todo = TodoRepository::create(&conn, "Original", None, None, None, None, priority::NORMAL, None).unwrap();
+
+    let outcome = RuleEngine::dry_run(&conn, &rule, todo.id).unwrap();
+    assert!(outcome.matched && outcome.error.is_none());
+    let after = outcome.todo_after.unwrap();
+    assert_eq!(after.title, "Renamed");
+    assert_eq!(after.steps.unwrap().len(), 1);
+
+    let todo = load(&conn, todo.id);
+    assert_eq!(todo.title, "Original");
+    assert!(todo.steps.unwrap_or_default().is_empty());
+    assert!(RuleRepository::list_executions(&conn, None, 10).unwrap().is_empty());
+
+    // 条件不满足时只返回未匹配
+    TodoRepository::update(&conn, todo.id, None, None, None, Some(priority::URGENT), None, None, None, None, None).unwrap();
+    let outcome = RuleEngine::dry_run(&conn, &rule, todo.id).unwrap();
+    assert!(!outcome.matched && outcome.todo_after.is_none());
+}
@@ -0,0 +1,113 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 本地目录存储，适用于 Syncthing、Dropbox、NAS 共享目录等
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use super::{split_path, Entry, Store};
+use crate::error::{Context, Error, Result};
+
+/// 以本地目录为根的存储
+pub struct FolderStore {
+    root: PathBuf,
+}
+
+impl FolderStore {
+    /// 目录必须已经存在，避免共享目录未挂载时在本地新建同名目录
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        if !root.is_dir() {
+            return Err(Error::NotFound(format!("Sync folder not found: {}", root.display())));
+        }
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let mut resolved = self.root.clone();
+        resolved.extend(split_path(path)?);
+        Ok(resolved)
+    }
+}
+
+impl Store for FolderStore {
+    fn entries(&self, dir: &str) -> Result<Vec<Entry>> {
+        let dir = if dir.is_empty() { self.root.clone() } else { self.resolve(dir)? };
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to list {}", dir.display())),
+        };
+
+        let mut items = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // 跳过临时文件（本存储和同步工具写入中的文件都以 . 开头）
+            if name.starts_with('.') {
+                continue;
+            }
+            // 列出期间被同步工具移走的文件直接跳过
+            let Ok(metadata) = entry.metadata() else { continue };
+            items.push(Entry {
+                name,
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                etag: None,
+            });
+        }
+        items.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(items)
+    }
+
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.resolve(path)?;
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> Result<()> {
+        let path = self.resolve(path)?;
+        let parent = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+
+        // 先写临时文件再重命名，同步工具不会传出写了一半的文件
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp = parent.join(format!(".{}.{}.tmp", name, uuid::Uuid::new_v4().simple()));
+        let result = (|| {
+            let mut file = fs::File::create(&temp)?;
+            file.write_all(data)?;
+            file.sync_all()?;
+            fs::rename(&temp, &path)
+        })();
+        if result.is_err() {
+            fs::remove_file(&temp).ok();
+        }
+        result.with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn delete(&self, path: &str) -> Result<()> {
+        let path = self.resolve(path)?;
+        let result = if path.is_dir() { fs::remove_dir(&path) } else { fs::remove_file(&path) };
+        match result {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to delete {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn location(&self) -> String {
+        self.root.display().to_string()
+    }
+}
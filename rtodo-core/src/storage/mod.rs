@@ -0,0 +1,119 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 同步和远程备份的存储
+//! 同步日志、备份快照等文件存放的位置，以 `/` 分隔的相对路径按整个文件读写。
+//! 写入必须是原子的：读取方要么看到完整的旧文件，要么看到完整的新文件。
+
+pub mod folder;
+pub mod s3;
+pub mod webdav;
+
+#[cfg(test)]
+pub(crate) mod tests;
+
+pub use folder::FolderStore;
+pub use s3::{S3Options, S3Store};
+pub use webdav::WebDavStore;
+
+use std::path::Path;
+
+use crate::error::{Context, Error, Result};
+
+/// 目录中的条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    /// 文件大小（目录为 0）
+    pub size: u64,
+    /// 内容变化时随之改变的标记（WebDAV 的 ETag），后端不提供时为 `None`
+    pub etag: Option<String>,
+}
+
+/// 存储后端
+pub trait Store: Send + Sync {
+    /// 列出目录下的条目（文件和子目录），按名称排序，目录不存在时返回空列表
+    fn entries(&self, dir: &str) -> Result<Vec<Entry>>;
+
+    /// 列出目录下的条目名称
+    fn list(&self, dir: &str) -> Result<Vec<String>> {
+        Ok(self.entries(dir)?.into_iter().map(|entry| entry.name).collect())
+    }
+
+    /// 查询单个文件或目录，不存在时返回 `None`
+    fn stat(&self, path: &str) -> Result<Option<Entry>> {
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        Ok(self.entries(dir)?.into_iter().find(|entry| entry.name == name))
+    }
+
+    /// 读取文件，不存在时返回 `None`
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>>;
+
+    /// 写入文件（覆盖已有文件），自动创建上级目录
+    fn write(&self, path: &str, data: &[u8]) -> Result<()>;
+
+    /// 上传本地文件，用于数据库快照和附件等较大的文件
+    fn upload(&self, path: &str, file: &Path) -> Result<()> {
+        let data = std::fs::read(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        self.write(path, &data)
+    }
+
+    /// 下载文件到本地（覆盖已有文件），远程文件不存在时返回 `false`
+    fn download(&self, path: &str, file: &Path) -> Result<bool> {
+        let Some(data) = self.read(path)? else {
+            return Ok(false);
+        };
+        std::fs::write(file, data)
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+        Ok(true)
+    }
+
+    /// 删除文件或空目录，不存在时忽略
+    fn delete(&self, path: &str) -> Result<()>;
+
+    /// 存储位置的说明（目录路径、URL 等），用于日志和识别同步目标是否变化
+    fn location(&self) -> String;
+}
+
+/// 拆分并检查相对路径，拒绝空段、`.`、`..` 和绝对路径
+pub fn split_path(path: &str) -> Result<Vec<&str>> {
+    let segments: Vec<&str> = path.split('/').collect();
+    let valid = segments.iter().all(|s| !s.is_empty() && *s != "." && *s != ".." && !s.contains('\\'));
+    if !valid {
+        return Err(Error::InvalidInput(format!("Invalid storage path: {}", path)));
+    }
+    Ok(segments)
+}
+
+/// 编码路径段，只保留 RFC 3986 的非保留字符
+pub(crate) fn encode_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+pub(crate) fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
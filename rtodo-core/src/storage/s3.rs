@@ -0,0 +1,519 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! S3 兼容对象存储，适用于 AWS S3、MinIO、Cloudflare R2 等
+//!
+//! 使用路径风格的地址（`<endpoint>/<bucket>/<key>`）和 AWS Signature Version 4 签名。路径映射为
+//! `<prefix>/<路径>` 的对象键，目录是以 `/` 分隔的键前缀，由 ListObjectsV2 的 delimiter 列出，
+//! 不需要创建也不需要删除。对象的 PUT 是原子的；超过一个分段大小的文件使用分段上传。
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use super::{encode_segment, split_path, Entry, Store};
+use crate::error::{Context, Error, Result};
+
+/// 连接超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// 单次读写的超时
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+/// 分段上传的分段大小（S3 要求除最后一段外不小于 5 MiB）
+const PART_SIZE: usize = 16 * 1024 * 1024;
+/// 未指定区域时使用的区域（MinIO 的默认区域）
+const DEFAULT_REGION: &str = "us-east-1";
+/// 空请求体的 SHA-256
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// S3 存储设置
+#[derive(Clone, Default)]
+pub struct S3Options {
+    /// 服务地址，例如 `https://s3.us-east-1.amazonaws.com` 或 `http://minio.internal:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    /// 存放 RTodo 数据的键前缀，例如 `team/rtodo`，为空时使用整个存储桶
+    pub prefix: String,
+    /// 区域，为空时使用 `us-east-1`
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// 以存储桶中的键前缀为根的存储
+pub struct S3Store {
+    agent: ureq::Agent,
+    /// 协议和主机，例如 `http://minio.internal:9000`
+    origin: String,
+    host: String,
+    /// 服务地址中的路径（不含末尾的 `/`），通常为空
+    base_path: String,
+    bucket: String,
+    /// 不含首尾 `/` 的键前缀
+    prefix: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    part_size: usize,
+}
+
+/// 参与签名的请求
+pub(crate) struct SignedRequest<'a> {
+    pub method: &'a str,
+    /// 编码后的路径
+    pub uri: &'a str,
+    /// 未编码的查询参数
+    pub query: &'a [(String, String)],
+    /// 小写的请求头名称和值
+    pub headers: &'a [(&'a str, String)],
+    pub payload_hash: &'a str,
+}
+
+impl S3Store {
+    pub fn new(options: S3Options) -> Result<Self> {
+        let endpoint = options.endpoint.trim().trim_end_matches('/');
+        let Some((scheme, rest)) = endpoint.split_once("://").filter(|(s, _)| *s == "https" || *s == "http") else {
+            return Err(Error::InvalidInput(format!("S3 endpoint must start with http:// or https://: {}", endpoint)));
+        };
+        let (host, base_path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        if host.is_empty() {
+            return Err(Error::InvalidInput(format!("Invalid S3 endpoint: {}", endpoint)));
+        }
+        let bucket = options.bucket.trim();
+        if bucket.is_empty() || bucket.contains('/') {
+            return Err(Error::InvalidInput(format!("Invalid S3 bucket name: {}", bucket)));
+        }
+        let prefix = options.prefix.trim().trim_matches('/');
+        if !prefix.is_empty() {
+            split_path(prefix)?;
+        }
+        let region = options.region.trim();
+
+        Ok(Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(CONNECT_TIMEOUT)
+                .timeout_read(IO_TIMEOUT)
+                .timeout_write(IO_TIMEOUT)
+                .user_agent(concat!("RTodo/", env!("CARGO_PKG_VERSION")))
+                .build(),
+            origin: format!("{}://{}", scheme, host),
+            host: host.to_string(),
+            base_path: base_path.to_string(),
+            bucket: bucket.to_string(),
+            prefix: prefix.to_string(),
+            region: if region.is_empty() { DEFAULT_REGION } else { region }.to_string(),
+            access_key_id: options.access_key_id.trim().to_string(),
+            secret_access_key: options.secret_access_key,
+            part_size: PART_SIZE,
+        })
+    }
+
+    /// 使用更小的分段，便于测试分段上传
+    #[cfg(test)]
+    pub(crate) fn with_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size;
+        self
+    }
+
+    /// 检查存储桶是否可以访问、凭据是否正确
+    pub fn connect(&self) -> Result<()> {
+        let query = [
+            ("list-type".to_string(), "2".to_string()),
+            ("max-keys".to_string(), "1".to_string()),
+            ("prefix".to_string(), self.dir_prefix("")?),
+        ];
+        let response = self.send("GET", None, &query, &[])
+            .map_err(|e| self.request_error("GET", None, e))?;
+        read_body(response)?;
+        Ok(())
+    }
+
+    /// 路径对应的对象键
+    fn key(&self, path: &str) -> Result<String> {
+        let path = split_path(path)?.join("/");
+        Ok(if self.prefix.is_empty() { path } else { format!("{}/{}", self.prefix, path) })
+    }
+
+    /// 列出目录时使用的键前缀（以 `/` 结尾，根目录且没有前缀时为空）
+    fn dir_prefix(&self, dir: &str) -> Result<String> {
+        if dir.is_empty() {
+            return Ok(if self.prefix.is_empty() { String::new() } else { format!("{}/", self.prefix) });
+        }
+        Ok(format!("{}/", self.key(dir)?))
+    }
+
+    fn uri(&self, key: Option<&str>) -> String {
+        let mut uri = format!("{}/{}", self.base_path, encode_segment(&self.bucket));
+        if let Some(key) = key {
+            let segments: Vec<String> = key.split('/').map(encode_segment).collect();
+            uri.push('/');
+            uri.push_str(&segments.join("/"));
+        }
+        uri
+    }
+
+    /// 签名并发送请求；`key` 为 `None` 时请求存储桶本身
+    #[allow(clippy::result_large_err)]
+    fn send(&self, method: &str, key: Option<&str>, query: &[(String, String)], body: &[u8]) -> std::result::Result<ureq::Response, ureq::Error> {
+        let uri = self.uri(key);
+        let payload_hash = if body.is_empty() { EMPTY_SHA256.to_string() } else { hex::encode(Sha256::digest(body)) };
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let headers = [
+            ("host", self.host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        let signed = SignedRequest { method, uri: &uri, query, headers: &headers, payload_hash: &payload_hash };
+        let authorization = authorization(&signed, &self.access_key_id, &self.secret_access_key, &self.region, &amz_date);
+
+        let query = canonical_query(query);
+        let url = if query.is_empty() {
+            format!("{}{}", self.origin, uri)
+        } else {
+            format!("{}{}?{}", self.origin, uri, query)
+        };
+        let request = self.agent.request(method, &url)
+            .set("Host", &self.host)
+            .set("x-amz-content-sha256", &payload_hash)
+            .set("x-amz-date", &amz_date)
+            .set("Authorization", &authorization);
+        match method {
+            "GET" | "HEAD" | "DELETE" => request.call(),
+            _ => request.send_bytes(body),
+        }
+    }
+
+    fn get(&self, path: &str) -> Result<Option<ureq::Response>> {
+        let key = self.key(path)?;
+        match self.send("GET", Some(&key), &[], &[]) {
+            Ok(response) => Ok(Some(response)),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(self.request_error("GET", Some(&key), e)),
+        }
+    }
+
+    fn put(&self, key: &str, query: &[(String, String)], data: &[u8]) -> Result<ureq::Response> {
+        self.send("PUT", Some(key), query, data)
+            .map_err(|e| self.request_error("PUT", Some(key), e))
+    }
+
+    /// 列出一页对象，返回文件、子目录和下一页的标记
+    fn list_page(&self, prefix: &str, token: Option<&str>) -> Result<(Vec<Entry>, Option<String>)> {
+        let mut query = vec![
+            ("list-type".to_string(), "2".to_string()),
+            ("delimiter".to_string(), "/".to_string()),
+            ("prefix".to_string(), prefix.to_string()),
+        ];
+        if let Some(token) = token {
+            query.push(("continuation-token".to_string(), token.to_string()));
+        }
+        let response = self.send("GET", None, &query, &[])
+            .map_err(|e| self.request_error("GET", None, e))?;
+        let body = String::from_utf8_lossy(&read_body(response)?).into_owned();
+        parse_list(&body, prefix)
+    }
+
+    /// 分段上传，失败时取消上传，避免残留的分段占用空间
+    fn multipart_upload(&self, key: &str, file: &Path) -> Result<()> {
+        let response = self.send("POST", Some(key), &[("uploads".to_string(), String::new())], &[])
+            .map_err(|e| self.request_error("POST", Some(key), e))?;
+        let body = String::from_utf8_lossy(&read_body(response)?).into_owned();
+        let upload_id = parse_xml(&body)?
+            .and_then(|document| element_text(document.root_element(), "UploadId"))
+            .ok_or_else(|| Error::Remote(format!("S3 did not return an upload ID for {}", key)))?;
+
+        let result = self.upload_parts(key, &upload_id, file)
+            .and_then(|parts| self.complete_upload(key, &upload_id, &parts));
+        if result.is_err() {
+            let query = [("uploadId".to_string(), upload_id)];
+            if let Err(e) = self.send("DELETE", Some(key), &query, &[]) {
+                tracing::warn!("Failed to abort multipart upload of {}: {}", key, e);
+            }
+        }
+        result
+    }
+
+    /// 逐段上传文件，返回各段的 ETag
+    fn upload_parts(&self, key: &str, upload_id: &str, file: &Path) -> Result<Vec<String>> {
+        let mut input = fs::File::open(file)
+            .with_context(|| format!("Failed to open {}", file.display()))?;
+        let mut etags = Vec::new();
+        let mut buffer = vec![0; self.part_size];
+        loop {
+            let mut filled = 0;
+            while filled < buffer.len() {
+                let read = input.read(&mut buffer[filled..])
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                if read == 0 {
+                    break;
+                }
+                filled += read;
+            }
+            if filled == 0 && !etags.is_empty() {
+                break;
+            }
+            let query = [
+                ("partNumber".to_string(), (etags.len() + 1).to_string()),
+                ("uploadId".to_string(), upload_id.to_string()),
+            ];
+            let response = self.put(key, &query, &buffer[..filled])?;
+            let etag = response.header("ETag")
+                .ok_or_else(|| Error::Remote(format!("S3 did not return an ETag for part {} of {}", etags.len() + 1, key)))?;
+            etags.push(etag.to_string());
+            if filled < buffer.len() {
+                break;
+            }
+        }
+        Ok(etags)
+    }
+
+    fn complete_upload(&self, key: &str, upload_id: &str, etags: &[String]) -> Result<()> {
+        let mut body = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in etags.iter().enumerate() {
+            body.push_str(&format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", i + 1, escape_xml(etag)));
+        }
+        body.push_str("</CompleteMultipartUpload>");
+
+        let query = [("uploadId".to_string(), upload_id.to_string())];
+        let response = self.send("POST", Some(key), &query, body.as_bytes())
+            .map_err(|e| self.request_error("POST", Some(key), e))?;
+        // 合并失败时 S3 可能在返回 200 之后才在响应体中报告错误
+        let body = String::from_utf8_lossy(&read_body(response)?).into_owned();
+        if let Some(document) = parse_xml(&body)? {
+            if document.root_element().tag_name().name() == "Error" {
+                return Err(Error::Remote(format!(
+                    "S3 failed to complete the upload of {}: {}", key, error_message(&document),
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn request_error(&self, method: &str, key: Option<&str>, error: ureq::Error) -> Error {
+        let target = match key {
+            Some(key) => format!("{}/{}", self.bucket, key),
+            None => self.bucket.clone(),
+        };
+        let message = match error {
+            ureq::Error::Status(code, response) => {
+                let status = response.status_text().to_string();
+                let detail = read_body(response).ok()
+                    .and_then(|body| parse_xml(&String::from_utf8_lossy(&body)).ok().flatten().map(|d| error_message(&d)));
+                match detail {
+                    Some(detail) => format!("HTTP {} {}: {}", code, status, detail),
+                    None => format!("HTTP {} {}", code, status),
+                }
+            }
+            ureq::Error::Transport(e) => e.to_string(),
+        };
+        Error::Remote(format!("S3 {} {} failed: {}", method, target, message))
+    }
+}
+
+impl Store for S3Store {
+    fn entries(&self, dir: &str) -> Result<Vec<Entry>> {
+        let prefix = self.dir_prefix(dir)?;
+        let mut entries = Vec::new();
+        let mut token = None;
+        loop {
+            let (page, next) = self.list_page(&prefix, token.as_deref())?;
+            entries.extend(page);
+            match next {
+                Some(next) => token = Some(next),
+                None => break,
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    fn stat(&self, path: &str) -> Result<Option<Entry>> {
+        let key = self.key(path)?;
+        let name = key.rsplit('/').next().unwrap_or_default().to_string();
+        match self.send("HEAD", Some(&key), &[], &[]) {
+            Ok(response) => {
+                return Ok(Some(Entry {
+                    name,
+                    is_dir: false,
+                    size: response.header("Content-Length").and_then(|v| v.parse().ok()).unwrap_or(0),
+                    etag: response.header("ETag").map(str::to_string),
+                }));
+            }
+            Err(ureq::Error::Status(404, _)) => {}
+            Err(e) => return Err(self.request_error("HEAD", Some(&key), e)),
+        }
+        // 目录只在其中有对象时存在
+        let (entries, _) = self.list_page(&format!("{}/", key), None)?;
+        Ok((!entries.is_empty()).then_some(Entry { name, is_dir: true, size: 0, etag: None }))
+    }
+
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        self.get(path)?.map(read_body).transpose()
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> Result<()> {
+        self.put(&self.key(path)?, &[], data).map(drop)
+    }
+
+    fn upload(&self, path: &str, file: &Path) -> Result<()> {
+        let key = self.key(path)?;
+        let size = fs::metadata(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?
+            .len();
+        if size > self.part_size as u64 {
+            return self.multipart_upload(&key, file);
+        }
+        let data = fs::read(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        self.put(&key, &[], &data).map(drop)
+    }
+
+    fn download(&self, path: &str, file: &Path) -> Result<bool> {
+        let Some(response) = self.get(path)? else {
+            return Ok(false);
+        };
+        let mut output = fs::File::create(file)
+            .with_context(|| format!("Failed to create {}", file.display()))?;
+        std::io::copy(&mut response.into_reader(), &mut output)
+            .map_err(|e| Error::Remote(format!("Failed to download to {}: {}", file.display(), e)))?;
+        Ok(true)
+    }
+
+    fn delete(&self, path: &str) -> Result<()> {
+        let key = self.key(path)?;
+        // S3 删除不存在的对象也返回成功；目录没有对应的对象，不需要删除
+        match self.send("DELETE", Some(&key), &[], &[]) {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(e) => Err(self.request_error("DELETE", Some(&key), e)),
+        }
+    }
+
+    fn location(&self) -> String {
+        let mut location = format!("{}{}/{}", self.origin, self.base_path, self.bucket);
+        if !self.prefix.is_empty() {
+            location.push('/');
+            location.push_str(&self.prefix);
+        }
+        location
+    }
+}
+
+/// 计算 AWS Signature Version 4 的 Authorization 请求头
+pub(crate) fn authorization(request: &SignedRequest, access_key_id: &str, secret_access_key: &str, region: &str, amz_date: &str) -> String {
+    let mut headers: Vec<&(&str, String)> = request.headers.iter().collect();
+    headers.sort_by_key(|(name, _)| *name);
+    let canonical_headers: String = headers.iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method, request.uri, canonical_query(request.query), canonical_headers, signed_headers, request.payload_hash,
+    );
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date, scope, hex::encode(Sha256::digest(canonical_request.as_bytes())),
+    );
+
+    let mut key = hmac(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes());
+    for part in [region, "s3", "aws4_request"] {
+        key = hmac(&key, part.as_bytes());
+    }
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{},SignedHeaders={},Signature={}",
+        access_key_id, scope, signed_headers, signature,
+    )
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// 编码并排序查询参数
+fn canonical_query(query: &[(String, String)]) -> String {
+    let mut pairs: Vec<(String, String)> = query.iter()
+        .map(|(name, value)| (encode_segment(name), encode_segment(value)))
+        .collect();
+    pairs.sort();
+    pairs.iter().map(|(name, value)| format!("{}={}", name, value)).collect::<Vec<_>>().join("&")
+}
+
+fn read_body(response: ureq::Response) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    response.into_reader().read_to_end(&mut data)
+        .map_err(|e| Error::Remote(format!("Failed to read S3 response: {}", e)))?;
+    Ok(data)
+}
+
+/// 解析 XML 响应体，空响应返回 `None`
+fn parse_xml(body: &str) -> Result<Option<roxmltree::Document<'_>>> {
+    if body.trim().is_empty() {
+        return Ok(None);
+    }
+    roxmltree::Document::parse(body)
+        .map(Some)
+        .map_err(|e| Error::Remote(format!("Invalid S3 response: {}", e)))
+}
+
+/// 解析 ListObjectsV2 的响应，返回 `prefix` 下的文件、子目录和下一页的标记
+fn parse_list(body: &str, prefix: &str) -> Result<(Vec<Entry>, Option<String>)> {
+    let Some(document) = parse_xml(body)? else {
+        return Ok((Vec::new(), None));
+    };
+    let root = document.root_element();
+    let mut entries = Vec::new();
+    for node in root.children().filter(|node| node.is_element()) {
+        let entry = match node.tag_name().name() {
+            "Contents" => element_text(node, "Key").map(|key| Entry {
+                name: key.strip_prefix(prefix).unwrap_or(&key).to_string(),
+                is_dir: false,
+                size: element_text(node, "Size").and_then(|size| size.parse().ok()).unwrap_or(0),
+                etag: element_text(node, "ETag").filter(|etag| !etag.is_empty()),
+            }),
+            "CommonPrefixes" => element_text(node, "Prefix").map(|dir| Entry {
+                name: dir.strip_prefix(prefix).unwrap_or(&dir).trim_end_matches('/').to_string(),
+                is_dir: true,
+                size: 0,
+                etag: None,
+            }),
+            _ => None,
+        };
+        // 跳过部分工具创建的目录占位对象（键以 / 结尾）和隐藏文件
+        if let Some(entry) = entry.filter(|e| !e.name.is_empty() && !e.name.contains('/') && !e.name.starts_with('.')) {
+            entries.push(entry);
+        }
+    }
+    let truncated = element_text(root, "IsTruncated").is_some_and(|value| value == "true");
+    let next = element_text(root, "NextContinuationToken").filter(|_| truncated);
+    Ok((entries, next))
+}
+
+/// 子元素的文本（忽略命名空间，S3 和 MinIO 使用的命名空间不完全一致）
+fn element_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+        .map(|child| child.text().unwrap_or_default().trim().to_string())
+}
+
+/// `<Error>` 响应中的错误代码和说明
+fn error_message(document: &roxmltree::Document) -> String {
+    let root = document.root_element();
+    let code = element_text(root, "Code").unwrap_or_default();
+    match element_text(root, "Message") {
+        Some(message) if !message.is_empty() => format!("{} ({})", code, message),
+        _ => code,
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
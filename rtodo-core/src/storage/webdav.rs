@@ -0,0 +1,424 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! WebDAV 存储，适用于 Nextcloud、ownCloud、群晖和其他 NAS
+//!
+//! 路径映射到根 URL 下的同名资源，上级集合不存在时用 MKCOL 逐级创建。常见服务器在 PUT 完成后才替换资源，
+//! 读取方不会看到写了一半的文件。目录列表、文件大小和 ETag 来自 `PROPFIND`（Depth: 1）。
+//!
+//! 根 URL 是 Nextcloud/ownCloud 的文件地址（`.../remote.php/dav/files/<用户>/...`）时，大文件使用分块上传：
+//! 分块写入 `uploads/<用户>/` 下的上传集合，全部完成后用 MOVE 合并为目标文件。上传集合的名称由目标地址、
+//! 文件大小和修改时间决定，中断后再次上传同一文件时跳过已经完整上传的分块。
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+use super::{encode_segment, percent_decode, split_path, Entry, Store};
+use crate::error::{Context, Error, Result};
+
+/// 连接超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// 单次读写的超时（不限制整个请求的时长，大文件上传不受影响）
+const IO_TIMEOUT: Duration = Duration::from_secs(60);
+/// 分块上传的分块大小（Nextcloud 要求除最后一块外不小于 5 MiB）
+const CHUNK_SIZE: usize = 10 * 1024 * 1024;
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:resourcetype/><d:getcontentlength/><d:getetag/></d:prop></d:propfind>"#;
+
+/// 以 WebDAV 集合为根的存储
+pub struct WebDavStore {
+    agent: ureq::Agent,
+    /// 以 `/` 结尾的根 URL
+    root: String,
+    authorization: Option<String>,
+    /// 已确认存在的集合，避免每次写入都逐级 MKCOL
+    collections: Mutex<HashSet<String>>,
+    /// Nextcloud/ownCloud 分块上传的集合（以 `/` 结尾），其他服务器为 `None`
+    uploads: Option<String>,
+    chunk_size: usize,
+}
+
+/// PROPFIND 返回的一个资源
+struct Resource {
+    /// 解码后的 URL 路径（不含末尾的 `/`）
+    path: String,
+    entry: Entry,
+}
+
+/// PUT 的请求体
+enum Body<'a> {
+    Bytes(&'a [u8]),
+    File(&'a Path),
+}
+
+impl WebDavStore {
+    /// `url` 为存放 RTodo 数据的集合，例如 `https://cloud.example.com/remote.php/dav/files/alice/RTodo`，
+    /// 不存在时在第一次写入时创建；用户名为空时不发送认证信息
+    pub fn new(url: &str, username: &str, password: &str) -> Result<Self> {
+        let url = url.trim();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(Error::InvalidInput(format!("WebDAV URL must start with http:// or https://: {}", url)));
+        }
+        let authorization = (!username.is_empty()).then(|| {
+            let credentials = format!("{}:{}", username, password);
+            format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(credentials))
+        });
+
+        let root = format!("{}/", url.trim_end_matches('/'));
+        Ok(Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout_connect(CONNECT_TIMEOUT)
+                .timeout_read(IO_TIMEOUT)
+                .timeout_write(IO_TIMEOUT)
+                .user_agent(concat!("RTodo/", env!("CARGO_PKG_VERSION")))
+                .build(),
+            uploads: uploads_url(&root),
+            root,
+            authorization,
+            collections: Mutex::new(HashSet::new()),
+            chunk_size: CHUNK_SIZE,
+        })
+    }
+
+    /// 使用更小的分块，便于测试分块上传
+    #[cfg(test)]
+    pub(crate) fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.root
+    }
+
+    /// 检查服务器是否可以访问、凭据是否正确，根集合不存在时创建
+    pub fn connect(&self) -> Result<()> {
+        if self.propfind(&self.root, 0)?.is_none() {
+            self.mkcol(&self.root)?;
+        }
+        self.lock_collections().insert(String::new());
+        Ok(())
+    }
+
+    fn url_for(&self, path: &str) -> Result<String> {
+        if path.is_empty() {
+            return Ok(self.root.clone());
+        }
+        let segments: Vec<String> = split_path(path)?.into_iter().map(encode_segment).collect();
+        Ok(format!("{}{}", self.root, segments.join("/")))
+    }
+
+    fn request(&self, method: &str, url: &str) -> ureq::Request {
+        let request = self.agent.request(method, url);
+        match &self.authorization {
+            Some(value) => request.set("Authorization", value),
+            None => request,
+        }
+    }
+
+    fn lock_collections(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.collections.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 资源不存在时返回 `None`
+    fn propfind(&self, url: &str, depth: u8) -> Result<Option<Vec<Resource>>> {
+        let response = self.request("PROPFIND", url)
+            .set("Depth", &depth.to_string())
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(PROPFIND_BODY);
+        let body = match response {
+            Ok(response) => read_body(response)?,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(e) => return Err(request_error("PROPFIND", url, e)),
+        };
+        let body = String::from_utf8_lossy(&body);
+        parse_multistatus(&body).map(Some)
+    }
+
+    fn mkcol(&self, url: &str) -> Result<()> {
+        match self.request("MKCOL", url).call() {
+            // 405：集合已经存在
+            Ok(_) | Err(ureq::Error::Status(405, _)) => Ok(()),
+            Err(e) => Err(request_error("MKCOL", url, e)),
+        }
+    }
+
+    /// 逐级创建 `path` 的上级集合（包括根集合）
+    fn ensure_parents(&self, path: &str) -> Result<()> {
+        let segments = split_path(path)?;
+        let mut dir = String::new();
+        for segment in std::iter::once("").chain(segments[..segments.len() - 1].iter().copied()) {
+            if !segment.is_empty() {
+                if !dir.is_empty() {
+                    dir.push('/');
+                }
+                dir.push_str(segment);
+            }
+            if self.lock_collections().contains(&dir) {
+                continue;
+            }
+            self.mkcol(&self.url_for(&dir)?)?;
+            self.lock_collections().insert(dir.clone());
+        }
+        Ok(())
+    }
+
+    fn put(&self, path: &str, body: Body) -> Result<()> {
+        let url = self.url_for(path)?;
+        self.ensure_parents(path)?;
+        let mut result = self.send_put(&url, &body)?;
+        // 409：上级集合已被其他设备删除，重新创建后再试一次
+        if matches!(result, Err(ureq::Error::Status(409, _))) {
+            self.lock_collections().clear();
+            self.ensure_parents(path)?;
+            result = self.send_put(&url, &body)?;
+        }
+        result.map(drop).map_err(|e| request_error("PUT", &url, e))
+    }
+
+    /// 分块上传；失败时保留上传集合，下次上传同一文件时只补传缺少的分块
+    fn chunked_upload(&self, uploads: &str, path: &str, file: &Path) -> Result<()> {
+        let url = self.url_for(path)?;
+        let metadata = fs::metadata(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let size = metadata.len();
+        let upload_url = format!("{}{}", uploads, upload_id(&url, &metadata));
+
+        let uploaded: HashMap<String, u64> = match self.propfind(&upload_url, 1)? {
+            Some(resources) => resources.into_iter()
+                .filter(|resource| !resource.entry.is_dir)
+                .map(|resource| (resource.entry.name, resource.entry.size))
+                .collect(),
+            None => {
+                self.request("MKCOL", &upload_url).set("Destination", &url).call()
+                    .map_err(|e| request_error("MKCOL", &upload_url, e))?;
+                HashMap::new()
+            }
+        };
+
+        let mut input = fs::File::open(file)
+            .with_context(|| format!("Failed to open {}", file.display()))?;
+        let mut buffer = vec![0; self.chunk_size];
+        let chunk_size = self.chunk_size as u64;
+        for number in 1..=size.div_ceil(chunk_size) {
+            let offset = (number - 1) * chunk_size;
+            let length = (size - offset).min(chunk_size);
+            let name = number.to_string();
+            if uploaded.get(&name) == Some(&length) {
+                continue;
+            }
+
+            let chunk = &mut buffer[..length as usize];
+            input.seek(SeekFrom::Start(offset))
+                .and_then(|_| input.read_exact(chunk))
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let chunk_url = format!("{}/{}", upload_url, name);
+            self.request("PUT", &chunk_url)
+                .set("Destination", &url)
+                .send_bytes(chunk)
+                .map_err(|e| request_error("PUT", &chunk_url, e))?;
+        }
+
+        self.ensure_parents(path)?;
+        let assembled = format!("{}/.file", upload_url);
+        self.request("MOVE", &assembled)
+            .set("Destination", &url)
+            .set("OC-Total-Length", &size.to_string())
+            .call()
+            .map_err(|e| request_error("MOVE", &assembled, e))?;
+        Ok(())
+    }
+
+    fn send_put(&self, url: &str, body: &Body) -> Result<Result<ureq::Response, ureq::Error>> {
+        let request = self.request("PUT", url);
+        Ok(match body {
+            Body::Bytes(data) => request.send_bytes(data),
+            Body::File(path) => {
+                let file = fs::File::open(path)
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                // 指定长度，避免分块传输（部分服务器不支持）
+                let size = file.metadata()?.len();
+                request.set("Content-Length", &size.to_string()).send(file)
+            }
+        })
+    }
+}
+
+impl Store for WebDavStore {
+    fn entries(&self, dir: &str) -> Result<Vec<Entry>> {
+        // 集合的 URL 以 / 结尾，部分服务器对不带 / 的地址返回重定向
+        let url = format!("{}/", self.url_for(dir)?.trim_end_matches('/'));
+        let own_path = href_path(&url);
+        let mut entries: Vec<Entry> = self.propfind(&url, 1)?
+            .unwrap_or_default()
+            .into_iter()
+            // 跳过集合本身和上传中的临时文件（例如 Nextcloud 的 .part）
+            .filter(|resource| resource.path != own_path && !resource.entry.name.starts_with('.'))
+            .map(|resource| resource.entry)
+            .collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    }
+
+    fn stat(&self, path: &str) -> Result<Option<Entry>> {
+        let url = self.url_for(path)?;
+        Ok(self.propfind(&url, 0)?.and_then(|resources| resources.into_iter().next()).map(|r| r.entry))
+    }
+
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>> {
+        let url = self.url_for(path)?;
+        match self.request("GET", &url).call() {
+            Ok(response) => read_body(response).map(Some),
+            Err(ureq::Error::Status(404, _)) => Ok(None),
+            Err(e) => Err(request_error("GET", &url, e)),
+        }
+    }
+
+    fn write(&self, path: &str, data: &[u8]) -> Result<()> {
+        self.put(path, Body::Bytes(data))
+    }
+
+    fn upload(&self, path: &str, file: &Path) -> Result<()> {
+        if let Some(uploads) = &self.uploads {
+            let size = fs::metadata(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?
+                .len();
+            if size > self.chunk_size as u64 {
+                return self.chunked_upload(uploads, path, file);
+            }
+        }
+        self.put(path, Body::File(file))
+    }
+
+    fn download(&self, path: &str, file: &Path) -> Result<bool> {
+        let url = self.url_for(path)?;
+        match self.request("GET", &url).call() {
+            Ok(response) => {
+                copy_body(response, file)?;
+                Ok(true)
+            }
+            Err(ureq::Error::Status(404, _)) => Ok(false),
+            Err(e) => Err(request_error("GET", &url, e)),
+        }
+    }
+
+    fn delete(&self, path: &str) -> Result<()> {
+        let url = self.url_for(path)?;
+        match self.request("DELETE", &url).call() {
+            Ok(_) | Err(ureq::Error::Status(404, _)) => Ok(()),
+            Err(e) => Err(request_error("DELETE", &url, e)),
+        }
+    }
+
+    fn location(&self) -> String {
+        self.root.clone()
+    }
+}
+
+/// Nextcloud/ownCloud 的上传集合：根 URL 为 `<服务器>/remote.php/dav/files/<用户>/...` 时是
+/// `<服务器>/remote.php/dav/uploads/<用户>/`
+fn uploads_url(root: &str) -> Option<String> {
+    let (server, rest) = root.split_once("/remote.php/dav/files/")?;
+    let user = rest.split('/').next().filter(|user| !user.is_empty())?;
+    Some(format!("{}/remote.php/dav/uploads/{}/", server, user))
+}
+
+/// 上传集合的名称：同一文件（目标地址、大小和修改时间都相同）再次上传时得到相同的名称
+fn upload_id(url: &str, metadata: &fs::Metadata) -> String {
+    let modified = metadata.modified().ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |time| time.as_millis());
+    let digest = Sha256::digest(format!("{}\n{}\n{}", url, metadata.len(), modified));
+    format!("rtodo-{}", &hex::encode(digest)[..32])
+}
+
+fn read_body(response: ureq::Response) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    response.into_reader().read_to_end(&mut data)
+        .map_err(|e| Error::Remote(format!("Failed to read WebDAV response: {}", e)))?;
+    Ok(data)
+}
+
+/// 把响应写入本地文件，不把整个文件读入内存
+fn copy_body(response: ureq::Response, file: &Path) -> Result<()> {
+    let mut output = fs::File::create(file)
+        .with_context(|| format!("Failed to create {}", file.display()))?;
+    std::io::copy(&mut response.into_reader(), &mut output)
+        .map_err(|e| Error::Remote(format!("Failed to download to {}: {}", file.display(), e)))?;
+    Ok(())
+}
+
+fn request_error(method: &str, url: &str, error: ureq::Error) -> Error {
+    let message = match error {
+        ureq::Error::Status(401, _) => "the server rejected the username or password (HTTP 401)".to_string(),
+        ureq::Error::Status(507, _) => "the server is out of storage space (HTTP 507)".to_string(),
+        ureq::Error::Status(code, response) => format!("HTTP {} {}", code, response.status_text()),
+        ureq::Error::Transport(e) => e.to_string(),
+    };
+    Error::Remote(format!("WebDAV {} {} failed: {}", method, url, message))
+}
+
+/// 解析 PROPFIND 的 207 Multi-Status 响应
+fn parse_multistatus(body: &str) -> Result<Vec<Resource>> {
+    let document = roxmltree::Document::parse(body)
+        .map_err(|e| Error::Remote(format!("Invalid WebDAV PROPFIND response: {}", e)))?;
+
+    let mut resources = Vec::new();
+    for response in document.descendants().filter(|node| is_dav(node, "response")) {
+        let Some(href) = child_text(response, "href") else { continue };
+        let path = href_path(&href);
+        let mut entry = Entry {
+            name: path.rsplit('/').next().unwrap_or_default().to_string(),
+            is_dir: false,
+            size: 0,
+            etag: None,
+        };
+        // 只取状态为 200 的属性，服务器不支持的属性以 404 返回
+        for propstat in response.children().filter(|node| is_dav(node, "propstat")) {
+            if !child_text(propstat, "status").is_some_and(|status| status.contains(" 200")) {
+                continue;
+            }
+            for prop in propstat.descendants() {
+                if is_dav(&prop, "collection") {
+                    entry.is_dir = true;
+                } else if is_dav(&prop, "getcontentlength") {
+                    entry.size = prop.text().and_then(|t| t.trim().parse().ok()).unwrap_or(0);
+                } else if is_dav(&prop, "getetag") {
+                    entry.etag = prop.text().map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+                }
+            }
+        }
+        resources.push(Resource { path, entry });
+    }
+    Ok(resources)
+}
+
+fn is_dav(node: &roxmltree::Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name && node.tag_name().namespace() == Some("DAV:")
+}
+
+fn child_text(node: roxmltree::Node, name: &str) -> Option<String> {
+    node.children()
+        .find(|child| is_dav(child, name))
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+}
+
+/// href 或 URL 的路径部分，解码并去掉末尾的 `/`，用于比较
+fn href_path(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(start) => {
+            let rest = &href[start + 3..];
+            rest.find('/').map_or("", |i| &rest[i..])
+        }
+        None => href,
+    };
+    percent_decode(path.trim_end_matches('/'))
+}
@@ -0,0 +1,339 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 本地表的读写
+//!
+//! 这里的字段以本地 id 表示引用（父级、任务组、标签、所属任务），与 uid 的转换由合并步骤负责。
+//! 同步在自己的事务中调用这些函数，因此不使用会开启事务的仓库方法。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use super::log::{Fields, Kind};
+use crate::error::{Context, Result};
+use crate::utils::now_timestamp;
+
+/// 引用其他实体的字段：(字段名, 被引用的实体类型, 是否为列表)
+pub(super) fn references(kind: Kind) -> &'static [(&'static str, Kind, bool)] {
+    match kind {
+        Kind::Group => &[("parent", Kind::Group, false)],
+        Kind::Tag => &[("parent", Kind::Tag, false)],
+        Kind::Todo => &[("group", Kind::Group, false), ("tags", Kind::Tag, true)],
+        Kind::Step => &[("todo", Kind::Todo, false)],
+    }
+}
+
+fn table(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Group => "task_groups",
+        Kind::Tag => "tags",
+        Kind::Todo => "todos",
+        Kind::Step => "todo_steps",
+    }
+}
+
+fn text(fields: &Fields, name: &str) -> Option<String> {
+    fields.get(name).and_then(Value::as_str).map(str::to_string)
+}
+
+fn int(fields: &Fields, name: &str) -> Option<i64> {
+    fields.get(name).and_then(Value::as_i64)
+}
+
+fn ids(fields: &Fields, name: &str) -> Vec<i64> {
+    fields.get(name)
+        .and_then(Value::as_array)
+        .map(|ids| ids.iter().filter_map(Value::as_i64).collect())
+        .unwrap_or_default()
+}
+
+/// 读取某类实体的全部本地记录
+pub(super) fn read_all(conn: &Connection, kind: Kind) -> Result<BTreeMap<i64, Fields>> {
+    let sql = match kind {
+        Kind::Group => "SELECT id, name, parent_id, icon, color, sort_order, created_at FROM task_groups",
+        Kind::Tag => "SELECT id, name, color, parent_id, created_at FROM tags",
+        Kind::Todo => "SELECT id, title, description, status, priority, group_id, assignee,
+                              start_date, due_date, completed_at, created_at
+                       FROM todos",
+        Kind::Step => "SELECT id, todo_id, title, is_completed, sort_order, created_at FROM todo_steps",
+    };
+    let mut stmt = conn.prepare(sql)
+        .with_context(|| format!("Failed to prepare {} sync query", kind.as_str()))?;
+    let rows = stmt.query_map([], |row| {
+        let fields = match kind {
+            Kind::Group => json!({
+                "name": row.get::<_, String>(1)?,
+                "parent": row.get::<_, Option<i64>>(2)?,
+                "icon": row.get::<_, Option<String>>(3)?,
+                "color": row.get::<_, Option<String>>(4)?,
+                "sort_order": row.get::<_, Option<i64>>(5)?.unwrap_or(0),
+                "created_at": row.get::<_, i64>(6)?,
+            }),
+            Kind::Tag => json!({
+                "name": row.get::<_, String>(1)?,
+                "color": row.get::<_, String>(2)?,
+                "parent": row.get::<_, Option<i64>>(3)?,
+                "created_at": row.get::<_, i64>(4)?,
+            }),
+            Kind::Todo => json!({
+                "title": row.get::<_, String>(1)?,
+                "description": row.get::<_, Option<String>>(2)?,
+                "status": row.get::<_, i64>(3)?,
+                "priority": row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+                "group": row.get::<_, Option<i64>>(5)?,
+                "assignee": row.get::<_, Option<String>>(6)?,
+                "start_date": row.get::<_, Option<i64>>(7)?,
+                "due_date": row.get::<_, Option<i64>>(8)?,
+                "completed_at": row.get::<_, Option<i64>>(9)?,
+                "created_at": row.get::<_, i64>(10)?,
+            }),
+            Kind::Step => json!({
+                "todo": row.get::<_, i64>(1)?,
+                "title": row.get::<_, String>(2)?,
+                "is_completed": row.get::<_, Option<bool>>(3)?.unwrap_or(false),
+                "sort_order": row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+                "created_at": row.get::<_, i64>(5)?,
+            }),
+        };
+        let Value::Object(fields) = fields else { unreachable!() };
+        Ok((row.get::<_, i64>(0)?, fields.into_iter().collect::<Fields>()))
+    })
+    .with_context(|| format!("Failed to read {} rows for sync", kind.as_str()))?;
+
+    let mut all = BTreeMap::new();
+    for row in rows {
+        let (id, fields) = row?;
+        all.insert(id, fields);
+    }
+
+    if kind == Kind::Todo {
+        let mut tags: HashMap<i64, Vec<i64>> = HashMap::new();
+        let mut stmt = conn.prepare("SELECT todo_id, tag_id FROM todo_tags ORDER BY tag_id")?;
+        let links = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?;
+        for link in links {
+            let (todo_id, tag_id) = link?;
+            tags.entry(todo_id).or_default().push(tag_id);
+        }
+        for (id, fields) in all.iter_mut() {
+            fields.insert("tags".to_string(), json!(tags.remove(id).unwrap_or_default()));
+        }
+    }
+    Ok(all)
+}
+
+/// 读取本地记录的 uid
+pub(super) fn uid(conn: &Connection, kind: Kind, id: i64) -> Result<Option<String>> {
+    conn.query_row(&format!("SELECT uid FROM {} WHERE id = ?1", table(kind)), params![id], |row| row.get(0))
+        .with_context(|| format!("Failed to read {} uid", kind.as_str()))
+}
+
+/// 修改本地记录的 uid
+pub(super) fn set_uid(conn: &Connection, kind: Kind, id: i64, uid: &str) -> Result<()> {
+    conn.execute(&format!("UPDATE {} SET uid = ?1 WHERE id = ?2", table(kind)), params![uid, id])
+        .with_context(|| format!("Failed to update {} uid", kind.as_str()))?;
+    Ok(())
+}
+
+/// 插入本地记录，返回新记录的 id
+pub(super) fn insert(conn: &Connection, kind: Kind, uid: &str, fields: &Fields) -> Result<i64> {
+    let now = now_timestamp();
+    let created_at = int(fields, "created_at").unwrap_or(now);
+    match kind {
+        Kind::Group => conn.execute(
+            "INSERT INTO task_groups (uid, name, parent_id, icon, color, sort_order, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                uid,
+                text(fields, "name").unwrap_or_default(),
+                int(fields, "parent"),
+                text(fields, "icon"),
+                text(fields, "color"),
+                int(fields, "sort_order").unwrap_or(0),
+                created_at,
+                now,
+            ],
+        ),
+        Kind::Tag => conn.execute(
+            "INSERT INTO tags (uid, name, color, parent_id, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                uid,
+                text(fields, "name").unwrap_or_default(),
+                text(fields, "color").unwrap_or_else(|| "#409EFF".to_string()),
+                int(fields, "parent"),
+                created_at,
+            ],
+        ),
+        Kind::Todo => conn.execute(
+            "INSERT INTO todos (uid, title, description, status, priority, group_id, assignee,
+                                start_date, due_date, completed_at, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                uid,
+                text(fields, "title").unwrap_or_default(),
+                text(fields, "description"),
+                int(fields, "status").unwrap_or(0),
+                int(fields, "priority").unwrap_or(0),
+                int(fields, "group"),
+                text(fields, "assignee"),
+                int(fields, "start_date"),
+                int(fields, "due_date"),
+                int(fields, "completed_at"),
+                created_at,
+                now,
+            ],
+        ),
+        Kind::Step => conn.execute(
+            "INSERT INTO todo_steps (uid, todo_id, title, is_completed, sort_order, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                uid,
+                int(fields, "todo"),
+                text(fields, "title").unwrap_or_default(),
+                fields.get("is_completed").and_then(Value::as_bool).unwrap_or(false),
+                int(fields, "sort_order").unwrap_or(0),
+                created_at,
+            ],
+        ),
+    }
+    .with_context(|| format!("Failed to insert synced {}", kind.as_str()))?;
+
+    let id = conn.last_insert_rowid();
+    if kind == Kind::Todo {
+        set_todo_tags(conn, id, &ids(fields, "tags"))?;
+    }
+    Ok(id)
+}
+
+/// 用合并后的字段覆盖本地记录
+pub(super) fn update(conn: &Connection, kind: Kind, id: i64, fields: &Fields) -> Result<()> {
+    let now = now_timestamp();
+    match kind {
+        Kind::Group => conn.execute(
+            "UPDATE task_groups SET name = ?1, parent_id = ?2, icon = ?3, color = ?4, sort_order = ?5,
+                                    created_at = ?6, updated_at = ?7
+             WHERE id = ?8",
+            params![
+                text(fields, "name").unwrap_or_default(),
+                int(fields, "parent"),
+                text(fields, "icon"),
+                text(fields, "color"),
+                int(fields, "sort_order").unwrap_or(0),
+                int(fields, "created_at").unwrap_or(now),
+                now,
+                id,
+            ],
+        ),
+        Kind::Tag => conn.execute(
+            "UPDATE tags SET name = ?1, color = ?2, parent_id = ?3, created_at = ?4 WHERE id = ?5",
+            params![
+                text(fields, "name").unwrap_or_default(),
+                text(fields, "color").unwrap_or_else(|| "#409EFF".to_string()),
+                int(fields, "parent"),
+                int(fields, "created_at").unwrap_or(now),
+                id,
+            ],
+        ),
+        Kind::Todo => conn.execute(
+            "UPDATE todos SET title = ?1, description = ?2, status = ?3, priority = ?4, group_id = ?5,
+                              assignee = ?6, start_date = ?7, due_date = ?8, completed_at = ?9,
+                              created_at = ?10, updated_at = ?11
+             WHERE id = ?12",
+            params![
+                text(fields, "title").unwrap_or_default(),
+                text(fields, "description"),
+                int(fields, "status").unwrap_or(0),
+                int(fields, "priority").unwrap_or(0),
+                int(fields, "group"),
+                text(fields, "assignee"),
+                int(fields, "start_date"),
+                int(fields, "due_date"),
+                int(fields, "completed_at"),
+                int(fields, "created_at").unwrap_or(now),
+                now,
+                id,
+            ],
+        ),
+        Kind::Step => conn.execute(
+            "UPDATE todo_steps SET todo_id = ?1, title = ?2, is_completed = ?3, sort_order = ?4, created_at = ?5
+             WHERE id = ?6",
+            params![
+                int(fields, "todo"),
+                text(fields, "title").unwrap_or_default(),
+                fields.get("is_completed").and_then(Value::as_bool).unwrap_or(false),
+                int(fields, "sort_order").unwrap_or(0),
+                int(fields, "created_at").unwrap_or(now),
+                id,
+            ],
+        ),
+    }
+    .with_context(|| format!("Failed to update synced {} {}", kind.as_str(), id))?;
+
+    if kind == Kind::Todo {
+        set_todo_tags(conn, id, &ids(fields, "tags"))?;
+    }
+    Ok(())
+}
+
+fn set_todo_tags(conn: &Connection, todo_id: i64, tag_ids: &[i64]) -> Result<()> {
+    conn.execute("DELETE FROM todo_tags WHERE todo_id = ?", params![todo_id])?;
+    for tag_id in tag_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO todo_tags (todo_id, tag_id) VALUES (?1, ?2)",
+            params![todo_id, tag_id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 删除本地记录
+///
+/// 不依赖外键的 ON DELETE 行为（旧版本迁移后的表没有外键约束），关联数据均显式处理；
+/// 其他设备删除子记录时会各自发送删除记录，这里只解除引用
+pub(super) fn delete(conn: &Connection, kind: Kind, id: i64) -> Result<()> {
+    let now = now_timestamp();
+    match kind {
+        Kind::Group => {
+            conn.execute("UPDATE todos SET group_id = NULL, updated_at = ?1 WHERE group_id = ?2", params![now, id])?;
+            conn.execute("UPDATE task_groups SET parent_id = NULL, updated_at = ?1 WHERE parent_id = ?2", params![now, id])?;
+        }
+        Kind::Tag => {
+            conn.execute("DELETE FROM todo_tags WHERE tag_id = ?", params![id])?;
+            conn.execute("UPDATE tags SET parent_id = NULL WHERE parent_id = ?", params![id])?;
+        }
+        Kind::Todo => {
+            conn.execute("DELETE FROM todo_steps WHERE todo_id = ?", params![id])?;
+            conn.execute("DELETE FROM todo_tags WHERE todo_id = ?", params![id])?;
+            conn.execute("DELETE FROM attachments WHERE todo_id = ?", params![id])?;
+        }
+        Kind::Step => {}
+    }
+    conn.execute(&format!("DELETE FROM {} WHERE id = ?", table(kind)), params![id])
+        .with_context(|| format!("Failed to delete synced {} {}", kind.as_str(), id))?;
+    Ok(())
+}
+
+/// 按名称（不区分大小写）查找标签
+pub(super) fn find_tag(conn: &Connection, name: &str, exclude: Option<i64>) -> Result<Option<i64>> {
+    let id = conn.query_row(
+        "SELECT id FROM tags WHERE name = ?1 COLLATE NOCASE AND id IS NOT ?2",
+        params![name, exclude],
+        |row| row.get(0),
+    )
+    .optional()?;
+    Ok(id)
+}
+
+/// 把 `id` 的父级设为 `parent` 是否会形成循环
+pub(super) fn creates_cycle(conn: &Connection, kind: Kind, id: i64, parent: i64) -> Result<bool> {
+    let sql = format!(
+        "WITH RECURSIVE ancestors(id) AS (
+            SELECT ?1
+            UNION
+            SELECT t.parent_id FROM {} t JOIN ancestors a ON t.id = a.id WHERE t.parent_id IS NOT NULL
+        )
+        SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = ?2)",
+        table(kind),
+    );
+    Ok(conn.query_row(&sql, params![parent, id], |row| row.get(0))?)
+}
@@ -0,0 +1,237 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 变更日志
+//!
+//! 每台设备只写自己的目录 `devices/<device_id>/`，日志段按序号命名（`0000000001.jsonl`），
+//! 写入后不再修改，因此同步工具不会产生冲突副本。段的第一行是头部（格式版本、设备、序号、
+//! 记录数），其余每行一条记录。记录是实体的完整快照，附带每个字段最后一次修改的版本。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{Context, Error, Result};
+use crate::storage::{Entry, Store};
+
+/// 存放各设备日志的目录
+pub const DEVICES_DIR: &str = "devices";
+
+/// 日志格式版本
+const FORMAT: u32 = 1;
+
+/// 实体字段（字段名 -> JSON 值）
+pub type Fields = BTreeMap<String, Value>;
+
+/// 同步的实体类型，按依赖顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Group,
+    Tag,
+    Todo,
+    Step,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 4] = [Kind::Group, Kind::Tag, Kind::Todo, Kind::Step];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Group => "group",
+            Self::Tag => "tag",
+            Self::Todo => "todo",
+            Self::Step => "step",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == value)
+    }
+}
+
+/// 版本：混合逻辑时钟的时间戳（毫秒）和写入的设备，按 (ts, device) 比较
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Version {
+    pub ts: i64,
+    pub device: String,
+}
+
+/// 字段的版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldVersion {
+    #[serde(flatten)]
+    pub version: Version,
+    /// 修改前看到的版本，用于判断两次修改是否并发
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<Version>,
+}
+
+/// 一条变更记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub kind: Kind,
+    pub uid: String,
+    /// 产生这条记录的修改（删除记录为删除的版本）
+    pub version: Version,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub deleted: bool,
+    #[serde(default)]
+    pub fields: Fields,
+    #[serde(default)]
+    pub versions: BTreeMap<String, FieldVersion>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: u32,
+    device: String,
+    segment: i64,
+    records: usize,
+}
+
+/// 一个日志段
+#[derive(Debug)]
+pub struct Segment {
+    pub device: String,
+    pub seq: i64,
+    pub records: Vec<Record>,
+}
+
+pub fn segment_path(device: &str, seq: i64) -> String {
+    format!("{}/{}/{:010}.jsonl", DEVICES_DIR, device, seq)
+}
+
+/// 日志段文件名中的序号；同步工具的冲突副本等其他文件返回 `None`
+fn parse_segment_name(name: &str) -> Option<i64> {
+    let digits = name.strip_suffix(".jsonl")?;
+    if digits.len() != 10 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+pub fn encode(device: &str, seq: i64, records: &[Record]) -> Result<String> {
+    let header = Header { format: FORMAT, device: device.to_string(), segment: seq, records: records.len() };
+    let mut body = serde_json::to_string(&header)?;
+    body.push('\n');
+    for record in records {
+        body.push_str(&serde_json::to_string(record)?);
+        body.push('\n');
+    }
+    Ok(body)
+}
+
+pub fn decode(data: &[u8]) -> Result<Segment> {
+    let text = std::str::from_utf8(data).map_err(|e| Error::Corrupted(e.to_string()))?;
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+
+    let header: Header = serde_json::from_str(lines.next().unwrap_or_default())
+        .map_err(|e| Error::Corrupted(format!("Invalid segment header: {}", e)))?;
+    if header.format > FORMAT {
+        return Err(Error::InvalidInput(format!(
+            "Sync log format {} is newer than this version of RTodo supports ({}). Please upgrade RTodo.",
+            header.format, FORMAT,
+        )));
+    }
+
+    let records = lines
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Record>, _>>()
+        .map_err(|e| Error::Corrupted(format!("Invalid sync record: {}", e)))?;
+    if records.len() != header.records {
+        return Err(Error::Corrupted(format!(
+            "Segment has {} of {} records",
+            records.len(),
+            header.records,
+        )));
+    }
+    Ok(Segment { device: header.device, seq: header.segment, records })
+}
+
+/// 设备目录中日志段的序号，升序
+fn list_segments(store: &dyn Store, device: &str) -> Result<Vec<i64>> {
+    let mut seqs: Vec<i64> = store.list(&format!("{}/{}", DEVICES_DIR, device))?
+        .iter()
+        .filter_map(|name| parse_segment_name(name))
+        .collect();
+    seqs.sort_unstable();
+    Ok(seqs)
+}
+
+/// 同步目录中各设备的目录
+fn device_entries(store: &dyn Store) -> Result<Vec<Entry>> {
+    Ok(store.entries(DEVICES_DIR)?
+        .into_iter()
+        .filter(|entry| entry.is_dir && crate::storage::split_path(&entry.name).is_ok())
+        .collect())
+}
+
+fn list_devices(store: &dyn Store) -> Result<Vec<String>> {
+    Ok(device_entries(store)?.into_iter().map(|entry| entry.name).collect())
+}
+
+/// 同步目录内容的指纹，变化时说明有新的变更
+///
+/// 后端为设备目录提供 ETag（WebDAV）时只需列出一次 `devices`，否则取各设备最新的日志段
+pub fn fingerprint(store: &dyn Store) -> Result<String> {
+    let devices = device_entries(store)?;
+    let mut parts = Vec::new();
+    if devices.iter().all(|device| device.etag.is_some()) {
+        for device in devices {
+            parts.push(format!("{}:{}", device.name, device.etag.unwrap_or_default()));
+        }
+    } else {
+        for device in devices {
+            let latest = list_segments(store, &device.name)?.last().copied().unwrap_or(0);
+            parts.push(format!("{}:{}", device.name, latest));
+        }
+    }
+    Ok(parts.join(","))
+}
+
+/// 读取结果
+pub struct Fetched {
+    /// 其他设备在游标之后的日志段，每台设备按序号排列
+    pub segments: Vec<Segment>,
+    /// 本设备目录中最大的日志段序号
+    pub own_latest: i64,
+}
+
+/// 读取其他设备在游标之后的日志段
+///
+/// 序号必须连续：某个段缺失或不完整（同步工具仍在传输）时，停在该段之前，下次再读
+pub fn fetch(store: &dyn Store, own_device: &str, cursors: &HashMap<String, i64>) -> Result<Fetched> {
+    let mut fetched = Fetched { segments: Vec::new(), own_latest: 0 };
+
+    for device in list_devices(store)? {
+        let seqs = list_segments(store, &device)?;
+        if device == own_device {
+            fetched.own_latest = seqs.last().copied().unwrap_or(0);
+            continue;
+        }
+
+        let cursor = cursors.get(&device).copied().unwrap_or(0);
+        for (expected, seq) in (cursor + 1..).zip(seqs.into_iter().filter(|seq| *seq > cursor)) {
+            if seq != expected {
+                tracing::debug!("Sync segment {} of device {} not available yet", expected, device);
+                break;
+            }
+            let path = segment_path(&device, seq);
+            let Some(data) = store.read(&path)? else { break };
+            match decode(&data).with_context(|| format!("Failed to read {}", path)) {
+                Ok(segment) if segment.device == device && segment.seq == seq => fetched.segments.push(segment),
+                Ok(_) => {
+                    tracing::warn!("Skipping {}: header does not match its location", path);
+                    break;
+                }
+                Err(e) if matches!(e.root(), Error::Corrupted(_)) => {
+                    tracing::warn!("Skipping incomplete sync segment: {}", e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+    Ok(fetched)
+}
@@ -0,0 +1,603 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 合并
+//!
+//! `sync_entities` 保存每个实体上次同步时的字段值和字段版本（影子副本）。本地数据与影子副本
+//! 不同的字段视为本地修改，获得新版本；其他设备的记录逐字段与影子副本比较，版本较新者获胜。
+//! 两个版本互不以对方为基础时是并发修改，值不同时记录冲突。
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use super::entities;
+use super::log::{FieldVersion, Fields, Kind, Record, Version};
+use super::{get_state, set_state, CLOCK_KEY};
+use crate::error::{Context, Result};
+use crate::models::{ConflictResolution, SyncReport};
+use crate::utils::{new_uuid, now_timestamp};
+
+/// 别名链的最大长度，防止损坏的数据造成死循环
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// 实体的影子副本
+#[derive(Debug, Clone)]
+struct Shadow {
+    uid: String,
+    local_id: Option<i64>,
+    fields: Fields,
+    versions: BTreeMap<String, FieldVersion>,
+    deleted_at: Option<i64>,
+    /// 与本地已有实体合并（同名标签）时指向该实体的 uid
+    alias_of: Option<String>,
+}
+
+impl Shadow {
+    fn new(uid: String) -> Self {
+        Self { uid, local_id: None, fields: Fields::new(), versions: BTreeMap::new(), deleted_at: None, alias_of: None }
+    }
+
+    fn record(&self, kind: Kind, version: Version) -> Record {
+        Record {
+            kind,
+            uid: self.uid.clone(),
+            version,
+            deleted: false,
+            fields: self.fields.clone(),
+            versions: self.versions.clone(),
+        }
+    }
+}
+
+const SHADOW_COLUMNS: &str = "uid, local_id, fields, versions, deleted_at, alias_of";
+
+/// sync_entities 的一行：uid、local_id、fields、versions、deleted_at、alias_of
+type ShadowRow = (String, Option<i64>, String, String, Option<i64>, Option<String>);
+
+fn shadow_from_row(row: &rusqlite::Row) -> rusqlite::Result<ShadowRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+}
+
+fn parse_shadow((uid, local_id, fields, versions, deleted_at, alias_of): ShadowRow) -> Result<Shadow> {
+    Ok(Shadow {
+        uid,
+        local_id,
+        fields: serde_json::from_str(&fields).context("Invalid sync state")?,
+        versions: serde_json::from_str(&versions).context("Invalid sync state")?,
+        deleted_at,
+        alias_of,
+    })
+}
+
+/// 引用解析结果
+enum Resolved {
+    Local(i64),
+    Deleted,
+    /// 被引用的实体尚未同步到本设备
+    Unknown,
+}
+
+/// 转换为本地字段的结果
+enum Localized {
+    /// 本地字段和去掉已删除引用后的同步字段
+    Ready(Fields, Fields),
+    Deferred,
+    /// 所属的任务已删除（步骤）
+    Orphaned,
+}
+
+/// 记录的应用结果
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Applied {
+    Done,
+    /// 引用的实体尚未到达，稍后重试
+    Deferred,
+    Ignored,
+}
+
+struct Conflict {
+    field: String,
+    local_value: Value,
+    remote_value: Value,
+    resolution: ConflictResolution,
+    remote_device: String,
+}
+
+pub(super) struct Merger<'a> {
+    conn: &'a Connection,
+    device: String,
+    /// 混合逻辑时钟：不小于本机时间，也不小于见过的任何版本
+    clock: i64,
+    uids: HashMap<(Kind, i64), String>,
+    pub report: SyncReport,
+}
+
+impl<'a> Merger<'a> {
+    pub fn new(conn: &'a Connection, device: &str) -> Result<Self> {
+        let clock = get_state(conn, CLOCK_KEY)?.and_then(|c| c.parse().ok()).unwrap_or(0);
+
+        let mut uids = HashMap::new();
+        let mut stmt = conn.prepare("SELECT entity, local_id, uid FROM sync_entities WHERE local_id IS NOT NULL")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?)))?;
+        for row in rows {
+            let (entity, local_id, uid) = row?;
+            if let Some(kind) = Kind::parse(&entity) {
+                uids.insert((kind, local_id), uid);
+            }
+        }
+
+        Ok(Self {
+            conn,
+            device: device.to_string(),
+            clock,
+            uids,
+            report: SyncReport { device_id: device.to_string(), ..Default::default() },
+        })
+    }
+
+    /// 保存时钟
+    pub fn finish(self) -> Result<SyncReport> {
+        set_state(self.conn, CLOCK_KEY, &self.clock.to_string())?;
+        Ok(self.report)
+    }
+
+    fn tick(&mut self) -> Version {
+        self.clock = now_timestamp().max(self.clock + 1);
+        Version { ts: self.clock, device: self.device.clone() }
+    }
+
+    fn observe(&mut self, ts: i64) {
+        self.clock = self.clock.max(ts);
+    }
+
+    fn load(&self, kind: Kind, uid: &str) -> Result<Option<Shadow>> {
+        let row = self.conn.query_row(
+            &format!("SELECT {} FROM sync_entities WHERE entity = ?1 AND uid = ?2", SHADOW_COLUMNS),
+            params![kind.as_str(), uid],
+            shadow_from_row,
+        )
+        .optional()?;
+        row.map(parse_shadow).transpose()
+    }
+
+    /// 某类实体的影子副本；`containing` 不为空时只返回字段中包含该文本的
+    fn load_all(&self, kind: Kind, containing: Option<&str>) -> Result<Vec<Shadow>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM sync_entities WHERE entity = ?1 AND (?2 IS NULL OR instr(fields, ?2) > 0) ORDER BY rowid",
+            SHADOW_COLUMNS,
+        ))?;
+        let rows = stmt.query_map(params![kind.as_str(), containing], shadow_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.into_iter().map(parse_shadow).collect()
+    }
+
+    fn save(&self, kind: Kind, shadow: &Shadow) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sync_entities (entity, uid, local_id, fields, versions, deleted_at, alias_of)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                kind.as_str(),
+                shadow.uid,
+                shadow.local_id,
+                serde_json::to_string(&shadow.fields)?,
+                serde_json::to_string(&shadow.versions)?,
+                shadow.deleted_at,
+                shadow.alias_of,
+            ],
+        )
+        .context("Failed to save sync state")?;
+        Ok(())
+    }
+
+    /// 为尚未同步过的本地记录分配 uid，沿用记录自身的 uid
+    pub fn assign_uids(&mut self) -> Result<()> {
+        for kind in Kind::ALL {
+            for id in entities::read_all(self.conn, kind)?.into_keys() {
+                if self.uids.contains_key(&(kind, id)) {
+                    continue;
+                }
+                // 记录的 uid 已被其他实体的同步状态占用（例如复制出来的数据库）时换一个新的
+                let mut uid = entities::uid(self.conn, kind, id)?.unwrap_or_default();
+                if uid.is_empty() || self.load(kind, &uid)?.is_some() {
+                    uid = new_uuid();
+                    entities::set_uid(self.conn, kind, id, &uid)?;
+                }
+                let mut shadow = Shadow::new(uid);
+                shadow.local_id = Some(id);
+                self.save(kind, &shadow)?;
+                self.uids.insert((kind, id), shadow.uid);
+            }
+        }
+        Ok(())
+    }
+
+    /// 本地字段中的引用（id）转换为 uid；列表按 uid 排序
+    fn to_shared(&self, kind: Kind, mut fields: Fields) -> Fields {
+        for (name, target, many) in entities::references(kind) {
+            let Some(value) = fields.get_mut(*name) else { continue };
+            *value = if *many {
+                let mut uids: Vec<&String> = value.as_array()
+                    .map(|ids| ids.iter().filter_map(|id| self.uids.get(&(*target, id.as_i64()?))).collect())
+                    .unwrap_or_default();
+                uids.sort();
+                Value::from(uids.into_iter().cloned().collect::<Vec<_>>())
+            } else {
+                value.as_i64()
+                    .and_then(|id| self.uids.get(&(*target, id)))
+                    .map_or(Value::Null, |uid| Value::from(uid.as_str()))
+            };
+        }
+        fields
+    }
+
+    /// 对比本地数据和影子副本，返回本地修改和删除产生的记录
+    pub fn scan(&mut self) -> Result<Vec<Record>> {
+        let mut records = Vec::new();
+        for kind in Kind::ALL {
+            let mut rows = entities::read_all(self.conn, kind)?;
+            for mut shadow in self.load_all(kind, None)? {
+                let Some(local_id) = shadow.local_id else { continue };
+
+                let Some(local) = rows.remove(&local_id) else {
+                    let version = self.tick();
+                    self.uids.remove(&(kind, local_id));
+                    shadow.local_id = None;
+                    shadow.deleted_at = Some(version.ts);
+                    shadow.fields.clear();
+                    shadow.versions.clear();
+                    self.save(kind, &shadow)?;
+                    records.push(Record { deleted: true, ..shadow.record(kind, version) });
+                    continue;
+                };
+
+                let local = self.to_shared(kind, local);
+                let changed: Vec<String> = local.iter()
+                    .filter(|(name, value)| shadow.fields.get(*name) != Some(value))
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                if changed.is_empty() {
+                    continue;
+                }
+
+                let version = self.tick();
+                for name in changed {
+                    let base = shadow.versions.get(&name).map(|v| v.version.clone());
+                    shadow.versions.insert(name.clone(), FieldVersion { version: version.clone(), base });
+                    shadow.fields.insert(name.clone(), local[&name].clone());
+                }
+                self.save(kind, &shadow)?;
+                records.push(shadow.record(kind, version));
+            }
+        }
+        Ok(records)
+    }
+
+    /// 所有实体当前状态的记录，用于向新的同步目标重新发布
+    pub fn snapshot(&mut self) -> Result<Vec<Record>> {
+        let mut records = Vec::new();
+        for kind in Kind::ALL {
+            for shadow in self.load_all(kind, None)? {
+                if let Some(deleted_at) = shadow.deleted_at {
+                    let version = Version { ts: deleted_at, device: self.device.clone() };
+                    records.push(Record { deleted: true, ..shadow.record(kind, version) });
+                } else if shadow.local_id.is_some() {
+                    let version = match shadow.versions.values().map(|v| &v.version).max() {
+                        Some(version) => version.clone(),
+                        None => self.tick(),
+                    };
+                    records.push(shadow.record(kind, version));
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// 沿别名找到本地使用的 uid
+    fn canonical(&self, kind: Kind, uid: &str) -> Result<String> {
+        let mut uid = uid.to_string();
+        for _ in 0..MAX_ALIAS_DEPTH {
+            match self.load(kind, &uid)?.and_then(|shadow| shadow.alias_of) {
+                Some(target) => uid = target,
+                None => break,
+            }
+        }
+        Ok(uid)
+    }
+
+    fn resolve(&self, kind: Kind, uid: &str) -> Result<Resolved> {
+        let uid = self.canonical(kind, uid)?;
+        Ok(match self.load(kind, &uid)? {
+            Some(Shadow { local_id: Some(id), .. }) => Resolved::Local(id),
+            Some(Shadow { deleted_at: Some(_), .. }) => Resolved::Deleted,
+            _ => Resolved::Unknown,
+        })
+    }
+
+    /// 把记录字段中的引用换成本地使用的 uid
+    fn canonicalize(&self, kind: Kind, mut fields: Fields) -> Result<Fields> {
+        for (name, target, many) in entities::references(kind) {
+            match fields.get_mut(*name) {
+                Some(Value::String(uid)) => *uid = self.canonical(*target, uid)?,
+                Some(Value::Array(uids)) if *many => {
+                    let mut canonical = Vec::new();
+                    for uid in uids.iter().filter_map(Value::as_str) {
+                        let uid = self.canonical(*target, uid)?;
+                        if !canonical.contains(&uid) {
+                            canonical.push(uid);
+                        }
+                    }
+                    canonical.sort();
+                    *uids = canonical.into_iter().map(Value::from).collect();
+                }
+                _ => {}
+            }
+        }
+        Ok(fields)
+    }
+
+    /// 把同步字段中的引用换成本地 id
+    fn localize(&self, kind: Kind, fields: &Fields) -> Result<Localized> {
+        let mut local = fields.clone();
+        let mut shared = fields.clone();
+        for (name, target, many) in entities::references(kind) {
+            let value = fields.get(*name).cloned().unwrap_or(Value::Null);
+            if *many {
+                let (mut ids, mut uids) = (Vec::new(), Vec::new());
+                for uid in value.as_array().into_iter().flatten().filter_map(Value::as_str) {
+                    match self.resolve(*target, uid)? {
+                        Resolved::Local(id) => {
+                            ids.push(id);
+                            uids.push(uid.to_string());
+                        }
+                        Resolved::Deleted => {}
+                        Resolved::Unknown => return Ok(Localized::Deferred),
+                    }
+                }
+                local.insert(name.to_string(), Value::from(ids));
+                shared.insert(name.to_string(), Value::from(uids));
+            } else if let Some(uid) = value.as_str() {
+                match self.resolve(*target, uid)? {
+                    Resolved::Local(id) => {
+                        local.insert(name.to_string(), Value::from(id));
+                    }
+                    Resolved::Deleted if kind == Kind::Step => return Ok(Localized::Orphaned),
+                    Resolved::Deleted => {
+                        local.insert(name.to_string(), Value::Null);
+                        shared.insert(name.to_string(), Value::Null);
+                    }
+                    Resolved::Unknown => return Ok(Localized::Deferred),
+                }
+            } else if kind == Kind::Step {
+                return Ok(Localized::Orphaned);
+            } else {
+                local.insert(name.to_string(), Value::Null);
+            }
+        }
+        Ok(Localized::Ready(local, shared))
+    }
+
+    /// 应用其他设备的一条记录
+    pub fn apply(&mut self, record: &Record) -> Result<Applied> {
+        let kind = record.kind;
+        self.observe(record.version.ts);
+        for version in record.versions.values() {
+            self.observe(version.version.ts);
+        }
+
+        let uid = self.canonical(kind, &record.uid)?;
+        let existing = self.load(kind, &uid)?;
+        // 删除优先：已删除的实体忽略之后的修改
+        if existing.as_ref().is_some_and(|shadow| shadow.deleted_at.is_some()) {
+            return Ok(Applied::Ignored);
+        }
+        if record.deleted {
+            return self.apply_delete(kind, uid, existing, &record.version);
+        }
+
+        let fields = self.canonicalize(kind, record.fields.clone())?;
+        match existing {
+            Some(shadow) if shadow.local_id.is_some() => self.apply_update(kind, shadow, record, fields),
+            _ => self.apply_new(kind, uid, record, fields),
+        }
+    }
+
+    fn apply_delete(&mut self, kind: Kind, uid: String, existing: Option<Shadow>, version: &Version) -> Result<Applied> {
+        if let Some(local_id) = existing.and_then(|shadow| shadow.local_id) {
+            entities::delete(self.conn, kind, local_id)?;
+            self.uids.remove(&(kind, local_id));
+            self.detach(kind, &uid)?;
+            self.report.deleted += 1;
+        }
+        let mut tombstone = Shadow::new(uid);
+        tombstone.deleted_at = Some(version.ts);
+        self.save(kind, &tombstone)?;
+        Ok(Applied::Done)
+    }
+
+    /// 删除实体后，从引用它的影子副本中去掉引用，与本地数据保持一致
+    fn detach(&mut self, kind: Kind, uid: &str) -> Result<()> {
+        for owner in Kind::ALL {
+            for (name, target, many) in entities::references(owner) {
+                if *target != kind {
+                    continue;
+                }
+                for mut shadow in self.load_all(owner, Some(uid))? {
+                    let Some(local_id) = shadow.local_id else { continue };
+                    let Some(value) = shadow.fields.get_mut(*name) else { continue };
+                    if *many {
+                        let Some(uids) = value.as_array_mut() else { continue };
+                        uids.retain(|u| u.as_str() != Some(uid));
+                    } else if value.as_str() == Some(uid) {
+                        if owner == Kind::Step {
+                            // 步骤已随任务一起删除
+                            self.uids.remove(&(owner, local_id));
+                            shadow = Shadow { deleted_at: Some(now_timestamp()), ..Shadow::new(shadow.uid) };
+                        } else {
+                            *value = Value::Null;
+                        }
+                    } else {
+                        continue;
+                    }
+                    self.save(owner, &shadow)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_new(&mut self, kind: Kind, uid: String, record: &Record, fields: Fields) -> Result<Applied> {
+        // 两台设备各自创建了同名标签：合并为本地已有的标签
+        if kind == Kind::Tag {
+            let name = fields.get("name").and_then(Value::as_str).unwrap_or_default();
+            if let Some(id) = entities::find_tag(self.conn, name, None)? {
+                if let Some(target) = self.uids.get(&(kind, id)).cloned() {
+                    if let Some(existing) = self.load(kind, &target)? {
+                        let mut alias = Shadow::new(uid);
+                        alias.alias_of = Some(target);
+                        self.save(kind, &alias)?;
+                        return self.apply_update(kind, existing, record, fields);
+                    }
+                }
+            }
+        }
+
+        let (local, shared) = match self.localize(kind, &fields)? {
+            Localized::Ready(local, shared) => (local, shared),
+            Localized::Deferred => return Ok(Applied::Deferred),
+            Localized::Orphaned => return Ok(Applied::Ignored),
+        };
+        let local_id = entities::insert(self.conn, kind, &uid, &local)?;
+
+        let mut shadow = Shadow::new(uid);
+        shadow.local_id = Some(local_id);
+        shadow.fields = shared;
+        shadow.versions = record.versions.clone();
+        self.save(kind, &shadow)?;
+        self.uids.insert((kind, local_id), shadow.uid);
+        self.report.created += 1;
+        Ok(Applied::Done)
+    }
+
+    fn apply_update(&mut self, kind: Kind, mut shadow: Shadow, record: &Record, fields: Fields) -> Result<Applied> {
+        let Some(local_id) = shadow.local_id else { return Ok(Applied::Ignored) };
+
+        let mut merged = shadow.fields.clone();
+        let mut versions = shadow.versions.clone();
+        let mut conflicts = Vec::new();
+        let mut local_changed = false;
+
+        for (name, remote) in &record.versions {
+            let Some(value) = fields.get(name) else { continue };
+            let local = shadow.versions.get(name);
+            if local.is_some_and(|local| local.version == remote.version) {
+                continue;
+            }
+            let current = shadow.fields.get(name).cloned().unwrap_or(Value::Null);
+            let mut remote_wins = local.is_none_or(|local| remote.version > local.version);
+
+            if remote_wins && *value != current {
+                if let Some(reason) = self.rejects(kind, local_id, name, value)? {
+                    tracing::info!("Keeping local {} of {} {}: {}", name, kind.as_str(), shadow.uid, reason);
+                    remote_wins = false;
+                    conflicts.push(Conflict {
+                        field: name.clone(),
+                        local_value: current.clone(),
+                        remote_value: value.clone(),
+                        resolution: ConflictResolution::Local,
+                        remote_device: remote.version.device.clone(),
+                    });
+                    continue;
+                }
+            }
+
+            // 双方都不是在对方的基础上修改的：并发修改
+            let concurrent = local.is_some_and(|local| {
+                remote.base.as_ref() != Some(&local.version) && local.base.as_ref() != Some(&remote.version)
+            });
+            if concurrent && *value != current {
+                conflicts.push(Conflict {
+                    field: name.clone(),
+                    local_value: current.clone(),
+                    remote_value: value.clone(),
+                    resolution: if remote_wins { ConflictResolution::Remote } else { ConflictResolution::Local },
+                    remote_device: remote.version.device.clone(),
+                });
+            }
+
+            if remote_wins {
+                local_changed |= *value != current;
+                merged.insert(name.clone(), value.clone());
+                versions.insert(name.clone(), remote.clone());
+            }
+        }
+
+        if local_changed {
+            let (local, shared) = match self.localize(kind, &merged)? {
+                Localized::Ready(local, shared) => (local, shared),
+                Localized::Deferred => return Ok(Applied::Deferred),
+                Localized::Orphaned => return Ok(Applied::Ignored),
+            };
+            entities::update(self.conn, kind, local_id, &local)?;
+            merged = shared;
+            self.report.updated += 1;
+        }
+        if merged != shadow.fields || versions != shadow.versions {
+            shadow.fields = merged;
+            shadow.versions = versions;
+            self.save(kind, &shadow)?;
+        }
+        for conflict in conflicts {
+            self.record_conflict(kind, &shadow, conflict)?;
+        }
+        Ok(Applied::Done)
+    }
+
+    /// 其他设备的值不能用于本地时返回原因（标签重名、父级形成循环）
+    fn rejects(&self, kind: Kind, local_id: i64, name: &str, value: &Value) -> Result<Option<&'static str>> {
+        if kind == Kind::Tag && name == "name" {
+            let name = value.as_str().unwrap_or_default();
+            if entities::find_tag(self.conn, name, Some(local_id))?.is_some() {
+                return Ok(Some("another tag has this name"));
+            }
+        }
+        if matches!(kind, Kind::Group | Kind::Tag) && name == "parent" {
+            if let Some(uid) = value.as_str() {
+                if let Resolved::Local(parent) = self.resolve(kind, uid)? {
+                    if entities::creates_cycle(self.conn, kind, local_id, parent)? {
+                        return Ok(Some("the parent would create a cycle"));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// 记录冲突；同一远程值重复出现时只记录一次
+    fn record_conflict(&mut self, kind: Kind, shadow: &Shadow, conflict: Conflict) -> Result<()> {
+        let remote_value = serde_json::to_string(&conflict.remote_value)?;
+        let inserted = self.conn.execute(
+            "INSERT INTO sync_conflicts
+                 (entity, uid, local_id, field, local_value, remote_value, resolution, remote_device, detected_at)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM sync_conflicts
+                 WHERE entity = ?1 AND uid = ?2 AND field = ?4 AND remote_value = ?6 AND remote_device = ?8
+             )",
+            params![
+                kind.as_str(),
+                shadow.uid,
+                shadow.local_id,
+                conflict.field,
+                serde_json::to_string(&conflict.local_value)?,
+                remote_value,
+                conflict.resolution.as_str(),
+                conflict.remote_device,
+                now_timestamp(),
+            ],
+        )
+        .context("Failed to record sync conflict")?;
+        self.report.conflicts += inserted;
+        Ok(())
+    }
+}
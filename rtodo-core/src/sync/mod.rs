@@ -0,0 +1,268 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 设备间同步
+//!
+//! 设备之间通过共享的存储（Syncthing、Dropbox 同步的目录、NAS 共享目录等）交换变更日志，
+//! 不需要复制数据库文件。一次同步分为三步：
+//! 1. 读取其他设备新写入的日志段；
+//! 2. 在一个写事务中对比本地数据和上次同步的状态，把本地修改生成本设备的新日志段，
+//!    再逐字段按最后修改者优先合并其他设备的记录（删除优先于修改），并发修改同一字段时记录冲突；
+//! 3. 把新日志段写入存储。
+//!
+//! 同步的数据包括任务组、标签、任务（含标签关联）和步骤；附件文件和设置不同步。
+//! 日志以明文保存，因此加密的数据库不能同步（返回 [`Error::SyncEncrypted`]）。
+
+mod entities;
+pub mod log;
+mod merge;
+
+#[cfg(test)]
+mod tests;
+
+pub use log::fingerprint;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction, TransactionBehavior};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::error::{Context, Error, Result};
+use crate::models::{ConflictResolution, SyncConflict, SyncReport};
+use crate::storage::Store;
+use crate::utils::{new_uuid, now_timestamp};
+use log::{Fetched, Record};
+use merge::{Applied, Merger};
+
+const DEVICE_ID_KEY: &str = "device_id";
+const CLOCK_KEY: &str = "clock";
+const NEXT_SEGMENT_KEY: &str = "next_segment";
+const LOCATION_KEY: &str = "location";
+/// 同步目标变化后，下次同步需要重新发布全部数据
+const REPUBLISH_KEY: &str = "republish";
+
+fn get_state(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let value = conn.query_row("SELECT value FROM sync_state WHERE key = ?", params![key], |row| row.get(0))
+        .optional()
+        .context("Failed to read sync state")?;
+    Ok(value)
+}
+
+fn set_state(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute("INSERT OR REPLACE INTO sync_state (key, value) VALUES (?1, ?2)", params![key, value])
+        .context("Failed to save sync state")?;
+    Ok(())
+}
+
+/// 本设备的同步 id，从未同步过时为空
+pub fn device_id(conn: &Connection) -> Result<Option<String>> {
+    get_state(conn, DEVICE_ID_KEY)
+}
+
+/// 与存储同步一次；加密的数据库拒绝同步
+pub async fn sync(db: &Database, store: Arc<dyn Store>) -> Result<SyncReport> {
+    if db.key().is_some() {
+        return Err(Error::SyncEncrypted);
+    }
+
+    let location = store.location();
+    let (device, cursors) = db.write(move |conn| prepare(conn, &location)).await
+        .context("Failed to prepare sync")?;
+
+    let fetch_store = store.clone();
+    let fetch_device = device.clone();
+    let fetched = tokio::task::spawn_blocking(move || log::fetch(&*fetch_store, &fetch_device, &cursors))
+        .await?
+        .context("Failed to read sync folder")?;
+
+    let merge_device = device.clone();
+    let (report, outbox) = db.write(move |conn| merge(conn, &merge_device, fetched)).await
+        .context("Failed to merge changes")?;
+
+    // 写入失败的日志段留在 sync_outbox 中，下次同步时重试
+    let (uploaded, error) = tokio::task::spawn_blocking(move || {
+        let mut uploaded = Vec::new();
+        for (seq, body) in outbox {
+            if let Err(e) = store.write(&log::segment_path(&device, seq), body.as_bytes()) {
+                return (uploaded, Some(e));
+            }
+            uploaded.push(seq);
+        }
+        (uploaded, None)
+    })
+    .await?;
+
+    if !uploaded.is_empty() {
+        db.write(move |conn| {
+            for seq in uploaded {
+                conn.execute("DELETE FROM sync_outbox WHERE segment = ?", params![seq])?;
+            }
+            Ok::<_, Error>(())
+        }).await?;
+    }
+    match error {
+        Some(e) => Err(e).context("Failed to write sync log"),
+        None => Ok(report),
+    }
+}
+
+/// 确定设备 id，同步目标变化时重置游标；返回设备 id 和各设备的游标
+fn prepare(conn: &Connection, location: &str) -> Result<(String, HashMap<String, i64>)> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+
+    let device = match get_state(&tx, DEVICE_ID_KEY)? {
+        Some(device) => device,
+        None => {
+            let device = new_uuid();
+            set_state(&tx, DEVICE_ID_KEY, &device)?;
+            device
+        }
+    };
+
+    if let Some(previous) = get_state(&tx, LOCATION_KEY)? {
+        if previous != location {
+            tracing::info!("Sync target changed from {} to {}, publishing all data again", previous, location);
+            tx.execute("DELETE FROM sync_cursors", [])?;
+            tx.execute("DELETE FROM sync_outbox", [])?;
+            set_state(&tx, REPUBLISH_KEY, "1")?;
+        }
+    }
+    set_state(&tx, LOCATION_KEY, location)?;
+
+    let mut stmt = tx.prepare("SELECT device_id, segment FROM sync_cursors")?;
+    let cursors = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<HashMap<_, _>, _>>()?;
+    drop(stmt);
+
+    tx.commit()?;
+    Ok((device, cursors))
+}
+
+/// 生成本地修改的日志段并合并其他设备的记录；返回结果和所有待写入的日志段
+fn merge(conn: &Connection, device: &str, fetched: Fetched) -> Result<(SyncReport, Vec<(i64, String)>)> {
+    let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+    let mut merger = Merger::new(&tx, device)?;
+
+    // 先记录本地修改，合并时才能判断是否与其他设备的修改并发
+    merger.assign_uids()?;
+    let mut outgoing = merger.scan()?;
+    if get_state(&tx, REPUBLISH_KEY)?.is_some() {
+        outgoing = merger.snapshot()?;
+        tx.execute("DELETE FROM sync_state WHERE key = ?", params![REPUBLISH_KEY])?;
+    }
+
+    let mut records: Vec<Record> = Vec::new();
+    {
+        let mut stmt = tx.prepare("SELECT record FROM sync_pending ORDER BY id")?;
+        let pending = stmt.query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        for record in pending {
+            match serde_json::from_str(&record) {
+                Ok(record) => records.push(record),
+                Err(e) => tracing::warn!("Dropping invalid pending sync record: {}", e),
+            }
+        }
+    }
+    tx.execute("DELETE FROM sync_pending", [])?;
+
+    let mut cursors: HashMap<String, i64> = HashMap::new();
+    for segment in fetched.segments {
+        merger.report.pulled += segment.records.len();
+        let cursor = cursors.entry(segment.device).or_default();
+        *cursor = (*cursor).max(segment.seq);
+        records.extend(segment.records);
+    }
+
+    // 按依赖顺序应用；引用同一批中稍后出现的记录时重试，仍无法应用的留到下次
+    records.sort_by_key(|record| record.kind);
+    loop {
+        let before = records.len();
+        let mut deferred = Vec::new();
+        for record in records {
+            if merger.apply(&record)? == Applied::Deferred {
+                deferred.push(record);
+            }
+        }
+        records = deferred;
+        if records.is_empty() || records.len() == before {
+            break;
+        }
+    }
+    merger.report.deferred = records.len();
+    for record in &records {
+        tx.execute("INSERT INTO sync_pending (record) VALUES (?)", params![serde_json::to_string(record)?])?;
+    }
+
+    for (device, segment) in cursors {
+        tx.execute(
+            "INSERT OR REPLACE INTO sync_cursors (device_id, segment) VALUES (?1, ?2)",
+            params![device, segment],
+        )?;
+    }
+
+    if !outgoing.is_empty() {
+        let next = get_state(&tx, NEXT_SEGMENT_KEY)?
+            .and_then(|seq| seq.parse::<i64>().ok())
+            .unwrap_or(1)
+            // 从备份恢复的数据库可能落后于已写入存储的日志段，不能覆盖它们
+            .max(fetched.own_latest + 1);
+        tx.execute(
+            "INSERT INTO sync_outbox (segment, body) VALUES (?1, ?2)",
+            params![next, log::encode(device, next, &outgoing)?],
+        )?;
+        set_state(&tx, NEXT_SEGMENT_KEY, &(next + 1).to_string())?;
+        merger.report.pushed = outgoing.len();
+    }
+
+    let mut report = merger.finish()?;
+    report.finished_at = now_timestamp();
+
+    let mut stmt = tx.prepare("SELECT segment, body FROM sync_outbox ORDER BY segment")?;
+    let outbox = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+
+    tx.commit()?;
+    Ok((report, outbox))
+}
+
+/// 冲突记录，最新的在前
+pub fn list_conflicts(conn: &Connection) -> Result<Vec<SyncConflict>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, entity, uid, local_id, field, local_value, remote_value, resolution, remote_device, detected_at
+         FROM sync_conflicts
+         ORDER BY detected_at DESC, id DESC"
+    )
+    .context("Failed to prepare sync conflicts query")?;
+
+    let conflicts = stmt.query_map([], |row| {
+        let value = |idx: usize| -> rusqlite::Result<serde_json::Value> {
+            let text: Option<String> = row.get(idx)?;
+            Ok(text.and_then(|t| serde_json::from_str(&t).ok()).unwrap_or_default())
+        };
+        Ok(SyncConflict {
+            id: row.get(0)?,
+            entity: row.get(1)?,
+            uid: row.get(2)?,
+            local_id: row.get(3)?,
+            field: row.get(4)?,
+            local_value: value(5)?,
+            remote_value: value(6)?,
+            resolution: ConflictResolution::from_str_lossy(&row.get::<_, String>(7)?),
+            remote_device: row.get(8)?,
+            detected_at: row.get(9)?,
+        })
+    })
+    .context("Failed to execute sync conflicts query")?
+    .collect::<Result<Vec<_>, _>>()
+    .context("Failed to parse sync conflicts")?;
+
+    Ok(conflicts)
+}
+
+/// 清空冲突记录，返回清除的条数
+pub fn clear_conflicts(conn: &Connection) -> Result<usize> {
+    let cleared = conn.execute("DELETE FROM sync_conflicts", [])
+        .context("Failed to clear sync conflicts")?;
+    Ok(cleared)
+}
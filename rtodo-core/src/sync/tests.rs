@@ -0,0 +1,264 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::database::repositories::{GroupRepository, StepRepository, TagRepository, TodoRepository};
+use crate::models::Todo;
+use crate::storage::FolderStore;
+use crate::test_support::temp_dir;
+use std::path::PathBuf;
+
+fn device() -> (Database, PathBuf) {
+    let dir = temp_dir("device");
+    (Database::open(&dir.join("rtodo.db"), None).unwrap(), dir)
+}
+
+fn folder() -> Arc<dyn Store> {
+    Arc::new(FolderStore::new(temp_dir("folder")).unwrap())
+}
+
+async fn todos(db: &Database) -> Vec<Todo> {
+    let mut todos = db.read(|conn| {
+        TodoRepository::list_with_filters(conn, None, None, None, None, None, None, None)
+    }).await.unwrap();
+    todos.sort_by(|a, b| a.title.cmp(&b.title));
+    todos
+}
+
+async fn create_todo(db: &Database, title: &str) -> i64 {
+    let title = title.to_string();
+    db.write(move |conn| TodoRepository::create(conn, &title, None, None, None, None, 0, None))
+        .await
+        .unwrap()
+        .id
+}
+
+async fn set_title(db: &Database, id: i64, title: &str) {
+    let title = title.to_string();
+    db.write(move |conn| {
+        TodoRepository::update(conn, id, Some(&title), None, None, None, None, None, None, None, None)
+    }).await.unwrap();
+}
+
+/// 两台设备轮流同步，直到没有新的变更
+async fn settle(a: &Database, b: &Database, store: &Arc<dyn Store>) {
+    for _ in 0..3 {
+        sync(a, store.clone()).await.unwrap();
+        sync(b, store.clone()).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn changes_travel_between_devices() {
+    let store = folder();
+    let (a, dir_a) = device();
+    let (b, dir_b) = device();
+
+    a.write(|conn| {
+        let group = GroupRepository::create(conn, "Work", None, None, Some("#409EFF"))?;
+        let tag = TagRepository::create(conn, "urgent", "#F56C6C", None)?;
+        let todo = TodoRepository::create(conn, "Send invoice", Some("March"), Some(group.id), None, None, 2, Some(vec![tag.id]))?;
+        StepRepository::create(conn, todo.id, "Print it")?;
+        Ok::<_, Error>(())
+    }).await.unwrap();
+
+    let report = sync(&a, store.clone()).await.unwrap();
+    assert_eq!(report.pushed, 4);
+    let report = sync(&b, store.clone()).await.unwrap();
+    assert_eq!(report.pulled, 4);
+    assert_eq!(report.created, 4);
+    assert_eq!(report.deferred, 0);
+
+    let todo = todos(&b).await.remove(0);
+    assert_eq!(todo.title, "Send invoice");
+    assert_eq!(todo.description.as_deref(), Some("March"));
+    assert_eq!(todo.priority, 2);
+    let tags: Vec<_> = todo.tags.iter().flatten().map(|t| t.name.as_str()).collect();
+    assert_eq!(tags, ["urgent"]);
+    let steps = b.read(move |conn| StepRepository::list_by_todo(conn, todo.id)).await.unwrap();
+    assert_eq!(steps.iter().map(|s| s.title.as_str()).collect::<Vec<_>>(), ["Print it"]);
+    let groups = b.read(GroupRepository::list).await.unwrap();
+    assert_eq!(todo.group_id, Some(groups[0].id));
+
+    // 同一实体在两台设备上的 uid 相同
+    assert_eq!(todo.uid, todos(&a).await[0].uid);
+    assert_eq!(groups[0].uid, a.read(GroupRepository::list).await.unwrap()[0].uid);
+
+    // 修改传回第一台设备，之后没有多余的记录来回传递
+    set_title(&b, todo.id, "Send invoice now").await;
+    sync(&b, store.clone()).await.unwrap();
+    let report = sync(&a, store.clone()).await.unwrap();
+    assert_eq!(report.updated, 1);
+    assert_eq!(todos(&a).await[0].title, "Send invoice now");
+    assert_eq!(sync(&a, store.clone()).await.unwrap().pushed, 0);
+    assert_eq!(sync(&b, store.clone()).await.unwrap().pushed, 0);
+
+    for dir in [dir_a, dir_b] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[tokio::test]
+async fn concurrent_edits_merge_field_by_field() {
+    let store = folder();
+    let (a, dir_a) = device();
+    let (b, dir_b) = device();
+    let id_a = create_todo(&a, "Plan trip").await;
+    settle(&a, &b, &store).await;
+    let id_b = todos(&b).await[0].id;
+
+    // 不同字段：两边的修改都保留，不算冲突
+    set_title(&a, id_a, "Plan summer trip").await;
+    b.write(move |conn| TodoRepository::update_status(conn, id_b, 2)).await.unwrap();
+    settle(&a, &b, &store).await;
+    for db in [&a, &b] {
+        let todo = todos(db).await.remove(0);
+        assert_eq!(todo.title, "Plan summer trip");
+        assert_eq!(todo.status.as_i32(), 2);
+        assert!(db.read(list_conflicts).await.unwrap().is_empty());
+    }
+
+    // 同一字段：后同步的修改获胜，两边都记录冲突
+    set_title(&a, id_a, "Trip to the coast").await;
+    set_title(&b, id_b, "Trip to the mountains").await;
+    sync(&a, store.clone()).await.unwrap();
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let report = sync(&b, store.clone()).await.unwrap();
+    assert_eq!(report.conflicts, 1);
+    settle(&a, &b, &store).await;
+
+    for (db, resolution) in [(&a, ConflictResolution::Remote), (&b, ConflictResolution::Local)] {
+        assert_eq!(todos(db).await[0].title, "Trip to the mountains");
+        let conflicts = db.read(list_conflicts).await.unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].field, "title");
+        assert_eq!(conflicts[0].resolution, resolution);
+    }
+    let conflict = &a.read(list_conflicts).await.unwrap()[0];
+    assert_eq!(conflict.local_value, "Trip to the coast");
+    assert_eq!(conflict.remote_value, "Trip to the mountains");
+    assert_eq!(a.write(clear_conflicts).await.unwrap(), 1);
+
+    for dir in [dir_a, dir_b] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[tokio::test]
+async fn deletes_win_over_edits() {
+    let store = folder();
+    let (a, dir_a) = device();
+    let (b, dir_b) = device();
+    let id_a = create_todo(&a, "Old task").await;
+    create_todo(&a, "Kept task").await;
+    a.write(move |conn| StepRepository::create(conn, id_a, "Step")).await.unwrap();
+    settle(&a, &b, &store).await;
+    let id_b = todos(&b).await.iter().find(|t| t.title == "Old task").unwrap().id;
+
+    a.write(move |conn| TodoRepository::delete(conn, id_a)).await.unwrap();
+    set_title(&b, id_b, "Old task, edited").await;
+    settle(&a, &b, &store).await;
+
+    for db in [&a, &b] {
+        let titles: Vec<_> = todos(db).await.into_iter().map(|t| t.title).collect();
+        assert_eq!(titles, ["Kept task"]);
+        let steps: i64 = db.read(|conn| {
+            conn.query_row("SELECT COUNT(*) FROM todo_steps", [], |row| row.get(0)).map_err(Error::from)
+        }).await.unwrap();
+        assert_eq!(steps, 0);
+    }
+
+    for dir in [dir_a, dir_b] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[tokio::test]
+async fn same_named_tags_are_merged() {
+    let store = folder();
+    let (a, dir_a) = device();
+    let (b, dir_b) = device();
+    for (db, name, title) in [(&a, "Work", "From A"), (&b, "work", "From B")] {
+        db.write(move |conn| {
+            let tag = TagRepository::create(conn, name, "#409EFF", None)?;
+            TodoRepository::create(conn, title, None, None, None, None, 0, Some(vec![tag.id]))
+        }).await.unwrap();
+    }
+    settle(&a, &b, &store).await;
+
+    for db in [&a, &b] {
+        assert_eq!(db.read(TagRepository::list).await.unwrap().len(), 1);
+        let todos = todos(db).await;
+        assert_eq!(todos.len(), 2);
+        assert!(todos.iter().all(|todo| todo.tags.as_ref().is_some_and(|tags| tags.len() == 1)));
+    }
+    assert_eq!(sync(&a, store.clone()).await.unwrap().pushed, 0);
+    assert_eq!(sync(&b, store.clone()).await.unwrap().pushed, 0);
+
+    for dir in [dir_a, dir_b] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[tokio::test]
+async fn incomplete_segments_are_read_later() {
+    let store = folder();
+    let (a, dir_a) = device();
+    let (b, dir_b) = device();
+    create_todo(&a, "First").await;
+    sync(&a, store.clone()).await.unwrap();
+
+    // 模拟同步工具只传输了一半的文件
+    let device = a.read(device_id).await.unwrap().unwrap();
+    let path = log::segment_path(&device, 1);
+    let full = store.read(&path).unwrap().unwrap();
+    store.write(&path, &full[..full.len() - 10]).unwrap();
+    let report = sync(&b, store.clone()).await.unwrap();
+    assert_eq!(report.pulled, 0);
+    assert!(todos(&b).await.is_empty());
+
+    store.write(&path, &full).unwrap();
+    let report = sync(&b, store.clone()).await.unwrap();
+    assert_eq!(report.created, 1);
+    assert_ne!(fingerprint(&*store).unwrap(), "");
+
+    for dir in [dir_a, dir_b] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
+
+#[cfg(feature = "encryption")]
+#[tokio::test]
+async fn encrypted_databases_are_not_synced() {
+    use crate::encryption::EncryptionConfig;
+
+    /// 目录下所有文件的内容
+    fn read_all(dir: &std::path::Path) -> Vec<u8> {
+        let mut contents = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                contents.extend(read_all(&path));
+            } else {
+                contents.extend(std::fs::read(&path).unwrap());
+            }
+        }
+        contents
+    }
+
+    let folder_dir = temp_dir("folder");
+    let store: Arc<dyn Store> = Arc::new(FolderStore::new(folder_dir.clone()).unwrap());
+    let dir = temp_dir("device");
+    let (_, key) = EncryptionConfig::create("correct horse").unwrap();
+    let db = Database::open_with_key(&dir.join("rtodo.db"), None, Some(Arc::new(key))).unwrap();
+    create_todo(&db, "Secret plan").await;
+
+    let error = sync(&db, store.clone()).await.unwrap_err();
+    assert!(matches!(error.root(), Error::SyncEncrypted));
+    assert!(!read_all(&folder_dir).windows(b"Secret plan".len()).any(|w| w == b"Secret plan"));
+    assert_eq!(db.read(device_id).await.unwrap(), None);
+
+    for dir in [dir, folder_dir] {
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,23 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 各模块测试共用的夹具，保证所有测试使用同样的迁移和 pragma
+
+use crate::database::migrations;
+use rusqlite::Connection;
+use std::path::PathBuf;
+
+/// 迁移到最新 schema 的内存数据库
+pub(crate) fn memory_db() -> Connection {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.pragma_update(None, "foreign_keys", "ON").unwrap();
+    migrations::run_migrations(&conn, None).unwrap();
+    conn
+}
+
+/// 在系统临时目录下创建一个唯一的空目录，`name` 用于区分来源
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rtodo-{}-{}", name, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
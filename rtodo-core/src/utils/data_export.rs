@@ -4,97 +4,35 @@
 //! 数据导出工具函数
 //! 处理 CSV 和 ZIP 文件的读写操作
 
-use crate::models::{TaskGroup, Tag, Todo, TodoStep, Attachment};
+use crate::encryption::{self, EncryptionKey};
+use crate::models::{TaskGroup, Tag, Todo, TodoStatus, TodoStep, Attachment, ExportData};
+use std::collections::HashMap;
 use std::io::{Cursor, Read, Seek, Write};
 use std::path::Path;
-use anyhow::{Result, Context};
-use serde::Deserialize;
-
-/// CSV 解析辅助结构
-
-#[derive(Debug, Deserialize)]
-struct TaskGroupCsvRecord {
-    id: String,
-    name: String,
-    #[serde(default)]
-    parent_id: String,
-    #[serde(default)]
-    icon: String,
-    #[serde(default)]
-    color: String,
-    sort_order: String,
-    created_at: String,
-    updated_at: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct TagCsvRecord {
-    id: String,
-    name: String,
-    color: String,
-    created_at: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct TodoCsvRecord {
-    id: String,
-    title: String,
-    #[serde(default)]
-    description: String,
-    status: String,
-    priority: String,
-    #[serde(default)]
-    group_id: String,
-    #[serde(default)]
-    assignee: String,
-    #[serde(default)]
-    start_date: String,
-    #[serde(default)]
-    due_date: String,
-    #[serde(default)]
-    completed_at: String,
-    created_at: String,
-    updated_at: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct TodoTagCsvRecord {
-    todo_id: String,
-    tag_id: String,
-}
+use crate::error::{Context, Error, Result};
 
-#[derive(Debug, Deserialize)]
-struct TodoStepCsvRecord {
-    id: String,
-    todo_id: String,
-    title: String,
-    is_completed: String,
-    sort_order: String,
-    created_at: String,
+/// 任务-标签关联 CSV 记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TodoTagCsvRecord {
+    pub todo_id: i64,
+    pub tag_id: i64,
 }
 
-#[derive(Debug, Deserialize)]
-struct AttachmentCsvRecord {
-    id: String,
-    todo_id: String,
-    name: String,
-    file_path: String,
-    file_size: String,
-    #[serde(default)]
-    mime_type: String,
-    created_at: String,
+/// 读取记录中的 uid 列（该列为后续版本新增，旧的导出文件中不存在，返回空字符串）
+fn parse_uid(record: &csv::StringRecord, index: usize) -> String {
+    record.get(index).unwrap_or("").to_string()
 }
 
 /// 解析任务组 CSV 记录
-pub fn parse_task_group_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, String, Option<i64>, Option<String>, Option<String>, i32, i64, i64)> {
+pub fn parse_task_group_csv(record: &csv::StringRecord) -> Result<TaskGroup> {
     let id: i64 = record
         .get(0)
-        .ok_or_else(|| anyhow::anyhow!("Missing id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing id field".to_string()))?
         .parse()
         .context("Failed to parse task group id")?;
     let name: String = record
         .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Missing name field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing name field".to_string()))?
         .to_string();
     let parent_id: Option<i64> = record
         .get(2)
@@ -112,64 +50,69 @@ pub fn parse_task_group_csv(record: &csv::StringRecord) -> anyhow::Result<(i64,
         .unwrap_or(0);
     let created_at: i64 = record
         .get(6)
-        .ok_or_else(|| anyhow::anyhow!("Missing created_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing created_at field".to_string()))?
         .parse()
         .context("Failed to parse task group created_at")?;
     let updated_at: i64 = record
         .get(7)
-        .ok_or_else(|| anyhow::anyhow!("Missing updated_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing updated_at field".to_string()))?
         .parse()
         .context("Failed to parse task group updated_at")?;
 
-    Ok((id, name, parent_id, icon, color, sort_order, created_at, updated_at))
+    Ok(TaskGroup { id, uid: parse_uid(record, 8), name, parent_id, icon, color, sort_order, created_at, updated_at })
 }
 
 /// 解析标签 CSV 记录
-pub fn parse_tag_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, String, String, i64)> {
+pub fn parse_tag_csv(record: &csv::StringRecord) -> Result<Tag> {
     let id: i64 = record
         .get(0)
-        .ok_or_else(|| anyhow::anyhow!("Missing id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing id field".to_string()))?
         .parse()
         .context("Failed to parse tag id")?;
     let name: String = record
         .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Missing name field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing name field".to_string()))?
         .to_string();
     let color: String = record
         .get(2)
-        .ok_or_else(|| anyhow::anyhow!("Missing color field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing color field".to_string()))?
         .to_string();
     let created_at: i64 = record
         .get(3)
-        .ok_or_else(|| anyhow::anyhow!("Missing created_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing created_at field".to_string()))?
         .parse()
         .context("Failed to parse tag created_at")?;
+    // parent_id 列为后续版本新增，旧的导出文件中不存在
+    let parent_id: Option<i64> = record
+        .get(4)
+        .and_then(|s| if s.is_empty() { None } else { Some(s) })
+        .and_then(|s| s.parse().ok());
 
-    Ok((id, name, color, created_at))
+    Ok(Tag { id, uid: parse_uid(record, 5), name, color, parent_id, created_at })
 }
 
-/// 解析任务 CSV 记录
-pub fn parse_todo_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, String, Option<String>, i32, i32, Option<i64>, Option<String>, Option<i64>, Option<i64>, Option<i64>, i64, i64)> {
+/// 解析任务 CSV 记录（标签、步骤和附件在其他表中，这里均为 None）
+pub fn parse_todo_csv(record: &csv::StringRecord) -> Result<Todo> {
     let id: i64 = record
         .get(0)
-        .ok_or_else(|| anyhow::anyhow!("Missing id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing id field".to_string()))?
         .parse()
         .context("Failed to parse todo id")?;
     let title: String = record
         .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Missing title field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing title field".to_string()))?
         .to_string();
     let description: Option<String> = record
         .get(2)
         .and_then(|s| if s.is_empty() { None } else { Some(s.to_string()) });
     let status: i32 = record
         .get(3)
-        .ok_or_else(|| anyhow::anyhow!("Missing status field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing status field".to_string()))?
         .parse()
         .context("Failed to parse todo status")?;
     let priority: i32 = record
         .get(4)
-        .ok_or_else(|| anyhow::anyhow!("Missing priority field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing priority field".to_string()))?
         .parse()
         .context("Failed to parse todo priority")?;
     let group_id: Option<i64> = record
@@ -190,32 +133,101 @@ pub fn parse_todo_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, String
         .and_then(|s| s.parse().ok());
     let created_at: i64 = record
         .get(10)
-        .ok_or_else(|| anyhow::anyhow!("Missing created_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing created_at field".to_string()))?
         .parse()
         .context("Failed to parse todo created_at")?;
     let updated_at: i64 = record
         .get(11)
-        .ok_or_else(|| anyhow::anyhow!("Missing updated_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing updated_at field".to_string()))?
         .parse()
         .context("Failed to parse todo updated_at")?;
 
-    Ok((id, title, description, status, priority, group_id, assignee, start_date, due_date, completed_at, created_at, updated_at))
+    Ok(Todo {
+        id,
+        uid: parse_uid(record, 12),
+        title,
+        description,
+        status: TodoStatus::from_i32(status),
+        priority,
+        group_id,
+        assignee,
+        start_date,
+        due_date,
+        completed_at,
+        created_at,
+        updated_at,
+        tags: None,
+        steps: None,
+        attachments: None,
+        group_info: None,
+    })
 }
 
 /// 解析任务-标签关联 CSV 记录
-pub fn parse_todo_tag_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, i64)> {
+pub fn parse_todo_tag_csv(record: &csv::StringRecord) -> Result<TodoTagCsvRecord> {
     let todo_id: i64 = record
         .get(0)
-        .ok_or_else(|| anyhow::anyhow!("Missing todo_id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing todo_id field".to_string()))?
         .parse()
         .context("Failed to parse todo_id")?;
     let tag_id: i64 = record
         .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Missing tag_id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing tag_id field".to_string()))?
         .parse()
         .context("Failed to parse tag_id")?;
 
-    Ok((todo_id, tag_id))
+    Ok(TodoTagCsvRecord { todo_id, tag_id })
+}
+
+/// 逐条解析 CSV 内容，内容为空时返回空列表
+fn parse_records<T>(csv_content: &str, mut parse: impl FnMut(&csv::StringRecord) -> Result<T>) -> Result<Vec<T>> {
+    let mut rdr = csv::Reader::from_reader(csv_content.as_bytes());
+    rdr.records()
+        .map(|record| parse(&record.context("Failed to read CSV record")?))
+        .collect()
+}
+
+/// 把 CSV 压缩包中的表格转换为导出数据，任务包含标签、步骤和附件
+///
+/// 压缩包中没有步骤或附件表时，任务的对应字段为 None（导入时不修改本地已有的步骤或附件）
+pub fn csv_to_export_data(csv_data: &ZipCsvData) -> Result<ExportData> {
+    let task_groups = parse_records(&csv_data.groups_csv, parse_task_group_csv)?;
+    let tags = parse_records(&csv_data.tags_csv, parse_tag_csv)?;
+    let tags_by_id: HashMap<i64, &Tag> = tags.iter().map(|tag| (tag.id, tag)).collect();
+
+    let mut todo_tags: HashMap<i64, Vec<Tag>> = HashMap::new();
+    for link in parse_records(&csv_data.todo_tags_csv, parse_todo_tag_csv)? {
+        if let Some(tag) = tags_by_id.get(&link.tag_id) {
+            todo_tags.entry(link.todo_id).or_default().push((*tag).clone());
+        }
+    }
+    let mut steps: HashMap<i64, Vec<TodoStep>> = HashMap::new();
+    for step in parse_records(&csv_data.steps_csv, parse_step_csv)? {
+        steps.entry(step.todo_id).or_default().push(step);
+    }
+    let mut attachments: HashMap<i64, Vec<Attachment>> = HashMap::new();
+    for attachment in parse_records(&csv_data.attachments_csv, parse_attachment_csv)? {
+        attachments.entry(attachment.todo_id).or_default().push(attachment);
+    }
+
+    let has_tags = !csv_data.todo_tags_csv.is_empty();
+    let has_steps = !csv_data.steps_csv.is_empty();
+    let has_attachments = !csv_data.attachments_csv.is_empty();
+    let todos = parse_records(&csv_data.todos_csv, |record| {
+        let mut todo = parse_todo_csv(record)?;
+        todo.tags = has_tags.then(|| todo_tags.remove(&todo.id).unwrap_or_default());
+        todo.steps = has_steps.then(|| steps.remove(&todo.id).unwrap_or_default());
+        todo.attachments = has_attachments.then(|| attachments.remove(&todo.id).unwrap_or_default());
+        Ok(todo)
+    })?;
+
+    Ok(ExportData {
+        version: "1.0".to_string(),
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        task_groups,
+        tags,
+        todos,
+    })
 }
 
 /// 将任务组导出为 CSV 格式
@@ -224,7 +236,7 @@ pub fn export_groups_to_csv(groups: &[TaskGroup]) -> Result<Vec<u8>> {
 
     // 写入表头
     csv_writer
-        .write_record(&[
+        .write_record([
             "id",
             "name",
             "parent_id",
@@ -233,21 +245,23 @@ pub fn export_groups_to_csv(groups: &[TaskGroup]) -> Result<Vec<u8>> {
             "sort_order",
             "created_at",
             "updated_at",
+            "uid",
         ])
         .context("Failed to write CSV header for groups")?;
 
     // 写入数据
     for group in groups {
         csv_writer
-            .write_record(&[
+            .write_record([
                 &group.id.to_string(),
                 &group.name,
                 &group.parent_id.map(|v| v.to_string()).unwrap_or("".to_string()),
-                group.icon.as_ref().map(|s| s.as_str()).unwrap_or(""),
-                group.color.as_ref().map(|s| s.as_str()).unwrap_or(""),
+                group.icon.as_deref().unwrap_or(""),
+                group.color.as_deref().unwrap_or(""),
                 &group.sort_order.to_string(),
                 &group.created_at.to_string(),
                 &group.updated_at.to_string(),
+                &group.uid,
             ])
             .context(format!("Failed to write CSV record for group {}", group.name))?;
     }
@@ -261,17 +275,19 @@ pub fn export_tags_to_csv(tags: &[Tag]) -> Result<Vec<u8>> {
 
     // 写入表头
     csv_writer
-        .write_record(&["id", "name", "color", "created_at"])
+        .write_record(["id", "name", "color", "created_at", "parent_id", "uid"])
         .context("Failed to write CSV header for tags")?;
 
     // 写入数据
     for tag in tags {
         csv_writer
-            .write_record(&[
+            .write_record([
                 &tag.id.to_string(),
                 &tag.name,
                 &tag.color,
                 &tag.created_at.to_string(),
+                &tag.parent_id.map(|v| v.to_string()).unwrap_or("".to_string()),
+                &tag.uid,
             ])
             .context(format!("Failed to write CSV record for tag {}", tag.name))?;
     }
@@ -285,7 +301,7 @@ pub fn export_todos_to_csv(todos: &[Todo]) -> Result<Vec<u8>> {
 
     // 写入表头
     csv_writer
-        .write_record(&[
+        .write_record([
             "id",
             "title",
             "description",
@@ -298,6 +314,7 @@ pub fn export_todos_to_csv(todos: &[Todo]) -> Result<Vec<u8>> {
             "completed_at",
             "created_at",
             "updated_at",
+            "uid",
         ])
         .context("Failed to write CSV header for todos")?;
 
@@ -310,19 +327,20 @@ pub fn export_todos_to_csv(todos: &[Todo]) -> Result<Vec<u8>> {
         };
 
         csv_writer
-            .write_record(&[
+            .write_record([
                 &todo.id.to_string(),
                 &todo.title,
-                todo.description.as_ref().map(|s| s.as_str()).unwrap_or(""),
+                todo.description.as_deref().unwrap_or(""),
                 status_value,
                 &todo.priority.to_string(),
                 &todo.group_id.map(|v| v.to_string()).unwrap_or("".to_string()),
-                todo.assignee.as_ref().map(|s| s.as_str()).unwrap_or(""),
+                todo.assignee.as_deref().unwrap_or(""),
                 &todo.start_date.map(|d| d.to_string()).unwrap_or("".to_string()),
                 &todo.due_date.map(|d| d.to_string()).unwrap_or("".to_string()),
                 &todo.completed_at.map(|d| d.to_string()).unwrap_or("".to_string()),
                 &todo.created_at.to_string(),
                 &todo.updated_at.to_string(),
+                &todo.uid,
             ])
             .context(format!("Failed to write CSV record for todo {}", todo.title))?;
     }
@@ -336,7 +354,7 @@ pub fn export_todo_tags_to_csv(todos: &[Todo]) -> Result<Vec<u8>> {
 
     // 写入表头
     csv_writer
-        .write_record(&["todo_id", "tag_id"])
+        .write_record(["todo_id", "tag_id"])
         .context("Failed to write CSV header for todo_tags")?;
 
     // 写入数据
@@ -344,7 +362,7 @@ pub fn export_todo_tags_to_csv(todos: &[Todo]) -> Result<Vec<u8>> {
         if let Some(tags) = &todo.tags {
             for tag in tags {
                 csv_writer
-                    .write_record(&[&todo.id.to_string(), &tag.id.to_string()])
+                    .write_record([&todo.id.to_string(), &tag.id.to_string()])
                     .context(format!("Failed to write CSV record for todo-tag relation {}-{}", todo.id, tag.id))?;
             }
         }
@@ -359,7 +377,7 @@ pub fn create_zip_archive(
     tags_csv: Vec<u8>,
     todos_csv: Vec<u8>,
     todo_tags_csv: Vec<u8>,
-) -> Result<Vec<u8>, String> {
+) -> Result<Vec<u8>> {
     let zip_buffer = Cursor::new(Vec::new());
     let mut zip_writer = zip::ZipWriter::new(zip_buffer);
     let file_options: zip::write::FileOptions<()> =
@@ -369,39 +387,39 @@ pub fn create_zip_archive(
     // 添加任务组 CSV
     zip_writer
         .start_file("task_groups.csv", file_options)
-        .map_err(|e| format!("Failed to create task_groups.csv: {}", e))?;
+        .context("Failed to create task_groups.csv")?;
     zip_writer
         .write_all(&groups_csv)
-        .map_err(|e| format!("Failed to write task_groups.csv: {}", e))?;
+        .context("Failed to write task_groups.csv")?;
 
     // 添加标签 CSV
     zip_writer
         .start_file("tags.csv", file_options)
-        .map_err(|e| format!("Failed to create tags.csv: {}", e))?;
+        .context("Failed to create tags.csv")?;
     zip_writer
         .write_all(&tags_csv)
-        .map_err(|e| format!("Failed to write tags.csv: {}", e))?;
+        .context("Failed to write tags.csv")?;
 
     // 添加任务 CSV
     zip_writer
         .start_file("todos.csv", file_options)
-        .map_err(|e| format!("Failed to create todos.csv: {}", e))?;
+        .context("Failed to create todos.csv")?;
     zip_writer
         .write_all(&todos_csv)
-        .map_err(|e| format!("Failed to write todos.csv: {}", e))?;
+        .context("Failed to write todos.csv")?;
 
     // 添加任务-标签关联 CSV
     zip_writer
         .start_file("todo_tags.csv", file_options)
-        .map_err(|e| format!("Failed to create todo_tags.csv: {}", e))?;
+        .context("Failed to create todo_tags.csv")?;
     zip_writer
         .write_all(&todo_tags_csv)
-        .map_err(|e| format!("Failed to write todo_tags.csv: {}", e))?;
+        .context("Failed to write todo_tags.csv")?;
 
     // 完成 ZIP 文件
     let result = zip_writer
         .finish()
-        .map_err(|e| format!("Failed to finish ZIP: {}", e))?;
+        .context("Failed to finish ZIP")?;
 
     Ok(result.into_inner())
 }
@@ -418,10 +436,10 @@ pub struct ZipCsvData {
 
 /// 从 ZIP 压缩包中提取所有 CSV 文件
 /// 从 tables/ 目录提取CSV文件
-pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
+pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData> {
     let reader = Cursor::new(zip_data);
     let mut zip_archive =
-        zip::ZipArchive::new(reader).map_err(|e| format!("Failed to open zip: {}", e))?;
+        zip::ZipArchive::new(reader).context("Failed to open zip")?;
 
     tracing::info!("ZIP file contains {} files", zip_archive.len());
 
@@ -435,7 +453,7 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     // 读取任务组 CSV
     if let Ok(mut file) = zip_archive.by_name("tables/task_groups.csv") {
         file.read_to_string(&mut groups_csv)
-            .map_err(|e| format!("Failed to read tables/task_groups.csv: {}", e))?;
+            .context("Failed to read tables/task_groups.csv")?;
         tracing::info!("Successfully read tables/task_groups.csv: {} bytes", groups_csv.len());
     } else {
         tracing::warn!("tables/task_groups.csv not found in ZIP");
@@ -444,7 +462,7 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     // 读取标签 CSV
     if let Ok(mut file) = zip_archive.by_name("tables/tags.csv") {
         file.read_to_string(&mut tags_csv)
-            .map_err(|e| format!("Failed to read tables/tags.csv: {}", e))?;
+            .context("Failed to read tables/tags.csv")?;
         tracing::info!("Successfully read tables/tags.csv: {} bytes", tags_csv.len());
     } else {
         tracing::warn!("tables/tags.csv not found in ZIP");
@@ -453,7 +471,7 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     // 读取任务 CSV
     if let Ok(mut file) = zip_archive.by_name("tables/todos.csv") {
         file.read_to_string(&mut todos_csv)
-            .map_err(|e| format!("Failed to read tables/todos.csv: {}", e))?;
+            .context("Failed to read tables/todos.csv")?;
         tracing::info!("Successfully read tables/todos.csv: {} bytes", todos_csv.len());
     } else {
         tracing::warn!("tables/todos.csv not found in ZIP");
@@ -462,7 +480,7 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     // 读取任务-标签关联 CSV
     if let Ok(mut file) = zip_archive.by_name("tables/todo_tags.csv") {
         file.read_to_string(&mut todo_tags_csv)
-            .map_err(|e| format!("Failed to read tables/todo_tags.csv: {}", e))?;
+            .context("Failed to read tables/todo_tags.csv")?;
         tracing::info!("Successfully read tables/todo_tags.csv: {} bytes", todo_tags_csv.len());
     } else {
         tracing::warn!("tables/todo_tags.csv not found in ZIP");
@@ -471,7 +489,7 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     // 读取步骤 CSV
     if let Ok(mut file) = zip_archive.by_name("tables/steps.csv") {
         file.read_to_string(&mut steps_csv)
-            .map_err(|e| format!("Failed to read tables/steps.csv: {}", e))?;
+            .context("Failed to read tables/steps.csv")?;
         tracing::info!("Successfully read tables/steps.csv: {} bytes", steps_csv.len());
     } else {
         tracing::warn!("tables/steps.csv not found in ZIP");
@@ -480,7 +498,7 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     // 读取附件 CSV
     if let Ok(mut file) = zip_archive.by_name("tables/attachments.csv") {
         file.read_to_string(&mut attachments_csv)
-            .map_err(|e| format!("Failed to read tables/attachments.csv: {}", e))?;
+            .context("Failed to read tables/attachments.csv")?;
         tracing::info!("Successfully read tables/attachments.csv: {} bytes", attachments_csv.len());
     } else {
         tracing::warn!("tables/attachments.csv not found in ZIP");
@@ -503,26 +521,27 @@ pub fn extract_csv_from_zip(zip_data: Vec<u8>) -> Result<ZipCsvData, String> {
     })
 }
 
-/// 递归将目录添加到 ZIP 文件中
+/// 递归将目录添加到 ZIP 文件中，加密的文件用 `key` 解密后写入
 fn add_dir_to_zip<W>(
     zip_writer: &mut zip::ZipWriter<W>,
     dir_path: &Path,
     zip_prefix: &str,
     options: zip::write::FileOptions<()>,
-) -> Result<(), String>
+    key: Option<&EncryptionKey>,
+) -> Result<()>
 where
     W: Write + Seek,
 {
     for entry in std::fs::read_dir(dir_path)
-        .map_err(|e| format!("Failed to read directory {}: {}", dir_path.display(), e))?
+        .with_context(|| format!("Failed to read directory {}", dir_path.display()))?
     {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let entry = entry.context("Failed to read entry")?;
         let path = entry.path();
-        let file_type = entry.file_type().map_err(|e| format!("Failed to get file type: {}", e))?;
+        let file_type = entry.file_type().context("Failed to get file type")?;
 
         let relative_path = path
             .strip_prefix(dir_path)
-            .map_err(|e| format!("Failed to strip prefix: {}", e))?;
+            .map_err(|e| Error::Other(format!("Failed to strip prefix: {}", e)))?;
 
         let zip_name = format!("{}/{}", zip_prefix, relative_path.display());
 
@@ -531,43 +550,44 @@ where
             let dir_name = format!("{}/", zip_name);
             zip_writer
                 .start_file(&dir_name, options)
-                .map_err(|e| format!("Failed to create directory {} in ZIP: {}", dir_name, e))?;
+                .with_context(|| format!("Failed to create directory {} in ZIP", dir_name))?;
 
             // 递归处理子目录
-            add_dir_to_zip(zip_writer, &path, zip_prefix, options)?;
+            add_dir_to_zip(zip_writer, &path, zip_prefix, options, key)?;
         } else {
             // 对于文件，直接添加到 ZIP
             zip_writer
                 .start_file(&zip_name, options)
-                .map_err(|e| format!("Failed to create file {} in ZIP: {}", zip_name, e))?;
+                .with_context(|| format!("Failed to create file {} in ZIP", zip_name))?;
 
-            let file_content = std::fs::read(&path)
-                .map_err(|e| format!("Failed to read file {}: {}", path.display(), e))?;
+            let file_content = encryption::read_file(&path, key)
+                .with_context(|| format!("Failed to read file {}", path.display()))?;
 
             zip_writer
                 .write_all(&file_content)
-                .map_err(|e| format!("Failed to write file {} to ZIP: {}", zip_name, e))?;
+                .with_context(|| format!("Failed to write file {} to ZIP", zip_name))?;
         }
     }
 
     Ok(())
 }
 
-/// 从 ZIP 文件中提取指定目录到目标位置
+/// 从 ZIP 文件中提取指定目录到目标位置，给出 `key` 时加密后写入
 fn extract_dir_from_zip(
     zip_archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>,
     zip_dir_prefix: &str,
     target_dir: &Path,
-) -> Result<(), String> {
+    key: Option<&EncryptionKey>,
+) -> Result<()> {
     // 确保目标目录存在
     std::fs::create_dir_all(target_dir)
-        .map_err(|e| format!("Failed to create target directory {}: {}", target_dir.display(), e))?;
+        .with_context(|| format!("Failed to create target directory {}", target_dir.display()))?;
 
     // 遍历 ZIP 中的所有文件
     for i in 0..zip_archive.len() {
         let mut zip_file = zip_archive
             .by_index(i)
-            .map_err(|e| format!("Failed to get file at index {}: {}", i, e))?;
+            .with_context(|| format!("Failed to get file at index {}", i))?;
 
         let name = zip_file.name();
 
@@ -583,22 +603,21 @@ fn extract_dir_from_zip(
             // 如果是目录（以 / 结尾），创建目录
             if name.ends_with('/') {
                 std::fs::create_dir_all(&target_path)
-                    .map_err(|e| format!("Failed to create directory {}: {}", target_path.display(), e))?;
+                    .with_context(|| format!("Failed to create directory {}", target_path.display()))?;
             } else {
                 // 确保父目录存在
                 if let Some(parent) = target_path.parent() {
                     std::fs::create_dir_all(parent)
-                        .map_err(|e| format!("Failed to create parent directory {}: {}", parent.display(), e))?;
+                        .with_context(|| format!("Failed to create parent directory {}", parent.display()))?;
                 }
 
                 // 解压文件
-                let mut file =
-                    std::fs::File::create(&target_path).map_err(|e| {
-                        format!("Failed to create file {}: {}", target_path.display(), e)
-                    })?;
+                let mut file_content = Vec::new();
+                zip_file.read_to_end(&mut file_content)
+                    .with_context(|| format!("Failed to read file {} from ZIP", target_path.display()))?;
 
-                std::io::copy(&mut zip_file, &mut file)
-                    .map_err(|e| format!("Failed to write file {}: {}", target_path.display(), e))?;
+                encryption::write_file(&target_path, &file_content, key)
+                    .with_context(|| format!("Failed to write file {}", target_path.display()))?;
             }
         }
     }
@@ -608,6 +627,7 @@ fn extract_dir_from_zip(
 
 /// 创建包含多个 CSV 文件和附件的 ZIP 压缩包
 /// attachments_path 是附件目录的路径，如果为 None 则不包含附件文件
+/// 加密的附件用 key 解密，导出的压缩包不加密
 ///
 /// ZIP 结构:
 /// tables/
@@ -620,6 +640,7 @@ fn extract_dir_from_zip(
 /// data/
 ///   └── attachments/
 ///       └── (附件文件)
+#[allow(clippy::too_many_arguments)]
 pub fn create_zip_archive_with_attachments(
     groups_csv: Vec<u8>,
     tags_csv: Vec<u8>,
@@ -628,7 +649,8 @@ pub fn create_zip_archive_with_attachments(
     steps_csv: Vec<u8>,
     attachments_csv: Vec<u8>,
     attachments_path: Option<&Path>,
-) -> Result<Vec<u8>, String> {
+    key: Option<&EncryptionKey>,
+) -> Result<Vec<u8>> {
     let zip_buffer = Cursor::new(Vec::new());
     let mut zip_writer = zip::ZipWriter::new(zip_buffer);
     let file_options: zip::write::FileOptions<()> =
@@ -639,56 +661,56 @@ pub fn create_zip_archive_with_attachments(
     // 任务组 CSV
     zip_writer
         .start_file("tables/task_groups.csv", file_options)
-        .map_err(|e| format!("Failed to create tables/task_groups.csv: {}", e))?;
+        .context("Failed to create tables/task_groups.csv")?;
     zip_writer
         .write_all(&groups_csv)
-        .map_err(|e| format!("Failed to write task_groups.csv: {}", e))?;
+        .context("Failed to write task_groups.csv")?;
 
     // 标签 CSV
     zip_writer
         .start_file("tables/tags.csv", file_options)
-        .map_err(|e| format!("Failed to create tables/tags.csv: {}", e))?;
+        .context("Failed to create tables/tags.csv")?;
     zip_writer
         .write_all(&tags_csv)
-        .map_err(|e| format!("Failed to write tags.csv: {}", e))?;
+        .context("Failed to write tags.csv")?;
 
     // 任务 CSV
     zip_writer
         .start_file("tables/todos.csv", file_options)
-        .map_err(|e| format!("Failed to create tables/todos.csv: {}", e))?;
+        .context("Failed to create tables/todos.csv")?;
     zip_writer
         .write_all(&todos_csv)
-        .map_err(|e| format!("Failed to write todos.csv: {}", e))?;
+        .context("Failed to write todos.csv")?;
 
     // 任务-标签关联 CSV
     zip_writer
         .start_file("tables/todo_tags.csv", file_options)
-        .map_err(|e| format!("Failed to create tables/todo_tags.csv: {}", e))?;
+        .context("Failed to create tables/todo_tags.csv")?;
     zip_writer
         .write_all(&todo_tags_csv)
-        .map_err(|e| format!("Failed to write todo_tags.csv: {}", e))?;
+        .context("Failed to write todo_tags.csv")?;
 
     // 步骤 CSV
     zip_writer
         .start_file("tables/steps.csv", file_options)
-        .map_err(|e| format!("Failed to create tables/steps.csv: {}", e))?;
+        .context("Failed to create tables/steps.csv")?;
     zip_writer
         .write_all(&steps_csv)
-        .map_err(|e| format!("Failed to write steps.csv: {}", e))?;
+        .context("Failed to write steps.csv")?;
 
     // 附件 CSV
     zip_writer
         .start_file("tables/attachments.csv", file_options)
-        .map_err(|e| format!("Failed to create tables/attachments.csv: {}", e))?;
+        .context("Failed to create tables/attachments.csv")?;
     zip_writer
         .write_all(&attachments_csv)
-        .map_err(|e| format!("Failed to write attachments.csv: {}", e))?;
+        .context("Failed to write attachments.csv")?;
 
     // 如果提供了附件目录，添加附件文件到 data/attachments/
     if let Some(attachments_dir) = attachments_path {
         if attachments_dir.exists() {
             tracing::info!("Adding attachments from: {} to data/attachments/", attachments_dir.display());
-            add_dir_to_zip(&mut zip_writer, attachments_dir, "data/attachments", file_options)?;
+            add_dir_to_zip(&mut zip_writer, attachments_dir, "data/attachments", file_options, key)?;
         } else {
             tracing::info!("Attachments directory does not exist, skipping: {}", attachments_dir.display());
         }
@@ -697,22 +719,23 @@ pub fn create_zip_archive_with_attachments(
     // 完成 ZIP 文件
     let result = zip_writer
         .finish()
-        .map_err(|e| format!("Failed to finish ZIP: {}", e))?;
+        .context("Failed to finish ZIP")?;
 
     Ok(result.into_inner())
 }
 
 /// 从 ZIP 压缩包中提取附件到指定目录
-/// 从 data/attachments/ 提取附件文件
+/// 从 data/attachments/ 提取附件文件，启用加密时用 key 加密后写入
 pub fn extract_attachments_from_zip(
     zip_data: Vec<u8>,
     target_attachments_dir: &Path,
-) -> Result<(), String> {
+    key: Option<&EncryptionKey>,
+) -> Result<()> {
     let reader = Cursor::new(zip_data);
     let mut zip_archive =
-        zip::ZipArchive::new(reader).map_err(|e| format!("Failed to open zip: {}", e))?;
+        zip::ZipArchive::new(reader).context("Failed to open zip")?;
 
-    extract_dir_from_zip(&mut zip_archive, "data/attachments", target_attachments_dir)?;
+    extract_dir_from_zip(&mut zip_archive, "data/attachments", target_attachments_dir, key)?;
 
     tracing::info!("Attachments extracted to: {}", target_attachments_dir.display());
 
@@ -725,26 +748,28 @@ pub fn export_steps_to_csv(steps: &[TodoStep]) -> Result<Vec<u8>> {
 
     // 写入表头
     csv_writer
-        .write_record(&[
+        .write_record([
             "id",
             "todo_id",
             "title",
             "is_completed",
             "sort_order",
             "created_at",
+            "uid",
         ])
         .context("Failed to write CSV header for steps")?;
 
     // 写入数据
     for step in steps {
         csv_writer
-            .write_record(&[
+            .write_record([
                 &step.id.to_string(),
                 &step.todo_id.to_string(),
                 &step.title,
                 &if step.is_completed { "1".to_string() } else { "0".to_string() },
                 &step.sort_order.to_string(),
                 &step.created_at.to_string(),
+                &step.uid,
             ])
             .context(format!("Failed to write CSV record for step {}", step.id))?;
     }
@@ -758,7 +783,7 @@ pub fn export_attachments_to_csv(attachments: &[Attachment]) -> Result<Vec<u8>>
 
     // 写入表头
     csv_writer
-        .write_record(&[
+        .write_record([
             "id",
             "todo_id",
             "name",
@@ -766,20 +791,22 @@ pub fn export_attachments_to_csv(attachments: &[Attachment]) -> Result<Vec<u8>>
             "file_size",
             "mime_type",
             "created_at",
+            "uid",
         ])
         .context("Failed to write CSV header for attachments")?;
 
     // 写入数据
     for attachment in attachments {
         csv_writer
-            .write_record(&[
+            .write_record([
                 &attachment.id.to_string(),
                 &attachment.todo_id.to_string(),
                 &attachment.name,
                 &attachment.file_path,
                 &attachment.file_size.to_string(),
-                &attachment.mime_type.as_ref().map(|s| s.as_str()).unwrap_or("").to_string(),
+                &attachment.mime_type.as_deref().unwrap_or("").to_string(),
                 &attachment.created_at.to_string(),
+                &attachment.uid,
             ])
             .context(format!("Failed to write CSV record for attachment {}", attachment.id))?;
     }
@@ -788,66 +815,66 @@ pub fn export_attachments_to_csv(attachments: &[Attachment]) -> Result<Vec<u8>>
 }
 
 /// 解析步骤 CSV 记录
-pub fn parse_step_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, i64, String, bool, i32, i64)> {
+pub fn parse_step_csv(record: &csv::StringRecord) -> Result<TodoStep> {
     let id: i64 = record
         .get(0)
-        .ok_or_else(|| anyhow::anyhow!("Missing id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing id field".to_string()))?
         .parse()
         .context("Failed to parse step id")?;
     let todo_id: i64 = record
         .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Missing todo_id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing todo_id field".to_string()))?
         .parse()
         .context("Failed to parse step todo_id")?;
     let title: String = record
         .get(2)
-        .ok_or_else(|| anyhow::anyhow!("Missing title field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing title field".to_string()))?
         .to_string();
     let is_completed_str = record
         .get(3)
-        .ok_or_else(|| anyhow::anyhow!("Missing is_completed field"))?;
+        .ok_or_else(|| Error::InvalidInput("Missing is_completed field".to_string()))?;
     let is_completed: bool = match is_completed_str {
         "1" => true,
         "0" | "" => false,
-        _ => anyhow::bail!("Invalid is_completed value: {}", is_completed_str),
+        _ => return Err(Error::InvalidInput(format!("Invalid is_completed value: {}", is_completed_str))),
     };
     let sort_order: i32 = record
         .get(4)
-        .ok_or_else(|| anyhow::anyhow!("Missing sort_order field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing sort_order field".to_string()))?
         .parse()
         .context("Failed to parse step sort_order")?;
     let created_at: i64 = record
         .get(5)
-        .ok_or_else(|| anyhow::anyhow!("Missing created_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing created_at field".to_string()))?
         .parse()
         .context("Failed to parse step created_at")?;
 
-    Ok((id, todo_id, title, is_completed, sort_order, created_at))
+    Ok(TodoStep { id, uid: parse_uid(record, 6), todo_id, title, is_completed, sort_order, created_at })
 }
 
 /// 解析附件 CSV 记录
-pub fn parse_attachment_csv(record: &csv::StringRecord) -> anyhow::Result<(i64, i64, String, String, i64, Option<String>, i64)> {
+pub fn parse_attachment_csv(record: &csv::StringRecord) -> Result<Attachment> {
     let id: i64 = record
         .get(0)
-        .ok_or_else(|| anyhow::anyhow!("Missing id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing id field".to_string()))?
         .parse()
         .context("Failed to parse attachment id")?;
     let todo_id: i64 = record
         .get(1)
-        .ok_or_else(|| anyhow::anyhow!("Missing todo_id field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing todo_id field".to_string()))?
         .parse()
         .context("Failed to parse attachment todo_id")?;
     let name: String = record
         .get(2)
-        .ok_or_else(|| anyhow::anyhow!("Missing name field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing name field".to_string()))?
         .to_string();
     let file_path: String = record
         .get(3)
-        .ok_or_else(|| anyhow::anyhow!("Missing file_path field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing file_path field".to_string()))?
         .to_string();
     let file_size: i64 = record
         .get(4)
-        .ok_or_else(|| anyhow::anyhow!("Missing file_size field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing file_size field".to_string()))?
         .parse()
         .context("Failed to parse attachment file_size")?;
     let mime_type: Option<String> = record
@@ -855,10 +882,10 @@ pub fn parse_attachment_csv(record: &csv::StringRecord) -> anyhow::Result<(i64,
         .and_then(|s| if s.is_empty() { None } else { Some(s.to_string()) });
     let created_at: i64 = record
         .get(6)
-        .ok_or_else(|| anyhow::anyhow!("Missing created_at field"))?
+        .ok_or_else(|| Error::InvalidInput("Missing created_at field".to_string()))?
         .parse()
         .context("Failed to parse attachment created_at")?;
 
-    Ok((id, todo_id, name, file_path, file_size, mime_type, created_at))
+    Ok(Attachment { id, uid: parse_uid(record, 7), todo_id, name, file_path, file_size, mime_type, created_at })
 }
 
@@ -0,0 +1,306 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! iCalendar（RFC 5545）读写
+//!
+//! 任务导出为 VTODO：`uid` 写入 UID，任务组路径写入 `X-RTODO-GROUP`，再次导入时按 UID 匹配到原来的任务。
+//! 时间一律以 UTC 写出；读取时带 TZID 的时间按本地时间处理。
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::models::{priority, TaskGroup, Todo, TodoStatus};
+
+/// 内容行的一个属性，如 `DTSTART;VALUE=DATE:20240315`
+#[derive(Debug, Clone)]
+pub struct Property {
+    /// 属性名（大写）
+    pub name: String,
+    /// 参数名（大写）和值
+    pub params: Vec<(String, String)>,
+    /// 未去掉转义的值
+    pub value: String,
+}
+
+impl Property {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// TEXT 类型的值
+    pub fn text(&self) -> String {
+        unescape(&self.value)
+    }
+
+    /// 逗号分隔的多个 TEXT 值（如 CATEGORIES）
+    pub fn texts(&self) -> Vec<String> {
+        split_unescaped(&self.value, ',').iter().map(|value| unescape(value)).filter(|value| !value.is_empty()).collect()
+    }
+
+    /// DATE 或 DATE-TIME 类型的值，返回毫秒时间戳；UTC 以外的时间和日期按本地时间处理
+    pub fn timestamp(&self) -> Option<i64> {
+        let value = self.value.trim();
+        if let Some(utc) = value.strip_suffix('Z') {
+            let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            return Some(Utc.from_utc_datetime(&time).timestamp_millis());
+        }
+        let time = match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+            Ok(time) => time,
+            Err(_) => NaiveDate::parse_from_str(value, "%Y%m%d").ok()?.and_hms_opt(0, 0, 0)?,
+        };
+        Local.from_local_datetime(&time).earliest().map(|time| time.timestamp_millis())
+    }
+}
+
+/// 组件，如 VCALENDAR、VTODO、VALARM
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub name: String,
+    pub properties: Vec<Property>,
+    pub components: Vec<Component>,
+}
+
+impl Component {
+    /// 第一个同名属性
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|property| property.name == name)
+    }
+}
+
+/// 解析 iCalendar 文本，返回顶层组件（通常是一个 VCALENDAR）
+pub fn parse(content: &str) -> Result<Vec<Component>> {
+    // 展开折行：以空格或制表符开头的行接在上一行后面
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.trim_start_matches('\u{feff}').split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut top = Vec::new();
+    let mut stack: Vec<Component> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = parse_line(line)
+            .ok_or_else(|| Error::InvalidInput(format!("Invalid iCalendar line {}: {}", index + 1, line)))?;
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: property.value.trim().to_uppercase(),
+                properties: Vec::new(),
+                components: Vec::new(),
+            }),
+            "END" => {
+                let component = stack
+                    .pop()
+                    .filter(|component| component.name == property.value.trim().to_uppercase())
+                    .ok_or_else(|| Error::InvalidInput(format!("Unexpected END:{} on line {}", property.value, index + 1)))?;
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => top.push(component),
+                }
+            }
+            _ => {
+                if let Some(component) = stack.last_mut() {
+                    component.properties.push(property);
+                }
+            }
+        }
+    }
+    if let Some(component) = stack.last() {
+        return Err(Error::InvalidInput(format!("Missing END:{}", component.name)));
+    }
+    Ok(top)
+}
+
+/// 解析一个内容行：`名称;参数=值;...:值`，参数值可以用双引号括起
+fn parse_line(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, ch)| {
+        match ch {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(index),
+            _ => {}
+        }
+        None
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = split_quoted(head, ';').into_iter();
+    let name = parts.next()?.trim().to_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((key.trim().to_uppercase(), value.trim().trim_matches('"').to_string()))
+        })
+        .collect();
+    Some(Property { name, params, value: value.to_string() })
+}
+
+/// 按分隔符拆分，忽略双引号中的分隔符
+fn split_quoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    for (index, ch) in text.char_indices() {
+        if ch == '"' {
+            quoted = !quoted;
+        } else if ch == separator && !quoted {
+            parts.push(&text[start..index]);
+            start = index + 1;
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// 按分隔符拆分，忽略转义的分隔符（`\,`）
+fn split_unescaped(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut escaped) = (0, false);
+    for (index, ch) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == separator {
+            parts.push(&text[start..index]);
+            start = index + 1;
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// 去掉 TEXT 值的转义
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// 转义 TEXT 值
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | ';' | ',' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            '\n' => result.push_str("\\n"),
+            '\r' => {}
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// 写入一个内容行，超过 75 字节时折行（不拆开多字节字符）
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for ch in line.chars() {
+        if width + ch.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(ch);
+        width += ch.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// UTC 的 DATE-TIME 值
+fn utc(timestamp: i64) -> Option<String> {
+    Utc.timestamp_millis_opt(timestamp).single().map(|time| time.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+/// 把任务写成包含 VTODO 的 iCalendar 文本，`groups` 用于写出任务组路径
+pub fn write_todos(todos: &[Todo], groups: &[TaskGroup]) -> String {
+    let groups: HashMap<i64, &TaskGroup> = groups.iter().map(|group| (group.id, group)).collect();
+    let stamp = utc(Utc::now().timestamp_millis()).unwrap_or_default();
+
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:-//RTodo//RTodo {}//EN", env!("CARGO_PKG_VERSION")));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    for todo in todos {
+        push_line(&mut out, "BEGIN:VTODO");
+        push_line(&mut out, &format!("UID:{}", todo.uid));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        let times = [
+            ("CREATED", Some(todo.created_at)),
+            ("LAST-MODIFIED", Some(todo.updated_at)),
+            ("DTSTART", todo.start_date),
+            ("DUE", todo.due_date),
+            ("COMPLETED", todo.completed_at),
+        ];
+        for (name, timestamp) in times {
+            if let Some(value) = timestamp.and_then(utc) {
+                push_line(&mut out, &format!("{}:{}", name, value));
+            }
+        }
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&todo.title)));
+        if let Some(description) = todo.description.as_deref().filter(|text| !text.is_empty()) {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape(description)));
+        }
+        let status = match todo.status {
+            TodoStatus::Todo => "NEEDS-ACTION",
+            TodoStatus::InProgress => "IN-PROCESS",
+            TodoStatus::Done => "COMPLETED",
+        };
+        push_line(&mut out, &format!("STATUS:{}", status));
+        // iCalendar 的优先级 1 最高、9 最低，0 表示未指定
+        match todo.priority {
+            priority::URGENT => push_line(&mut out, "PRIORITY:1"),
+            priority::IMPORTANT => push_line(&mut out, "PRIORITY:5"),
+            _ => {}
+        }
+        if let Some(tags) = todo.tags.as_ref().filter(|tags| !tags.is_empty()) {
+            let names: Vec<_> = tags.iter().map(|tag| escape(&tag.name)).collect();
+            push_line(&mut out, &format!("CATEGORIES:{}", names.join(",")));
+        }
+        let path = group_path(&groups, todo.group_id);
+        if !path.is_empty() {
+            let names: Vec<_> = path.iter().map(|name| escape(name)).collect();
+            push_line(&mut out, &format!("X-RTODO-GROUP:{}", names.join(",")));
+        }
+        push_line(&mut out, "END:VTODO");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// 从顶级到任务组本身的名称
+fn group_path<'a>(groups: &HashMap<i64, &'a TaskGroup>, group_id: Option<i64>) -> Vec<&'a str> {
+    let mut path = Vec::new();
+    let mut current = group_id;
+    while let Some(group) = current.and_then(|id| groups.get(&id)) {
+        // 数据损坏形成循环时停止
+        if path.len() > groups.len() {
+            break;
+        }
+        path.push(group.name.as_str());
+        current = group.parent_id;
+    }
+    path.reverse();
+    path
+}
@@ -0,0 +1,26 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+pub mod data_export;
+pub mod ical;
+
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+
+/// 获取当前时间戳（毫秒）
+pub fn now_timestamp() -> i64 {
+    Utc::now().timestamp_millis()
+}
+
+/// 生成新的 UUID
+pub fn new_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// 由名称生成固定的 UUID（同一名称总是得到同一个 UUID），用于为外部数据分配稳定的 uid
+pub fn name_uuid(name: &str) -> String {
+    let hash = Sha256::digest(name.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hash[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid().to_string()
+}
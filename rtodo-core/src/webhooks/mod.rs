@@ -0,0 +1,109 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 出站 Webhook 的投递队列
+//!
+//! 事件先写入数据库中的投递队列，再由调用方的后台任务发送；这里负责入队、签名和记录发送结果，
+//! 失败后按指数退避重新排队，超过最大次数后标记为失败。
+
+#[cfg(test)]
+mod tests;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rusqlite::Connection;
+use serde::Serialize;
+use sha2::Sha256;
+
+use crate::database::repositories::WebhookRepository;
+use crate::error::Result;
+use crate::models::{webhook_event, DeliveryStatus, Todo, WebhookDelivery};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 最大投递次数（含首次）
+pub const MAX_ATTEMPTS: i32 = 8;
+
+/// 首次重试间隔，之后每次翻倍
+const BASE_RETRY_DELAY_MS: i64 = 30 * 1000;
+
+/// 最长重试间隔
+const MAX_RETRY_DELAY_MS: i64 = 60 * 60 * 1000;
+
+/// 发送失败
+#[derive(Debug)]
+pub struct SendError {
+    /// 服务器返回的状态码（连接失败时为空）
+    pub status: Option<u16>,
+    pub message: String,
+}
+
+/// 构建事件请求体
+pub fn build_payload<T: Serialize>(event: &str, data: &T) -> String {
+    serde_json::json!({
+        "event": event,
+        "occurred_at": Utc::now().timestamp_millis(),
+        "data": data,
+    })
+    .to_string()
+}
+
+/// 为订阅了该事件的 Webhook 创建投递记录，返回创建的数量
+pub fn enqueue<T: Serialize>(conn: &Connection, event: &str, data: &T) -> Result<usize> {
+    WebhookRepository::enqueue(conn, event, &build_payload(event, data))
+}
+
+/// 任务更新后加入 `todo.updated`，状态变化时额外加入 `todo.status_changed`
+pub fn enqueue_todo_updated(conn: &Connection, before: &Todo, after: &Todo) -> Result<usize> {
+    let mut count = enqueue(conn, webhook_event::TODO_UPDATED, after)?;
+    if before.status != after.status {
+        count += enqueue(conn, webhook_event::TODO_STATUS_CHANGED, &serde_json::json!({
+            "todo": after,
+            "previous_status": before.status,
+        }))?;
+    }
+    Ok(count)
+}
+
+/// 计算签名：`sha256=` + HMAC-SHA256(secret, "{timestamp}.{body}") 的十六进制
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// 第 `attempts` 次投递失败后的重试间隔，超过最大次数时返回 None
+pub fn retry_delay_ms(attempts: i32) -> Option<i64> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    Some((BASE_RETRY_DELAY_MS * 2_i64.pow(exponent)).min(MAX_RETRY_DELAY_MS))
+}
+
+/// 记录一次发送的结果：成功时标记为已投递，失败时按退避时间重新排队，返回投递记录的新状态
+pub fn record_attempt(
+    conn: &Connection,
+    item: &WebhookDelivery,
+    result: std::result::Result<u16, SendError>,
+) -> Result<DeliveryStatus> {
+    match result {
+        Ok(status) => {
+            tracing::info!("Webhook delivery {} ({}) succeeded: HTTP {}", item.id, item.event, status);
+            WebhookRepository::mark_delivered(conn, item.id, status)?;
+            Ok(DeliveryStatus::Delivered)
+        }
+        Err(e) => {
+            let attempts = item.attempts + 1;
+            let next_attempt_at = retry_delay_ms(attempts).map(|delay| Utc::now().timestamp_millis() + delay);
+            let status = if next_attempt_at.is_some() { DeliveryStatus::Pending } else { DeliveryStatus::Failed };
+            tracing::warn!("Webhook delivery {} ({}) attempt {} failed: {} -> {:?}",
+                item.id, item.event, attempts, e.message, status);
+            WebhookRepository::mark_attempt_failed(conn, item.id, &e.message, e.status, next_attempt_at)?;
+            Ok(status)
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use crate::models::Webhook;
+use crate::test_support::memory_db;
+
+fn webhook(events: &[&str]) -> Webhook {
+    Webhook {
+        id: 1,
+        name: "Hook".to_string(),
+        url: "https://example.com/hook".to_string(),
+        secret: None,
+        events: events.iter().map(|e| e.to_string()).collect(),
+        enabled: true,
+        created_at: 0,
+        updated_at: 0,
+    }
+}
+
+#[test]
+fn signature_is_hmac_sha256_of_timestamp_and_body() {
+    // HMAC-SHA256("Jefe", "1700000000000.{\"event\":\"ping\"}")
+    assert_eq!(
+        sign("Jefe", 1_700_000_000_000, r#"{"event":"ping"}"#),
+        "sha256=d4caf0fecdc86e523417e1cdb31d2a38c73d1f1b31100ddffe3d5ea952e33049",
+    );
+    // 时间戳参与签名，重放旧请求时签名不匹配
+    assert_ne!(sign("Jefe", 1_700_000_000_001, r#"{"event":"ping"}"#), sign("Jefe", 1_700_000_000_000, r#"{"event":"ping"}"#));
+}
+
+#[test]
+fn retry_delay_doubles_up_to_the_limit() {
+    assert_eq!(retry_delay_ms(0), Some(BASE_RETRY_DELAY_MS));
+    assert_eq!(retry_delay_ms(1), Some(30 * 1000));
+    assert_eq!(retry_delay_ms(2), Some(60 * 1000));
+    assert_eq!(retry_delay_ms(3), Some(120 * 1000));
+    let delays: Vec<i64> = (1..MAX_ATTEMPTS).map(|attempts| retry_delay_ms(attempts).unwrap()).collect();
+    assert!(delays.windows(2).all(|pair| pair[1] == (pair[0] * 2).min(MAX_RETRY_DELAY_MS)));
+    assert!(delays.iter().all(|delay| *delay <= MAX_RETRY_DELAY_MS));
+    // 超过最大次数后不再重试
+    assert_eq!(retry_delay_ms(MAX_ATTEMPTS), None);
+    assert_eq!(retry_delay_ms(i32::MAX), None);
+}
+
+#[test]
+fn subscriptions_support_wildcards() {
+    assert!(webhook(&[]).subscribes("tag.deleted"));
+    assert!(webhook(&["*"]).subscribes("group.created"));
+    assert!(webhook(&["todo.created"]).subscribes("todo.created"));
+    assert!(!webhook(&["todo.created"]).subscribes("todo.updated"));
+    let todos = webhook(&["todo.*", "tag.deleted"]);
+    assert!(todos.subscribes("todo.status_changed"));
+    assert!(todos.subscribes("tag.deleted"));
+    assert!(!todos.subscribes("tag.created"));
+    // 前缀必须是完整的一段
+    assert!(!webhook(&["todo.*"]).subscribes("todolist.created"));
+    assert!(!webhook(&["todo.*"]).subscribes("todo"));
+}
+
+#[test]
+fn failed_attempts_back_off_until_given_up() {
+    let conn = memory_db();
+    let events = ["todo.*".to_string()];
+    WebhookRepository::create(&conn, "Todos", "https://example.com/todos", None, &events, true).unwrap();
+    WebhookRepository::create(&conn, "Disabled", "https://example.com/off", None, &[], false).unwrap();
+
+    assert_eq!(enqueue(&conn, webhook_event::TAG_CREATED, &serde_json::json!({})).unwrap(), 0);
+    assert_eq!(enqueue(&conn, webhook_event::TODO_DELETED, &serde_json::json!({ "id": 1 })).unwrap(), 1);
+    let now = Utc::now().timestamp_millis();
+    let item = WebhookRepository::list_due(&conn, now, 10).unwrap().remove(0);
+    let payload: serde_json::Value = serde_json::from_str(&item.payload).unwrap();
+    assert_eq!((payload["event"].as_str(), payload["data"]["id"].as_i64()), (Some("todo.deleted"), Some(1)));
+
+    let failure = || Err(SendError { status: Some(503), message: "HTTP 503".to_string() });
+    assert_eq!(record_attempt(&conn, &item, failure()).unwrap(), DeliveryStatus::Pending);
+    let item = WebhookRepository::list_deliveries(&conn, None, 10).unwrap().remove(0);
+    assert_eq!((item.attempts, item.response_status), (1, Some(503)));
+    assert!(item.next_attempt_at >= now + BASE_RETRY_DELAY_MS);
+    assert!(WebhookRepository::list_due(&conn, now, 10).unwrap().is_empty());
+
+    let last = WebhookDelivery { attempts: MAX_ATTEMPTS - 1, ..item };
+    assert_eq!(record_attempt(&conn, &last, failure()).unwrap(), DeliveryStatus::Failed);
+    let item = WebhookRepository::list_deliveries(&conn, None, 10).unwrap().remove(0);
+    assert_eq!((item.status, item.last_error.as_deref()), (DeliveryStatus::Failed, Some("HTTP 503")));
+}
+
+#[test]
+fn status_changes_queue_an_extra_event() {
+    let conn = memory_db();
+    WebhookRepository::create(&conn, "All", "https://example.com/all", None, &[], true).unwrap();
+    let before = crate::database::repositories::TodoRepository::create(&conn, "Task", None, None, None, None, 0, None)
+        .unwrap();
+    let mut after = before.clone();
+    after.title = "Renamed".to_string();
+    assert_eq!(enqueue_todo_updated(&conn, &before, &after).unwrap(), 1);
+    after.status = crate::models::TodoStatus::Done;
+    assert_eq!(enqueue_todo_updated(&conn, &before, &after).unwrap(), 2);
+
+    let deliveries = WebhookRepository::list_deliveries(&conn, None, 10).unwrap();
+    let changed = deliveries.iter().find(|d| d.event == webhook_event::TODO_STATUS_CHANGED).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&changed.payload).unwrap();
+    assert_eq!(payload["data"]["previous_status"], 0);
+    assert_eq!(payload["data"]["todo"]["title"], "Renamed");
+}
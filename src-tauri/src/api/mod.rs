@@ -0,0 +1,157 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 本地 REST API
+//!
+//! 可选的 HTTP 服务，只监听 127.0.0.1，供脚本、编辑器插件等调用。接口直接调用对应的 Tauri 命令，
+//! 自动化规则、Webhook 和前端刷新事件与在界面中操作时相同。请求需携带设置中生成的
+//! `Authorization: Bearer <token>`，错误以 JSON 返回，`/api/v1/openapi.json` 提供接口描述。
+
+mod routes;
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{oneshot, Mutex};
+
+use crate::config::AppConfig;
+use crate::models::ApiStatus;
+
+/// 默认端口
+pub const DEFAULT_PORT: u16 = 7846;
+
+/// 接口路径前缀
+pub const BASE_PATH: &str = "/api/v1";
+
+/// 停止服务时等待进行中请求完成的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 本地 API 配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 监听端口（只监听 127.0.0.1）
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// 访问令牌，首次启用时生成
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_port(),
+            token: String::new(),
+        }
+    }
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+/// 生成新的访问令牌（两个随机 UUID，共 64 个十六进制字符）
+pub fn generate_token() -> String {
+    format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+/// API 服务状态
+#[derive(Default)]
+pub struct ApiServer {
+    running: Mutex<Option<RunningServer>>,
+}
+
+struct RunningServer {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// 获取配置中的 API 设置
+pub fn current_config(app: &AppHandle) -> ApiConfig {
+    app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| state.lock().ok().map(|config| config.api.clone()))
+        .unwrap_or_default()
+}
+
+/// 注册服务状态，配置启用时启动服务（失败只记录日志）
+pub fn spawn(app: AppHandle) {
+    app.manage(ApiServer::default());
+
+    let config = current_config(&app);
+    if !config.enabled {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = apply(&app, &config).await {
+            tracing::error!("Failed to start local API: {:#}", e);
+        }
+    });
+}
+
+/// 按配置停止、启动或重启服务
+///
+/// 总是先停止正在运行的服务；未启用时只停止
+pub async fn apply(app: &AppHandle, config: &ApiConfig) -> Result<()> {
+    let server = app.try_state::<ApiServer>().context("API server state not available")?;
+    let mut running = server.running.lock().await;
+
+    if let Some(previous) = running.take() {
+        let _ = previous.shutdown.send(());
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, previous.task).await.is_err() {
+            tracing::warn!("Local API did not shut down in time");
+        }
+        tracing::info!("Local API on {} stopped", previous.addr);
+    }
+    if !config.enabled {
+        return Ok(());
+    }
+    if config.token.is_empty() {
+        anyhow::bail!("API token has not been generated");
+    }
+
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, config.port));
+    let listener = tokio::net::TcpListener::bind(addr).await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    let router = routes::router(app.clone(), &config.token, config.port);
+
+    let (shutdown, stopped) = oneshot::channel::<()>();
+    let task = tauri::async_runtime::spawn(async move {
+        let result = axum::serve(listener, router)
+            .with_graceful_shutdown(async {
+                let _ = stopped.await;
+            })
+            .await;
+        if let Err(e) = result {
+            tracing::error!("Local API server failed: {}", e);
+        }
+    });
+
+    tracing::info!("Local API listening on http://{}{}", addr, BASE_PATH);
+    *running = Some(RunningServer { addr, shutdown, task });
+    Ok(())
+}
+
+/// 当前配置和运行状态
+pub async fn status(app: &AppHandle, config: &ApiConfig) -> ApiStatus {
+    let running = match app.try_state::<ApiServer>() {
+        Some(server) => server.running.lock().await.is_some(),
+        None => false,
+    };
+    ApiStatus {
+        enabled: config.enabled,
+        port: config.port,
+        token: config.token.clone(),
+        running,
+        base_url: format!("http://{}:{}{}", Ipv4Addr::LOCALHOST, config.port, BASE_PATH),
+    }
+}
@@ -0,0 +1,374 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 路由和处理函数
+//!
+//! 每个处理函数只做参数转换，然后调用同名的 Tauri 命令；命令返回的错误按类型映射为 HTTP 状态码
+
+use axum::extract::rejection::{JsonRejection, PathRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post, put};
+use axum::{Json, Router};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use rtodo_core::Error;
+use tauri::{AppHandle, Manager};
+
+use super::BASE_PATH;
+use crate::commands::{CommandError, group_commands, stats_commands, step_commands, tag_commands, todo_commands};
+use crate::database::Database;
+use crate::models::{StatsByDate, Tag, TaskGroup, Todo, TodoStats, TodoStep};
+use crate::pojo::request::{GetStatsByDateRequest, GetStatsRequest, GetTodosRequest};
+
+#[derive(Clone)]
+struct ApiState {
+    app: AppHandle,
+    token: Arc<str>,
+}
+
+impl ApiState {
+    fn db(&self) -> tauri::State<'_, Database> {
+        self.app.state::<Database>()
+    }
+}
+
+/// 构建路由；除接口描述外都需要令牌
+pub(super) fn router(app: AppHandle, token: &str, port: u16) -> Router {
+    let state = ApiState { app, token: Arc::from(token) };
+    let spec = Arc::new(openapi_spec(port));
+
+    let protected = Router::new()
+        .route("/todos", get(list_todos).post(create_todo))
+        .route("/todos/{id}", get(get_todo).patch(update_todo).delete(delete_todo))
+        .route("/todos/{id}/status", put(update_todo_status))
+        .route("/todos/{id}/steps", get(list_steps).post(create_step))
+        .route("/steps/{id}", axum::routing::patch(update_step).delete(delete_step))
+        .route("/steps/{id}/toggle", post(toggle_step))
+        .route("/groups", get(list_groups).post(create_group))
+        .route("/groups/{id}", axum::routing::patch(update_group).delete(delete_group))
+        .route("/tags", get(list_tags).post(create_tag))
+        .route("/tags/{id}", axum::routing::patch(update_tag).delete(delete_tag))
+        .route("/stats", get(get_stats))
+        .route("/stats/by-date", get(get_stats_by_date))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+
+    let api = Router::new()
+        .route("/openapi.json", get(move || async move { Json(spec.as_ref().clone()) }))
+        .merge(protected);
+
+    Router::new()
+        .nest(BASE_PATH, api)
+        .fallback(|| async { ApiError::new(StatusCode::NOT_FOUND, "No such endpoint") })
+        .method_not_allowed_fallback(|| async {
+            ApiError::new(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed for this endpoint")
+        })
+}
+
+/// 接口描述，版本号和服务地址在运行时填入
+pub(super) fn openapi_spec(port: u16) -> Value {
+    let mut spec: Value = serde_json::from_str(include_str!("openapi.json"))
+        .expect("openapi.json is valid JSON");
+    spec["info"]["version"] = json!(env!("CARGO_PKG_VERSION"));
+    spec["servers"] = json!([{ "url": format!("http://127.0.0.1:{}{}", port, BASE_PATH) }]);
+    spec
+}
+
+// ==================== 认证 ====================
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if authorized(request.headers(), &state.token) {
+        return next.run(request).await;
+    }
+    let mut response = ApiError::new(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token").into_response();
+    response.headers_mut().insert(header::WWW_AUTHENTICATE, header::HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// 检查 `Authorization: Bearer <token>`
+pub(super) fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    let Some(value) = headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    match value.trim().split_once(' ') {
+        Some((scheme, presented)) if scheme.eq_ignore_ascii_case("bearer") => {
+            !token.is_empty() && constant_time_eq(presented.trim().as_bytes(), token.as_bytes())
+        }
+        _ => false,
+    }
+}
+
+/// 比较时间与内容无关，避免通过响应时间逐字符猜出令牌
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ==================== 错误 ====================
+
+/// JSON 错误响应：`{"error": {"status": 404, "message": "..."}}`
+#[derive(Debug)]
+pub(super) struct ApiError {
+    pub(super) status: StatusCode,
+    pub(super) message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+
+    /// 命令返回的错误
+    pub(super) fn from_command(error: CommandError) -> Self {
+        Self { status: error_status(&error.0), message: error.to_string() }
+    }
+}
+
+/// 按核心错误的类型选择状态码：不存在为 404，参数不合法为 422，冲突为 409，数据库等待解锁为 423，其余为 400
+fn error_status(error: &Error) -> StatusCode {
+    match error.root() {
+        Error::NotFound(_) => StatusCode::NOT_FOUND,
+        Error::InvalidInput(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        Error::Conflict(_) => StatusCode::CONFLICT,
+        Error::Locked => StatusCode::LOCKED,
+        _ => StatusCode::BAD_REQUEST,
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({ "error": { "status": self.status.as_u16(), "message": self.message } });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        Self::new(rejection.status(), rejection.body_text())
+    }
+}
+
+/// 参数错误也以 JSON 返回的提取器
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+struct ApiJson<T>(T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+struct ApiPath<T>(T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+struct ApiQuery<T>(T);
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+fn reply<T>(result: Result<T, CommandError>) -> ApiResult<T> {
+    result.map(Json).map_err(ApiError::from_command)
+}
+
+fn created<T>(result: Result<T, CommandError>) -> Result<(StatusCode, Json<T>), ApiError> {
+    Ok((StatusCode::CREATED, reply(result)?))
+}
+
+fn no_content(result: Result<(), CommandError>) -> Result<StatusCode, ApiError> {
+    result.map(|_| StatusCode::NO_CONTENT).map_err(ApiError::from_command)
+}
+
+/// 把路径中的 ID 写入请求体后反序列化为命令的请求对象
+pub(super) fn with_id<T: DeserializeOwned>(mut body: Value, field: &str, id: i64) -> Result<T, ApiError> {
+    let Some(object) = body.as_object_mut() else {
+        return Err(ApiError::new(StatusCode::BAD_REQUEST, "Request body must be a JSON object"));
+    };
+    object.insert(field.to_string(), json!(id));
+    serde_json::from_value(body)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid request body: {}", e)))
+}
+
+// ==================== 任务 ====================
+
+/// 任务列表的查询参数；`group_ids`、`tag_ids` 为逗号分隔的 ID
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(super) struct TodoQuery {
+    group_id: Option<i64>,
+    tag_id: Option<i64>,
+    group_ids: Option<String>,
+    tag_ids: Option<String>,
+    status: Option<i32>,
+    search: Option<String>,
+    priority: Option<i32>,
+    start_date: Option<i64>,
+    end_date: Option<i64>,
+}
+
+impl TodoQuery {
+    pub(super) fn into_request(self) -> Result<GetTodosRequest, ApiError> {
+        Ok(GetTodosRequest {
+            group_id: self.group_id,
+            tag_id: self.tag_id,
+            group_ids: self.group_ids.as_deref().map(parse_ids).transpose()?,
+            tag_ids: self.tag_ids.as_deref().map(parse_ids).transpose()?,
+            status: self.status,
+            search: self.search,
+            priority: self.priority,
+            start_date: self.start_date,
+            end_date: self.end_date,
+        })
+    }
+}
+
+fn parse_ids(text: &str) -> Result<Vec<i64>, ApiError> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse().map_err(|_| ApiError::new(StatusCode::BAD_REQUEST, format!("Invalid id: {}", s))))
+        .collect()
+}
+
+async fn list_todos(State(state): State<ApiState>, ApiQuery(query): ApiQuery<TodoQuery>) -> ApiResult<Vec<Todo>> {
+    reply(todo_commands::get_todos(query.into_request()?, state.db()).await)
+}
+
+async fn get_todo(State(state): State<ApiState>, ApiPath(id): ApiPath<i64>) -> ApiResult<Todo> {
+    reply(todo_commands::get_todo(id, state.db()).await)
+}
+
+async fn create_todo(State(state): State<ApiState>, ApiJson(body): ApiJson<Value>) -> Result<(StatusCode, Json<Todo>), ApiError> {
+    let payload = serde_json::from_value(body)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid request body: {}", e)))?;
+    created(todo_commands::create_todo(payload, state.app.clone(), state.db()).await)
+}
+
+async fn update_todo(
+    State(state): State<ApiState>,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(body): ApiJson<Value>,
+) -> ApiResult<Todo> {
+    let payload = with_id(body, "id", id)?;
+    reply(todo_commands::update_todo(payload, state.app.clone(), state.db()).await)
+}
+
+async fn delete_todo(State(state): State<ApiState>, ApiPath(id): ApiPath<i64>) -> Result<StatusCode, ApiError> {
+    no_content(todo_commands::delete_todo(id, state.app.clone(), state.db()).await)
+}
+
+async fn update_todo_status(
+    State(state): State<ApiState>,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(body): ApiJson<Value>,
+) -> ApiResult<Todo> {
+    let payload = with_id(body, "id", id)?;
+    reply(todo_commands::update_todo_status(payload, state.app.clone(), state.db()).await)
+}
+
+// ==================== 步骤 ====================
+
+async fn list_steps(State(state): State<ApiState>, ApiPath(todo_id): ApiPath<i64>) -> ApiResult<Vec<TodoStep>> {
+    reply(step_commands::get_todo_steps(todo_id, state.db()).await)
+}
+
+async fn create_step(
+    State(state): State<ApiState>,
+    ApiPath(todo_id): ApiPath<i64>,
+    ApiJson(body): ApiJson<Value>,
+) -> Result<(StatusCode, Json<TodoStep>), ApiError> {
+    let payload = with_id(body, "todo_id", todo_id)?;
+    created(step_commands::create_step(payload, state.app.clone(), state.db()).await)
+}
+
+async fn update_step(
+    State(state): State<ApiState>,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(body): ApiJson<Value>,
+) -> ApiResult<TodoStep> {
+    let payload = with_id(body, "id", id)?;
+    reply(step_commands::update_step(payload, state.app.clone(), state.db()).await)
+}
+
+async fn toggle_step(State(state): State<ApiState>, ApiPath(id): ApiPath<i64>) -> ApiResult<TodoStep> {
+    reply(step_commands::toggle_step(id, state.app.clone(), state.db()).await)
+}
+
+async fn delete_step(State(state): State<ApiState>, ApiPath(id): ApiPath<i64>) -> Result<StatusCode, ApiError> {
+    no_content(step_commands::delete_step(id, state.app.clone(), state.db()).await)
+}
+
+// ==================== 任务组 ====================
+
+async fn list_groups(State(state): State<ApiState>) -> ApiResult<Vec<TaskGroup>> {
+    reply(group_commands::get_task_groups(state.db()).await)
+}
+
+async fn create_group(State(state): State<ApiState>, ApiJson(body): ApiJson<Value>) -> Result<(StatusCode, Json<TaskGroup>), ApiError> {
+    let payload = serde_json::from_value(body)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid request body: {}", e)))?;
+    created(group_commands::create_task_group(payload, state.app.clone(), state.db()).await)
+}
+
+async fn update_group(
+    State(state): State<ApiState>,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(body): ApiJson<Value>,
+) -> ApiResult<TaskGroup> {
+    let payload = with_id(body, "id", id)?;
+    reply(group_commands::update_task_group(payload, state.app.clone(), state.db()).await)
+}
+
+async fn delete_group(State(state): State<ApiState>, ApiPath(id): ApiPath<i64>) -> Result<StatusCode, ApiError> {
+    no_content(group_commands::delete_task_group(id, state.app.clone(), state.db()).await)
+}
+
+// ==================== 标签 ====================
+
+async fn list_tags(State(state): State<ApiState>) -> ApiResult<Vec<Tag>> {
+    reply(tag_commands::get_tags(state.db()).await)
+}
+
+async fn create_tag(State(state): State<ApiState>, ApiJson(body): ApiJson<Value>) -> Result<(StatusCode, Json<Tag>), ApiError> {
+    let payload = serde_json::from_value(body)
+        .map_err(|e| ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid request body: {}", e)))?;
+    created(tag_commands::create_tag(payload, state.app.clone(), state.db()).await)
+}
+
+async fn update_tag(
+    State(state): State<ApiState>,
+    ApiPath(id): ApiPath<i64>,
+    ApiJson(body): ApiJson<Value>,
+) -> ApiResult<Tag> {
+    let payload = with_id(body, "id", id)?;
+    reply(tag_commands::update_tag(payload, state.app.clone(), state.db()).await)
+}
+
+async fn delete_tag(State(state): State<ApiState>, ApiPath(id): ApiPath<i64>) -> Result<StatusCode, ApiError> {
+    no_content(tag_commands::delete_tag(id, state.app.clone(), state.db()).await)
+}
+
+// ==================== 统计 ====================
+
+async fn get_stats(State(state): State<ApiState>, ApiQuery(query): ApiQuery<GetStatsRequest>) -> ApiResult<TodoStats> {
+    reply(stats_commands::get_stats(query, state.db()).await)
+}
+
+async fn get_stats_by_date(
+    State(state): State<ApiState>,
+    ApiQuery(query): ApiQuery<GetStatsByDateRequest>,
+) -> ApiResult<Vec<StatsByDate>> {
+    reply(stats_commands::get_stats_by_date(query, state.db()).await)
+}
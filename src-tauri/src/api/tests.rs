@@ -0,0 +1,115 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use routes::{authorized, openapi_spec, with_id, ApiError, TodoQuery};
+use rtodo_core::Error;
+
+use crate::commands::CommandError;
+use crate::pojo::request::{UpdateStepRequest, UpdateTodoStatusRequest};
+
+fn auth_header(value: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+    headers
+}
+
+#[test]
+fn bearer_token_must_match() {
+    let token = "abc123";
+
+    assert!(authorized(&auth_header("Bearer abc123"), token));
+    assert!(authorized(&auth_header("bearer abc123"), token));
+    assert!(!authorized(&auth_header("Bearer abc124"), token));
+    assert!(!authorized(&auth_header("Bearer abc1234"), token));
+    assert!(!authorized(&auth_header("Basic abc123"), token));
+    assert!(!authorized(&auth_header("abc123"), token));
+    assert!(!authorized(&HeaderMap::new(), token));
+    // 未生成令牌时拒绝所有请求
+    assert!(!authorized(&auth_header("Bearer "), ""));
+}
+
+#[test]
+fn command_errors_map_to_status_codes() {
+    let status = |error: Error| ApiError::from_command(CommandError(error)).status;
+    let context = |error: Error| Error::Context { context: "Failed to get todo".to_string(), error: Box::new(error) };
+
+    assert_eq!(status(Error::NotFound("Todo not found: 7".to_string())), StatusCode::NOT_FOUND);
+    assert_eq!(status(context(Error::NotFound("Todo not found: 7".to_string()))), StatusCode::NOT_FOUND);
+    assert_eq!(status(Error::InvalidInput("Title must not be empty".to_string())), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(status(Error::Conflict("Tag already exists".to_string())), StatusCode::CONFLICT);
+    assert_eq!(status(context(Error::Locked)), StatusCode::LOCKED);
+    assert_eq!(status(Error::Other("Group not found in message only".to_string())), StatusCode::BAD_REQUEST);
+
+    let error = ApiError::from_command(CommandError(context(Error::NotFound("Todo not found: 7".to_string()))));
+    assert_eq!(error.message, "Failed to get todo: Todo not found: 7");
+}
+
+#[test]
+fn path_id_overrides_body() {
+    let request: UpdateTodoStatusRequest = with_id(serde_json::json!({ "id": 99, "status": 2 }), "id", 5).unwrap();
+    assert_eq!(request.id, 5);
+    assert_eq!(request.status, 2);
+
+    let request: UpdateStepRequest = with_id(serde_json::json!({ "is_completed": true }), "id", 3).unwrap();
+    assert_eq!(request.id, 3);
+    assert_eq!(request.is_completed, Some(true));
+
+    let error = with_id::<UpdateStepRequest>(serde_json::json!([1, 2]), "id", 3).unwrap_err();
+    assert_eq!(error.status, StatusCode::BAD_REQUEST);
+    let error = with_id::<UpdateTodoStatusRequest>(serde_json::json!({}), "id", 3).unwrap_err();
+    assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[test]
+fn todo_query_splits_id_lists() {
+    let query: TodoQuery = parse_query("group_ids=1,%202,&status=1");
+    let request = query.into_request().unwrap();
+    assert_eq!(request.group_ids, Some(vec![1, 2]));
+    assert_eq!(request.tag_ids, None);
+    assert_eq!(request.status, Some(1));
+
+    let query: TodoQuery = parse_query("tag_ids=1,x");
+    assert_eq!(query.into_request().unwrap_err().status, StatusCode::BAD_REQUEST);
+}
+
+fn parse_query(query: &str) -> TodoQuery {
+    let uri: axum::http::Uri = format!("/todos?{}", query).parse().unwrap();
+    axum::extract::Query::try_from_uri(&uri).unwrap().0
+}
+
+#[test]
+fn config_defaults_to_disabled() {
+    let config: ApiConfig = serde_json::from_str("{}").unwrap();
+    assert_eq!(config, ApiConfig::default());
+    assert!(!config.enabled);
+    assert_eq!(config.port, DEFAULT_PORT);
+    assert!(config.token.is_empty());
+
+    let token = generate_token();
+    assert_eq!(token.len(), 64);
+    assert_ne!(token, generate_token());
+}
+
+#[test]
+fn openapi_describes_every_route() {
+    let spec = openapi_spec(9000);
+
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(spec["servers"][0]["url"], "http://127.0.0.1:9000/api/v1");
+
+    let operations: Vec<(&str, &str)> = vec![
+        ("/todos", "get"), ("/todos", "post"),
+        ("/todos/{id}", "get"), ("/todos/{id}", "patch"), ("/todos/{id}", "delete"),
+        ("/todos/{id}/status", "put"),
+        ("/todos/{id}/steps", "get"), ("/todos/{id}/steps", "post"),
+        ("/steps/{id}", "patch"), ("/steps/{id}", "delete"), ("/steps/{id}/toggle", "post"),
+        ("/groups", "get"), ("/groups", "post"), ("/groups/{id}", "patch"), ("/groups/{id}", "delete"),
+        ("/tags", "get"), ("/tags", "post"), ("/tags/{id}", "patch"), ("/tags/{id}", "delete"),
+        ("/stats", "get"), ("/stats/by-date", "get"),
+    ];
+    for (path, method) in operations {
+        assert!(spec["paths"][path][method].is_object(), "missing {} {}", method, path);
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 自动备份
+//!
+//! 按配置的间隔定时创建数据库快照（附带附件清单），并在导入、清空、迁移、恢复和修复前创建安全备份。
+//! 旧备份按祖父-父-子策略轮换。启用上传时，定时备份后把备份和附件上传到工作区的远程存储。
+
+pub use rtodo_core::backup::{remote, retention, snapshot};
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::config::AppConfig;
+use crate::database::{Database, DbConnection};
+use crate::models::{BackupInfo, BackupReason, RemoteBackupReport};
+use retention::RetentionPolicy;
+
+/// 定时备份的检查间隔
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// 备份配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// 是否启用定时备份（安全备份始终执行）
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// 定时备份间隔（小时）
+    #[serde(default = "default_interval_hours")]
+    pub interval_hours: u32,
+    /// 备份目录（可选，为空时使用数据目录下的 backups）
+    #[serde(default)]
+    pub directory: Option<String>,
+    /// 保留最近几天的每日备份
+    #[serde(default = "default_keep_daily")]
+    pub keep_daily: u32,
+    /// 保留最近几周的每周备份
+    #[serde(default = "default_keep_weekly")]
+    pub keep_weekly: u32,
+    /// 保留最近几个月的每月备份
+    #[serde(default = "default_keep_monthly")]
+    pub keep_monthly: u32,
+    /// 定时备份后上传到工作区的远程存储
+    #[serde(default)]
+    pub upload: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            interval_hours: default_interval_hours(),
+            directory: None,
+            keep_daily: default_keep_daily(),
+            keep_weekly: default_keep_weekly(),
+            keep_monthly: default_keep_monthly(),
+            upload: false,
+        }
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_interval_hours() -> u32 {
+    24
+}
+
+fn default_keep_daily() -> u32 {
+    7
+}
+
+fn default_keep_weekly() -> u32 {
+    4
+}
+
+fn default_keep_monthly() -> u32 {
+    6
+}
+
+impl BackupConfig {
+    /// 备份目录
+    pub fn backup_dir(&self) -> Result<PathBuf> {
+        Ok(self.backup_dir_in(&DbConnection::get_data_dir()?))
+    }
+
+    /// 数据目录为 `data_dir` 时的备份目录
+    pub fn backup_dir_in(&self, data_dir: &Path) -> PathBuf {
+        match self.directory.as_deref().filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => data_dir.join("backups"),
+        }
+    }
+
+    pub fn retention(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_daily: self.keep_daily,
+            keep_weekly: self.keep_weekly,
+            keep_monthly: self.keep_monthly,
+        }
+    }
+}
+
+/// 读取当前工作区的备份配置
+pub fn current_config(app: &AppHandle) -> BackupConfig {
+    app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| state.lock().ok().map(|config| config.active_workspace().backup.clone()))
+        .unwrap_or_default()
+}
+
+/// 创建备份并按轮换策略清理旧备份
+pub async fn create(app: &AppHandle, db: &Database, reason: BackupReason) -> Result<BackupInfo> {
+    let info = snapshot_now(app, db, reason).await?;
+    prune(app).await;
+    Ok(info)
+}
+
+/// 创建备份，不清理旧备份
+pub async fn snapshot_now(app: &AppHandle, db: &Database, reason: BackupReason) -> Result<BackupInfo> {
+    let backup_dir = current_config(app).backup_dir()?;
+    // SQLCipher 构建通过附加新数据库导出快照，只读连接无法附加可写的数据库
+    let info = db.write(move |conn| snapshot::create_backup(conn, &backup_dir, reason)).await?;
+    Ok(info)
+}
+
+/// 按轮换策略清理旧备份，失败只记录日志
+pub async fn prune(app: &AppHandle) {
+    let config = current_config(app);
+    let pruned = match config.backup_dir() {
+        Ok(backup_dir) => {
+            let policy = config.retention();
+            tokio::task::spawn_blocking(move || prune_dir(&backup_dir, &policy))
+                .await
+                .unwrap_or_else(|e| Err(e.into()))
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = pruned {
+        tracing::warn!("Failed to prune old backups: {}", e);
+    }
+}
+
+/// 删除轮换策略不再保留的备份
+fn prune_dir(backup_dir: &Path, policy: &RetentionPolicy) -> Result<()> {
+    let backups = snapshot::list_backups(backup_dir)?;
+    for name in retention::backups_to_prune(&backups, policy, Utc::now().timestamp_millis()) {
+        snapshot::delete_backup(backup_dir, &name)?;
+    }
+    Ok(())
+}
+
+/// 把备份上传到远程存储，并按轮换策略清理远程的旧备份
+///
+/// 已上传的备份和附件不会重复上传，中断后再次调用即可继续
+pub async fn upload(app: &AppHandle) -> Result<RemoteBackupReport> {
+    let config = current_config(app);
+    let store = crate::remote::open_store(app)?;
+    let backup_dir = config.backup_dir()?;
+    let data_dir = DbConnection::get_data_dir()?;
+    let policy = config.retention();
+
+    let report = tokio::task::spawn_blocking(move || remote::push(&*store, &backup_dir, &data_dir, &policy)).await??;
+    tracing::info!(
+        "Uploaded {} backups ({} attachments), pruned {} remote backups",
+        report.uploaded.len(), report.attachments_uploaded, report.pruned.len(),
+    );
+    Ok(report)
+}
+
+/// 把远程备份下载到本地备份目录，并取回数据目录中缺失的附件，之后按本地备份恢复
+pub async fn download(app: &AppHandle, name: String) -> Result<BackupInfo> {
+    let store = crate::remote::open_store(app)?;
+    let backup_dir = current_config(app).backup_dir()?;
+    let data_dir = DbConnection::get_data_dir()?;
+
+    let info = tokio::task::spawn_blocking(move || remote::download(&*store, &name, &backup_dir, &data_dir)).await??;
+    Ok(info)
+}
+
+/// 启动后台任务，按配置的间隔创建定时备份，启用上传时上传到远程存储
+pub fn spawn_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULE_CHECK_INTERVAL);
+        // 启动时上传一次（补上离线期间的备份），上传失败后在之后的每次检查时重试
+        let mut upload_pending = true;
+        loop {
+            interval.tick().await;
+            match run_scheduled(&app).await {
+                Ok(created) => upload_pending |= created,
+                Err(e) => tracing::warn!("Scheduled backup failed: {:#}", e),
+            }
+            if upload_pending && current_config(&app).upload {
+                match upload(&app).await {
+                    Ok(_) => upload_pending = false,
+                    Err(e) => tracing::warn!("Backup upload failed: {:#}", e),
+                }
+            }
+        }
+    });
+}
+
+/// 到时间时创建定时备份，返回是否创建了备份
+async fn run_scheduled(app: &AppHandle) -> Result<bool> {
+    let config = current_config(app);
+    if !config.enabled {
+        return Ok(false);
+    }
+    let Some(db) = app.try_state::<Database>().filter(|db| !db.is_locked()) else {
+        return Ok(false);
+    };
+
+    let backup_dir = config.backup_dir()?;
+    let backups = tokio::task::spawn_blocking(move || snapshot::list_backups(&backup_dir)).await??;
+    let last_scheduled = backups.iter()
+        .filter(|b| b.reason == BackupReason::Scheduled)
+        .map(|b| b.created_at)
+        .max();
+
+    let interval_ms = i64::from(config.interval_hours.max(1)) * 60 * 60 * 1000;
+    if last_scheduled.is_some_and(|t| Utc::now().timestamp_millis() - t < interval_ms) {
+        return Ok(false);
+    }
+
+    create(app, &db, BackupReason::Scheduled).await?;
+    Ok(true)
+}
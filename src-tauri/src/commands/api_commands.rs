@@ -0,0 +1,78 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 本地 API 命令
+//! 查询状态、启用或关闭服务、修改端口和重新生成令牌，修改后立即生效
+
+use tauri::Manager;
+
+use crate::api::{self, ApiConfig};
+use crate::config::AppConfig;
+use crate::models::ApiStatus;
+use crate::pojo::request::UpdateApiConfigRequest;
+
+/// 保存 API 设置
+fn save_config(app: &tauri::AppHandle, api: ApiConfig) -> Result<(), String> {
+    let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .ok_or_else(|| "Config not initialized".to_string())?;
+    let mut config = config_state.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    config.update_api(api, app)
+}
+
+/// 获取本地 API 设置和运行状态
+#[tauri::command]
+pub async fn get_api_status(app: tauri::AppHandle) -> Result<ApiStatus, String> {
+    let config = api::current_config(&app);
+    Ok(api::status(&app, &config).await)
+}
+
+/// 更新本地 API 设置
+///
+/// 首次启用时生成令牌；新设置无法启动服务（例如端口被占用）时恢复原来的服务并返回错误，设置不保存
+#[tauri::command]
+pub async fn update_api_config(
+    payload: UpdateApiConfigRequest,
+    app: tauri::AppHandle,
+) -> Result<ApiStatus, String> {
+    tracing::info!("update_api_config called: {:?}", payload);
+
+    let previous = api::current_config(&app);
+    let mut config = previous.clone();
+    if let Some(enabled) = payload.enabled {
+        config.enabled = enabled;
+    }
+    if let Some(port) = payload.port {
+        if port == 0 {
+            return Err("Port must be between 1 and 65535".to_string());
+        }
+        config.port = port;
+    }
+    if config.enabled && config.token.is_empty() {
+        config.token = api::generate_token();
+    }
+
+    if let Err(e) = api::apply(&app, &config).await {
+        if let Err(restore) = api::apply(&app, &previous).await {
+            tracing::error!("Failed to restart local API with previous settings: {:#}", restore);
+        }
+        return Err(format!("Failed to apply API settings: {:#}", e));
+    }
+    save_config(&app, config.clone())?;
+    Ok(api::status(&app, &config).await)
+}
+
+/// 重新生成访问令牌，原令牌立即失效
+#[tauri::command]
+pub async fn regenerate_api_token(app: tauri::AppHandle) -> Result<ApiStatus, String> {
+    tracing::info!("regenerate_api_token called");
+
+    let mut config = api::current_config(&app);
+    config.token = api::generate_token();
+    save_config(&app, config.clone())?;
+    if config.enabled {
+        api::apply(&app, &config).await
+            .map_err(|e| format!("Failed to restart local API: {:#}", e))?;
+    }
+    Ok(api::status(&app, &config).await)
+}
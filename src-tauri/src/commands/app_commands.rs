@@ -5,9 +5,11 @@
 //! 处理全局快捷键、窗口显示/隐藏、托盘等功能
 
 use tauri::{AppHandle, Emitter, Manager, State, Window};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use std::collections::BTreeMap;
 use std::sync::Mutex;
 
+use crate::shortcuts::{self, ShortcutAction};
+
 /// 关闭行为选项
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CloseBehavior {
@@ -36,7 +38,8 @@ impl CloseBehavior {
 
 /// 应用程序状态
 pub struct AppState {
-    pub hide_hotkey: Mutex<Option<String>>,
+    /// 已注册的全局快捷键
+    pub shortcuts: Mutex<BTreeMap<ShortcutAction, String>>,
     pub close_behavior: Mutex<CloseBehavior>,
     pub auto_launch: Mutex<bool>,
 }
@@ -44,66 +47,64 @@ pub struct AppState {
 impl AppState {
     pub fn new() -> Self {
         Self {
-            hide_hotkey: Mutex::new(None),
+            shortcuts: Mutex::new(BTreeMap::new()),
             close_behavior: Mutex::new(CloseBehavior::Direct), // 默认直接关闭
             auto_launch: Mutex::new(false), // 默认不开启开机启动
         }
     }
 }
 
-/// 设置全局快捷键
+/// 设置全局快捷键（切换主窗口）
 #[tauri::command]
 pub async fn set_global_shortcut(
     shortcut: String,
     app: AppHandle,
-    state: State<'_, AppState>,
 ) -> Result<(), String> {
     tracing::info!("set_global_shortcut called with: {}", shortcut);
 
-    // 获取全局快捷键管理器
-    let shortcut_manager = app.global_shortcut();
-
-    // 如果之前有注册的快捷键，先取消注册
-    let mut current_hotkey = state.hide_hotkey.lock().map_err(|e| format!("Lock error: {}", e))?;
-    if let Some(old_shortcut) = current_hotkey.take() {
-        // 使用字符串检查和取消注册
-        if shortcut_manager.is_registered(old_shortcut.as_str()) {
-            shortcut_manager
-                .unregister(old_shortcut.as_str())
-                .map_err(|e| format!("Failed to unregister old shortcut: {}", e))?;
-            tracing::info!("Unregistered old shortcut: {}", old_shortcut);
-        }
-    }
+    shortcuts::bind(&app, ShortcutAction::ToggleMainWindow, Some(&shortcut))
+}
 
-    // 注册新的快捷键 - 使用字符串切片
-    shortcut_manager
-        .register(shortcut.as_str())
-        .map_err(|e| format!("Failed to register shortcut: {}", e))?;
+/// 获取当前注册的全局快捷键（切换主窗口）
+#[tauri::command]
+pub async fn get_global_shortcut(
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    let bindings = state.shortcuts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(bindings.get(&ShortcutAction::ToggleMainWindow).cloned())
+}
 
-    // 保存新的快捷键到内存
-    *current_hotkey = Some(shortcut.clone());
+/// 设置某个动作的全局快捷键，`shortcut` 为空时解除绑定
+#[tauri::command]
+pub async fn set_shortcut(
+    action: ShortcutAction,
+    shortcut: Option<String>,
+    app: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("set_shortcut called: action={}, shortcut={:?}", action.as_str(), shortcut);
 
-    // 保存到配置文件
-    if let Some(config_state) = app.try_state::<std::sync::Mutex<crate::config::AppConfig>>() {
-        if let Ok(mut config) = config_state.lock() {
-            let shortcut_to_save = Some(shortcut.clone());
-            config.update_global_shortcut(shortcut_to_save, &app)?;
-            tracing::info!("Saved global shortcut to config: {}", shortcut);
-        }
-    }
+    shortcuts::bind(&app, action, shortcut.as_deref())
+}
 
-    tracing::info!("Registered new shortcut: {}", shortcut);
+/// 获取所有动作的全局快捷键
+#[tauri::command]
+pub async fn get_shortcuts(
+    state: State<'_, AppState>,
+) -> Result<BTreeMap<ShortcutAction, String>, String> {
+    let bindings = state.shortcuts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(bindings.clone())
+}
 
-    Ok(())
+/// 打开/隐藏快速添加窗口
+#[tauri::command]
+pub async fn toggle_quick_add_window(app: AppHandle) -> Result<(), String> {
+    shortcuts::toggle_quick_add_window(&app)
 }
 
-/// 获取当前注册的全局快捷键
+/// 将剪贴板文本创建为任务
 #[tauri::command]
-pub async fn get_global_shortcut(
-    state: State<'_, AppState>,
-) -> Result<Option<String>, String> {
-    let hotkey = state.hide_hotkey.lock().map_err(|e| format!("Lock error: {}", e))?;
-    Ok(hotkey.clone())
+pub async fn capture_clipboard(app: AppHandle) -> Result<(), String> {
+    shortcuts::capture_clipboard(&app).await
 }
 
 /// 显示/隐藏窗口
@@ -177,6 +178,14 @@ pub async fn get_close_behavior(
     Ok(behavior.as_str().to_string())
 }
 
+/// 获取当前数据变更修订号（新打开的窗口据此判断是否错过了事件）
+#[tauri::command]
+pub async fn get_event_revision(
+    bus: State<'_, crate::events::EventBus>,
+) -> Result<u64, String> {
+    Ok(bus.revision())
+}
+
 /// 设置开机启动
 #[tauri::command]
 pub async fn set_auto_launch(
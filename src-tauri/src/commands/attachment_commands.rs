@@ -7,9 +7,12 @@
 use crate::database::Database;
 use crate::database::repositories::AttachmentRepository;
 use crate::database::DbConnection;
+use crate::encryption::{self, EncryptionKey};
 use crate::models::Attachment;
 use crate::pojo::request::DownloadAttachmentRequest;
-use std::path::Path;
+use crate::events::{self, ChangeKind};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::fs;
 use tauri_plugin_shell::ShellExt;
 
@@ -21,12 +24,10 @@ pub async fn get_attachments(
 ) -> Result<Vec<Attachment>, String> {
     tracing::info!("get_attachments called: todo_id={}", todo_id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    AttachmentRepository::list_by_todo(inner, todo_id)
-        .map_err(|e| format!("Failed to get attachments: {}", e))
+    db.read(move |conn| {
+        AttachmentRepository::list_by_todo(conn, todo_id)
+            .map_err(|e| format!("Failed to get attachments: {}", e))
+    }).await
 }
 
 /// 上传附件
@@ -35,6 +36,7 @@ pub async fn upload_attachment(
     todo_id: i64,
     file_path: String,
     file_name: String,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
 ) -> Result<Attachment, String> {
     tracing::info!("upload_attachment called: todo_id={}, file_name={}", todo_id, file_name);
@@ -85,9 +87,11 @@ pub async fn upload_attachment(
     
     let target_path = attachments_dir.join(&hash_filename);
 
-    // 复制文件到附件目录
-    fs::copy(&source_path, &target_path)
-        .map_err(|e| format!("复制文件失败: {}", e))?;
+    // 复制文件到附件目录（启用加密时加密后写入）
+    let contents = fs::read(source_path)
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    encryption::write_file(&target_path, &contents, db.key().as_deref())
+        .map_err(|e| format!("复制文件失败: {:#}", e))?;
 
     // 存储相对路径（使用hash文件名）
     let relative_path = format!("attachments/{}", hash_filename);
@@ -97,44 +101,55 @@ pub async fn upload_attachment(
         .first()
         .map(|m| m.to_string());
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    AttachmentRepository::create(
-        inner,
-        todo_id,
-        &file_name,  // 数据库中存储真实文件名
-        &relative_path,  // 文件系统中使用hash文件名
-        file_size as i64,
-        mime_type.as_deref()
-    ).map_err(|e| {
-        // 失败时删除已复制的文件
-        let _ = fs::remove_file(&target_path);
-        format!("Failed to create attachment record: {}", e)
-    })
+    let attachment = db.write(move |conn| {
+        AttachmentRepository::create(
+            conn,
+            todo_id,
+            &file_name,  // 数据库中存储真实文件名
+            &relative_path,  // 文件系统中使用hash文件名
+            file_size as i64,
+            mime_type.as_deref()
+        ).map_err(|e| {
+            // 失败时删除已复制的文件
+            let _ = fs::remove_file(&target_path);
+            format!("Failed to create attachment record: {}", e)
+        })
+    }).await?;
+
+    events::publish(&app, events::TODO_CHANGED, ChangeKind::Updated, vec![todo_id]);
+    Ok(attachment)
 }
 
 /// 删除附件
 #[tauri::command]
 pub async fn delete_attachment(
     id: i64,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
 ) -> Result<(), String> {
     tracing::info!("delete_attachment called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    // 先查询附件信息，获取文件路径
-    let attachment = AttachmentRepository::get(inner, id)
-        .map_err(|e| format!("查询附件失败: {}", e))?
-        .ok_or_else(|| "附件不存在".to_string())?;
-
-    // 删除数据库记录
-    AttachmentRepository::delete(inner, id)
-        .map_err(|e| format!("删除数据库记录失败: {}", e))?;
+    let (attachment, remaining) = db.write(move |conn| {
+        // 先查询附件信息，获取文件路径
+        let attachment = AttachmentRepository::get(conn, id)
+            .map_err(|e| format!("查询附件失败: {}", e))?
+            .ok_or_else(|| "附件不存在".to_string())?;
+
+        // 删除数据库记录
+        AttachmentRepository::delete(conn, id)
+            .map_err(|e| format!("删除数据库记录失败: {}", e))?;
+
+        // 复制任务时附件文件可能被共享，仍有其他记录引用时保留物理文件
+        let remaining = AttachmentRepository::count_by_file_path(conn, &attachment.file_path)
+            .map_err(|e| format!("查询附件引用失败: {}", e))?;
+        Ok::<_, String>((attachment, remaining))
+    }).await?;
+    events::publish(&app, events::TODO_CHANGED, ChangeKind::Updated, vec![attachment.todo_id]);
+
+    if remaining > 0 {
+        tracing::info!("Attachment file still referenced by {} records, keeping file: {}", remaining, attachment.file_path);
+        return Ok(());
+    }
 
     // 删除物理文件
     let data_dir = DbConnection::get_data_dir()
@@ -161,13 +176,11 @@ pub async fn open_attachment(
 ) -> Result<(), String> {
     tracing::info!("open_attachment called: id={}", attachment_id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    let attachment = AttachmentRepository::get(inner, attachment_id)
-        .map_err(|e| format!("查询附件失败: {}", e))?
-        .ok_or_else(|| "附件不存在".to_string())?;
+    let attachment = db.read(move |conn| {
+        AttachmentRepository::get(conn, attachment_id)
+            .map_err(|e| format!("查询附件失败: {}", e))?
+            .ok_or_else(|| "附件不存在".to_string())
+    }).await?;
 
     let data_dir = DbConnection::get_data_dir()
         .map_err(|e| format!("无法获取数据目录: {}", e))?;
@@ -177,6 +190,13 @@ pub async fn open_attachment(
         return Err("附件文件不存在".to_string());
     }
 
+    // 加密的附件解密到临时目录后再打开
+    let full_path = if encryption::is_encrypted_file(&full_path) {
+        decrypted_copy(&attachment, &full_path, db.key().as_deref())?
+    } else {
+        full_path
+    };
+
     // 使用 shell 插件打开文件
     let path_str = full_path.to_string_lossy().to_string();
     app.shell().open(path_str, None)
@@ -193,13 +213,12 @@ pub async fn download_attachment(
 ) -> Result<(), String> {
     tracing::info!("download_attachment called: id={}, target={}", payload.attachment_id, payload.target_path);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    let attachment = AttachmentRepository::get(inner, payload.attachment_id)
-        .map_err(|e| format!("查询附件失败: {}", e))?
-        .ok_or_else(|| "附件不存在".to_string())?;
+    let attachment_id = payload.attachment_id;
+    let attachment = db.read(move |conn| {
+        AttachmentRepository::get(conn, attachment_id)
+            .map_err(|e| format!("查询附件失败: {}", e))?
+            .ok_or_else(|| "附件不存在".to_string())
+    }).await?;
 
     let data_dir = DbConnection::get_data_dir()
         .map_err(|e| format!("无法获取数据目录: {}", e))?;
@@ -209,10 +228,96 @@ pub async fn download_attachment(
         return Err("附件文件不存在".to_string());
     }
 
-    // 复制文件到目标位置
-    fs::copy(&source_path, &payload.target_path)
+    // 复制文件到目标位置（加密的附件解密后写入）
+    let contents = encryption::read_file(&source_path, db.key().as_deref())
+        .map_err(|e| format!("读取附件失败: {:#}", e))?;
+    fs::write(&payload.target_path, contents)
         .map_err(|e| format!("复制文件失败: {}", e))?;
 
     tracing::info!("Attachment downloaded successfully: id={} to {}", payload.attachment_id, payload.target_path);
     Ok(())
 }
+
+/// 解密后的附件临时目录，打开加密附件时使用
+fn decrypted_dir() -> PathBuf {
+    std::env::temp_dir().join("rtodo-decrypted")
+}
+
+/// 把加密的附件解密到临时目录（保留原文件名，便于系统选择打开方式）
+fn decrypted_copy(attachment: &Attachment, path: &Path, key: Option<&EncryptionKey>) -> Result<PathBuf, String> {
+    let contents = encryption::read_file(path, key)
+        .map_err(|e| format!("读取附件失败: {:#}", e))?;
+
+    let file_name = Path::new(&attachment.name).file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_else(|| attachment.id.to_string().into());
+    let dir = decrypted_dir().join(attachment.id.to_string());
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("无法创建临时目录: {}", e))?;
+    let target = dir.join(file_name);
+    fs::write(&target, contents)
+        .map_err(|e| format!("写入临时文件失败: {}", e))?;
+    Ok(target)
+}
+
+/// 清理上次运行留下的解密副本
+pub(crate) fn clear_decrypted_copies() {
+    let dir = decrypted_dir();
+    if dir.exists() {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            tracing::warn!("Failed to remove decrypted attachments: {}", e);
+        }
+    }
+}
+
+/// 为待复制的附件生成新的物理文件
+///
+/// 返回原附件 ID 到新文件相对路径的映射；任一文件复制失败时会清理已复制的文件
+pub(crate) fn copy_attachment_files(attachments: &[Attachment]) -> Result<HashMap<i64, String>, String> {
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("无法获取数据目录: {}", e))?;
+    let attachments_dir = DbConnection::get_attachments_dir()
+        .map_err(|e| format!("无法获取数据目录: {}", e))?;
+
+    fs::create_dir_all(&attachments_dir)
+        .map_err(|e| format!("无法创建附件目录: {}", e))?;
+
+    let mut copied: HashMap<i64, String> = HashMap::new();
+
+    for attachment in attachments {
+        let source_path = data_dir.join(&attachment.file_path);
+
+        let file_ext = Path::new(&attachment.file_path)
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("");
+        let hash_filename = if file_ext.is_empty() {
+            format!("{}", uuid::Uuid::new_v4())
+        } else {
+            format!("{}.{}", uuid::Uuid::new_v4(), file_ext)
+        };
+
+        if let Err(e) = fs::copy(&source_path, attachments_dir.join(&hash_filename)) {
+            remove_attachment_files(copied.values());
+            return Err(format!("复制附件文件失败 ({}): {}", attachment.name, e));
+        }
+
+        copied.insert(attachment.id, format!("attachments/{}", hash_filename));
+    }
+
+    Ok(copied)
+}
+
+/// 删除数据目录下的附件文件（相对路径），失败仅记录日志
+pub(crate) fn remove_attachment_files<'a>(paths: impl IntoIterator<Item = &'a String>) {
+    let data_dir = match DbConnection::get_data_dir() {
+        Ok(dir) => dir,
+        Err(_) => return,
+    };
+
+    for relative_path in paths {
+        if let Err(e) = fs::remove_file(data_dir.join(relative_path)) {
+            tracing::warn!("Failed to remove attachment file {}: {}", relative_path, e);
+        }
+    }
+}
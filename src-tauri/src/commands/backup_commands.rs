@@ -0,0 +1,164 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::backup::{self, snapshot, BackupConfig};
+use crate::config::AppConfig;
+use crate::database::{Database, DbConnection};
+use crate::events;
+use crate::models::{BackupInfo, BackupReason, BackupVerifyResult};
+use crate::pojo::request::UpdateBackupConfigRequest;
+
+fn backup_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    backup::current_config(app).backup_dir()
+        .map_err(|e| format!("Failed to get backup directory: {}", e))
+}
+
+/// 获取所有备份（按时间倒序）
+#[tauri::command]
+pub async fn get_backups(app: tauri::AppHandle) -> Result<Vec<BackupInfo>, String> {
+    tracing::info!("get_backups called");
+
+    let dir = backup_dir(&app)?;
+    tokio::task::spawn_blocking(move || snapshot::list_backups(&dir))
+        .await
+        .map_err(|e| format!("Failed to list backups: {}", e))?
+        .map_err(|e| format!("Failed to list backups: {}", e))
+}
+
+/// 立即创建手动备份（不会被自动清理）
+#[tauri::command]
+pub async fn create_backup(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<BackupInfo, String> {
+    tracing::info!("create_backup called");
+
+    backup::create(&app, &db, BackupReason::Manual).await
+        .map_err(|e| format!("Failed to create backup: {:#}", e))
+}
+
+/// 校验备份
+#[tauri::command]
+pub async fn verify_backup(
+    name: String,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<BackupVerifyResult, String> {
+    tracing::info!("verify_backup called: name={}", name);
+
+    let dir = backup_dir(&app)?;
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+    let key = db.key();
+    tokio::task::spawn_blocking(move || snapshot::verify_backup(&dir, &name, &data_dir, key.as_deref()))
+        .await
+        .map_err(|e| format!("Failed to verify backup: {}", e))?
+        .map_err(|e| format!("Failed to verify backup: {:#}", e))
+}
+
+/// 从备份恢复数据库
+///
+/// 恢复前会校验备份并为当前数据创建安全备份；附件不在备份中，返回的校验结果列出已缺失或被修改的附件
+#[tauri::command]
+pub async fn restore_backup(
+    name: String,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<BackupVerifyResult, String> {
+    tracing::info!("restore_backup called: name={}", name);
+
+    let result = verify_backup(name.clone(), app.clone(), db.clone()).await?;
+    if !result.valid {
+        return Err(format!("Backup {} cannot be restored: {}", name, result.errors.join("; ")));
+    }
+
+    // 恢复完成后再清理旧备份，避免要恢复的备份被轮换删除
+    backup::snapshot_now(&app, &db, BackupReason::PreRestore).await
+        .map_err(|e| format!("Failed to back up current data before restore: {:#}", e))?;
+
+    let snapshot_path = snapshot::backup_path(&backup_dir(&app)?, &name)
+        .map_err(|e| format!("Failed to restore backup: {}", e))?
+        .join(snapshot::DATABASE_FILE);
+    db.restore_from(snapshot_path).await
+        .map_err(|e| format!("Failed to restore backup: {:#}", e))?;
+
+    events::publish_reload(&app);
+    backup::prune(&app).await;
+    tracing::info!("Restored backup {}", name);
+    Ok(result)
+}
+
+/// 删除备份
+#[tauri::command]
+pub async fn delete_backup(
+    name: String,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    tracing::info!("delete_backup called: name={}", name);
+
+    let dir = backup_dir(&app)?;
+    tokio::task::spawn_blocking(move || snapshot::delete_backup(&dir, &name))
+        .await
+        .map_err(|e| format!("Failed to delete backup: {}", e))?
+        .map_err(|e| format!("Failed to delete backup: {}", e))
+}
+
+/// 获取备份设置（备份目录为空时返回默认路径）
+#[tauri::command]
+pub async fn get_backup_config(app: tauri::AppHandle) -> Result<BackupConfig, String> {
+    let mut config = backup::current_config(&app);
+    if config.directory.is_none() {
+        config.directory = Some(backup_dir(&app)?.to_string_lossy().to_string());
+    }
+    Ok(config)
+}
+
+/// 更新备份设置
+#[tauri::command]
+pub async fn update_backup_config(
+    payload: UpdateBackupConfigRequest,
+    app: tauri::AppHandle,
+) -> Result<BackupConfig, String> {
+    tracing::info!("update_backup_config called: {:?}", payload);
+
+    let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .ok_or_else(|| "Config not initialized".to_string())?;
+    let mut config = config_state.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    let mut backup = config.active_workspace().backup.clone();
+    if let Some(enabled) = payload.enabled {
+        backup.enabled = enabled;
+    }
+    if let Some(hours) = payload.interval_hours {
+        if hours == 0 {
+            return Err("Backup interval must be at least 1 hour".to_string());
+        }
+        backup.interval_hours = hours;
+    }
+    if let Some(directory) = payload.directory {
+        backup.directory = if directory.is_empty() { None } else { Some(directory) };
+    }
+    if let Some(keep) = payload.keep_daily {
+        backup.keep_daily = keep;
+    }
+    if let Some(keep) = payload.keep_weekly {
+        backup.keep_weekly = keep;
+    }
+    if let Some(keep) = payload.keep_monthly {
+        backup.keep_monthly = keep;
+    }
+    if let Some(upload) = payload.upload {
+        if upload && config.active_workspace().remote.is_none() {
+            return Err("Configure remote storage before enabling backup upload".to_string());
+        }
+        backup.upload = upload;
+    }
+
+    config.update_backup(backup.clone(), &app)?;
+    tracing::info!("Backup config updated: {:?}", backup);
+    Ok(backup)
+}
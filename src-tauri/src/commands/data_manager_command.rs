@@ -4,51 +4,67 @@
 //! 数据管理命令
 //! 处理数据的导入、导出和清理操作
 
+use crate::backup;
+use crate::commands::todo_commands;
 use crate::database::Database;
 use crate::database::repositories::DataRepository;
-use crate::database::repositories::{StepRepository, AttachmentRepository};
-use crate::utils::data_export::{
-    export_groups_to_csv,
-    export_tags_to_csv,
-    export_todos_to_csv,
-    export_todo_tags_to_csv,
-    export_steps_to_csv,
-    export_attachments_to_csv,
-    create_zip_archive_with_attachments,
-    extract_csv_from_zip,
-    extract_attachments_from_zip,
-};
-use std::path::PathBuf;
+use crate::events;
+use crate::models::{BackupReason, ExportData, ImportOptions, ImportReport, ImportSource};
+use crate::pojo::request::GetTodosRequest;
+use crate::utils::data_export::{extract_csv_from_zip, extract_attachments_from_zip};
+use std::path::{Path, PathBuf};
 
 /// 导出所有数据为 JSON 格式
 #[tauri::command]
 pub async fn export_all_data(
     db: tauri::State<'_, Database>,
-) -> Result<crate::models::ExportData, String> {
+) -> Result<ExportData, String> {
     tracing::info!("export_all_data called");
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.read(move |conn| {
+        DataRepository::export_all(conn)
+            .map_err(|e| format!("Failed to export data: {}", e))
+    }).await
+}
 
-    DataRepository::export_all(inner)
-        .map_err(|e| format!("Failed to export data: {}", e))
+/// 预览导入数据（JSON 格式）的结果，不写入任何数据
+#[tauri::command]
+pub async fn preview_import(
+    data: ExportData,
+    options: Option<ImportOptions>,
+    db: tauri::State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("preview_import called: version={}, items={}, options={:?}", data.version, data.todos.len(), options);
+
+    db.read(move |conn| {
+        DataRepository::preview_import(conn, &data, options)
+            .map_err(|e| format!("Failed to preview import: {}", e))
+    }).await
 }
 
 /// 导入数据（JSON 格式）
 #[tauri::command]
 pub async fn import_data(
-    data: crate::models::ExportData,
+    data: ExportData,
+    options: Option<ImportOptions>,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<(), String> {
-    tracing::info!("import_data called: version={}, items={}", data.version, data.todos.len());
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("import_data called: version={}, items={}, options={:?}", data.version, data.todos.len(), options);
+
+    // 导入前创建安全备份
+    backup::create(&app, &db, BackupReason::PreImport).await
+        .map_err(|e| format!("Failed to back up before import: {:#}", e))?;
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let report = DataRepository::import_data(conn, &data, options)
+            .map_err(|e| format!("Failed to import data: {}", e))?;
 
-    DataRepository::import_data(inner, &data)
-        .map_err(|e| format!("Failed to import data: {}", e))
+        events::publish_reload(&app);
+        Ok(report)
+    }).await
 }
 
 /// 导出所有数据为 CSV 格式并保存到指定文件（包含附件和步骤）
@@ -59,77 +75,52 @@ pub async fn export_data_as_csv(
 ) -> Result<(), String> {
     tracing::info!("export_data_as_csv called: path={}", file_path);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    // 从数据库导出数据
-    let export_data = DataRepository::export_all(inner)
-        .map_err(|e| format!("Failed to export data: {}", e))?;
-
-    // 转换为 CSV 格式
-    let groups_csv = export_groups_to_csv(&export_data.task_groups)
-        .map_err(|e| format!("Failed to export groups to CSV: {}", e))?;
-    let tags_csv = export_tags_to_csv(&export_data.tags)
-        .map_err(|e| format!("Failed to export tags to CSV: {}", e))?;
-    let todos_csv = export_todos_to_csv(&export_data.todos)
-        .map_err(|e| format!("Failed to export todos to CSV: {}", e))?;
-    let todo_tags_csv = export_todo_tags_to_csv(&export_data.todos)
-        .map_err(|e| format!("Failed to export todo tags to CSV: {}", e))?;
-
-    // 导出步骤数据
-    let steps = StepRepository::list_all(inner)
-        .map_err(|e| format!("Failed to export steps: {}", e))?;
-    let steps_csv = export_steps_to_csv(&steps)
-        .map_err(|e| format!("Failed to export steps to CSV: {}", e))?;
-
-    // 导出附件数据
-    let attachments = AttachmentRepository::list_all(inner)
-        .map_err(|e| format!("Failed to export attachments: {}", e))?;
-    let attachments_csv = export_attachments_to_csv(&attachments)
-        .map_err(|e| format!("Failed to export attachments to CSV: {}", e))?;
-
-    tracing::info!("Exported {} steps and {} attachments", steps.len(), attachments.len());
-
-    // 获取附件目录路径
+    let key = db.key();
     let attachments_dir = crate::database::DbConnection::get_attachments_dir()
         .unwrap_or_else(|_| PathBuf::from("attachments"));
-
-    let attachments_path = if attachments_dir.exists() {
-        Some(attachments_dir.as_path())
-    } else {
-        tracing::info!("Attachments directory does not exist: {}", attachments_dir.display());
-        None
-    };
-
-    // 创建包含附件、步骤和附件表的 ZIP 压缩包
-    let zip_data = create_zip_archive_with_attachments(
-        groups_csv,
-        tags_csv,
-        todos_csv,
-        todo_tags_csv,
-        steps_csv,
-        attachments_csv,
-        attachments_path,
-    )
-    .map_err(|e| format!("Failed to create ZIP: {}", e))?;
+    let zip_data = db.read(move |conn| DataRepository::export_archive(conn, &attachments_dir, key.as_deref()))
+        .await
+        .map_err(|e| format!("Failed to export data: {}", e))?;
 
     // 直接写入文件
     std::fs::write(&file_path, &zip_data)
         .map_err(|e| format!("Failed to write export file: {}", e))?;
 
     tracing::info!("Export completed: {} bytes written to {}", zip_data.len(), file_path);
-
     Ok(())
 }
 
+/// 预览从 CSV 压缩包导入的结果，不写入任何数据
+#[tauri::command]
+pub async fn preview_csv_import(
+    file_path: String,
+    options: Option<ImportOptions>,
+    db: tauri::State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("preview_csv_import called: path={}, options={:?}", file_path, options);
+
+    let file_data = std::fs::read(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let csv_data = extract_csv_from_zip(file_data)
+        .map_err(|e| format!("Failed to extract CSV: {}", e))?;
+
+    db.read(move |conn| {
+        DataRepository::preview_csv_import(conn, &csv_data, options)
+            .map_err(|e| format!("Failed to preview import: {}", e))
+    }).await
+}
+
 /// 从 CSV 压缩包导入数据（通过文件路径，包含附件）
 #[tauri::command]
 pub async fn import_data_from_csv(
     file_path: String,
+    options: Option<ImportOptions>,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<(), String> {
-    tracing::info!("import_data_from_csv called: path={}", file_path);
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("import_data_from_csv called: path={}, options={:?}", file_path, options);
 
     // 读取文件
     let file_data = std::fs::read(&file_path)
@@ -141,46 +132,183 @@ pub async fn import_data_from_csv(
     let csv_data = extract_csv_from_zip(file_data.clone())
         .map_err(|e| format!("Failed to extract CSV: {}", e))?;
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    // 导入前创建安全备份
+    backup::create(&app, &db, BackupReason::PreImport).await
+        .map_err(|e| format!("Failed to back up before import: {:#}", e))?;
 
-    // 使用 repository 层的方法导入数据
-    DataRepository::import_from_csv(inner, &csv_data)
-        .map_err(|e| format!("Failed to import CSV data: {}", e))?;
+    let key = db.key();
+    db.write(move |conn| {
+        // 使用 repository 层的方法导入数据
+        let report = DataRepository::import_from_csv(conn, &csv_data, options)
+            .map_err(|e| format!("Failed to import CSV data: {}", e))?;
 
-    tracing::info!("CSV data imported successfully");
+        tracing::info!("CSV data imported successfully");
 
-    // 导入附件文件
-    let attachments_dir = crate::database::DbConnection::get_attachments_dir()
-        .map_err(|e| format!("Failed to get attachments directory: {}", e))?;
+        // 导入附件文件
+        let attachments_dir = crate::database::DbConnection::get_attachments_dir()
+            .map_err(|e| format!("Failed to get attachments directory: {}", e))?;
 
-    tracing::info!("Extracting attachments to: {}", attachments_dir.display());
+        tracing::info!("Extracting attachments to: {}", attachments_dir.display());
 
-    match extract_attachments_from_zip(file_data, &attachments_dir) {
-        Ok(_) => {
-            tracing::info!("Attachments imported successfully");
+        match extract_attachments_from_zip(file_data, &attachments_dir, key.as_deref()) {
+            Ok(_) => {
+                tracing::info!("Attachments imported successfully");
+            }
+            Err(e) => {
+                // 附件导入失败不应阻止整体导入，记录警告即可
+                tracing::warn!("Failed to import attachments (non-critical): {}", e);
+            }
         }
-        Err(e) => {
-            // 附件导入失败不应阻止整体导入，记录警告即可
-            tracing::warn!("Failed to import attachments (non-critical): {}", e);
-        }
-    }
 
+        events::publish_reload(&app);
+        Ok(report)
+    }).await
+}
+
+/// 读取其他应用的导出文件，返回文件名（不含扩展名）和内容
+fn read_external_file(file_path: &str) -> Result<(String, String), String> {
+    let content = std::fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let name = Path::new(file_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok((name, content))
+}
+
+/// 预览从其他应用（Todoist、Microsoft To Do、TickTick、Taskwarrior）的导出文件导入的结果，不写入任何数据
+#[tauri::command]
+pub async fn preview_external_import(
+    source: ImportSource,
+    file_path: String,
+    options: Option<ImportOptions>,
+    db: tauri::State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("preview_external_import called: source={:?}, path={}, options={:?}", source, file_path, options);
+
+    let (name, content) = read_external_file(&file_path)?;
+    db.read(move |conn| {
+        DataRepository::preview_external_import(conn, source, &name, &content, options)
+            .map_err(|e| format!("Failed to preview import: {}", e))
+    }).await
+}
+
+/// 从其他应用的导出文件导入数据
+#[tauri::command]
+pub async fn import_external_data(
+    source: ImportSource,
+    file_path: String,
+    options: Option<ImportOptions>,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    tracing::info!("import_external_data called: source={:?}, path={}, options={:?}", source, file_path, options);
+
+    let (name, content) = read_external_file(&file_path)?;
+
+    // 导入前创建安全备份
+    backup::create(&app, &db, BackupReason::PreImport).await
+        .map_err(|e| format!("Failed to back up before import: {:#}", e))?;
+
+    db.write(move |conn| {
+        let report = DataRepository::import_external(conn, source, &name, &content, options)
+            .map_err(|e| format!("Failed to import data: {}", e))?;
+
+        events::publish_reload(&app);
+        Ok(report)
+    }).await
+}
+
+/// 把任务导出为 iCalendar 文件（VTODO），可按与任务列表相同的条件筛选
+#[tauri::command]
+pub async fn export_todos_as_ical(
+    file_path: String,
+    filter: Option<GetTodosRequest>,
+    db: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    tracing::info!("export_todos_as_ical called: path={}, filter={:?}", file_path, filter);
+
+    let todos = todo_commands::get_todos(filter.unwrap_or_default(), db.clone()).await?;
+    let count = todos.len();
+    let ics = db.read(move |conn| DataRepository::export_ical(conn, &todos))
+        .await
+        .map_err(|e| format!("Failed to export todos: {}", e))?;
+
+    std::fs::write(&file_path, ics)
+        .map_err(|e| format!("Failed to write export file: {}", e))?;
+
+    tracing::info!("iCalendar export completed: {} todos written to {}", count, file_path);
     Ok(())
 }
 
+/// 预览从 iCalendar 文件导入的结果，不写入任何数据；`with_events` 为 true 时事件也作为任务导入
+#[tauri::command]
+pub async fn preview_ical_import(
+    file_path: String,
+    with_events: Option<bool>,
+    options: Option<ImportOptions>,
+    db: tauri::State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    let with_events = with_events.unwrap_or(false);
+    tracing::info!("preview_ical_import called: path={}, with_events={}, options={:?}", file_path, with_events, options);
+
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    db.read(move |conn| {
+        DataRepository::preview_ical_import(conn, &content, with_events, options)
+            .map_err(|e| format!("Failed to preview import: {}", e))
+    }).await
+}
+
+/// 从 iCalendar 文件导入任务
+#[tauri::command]
+pub async fn import_ical(
+    file_path: String,
+    with_events: Option<bool>,
+    options: Option<ImportOptions>,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<ImportReport, String> {
+    let options = options.unwrap_or_default();
+    let with_events = with_events.unwrap_or(false);
+    tracing::info!("import_ical called: path={}, with_events={}, options={:?}", file_path, with_events, options);
+
+    let content = std::fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    // 导入前创建安全备份
+    backup::create(&app, &db, BackupReason::PreImport).await
+        .map_err(|e| format!("Failed to back up before import: {:#}", e))?;
+
+    db.write(move |conn| {
+        let report = DataRepository::import_ical(conn, &content, with_events, options)
+            .map_err(|e| format!("Failed to import iCalendar file: {}", e))?;
+
+        events::publish_reload(&app);
+        Ok(report)
+    }).await
+}
+
 /// 清空所有数据
 #[tauri::command]
 pub async fn clear_all_data(
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
 ) -> Result<(), String> {
     tracing::info!("clear_all_data called");
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    // 清空前创建安全备份
+    backup::create(&app, &db, BackupReason::PreClear).await
+        .map_err(|e| format!("Failed to back up before clearing data: {:#}", e))?;
+
+    db.write(move |conn| {
+        DataRepository::clear_all(conn)
+            .map_err(|e| format!("Failed to clear data: {}", e))?;
 
-    DataRepository::clear_all(inner)
-        .map_err(|e| format!("Failed to clear data: {}", e))
+        events::publish_reload(&app);
+        Ok(())
+    }).await
 }
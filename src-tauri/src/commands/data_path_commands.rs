@@ -4,33 +4,25 @@
 //! 数据路径管理命令
 //! 提供获取、设置、重置数据路径以及数据迁移功能
 
-use crate::database::DbConnection;
+use crate::backup;
+use crate::database::{paths, Database, DbConnection};
 use crate::config::AppConfig;
+use crate::encryption::{self, EncryptionConfig};
+use crate::events;
 use crate::pojo::request::MigrateDataRequest;
+use crate::workspaces::Workspace;
 use tauri::{AppHandle, Emitter, Manager};
 use std::fs;
 use std::path::PathBuf;
-use rusqlite::Connection;
 
 /// 获取当前数据路径
+///
+/// 返回实际使用的目录；配置的目录在启动时不可用时，这里是回退后的默认目录
 #[tauri::command]
-pub async fn get_data_path(app: AppHandle) -> Result<String, String> {
-    // 尝试从配置中读取自定义路径
-    if let Some(config_state) = app.try_state::<std::sync::Mutex<AppConfig>>() {
-        if let Ok(config) = config_state.lock() {
-            if let Some(custom_path) = &config.data_path {
-                tracing::info!("Using custom data path from config: {}", custom_path);
-                return Ok(custom_path.clone());
-            }
-        }
-    }
-
-    // 如果没有自定义路径，返回默认路径
-    let default_path = DbConnection::get_data_dir()
+pub async fn get_data_path() -> Result<String, String> {
+    DbConnection::get_data_dir()
         .map(|p| p.to_string_lossy().to_string())
-        .map_err(|e| format!("Failed to get data path: {}", e))?;
-    tracing::info!("Using default data path: {}", default_path);
-    Ok(default_path)
+        .map_err(|e| format!("Failed to get data path: {}", e))
 }
 
 /// 检查目录是否为空（忽略系统隐藏文件）
@@ -94,31 +86,17 @@ pub async fn set_data_path(
 
     // 验证路径可写
     let path = PathBuf::from(&newPath);
-    if !path.exists() {
-        fs::create_dir_all(&path)
-            .map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
+    paths::validate_data_dir(&path)
+        .map_err(|e| format!("{:#}", e))?;
 
-    // 测试写入权限
-    let test_file = path.join(".write_test");
-    fs::write(&test_file, b"test")
-        .map_err(|e| format!("Path not writable: {}", e))?;
-    fs::remove_file(&test_file)
-        .map_err(|e| format!("Failed to cleanup test file: {}", e))?;
-
-    // 更新配置
-    if let Some(config_state) = app.try_state::<std::sync::Mutex<AppConfig>>() {
-        if let Ok(mut config) = config_state.lock() {
-            config.data_path = Some(newPath.clone());
-            config.save(&app)?;
-            tracing::info!("Data path updated to: {}", newPath);
-            Ok(())
-        } else {
-            Err("Failed to acquire config lock".to_string())
-        }
-    } else {
-        Err("Config state not available".to_string())
-    }
+    // 在新位置打开数据库（目录为空时创建新数据库）
+    let backup_dir = backup::current_config(&app).backup_dir_in(&path);
+    switch_data_dir(&app, path, backup_dir).await?;
+
+    // 更新当前工作区的配置
+    update_data_path(&app, Some(newPath.clone()))?;
+    tracing::info!("Data path updated to: {}", newPath);
+    Ok(())
 }
 
 /// 重置为默认数据路径
@@ -126,19 +104,15 @@ pub async fn set_data_path(
 pub async fn reset_data_path(app: AppHandle) -> Result<(), String> {
     tracing::info!("reset_data_path called");
 
-    // 更新配置，移除自定义路径
-    if let Some(config_state) = app.try_state::<std::sync::Mutex<AppConfig>>() {
-        if let Ok(mut config) = config_state.lock() {
-            config.data_path = None;
-            config.save(&app)?;
-            tracing::info!("Data path reset to default");
-            Ok(())
-        } else {
-            Err("Failed to acquire config lock".to_string())
-        }
-    } else {
-        Err("Config state not available".to_string())
-    }
+    let data_dir = active_workspace(&app)?.default_data_dir()
+        .map_err(|e| format!("Failed to get data dir: {}", e))?;
+    let backup_dir = backup::current_config(&app).backup_dir_in(&data_dir);
+    switch_data_dir(&app, data_dir, backup_dir).await?;
+
+    // 更新当前工作区的配置，移除自定义路径
+    update_data_path(&app, None)?;
+    tracing::info!("Data path reset to default");
+    Ok(())
 }
 
 /// 迁移数据到新路径
@@ -190,7 +164,7 @@ pub async fn migrate_data(
         }
     };
 
-    // 4. 复制数据库文件（使用 VACUUM INTO 命令）
+    // 4. 复制数据库文件（通过当前连接导出一致的快照，加密数据库的副本使用相同的密钥）
     app.emit("migrate-progress", json!({
         "status": "copying_db",
         "message": "正在复制数据库..."
@@ -204,20 +178,25 @@ pub async fn migrate_data(
 
     let db_target = temp_path.join("rtodo.db");
 
-    // 使用 VACUUM INTO 命令复制数据库
-    // 这是在数据库正在使用时最安全的复制方法
-    Connection::open(&db_source)
-        .and_then(|conn| {
-            // 执行 VACUUM INTO 命令将数据库复制到目标位置
-            // 转义路径中的单引号
-            let escaped_path = db_target.to_string_lossy().replace('\'', "''");
-            conn.execute(&format!("VACUUM INTO '{}'", escaped_path), [])
-        })
+    let db = app.try_state::<Database>()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    let target = db_target.clone();
+    db.write(move |conn| DbConnection::copy_database(conn, &target)).await
         .map_err(|e| {
             cleanup_temp();
-            format!("复制数据库失败: {}", e)
+            format!("复制数据库失败: {:#}", e)
         })?;
 
+    // 加密配置随数据库一起迁移
+    let encryption_source = current_path.join(encryption::CONFIG_FILE);
+    if encryption_source.exists() {
+        fs::copy(&encryption_source, temp_path.join(encryption::CONFIG_FILE))
+            .map_err(|e| {
+                cleanup_temp();
+                format!("复制加密配置失败: {}", e)
+            })?;
+    }
+
     // 5. 复制附件目录（如果存在）
     let attachments_source = current_path.join("attachments");
     if attachments_source.exists() {
@@ -240,10 +219,11 @@ pub async fn migrate_data(
         "message": "正在验证数据..."
     })).map_err(|e| e.to_string())?;
 
-    rusqlite::Connection::open(&db_target)
+    DbConnection::open_reader(&db_target, db.key().as_deref())
+        .and_then(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?))
         .map_err(|e| {
             cleanup_temp();
-            format!("新数据库验证失败: {}", e)
+            format!("新数据库验证失败: {:#}", e)
         })?;
 
     // 7. 原子性替换
@@ -340,13 +320,10 @@ pub async fn migrate_data(
         tracing::info!("Same-device move completed successfully");
     }
 
-    // 8. 更新配置
-    if let Some(config_state) = app.try_state::<std::sync::Mutex<AppConfig>>() {
-        if let Ok(mut config) = config_state.lock() {
-            config.data_path = Some(payload.new_path.clone());
-            config.save(&app)?;
-        }
-    }
+    // 8. 切换到新位置的数据库并更新配置
+    let backup_dir = backup::current_config(&app).backup_dir_in(&target_path);
+    switch_data_dir(&app, target_path.clone(), backup_dir).await?;
+    update_data_path(&app, Some(payload.new_path.clone()))?;
 
     // 9. 根据用户选择决定是否删除原始数据
     // 注意：原子替换操作后，原始位置的数据实际上已经移动到新位置
@@ -382,6 +359,9 @@ pub async fn migrate_data(
             let _ = fs::remove_dir_all(&attachments_source);
             tracing::info!("Attempted to remove original attachments");
         }
+        if encryption_source.exists() {
+            let _ = fs::remove_file(&encryption_source);
+        }
 
         tracing::info!("Cleanup completed");
     } else {
@@ -398,6 +378,51 @@ pub async fn migrate_data(
     Ok(())
 }
 
+/// 切换数据目录，`backup_dir` 为升级 schema 前的备份位置
+///
+/// 先在新位置打开数据库并执行迁移，成功后数据库、附件和默认备份目录都切换到新位置，无需重启。
+/// 新位置已加密且当前密钥不适用时，关闭数据库进入锁定状态，等待用该目录的口令解锁
+pub(crate) async fn switch_data_dir(app: &AppHandle, data_dir: PathBuf, backup_dir: PathBuf) -> Result<(), String> {
+    let db = app.try_state::<Database>()
+        .ok_or_else(|| "Database not initialized".to_string())?;
+    let encryption = EncryptionConfig::load(&data_dir)
+        .map_err(|e| format!("Failed to read encryption config: {:#}", e))?;
+    let key = match &encryption {
+        Some(config) => db.key().filter(|key| config.matches(key)),
+        None => None,
+    };
+    if encryption.is_some() && key.is_none() {
+        if !db.is_locked() {
+            db.close_with(|_| Ok(())).await
+                .map_err(|e| format!("Failed to close database: {:#}", e))?;
+        }
+    } else {
+        db.reopen(data_dir.join("rtodo.db"), Some(backup_dir), key).await
+            .map_err(|e| format!("Failed to open database at {}: {:#}", data_dir.display(), e))?;
+    }
+    paths::set_data_dir(Some(data_dir.clone()));
+
+    events::publish_reload(app);
+    tracing::info!("Switched data directory to {}", data_dir.display());
+    Ok(())
+}
+
+fn active_workspace(app: &AppHandle) -> Result<Workspace, String> {
+    let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .ok_or_else(|| "Config state not available".to_string())?;
+    let config = config_state.lock()
+        .map_err(|_| "Failed to acquire config lock".to_string())?;
+    Ok(config.active_workspace().clone())
+}
+
+fn update_data_path(app: &AppHandle, data_path: Option<String>) -> Result<(), String> {
+    let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .ok_or_else(|| "Config state not available".to_string())?;
+    let mut config = config_state.lock()
+        .map_err(|_| "Failed to acquire config lock".to_string())?;
+    config.update_data_path(data_path, app)
+}
+
 /// 递归复制目录
 fn copy_dir_recursive(source: &PathBuf, target: &PathBuf) -> std::io::Result<()> {
     fs::create_dir_all(target)?;
@@ -0,0 +1,76 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 加密命令
+//! 查询加密状态、用口令解锁数据库，以及启用或关闭加密
+
+use crate::backup;
+use crate::database::{Database, DbConnection};
+use crate::encryption;
+use crate::events;
+use crate::models::EncryptionStatus;
+use crate::pojo::request::SetEncryptionRequest;
+use crate::sync;
+
+/// 获取加密状态
+#[tauri::command]
+pub async fn get_encryption_status(
+    db: tauri::State<'_, Database>,
+) -> Result<EncryptionStatus, String> {
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+
+    Ok(EncryptionStatus {
+        supported: encryption::SUPPORTED,
+        enabled: encryption::is_enabled(&data_dir),
+        locked: db.is_locked(),
+    })
+}
+
+/// 用口令解锁加密的数据库
+#[tauri::command]
+pub async fn unlock_database(
+    passphrase: String,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    tracing::info!("unlock_database called");
+
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+    let backup_dir = backup::current_config(&app).backup_dir_in(&data_dir);
+    encryption::unlock(&db, &data_dir, &passphrase, Some(backup_dir)).await
+        .map_err(|e| format!("Failed to unlock database: {:#}", e))?;
+
+    events::publish_reload(&app);
+    tracing::info!("Database unlocked");
+    Ok(())
+}
+
+/// 启用或关闭加密，就地转换数据库和附件
+///
+/// 已有的备份保持创建时的格式，不会被转换；同步的变更日志不加密，启用同步时不能启用加密
+#[tauri::command]
+pub async fn set_encryption(
+    payload: SetEncryptionRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    tracing::info!("set_encryption called: enabled={}", payload.enabled);
+
+    if payload.enabled && sync::current_config(&app).enabled {
+        return Err("Failed to change encryption: turn off sync first, sync change logs are stored unencrypted".to_string());
+    }
+
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+    let result = if payload.enabled {
+        encryption::enable(&db, &data_dir, &payload.passphrase).await
+    } else {
+        encryption::disable(&db, &data_dir, &payload.passphrase).await
+    };
+    result.map_err(|e| format!("Failed to change encryption: {:#}", e))?;
+
+    events::publish_reload(&app);
+    Ok(())
+}
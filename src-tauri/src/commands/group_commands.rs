@@ -1,84 +1,257 @@
 // Copyright 2025 RTodo Team. All rights reserved.
 // SPDX-License-Identifier: MIT
 
+use rtodo_core::Context;
 use crate::database::Database;
-use crate::database::repositories::GroupRepository;
-use crate::models::TaskGroup;
-use crate::pojo::request::{CreateGroupRequest, UpdateGroupRequest};
+use crate::commands::CommandError;
+use crate::commands::attachment_commands::{copy_attachment_files, remove_attachment_files};
+use crate::database::repositories::{GroupRepository, TodoRepository};
+use crate::models::{TaskGroup, GroupDeleteSummary, GroupTreeNode, webhook_event};
+use crate::pojo::request::{
+    CreateGroupRequest, UpdateGroupRequest, DuplicateGroupRequest,
+    MoveGroupRequest, ReorderGroupsRequest, DeleteGroupRequest,
+};
+use crate::webhooks;
+use crate::events::{self, ChangeKind};
+use std::collections::HashMap;
 
 /// 获取所有任务组
 #[tauri::command]
 pub async fn get_task_groups(
     db: tauri::State<'_, Database>,
-) -> Result<Vec<TaskGroup>, String> {
+) -> Result<Vec<TaskGroup>, CommandError> {
     tracing::info!("get_task_groups called");
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    GroupRepository::list(inner)
-        .map_err(|e| format!("Failed to get task groups: {}", e))
+    db.read(move |conn| {
+        GroupRepository::list(conn)
+            .context("Failed to get task groups")
+    }).await.map_err(CommandError::from)
 }
 
 /// 创建任务组
 #[tauri::command]
 pub async fn create_task_group(
     payload: CreateGroupRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<TaskGroup, String> {
+) -> Result<TaskGroup, CommandError> {
     tracing::info!("create_task_group called: name={}", payload.name);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    GroupRepository::create(
-        inner,
-        &payload.name,
-        payload.parent_id,
-        payload.icon.as_deref(),
-        payload.color.as_deref(),
-    )
-    .map_err(|e| format!("Failed to create task group: {}", e))
+    db.write(move |conn| {
+        let group = GroupRepository::create(
+            conn,
+            &payload.name,
+            payload.parent_id,
+            payload.icon.as_deref(),
+            payload.color.as_deref(),
+        )
+        .context("Failed to create task group")?;
+
+        webhooks::dispatch(&app, conn, webhook_event::GROUP_CREATED, &group);
+        events::publish(&app, events::GROUP_CHANGED, ChangeKind::Created, vec![group.id]);
+        Ok(group)
+    }).await
 }
 
 /// 更新任务组
 #[tauri::command]
 pub async fn update_task_group(
     payload: UpdateGroupRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<TaskGroup, String> {
+) -> Result<TaskGroup, CommandError> {
     let id = payload.id;
     tracing::info!("update_task_group called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    GroupRepository::update(
-        inner,
-        id,
-        payload.name.as_deref(),
-        payload.parent_id,
-        payload.icon.as_deref(),
-        payload.color.as_deref(),
-    )
-    .map_err(|e| format!("Failed to update task group: {}", e))
+    db.write(move |conn| {
+        let group = GroupRepository::update(
+            conn,
+            id,
+            payload.name.as_deref(),
+            payload.parent_id,
+            payload.icon.as_deref(),
+            payload.color.as_deref(),
+        )
+        .context("Failed to update task group")?;
+
+        webhooks::dispatch(&app, conn, webhook_event::GROUP_UPDATED, &group);
+        events::publish(&app, events::GROUP_CHANGED, ChangeKind::Updated, vec![group.id]);
+        Ok(group)
+    }).await
 }
 
 /// 删除任务组
 #[tauri::command]
 pub async fn delete_task_group(
     id: i64,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     tracing::info!("delete_task_group called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let group = GroupRepository::get(conn, id)
+            .context("Failed to get task group")?;
+
+        let summary = GroupRepository::delete(conn, id)
+            .context("Failed to delete task group")?;
+
+        if let Some(group) = group {
+            webhooks::dispatch(&app, conn, webhook_event::GROUP_DELETED, &serde_json::json!({ "group": group }));
+        }
+        events::publish_all(&app, events::Change::group_deleted(&summary));
+        Ok(())
+    }).await
+}
+
+/// 按指定策略删除任务组
+#[tauri::command]
+pub async fn delete_task_group_with_options(
+    payload: DeleteGroupRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<GroupDeleteSummary, CommandError> {
+    tracing::info!(
+        "delete_task_group_with_options called: id={}, child_policy={:?}, todo_policy={:?}",
+        payload.id, payload.child_policy, payload.todo_policy
+    );
+
+    db.write(move |conn| {
+        let group = GroupRepository::get(conn, payload.id)
+            .context("Failed to get task group")?;
+
+        let summary = GroupRepository::delete_with_options(
+            conn,
+            payload.id,
+            payload.child_policy,
+            payload.todo_policy,
+        )
+        .context("Failed to delete task group")?;
+
+        // 数据库已提交后再删除不再被引用的附件文件
+        remove_attachment_files(&summary.orphaned_files);
+
+        if let Some(group) = group {
+            webhooks::dispatch(&app, conn, webhook_event::GROUP_DELETED, &serde_json::json!({
+                "group": group,
+                "summary": summary,
+            }));
+        }
+        events::publish_all(&app, events::Change::group_deleted(&summary));
+
+        Ok(summary)
+    }).await
+}
+
+/// 移动任务组到新的父级及位置
+#[tauri::command]
+pub async fn move_task_group(
+    payload: MoveGroupRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<TaskGroup, CommandError> {
+    tracing::info!(
+        "move_task_group called: id={}, parent_id={:?}, position={:?}",
+        payload.id, payload.parent_id, payload.position
+    );
+
+    db.write(move |conn| {
+        let group = GroupRepository::move_group(conn, payload.id, payload.parent_id, payload.position)
+            .context("Failed to move task group")?;
+
+        webhooks::dispatch(&app, conn, webhook_event::GROUP_UPDATED, &group);
+        events::publish(&app, events::GROUP_CHANGED, ChangeKind::Updated, vec![group.id]);
+        Ok(group)
+    }).await
+}
+
+/// 调整同级任务组的顺序
+#[tauri::command]
+pub async fn reorder_task_groups(
+    payload: ReorderGroupsRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<TaskGroup>, CommandError> {
+    tracing::info!(
+        "reorder_task_groups called: parent_id={:?}, count={}",
+        payload.parent_id, payload.ordered_ids.len()
+    );
+
+    db.write(move |conn| {
+        let groups = GroupRepository::reorder(conn, payload.parent_id, &payload.ordered_ids)
+            .context("Failed to reorder task groups")?;
+
+        for group in &groups {
+            webhooks::dispatch(&app, conn, webhook_event::GROUP_UPDATED, group);
+        }
+        events::publish(&app, events::GROUP_CHANGED, ChangeKind::Updated, groups.iter().map(|g| g.id).collect());
+        Ok(groups)
+    }).await
+}
+
+/// 获取任务组树（含任务数量统计）
+#[tauri::command]
+pub async fn get_group_tree(
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<GroupTreeNode>, CommandError> {
+    tracing::info!("get_group_tree called");
+
+    db.read(move |conn| {
+        GroupRepository::tree(conn)
+            .context("Failed to get group tree")
+    }).await.map_err(CommandError::from)
+}
+
+/// 复制任务组（连同组内所有任务）
+#[tauri::command]
+pub async fn duplicate_task_group(
+    payload: DuplicateGroupRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<TaskGroup, CommandError> {
+    tracing::info!("duplicate_task_group called: id={}, name={:?}", payload.id, payload.name);
+
+    db.write(move |conn| {
+        // 不共享附件时，先复制组内所有任务的附件文件
+        let attachment_paths = if payload.share_attachments {
+            HashMap::new()
+        } else {
+            let todos = TodoRepository::list_with_filters(
+                conn,
+                Some(vec![payload.id]),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            ).context("Failed to get group todos")?;
+            let attachments: Vec<_> = todos.into_iter()
+                .flat_map(|t| t.attachments.unwrap_or_default())
+                .collect();
+            copy_attachment_files(&attachments)?
+        };
+
+        let group = GroupRepository::duplicate(
+            conn,
+            payload.id,
+            payload.name.as_deref(),
+            payload.reset_status,
+            payload.reset_dates,
+            &attachment_paths,
+        )
+        .inspect_err(|_| remove_attachment_files(attachment_paths.values()))
+        .context("Failed to duplicate task group")?;
+
+        webhooks::dispatch(&app, conn, webhook_event::GROUP_CREATED, &group);
+        let todos = TodoRepository::list_with_filters(conn, Some(vec![group.id]), None, None, None, None, None, None)
+            .context("Failed to get group todos")?;
+        for todo in &todos {
+            webhooks::dispatch(&app, conn, webhook_event::TODO_CREATED, todo);
+        }
+        events::publish(&app, events::GROUP_CHANGED, ChangeKind::Created, vec![group.id]);
+        events::publish(&app, events::TODO_CHANGED, ChangeKind::Created, todos.iter().map(|t| t.id).collect());
 
-    GroupRepository::delete(inner, id)
-        .map_err(|e| format!("Failed to delete task group: {}", e))
+        Ok(group)
+    }).await
 }
@@ -0,0 +1,58 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 数据完整性命令
+//! 检查并修复数据库与附件目录中的不一致
+
+use crate::backup;
+use crate::database::{Database, DbConnection};
+use crate::events;
+use crate::integrity;
+use crate::models::{BackupReason, IntegrityRepairResult, IntegrityReport};
+use crate::pojo::request::RepairIntegrityRequest;
+
+/// 检查数据完整性
+#[tauri::command]
+pub async fn check_data_integrity(
+    db: tauri::State<'_, Database>,
+) -> Result<IntegrityReport, String> {
+    tracing::info!("check_data_integrity called");
+
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+    let report = db.read(move |conn| {
+        integrity::check(conn, &data_dir)
+            .map_err(|e| format!("Failed to check data integrity: {:#}", e))
+    }).await?;
+
+    tracing::info!("Integrity check found {} issues", report.issues.len());
+    Ok(report)
+}
+
+/// 修复选中的完整性问题（非预演时先创建安全备份）
+#[tauri::command]
+pub async fn repair_data_integrity(
+    payload: RepairIntegrityRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<IntegrityRepairResult, String> {
+    tracing::info!("repair_data_integrity called: issues={}, dry_run={}", payload.issues.len(), payload.dry_run);
+
+    let data_dir = DbConnection::get_data_dir()
+        .map_err(|e| format!("Failed to get data directory: {}", e))?;
+    if !payload.dry_run {
+        backup::create(&app, &db, BackupReason::PreRepair).await
+            .map_err(|e| format!("Failed to back up data before repair: {:#}", e))?;
+    }
+
+    let dry_run = payload.dry_run;
+    let result = db.write(move |conn| {
+        integrity::repair(conn, &data_dir, &payload.issues, dry_run)
+            .map_err(|e| format!("Failed to repair data integrity: {:#}", e))
+    }).await?;
+
+    if !dry_run && !result.repaired.is_empty() {
+        events::publish_reload(&app);
+    }
+    Ok(result)
+}
@@ -11,3 +11,52 @@ pub mod stats_commands;
 pub mod data_manager_command;
 pub mod app_commands;
 pub mod log_commands;
+pub mod rule_commands;
+pub mod webhook_commands;
+pub mod backup_commands;
+pub mod integrity_commands;
+pub mod encryption_commands;
+pub mod workspace_commands;
+pub mod api_commands;
+pub mod sync_commands;
+pub mod remote_commands;
+
+use rtodo_core::Error;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+/// 保留核心错误类型的命令错误
+///
+/// 前端收到的仍是错误说明文本；HTTP 接口按 [`Error::root`] 的变体选择状态码。
+#[derive(Debug)]
+pub struct CommandError(pub Error);
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<Error> for CommandError {
+    fn from(e: Error) -> Self {
+        Self(e)
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self(Error::Other(message))
+    }
+}
+
+impl From<CommandError> for String {
+    fn from(e: CommandError) -> Self {
+        e.to_string()
+    }
+}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
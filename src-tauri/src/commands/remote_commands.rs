@@ -0,0 +1,129 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 远程存储命令
+//! 设置 WebDAV 服务器或 S3 兼容存储（密码保存在系统钥匙串中）、上传备份、查看和恢复远程备份
+
+use tauri::Manager;
+
+use crate::backup::{self, remote as remote_backup};
+use crate::commands::backup_commands;
+use crate::config::AppConfig;
+use crate::database::Database;
+use crate::models::{BackupInfo, BackupVerifyResult, RemoteBackupReport, RemoteStorageStatus};
+use crate::pojo::request::UpdateRemoteStorageRequest;
+use crate::remote;
+use crate::sync::{self, SyncTarget};
+
+/// 获取当前工作区的远程存储设置
+#[tauri::command]
+pub async fn get_remote_storage(app: tauri::AppHandle) -> Result<RemoteStorageStatus, String> {
+    let (workspace_id, config) = remote::current_config(&app);
+    let password_saved = config.is_some()
+        && remote::load_password(&workspace_id)
+            .map_err(|e| format!("Failed to get remote storage: {:#}", e))?
+            .is_some();
+    Ok(RemoteStorageStatus { config, password_saved })
+}
+
+/// 设置远程存储；先检查能否连接并通过认证，成功后才保存
+#[tauri::command]
+pub async fn update_remote_storage(
+    payload: UpdateRemoteStorageRequest,
+    app: tauri::AppHandle,
+) -> Result<RemoteStorageStatus, String> {
+    tracing::info!("update_remote_storage called: {:?}", payload);
+
+    let (workspace_id, _) = remote::current_config(&app);
+    let password = match payload.password {
+        Some(password) => password,
+        None => remote::load_password(&workspace_id)
+            .map_err(|e| format!("Failed to update remote storage: {:#}", e))?
+            .unwrap_or_default(),
+    };
+
+    let config = payload.config;
+    let check_config = config.clone();
+    let check_password = password.clone();
+    tokio::task::spawn_blocking(move || check_config.check(&check_password))
+        .await
+        .map_err(|e| format!("Failed to connect to remote storage: {}", e))?
+        .map_err(|e| format!("Failed to connect to remote storage: {:#}", e))?;
+
+    remote::save_password(&workspace_id, &password)
+        .map_err(|e| format!("Failed to update remote storage: {:#}", e))?;
+    {
+        let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+            .ok_or_else(|| "Config not initialized".to_string())?;
+        let mut app_config = config_state.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        app_config.update_remote(Some(config.clone()), &app)?;
+    }
+    tracing::info!("Remote storage updated: {}", config.location());
+
+    sync::wake_worker(&app);
+    Ok(RemoteStorageStatus { config: Some(config), password_saved: !password.is_empty() })
+}
+
+/// 移除远程存储和保存的密码，同时关闭依赖它的同步和备份上传
+#[tauri::command]
+pub async fn remove_remote_storage(app: tauri::AppHandle) -> Result<(), String> {
+    tracing::info!("remove_remote_storage called");
+
+    let workspace_id = {
+        let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+            .ok_or_else(|| "Config not initialized".to_string())?;
+        let mut app_config = config_state.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        let workspace = app_config.active_workspace_mut();
+        workspace.remote = None;
+        workspace.backup.upload = false;
+        if workspace.sync.target == SyncTarget::Remote {
+            workspace.sync.enabled = false;
+        }
+        let workspace_id = workspace.id.clone();
+        app_config.save(&app)?;
+        workspace_id
+    };
+
+    remote::delete_password(&workspace_id)
+        .map_err(|e| format!("Failed to remove remote storage: {:#}", e))?;
+    tracing::info!("Remote storage removed");
+    Ok(())
+}
+
+/// 立即把备份上传到远程存储
+#[tauri::command]
+pub async fn upload_backups(app: tauri::AppHandle) -> Result<RemoteBackupReport, String> {
+    tracing::info!("upload_backups called");
+
+    backup::upload(&app).await
+        .map_err(|e| format!("Failed to upload backups: {:#}", e))
+}
+
+/// 获取远程存储中的备份（按时间倒序）
+#[tauri::command]
+pub async fn get_remote_backups(app: tauri::AppHandle) -> Result<Vec<BackupInfo>, String> {
+    tracing::info!("get_remote_backups called");
+
+    let store = remote::open_store(&app)
+        .map_err(|e| format!("Failed to list remote backups: {:#}", e))?;
+    tokio::task::spawn_blocking(move || remote_backup::list(&*store))
+        .await
+        .map_err(|e| format!("Failed to list remote backups: {}", e))?
+        .map_err(|e| format!("Failed to list remote backups: {:#}", e))
+}
+
+/// 从远程存储恢复备份：下载到本地备份目录并取回缺失的附件，再按本地备份恢复
+#[tauri::command]
+pub async fn restore_remote_backup(
+    name: String,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<BackupVerifyResult, String> {
+    tracing::info!("restore_remote_backup called: name={}", name);
+
+    let info = backup::download(&app, name).await
+        .map_err(|e| format!("Failed to download remote backup: {:#}", e))?;
+    backup_commands::restore_backup(info.name, app, db).await
+}
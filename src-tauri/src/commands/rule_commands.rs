@@ -0,0 +1,188 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use rusqlite::Connection;
+use chrono::Utc;
+use tauri::Manager;
+
+use crate::database::Database;
+use crate::database::repositories::RuleRepository;
+use crate::models::{Rule, RuleEvent, RuleExecution, RuleOutcome};
+use crate::config::AppConfig;
+use crate::pojo::request::{CreateRuleRequest, UpdateRuleRequest, TestRuleRequest, GetRuleExecutionsRequest, UpdateReminderConfigRequest};
+use crate::rules::{self, ReminderConfig, RuleEngine};
+
+/// 执行日志默认返回条数
+const DEFAULT_EXECUTION_LIMIT: i64 = 100;
+
+/// 执行事件触发的规则并发送通知，返回任务是否被规则修改
+///
+/// 规则执行失败只记录日志，不影响触发它的操作
+pub(crate) fn apply_rules(app: &tauri::AppHandle, conn: &Connection, todo_id: i64, events: &[RuleEvent]) -> bool {
+    match RuleEngine::run_all(conn, todo_id, events) {
+        Ok(outcomes) => {
+            rules::emit_notifications(app, &outcomes);
+            outcomes.iter().any(|o| o.error.is_none() && !o.applied_actions.is_empty())
+        }
+        Err(e) => {
+            tracing::error!("Failed to run rules for todo {}: {}", todo_id, e);
+            false
+        }
+    }
+}
+
+/// 获取所有规则
+#[tauri::command]
+pub async fn get_rules(
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<Rule>, String> {
+    tracing::info!("get_rules called");
+
+    db.read(move |conn| {
+        RuleRepository::list(conn)
+            .map_err(|e| format!("Failed to get rules: {}", e))
+    }).await
+}
+
+/// 创建规则
+#[tauri::command]
+pub async fn create_rule(
+    payload: CreateRuleRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<Rule, String> {
+    tracing::info!("create_rule called: name={}, trigger={:?}", payload.name, payload.trigger);
+
+    db.write(move |conn| {
+        RuleRepository::create(
+            conn,
+            &payload.name,
+            payload.enabled,
+            &payload.trigger,
+            &payload.conditions,
+            &payload.actions,
+        )
+        .map_err(|e| format!("Failed to create rule: {}", e))
+    }).await
+}
+
+/// 更新规则
+#[tauri::command]
+pub async fn update_rule(
+    payload: UpdateRuleRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<Rule, String> {
+    tracing::info!("update_rule called: id={}", payload.id);
+
+    db.write(move |conn| {
+        RuleRepository::update(
+            conn,
+            payload.id,
+            payload.name.as_deref(),
+            payload.enabled,
+            payload.trigger.as_ref(),
+            payload.conditions.as_deref(),
+            payload.actions.as_deref(),
+            payload.sort_order,
+        )
+        .map_err(|e| format!("Failed to update rule: {}", e))
+    }).await
+}
+
+/// 删除规则
+#[tauri::command]
+pub async fn delete_rule(
+    id: i64,
+    db: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    tracing::info!("delete_rule called: id={}", id);
+
+    db.write(move |conn| {
+        RuleRepository::delete(conn, id)
+            .map_err(|e| format!("Failed to delete rule: {}", e))
+    }).await
+}
+
+/// 获取规则执行日志
+#[tauri::command]
+pub async fn get_rule_executions(
+    payload: GetRuleExecutionsRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<RuleExecution>, String> {
+    tracing::info!("get_rule_executions called: rule_id={:?}, limit={:?}", payload.rule_id, payload.limit);
+
+    db.read(move |conn| {
+        RuleRepository::list_executions(conn, payload.rule_id, payload.limit.unwrap_or(DEFAULT_EXECUTION_LIMIT))
+            .map_err(|e| format!("Failed to get rule executions: {}", e))
+    }).await
+}
+
+/// 试运行规则：对指定任务评估条件并预览动作结果，不写入数据库、不记录日志、不发送通知
+#[tauri::command]
+pub async fn test_rule(
+    payload: TestRuleRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<RuleOutcome, String> {
+    tracing::info!("test_rule called: todo_id={}, rule_id={:?}", payload.todo_id, payload.rule_id);
+
+    // 试运行在回滚的事务中执行动作，需要使用写连接
+    db.write(move |conn| {
+        let rule = match (payload.rule_id, payload.draft) {
+            (_, Some(draft)) => {
+                let now = Utc::now().timestamp_millis();
+                Rule {
+                    id: payload.rule_id.unwrap_or(0),
+                    name: draft.name,
+                    enabled: draft.enabled,
+                    trigger: draft.trigger,
+                    conditions: draft.conditions,
+                    actions: draft.actions,
+                    sort_order: 0,
+                    created_at: now,
+                    updated_at: now,
+                }
+            }
+            (Some(rule_id), None) => RuleRepository::get(conn, rule_id)
+                .map_err(|e| format!("Failed to get rule: {}", e))?
+                .ok_or_else(|| format!("Rule not found: {}", rule_id))?,
+            (None, None) => return Err("Either rule_id or draft is required".to_string()),
+        };
+
+        RuleEngine::dry_run(conn, &rule, payload.todo_id)
+            .map_err(|e| format!("Failed to test rule: {}", e))
+    }).await
+}
+
+/// 获取当前工作区的提醒设置
+#[tauri::command]
+pub async fn get_reminder_config(app: tauri::AppHandle) -> Result<ReminderConfig, String> {
+    Ok(rules::current_reminder_config(&app))
+}
+
+/// 更新当前工作区的提醒设置
+#[tauri::command]
+pub async fn update_reminder_config(
+    payload: UpdateReminderConfigRequest,
+    app: tauri::AppHandle,
+) -> Result<ReminderConfig, String> {
+    tracing::info!("update_reminder_config called: {:?}", payload);
+
+    let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .ok_or_else(|| "Config not initialized".to_string())?;
+    let mut config = config_state.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    let mut reminders = config.active_workspace().reminders.clone();
+    if let Some(enabled) = payload.enabled {
+        reminders.enabled = enabled;
+    }
+    if let Some(minutes) = payload.check_interval_minutes {
+        if minutes == 0 {
+            return Err("Reminder check interval must be at least 1 minute".to_string());
+        }
+        reminders.check_interval_minutes = minutes;
+    }
+
+    config.update_reminders(reminders.clone(), &app)?;
+    tracing::info!("Reminder config updated: {:?}", reminders);
+    Ok(reminders)
+}
@@ -1,89 +1,109 @@
 // Copyright 2025 RTodo Team. All rights reserved.
 // SPDX-License-Identifier: MIT
 
+use rtodo_core::Context;
 use crate::database::Database;
+use crate::commands::CommandError;
 use crate::database::repositories::StepRepository;
-use crate::models::TodoStep;
+use crate::commands::rule_commands::apply_rules;
+use crate::models::{TodoStep, RuleEvent};
 use crate::pojo::request::{CreateStepRequest, UpdateStepRequest};
+use crate::events::{self, ChangeKind};
 
 /// 获取任务的所有步骤
 #[tauri::command]
 pub async fn get_todo_steps(
     todo_id: i64,
     db: tauri::State<'_, Database>,
-) -> Result<Vec<TodoStep>, String> {
+) -> Result<Vec<TodoStep>, CommandError> {
     tracing::info!("get_todo_steps called: todo_id={}", todo_id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    StepRepository::list_by_todo(inner, todo_id)
-        .map_err(|e| format!("Failed to get steps: {}", e))
+    db.read(move |conn| {
+        StepRepository::list_by_todo(conn, todo_id)
+            .context("Failed to get steps")
+    }).await.map_err(CommandError::from)
 }
 
 /// 创建步骤
 #[tauri::command]
 pub async fn create_step(
     payload: CreateStepRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<TodoStep, String> {
+) -> Result<TodoStep, CommandError> {
     tracing::info!("create_step called: todo_id={}, title={}", payload.todo_id, payload.title);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let step = StepRepository::create(conn, payload.todo_id, &payload.title)
+            .context("Failed to create step")?;
 
-    StepRepository::create(inner, payload.todo_id, &payload.title)
-        .map_err(|e| format!("Failed to create step: {}", e))
+        events::publish_steps(&app, ChangeKind::Created, step.todo_id, vec![step.id]);
+        Ok(step)
+    }).await
 }
 
 /// 切换步骤状态
 #[tauri::command]
 pub async fn toggle_step(
     id: i64,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<TodoStep, String> {
+) -> Result<TodoStep, CommandError> {
     tracing::info!("toggle_step called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let step = StepRepository::toggle(conn, id)
+            .context("Failed to toggle step")?;
+
+        // 步骤完成时执行自动化规则
+        events::publish_steps(&app, ChangeKind::Updated, step.todo_id, vec![step.id]);
+        if step.is_completed && apply_rules(&app, conn, step.todo_id, &[RuleEvent::StepCompleted { step_id: step.id }]) {
+            events::publish(&app, events::TODO_CHANGED, ChangeKind::Updated, vec![step.todo_id]);
+        }
 
-    StepRepository::toggle(inner, id)
-        .map_err(|e| format!("Failed to toggle step: {}", e))
+        Ok(step)
+    }).await
 }
 
 /// 更新步骤标题
 #[tauri::command]
 pub async fn update_step(
     payload: UpdateStepRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<TodoStep, String> {
+) -> Result<TodoStep, CommandError> {
     let id = payload.id;
     let title = payload.title.unwrap_or_default();
     tracing::info!("update_step called: id={}, title={}", id, title);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let step = StepRepository::update(conn, id, &title)
+            .context("Failed to update step")?;
 
-    StepRepository::update(inner, id, &title)
-        .map_err(|e| format!("Failed to update step: {}", e))
+        events::publish_steps(&app, ChangeKind::Updated, step.todo_id, vec![step.id]);
+        Ok(step)
+    }).await
 }
 
 /// 删除步骤
 #[tauri::command]
 pub async fn delete_step(
     id: i64,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     tracing::info!("delete_step called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let step = StepRepository::get(conn, id)
+            .context("Failed to get step")?;
+
+        StepRepository::delete(conn, id)
+            .context("Failed to delete step")?;
 
-    StepRepository::delete(inner, id)
-        .map_err(|e| format!("Failed to delete step: {}", e))
+        if let Some(step) = step {
+            events::publish_steps(&app, ChangeKind::Deleted, step.todo_id, vec![step.id]);
+        }
+        Ok(())
+    }).await
 }
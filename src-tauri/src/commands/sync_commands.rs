@@ -0,0 +1,94 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 设备间同步命令
+//! 设置共享目录、立即同步、查看和清除冲突记录
+
+use tauri::Manager;
+
+use crate::config::AppConfig;
+use crate::database::Database;
+use crate::models::{SyncConflict, SyncReport, SyncStatus};
+use crate::pojo::request::UpdateSyncConfigRequest;
+use crate::sync;
+
+/// 获取同步设置和上次同步的结果
+#[tauri::command]
+pub async fn get_sync_status(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<SyncStatus, String> {
+    Ok(sync::status(&app, &db).await)
+}
+
+/// 更新同步设置，启用时立即同步一次
+///
+/// 启用时共享目录必须已经存在，或已配置远程存储；启用了加密的工作区不能启用同步
+#[tauri::command]
+pub async fn update_sync_config(
+    payload: UpdateSyncConfigRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<SyncStatus, String> {
+    tracing::info!("update_sync_config called: {:?}", payload);
+
+    let mut config = sync::current_config(&app);
+    if let Some(enabled) = payload.enabled {
+        config.enabled = enabled;
+    }
+    if let Some(target) = payload.target {
+        config.target = target;
+    }
+    if let Some(folder) = payload.folder {
+        let folder = folder.trim().to_string();
+        config.folder = if folder.is_empty() { None } else { Some(folder) };
+    }
+    if config.enabled {
+        if sync::is_encrypted(&db) {
+            return Err(format!("Failed to enable sync: {}", rtodo_core::Error::SyncEncrypted));
+        }
+        sync::open_store(&app, &config).map_err(|e| format!("Failed to enable sync: {:#}", e))?;
+    }
+
+    {
+        let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+            .ok_or_else(|| "Config not initialized".to_string())?;
+        let mut app_config = config_state.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        app_config.update_sync(config.clone(), &app)?;
+    }
+    tracing::info!("Sync config updated: {:?}", config);
+
+    sync::wake_worker(&app);
+    Ok(sync::status(&app, &db).await)
+}
+
+/// 立即同步
+#[tauri::command]
+pub async fn sync_now(
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<SyncReport, String> {
+    tracing::info!("sync_now called");
+
+    sync::run(&app, &db).await
+        .map_err(|e| format!("Failed to sync: {:#}", e))
+}
+
+/// 获取冲突记录（最新的在前）
+#[tauri::command]
+pub async fn get_sync_conflicts(db: tauri::State<'_, Database>) -> Result<Vec<SyncConflict>, String> {
+    tracing::info!("get_sync_conflicts called");
+
+    db.read(sync::list_conflicts).await
+        .map_err(|e| format!("Failed to get sync conflicts: {}", e))
+}
+
+/// 清除所有冲突记录，返回清除的条数
+#[tauri::command]
+pub async fn clear_sync_conflicts(db: tauri::State<'_, Database>) -> Result<usize, String> {
+    tracing::info!("clear_sync_conflicts called");
+
+    db.write(sync::clear_conflicts).await
+        .map_err(|e| format!("Failed to clear sync conflicts: {}", e))
+}
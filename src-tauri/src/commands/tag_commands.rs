@@ -1,76 +1,142 @@
 // Copyright 2025 RTodo Team. All rights reserved.
 // SPDX-License-Identifier: MIT
 
+use rtodo_core::Context;
 use crate::database::Database;
+use crate::commands::CommandError;
 use crate::database::repositories::TagRepository;
-use crate::models::Tag;
-use crate::pojo::request::{CreateTagRequest, UpdateTagRequest};
+use crate::commands::rule_commands::apply_rules;
+use crate::models::{Tag, RuleEvent, webhook_event};
+use crate::webhooks;
+use crate::events::{self, ChangeKind};
+use crate::pojo::request::{CreateTagRequest, UpdateTagRequest, MergeTagsRequest};
 
 /// 获取所有标签
 #[tauri::command]
 pub async fn get_tags(
     db: tauri::State<'_, Database>,
-) -> Result<Vec<Tag>, String> {
+) -> Result<Vec<Tag>, CommandError> {
     tracing::info!("get_tags called");
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    TagRepository::list(inner)
-        .map_err(|e| format!("Failed to get tags: {}", e))
+    db.read(move |conn| {
+        TagRepository::list(conn)
+            .context("Failed to get tags")
+    }).await.map_err(CommandError::from)
 }
 
 /// 创建标签
 #[tauri::command]
 pub async fn create_tag(
     payload: CreateTagRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<Tag, String> {
+) -> Result<Tag, CommandError> {
     tracing::info!("create_tag called: name={}", payload.name);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let tag = TagRepository::create(conn, &payload.name, &payload.color, payload.parent_id)
+            .context("Failed to create tag")?;
 
-    TagRepository::create(inner, &payload.name, &payload.color)
-        .map_err(|e| format!("Failed to create tag: {}", e))
+        webhooks::dispatch(&app, conn, webhook_event::TAG_CREATED, &tag);
+        events::publish(&app, events::TAG_CHANGED, ChangeKind::Created, vec![tag.id]);
+        Ok(tag)
+    }).await
 }
 
 /// 更新标签
 #[tauri::command]
 pub async fn update_tag(
     payload: UpdateTagRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<Tag, String> {
+) -> Result<Tag, CommandError> {
     let id = payload.id;
     tracing::info!("update_tag called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    TagRepository::update(
-        inner,
-        id,
-        payload.name.as_deref(),
-        payload.color.as_deref(),
-    )
-    .map_err(|e| format!("Failed to update tag: {}", e))
+    db.write(move |conn| {
+        let tag = TagRepository::update(
+            conn,
+            id,
+            payload.name.as_deref(),
+            payload.color.as_deref(),
+            payload.parent_id,
+        )
+        .context("Failed to update tag")?;
+
+        webhooks::dispatch(&app, conn, webhook_event::TAG_UPDATED, &tag);
+        events::publish(&app, events::TAG_CHANGED, ChangeKind::Updated, vec![tag.id]);
+        Ok(tag)
+    }).await
 }
 
 /// 删除标签
 #[tauri::command]
 pub async fn delete_tag(
     id: i64,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     tracing::info!("delete_tag called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let tag = TagRepository::get(conn, id)
+            .context("Failed to get tag")?;
+
+        // 子标签会被移到上一级
+        let children: Vec<i64> = TagRepository::list(conn)
+            .context("Failed to get tags")?
+            .into_iter()
+            .filter(|t| t.parent_id == Some(id))
+            .map(|t| t.id)
+            .collect();
+
+        TagRepository::delete(conn, id)
+            .context("Failed to delete tag")?;
+
+        if let Some(tag) = tag {
+            webhooks::dispatch(&app, conn, webhook_event::TAG_DELETED, &tag);
+        }
+        events::publish(&app, events::TAG_CHANGED, ChangeKind::Deleted, vec![id]);
+        events::publish(&app, events::TAG_CHANGED, ChangeKind::Updated, children);
+        Ok(())
+    }).await
+}
+
+/// 合并标签
+#[tauri::command]
+pub async fn merge_tags(
+    payload: MergeTagsRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Tag, CommandError> {
+    tracing::info!("merge_tags called: target_id={}, source_ids={:?}", payload.target_id, payload.source_ids);
+
+    db.write(move |conn| {
+        let mut sources = Vec::new();
+        for sid in payload.source_ids.iter().filter(|sid| **sid != payload.target_id) {
+            if let Some(source) = TagRepository::get(conn, *sid).context("Failed to get tag")? {
+                sources.push(source);
+            }
+        }
+
+        let (tag, newly_tagged) = TagRepository::merge(conn, payload.target_id, &payload.source_ids)
+            .context("Failed to merge tags")?;
+
+        for source in &sources {
+            webhooks::dispatch(&app, conn, webhook_event::TAG_DELETED, &serde_json::json!({
+                "tag": source,
+                "merged_into": tag.id,
+            }));
+        }
+        webhooks::dispatch(&app, conn, webhook_event::TAG_UPDATED, &tag);
+
+        // 合并后新获得该标签的任务触发“添加标签”规则
+        for todo_id in &newly_tagged {
+            apply_rules(&app, conn, *todo_id, &[RuleEvent::TagAdded { tag_id: tag.id }]);
+        }
+
+        events::publish_all(&app, events::Change::tags_merged(sources.iter().map(|t| t.id).collect(), tag.id, newly_tagged));
 
-    TagRepository::delete(inner, id)
-        .map_err(|e| format!("Failed to delete tag: {}", e))
+        Ok(tag)
+    }).await
 }
@@ -1,17 +1,25 @@
 // Copyright 2025 RTodo Team. All rights reserved.
 // SPDX-License-Identifier: MIT
 
+use rtodo_core::Context;
 use crate::database::Database;
+use crate::commands::CommandError;
+use crate::commands::attachment_commands::{copy_attachment_files, remove_attachment_files};
 use crate::database::repositories::TodoRepository;
-use crate::models::Todo;
-use crate::pojo::request::{CreateTodoRequest, UpdateTodoRequest, UpdateTodoStatusRequest, GetTodosRequest};
+use crate::commands::rule_commands::apply_rules;
+use crate::models::{Todo, RuleEvent, webhook_event};
+use crate::rules::RuleEngine;
+use crate::webhooks;
+use crate::events::{self, ChangeKind};
+use crate::pojo::request::{CreateTodoRequest, UpdateTodoRequest, UpdateTodoStatusRequest, GetTodosRequest, DuplicateTodoRequest};
+use std::collections::HashMap;
 
 /// 获取任务列表
 #[tauri::command]
 pub async fn get_todos(
     payload: GetTodosRequest,
     db: tauri::State<'_, Database>,
-) -> Result<Vec<Todo>, String> {
+) -> Result<Vec<Todo>, CommandError> {
     tracing::info!("get_todos called: group_id={:?}, tag_id={:?}, group_ids={:?}, tag_ids={:?}, status={:?}, search={:?}, priority={:?}, start_date={:?}, end_date={:?}",
         payload.group_id, payload.tag_id, payload.group_ids, payload.tag_ids, payload.status, payload.search, payload.priority, payload.start_date, payload.end_date);
 
@@ -32,26 +40,23 @@ pub async fn get_todos(
         None
     };
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    let result = TodoRepository::list_with_filters(
-        inner,
-        group_ids,
-        tag_ids,
-        payload.status,
-        payload.search.as_deref(),
-        payload.priority,
-        payload.start_date,
-        payload.end_date,
-    ).map_err(|e| {
-        tracing::error!("get_todos failed: {}", e);
-        format!("Failed to get todos: {}", e)
-    })?;
-
-    tracing::info!("get_todos returned {} todos", result.len());
-    Ok(result)
+    db.read(move |conn| {
+        let result = TodoRepository::list_with_filters(
+            conn,
+            group_ids,
+            tag_ids,
+            payload.status,
+            payload.search.as_deref(),
+            payload.priority,
+            payload.start_date,
+            payload.end_date,
+        ).inspect_err(|e| {
+            tracing::error!("get_todos failed: {}", e);
+        }).context("Failed to get todos")?;
+
+        tracing::info!("get_todos returned {} todos", result.len());
+        Ok(result)
+    }).await
 }
 
 /// 获取单个任务详情
@@ -59,33 +64,31 @@ pub async fn get_todos(
 pub async fn get_todo(
     id: i64,
     db: tauri::State<'_, Database>,
-) -> Result<Todo, String> {
+) -> Result<Todo, CommandError> {
     tracing::info!("get_todo called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    let result = TodoRepository::get(inner, id)
-        .map_err(|e| {
-            tracing::error!("get_todo failed for id={}: {}", id, e);
-            format!("Failed to get todo: {}", e)
-        })?
-        .ok_or_else(|| {
-            tracing::warn!("get_todo: todo not found: {}", id);
-            format!("Todo not found: {}", id)
-        })?;
-
-    tracing::info!("get_todo returned todo: id={}, title={}", result.id, result.title);
-    Ok(result)
+    db.read(move |conn| {
+        let result = TodoRepository::get(conn, id)
+            .inspect_err(|e| {
+                tracing::error!("get_todo failed for id={}: {}", id, e);
+            }).context("Failed to get todo")?
+            .with_context(|| {
+                tracing::warn!("get_todo: todo not found: {}", id);
+                format!("Todo not found: {}", id)
+            })?;
+
+        tracing::info!("get_todo returned todo: id={}, title={}", result.id, result.title);
+        Ok(result)
+    }).await
 }
 
 /// 创建任务
 #[tauri::command]
 pub async fn create_todo(
     payload: CreateTodoRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<Todo, String> {
+) -> Result<Todo, CommandError> {
     tracing::info!("create_todo called:");
     tracing::info!("  title: {}", payload.title);
     tracing::info!("  description: {:?}", payload.description);
@@ -95,114 +98,203 @@ pub async fn create_todo(
     tracing::info!("  priority: {:?}", payload.priority);
     tracing::info!("  tag_ids: {:?}", payload.tag_ids);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    let result = TodoRepository::create(
-        inner,
-        &payload.title,
-        payload.description.as_deref(),
-        payload.group_id,
-        payload.start_date,
-        payload.due_date,
-        payload.priority.unwrap_or(0),
-        payload.tag_ids,
-    ).map_err(|e| {
-        tracing::error!("create_todo failed: {}", e);
-        format!("Failed to create todo: {}", e)
-    })?;
-
-    tracing::info!("create_todo succeeded:");
-    tracing::info!("  id: {}", result.id);
-    tracing::info!("  title: {}", result.title);
-    tracing::info!("  group_id: {:?}", result.group_id);
-    tracing::info!("  tags: {:?}", result.tags.as_ref().map(|t| t.len()).unwrap_or(0));
-    Ok(result)
+    db.write(move |conn| {
+        let result = TodoRepository::create(
+            conn,
+            &payload.title,
+            payload.description.as_deref(),
+            payload.group_id,
+            payload.start_date,
+            payload.due_date,
+            payload.priority.unwrap_or(0),
+            payload.tag_ids,
+        ).inspect_err(|e| {
+            tracing::error!("create_todo failed: {}", e);
+        }).context("Failed to create todo")?;
+
+        // 执行自动化规则
+        let mut events = vec![RuleEvent::Created];
+        events.extend(result.tags.iter().flatten().map(|t| RuleEvent::TagAdded { tag_id: t.id }));
+        let result = if apply_rules(&app, conn, result.id, &events) {
+            TodoRepository::get(conn, result.id)
+                .context("Failed to get todo")?
+                .unwrap_or(result)
+        } else {
+            result
+        };
+
+        webhooks::dispatch(&app, conn, webhook_event::TODO_CREATED, &result);
+        events::publish(&app, events::TODO_CHANGED, ChangeKind::Created, vec![result.id]);
+
+        tracing::info!("create_todo succeeded:");
+        tracing::info!("  id: {}", result.id);
+        tracing::info!("  title: {}", result.title);
+        tracing::info!("  group_id: {:?}", result.group_id);
+        tracing::info!("  tags: {:?}", result.tags.as_ref().map(|t| t.len()).unwrap_or(0));
+        Ok(result)
+    }).await
 }
 
 /// 更新任务
 #[tauri::command]
 pub async fn update_todo(
     payload: UpdateTodoRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<Todo, String> {
+) -> Result<Todo, CommandError> {
     tracing::info!("update_todo called: id={}, title={:?}, start_date={:?}, due_date={:?}, status={:?}",
         payload.id, payload.title, payload.start_date, payload.due_date, payload.status);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
-
-    // 将 Option<String> 转换为 Option<Option<String>> 用于表示是否需要更新
-    let desc_opt: Option<Option<String>> = payload.description.map(Some);
-    let group_opt: Option<Option<i64>> = payload.group_id.map(Some);
-    let assignee_opt: Option<Option<String>> = payload.assignee.map(Some);
-    let start_opt: Option<Option<i64>> = payload.start_date.map(Some);
-    let due_opt: Option<Option<i64>> = payload.due_date.map(Some);
-
-    let result = TodoRepository::update(
-        inner,
-        payload.id,
-        payload.title.as_deref(),
-        desc_opt,
-        payload.status.map(|s| s as i32),
-        payload.priority,
-        group_opt,
-        assignee_opt,
-        start_opt,
-        due_opt,
-        payload.tag_ids,
-    ).map_err(|e| {
-        tracing::error!("update_todo failed for id={}: {}", payload.id, e);
-        format!("Failed to update todo: {}", e)
-    })?;
-
-    tracing::info!("update_todo succeeded: id={}, start_date={:?}, due_date={:?}",
-        result.id, result.start_date, result.due_date);
-    Ok(result)
+    db.write(move |conn| {
+        let before = TodoRepository::get(conn, payload.id)
+            .context("Failed to get todo")?
+            .with_context(|| format!("Todo not found: {}", payload.id))?;
+
+        // 将 Option<String> 转换为 Option<Option<String>> 用于表示是否需要更新
+        let desc_opt: Option<Option<String>> = payload.description.map(Some);
+        let group_opt: Option<Option<i64>> = payload.group_id.map(Some);
+        let assignee_opt: Option<Option<String>> = payload.assignee.map(Some);
+        let start_opt: Option<Option<i64>> = payload.start_date.map(Some);
+        let due_opt: Option<Option<i64>> = payload.due_date.map(Some);
+
+        let result = TodoRepository::update(
+            conn,
+            payload.id,
+            payload.title.as_deref(),
+            desc_opt,
+            payload.status.map(|s| s as i32),
+            payload.priority,
+            group_opt,
+            assignee_opt,
+            start_opt,
+            due_opt,
+            payload.tag_ids,
+        ).inspect_err(|e| {
+            tracing::error!("update_todo failed for id={}: {}", payload.id, e);
+        }).context("Failed to update todo")?;
+
+        // 执行自动化规则（状态变化、新增标签）
+        let events = RuleEngine::diff_events(&before, &result);
+        let result = if apply_rules(&app, conn, result.id, &events) {
+            TodoRepository::get(conn, result.id)
+                .context("Failed to get todo")?
+                .unwrap_or(result)
+        } else {
+            result
+        };
+
+        webhooks::dispatch_todo_updated(&app, conn, &before, &result);
+        events::publish(&app, events::TODO_CHANGED, ChangeKind::Updated, vec![result.id]);
+
+        tracing::info!("update_todo succeeded: id={}, start_date={:?}, due_date={:?}",
+            result.id, result.start_date, result.due_date);
+        Ok(result)
+    }).await
 }
 
 /// 删除任务
 #[tauri::command]
 pub async fn delete_todo(
     id: i64,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     tracing::info!("delete_todo called: id={}", id);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let before = TodoRepository::get(conn, id)
+            .context("Failed to get todo")?;
+
+        TodoRepository::delete(conn, id)
+            .inspect_err(|e| {
+                tracing::error!("delete_todo failed for id={}: {}", id, e);
+            }).context("Failed to delete todo")?;
 
-    TodoRepository::delete(inner, id)
-        .map_err(|e| {
-            tracing::error!("delete_todo failed for id={}: {}", id, e);
-            format!("Failed to delete todo: {}", e)
-        })?;
+        if let Some(todo) = before {
+            webhooks::dispatch(&app, conn, webhook_event::TODO_DELETED, &todo);
+        }
+        events::publish(&app, events::TODO_CHANGED, ChangeKind::Deleted, vec![id]);
 
-    tracing::info!("delete_todo succeeded: id={}", id);
-    Ok(())
+        tracing::info!("delete_todo succeeded: id={}", id);
+        Ok(())
+    }).await
 }
 
 /// 更新任务状态
 #[tauri::command]
 pub async fn update_todo_status(
     payload: UpdateTodoStatusRequest,
+    app: tauri::AppHandle,
     db: tauri::State<'_, Database>,
-) -> Result<Todo, String> {
+) -> Result<Todo, CommandError> {
     tracing::info!("update_todo_status called: id={}, status={}", payload.id, payload.status);
 
-    let conn = db.get_connection().await;
-    let conn_guard = conn.lock().await;
-    let inner = conn_guard.inner();
+    db.write(move |conn| {
+        let before = TodoRepository::get(conn, payload.id)
+            .context("Failed to get todo")?
+            .with_context(|| format!("Todo not found: {}", payload.id))?;
+
+        let result = TodoRepository::update_status(conn, payload.id, payload.status)
+            .inspect_err(|e| {
+                tracing::error!("update_todo_status failed for id={}: {}", payload.id, e);
+            }).context("Failed to update todo status")?;
+
+        // 执行自动化规则
+        let events = RuleEngine::diff_events(&before, &result);
+        let result = if apply_rules(&app, conn, result.id, &events) {
+            TodoRepository::get(conn, result.id)
+                .context("Failed to get todo")?
+                .unwrap_or(result)
+        } else {
+            result
+        };
+
+        webhooks::dispatch_todo_updated(&app, conn, &before, &result);
+        events::publish(&app, events::TODO_CHANGED, ChangeKind::Updated, vec![result.id]);
+
+        tracing::info!("update_todo_status succeeded: id={}, status={}", result.id, result.status as i32);
+        Ok(result)
+    }).await
+}
+
+/// 复制任务（包含步骤、标签和附件）
+#[tauri::command]
+pub async fn duplicate_todo(
+    payload: DuplicateTodoRequest,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<Todo, CommandError> {
+    tracing::info!("duplicate_todo called: id={}, target_group_id={:?}, reset_status={}, reset_dates={}, share_attachments={}",
+        payload.id, payload.target_group_id, payload.reset_status, payload.reset_dates, payload.share_attachments);
+
+    db.write(move |conn| {
+        let source = TodoRepository::get(conn, payload.id)
+            .context("Failed to get todo")?
+            .with_context(|| format!("Todo not found: {}", payload.id))?;
+
+        // 不共享附件时，先复制物理文件
+        let attachment_paths = if payload.share_attachments {
+            HashMap::new()
+        } else {
+            copy_attachment_files(source.attachments.as_deref().unwrap_or_default())?
+        };
+
+        let result = TodoRepository::duplicate(
+            conn,
+            payload.id,
+            payload.target_group_id,
+            payload.reset_status,
+            payload.reset_dates,
+            &attachment_paths,
+        ).inspect_err(|e| {
+            remove_attachment_files(attachment_paths.values());
+            tracing::error!("duplicate_todo failed for id={}: {}", payload.id, e);
+        }).context("Failed to duplicate todo")?;
 
-    let result = TodoRepository::update_status(inner, payload.id, payload.status)
-        .map_err(|e| {
-            tracing::error!("update_todo_status failed for id={}: {}", payload.id, e);
-            format!("Failed to update todo status: {}", e)
-        })?;
+        webhooks::dispatch(&app, conn, webhook_event::TODO_CREATED, &result);
+        events::publish(&app, events::TODO_CHANGED, ChangeKind::Created, vec![result.id]);
 
-    tracing::info!("update_todo_status succeeded: id={}, status={}", result.id, result.status as i32);
-    Ok(result)
+        tracing::info!("duplicate_todo succeeded: source={}, new id={}", payload.id, result.id);
+        Ok(result)
+    }).await
 }
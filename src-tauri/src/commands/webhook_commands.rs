@@ -0,0 +1,154 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use std::time::Instant;
+
+use crate::database::Database;
+use crate::database::repositories::WebhookRepository;
+use crate::models::{Webhook, WebhookDelivery, WebhookTestResult, webhook_event};
+use crate::pojo::request::{CreateWebhookRequest, UpdateWebhookRequest, GetWebhookDeliveriesRequest};
+use crate::webhooks::{self, delivery};
+
+/// 投递记录默认返回条数
+const DEFAULT_DELIVERY_LIMIT: i64 = 100;
+
+/// 获取所有 Webhook
+#[tauri::command]
+pub async fn get_webhooks(
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<Webhook>, String> {
+    tracing::info!("get_webhooks called");
+
+    db.read(move |conn| {
+        WebhookRepository::list(conn)
+            .map_err(|e| format!("Failed to get webhooks: {}", e))
+    }).await
+}
+
+/// 创建 Webhook
+#[tauri::command]
+pub async fn create_webhook(
+    payload: CreateWebhookRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<Webhook, String> {
+    tracing::info!("create_webhook called: name={}, url={}, events={:?}", payload.name, payload.url, payload.events);
+
+    db.write(move |conn| {
+        WebhookRepository::create(
+            conn,
+            &payload.name,
+            &payload.url,
+            payload.secret.as_deref().filter(|s| !s.is_empty()),
+            &payload.events,
+            payload.enabled,
+        )
+        .map_err(|e| format!("Failed to create webhook: {}", e))
+    }).await
+}
+
+/// 更新 Webhook
+#[tauri::command]
+pub async fn update_webhook(
+    payload: UpdateWebhookRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<Webhook, String> {
+    tracing::info!("update_webhook called: id={}", payload.id);
+
+    db.write(move |conn| {
+        let secret = payload.secret.as_deref().map(|s| if s.is_empty() { None } else { Some(s) });
+
+        WebhookRepository::update(
+            conn,
+            payload.id,
+            payload.name.as_deref(),
+            payload.url.as_deref(),
+            secret,
+            payload.events.as_deref(),
+            payload.enabled,
+        )
+        .map_err(|e| format!("Failed to update webhook: {}", e))
+    }).await
+}
+
+/// 删除 Webhook
+#[tauri::command]
+pub async fn delete_webhook(
+    id: i64,
+    db: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    tracing::info!("delete_webhook called: id={}", id);
+
+    db.write(move |conn| {
+        WebhookRepository::delete(conn, id)
+            .map_err(|e| format!("Failed to delete webhook: {}", e))
+    }).await
+}
+
+/// 立即向 Webhook 发送一个 ping 事件（不经过队列，不重试）
+#[tauri::command]
+pub async fn test_webhook(
+    id: i64,
+    db: tauri::State<'_, Database>,
+) -> Result<WebhookTestResult, String> {
+    tracing::info!("test_webhook called: id={}", id);
+
+    let webhook = db.read(move |conn| {
+        WebhookRepository::get(conn, id)
+            .map_err(|e| format!("Failed to get webhook: {}", e))?
+            .ok_or_else(|| format!("Webhook not found: {}", id))
+    }).await?;
+
+    let body = webhooks::build_payload(webhook_event::PING, &serde_json::json!({ "webhook_id": id }));
+    let started = Instant::now();
+    let result = tokio::task::spawn_blocking(move || delivery::send(&webhook, webhook_event::PING, 0, &body))
+        .await
+        .map_err(|e| format!("Failed to send webhook: {}", e))?;
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    Ok(match result {
+        Ok(status) => WebhookTestResult {
+            success: true,
+            response_status: Some(status),
+            error: None,
+            duration_ms,
+        },
+        Err(e) => WebhookTestResult {
+            success: false,
+            response_status: e.status,
+            error: Some(e.message),
+            duration_ms,
+        },
+    })
+}
+
+/// 获取 Webhook 投递记录
+#[tauri::command]
+pub async fn get_webhook_deliveries(
+    payload: GetWebhookDeliveriesRequest,
+    db: tauri::State<'_, Database>,
+) -> Result<Vec<WebhookDelivery>, String> {
+    tracing::info!("get_webhook_deliveries called: webhook_id={:?}, limit={:?}", payload.webhook_id, payload.limit);
+
+    db.read(move |conn| {
+        WebhookRepository::list_deliveries(conn, payload.webhook_id, payload.limit.unwrap_or(DEFAULT_DELIVERY_LIMIT))
+            .map_err(|e| format!("Failed to get webhook deliveries: {}", e))
+    }).await
+}
+
+/// 重新投递失败的记录
+#[tauri::command]
+pub async fn retry_webhook_delivery(
+    id: i64,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, Database>,
+) -> Result<(), String> {
+    tracing::info!("retry_webhook_delivery called: id={}", id);
+
+    db.write(move |conn| {
+        WebhookRepository::retry(conn, id)
+            .map_err(|e| format!("Failed to retry webhook delivery: {}", e))?;
+        webhooks::wake_worker(&app);
+
+        Ok(())
+    }).await
+}
@@ -0,0 +1,172 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 工作区命令
+//! 列出、创建、重命名、删除和切换工作区；切换时重新打开对应的数据库
+
+use std::fs;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::data_path_commands;
+use crate::config::AppConfig;
+use crate::models::WorkspaceInfo;
+use crate::pojo::request::{CreateWorkspaceRequest, DeleteWorkspaceRequest, RenameWorkspaceRequest};
+use crate::remote;
+use crate::tray;
+use crate::workspaces::{self, Workspace, WORKSPACES_CHANGED_EVENT};
+
+/// 获取所有工作区
+#[tauri::command]
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<WorkspaceInfo>, String> {
+    with_config(&app, |config| Ok(workspaces::list(config)))
+}
+
+/// 创建工作区（不切换过去）
+#[tauri::command]
+pub async fn create_workspace(
+    payload: CreateWorkspaceRequest,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    tracing::info!("create_workspace called: {:?}", payload);
+
+    let info = with_config(&app, |config| {
+        let name = workspaces::validate_name(&config.workspaces, &payload.name, None)
+            .map_err(|e| format!("{:#}", e))?;
+        let data_path = payload.data_path.clone().filter(|p| !p.trim().is_empty());
+        let workspace = Workspace::new(name, data_path);
+        workspaces::prepare_data_dir(&workspace)
+            .map_err(|e| format!("Failed to create workspace: {:#}", e))?;
+
+        let info = workspace.to_info(&config.active_workspace);
+        config.workspaces.push(workspace);
+        config.save(&app)?;
+        Ok(info)
+    })?;
+
+    notify_changed(&app);
+    tracing::info!("Workspace created: {} ({})", info.name, info.id);
+    Ok(info)
+}
+
+/// 重命名工作区
+#[tauri::command]
+pub async fn rename_workspace(
+    payload: RenameWorkspaceRequest,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    tracing::info!("rename_workspace called: {:?}", payload);
+
+    let info = with_config(&app, |config| {
+        let name = workspaces::validate_name(&config.workspaces, &payload.name, Some(&payload.id))
+            .map_err(|e| format!("{:#}", e))?;
+        let active = config.active_workspace.clone();
+        let workspace = config.workspaces.iter_mut()
+            .find(|w| w.id == payload.id)
+            .ok_or_else(|| format!("Workspace not found: {}", payload.id))?;
+        workspace.name = name;
+
+        let info = workspace.to_info(&active);
+        config.save(&app)?;
+        Ok(info)
+    })?;
+
+    notify_changed(&app);
+    Ok(info)
+}
+
+/// 删除工作区
+///
+/// 默认工作区和当前工作区不能删除。`delete_data` 只删除工作区自动生成的目录，
+/// 自定义数据路径下的文件保持不动
+#[tauri::command]
+pub async fn delete_workspace(
+    payload: DeleteWorkspaceRequest,
+    app: AppHandle,
+) -> Result<(), String> {
+    tracing::info!("delete_workspace called: {:?}", payload);
+
+    let removed = with_config(&app, |config| {
+        let index = config.workspaces.iter()
+            .position(|w| w.id == payload.id)
+            .ok_or_else(|| format!("Workspace not found: {}", payload.id))?;
+        if config.workspaces[index].is_default() {
+            return Err("The default workspace cannot be deleted".to_string());
+        }
+        if config.active_workspace == payload.id {
+            return Err("Switch to another workspace before deleting this one".to_string());
+        }
+
+        let removed = config.workspaces.remove(index);
+        config.save(&app)?;
+        Ok(removed)
+    })?;
+
+    if payload.delete_data && removed.custom_data_dir().is_none() {
+        let dir = removed.default_data_dir()
+            .map_err(|e| format!("Failed to get workspace directory: {:#}", e))?;
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to delete workspace data: {}", e))?;
+            tracing::info!("Deleted workspace data: {}", dir.display());
+        }
+    }
+
+    if let Err(e) = remote::delete_password(&removed.id) {
+        tracing::warn!("Failed to remove saved remote storage password: {:#}", e);
+    }
+
+    notify_changed(&app);
+    tracing::info!("Workspace deleted: {} ({})", removed.name, removed.id);
+    Ok(())
+}
+
+/// 切换到指定工作区
+#[tauri::command]
+pub async fn switch_workspace(id: String, app: AppHandle) -> Result<WorkspaceInfo, String> {
+    tracing::info!("switch_workspace called: {}", id);
+    switch_to(&app, &id).await
+}
+
+/// 切换工作区：在工作区的数据目录重新打开数据库，成功后才更新配置
+pub(crate) async fn switch_to(app: &AppHandle, id: &str) -> Result<WorkspaceInfo, String> {
+    let workspace = with_config(app, |config| {
+        config.workspace(id)
+            .cloned()
+            .ok_or_else(|| format!("Workspace not found: {}", id))
+    })?;
+
+    let data_dir = workspaces::prepare_data_dir(&workspace)
+        .map_err(|e| format!("Failed to switch workspace: {:#}", e))?;
+    let backup_dir = workspace.backup.backup_dir_in(&data_dir);
+    data_path_commands::switch_data_dir(app, data_dir, backup_dir).await?;
+
+    let info = with_config(app, |config| {
+        config.active_workspace = workspace.id.clone();
+        config.save(app)?;
+        Ok(workspace.to_info(&config.active_workspace))
+    })?;
+
+    notify_changed(app);
+    tracing::info!("Switched to workspace: {} ({})", info.name, info.id);
+    Ok(info)
+}
+
+fn with_config<T>(
+    app: &AppHandle,
+    f: impl FnOnce(&mut AppConfig) -> Result<T, String>,
+) -> Result<T, String> {
+    let config_state = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .ok_or_else(|| "Config state not available".to_string())?;
+    let mut config = config_state.lock()
+        .map_err(|_| "Failed to acquire config lock".to_string())?;
+    f(&mut config)
+}
+
+/// 更新托盘菜单并通知前端
+fn notify_changed(app: &AppHandle) {
+    tray::refresh(app);
+    let list = with_config(app, |config| Ok(workspaces::list(config))).unwrap_or_default();
+    if let Err(e) = app.emit(WORKSPACES_CHANGED_EVENT, list) {
+        tracing::warn!("Failed to emit workspaces event: {}", e);
+    }
+}
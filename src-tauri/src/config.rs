@@ -5,11 +5,20 @@
 //! 负责加载和保存应用配置到文件系统
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::Manager;
 use tauri::AppHandle;
 
+use crate::api::ApiConfig;
+use crate::backup::BackupConfig;
+use crate::rules::ReminderConfig;
+use crate::shortcuts::ShortcutAction;
+use crate::remote::RemoteConfig;
+use crate::sync::SyncConfig;
+use crate::workspaces::{Workspace, DEFAULT_WORKSPACE_ID, LEGACY_DEFAULT_NAME};
+
 /// 应用配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -19,12 +28,27 @@ pub struct AppConfig {
     /// 关闭行为
     #[serde(default = "default_close_behavior")]
     pub close_behavior: String,
-    /// 全局快捷键
-    #[serde(default)]
+    /// 旧版本的全局快捷键（切换主窗口），加载后迁移到 `shortcuts`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub global_shortcut: Option<String>,
-    /// 自定义数据路径（可选，为空时使用默认路径）
+    /// 各动作绑定的全局快捷键
+    #[serde(default)]
+    pub shortcuts: BTreeMap<ShortcutAction, String>,
+    /// 工作区，每个工作区有独立的数据目录、备份、提醒和同步设置
     #[serde(default)]
-    pub data_path: Option<String>,
+    pub workspaces: Vec<Workspace>,
+    /// 当前工作区 ID
+    #[serde(default = "default_active_workspace")]
+    pub active_workspace: String,
+    /// 本地 REST API 设置（所有工作区共用）
+    #[serde(default)]
+    pub api: ApiConfig,
+    /// 旧版本的自定义数据路径，加载后迁移到默认工作区
+    #[serde(default, skip_serializing)]
+    data_path: Option<String>,
+    /// 旧版本的备份设置，加载后迁移到默认工作区
+    #[serde(default, skip_serializing)]
+    backup: Option<BackupConfig>,
 }
 
 impl Default for AppConfig {
@@ -33,7 +57,12 @@ impl Default for AppConfig {
             auto_launch: false,
             close_behavior: "direct".to_string(),
             global_shortcut: None,
+            shortcuts: BTreeMap::new(),
+            workspaces: vec![Workspace::default_workspace(None, BackupConfig::default())],
+            active_workspace: default_active_workspace(),
+            api: ApiConfig::default(),
             data_path: None,
+            backup: None,
         }
     }
 }
@@ -46,6 +75,10 @@ fn default_close_behavior() -> String {
     "direct".to_string()
 }
 
+fn default_active_workspace() -> String {
+    DEFAULT_WORKSPACE_ID.to_string()
+}
+
 impl AppConfig {
     /// 获取配置文件路径
     pub fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
@@ -71,8 +104,9 @@ impl AppConfig {
         let content = fs::read_to_string(&config_path)
             .map_err(|e| format!("Failed to read config file: {}", e))?;
 
-        let config: AppConfig = serde_json::from_str(&content)
+        let mut config: AppConfig = serde_json::from_str(&content)
             .map_err(|e| format!("Failed to parse config file: {}", e))?;
+        config.migrate_workspaces();
 
         tracing::info!("Loaded config from file: {:?}", config);
         Ok(config)
@@ -106,9 +140,100 @@ impl AppConfig {
         Ok(())
     }
 
-    /// 更新全局快捷键并保存
-    pub fn update_global_shortcut(&mut self, shortcut: Option<String>, app: &AppHandle) -> Result<(), String> {
-        self.global_shortcut = shortcut;
+    /// 获取快捷键绑定（兼容旧版本的 `global_shortcut`）
+    pub fn shortcut_bindings(&self) -> BTreeMap<ShortcutAction, String> {
+        let mut bindings = self.shortcuts.clone();
+        if let Some(shortcut) = &self.global_shortcut {
+            bindings.entry(ShortcutAction::ToggleMainWindow).or_insert_with(|| shortcut.clone());
+        }
+        bindings
+    }
+
+    /// 更新快捷键绑定并保存
+    pub fn update_shortcuts(&mut self, shortcuts: BTreeMap<ShortcutAction, String>, app: &AppHandle) -> Result<(), String> {
+        self.shortcuts = shortcuts;
+        self.global_shortcut = None;
+        self.save(app)?;
+        Ok(())
+    }
+
+    /// 把旧版本的数据路径和备份设置迁移到默认工作区，并确保当前工作区存在
+    pub(crate) fn migrate_workspaces(&mut self) {
+        let legacy_backup = self.backup.take();
+        let legacy_data_path = self.data_path.take();
+        if !self.workspaces.iter().any(|w| w.is_default()) {
+            self.workspaces.insert(0, Workspace::default_workspace(
+                legacy_data_path,
+                legacy_backup.unwrap_or_default(),
+            ));
+        }
+        if !self.workspaces.iter().any(|w| w.id == self.active_workspace) {
+            self.active_workspace = default_active_workspace();
+        }
+        // 旧版本固定写入的中文名称改为空，由界面按当前语言显示
+        if let Some(default) = self.workspaces.iter_mut().find(|w| w.is_default() && w.name == LEGACY_DEFAULT_NAME) {
+            default.name.clear();
+        }
+    }
+
+    /// 当前工作区
+    pub fn active_workspace(&self) -> &Workspace {
+        self.workspace(&self.active_workspace)
+            .or_else(|| self.workspaces.first())
+            .expect("default workspace always exists")
+    }
+
+    /// 当前工作区（可修改）
+    pub fn active_workspace_mut(&mut self) -> &mut Workspace {
+        let index = self.workspaces.iter()
+            .position(|w| w.id == self.active_workspace)
+            .unwrap_or(0);
+        &mut self.workspaces[index]
+    }
+
+    /// 按 ID 查找工作区
+    pub fn workspace(&self, id: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.id == id)
+    }
+
+    /// 更新当前工作区的备份设置并保存
+    pub fn update_backup(&mut self, backup: BackupConfig, app: &AppHandle) -> Result<(), String> {
+        self.active_workspace_mut().backup = backup;
+        self.save(app)?;
+        Ok(())
+    }
+
+    /// 更新当前工作区的提醒设置并保存
+    pub fn update_reminders(&mut self, reminders: ReminderConfig, app: &AppHandle) -> Result<(), String> {
+        self.active_workspace_mut().reminders = reminders;
+        self.save(app)?;
+        Ok(())
+    }
+
+    /// 更新当前工作区的同步设置并保存
+    pub fn update_sync(&mut self, sync: SyncConfig, app: &AppHandle) -> Result<(), String> {
+        self.active_workspace_mut().sync = sync;
+        self.save(app)?;
+        Ok(())
+    }
+
+    /// 更新当前工作区的远程存储设置并保存
+    pub fn update_remote(&mut self, remote: Option<RemoteConfig>, app: &AppHandle) -> Result<(), String> {
+        self.active_workspace_mut().remote = remote;
+        self.save(app)?;
+        Ok(())
+    }
+
+    /// 更新当前工作区的数据路径并保存（None 为工作区的默认目录）
+    pub fn update_data_path(&mut self, data_path: Option<String>, app: &AppHandle) -> Result<(), String> {
+        self.active_workspace_mut().data_path = data_path;
+        self.save(app)?;
+        Ok(())
+    }
+
+    /// 更新本地 API 设置并保存
+    pub fn update_api(&mut self, api: ApiConfig, app: &AppHandle) -> Result<(), String> {
+        self.api = api;
         self.save(app)?;
         Ok(())
     }
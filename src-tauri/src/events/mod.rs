@@ -0,0 +1,146 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 领域事件
+//!
+//! 每次数据变更成功后向所有窗口广播 `todo:changed` 等事件，携带变更的实体 ID 和单调递增的修订号，
+//! 前端据此增量刷新，并可通过比较修订号发现遗漏的事件
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::models::GroupDeleteSummary;
+
+#[cfg(test)]
+mod tests;
+
+pub const TODO_CHANGED: &str = "todo:changed";
+pub const GROUP_CHANGED: &str = "group:changed";
+pub const TAG_CHANGED: &str = "tag:changed";
+pub const STEP_CHANGED: &str = "step:changed";
+
+/// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+    /// 批量导入或清空数据，前端应重新加载全部数据
+    Reloaded,
+}
+
+/// 变更事件载荷
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeEvent {
+    pub revision: u64,
+    pub kind: ChangeKind,
+    pub ids: Vec<i64>,
+    /// 步骤事件所属的任务 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub todo_id: Option<i64>,
+}
+
+/// 一条待广播的实体变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub topic: &'static str,
+    pub kind: ChangeKind,
+    pub ids: Vec<i64>,
+    pub todo_id: Option<i64>,
+}
+
+impl Change {
+    pub fn new(topic: &'static str, kind: ChangeKind, ids: Vec<i64>) -> Self {
+        Self { topic, kind, ids, todo_id: None }
+    }
+
+    /// 某个任务下的步骤变更
+    pub fn steps(kind: ChangeKind, todo_id: i64, step_ids: Vec<i64>) -> Self {
+        Self { topic: STEP_CHANGED, kind, ids: step_ids, todo_id: Some(todo_id) }
+    }
+
+    /// 导入或清空数据后所有类型的数据都需要重新加载
+    pub fn reload_all() -> Vec<Self> {
+        [TODO_CHANGED, GROUP_CHANGED, TAG_CHANGED, STEP_CHANGED]
+            .into_iter()
+            .map(|topic| Self::new(topic, ChangeKind::Reloaded, Vec::new()))
+            .collect()
+    }
+
+    /// 删除任务组：被删除的任务组、被移动的子任务组以及组内任务的变化
+    pub fn group_deleted(summary: &GroupDeleteSummary) -> Vec<Self> {
+        let todo_kind = if summary.deleted_todos > 0 { ChangeKind::Deleted } else { ChangeKind::Updated };
+        vec![
+            Self::new(GROUP_CHANGED, ChangeKind::Deleted, summary.deleted_group_ids.clone()),
+            Self::new(GROUP_CHANGED, ChangeKind::Updated, summary.reparented_group_ids.clone()),
+            Self::new(TODO_CHANGED, todo_kind, summary.affected_todo_ids.clone()),
+        ]
+    }
+
+    /// 合并标签：来源标签被删除，目标标签更新，新获得该标签的任务更新
+    pub fn tags_merged(source_ids: Vec<i64>, target_id: i64, newly_tagged: Vec<i64>) -> Vec<Self> {
+        vec![
+            Self::new(TAG_CHANGED, ChangeKind::Deleted, source_ids),
+            Self::new(TAG_CHANGED, ChangeKind::Updated, vec![target_id]),
+            Self::new(TODO_CHANGED, ChangeKind::Updated, newly_tagged),
+        ]
+    }
+}
+
+/// 事件总线状态，保存当前修订号
+#[derive(Default)]
+pub struct EventBus {
+    revision: AtomicU64,
+}
+
+impl EventBus {
+    /// 当前修订号
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::SeqCst)
+    }
+
+    /// 为变更分配下一个修订号；`ids` 为空的变更不发送，也不占用修订号（`Reloaded` 除外）
+    fn stamp(&self, change: Change) -> Option<(&'static str, ChangeEvent)> {
+        if change.ids.is_empty() && change.kind != ChangeKind::Reloaded {
+            return None;
+        }
+        let event = ChangeEvent {
+            revision: self.revision.fetch_add(1, Ordering::SeqCst) + 1,
+            kind: change.kind,
+            ids: change.ids,
+            todo_id: change.todo_id,
+        };
+        Some((change.topic, event))
+    }
+}
+
+/// 广播实体变更事件；`ids` 为空时不发送（`Reloaded` 除外）
+pub fn publish(app: &AppHandle, topic: &'static str, kind: ChangeKind, ids: Vec<i64>) {
+    publish_all(app, [Change::new(topic, kind, ids)]);
+}
+
+/// 广播步骤变更事件
+pub fn publish_steps(app: &AppHandle, kind: ChangeKind, todo_id: i64, step_ids: Vec<i64>) {
+    publish_all(app, [Change::steps(kind, todo_id, step_ids)]);
+}
+
+/// 导入或清空数据后通知所有类型的数据需要重新加载
+pub fn publish_reload(app: &AppHandle) {
+    publish_all(app, Change::reload_all());
+}
+
+/// 依次广播多条变更
+pub fn publish_all(app: &AppHandle, changes: impl IntoIterator<Item = Change>) {
+    let Some(bus) = app.try_state::<EventBus>() else {
+        return;
+    };
+
+    for (topic, event) in changes.into_iter().filter_map(|change| bus.stamp(change)) {
+        tracing::debug!("Emitting {} revision={} kind={:?} ids={:?}", topic, event.revision, event.kind, event.ids);
+        if let Err(e) = app.emit(topic, &event) {
+            tracing::warn!("Failed to emit {}: {}", topic, e);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+
+/// 依次为变更分配修订号，返回实际会发送的主题和事件
+fn stamp_all(bus: &EventBus, changes: Vec<Change>) -> Vec<(&'static str, ChangeEvent)> {
+    changes.into_iter().filter_map(|change| bus.stamp(change)).collect()
+}
+
+fn topics(changes: &[Change]) -> Vec<(&'static str, ChangeKind)> {
+    changes.iter().map(|change| (change.topic, change.kind)).collect()
+}
+
+#[test]
+fn revision_increases_once_per_published_change() {
+    let bus = EventBus::default();
+    assert_eq!(bus.revision(), 0);
+
+    let sent = stamp_all(&bus, vec![
+        Change::new(TODO_CHANGED, ChangeKind::Created, vec![1]),
+        Change::new(GROUP_CHANGED, ChangeKind::Updated, vec![2, 3]),
+        Change::steps(ChangeKind::Deleted, 1, vec![4]),
+    ]);
+
+    let revisions: Vec<u64> = sent.iter().map(|(_, event)| event.revision).collect();
+    assert_eq!(revisions, vec![1, 2, 3]);
+    assert_eq!(bus.revision(), 3);
+}
+
+#[test]
+fn empty_changes_are_skipped_without_using_a_revision() {
+    let bus = EventBus::default();
+
+    let sent = stamp_all(&bus, vec![
+        Change::new(TODO_CHANGED, ChangeKind::Updated, Vec::new()),
+        Change::new(TAG_CHANGED, ChangeKind::Deleted, vec![7]),
+    ]);
+
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, TAG_CHANGED);
+    assert_eq!(sent[0].1.revision, 1);
+    assert_eq!(bus.revision(), 1);
+}
+
+#[test]
+fn reload_is_sent_on_every_topic() {
+    let bus = EventBus::default();
+
+    let sent = stamp_all(&bus, Change::reload_all());
+
+    let topics: Vec<&str> = sent.iter().map(|(topic, _)| *topic).collect();
+    assert_eq!(topics, vec![TODO_CHANGED, GROUP_CHANGED, TAG_CHANGED, STEP_CHANGED]);
+    assert!(sent.iter().all(|(_, event)| event.kind == ChangeKind::Reloaded && event.ids.is_empty()));
+    assert_eq!(bus.revision(), 4);
+}
+
+#[test]
+fn step_changes_carry_their_todo() {
+    let change = Change::steps(ChangeKind::Updated, 5, vec![8, 9]);
+
+    assert_eq!(change.topic, STEP_CHANGED);
+    assert_eq!(change.todo_id, Some(5));
+    let json = serde_json::to_value(EventBus::default().stamp(change).unwrap().1).unwrap();
+    assert_eq!(json["todo_id"], 5);
+    assert_eq!(json["kind"], "updated");
+
+    let todo = EventBus::default().stamp(Change::new(TODO_CHANGED, ChangeKind::Created, vec![1])).unwrap().1;
+    assert!(serde_json::to_value(todo).unwrap().get("todo_id").is_none());
+}
+
+#[test]
+fn group_deletion_maps_to_group_and_todo_topics() {
+    let summary = GroupDeleteSummary {
+        deleted_group_ids: vec![1, 2],
+        reparented_group_ids: vec![3],
+        deleted_todos: 2,
+        affected_todo_ids: vec![10, 11],
+        ..Default::default()
+    };
+
+    let changes = Change::group_deleted(&summary);
+    assert_eq!(topics(&changes), vec![
+        (GROUP_CHANGED, ChangeKind::Deleted),
+        (GROUP_CHANGED, ChangeKind::Updated),
+        (TODO_CHANGED, ChangeKind::Deleted),
+    ]);
+    assert_eq!(changes[0].ids, vec![1, 2]);
+    assert_eq!(changes[1].ids, vec![3]);
+    assert_eq!(changes[2].ids, vec![10, 11]);
+
+    // 任务被移动或取消分组时只是更新
+    let moved = GroupDeleteSummary { deleted_todos: 0, moved_todos: 2, ..summary };
+    assert_eq!(Change::group_deleted(&moved)[2].kind, ChangeKind::Updated);
+}
+
+#[test]
+fn tag_merge_maps_to_tag_and_todo_topics() {
+    let changes = Change::tags_merged(vec![2, 3], 1, vec![10]);
+
+    assert_eq!(topics(&changes), vec![
+        (TAG_CHANGED, ChangeKind::Deleted),
+        (TAG_CHANGED, ChangeKind::Updated),
+        (TODO_CHANGED, ChangeKind::Updated),
+    ]);
+    assert_eq!(changes[0].ids, vec![2, 3]);
+    assert_eq!(changes[1].ids, vec![1]);
+    assert_eq!(changes[2].ids, vec![10]);
+}
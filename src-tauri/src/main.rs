@@ -5,23 +5,32 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use tauri::{
-    menu::{Menu, MenuItem, CheckMenuItem},
     tray::{MouseButton, TrayIconBuilder, TrayIconEvent},
     Manager,
     Emitter,
     include_image,
 };
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri_plugin_global_shortcut::ShortcutState;
 use tauri_plugin_dialog::DialogExt;
 
 mod commands;
-mod database;
 mod models;
 mod pojo;
-mod utils;
 mod logging;
 mod config;
-
+mod rules;
+mod webhooks;
+mod events;
+mod shortcuts;
+mod backup;
+mod workspaces;
+mod api;
+mod remote;
+mod sync;
+mod tray;
+
+// 领域逻辑在 rtodo-core 中，命令层通过 crate::database 等路径访问
+use rtodo_core::{database, encryption, integrity, utils};
 use database::Database;
 use logging::{load_config, init_logging};
 use commands::app_commands::AppState;
@@ -101,20 +110,14 @@ async fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app_handle, shortcut, _| {
-                    tracing::info!("Global shortcut triggered: {}", shortcut);
-                    // 调用 toggle_webview_window_visibility 命令
-                    if let Some(window) = app_handle.get_webview_window("main") {
-                        // 使用 async runtime 执行异步命令
-                        let app = app_handle.clone();
-                        tauri::async_runtime::spawn(async move {
-                            match commands::app_commands::toggle_webview_window_visibility(window).await {
-                                Ok(_) => tracing::info!("Window toggled successfully"),
-                                Err(e) => tracing::error!("Failed to toggle window: {}", e),
-                            }
-                        });
+                .with_handler(|app_handle, shortcut, event| {
+                    // 只处理按下事件，避免松开时重复执行
+                    if event.state() == ShortcutState::Pressed {
+                        tracing::info!("Global shortcut triggered: {}", shortcut);
+                        shortcuts::handle(app_handle, shortcut);
                     }
                 })
                 .build()
@@ -123,7 +126,7 @@ async fn main() {
             tracing::info!("Setting up application...");
 
             // 加载应用配置
-            let config = AppConfig::load(&app.handle())
+            let mut config = AppConfig::load(&app.handle())
                 .unwrap_or_else(|e| {
                     tracing::warn!("Failed to load config: {}, using defaults", e);
                     AppConfig::default()
@@ -139,23 +142,32 @@ async fn main() {
                 _ => commands::app_commands::CloseBehavior::Direct,
             };
 
+            // 注册配置中保存的快捷键（冲突的快捷键会被跳过）
+            let registered_shortcuts = shortcuts::register_saved(app.handle(), &config.shortcut_bindings());
+
             let app_state = AppState {
-                hide_hotkey: std::sync::Mutex::new(config.global_shortcut.clone()),
+                shortcuts: std::sync::Mutex::new(registered_shortcuts),
                 close_behavior: std::sync::Mutex::new(close_behavior),
                 auto_launch: std::sync::Mutex::new(system_enabled),
             };
             app.manage(app_state);
 
-            // 如果配置中保存了快捷键，则注册它
-            if let Some(ref shortcut) = config.global_shortcut {
-                tracing::info!("Registering saved global shortcut: {}", shortcut);
-                let shortcut_manager = app.global_shortcut();
-                match shortcut_manager.register(shortcut.as_str()) {
-                    Ok(_) => tracing::info!("Successfully registered saved shortcut: {}", shortcut),
-                    Err(e) => tracing::warn!("Failed to register saved shortcut {}: {}", shortcut, e),
-                }
+            // 确定当前工作区的数据目录，不可用时回退到默认目录并提示用户
+            if let Some(reason) = workspaces::init(&mut config) {
+                app.dialog()
+                    .message(format!("自定义数据目录不可用，已使用默认目录: {}", reason))
+                    .title("RTodo")
+                    .kind(tauri_plugin_dialog::MessageDialogKind::Warning)
+                    .show(|_| {});
             }
 
+            // 迁移前备份写入配置的备份目录
+            let backup_dir = config.active_workspace().backup.backup_dir()
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to resolve backup directory: {}", e);
+                    std::env::temp_dir().join("rtodo-backups")
+                });
+
             // 更新配置中的开机启动状态（与系统保持同步）
             let mut config_to_save = config;
             config_to_save.auto_launch = system_enabled;
@@ -163,30 +175,61 @@ async fn main() {
             // 保存配置到 app manage 中，方便后续访问
             app.manage(std::sync::Mutex::new(config_to_save));
 
-            // 初始化数据库连接池
-            let db = Database::new().expect("Failed to initialize database");
+            // 初始化数据库连接池；加密的数据目录先以锁定状态启动，等待用户输入口令
+            let encrypted = database::DbConnection::get_data_dir()
+                .is_ok_and(|dir| encryption::is_enabled(&dir));
+            let opened = if encrypted {
+                tracing::info!("Database is encrypted, waiting for unlock");
+                Ok(Database::locked())
+            } else {
+                Database::new(Some(&backup_dir))
+            };
+            let db = match opened {
+                Ok(db) => db,
+                Err(e) => {
+                    // 数据库版本比应用新或迁移失败时，提示用户后退出，避免损坏数据
+                    tracing::error!("Failed to initialize database: {:#}", e);
+                    app.dialog()
+                        .message(format!("无法打开数据库: {:#}", e))
+                        .title("RTodo")
+                        .kind(tauri_plugin_dialog::MessageDialogKind::Error)
+                        .blocking_show();
+                    return Err(e.into());
+                }
+            };
             app.manage(db);
 
+            // 清理上次打开加密附件时留下的解密副本
+            commands::attachment_commands::clear_decrypted_copies();
+
+            // 数据变更事件的修订号
+            app.manage(events::EventBus::default());
+
+            // 启动截止时间规则的后台检查
+            rules::spawn_due_soon_checker(app.handle().clone());
+
+            // 启动 Webhook 投递队列
+            webhooks::spawn_worker(app.handle().clone());
+
+            // 启动定时备份
+            backup::spawn_scheduler(app.handle().clone());
+
+            // 启动本地 API（设置中启用时）
+            api::spawn(app.handle().clone());
+
+            // 启动设备间同步（设置中启用时）
+            sync::spawn_worker(app.handle().clone());
+
             // 初始化日志状态
             let log_state = LogState::new(log_reload_handle, log_config);
             app.manage(log_state);
 
             // 创建托盘图标菜单（使用系统实际状态作为初始值）
-            let show_item = MenuItem::with_id(app, "show", "显示", true, None::<&str>).unwrap();
-            let autolaunch_item = CheckMenuItem::with_id(
-                app,
-                "autolaunch",
-                "开机启动",
-                true,
-                system_enabled,
-                None::<&str>
-            ).unwrap();
-            let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>).unwrap();
-            let menu = Menu::with_items(app, &[&show_item, &autolaunch_item, &quit_item]).unwrap();
+            let menu = tray::build_menu(app.handle(), system_enabled)?;
 
             // 创建托盘图标
             let tray_icon = include_image!("icons/tray-icon.png");
-            let tray = TrayIconBuilder::new()
+            let tray = TrayIconBuilder::with_id(tray::TRAY_ID)
                 .icon(tray_icon)
                 .menu(&menu)
                 .show_menu_on_left_click(false)
@@ -297,7 +340,20 @@ async fn main() {
                         tracing::info!("Quit requested from tray menu");
                         app.exit(0);
                     }
-                    _ => {}
+                    id => {
+                        if let Some(workspace_id) = tray::workspace_id(id) {
+                            let app_handle = app.clone();
+                            let workspace_id = workspace_id.to_string();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = commands::workspace_commands::switch_to(&app_handle, &workspace_id).await {
+                                    tracing::error!("Failed to switch workspace: {}", e);
+                                    // 恢复托盘菜单的勾选状态
+                                    tray::refresh(&app_handle);
+                                    let _ = app_handle.dialog().message(format!("切换工作区失败: {}", e)).show(|_dialog| ());
+                                }
+                            });
+                        }
+                    }
                 }
             });
 
@@ -311,14 +367,21 @@ async fn main() {
             commands::todo_commands::update_todo,
             commands::todo_commands::delete_todo,
             commands::todo_commands::update_todo_status,
+            commands::todo_commands::duplicate_todo,
             commands::group_commands::get_task_groups,
             commands::group_commands::create_task_group,
             commands::group_commands::update_task_group,
             commands::group_commands::delete_task_group,
+            commands::group_commands::delete_task_group_with_options,
+            commands::group_commands::move_task_group,
+            commands::group_commands::reorder_task_groups,
+            commands::group_commands::get_group_tree,
+            commands::group_commands::duplicate_task_group,
             commands::tag_commands::get_tags,
             commands::tag_commands::create_tag,
             commands::tag_commands::update_tag,
             commands::tag_commands::delete_tag,
+            commands::tag_commands::merge_tags,
             commands::step_commands::get_todo_steps,
             commands::step_commands::create_step,
             commands::step_commands::toggle_step,
@@ -337,18 +400,77 @@ async fn main() {
             commands::stats_commands::get_stats,
             commands::stats_commands::get_stats_by_date,
             commands::stats_commands::get_stats_with_details,
+            commands::stats_commands::get_tag_usage_report,
+            commands::rule_commands::get_rules,
+            commands::rule_commands::create_rule,
+            commands::rule_commands::update_rule,
+            commands::rule_commands::delete_rule,
+            commands::rule_commands::get_rule_executions,
+            commands::rule_commands::test_rule,
+            commands::rule_commands::get_reminder_config,
+            commands::rule_commands::update_reminder_config,
+            commands::webhook_commands::get_webhooks,
+            commands::webhook_commands::create_webhook,
+            commands::webhook_commands::update_webhook,
+            commands::webhook_commands::delete_webhook,
+            commands::webhook_commands::test_webhook,
+            commands::webhook_commands::get_webhook_deliveries,
+            commands::webhook_commands::retry_webhook_delivery,
             commands::data_manager_command::export_all_data,
+            commands::data_manager_command::preview_import,
             commands::data_manager_command::import_data,
             commands::data_manager_command::export_data_as_csv,
+            commands::data_manager_command::preview_csv_import,
             commands::data_manager_command::import_data_from_csv,
+            commands::data_manager_command::preview_external_import,
+            commands::data_manager_command::import_external_data,
+            commands::data_manager_command::export_todos_as_ical,
+            commands::data_manager_command::preview_ical_import,
+            commands::data_manager_command::import_ical,
             commands::data_manager_command::clear_all_data,
+            commands::backup_commands::get_backups,
+            commands::backup_commands::create_backup,
+            commands::backup_commands::verify_backup,
+            commands::backup_commands::restore_backup,
+            commands::backup_commands::delete_backup,
+            commands::backup_commands::get_backup_config,
+            commands::backup_commands::update_backup_config,
+            commands::workspace_commands::list_workspaces,
+            commands::workspace_commands::create_workspace,
+            commands::workspace_commands::rename_workspace,
+            commands::workspace_commands::delete_workspace,
+            commands::workspace_commands::switch_workspace,
+            commands::integrity_commands::check_data_integrity,
+            commands::integrity_commands::repair_data_integrity,
+            commands::encryption_commands::get_encryption_status,
+            commands::encryption_commands::unlock_database,
+            commands::encryption_commands::set_encryption,
+            commands::api_commands::get_api_status,
+            commands::api_commands::update_api_config,
+            commands::api_commands::regenerate_api_token,
+            commands::sync_commands::get_sync_status,
+            commands::sync_commands::update_sync_config,
+            commands::sync_commands::sync_now,
+            commands::sync_commands::get_sync_conflicts,
+            commands::sync_commands::clear_sync_conflicts,
+            commands::remote_commands::get_remote_storage,
+            commands::remote_commands::update_remote_storage,
+            commands::remote_commands::remove_remote_storage,
+            commands::remote_commands::upload_backups,
+            commands::remote_commands::get_remote_backups,
+            commands::remote_commands::restore_remote_backup,
             commands::app_commands::set_global_shortcut,
             commands::app_commands::get_global_shortcut,
+            commands::app_commands::set_shortcut,
+            commands::app_commands::get_shortcuts,
+            commands::app_commands::toggle_quick_add_window,
+            commands::app_commands::capture_clipboard,
             commands::app_commands::toggle_window_visibility,
             commands::app_commands::show_window,
             commands::app_commands::hide_window,
             commands::app_commands::set_close_behavior,
             commands::app_commands::get_close_behavior,
+            commands::app_commands::get_event_revision,
             commands::app_commands::set_auto_launch,
             commands::app_commands::get_auto_launch,
             commands::app_commands::toggle_auto_launch,
@@ -362,6 +484,11 @@ async fn main() {
             commands::log_commands::compress_logs,
         ])
         .on_window_event(|window, event| match event {
+            tauri::WindowEvent::CloseRequested { api, .. } if window.label() == shortcuts::QUICK_ADD_WINDOW => {
+                // 快速添加窗口关闭时只隐藏，下次打开更快
+                api.prevent_close();
+                let _ = window.hide();
+            }
             tauri::WindowEvent::CloseRequested { api, .. } => {
                 // 检查关闭行为设置
                 let app_handle = window.app_handle();
@@ -371,6 +498,10 @@ async fn main() {
                         commands::app_commands::CloseBehavior::Direct => {
                             // 直接关闭，不做任何处理
                             tracing::info!("Window close requested - closing directly");
+                            // 快速添加窗口不应阻止应用退出
+                            if let Some(quick_add) = app_handle.get_webview_window(shortcuts::QUICK_ADD_WINDOW) {
+                                let _ = quick_add.destroy();
+                            }
                         }
                         commands::app_commands::CloseBehavior::MinimizeToTray => {
                             // 阻止窗口关闭，改为隐藏到托盘
@@ -0,0 +1,18 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+
+/// 本地 API 状态
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatus {
+    /// 设置中是否启用
+    pub enabled: bool,
+    pub port: u16,
+    /// 访问令牌（尚未启用过时为空）
+    pub token: String,
+    /// 服务是否正在运行
+    pub running: bool,
+    /// 接口地址，例如 `http://127.0.0.1:7846/api/v1`
+    pub base_url: String,
+}
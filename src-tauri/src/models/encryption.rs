@@ -0,0 +1,15 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+
+/// 加密状态
+#[derive(Debug, Clone, Serialize)]
+pub struct EncryptionStatus {
+    /// 当前构建是否支持加密
+    pub supported: bool,
+    /// 当前数据目录是否已加密
+    pub enabled: bool,
+    /// 数据库是否等待口令解锁
+    pub locked: bool,
+}
@@ -1,22 +1,17 @@
 // Copyright 2025 RTodo Team. All rights reserved.
 // SPDX-License-Identifier: MIT
 
-pub mod todo;
-pub mod group;
-pub mod tag;
-pub mod step;
-pub mod attachment;
-pub mod stats;
-pub mod constants;
+//! 数据模型：领域模型来自 rtodo-core，这里只定义应用自身的模型
 
-// 重新导出数据模型（不包含 Request 对象，Request 对象已移至 pojo/request）
-pub use todo::{Todo, TodoStatus};
-pub use group::TaskGroup;
-pub use tag::Tag;
-pub use step::TodoStep;
-pub use attachment::Attachment;
-pub use stats::{
-    TodoStats, StatsByDate, TodoStatsWithDetails, ExportData,
-    GroupStats, TagStats
-};
-pub use constants::{priority, status};
+pub mod api;
+pub mod encryption;
+pub mod remote;
+pub mod sync;
+pub mod workspace;
+
+pub use rtodo_core::models::*;
+pub use api::ApiStatus;
+pub use encryption::EncryptionStatus;
+pub use remote::RemoteStorageStatus;
+pub use sync::SyncStatus;
+pub use workspace::WorkspaceInfo;
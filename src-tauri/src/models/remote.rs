@@ -0,0 +1,15 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+
+use crate::remote::RemoteConfig;
+
+/// 远程存储状态
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteStorageStatus {
+    /// 当前工作区的远程存储（未配置时为空）
+    pub config: Option<RemoteConfig>,
+    /// 系统钥匙串中是否保存了密码
+    pub password_saved: bool,
+}
@@ -0,0 +1,26 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+
+use super::SyncReport;
+use crate::sync::SyncTarget;
+
+/// 同步状态
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    /// 设置中是否启用
+    pub enabled: bool,
+    pub target: SyncTarget,
+    /// 共享目录
+    pub folder: Option<String>,
+    /// 本设备的同步 id（从未同步过时为空）
+    pub device_id: Option<String>,
+    /// 上次同步的时间（本次运行期间）
+    pub last_sync_at: Option<i64>,
+    pub last_report: Option<SyncReport>,
+    /// 上次同步失败的原因
+    pub last_error: Option<String>,
+    /// 未清除的冲突记录数
+    pub conflict_count: usize,
+}
@@ -0,0 +1,18 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Serialize;
+
+/// 工作区信息
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceInfo {
+    pub id: String,
+    /// 默认工作区未重命名时为空，前端显示本地化的名称
+    pub name: String,
+    /// 实际使用的数据目录
+    pub data_path: String,
+    /// 默认工作区不能删除
+    pub is_default: bool,
+    /// 是否为当前工作区
+    pub active: bool,
+}
@@ -0,0 +1,13 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+/// 更新本地 API 设置请求（未提供的字段保持不变）
+#[derive(Debug, Deserialize)]
+pub struct UpdateApiConfigRequest {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub port: Option<u16>,
+}
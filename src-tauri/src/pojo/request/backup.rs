@@ -0,0 +1,25 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+/// 更新备份设置请求（未提供的字段保持不变）
+#[derive(Debug, Deserialize)]
+pub struct UpdateBackupConfigRequest {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub interval_hours: Option<u32>,
+    /// 空字符串表示恢复默认目录
+    #[serde(default)]
+    pub directory: Option<String>,
+    #[serde(default)]
+    pub keep_daily: Option<u32>,
+    #[serde(default)]
+    pub keep_weekly: Option<u32>,
+    #[serde(default)]
+    pub keep_monthly: Option<u32>,
+    /// 定时备份后上传到远程存储
+    #[serde(default)]
+    pub upload: Option<bool>,
+}
@@ -0,0 +1,13 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+/// 启用或关闭加密请求
+#[derive(Deserialize)]
+pub struct SetEncryptionRequest {
+    /// true 为启用加密，false 为关闭
+    pub enabled: bool,
+    /// 启用时为新口令，关闭时为当前口令
+    pub passphrase: String,
+}
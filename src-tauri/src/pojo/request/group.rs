@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT
 
 use serde::Deserialize;
+use crate::models::{GroupChildPolicy, GroupTodoPolicy};
 
 /// 创建任务组请求
 #[derive(Debug, Deserialize)]
@@ -25,3 +26,51 @@ pub struct UpdateGroupRequest {
     #[serde(default)]
     pub color: Option<String>,
 }
+
+/// 复制任务组请求（连同组内所有任务）
+#[derive(Debug, Deserialize)]
+pub struct DuplicateGroupRequest {
+    pub id: i64,
+    /// 新任务组名称（为空时使用 "原名称 (copy)"）
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 是否将任务状态重置为待办
+    #[serde(default)]
+    pub reset_status: bool,
+    /// 是否清空任务的开始时间和截止时间
+    #[serde(default)]
+    pub reset_dates: bool,
+    /// 是否与原任务共享附件文件
+    #[serde(default)]
+    pub share_attachments: bool,
+}
+
+/// 移动任务组请求
+#[derive(Debug, Deserialize)]
+pub struct MoveGroupRequest {
+    pub id: i64,
+    /// 新的父任务组（为空表示移动到顶层）
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+    /// 在新父级下的位置（从 0 开始，为空时放到末尾）
+    #[serde(default)]
+    pub position: Option<usize>,
+}
+
+/// 同级任务组排序请求
+#[derive(Debug, Deserialize)]
+pub struct ReorderGroupsRequest {
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+    pub ordered_ids: Vec<i64>,
+}
+
+/// 带选项的删除任务组请求
+#[derive(Debug, Deserialize)]
+pub struct DeleteGroupRequest {
+    pub id: i64,
+    #[serde(default)]
+    pub child_policy: GroupChildPolicy,
+    #[serde(default)]
+    pub todo_policy: GroupTodoPolicy,
+}
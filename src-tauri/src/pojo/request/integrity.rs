@@ -0,0 +1,15 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+use crate::models::IntegrityIssue;
+
+/// 修复完整性问题请求
+#[derive(Debug, Deserialize)]
+pub struct RepairIntegrityRequest {
+    /// 要修复的问题（取自检查报告）
+    pub issues: Vec<IntegrityIssue>,
+    /// 只返回将要执行的修复，不修改数据
+    #[serde(default)]
+    pub dry_run: bool,
+}
@@ -10,12 +10,30 @@ pub mod step;
 pub mod attachment;
 pub mod stats;
 pub mod data_path;
+pub mod rule;
+pub mod webhook;
+pub mod backup;
+pub mod integrity;
+pub mod encryption;
+pub mod workspace;
+pub mod api;
+pub mod sync;
+pub mod remote;
 
 // 重新导出常用类型
-pub use todo::{CreateTodoRequest, UpdateTodoRequest, UpdateTodoStatusRequest, GetTodosRequest};
-pub use group::{CreateGroupRequest, UpdateGroupRequest};
-pub use tag::{CreateTagRequest, UpdateTagRequest};
+pub use todo::{CreateTodoRequest, UpdateTodoRequest, UpdateTodoStatusRequest, GetTodosRequest, DuplicateTodoRequest};
+pub use group::{CreateGroupRequest, UpdateGroupRequest, DuplicateGroupRequest, MoveGroupRequest, ReorderGroupsRequest, DeleteGroupRequest};
+pub use tag::{CreateTagRequest, UpdateTagRequest, MergeTagsRequest};
 pub use step::{CreateStepRequest, UpdateStepRequest};
 pub use attachment::{CreateAttachmentRequest, DownloadAttachmentRequest};
-pub use stats::{GetStatsRequest, GetStatsByDateRequest, GetStatsWithDetailsRequest};
+pub use stats::{GetStatsRequest, GetStatsByDateRequest, GetStatsWithDetailsRequest, GetTagUsageRequest};
 pub use data_path::MigrateDataRequest;
+pub use rule::{CreateRuleRequest, UpdateRuleRequest, TestRuleRequest, GetRuleExecutionsRequest, UpdateReminderConfigRequest};
+pub use webhook::{CreateWebhookRequest, UpdateWebhookRequest, GetWebhookDeliveriesRequest};
+pub use backup::UpdateBackupConfigRequest;
+pub use integrity::RepairIntegrityRequest;
+pub use encryption::SetEncryptionRequest;
+pub use workspace::{CreateWorkspaceRequest, RenameWorkspaceRequest, DeleteWorkspaceRequest};
+pub use api::UpdateApiConfigRequest;
+pub use sync::UpdateSyncConfigRequest;
+pub use remote::UpdateRemoteStorageRequest;
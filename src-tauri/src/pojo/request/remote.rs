@@ -0,0 +1,25 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+use crate::remote::RemoteConfig;
+
+/// 设置远程存储请求
+#[derive(Deserialize)]
+pub struct UpdateRemoteStorageRequest {
+    pub config: RemoteConfig,
+    /// 新密码；不提供时沿用系统钥匙串中已保存的密码
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl std::fmt::Debug for UpdateRemoteStorageRequest {
+    // 日志中不输出密码
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateRemoteStorageRequest")
+            .field("config", &self.config)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+use crate::models::{RuleTrigger, RuleCondition, RuleAction};
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 创建规则请求
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateRuleRequest {
+    pub name: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub trigger: RuleTrigger,
+    #[serde(default)]
+    pub conditions: Vec<RuleCondition>,
+    #[serde(default)]
+    pub actions: Vec<RuleAction>,
+}
+
+/// 更新规则请求
+#[derive(Debug, Deserialize)]
+pub struct UpdateRuleRequest {
+    pub id: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub trigger: Option<RuleTrigger>,
+    #[serde(default)]
+    pub conditions: Option<Vec<RuleCondition>>,
+    #[serde(default)]
+    pub actions: Option<Vec<RuleAction>>,
+    #[serde(default)]
+    pub sort_order: Option<i32>,
+}
+
+/// 试运行规则请求：指定已保存的规则或未保存的规则草稿
+#[derive(Debug, Deserialize)]
+pub struct TestRuleRequest {
+    pub todo_id: i64,
+    #[serde(default)]
+    pub rule_id: Option<i64>,
+    #[serde(default)]
+    pub draft: Option<CreateRuleRequest>,
+}
+
+/// 获取规则执行日志请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GetRuleExecutionsRequest {
+    pub rule_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+/// 更新提醒设置请求（未提供的字段保持不变）
+#[derive(Debug, Deserialize)]
+pub struct UpdateReminderConfigRequest {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub check_interval_minutes: Option<u32>,
+}
@@ -31,3 +31,11 @@ pub struct GetStatsWithDetailsRequest {
     pub tag_ids: Option<Vec<i64>>,
     pub status_ids: Option<Vec<i32>>,
 }
+
+/// 标签使用情况报告请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GetTagUsageRequest {
+    pub start_date: Option<i64>,
+    pub end_date: Option<i64>,
+}
@@ -0,0 +1,18 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+use crate::sync::SyncTarget;
+
+/// 更新同步设置请求（未提供的字段保持不变）
+#[derive(Debug, Deserialize)]
+pub struct UpdateSyncConfigRequest {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub target: Option<SyncTarget>,
+    /// 空字符串表示清除共享目录
+    #[serde(default)]
+    pub folder: Option<String>,
+}
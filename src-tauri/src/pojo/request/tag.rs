@@ -8,6 +8,8 @@ use serde::Deserialize;
 pub struct CreateTagRequest {
     pub name: String,
     pub color: String,
+    #[serde(default)]
+    pub parent_id: Option<i64>,
 }
 
 /// 更新标签请求
@@ -18,4 +20,15 @@ pub struct UpdateTagRequest {
     pub name: Option<String>,
     #[serde(default)]
     pub color: Option<String>,
+    #[serde(default)]
+    pub parent_id: Option<i64>,
+}
+
+/// 合并标签请求
+#[derive(Debug, Deserialize)]
+pub struct MergeTagsRequest {
+    /// 保留的标签
+    pub target_id: i64,
+    /// 被合并（删除）的标签
+    pub source_ids: Vec<i64>,
 }
@@ -67,3 +67,21 @@ pub struct UpdateTodoStatusRequest {
     pub id: i64,
     pub status: i32,
 }
+
+/// 复制任务请求
+#[derive(Debug, Deserialize)]
+pub struct DuplicateTodoRequest {
+    pub id: i64,
+    /// 目标任务组（为空时保留原任务组）
+    #[serde(default)]
+    pub target_group_id: Option<i64>,
+    /// 是否将状态重置为待办（同时重置步骤的完成状态）
+    #[serde(default)]
+    pub reset_status: bool,
+    /// 是否清空开始时间和截止时间
+    #[serde(default)]
+    pub reset_dates: bool,
+    /// 是否与原任务共享附件文件（否则复制出新的文件）
+    #[serde(default)]
+    pub share_attachments: bool,
+}
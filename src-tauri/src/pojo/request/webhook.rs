@@ -0,0 +1,46 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// 创建 Webhook 请求
+#[derive(Debug, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// 更新 Webhook 请求
+#[derive(Debug, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub id: i64,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// 空字符串表示清除密钥
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Option<Vec<String>>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// 获取 Webhook 投递记录请求
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GetWebhookDeliveriesRequest {
+    pub webhook_id: Option<i64>,
+    pub limit: Option<i64>,
+}
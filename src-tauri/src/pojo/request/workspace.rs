@@ -0,0 +1,29 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+
+/// 创建工作区请求
+#[derive(Debug, Deserialize)]
+pub struct CreateWorkspaceRequest {
+    pub name: String,
+    /// 自定义数据目录（为空时使用默认数据目录下的 workspaces/<id>）
+    #[serde(default)]
+    pub data_path: Option<String>,
+}
+
+/// 重命名工作区请求
+#[derive(Debug, Deserialize)]
+pub struct RenameWorkspaceRequest {
+    pub id: String,
+    pub name: String,
+}
+
+/// 删除工作区请求
+#[derive(Debug, Deserialize)]
+pub struct DeleteWorkspaceRequest {
+    pub id: String,
+    /// 同时删除工作区的数据（只删除默认位置的目录，自定义目录中的数据保留）
+    #[serde(default)]
+    pub delete_data: bool,
+}
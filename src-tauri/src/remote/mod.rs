@@ -0,0 +1,154 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 远程存储
+//!
+//! 每个工作区可以配置一个远程存储（WebDAV 或 S3 兼容的对象存储），用于设备间同步和上传备份。
+//! 密码（S3 为 Secret Access Key）保存在系统钥匙串（macOS 钥匙串、Windows 凭据管理器、Secret Service）中，
+//! 不写入配置文件。
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+use rtodo_core::storage::{S3Options, S3Store, Store, WebDavStore};
+
+use crate::config::AppConfig;
+
+/// 钥匙串中的服务名
+const KEYRING_SERVICE: &str = "rtodo";
+
+/// WebDAV 服务器设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    /// 存放 RTodo 数据的集合，例如 `https://cloud.example.com/remote.php/dav/files/alice/RTodo`
+    pub url: String,
+    #[serde(default)]
+    pub username: String,
+}
+
+/// S3 兼容对象存储（AWS S3、MinIO 等）设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S3Config {
+    /// 服务地址，例如 `http://minio.internal:9000`
+    pub endpoint: String,
+    pub bucket: String,
+    /// 存放 RTodo 数据的键前缀，为空时使用整个存储桶
+    #[serde(default)]
+    pub prefix: String,
+    /// 区域，为空时使用 `us-east-1`
+    #[serde(default)]
+    pub region: String,
+    pub access_key_id: String,
+}
+
+impl S3Config {
+    fn store(&self, secret_access_key: &str) -> Result<S3Store> {
+        Ok(S3Store::new(S3Options {
+            endpoint: self.endpoint.clone(),
+            bucket: self.bucket.clone(),
+            prefix: self.prefix.clone(),
+            region: self.region.clone(),
+            access_key_id: self.access_key_id.clone(),
+            secret_access_key: secret_access_key.to_string(),
+        })?)
+    }
+}
+
+/// 远程存储设置（每个工作区独立）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RemoteConfig {
+    WebDav(WebDavConfig),
+    S3(S3Config),
+}
+
+impl RemoteConfig {
+    /// 存储位置，用于识别同步目标是否变化
+    pub fn location(&self) -> String {
+        match self {
+            Self::WebDav(webdav) => webdav.url.clone(),
+            Self::S3(s3) => {
+                let prefix = s3.prefix.trim_matches('/');
+                let location = format!("{}/{}", s3.endpoint.trim_end_matches('/'), s3.bucket);
+                if prefix.is_empty() { location } else { format!("{}/{}", location, prefix) }
+            }
+        }
+    }
+
+    /// 用给定的密码创建存储
+    pub fn open(&self, password: &str) -> Result<Arc<dyn Store>> {
+        match self {
+            Self::WebDav(webdav) => {
+                let store = WebDavStore::new(&webdav.url, &webdav.username, password)?;
+                Ok(Arc::new(store))
+            }
+            Self::S3(s3) => Ok(Arc::new(s3.store(password)?)),
+        }
+    }
+
+    /// 检查能否连接并通过认证
+    pub fn check(&self, password: &str) -> Result<()> {
+        match self {
+            Self::WebDav(webdav) => {
+                WebDavStore::new(&webdav.url, &webdav.username, password)?.connect()?;
+                Ok(())
+            }
+            Self::S3(s3) => {
+                s3.store(password)?.connect()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 当前工作区的 ID 和远程存储设置
+pub fn current_config(app: &AppHandle) -> (String, Option<RemoteConfig>) {
+    app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| {
+            state.lock().ok().map(|config| {
+                let workspace = config.active_workspace();
+                (workspace.id.clone(), workspace.remote.clone())
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// 打开当前工作区的远程存储
+pub fn open_store(app: &AppHandle) -> Result<Arc<dyn Store>> {
+    let (workspace_id, config) = current_config(app);
+    let config = config.context("Remote storage is not configured")?;
+    config.open(&load_password(&workspace_id)?.unwrap_or_default())
+}
+
+fn credential(workspace_id: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("remote:{}", workspace_id))
+        .context("System keychain is not available")
+}
+
+/// 读取工作区保存的密码
+pub fn load_password(workspace_id: &str) -> Result<Option<String>> {
+    match credential(workspace_id)?.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).context("Failed to read the password from the system keychain"),
+    }
+}
+
+/// 保存工作区的密码，空密码删除已保存的密码
+pub fn save_password(workspace_id: &str, password: &str) -> Result<()> {
+    if password.is_empty() {
+        return delete_password(workspace_id);
+    }
+    credential(workspace_id)?.set_password(password)
+        .context("Failed to save the password to the system keychain")
+}
+
+/// 删除工作区保存的密码
+pub fn delete_password(workspace_id: &str) -> Result<()> {
+    match credential(workspace_id)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e).context("Failed to remove the password from the system keychain"),
+    }
+}
@@ -0,0 +1,103 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 自动化规则
+//!
+//! 规则的求值和执行在 rtodo-core 中，这里负责发送通知、保存提醒设置和定时执行截止时间规则
+
+pub use rtodo_core::rules::RuleEngine;
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::AppConfig;
+use crate::database::Database;
+use crate::events::{self, ChangeKind};
+use crate::models::RuleOutcome;
+
+/// 规则通知事件名
+pub const RULE_NOTIFICATION_EVENT: &str = "rule-notification";
+
+/// 截止时间规则的最小检查间隔
+const DUE_SOON_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 截止时间提醒设置（每个工作区独立）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReminderConfig {
+    /// 是否执行截止时间规则
+    #[serde(default = "default_reminders_enabled")]
+    pub enabled: bool,
+    /// 检查间隔（分钟）
+    #[serde(default = "default_check_interval_minutes")]
+    pub check_interval_minutes: u32,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_reminders_enabled(),
+            check_interval_minutes: default_check_interval_minutes(),
+        }
+    }
+}
+
+fn default_reminders_enabled() -> bool {
+    true
+}
+
+fn default_check_interval_minutes() -> u32 {
+    1
+}
+
+/// 读取当前工作区的提醒设置
+pub fn current_reminder_config(app: &AppHandle) -> ReminderConfig {
+    app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| state.lock().ok().map(|config| config.active_workspace().reminders.clone()))
+        .unwrap_or_default()
+}
+
+/// 将规则产生的通知发送到前端
+pub fn emit_notifications(app: &AppHandle, outcomes: &[RuleOutcome]) {
+    for notification in outcomes.iter().flat_map(|o| &o.notifications) {
+        if let Err(e) = app.emit(RULE_NOTIFICATION_EVENT, notification) {
+            tracing::warn!("Failed to emit rule notification: {}", e);
+        }
+    }
+}
+
+/// 启动后台任务，定期执行截止时间规则
+pub fn spawn_due_soon_checker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(DUE_SOON_CHECK_INTERVAL);
+        let mut last_check: Option<Instant> = None;
+        loop {
+            interval.tick().await;
+
+            let config = current_reminder_config(&app);
+            let check_interval = Duration::from_secs(u64::from(config.check_interval_minutes.max(1)) * 60);
+            if !config.enabled || last_check.is_some_and(|t| t.elapsed() < check_interval) {
+                continue;
+            }
+
+            // 加密数据库解锁前跳过
+            let Some(db) = app.try_state::<Database>().filter(|db| !db.is_locked()) else {
+                continue;
+            };
+            last_check = Some(Instant::now());
+            let result = db.write(RuleEngine::check_due_soon).await;
+
+            match result {
+                Ok(outcomes) => {
+                    emit_notifications(&app, &outcomes);
+                    let changed: Vec<i64> = outcomes.iter()
+                        .filter(|o| o.error.is_none() && !o.applied_actions.is_empty())
+                        .map(|o| o.todo_id)
+                        .collect();
+                    events::publish(&app, events::TODO_CHANGED, ChangeKind::Updated, changed);
+                }
+                Err(e) => tracing::warn!("Failed to check due soon rules: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,260 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 全局快捷键
+//! 每个动作可绑定一个快捷键：切换主窗口、打开快速添加窗口、将剪贴板内容捕获为任务
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use crate::commands::app_commands::AppState;
+use crate::pojo::request::CreateTodoRequest;
+
+#[cfg(test)]
+mod tests;
+
+/// 快速添加窗口的标签
+pub const QUICK_ADD_WINDOW: &str = "quick-add";
+
+/// 剪贴板捕获成功后发送给前端的事件
+pub const CLIPBOARD_CAPTURED_EVENT: &str = "clipboard-captured";
+
+/// 剪贴板内容作为任务标题的最大长度（字符数），超出部分放入描述
+const MAX_TITLE_CHARS: usize = 200;
+
+/// 快捷键动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// 显示/隐藏主窗口
+    ToggleMainWindow,
+    /// 打开置顶的快速添加窗口
+    QuickAdd,
+    /// 将剪贴板文本创建为任务
+    CaptureClipboard,
+}
+
+impl ShortcutAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShortcutAction::ToggleMainWindow => "toggle_main_window",
+            ShortcutAction::QuickAdd => "quick_add",
+            ShortcutAction::CaptureClipboard => "capture_clipboard",
+        }
+    }
+}
+
+/// 解析快捷键字符串，用于比较两个写法不同的快捷键是否相同
+pub fn parse(shortcut: &str) -> Result<Shortcut, String> {
+    shortcut.parse::<Shortcut>()
+        .map_err(|e| format!("Invalid shortcut {}: {}", shortcut, e))
+}
+
+/// 绑定（或在 `shortcut` 为 None 时解除）某个动作的快捷键
+///
+/// 与其他动作的快捷键或其他程序已注册的快捷键冲突时返回错误，原有绑定保持不变
+pub fn bind(app: &AppHandle, action: ShortcutAction, shortcut: Option<&str>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let mut bindings = state.shortcuts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let manager = app.global_shortcut();
+
+    let new_shortcut = match shortcut.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(s) => {
+            let parsed = parse(s)?;
+            for (other_action, other) in bindings.iter() {
+                if *other_action != action && parse(other).ok() == Some(parsed) {
+                    return Err(format!(
+                        "Shortcut {} is already used by {}",
+                        s, other_action.as_str()
+                    ));
+                }
+            }
+            Some((s.to_string(), parsed))
+        }
+        None => None,
+    };
+
+    let old = bindings.get(&action).cloned();
+    if let (Some(old), Some((_, parsed))) = (&old, &new_shortcut) {
+        if parse(old).ok() == Some(*parsed) {
+            return Ok(());
+        }
+    }
+
+    // 先注册新快捷键，成功后再取消旧快捷键，失败时原绑定仍然有效
+    if let Some((s, parsed)) = &new_shortcut {
+        if manager.is_registered(*parsed) {
+            return Err(format!("Shortcut {} is already registered", s));
+        }
+        manager.register(*parsed)
+            .map_err(|e| format!("Failed to register shortcut {} (it may be used by another application): {}", s, e))?;
+    }
+    if let Some(old) = &old {
+        if let Ok(parsed) = parse(old) {
+            if manager.is_registered(parsed) {
+                manager.unregister(parsed)
+                    .map_err(|e| format!("Failed to unregister old shortcut: {}", e))?;
+            }
+        }
+        tracing::info!("Unregistered shortcut {} for {}", old, action.as_str());
+    }
+
+    match new_shortcut {
+        Some((s, _)) => {
+            tracing::info!("Registered shortcut {} for {}", s, action.as_str());
+            bindings.insert(action, s);
+        }
+        None => {
+            bindings.remove(&action);
+        }
+    }
+
+    // 保存到配置文件
+    if let Some(config_state) = app.try_state::<std::sync::Mutex<crate::config::AppConfig>>() {
+        if let Ok(mut config) = config_state.lock() {
+            config.update_shortcuts(bindings.clone(), app)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 启动时注册配置中保存的快捷键，返回成功注册的绑定；冲突的快捷键只记录警告
+pub fn register_saved(app: &AppHandle, saved: &BTreeMap<ShortcutAction, String>) -> BTreeMap<ShortcutAction, String> {
+    let manager = app.global_shortcut();
+    let mut registered: BTreeMap<ShortcutAction, String> = BTreeMap::new();
+
+    for (action, shortcut) in saved {
+        let parsed = match parse(shortcut) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                tracing::warn!("Skipping saved shortcut for {}: {}", action.as_str(), e);
+                continue;
+            }
+        };
+        if registered.values().any(|s| parse(s).ok() == Some(parsed)) {
+            tracing::warn!("Skipping saved shortcut {} for {}: conflicts with another action", shortcut, action.as_str());
+            continue;
+        }
+        match manager.register(parsed) {
+            Ok(_) => {
+                tracing::info!("Registered saved shortcut {} for {}", shortcut, action.as_str());
+                registered.insert(*action, shortcut.clone());
+            }
+            Err(e) => tracing::warn!("Failed to register saved shortcut {}: {}", shortcut, e),
+        }
+    }
+
+    registered
+}
+
+/// 全局快捷键触发时执行对应动作
+pub fn handle(app: &AppHandle, shortcut: &Shortcut) {
+    let action = {
+        let Some(state) = app.try_state::<AppState>() else {
+            return;
+        };
+        let Ok(bindings) = state.shortcuts.lock() else {
+            return;
+        };
+        bindings.iter()
+            .find(|(_, s)| parse(s).ok().as_ref() == Some(shortcut))
+            .map(|(action, _)| *action)
+    };
+    let Some(action) = action else {
+        tracing::warn!("No action bound to shortcut: {}", shortcut);
+        return;
+    };
+
+    tracing::info!("Shortcut {} triggered action {}", shortcut, action.as_str());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match action {
+            ShortcutAction::ToggleMainWindow => match app.get_webview_window("main") {
+                Some(window) => crate::commands::app_commands::toggle_webview_window_visibility(window).await,
+                None => Ok(()),
+            },
+            ShortcutAction::QuickAdd => toggle_quick_add_window(&app),
+            ShortcutAction::CaptureClipboard => capture_clipboard(&app).await,
+        };
+        if let Err(e) = result {
+            tracing::error!("Shortcut action {} failed: {}", action.as_str(), e);
+        }
+    });
+}
+
+/// 打开快速添加窗口；窗口已显示时隐藏
+pub fn toggle_quick_add_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(QUICK_ADD_WINDOW) {
+        if window.is_visible().map_err(|e| format!("Failed to check visibility: {}", e))? {
+            window.hide().map_err(|e| format!("Failed to hide window: {}", e))?;
+        } else {
+            window.show().map_err(|e| format!("Failed to show window: {}", e))?;
+            window.set_focus().map_err(|e| format!("Failed to focus window: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    WebviewWindowBuilder::new(app, QUICK_ADD_WINDOW, WebviewUrl::App("quick-add".into()))
+        .title("RTodo")
+        .inner_size(520.0, 180.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build()
+        .map_err(|e| format!("Failed to create quick add window: {}", e))?;
+
+    tracing::info!("Quick add window created");
+    Ok(())
+}
+
+/// 将剪贴板文本创建为任务：第一行作为标题，其余内容作为描述
+pub async fn capture_clipboard(app: &AppHandle) -> Result<(), String> {
+    let text = app.clipboard().read_text()
+        .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+
+    let Some(payload) = todo_from_text(&text) else {
+        tracing::info!("Clipboard is empty, nothing to capture");
+        return Ok(());
+    };
+
+    let todo = crate::commands::todo_commands::create_todo(payload, app.clone(), app.state()).await?;
+    tracing::info!("Captured clipboard as todo: id={}", todo.id);
+
+    if let Err(e) = app.emit(CLIPBOARD_CAPTURED_EVENT, &todo) {
+        tracing::warn!("Failed to emit clipboard captured event: {}", e);
+    }
+    Ok(())
+}
+
+fn todo_from_text(text: &str) -> Option<CreateTodoRequest> {
+    let text = text.trim();
+    let mut lines = text.lines();
+    let first_line = lines.next()?.trim();
+    if first_line.is_empty() {
+        return None;
+    }
+
+    let title: String = first_line.chars().take(MAX_TITLE_CHARS).collect();
+    let description = if title.len() < first_line.len() || text.len() > first_line.len() {
+        Some(text.to_string())
+    } else {
+        None
+    };
+
+    Some(CreateTodoRequest {
+        title,
+        description,
+        group_id: None,
+        start_date: None,
+        due_date: None,
+        priority: None,
+        tag_ids: None,
+    })
+}
@@ -0,0 +1,49 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+
+#[test]
+fn empty_or_blank_text_creates_nothing() {
+    assert!(todo_from_text("").is_none());
+    assert!(todo_from_text("   ").is_none());
+    assert!(todo_from_text("\n\t \r\n  \n").is_none());
+}
+
+#[test]
+fn single_line_becomes_the_title() {
+    let payload = todo_from_text("  Buy milk \n").unwrap();
+
+    assert_eq!(payload.title, "Buy milk");
+    assert_eq!(payload.description, None);
+    assert_eq!(payload.group_id, None);
+    assert_eq!(payload.tag_ids, None);
+}
+
+#[test]
+fn multi_line_text_keeps_the_rest_as_description() {
+    let payload = todo_from_text("\n\nCall the plumber\r\nKitchen sink is leaking\r\nAsk for a quote\n").unwrap();
+
+    assert_eq!(payload.title, "Call the plumber");
+    assert_eq!(
+        payload.description.as_deref(),
+        Some("Call the plumber\r\nKitchen sink is leaking\r\nAsk for a quote")
+    );
+}
+
+#[test]
+fn long_text_is_truncated_in_the_title_only() {
+    let first_line = "长".repeat(MAX_TITLE_CHARS + 50);
+    let text = format!("{}\n{}", first_line, "x".repeat(100_000));
+
+    let payload = todo_from_text(&text).unwrap();
+
+    assert_eq!(payload.title.chars().count(), MAX_TITLE_CHARS);
+    assert!(first_line.starts_with(&payload.title));
+    assert_eq!(payload.description.as_deref(), Some(text.as_str()));
+
+    // 单行超长时完整内容同样保留在描述中
+    let payload = todo_from_text(&first_line).unwrap();
+    assert_eq!(payload.title.chars().count(), MAX_TITLE_CHARS);
+    assert_eq!(payload.description.as_deref(), Some(first_line.as_str()));
+}
@@ -0,0 +1,272 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 设备间同步
+//!
+//! 通过用户选择的共享目录（Syncthing、Dropbox、NAS 等）或远程存储（WebDAV）与其他设备交换变更日志，
+//! 合并逻辑在 `rtodo_core::sync` 中。后台任务启动时同步一次，之后定期检查：同步目标中出现其他设备的新日志，
+//! 或本地数据有变更时再同步。变更日志不加密，因此启用了加密的工作区不能同步。
+
+pub use rtodo_core::sync::{clear_conflicts, list_conflicts};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+use rtodo_core::storage::{FolderStore, Store};
+use rtodo_core::sync;
+
+use crate::config::AppConfig;
+use crate::database::{Database, DbConnection};
+use crate::encryption;
+use crate::events::{self, EventBus};
+use crate::models::{SyncReport, SyncStatus};
+use crate::remote;
+
+/// 检查同步目录和本地变更的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 没有发现变化时也定期同步（其他进程如命令行工具 rtodo 的修改不会触发事件）
+const FULL_SYNC_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// 同步目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncTarget {
+    /// 共享目录
+    #[default]
+    Folder,
+    /// 工作区的远程存储
+    Remote,
+}
+
+/// 同步设置（每个工作区独立）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub target: SyncTarget,
+    /// 共享目录
+    #[serde(default)]
+    pub folder: Option<String>,
+}
+
+impl SyncConfig {
+    /// 共享目录（忽略空字符串）
+    pub fn folder(&self) -> Option<&str> {
+        self.folder.as_deref().filter(|f| !f.is_empty())
+    }
+}
+
+/// 读取当前工作区的同步设置
+pub fn current_config(app: &AppHandle) -> SyncConfig {
+    app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| state.lock().ok().map(|config| config.active_workspace().sync.clone()))
+        .unwrap_or_default()
+}
+
+/// 上次同步的结果
+#[derive(Debug, Clone, Default)]
+struct LastSync {
+    report: Option<SyncReport>,
+    error: Option<String>,
+    at: Option<i64>,
+}
+
+/// 同步后台任务状态
+#[derive(Default)]
+pub struct SyncWorker {
+    /// 同一时间只运行一次同步
+    running: tokio::sync::Mutex<()>,
+    notify: Arc<Notify>,
+    last: std::sync::Mutex<LastSync>,
+}
+
+/// 当前工作区是否启用了加密（包括尚未解锁时）；加密的工作区不能同步
+pub fn is_encrypted(db: &Database) -> bool {
+    db.key().is_some() || DbConnection::get_data_dir().is_ok_and(|dir| encryption::is_enabled(&dir))
+}
+
+/// 打开同步目标；共享目录必须已经存在
+pub fn open_store(app: &AppHandle, config: &SyncConfig) -> Result<Arc<dyn Store>> {
+    match config.target {
+        SyncTarget::Folder => {
+            let folder = config.folder().context("Choose a sync folder first")?;
+            let store = FolderStore::new(Path::new(folder))
+                .with_context(|| format!("Sync folder is not available: {}", folder))?;
+            Ok(Arc::new(store))
+        }
+        SyncTarget::Remote => remote::open_store(app),
+    }
+}
+
+/// 同步目标的位置（共享目录或远程存储的地址），未设置时返回 `None`
+fn location(app: &AppHandle, config: &SyncConfig) -> Option<String> {
+    match config.target {
+        SyncTarget::Folder => config.folder().map(str::to_string),
+        SyncTarget::Remote => remote::current_config(app).1.map(|remote| remote.location()),
+    }
+}
+
+/// 立即同步一次；其他设备的变更已应用时通知前端重新加载
+pub async fn run(app: &AppHandle, db: &Database) -> Result<SyncReport> {
+    let worker = app.try_state::<SyncWorker>().context("Sync worker not initialized")?;
+    let _running = worker.running.lock().await;
+
+    let config = current_config(app);
+    if !config.enabled {
+        anyhow::bail!("Sync is not enabled");
+    }
+
+    let mut location = String::new();
+    let result = match open_store(app, &config) {
+        Ok(_) if is_encrypted(db) => Err(rtodo_core::Error::SyncEncrypted.into()),
+        Ok(store) => {
+            location = store.location();
+            sync::sync(db, store).await.map_err(anyhow::Error::from)
+        }
+        Err(e) => Err(e),
+    };
+
+    let mut last = worker.last.lock().unwrap_or_else(|e| e.into_inner());
+    last.at = Some(chrono::Utc::now().timestamp_millis());
+    match &result {
+        Ok(report) => {
+            last.report = Some(report.clone());
+            last.error = None;
+            if report.changed_local() {
+                events::publish_reload(app);
+            }
+            tracing::info!(
+                "Synced with {}: pushed {}, pulled {}, {} created, {} updated, {} deleted, {} conflicts",
+                location, report.pushed, report.pulled, report.created, report.updated, report.deleted, report.conflicts,
+            );
+        }
+        Err(e) => last.error = Some(format!("{:#}", e)),
+    }
+    result
+}
+
+/// 当前的同步状态
+pub async fn status(app: &AppHandle, db: &Database) -> SyncStatus {
+    let config = current_config(app);
+    let last = app.try_state::<SyncWorker>()
+        .map(|worker| worker.last.lock().unwrap_or_else(|e| e.into_inner()).clone())
+        .unwrap_or_default();
+
+    let (device_id, conflict_count) = if db.is_locked() {
+        (None, 0)
+    } else {
+        db.read(|conn| {
+            let conflicts: i64 = conn.query_row("SELECT COUNT(*) FROM sync_conflicts", [], |row| row.get(0))?;
+            Ok::<_, anyhow::Error>((sync::device_id(conn)?, conflicts as usize))
+        }).await.unwrap_or_default()
+    };
+
+    SyncStatus {
+        enabled: config.enabled,
+        target: config.target,
+        folder: config.folder,
+        device_id,
+        last_sync_at: last.at,
+        last_report: last.report,
+        last_error: last.error,
+        conflict_count,
+    }
+}
+
+/// 唤醒后台任务立即检查（例如设置变化后）
+pub fn wake_worker(app: &AppHandle) {
+    if let Some(worker) = app.try_state::<SyncWorker>() {
+        worker.notify.notify_one();
+    }
+}
+
+/// 注册后台任务状态并启动同步循环
+pub fn spawn_worker(app: AppHandle) {
+    let worker = SyncWorker::default();
+    let notify = worker.notify.clone();
+    app.manage(worker);
+
+    tauri::async_runtime::spawn(async move {
+        // 上次同步时的同步目标、同步目录指纹和事件修订号
+        let mut target: Option<(String, String)> = None;
+        let mut fingerprint: Option<String> = None;
+        let mut revision = 0;
+        let mut last_run: Option<Instant> = None;
+
+        loop {
+            check(&app, &mut target, &mut fingerprint, &mut revision, &mut last_run).await;
+            tokio::select! {
+                _ = notify.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    });
+}
+
+/// 有需要时同步一次
+async fn check(
+    app: &AppHandle,
+    target: &mut Option<(String, String)>,
+    fingerprint: &mut Option<String>,
+    revision: &mut u64,
+    last_run: &mut Option<Instant>,
+) {
+    let config = current_config(app);
+    let Some(location) = location(app, &config).filter(|_| config.enabled) else {
+        return;
+    };
+    // 加密数据库解锁前不同步
+    let db = match app.try_state::<Database>() {
+        Some(db) if !db.is_locked() => db.inner().clone(),
+        _ => return,
+    };
+
+    // 切换工作区或同步目标后重新同步
+    let workspace = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| state.lock().ok().map(|config| config.active_workspace.clone()))
+        .unwrap_or_default();
+    let current_target = (workspace, location);
+    if target.as_ref() != Some(&current_target) {
+        *target = Some(current_target);
+        *fingerprint = None;
+        *last_run = None;
+    }
+
+    // 加密的工作区不同步：只运行一次，在同步状态中显示原因
+    if last_run.is_some() && is_encrypted(&db) {
+        return;
+    }
+
+    let current_revision = app.try_state::<EventBus>().map(|bus| bus.revision()).unwrap_or_default();
+    let current_fingerprint = read_fingerprint(app, &config).await;
+    let due = last_run.is_none_or(|t| t.elapsed() >= FULL_SYNC_INTERVAL);
+    // 同步目标暂时不可用（例如 NAS 未挂载、离线）时只在定期同步时重试，避免反复记录错误
+    let unchanged = current_revision == *revision && current_fingerprint == *fingerprint;
+    if !due && (current_fingerprint.is_none() || unchanged) {
+        return;
+    }
+
+    *last_run = Some(Instant::now());
+    if let Err(e) = run(app, &db).await {
+        tracing::warn!("Sync failed: {:#}", e);
+    }
+    // 同步本身会写入日志段并可能发送事件，以同步后的状态为准
+    *revision = app.try_state::<EventBus>().map(|bus| bus.revision()).unwrap_or_default();
+    *fingerprint = read_fingerprint(app, &config).await;
+}
+
+/// 同步目标的指纹；远程存储只需一次请求（见 `rtodo_core::sync::fingerprint`）
+async fn read_fingerprint(app: &AppHandle, config: &SyncConfig) -> Option<String> {
+    let store = open_store(app, config).ok()?;
+    tokio::task::spawn_blocking(move || sync::fingerprint(&*store))
+        .await
+        .ok()?
+        .ok()
+}
@@ -0,0 +1,70 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 托盘菜单
+//! 显示窗口、开机启动、工作区切换和退出；工作区变化后重新生成菜单
+
+use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::commands::app_commands::AppState;
+use crate::config::AppConfig;
+
+/// 托盘图标 ID
+pub const TRAY_ID: &str = "main";
+
+/// 工作区菜单项 ID 前缀，后接工作区 ID
+const WORKSPACE_ITEM_PREFIX: &str = "workspace:";
+
+/// 生成托盘菜单
+pub fn build_menu<R: Runtime>(app: &AppHandle<R>, auto_launch: bool) -> tauri::Result<Menu<R>> {
+    let (workspaces, active) = app.try_state::<std::sync::Mutex<AppConfig>>()
+        .and_then(|state| state.lock().ok().map(|config| (config.workspaces.clone(), config.active_workspace.clone())))
+        .unwrap_or_default();
+
+    let workspace_items = workspaces.iter()
+        .map(|workspace| CheckMenuItem::with_id(
+            app,
+            format!("{}{}", WORKSPACE_ITEM_PREFIX, workspace.id),
+            if workspace.name.is_empty() { "默认" } else { workspace.name.as_str() },
+            true,
+            workspace.id == active,
+            None::<&str>,
+        ))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let workspace_refs: Vec<&dyn IsMenuItem<R>> = workspace_items.iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+    let workspace_menu = Submenu::with_items(app, "工作区", !workspace_refs.is_empty(), &workspace_refs)?;
+
+    let show_item = MenuItem::with_id(app, "show", "显示", true, None::<&str>)?;
+    let autolaunch_item = CheckMenuItem::with_id(app, "autolaunch", "开机启动", true, auto_launch, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let quit_item = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+
+    Menu::with_items(app, &[&show_item, &workspace_menu, &autolaunch_item, &separator, &quit_item])
+}
+
+/// 工作区变化后重新生成托盘菜单
+pub fn refresh<R: Runtime>(app: &AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let auto_launch = app.try_state::<AppState>()
+        .and_then(|state| state.auto_launch.lock().ok().map(|enabled| *enabled))
+        .unwrap_or(false);
+
+    match build_menu(app, auto_launch) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                tracing::warn!("Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to build tray menu: {}", e),
+    }
+}
+
+/// 菜单项对应的工作区 ID（不是工作区菜单项时返回 None）
+pub fn workspace_id(menu_id: &str) -> Option<&str> {
+    menu_id.strip_prefix(WORKSPACE_ITEM_PREFIX)
+}
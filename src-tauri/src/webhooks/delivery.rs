@@ -0,0 +1,50 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! Webhook 请求发送
+
+use std::time::Duration;
+
+use rtodo_core::webhooks::{sign, SendError};
+
+use crate::models::Webhook;
+
+/// 单次请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 发送一次 Webhook 请求（阻塞），返回 2xx 状态码
+///
+/// 请求头：
+/// - `X-RTodo-Event`: 事件名称
+/// - `X-RTodo-Delivery`: 投递 ID（重试时不变，可用于去重）
+/// - `X-RTodo-Timestamp`: 发送时间（毫秒）
+/// - `X-RTodo-Signature`: 配置了密钥时的签名，见 [`sign`]
+pub fn send(webhook: &Webhook, event: &str, delivery_id: i64, body: &str) -> Result<u16, SendError> {
+    let timestamp = chrono::Utc::now().timestamp_millis();
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build();
+
+    let mut request = agent.post(&webhook.url)
+        .set("Content-Type", "application/json")
+        .set("User-Agent", concat!("RTodo-Webhook/", env!("CARGO_PKG_VERSION")))
+        .set("X-RTodo-Event", event)
+        .set("X-RTodo-Delivery", &delivery_id.to_string())
+        .set("X-RTodo-Timestamp", &timestamp.to_string());
+    if let Some(secret) = &webhook.secret {
+        request = request.set("X-RTodo-Signature", &sign(secret, timestamp, body));
+    }
+
+    match request.send_string(body) {
+        Ok(response) => Ok(response.status()),
+        Err(ureq::Error::Status(code, _)) => Err(SendError {
+            status: Some(code),
+            message: format!("HTTP {}", code),
+        }),
+        Err(ureq::Error::Transport(e)) => Err(SendError {
+            status: None,
+            message: e.to_string(),
+        }),
+    }
+}
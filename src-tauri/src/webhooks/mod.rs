@@ -0,0 +1,149 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 出站 Webhook
+//!
+//! 入队、签名和重试策略在 rtodo-core 中；这里负责唤醒后台任务并发送请求，应用重启后继续投递
+
+pub mod delivery;
+
+#[cfg(test)]
+mod tests;
+
+pub use rtodo_core::webhooks::build_payload;
+use rtodo_core::webhooks::{record_attempt, SendError};
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+
+use crate::database::Database;
+use crate::database::repositories::WebhookRepository;
+use crate::models::{Todo, WebhookDelivery, webhook_event};
+
+/// 每批处理的投递数量
+const BATCH_SIZE: i64 = 20;
+
+/// 队列为空时的最长休眠时间
+const MAX_IDLE: Duration = Duration::from_secs(60);
+
+/// Webhook 后台任务状态（用于在有新事件时唤醒）
+#[derive(Default)]
+pub struct WebhookWorker {
+    notify: Arc<Notify>,
+}
+
+/// 将事件加入投递队列并唤醒后台任务；失败只记录日志，不影响触发它的操作
+pub fn dispatch<T: Serialize>(app: &AppHandle, conn: &Connection, event: &str, data: &T) {
+    wake_if_queued(app, event, rtodo_core::webhooks::enqueue(conn, event, data));
+}
+
+/// 任务更新后发送 `todo.updated`，状态变化时额外发送 `todo.status_changed`
+pub fn dispatch_todo_updated(app: &AppHandle, conn: &Connection, before: &Todo, after: &Todo) {
+    let queued = rtodo_core::webhooks::enqueue_todo_updated(conn, before, after);
+    wake_if_queued(app, webhook_event::TODO_UPDATED, queued);
+}
+
+/// 有新的投递记录时唤醒后台任务
+fn wake_if_queued(app: &AppHandle, event: &str, queued: rtodo_core::Result<usize>) {
+    match queued {
+        Ok(0) => {}
+        Ok(count) => {
+            tracing::debug!("Queued {} webhook deliveries for {}", count, event);
+            wake_worker(app);
+        }
+        Err(e) => tracing::error!("Failed to queue webhook event {}: {}", event, e),
+    }
+}
+
+/// 唤醒后台任务立即处理队列
+pub fn wake_worker(app: &AppHandle) {
+    if let Some(worker) = app.try_state::<WebhookWorker>() {
+        worker.notify.notify_one();
+    }
+}
+
+/// 注册后台任务状态并启动投递循环
+pub fn spawn_worker(app: AppHandle) {
+    let worker = WebhookWorker::default();
+    let notify = worker.notify.clone();
+    app.manage(worker);
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // 加密数据库解锁前只等待
+            let db = match app.try_state::<Database>() {
+                Some(db) if !db.is_locked() => db.inner().clone(),
+                _ => {
+                    tokio::time::sleep(MAX_IDLE).await;
+                    continue;
+                }
+            };
+
+            let now = Utc::now().timestamp_millis();
+            let loaded = db.read(move |conn| {
+                Ok::<_, anyhow::Error>((
+                    WebhookRepository::list_due(conn, now, BATCH_SIZE)?,
+                    WebhookRepository::next_attempt_at(conn)?,
+                ))
+            }).await;
+            let (due, next_attempt_at) = loaded.unwrap_or_else(|e| {
+                tracing::error!("Failed to load webhook deliveries: {}", e);
+                (Vec::new(), None)
+            });
+
+            if due.is_empty() {
+                let idle = next_attempt_at
+                    .map(|t| Duration::from_millis((t - now).max(0) as u64).min(MAX_IDLE))
+                    .unwrap_or(MAX_IDLE);
+                tokio::select! {
+                    _ = notify.notified() => {}
+                    _ = tokio::time::sleep(idle) => {}
+                }
+                continue;
+            }
+
+            for item in due {
+                process_delivery(&db, item).await;
+            }
+        }
+    });
+}
+
+/// 发送一条投递记录并更新其状态
+async fn process_delivery(db: &Database, item: WebhookDelivery) {
+    let webhook_id = item.webhook_id;
+    let webhook = db.read(move |conn| WebhookRepository::get(conn, webhook_id)).await;
+
+    let webhook = match webhook {
+        Ok(Some(w)) if w.enabled => w,
+        Ok(_) => {
+            // Webhook 已删除或被禁用，不再投递
+            let delivery_id = item.id;
+            let _ = db.write(move |conn| {
+                WebhookRepository::mark_attempt_failed(conn, delivery_id, "Webhook removed or disabled", None, None)
+            }).await;
+            return;
+        }
+        Err(e) => {
+            tracing::error!("Failed to load webhook {}: {}", item.webhook_id, e);
+            return;
+        }
+    };
+
+    // ureq 为阻塞请求，放到阻塞线程池中执行
+    let (event, delivery_id, payload) = (item.event.clone(), item.id, item.payload.clone());
+    let result = tokio::task::spawn_blocking(move || delivery::send(&webhook, &event, delivery_id, &payload))
+        .await
+        .unwrap_or_else(|e| Err(SendError { status: None, message: e.to_string() }));
+
+    let update = db.write(move |conn| record_attempt(conn, &item, result)).await;
+
+    if let Err(e) = update {
+        tracing::error!("Failed to update webhook delivery {}: {}", delivery_id, e);
+    }
+}
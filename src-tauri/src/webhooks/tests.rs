@@ -0,0 +1,92 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+
+use rtodo_core::database::migrations;
+use rtodo_core::webhooks::sign;
+use crate::models::DeliveryStatus;
+
+/// 收到的请求：小写的请求头和请求体
+struct Received {
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// 在本地端口上依次用 `statuses` 响应请求，返回地址和收到的请求
+fn serve(statuses: Vec<u16>) -> (String, mpsc::Receiver<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        for status in statuses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut headers = HashMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+            let length = headers.get("content-length").map_or(0, |l| l.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let response = format!("HTTP/1.1 {} Test\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            sender.send(Received { headers, body: String::from_utf8(body).unwrap() }).unwrap();
+        }
+    });
+
+    (url, receiver)
+}
+
+#[test]
+fn deliveries_are_signed_and_retried_until_accepted() {
+    let conn = Connection::open_in_memory().unwrap();
+    migrations::run_migrations(&conn, None).unwrap();
+    let (url, received) = serve(vec![503, 204]);
+    let webhook = WebhookRepository::create(&conn, "Local", &url, Some("s3cret"), &[], true).unwrap();
+    rtodo_core::webhooks::enqueue(&conn, webhook_event::TODO_DELETED, &serde_json::json!({ "id": 7 })).unwrap();
+
+    // 5xx：重新排队，按退避时间重试
+    let now = Utc::now().timestamp_millis();
+    let item = WebhookRepository::list_due(&conn, now, 10).unwrap().remove(0);
+    let result = delivery::send(&webhook, &item.event, item.id, &item.payload);
+    assert_eq!(result.as_ref().unwrap_err().status, Some(503));
+    assert_eq!(record_attempt(&conn, &item, result).unwrap(), DeliveryStatus::Pending);
+
+    let request = received.recv().unwrap();
+    assert_eq!(request.body, item.payload);
+    assert_eq!(request.headers["x-rtodo-event"], "todo.deleted");
+    assert_eq!(request.headers["x-rtodo-delivery"], item.id.to_string());
+    assert_eq!(request.headers["content-type"], "application/json");
+    let timestamp: i64 = request.headers["x-rtodo-timestamp"].parse().unwrap();
+    assert_eq!(request.headers["x-rtodo-signature"], sign("s3cret", timestamp, &item.payload));
+
+    let retried = WebhookRepository::list_deliveries(&conn, None, 10).unwrap().remove(0);
+    assert_eq!((retried.status, retried.attempts, retried.response_status), (DeliveryStatus::Pending, 1, Some(503)));
+    assert!(retried.next_attempt_at >= now + 30 * 1000);
+    assert!(WebhookRepository::list_due(&conn, now, 10).unwrap().is_empty());
+
+    // 2xx：标记为已投递，投递 ID 不变
+    let result = delivery::send(&webhook, &retried.event, retried.id, &retried.payload);
+    assert_eq!(result.as_ref().unwrap(), &204);
+    assert_eq!(record_attempt(&conn, &retried, result).unwrap(), DeliveryStatus::Delivered);
+    assert_eq!(received.recv().unwrap().headers["x-rtodo-delivery"], item.id.to_string());
+
+    let delivered = WebhookRepository::list_deliveries(&conn, None, 10).unwrap().remove(0);
+    assert_eq!((delivered.status, delivered.attempts, delivered.response_status), (DeliveryStatus::Delivered, 2, Some(204)));
+    assert!(delivered.delivered_at.is_some() && delivered.last_error.is_none());
+}
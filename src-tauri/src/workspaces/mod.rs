@@ -0,0 +1,181 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+//! 工作区
+//!
+//! 每个工作区有独立的数据目录（数据库、附件、默认备份目录）以及各自的备份、提醒、同步和远程存储设置。
+//! 旧版本的单一数据目录成为默认工作区；新建的工作区默认放在默认数据目录下的 `workspaces/<id>`。
+
+#[cfg(test)]
+mod tests;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::backup::BackupConfig;
+use crate::config::AppConfig;
+use crate::database::paths;
+use crate::database::workspaces::{self as data_dirs, WorkspaceEntry, WorkspacesConfig};
+use crate::models::WorkspaceInfo;
+use crate::remote::RemoteConfig;
+use crate::rules::ReminderConfig;
+use crate::sync::SyncConfig;
+
+pub use crate::database::workspaces::DEFAULT_WORKSPACE_ID;
+
+/// 工作区列表或当前工作区变化后发送给前端的事件，载荷为全部工作区
+pub const WORKSPACES_CHANGED_EVENT: &str = "workspaces-changed";
+
+/// 工作区名称最大长度（字符数）
+const MAX_NAME_CHARS: usize = 50;
+
+/// 旧版本写入配置的默认工作区名称
+pub(crate) const LEGACY_DEFAULT_NAME: &str = "默认";
+
+/// 工作区配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    /// 名称（默认工作区未重命名时为空）
+    #[serde(default)]
+    pub name: String,
+    /// 自定义数据路径（为空时使用工作区的默认目录）
+    #[serde(default)]
+    pub data_path: Option<String>,
+    /// 自动备份设置
+    #[serde(default)]
+    pub backup: BackupConfig,
+    /// 截止时间提醒设置
+    #[serde(default)]
+    pub reminders: ReminderConfig,
+    /// 设备间同步设置
+    #[serde(default)]
+    pub sync: SyncConfig,
+    /// 远程存储（密码保存在系统钥匙串中）
+    #[serde(default)]
+    pub remote: Option<RemoteConfig>,
+}
+
+impl Workspace {
+    /// 新建工作区（生成新 ID，使用默认设置）
+    pub fn new(name: String, data_path: Option<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            name,
+            data_path,
+            backup: BackupConfig::default(),
+            reminders: ReminderConfig::default(),
+            sync: SyncConfig::default(),
+            remote: None,
+        }
+    }
+
+    /// 默认工作区，沿用旧版本的数据路径和备份设置；名称为空，由界面按当前语言显示
+    pub fn default_workspace(data_path: Option<String>, backup: BackupConfig) -> Self {
+        Self {
+            id: DEFAULT_WORKSPACE_ID.to_string(),
+            name: String::new(),
+            data_path,
+            backup,
+            reminders: ReminderConfig::default(),
+            sync: SyncConfig::default(),
+            remote: None,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_WORKSPACE_ID
+    }
+
+    /// 自定义数据路径（忽略空字符串）
+    pub fn custom_data_dir(&self) -> Option<PathBuf> {
+        self.data_path.as_deref()
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// 未设置数据路径时使用的目录：默认工作区为平台默认目录，其他工作区为其下的 `workspaces/<id>`
+    pub fn default_data_dir(&self) -> Result<PathBuf> {
+        Ok(data_dirs::default_data_dir(&self.id)?)
+    }
+
+    /// 工作区的数据目录
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        Ok(data_dirs::data_dir(&self.id, self.data_path.as_deref())?)
+    }
+
+    /// 确定数据目录所需的部分
+    fn entry(&self) -> WorkspaceEntry {
+        WorkspaceEntry { id: self.id.clone(), name: self.name.clone(), data_path: self.data_path.clone() }
+    }
+
+    /// 转换为前端使用的工作区信息
+    pub fn to_info(&self, active_id: &str) -> WorkspaceInfo {
+        WorkspaceInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            data_path: self.data_dir()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            is_default: self.is_default(),
+            active: self.id == active_id,
+        }
+    }
+}
+
+/// 检查工作区名称：去掉首尾空白后不能为空、过长或与其他工作区重名（不区分大小写）
+///
+/// `except` 为重命名时的工作区自身 ID
+pub fn validate_name(workspaces: &[Workspace], name: &str, except: Option<&str>) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        anyhow::bail!("Workspace name cannot be empty");
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        anyhow::bail!("Workspace name cannot be longer than {} characters", MAX_NAME_CHARS);
+    }
+    let duplicate = workspaces.iter()
+        .filter(|w| Some(w.id.as_str()) != except)
+        .any(|w| w.name.to_lowercase() == name.to_lowercase());
+    if duplicate {
+        anyhow::bail!("A workspace named {} already exists", name);
+    }
+    Ok(name.to_string())
+}
+
+/// 所有工作区的信息（按配置中的顺序）
+pub fn list(config: &AppConfig) -> Vec<WorkspaceInfo> {
+    config.workspaces.iter()
+        .map(|w| w.to_info(&config.active_workspace))
+        .collect()
+}
+
+/// 启动时确定当前工作区的数据目录
+///
+/// 规则与命令行相同，见 [`data_dirs::resolve`]；目录不可用时返回原因，回退到默认工作区时同时修改当前工作区
+pub fn init(config: &mut AppConfig) -> Option<String> {
+    let entries = config.workspaces.iter().map(Workspace::entry).collect();
+    let selection = WorkspacesConfig::new(entries, &config.active_workspace);
+    match data_dirs::resolve(&selection, None) {
+        Ok(resolved) => {
+            tracing::info!("Using data directory: {}", resolved.data_dir.display());
+            paths::set_data_dir(Some(resolved.data_dir));
+            config.active_workspace = resolved.workspace_id;
+            resolved.fallback_reason
+        }
+        Err(e) => {
+            tracing::error!("Failed to resolve data directory: {:#}", e);
+            paths::set_data_dir(None);
+            Some(format!("{:#}", e))
+        }
+    }
+}
+
+/// 创建工作区的数据目录（自定义路径需可写）
+pub fn prepare_data_dir(workspace: &Workspace) -> Result<PathBuf> {
+    let dir = workspace.data_dir()?;
+    paths::validate_data_dir(&dir)
+        .with_context(|| format!("Workspace directory is not usable: {}", dir.display()))?;
+    Ok(dir)
+}
@@ -0,0 +1,81 @@
+// Copyright 2025 RTodo Team. All rights reserved.
+// SPDX-License-Identifier: MIT
+
+use super::*;
+
+#[test]
+fn legacy_config_becomes_default_workspace() {
+    let mut config: AppConfig = serde_json::from_str(r#"{
+        "data_path": "/data/rtodo",
+        "backup": { "enabled": false, "interval_hours": 12 }
+    }"#).unwrap();
+    config.migrate_workspaces();
+
+    assert_eq!(config.workspaces.len(), 1);
+    let workspace = config.active_workspace();
+    assert!(workspace.is_default());
+    assert!(workspace.name.is_empty());
+    assert_eq!(workspace.data_path.as_deref(), Some("/data/rtodo"));
+    assert!(!workspace.backup.enabled);
+    assert_eq!(workspace.backup.interval_hours, 12);
+    assert!(workspace.reminders.enabled);
+
+    // 旧字段不再写回配置文件
+    let saved = serde_json::to_value(&config).unwrap();
+    assert!(saved.get("data_path").is_none());
+    assert!(saved.get("backup").is_none());
+}
+
+#[test]
+fn unknown_active_workspace_falls_back_to_default() {
+    let mut config: AppConfig = serde_json::from_str(r#"{ "active_workspace": "missing" }"#).unwrap();
+    config.migrate_workspaces();
+
+    assert_eq!(config.active_workspace, DEFAULT_WORKSPACE_ID);
+    assert!(config.active_workspace().is_default());
+}
+
+#[test]
+fn legacy_default_name_is_cleared() {
+    let mut config: AppConfig = serde_json::from_str(r#"{
+        "workspaces": [
+            { "id": "default", "name": "默认" },
+            { "id": "work", "name": "默认" }
+        ]
+    }"#).unwrap();
+    config.migrate_workspaces();
+
+    // 只清除默认工作区的旧名称，用户自己起的名称保留
+    assert_eq!(config.workspaces[0].name, "");
+    assert_eq!(config.workspaces[1].name, "默认");
+
+    let mut config: AppConfig = serde_json::from_str(r#"{ "workspaces": [{ "id": "default", "name": "Home" }] }"#).unwrap();
+    config.migrate_workspaces();
+    assert_eq!(config.workspaces[0].name, "Home");
+}
+
+#[test]
+fn names_are_trimmed_and_unique() {
+    let work = Workspace::new("Work".to_string(), None);
+    let workspaces = vec![Workspace::default_workspace(None, BackupConfig::default()), work.clone()];
+
+    assert_eq!(validate_name(&workspaces, "  Home ", None).unwrap(), "Home");
+    assert!(validate_name(&workspaces, "   ", None).is_err());
+    assert!(validate_name(&workspaces, "work", None).is_err());
+    assert!(validate_name(&workspaces, &"x".repeat(MAX_NAME_CHARS + 1), None).is_err());
+    // 重命名时可以保留自己的名称
+    assert_eq!(validate_name(&workspaces, "WORK", Some(&work.id)).unwrap(), "WORK");
+}
+
+#[test]
+fn new_workspaces_live_under_default_data_dir() {
+    let default = Workspace::default_workspace(None, BackupConfig::default());
+    let work = Workspace::new("Work".to_string(), None);
+    let custom = Workspace::new("Custom".to_string(), Some("/data/custom".to_string()));
+    let base = paths::default_data_dir().unwrap();
+
+    assert_eq!(default.data_dir().unwrap(), base);
+    assert_eq!(work.data_dir().unwrap(), base.join("workspaces").join(&work.id));
+    assert_eq!(custom.data_dir().unwrap(), PathBuf::from("/data/custom"));
+    assert_ne!(work.id, custom.id);
+}